@@ -35,7 +35,8 @@ pub struct CliArgs {
     #[arg(long = "log-dir")]
     pub log_dir: Option<PathBuf>,
 
-    /// Supervisor HTTP port
+    /// Supervisor HTTP port. `0` binds a free ephemeral port; the chosen
+    /// port is published in `.dev-logs/supervisor.json`.
     #[arg(long = "port", default_value_t = DEFAULT_SUPERVISOR_PORT)]
     pub port: u16,
 
@@ -137,6 +138,24 @@ pub fn min_free_disk_gb() -> u64 {
         .unwrap_or(DEFAULT_MIN_FREE_DISK_GB)
 }
 
+/// Env var naming the dev-server orchestrator the velocity improvement loop
+/// asks to restart the web frontend (and optionally the backend) after a fix.
+pub const DEV_ORCHESTRATOR_URL_ENV: &str = "QONTINUI_SUPERVISOR_DEV_ORCHESTRATOR_URL";
+
+/// Base URL of the dev-server orchestrator, from [`DEV_ORCHESTRATOR_URL_ENV`],
+/// without a trailing slash. `None` when unset or blank: there is no
+/// orchestrator, and the improvement loop relies on the dev servers' own hot
+/// reload.
+pub fn dev_orchestrator_url() -> Option<String> {
+    normalize_base_url(std::env::var(DEV_ORCHESTRATOR_URL_ENV).ok())
+}
+
+/// Trim whitespace and trailing slashes; blank means unset.
+pub(crate) fn normalize_base_url(raw: Option<String>) -> Option<String> {
+    raw.map(|s| s.trim().trim_end_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+}
+
 /// Configuration for a single managed runner instance.
 ///
 /// The canonical discriminator is the `kind` field (a [`RunnerKind`]).
@@ -182,6 +201,10 @@ pub struct RunnerConfig {
     /// settings file.
     #[serde(default)]
     pub extra_env: std::collections::HashMap<String, String>,
+    /// How the health cache decides this runner is responding; `None` is
+    /// `GET /health` on its port.
+    #[serde(default)]
+    pub health_probe: Option<crate::process::probe::HealthProbe>,
 }
 
 impl RunnerConfig {
@@ -200,6 +223,11 @@ impl RunnerConfig {
         }
     }
 
+    /// The probe that decides whether this runner is responding.
+    pub fn health_probe(&self) -> crate::process::probe::HealthProbe {
+        self.health_probe.clone().unwrap_or_default()
+    }
+
     /// Create the default primary runner config.
     pub fn default_primary() -> Self {
         Self {
@@ -215,6 +243,7 @@ impl RunnerConfig {
             external_restate_admin_url: None,
             external_restate_ingress_url: None,
             extra_env: std::collections::HashMap::new(),
+            health_probe: None,
         }
     }
 }
@@ -889,4 +918,14 @@ mod tests {
         assert_eq!(config.expo_dir, Some(PathBuf::from("/tmp/qontinui-mobile")));
         assert_eq!(config.expo_port, EXPO_PORT);
     }
+
+    #[test]
+    fn test_normalize_base_url() {
+        assert_eq!(
+            normalize_base_url(Some(" http://localhost:9875/ ".to_string())),
+            Some("http://localhost:9875".to_string())
+        );
+        assert_eq!(normalize_base_url(Some("  ".to_string())), None);
+        assert_eq!(normalize_base_url(None), None);
+    }
 }
@@ -16,6 +16,14 @@ impl RestartSource {
     pub fn is_manual(&self) -> bool {
         matches!(self, Self::Manual)
     }
+
+    /// Short stable name for persistence.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Watchdog => "watchdog",
+        }
+    }
 }
 
 impl std::fmt::Display for RestartSource {
@@ -53,6 +61,47 @@ pub enum DiagnosticEventKind {
         success: bool,
         error: Option<String>,
     },
+
+    // Eval regressions against the pinned baseline
+    EvalRegression {
+        run_id: String,
+        baseline_run_id: String,
+        avg_overall_delta: Option<f64>,
+        regressions: usize,
+    },
+
+    // Endpoint p95 well above its rolling baseline
+    LatencyAnomaly {
+        service: String,
+        http_method: String,
+        http_route: String,
+        baseline_p95_ms: f64,
+        current_p95_ms: f64,
+        ratio: f64,
+    },
+
+    // Streaming clients cut off for falling too far behind
+    SlowClientDisconnected {
+        endpoint: String,
+        client_id: u64,
+        dropped: u64,
+    },
+
+    // Runner crashes
+    RunnerCrashed {
+        runner_id: String,
+        exit_code: Option<i32>,
+        /// Forensics bundle directory, when it could be written.
+        bundle_dir: Option<String>,
+    },
+
+    // A supervised process went over the memory ceiling
+    MemoryCeilingExceeded {
+        process: String,
+        pid: u32,
+        memory_bytes: u64,
+        ceiling_bytes: u64,
+    },
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -119,6 +168,16 @@ impl DiagnosticEvent {
             DiagnosticEventKind::BuildStarted | DiagnosticEventKind::BuildCompleted { .. } => {
                 "build"
             }
+
+            DiagnosticEventKind::EvalRegression { .. } => "eval",
+
+            DiagnosticEventKind::LatencyAnomaly { .. } => "velocity",
+
+            DiagnosticEventKind::SlowClientDisconnected { .. } => "stream",
+
+            DiagnosticEventKind::MemoryCeilingExceeded { .. } => "resources",
+
+            DiagnosticEventKind::RunnerCrashed { .. } => "crash",
         }
     }
 }
@@ -0,0 +1,206 @@
+//! Pinned baseline run and automatic regression alerts.
+//!
+//! One eval run can be pinned as the baseline (`PUT /eval/baseline`). Every
+//! completed non-retry run after that is compared against it with
+//! [`super::queries::compare_runs`]; when the result crosses the pin's
+//! thresholds the supervisor logs a warning, records an `eval_regression`
+//! diagnostics event, and — if configured — POSTs the alert to a webhook.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::db::EvalDb;
+use super::{queries, CompareReport};
+use crate::diagnostics::DiagnosticEventKind;
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::SharedState;
+
+/// Default tolerated drop in average overall score (judge 1-5 scale).
+pub const DEFAULT_MAX_AVG_DROP: f64 = 0.25;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselinePin {
+    pub run_id: String,
+    pub pinned_at: String,
+    /// Alert when the average overall score falls by more than this.
+    pub max_avg_drop: f64,
+    /// Alert when more prompts than this regress (drop by >= 1 point).
+    pub max_regressions: usize,
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegressionAlert {
+    pub run_id: String,
+    pub baseline_run_id: String,
+    pub avg_overall_delta: Option<f64>,
+    pub regressions: usize,
+    pub regressed_prompts: Vec<String>,
+}
+
+/// Decide whether `report` breaches the pin's thresholds.
+pub fn check(pin: &BaselinePin, report: &CompareReport) -> Option<RegressionAlert> {
+    let avg_drop = report
+        .aggregate
+        .avg_overall_delta
+        .is_some_and(|d| -d > pin.max_avg_drop);
+    let too_many = report.aggregate.regressions > pin.max_regressions;
+    if !avg_drop && !too_many {
+        return None;
+    }
+    Some(RegressionAlert {
+        run_id: report.current_run_id.clone(),
+        baseline_run_id: report.baseline_run_id.clone(),
+        avg_overall_delta: report.aggregate.avg_overall_delta,
+        regressions: report.aggregate.regressions,
+        regressed_prompts: report
+            .per_prompt
+            .iter()
+            .filter(|p| p.regression)
+            .map(|p| p.test_prompt_id.clone())
+            .collect(),
+    })
+}
+
+/// Compare a just-completed run against the pinned baseline and raise an
+/// alert if it regressed. No-op when nothing is pinned or the run is the
+/// baseline itself.
+pub async fn alert_on_regression(db: &EvalDb, state: &SharedState, run_id: &str) {
+    let pin = match db.get_baseline() {
+        Ok(Some(pin)) if pin.run_id != run_id => pin,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::warn!("Failed to load eval baseline: {}", e);
+            return;
+        }
+    };
+    let report = match queries::compare_runs(db, run_id, &pin.run_id) {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!("Failed to compare run {} to baseline: {}", run_id, e);
+            return;
+        }
+    };
+    let Some(alert) = check(&pin, &report) else {
+        return;
+    };
+
+    let message = format!(
+        "Eval run {} regressed vs baseline {}: avg overall delta {}, {} prompt(s) regressed",
+        alert.run_id,
+        alert.baseline_run_id,
+        alert
+            .avg_overall_delta
+            .map(|d| format!("{:+.2}", d))
+            .unwrap_or_else(|| "n/a".to_string()),
+        alert.regressions
+    );
+    tracing::warn!("{}", message);
+    state
+        .logs
+        .emit(LogSource::Supervisor, LogLevel::Warn, message)
+        .await;
+    state
+        .diagnostics
+        .write()
+        .await
+        .emit(DiagnosticEventKind::EvalRegression {
+            run_id: alert.run_id.clone(),
+            baseline_run_id: alert.baseline_run_id.clone(),
+            avg_overall_delta: alert.avg_overall_delta,
+            regressions: alert.regressions,
+        });
+
+    if let Some(url) = pin.webhook_url {
+        tokio::spawn(async move {
+            let result = reqwest::Client::new()
+                .post(&url)
+                .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                .json(&alert)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("Eval regression webhook {} failed: {}", url, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::{AggregateDelta, DimensionDeltas, PromptComparison};
+
+    fn pin(max_avg_drop: f64, max_regressions: usize) -> BaselinePin {
+        BaselinePin {
+            run_id: "base".to_string(),
+            pinned_at: String::new(),
+            max_avg_drop,
+            max_regressions,
+            webhook_url: None,
+        }
+    }
+
+    fn report(avg_delta: Option<f64>, deltas: &[f64]) -> CompareReport {
+        let per_prompt: Vec<PromptComparison> = deltas
+            .iter()
+            .enumerate()
+            .map(|(i, d)| PromptComparison {
+                test_prompt_id: format!("p{}", i),
+                baseline_overall: Some(4.0),
+                current_overall: Some(4.0 + d),
+                delta: Some(*d),
+                regression: *d <= -1.0,
+                improvement: *d >= 1.0,
+                dimension_deltas: DimensionDeltas {
+                    structural_correctness: None,
+                    command_accuracy: None,
+                    phase_flow_logic: None,
+                    step_completeness: None,
+                    prompt_quality: None,
+                    determinism: None,
+                },
+                history_samples: 0,
+                significant: None,
+            })
+            .collect();
+        let regressions = per_prompt.iter().filter(|p| p.regression).count();
+        CompareReport {
+            current_run_id: "new".to_string(),
+            baseline_run_id: "base".to_string(),
+            aggregate: AggregateDelta {
+                avg_overall_delta: avg_delta,
+                regressions,
+                improvements: 0,
+                unchanged: per_prompt.len() - regressions,
+                significant_regressions: 0,
+                significant_improvements: 0,
+                avg_delta_ci_low: None,
+                avg_delta_ci_high: None,
+                significant: None,
+            },
+            per_prompt,
+        }
+    }
+
+    #[test]
+    fn within_thresholds_is_quiet() {
+        assert!(check(&pin(0.25, 0), &report(Some(-0.1), &[0.0, -0.5])).is_none());
+    }
+
+    #[test]
+    fn average_drop_triggers_alert() {
+        let alert = check(&pin(0.25, 5), &report(Some(-0.4), &[-0.4])).unwrap();
+        assert_eq!(alert.regressions, 0);
+        assert_eq!(alert.avg_overall_delta, Some(-0.4));
+    }
+
+    #[test]
+    fn regression_count_triggers_alert_and_lists_prompts() {
+        let alert = check(&pin(1.0, 0), &report(Some(0.0), &[-1.5, 1.5])).unwrap();
+        assert_eq!(alert.regressed_prompts, vec!["p0".to_string()]);
+    }
+}
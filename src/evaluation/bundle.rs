@@ -0,0 +1,186 @@
+//! Portable prompt bundle for `GET /eval/prompts/export` and
+//! `POST /eval/prompts/import`, so prompt sets can be versioned in git and
+//! moved between machines instead of living only in `eval-benchmark.db`.
+//!
+//! Format (JSON, `version: 1`):
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "exported_at": "2026-01-01T00:00:00Z",
+//!   "prompts": [ { "id": "...", "prompt": "...", "category": "...", ... } ],
+//!   "suites":  [ { "name": "...", "prompt_ids": ["..."] } ]
+//! }
+//! ```
+//!
+//! `prompts` entries use the same shape as `GET /eval/test-suite`; fields
+//! with defaults (`status`, `needs_review`, timestamps) may be omitted.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::{PromptSuite, TestPrompt};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: String,
+    pub prompts: Vec<TestPrompt>,
+    #[serde(default)]
+    pub suites: Vec<PromptSuite>,
+}
+
+/// What to do when an imported prompt ID (or suite name) already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportAction {
+    Insert(String),
+    Overwrite(String),
+    Rename { from: String, to: String },
+    Skip(String),
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub inserted: Vec<String>,
+    pub overwritten: Vec<String>,
+    pub renamed: Vec<RenamedEntry>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenamedEntry {
+    pub from: String,
+    pub to: String,
+}
+
+/// First `<id>-N` (N >= 2) not present in `taken`.
+pub fn next_free_id(id: &str, taken: &HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", id, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("unbounded range always yields a free id")
+}
+
+/// Decide, per incoming key, how it lands given the keys already stored.
+/// Renamed keys are reserved as they're assigned so two incoming
+/// duplicates never collide with each other.
+pub fn plan_import(
+    existing: &HashSet<String>,
+    incoming: &[String],
+    policy: ConflictPolicy,
+) -> Vec<ImportAction> {
+    let mut taken = existing.clone();
+    incoming
+        .iter()
+        .map(|id| {
+            if !taken.contains(id) {
+                taken.insert(id.clone());
+                return ImportAction::Insert(id.clone());
+            }
+            match policy {
+                ConflictPolicy::Skip => ImportAction::Skip(id.clone()),
+                ConflictPolicy::Overwrite => ImportAction::Overwrite(id.clone()),
+                ConflictPolicy::Rename => {
+                    let to = next_free_id(id, &taken);
+                    taken.insert(to.clone());
+                    ImportAction::Rename {
+                        from: id.clone(),
+                        to,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Rewrite suite membership after prompt renames so imported suites keep
+/// pointing at the prompts they shipped with.
+pub fn remap_suite_ids(suite: &mut PromptSuite, renames: &HashMap<String, String>) {
+    for id in &mut suite.prompt_ids {
+        if let Some(new_id) = renames.get(id) {
+            *id = new_id.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn next_free_id_skips_taken_suffixes() {
+        assert_eq!(next_free_id("p", &set(&["p"])), "p-2");
+        assert_eq!(next_free_id("p", &set(&["p", "p-2", "p-3"])), "p-4");
+    }
+
+    #[test]
+    fn plan_inserts_new_and_applies_policy_to_conflicts() {
+        let existing = set(&["a"]);
+        let incoming = ids(&["a", "b"]);
+
+        assert_eq!(
+            plan_import(&existing, &incoming, ConflictPolicy::Skip),
+            vec![
+                ImportAction::Skip("a".into()),
+                ImportAction::Insert("b".into())
+            ]
+        );
+        assert_eq!(
+            plan_import(&existing, &incoming, ConflictPolicy::Overwrite)[0],
+            ImportAction::Overwrite("a".into())
+        );
+    }
+
+    #[test]
+    fn plan_rename_never_collides_within_one_import() {
+        let plan = plan_import(&set(&["a"]), &ids(&["a", "a"]), ConflictPolicy::Rename);
+        assert_eq!(
+            plan,
+            vec![
+                ImportAction::Rename {
+                    from: "a".into(),
+                    to: "a-2".into()
+                },
+                ImportAction::Rename {
+                    from: "a".into(),
+                    to: "a-3".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn suite_ids_follow_renames() {
+        let mut suite = PromptSuite {
+            name: "smoke".into(),
+            description: None,
+            prompt_ids: ids(&["a", "b"]),
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let renames = HashMap::from([("a".to_string(), "a-2".to_string())]);
+        remap_suite_ids(&mut suite, &renames);
+        assert_eq!(suite.prompt_ids, ids(&["a-2", "b"]));
+    }
+}
@@ -1,9 +1,29 @@
 use chrono::Utc;
 use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use super::{EvalResult, EvalRunSummary, TestPrompt};
+use super::baseline::BaselinePin;
+use super::judge::JudgeTemplate;
+use super::retention::RunRow;
+use super::schedule::EvalSchedule;
+use super::{
+    CategoryScore, DimensionScores, EvalResult, EvalRunSummary, PromptStatus, PromptSuite,
+    ResultFilter, ScoreOverride, TestPrompt,
+};
+use crate::run_environment::EnvironmentSnapshot;
+
+/// `FROM ... WHERE` shared by [`EvalDb::query_results`] and
+/// [`EvalDb::count_results`]. Binds ?1 run_id, ?2/?3 score bounds,
+/// ?4 category, ?5 has_error.
+const RESULT_FILTER_SQL: &str = "FROM eval_results r
+     LEFT JOIN test_prompts p ON p.id = r.test_prompt_id
+     WHERE r.run_id = ?1
+       AND (?2 IS NULL OR r.overall_score >= ?2)
+       AND (?3 IS NULL OR r.overall_score <= ?3)
+       AND (?4 IS NULL OR p.category = ?4)
+       AND (?5 IS NULL OR (r.generation_error IS NOT NULL OR r.scoring_error IS NOT NULL) = ?5)";
 
 pub struct EvalDb {
     conn: Mutex<Connection>,
@@ -12,7 +32,10 @@ pub struct EvalDb {
 }
 
 impl EvalDb {
-    pub fn new(dev_logs_dir: &Path) -> anyhow::Result<Self> {
+    /// Open (creating if needed) the eval DB. `seed_defaults` seeds the
+    /// built-in prompts into an empty DB; it is off when prompts come from a
+    /// directory (see [`super::prompt_source`]).
+    pub fn new(dev_logs_dir: &Path, seed_defaults: bool) -> anyhow::Result<Self> {
         let db_path = dev_logs_dir.join("eval-benchmark.db");
         let conn = Connection::open(&db_path)?;
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
@@ -22,7 +45,9 @@ impl EvalDb {
         };
         db.init_schema()?;
         db.migrate()?;
-        db.seed_defaults()?;
+        if seed_defaults {
+            db.seed_defaults()?;
+        }
         db.cleanup_stale_runs()?;
         Ok(db)
     }
@@ -44,6 +69,8 @@ impl EvalDb {
                 tags TEXT,
                 ground_truth_json TEXT,
                 enabled INTEGER NOT NULL DEFAULT 1,
+                status TEXT NOT NULL DEFAULT 'active',
+                needs_review INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             );
@@ -79,7 +106,11 @@ impl EvalDb {
                 gen_count INTEGER,
                 error TEXT,
                 started_at TEXT NOT NULL,
-                completed_at TEXT
+                completed_at TEXT,
+                judge_provider TEXT,
+                judge_model TEXT,
+                environment_json TEXT,
+                parent_run_id TEXT
             );
 
             CREATE TABLE IF NOT EXISTS eval_results (
@@ -99,6 +130,8 @@ impl EvalDb {
                 overall_score REAL,
 
                 score_rationales TEXT,
+                structural_metrics TEXT,
+                structural_f1 REAL,
 
                 generation_error TEXT,
                 scoring_error TEXT,
@@ -110,6 +143,69 @@ impl EvalDb {
                 completed_at TEXT
             );
 
+            CREATE TABLE IF NOT EXISTS eval_run_category_scores (
+                run_id TEXT NOT NULL REFERENCES eval_runs(id) ON DELETE CASCADE,
+                category TEXT NOT NULL,
+                count INTEGER NOT NULL,
+                avg_overall REAL,
+                avg_structural REAL,
+                avg_command_accuracy REAL,
+                avg_phase_flow REAL,
+                avg_step_completeness REAL,
+                avg_prompt_quality REAL,
+                avg_determinism REAL,
+                PRIMARY KEY (run_id, category)
+            );
+
+            CREATE TABLE IF NOT EXISTS prompt_suites (
+                name TEXT PRIMARY KEY,
+                description TEXT,
+                prompt_ids TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS prompt_sources (
+                prompt_id TEXT PRIMARY KEY,
+                source_path TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                synced_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS eval_baseline (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                run_id TEXT NOT NULL,
+                pinned_at TEXT NOT NULL,
+                max_avg_drop REAL NOT NULL,
+                max_regressions INTEGER NOT NULL,
+                webhook_url TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS eval_schedules (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                cron TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                suite TEXT,
+                prompt_ids TEXT,
+                concurrency INTEGER,
+                judge_provider TEXT,
+                judge_model TEXT,
+                last_fired_at TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS judge_prompts (
+                version INTEGER PRIMARY KEY AUTOINCREMENT,
+                system_prompt TEXT NOT NULL,
+                ground_truth_template TEXT NOT NULL,
+                generic_template TEXT NOT NULL,
+                notes TEXT,
+                active INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL
+            );
+
             CREATE INDEX IF NOT EXISTS idx_er_run_id ON eval_results(run_id);
             CREATE INDEX IF NOT EXISTS idx_er_prompt_id ON eval_results(test_prompt_id);
             CREATE INDEX IF NOT EXISTS idx_er_overall ON eval_results(overall_score);
@@ -162,6 +258,175 @@ impl EvalDb {
             tracing::info!("Migrated eval DB: added gt/gen aggregate columns");
         }
 
+        // Migration v4: FTS5 index over judge rationales and error text.
+        // External-content table kept in sync by triggers; existing rows are
+        // backfilled with a one-off rebuild.
+        if conn
+            .prepare("SELECT rowid FROM eval_results_fts LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "CREATE VIRTUAL TABLE eval_results_fts USING fts5(
+                    score_rationales, generation_error, scoring_error,
+                    content='eval_results', content_rowid='id'
+                 );
+                 CREATE TRIGGER IF NOT EXISTS eval_results_fts_ai AFTER INSERT ON eval_results BEGIN
+                    INSERT INTO eval_results_fts(rowid, score_rationales, generation_error, scoring_error)
+                    VALUES (new.id, new.score_rationales, new.generation_error, new.scoring_error);
+                 END;
+                 CREATE TRIGGER IF NOT EXISTS eval_results_fts_ad AFTER DELETE ON eval_results BEGIN
+                    INSERT INTO eval_results_fts(eval_results_fts, rowid, score_rationales, generation_error, scoring_error)
+                    VALUES ('delete', old.id, old.score_rationales, old.generation_error, old.scoring_error);
+                 END;
+                 CREATE TRIGGER IF NOT EXISTS eval_results_fts_au AFTER UPDATE ON eval_results BEGIN
+                    INSERT INTO eval_results_fts(eval_results_fts, rowid, score_rationales, generation_error, scoring_error)
+                    VALUES ('delete', old.id, old.score_rationales, old.generation_error, old.scoring_error);
+                    INSERT INTO eval_results_fts(rowid, score_rationales, generation_error, scoring_error)
+                    VALUES (new.id, new.score_rationales, new.generation_error, new.scoring_error);
+                 END;
+                 INSERT INTO eval_results_fts(eval_results_fts) VALUES ('rebuild');",
+            )?;
+            tracing::info!("Migrated eval DB: added eval_results_fts search index");
+        }
+
+        // Migration v5: Prompt lifecycle status and review flag
+        if conn
+            .prepare("SELECT status FROM test_prompts LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE test_prompts ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+                 ALTER TABLE test_prompts ADD COLUMN needs_review INTEGER NOT NULL DEFAULT 0;",
+            )?;
+            tracing::info!("Migrated eval DB: added status/needs_review columns");
+        }
+
+        // Migration v6: Per-run judge provider/model
+        if conn
+            .prepare("SELECT judge_model FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE eval_runs ADD COLUMN judge_provider TEXT;
+                 ALTER TABLE eval_runs ADD COLUMN judge_model TEXT;",
+            )?;
+            tracing::info!("Migrated eval DB: added judge_provider/judge_model columns");
+        }
+
+        // Migration v7: Environment snapshot per run
+        if conn
+            .prepare("SELECT environment_json FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE eval_runs ADD COLUMN environment_json TEXT;")?;
+            tracing::info!("Migrated eval DB: added environment_json column");
+        }
+
+        // Migration v8: Deterministic structural metrics per result
+        if conn
+            .prepare("SELECT structural_metrics FROM eval_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE eval_results ADD COLUMN structural_metrics TEXT;
+                 ALTER TABLE eval_results ADD COLUMN structural_f1 REAL;",
+            )?;
+            tracing::info!("Migrated eval DB: added structural metrics columns");
+        }
+
+        // Migration v9: Link retry runs to the run they retried
+        if conn
+            .prepare("SELECT parent_run_id FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE eval_runs ADD COLUMN parent_run_id TEXT;")?;
+            tracing::info!("Migrated eval DB: added parent_run_id column");
+        }
+
+        // Migration v10: Judge prompt version used to score each run
+        if conn
+            .prepare("SELECT judge_template_version FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE eval_runs ADD COLUMN judge_template_version INTEGER;")?;
+            tracing::info!("Migrated eval DB: added judge_template_version column");
+        }
+
+        // Migration v11: Model matrix grouping
+        if conn
+            .prepare("SELECT matrix_id FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE eval_runs ADD COLUMN matrix_id TEXT;
+                 CREATE INDEX IF NOT EXISTS idx_eval_runs_matrix ON eval_runs(matrix_id);",
+            )?;
+            tracing::info!("Migrated eval DB: added matrix_id column");
+        }
+
+        // Migration v12: Prompts a run set out to evaluate, for resuming it
+        if conn
+            .prepare("SELECT planned_prompt_ids FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE eval_runs ADD COLUMN planned_prompt_ids TEXT;")?;
+            tracing::info!("Migrated eval DB: added planned_prompt_ids column");
+        }
+
+        // Migration v13: Manual score overrides
+        if conn
+            .prepare("SELECT score_override FROM eval_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE eval_results ADD COLUMN score_override TEXT;
+                 ALTER TABLE eval_runs ADD COLUMN overridden_count INTEGER;",
+            )?;
+            tracing::info!("Migrated eval DB: added score override columns");
+        }
+
+        // Migration v14: Generations per prompt
+        if conn
+            .prepare("SELECT samples_per_prompt FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE eval_runs ADD COLUMN samples_per_prompt INTEGER;")?;
+            tracing::info!("Migrated eval DB: added samples_per_prompt column");
+        }
+
+        // Migration v15: Generation model override per run
+        if conn
+            .prepare("SELECT generation_provider FROM eval_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE eval_runs ADD COLUMN generation_provider TEXT;
+                 ALTER TABLE eval_runs ADD COLUMN generation_model TEXT;",
+            )?;
+            tracing::info!("Migrated eval DB: added generation model columns");
+        }
+
+        // The built-in rubric is always version 1.
+        let templates: i64 =
+            conn.query_row("SELECT COUNT(*) FROM judge_prompts", [], |row| row.get(0))?;
+        if templates == 0 {
+            let builtin = JudgeTemplate::builtin();
+            conn.execute(
+                "INSERT INTO judge_prompts
+                    (version, system_prompt, ground_truth_template, generic_template, notes,
+                     active, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6)",
+                params![
+                    builtin.version,
+                    builtin.system_prompt,
+                    builtin.ground_truth_template,
+                    builtin.generic_template,
+                    builtin.notes,
+                    Utc::now().to_rfc3339(),
+                ],
+            )?;
+        }
+
         Ok(())
     }
 
@@ -367,6 +632,73 @@ impl EvalDb {
         ]
     }
 
+    /// Every run, newest first, as seen by the retention policy.
+    pub fn list_runs_for_retention(&self) -> anyhow::Result<Vec<RunRow>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, status, started_at, parent_run_id FROM eval_runs
+             ORDER BY started_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RunRow {
+                id: row.get(0)?,
+                status: row.get(1)?,
+                started_at: row.get(2)?,
+                parent_run_id: row.get(3)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Delete runs; their results and category scores cascade.
+    pub fn delete_runs(&self, ids: &[String]) -> anyhow::Result<usize> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut deleted = 0;
+        {
+            let mut stmt = tx.prepare("DELETE FROM eval_runs WHERE id=?1")?;
+            for id in ids {
+                deleted += stmt.execute(params![id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    pub fn vacuum(&self) -> anyhow::Result<()> {
+        self.conn().execute_batch("VACUUM;")?;
+        Ok(())
+    }
+
+    /// `(status, seconds)` of every finished run, for `GET /metrics`.
+    pub fn run_durations(&self) -> anyhow::Result<Vec<(String, f64)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT status, started_at, completed_at FROM eval_runs
+             WHERE completed_at IS NOT NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut durations = Vec::new();
+        for row in rows {
+            let (status, started, completed) = row?;
+            let (Ok(started), Ok(completed)) = (
+                chrono::DateTime::parse_from_rfc3339(&started),
+                chrono::DateTime::parse_from_rfc3339(&completed),
+            ) else {
+                continue;
+            };
+            let secs = (completed - started).num_milliseconds() as f64 / 1000.0;
+            durations.push((status, secs.max(0.0)));
+        }
+        Ok(durations)
+    }
+
     /// Mark any runs left in "running" status as "interrupted".
     /// Called on startup to clean up stale state from previous supervisor instances.
     pub fn cleanup_stale_runs(&self) -> anyhow::Result<usize> {
@@ -394,46 +726,75 @@ impl EvalDb {
     // Test prompt CRUD
     // ========================================================================
 
+    fn row_to_test_prompt(row: &rusqlite::Row<'_>) -> rusqlite::Result<TestPrompt> {
+        Ok(TestPrompt {
+            id: row.get(0)?,
+            prompt: row.get(1)?,
+            category: row.get(2)?,
+            complexity: row.get(3)?,
+            expected_phases: row
+                .get::<_, Option<String>>(4)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            expected_step_types: row
+                .get::<_, Option<String>>(5)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            tags: row
+                .get::<_, Option<String>>(6)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            ground_truth_json: row.get(7)?,
+            enabled: row.get::<_, i64>(8)? != 0,
+            status: PromptStatus::parse(&row.get::<_, String>(9)?).unwrap_or_default(),
+            needs_review: row.get::<_, i64>(10)? != 0,
+            created_at: row.get(11)?,
+            updated_at: row.get(12)?,
+        })
+    }
+
     pub fn list_test_prompts(&self) -> anyhow::Result<Vec<TestPrompt>> {
         let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, prompt, category, complexity, expected_phases, expected_step_types, tags, ground_truth_json, enabled, created_at, updated_at
+            "SELECT id, prompt, category, complexity, expected_phases, expected_step_types, tags, ground_truth_json, enabled, status, needs_review, created_at, updated_at
              FROM test_prompts ORDER BY category, id",
         )?;
-        let rows = stmt.query_map([], |row| {
-            Ok(TestPrompt {
-                id: row.get(0)?,
-                prompt: row.get(1)?,
-                category: row.get(2)?,
-                complexity: row.get(3)?,
-                expected_phases: row
-                    .get::<_, Option<String>>(4)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                expected_step_types: row
-                    .get::<_, Option<String>>(5)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                tags: row
-                    .get::<_, Option<String>>(6)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                ground_truth_json: row.get(7)?,
-                enabled: row.get::<_, i64>(8)? != 0,
-                created_at: row.get(9)?,
-                updated_at: row.get(10)?,
-            })
-        })?;
+        let rows = stmt.query_map([], Self::row_to_test_prompt)?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    pub fn get_test_prompt(&self, id: &str) -> anyhow::Result<Option<TestPrompt>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT id, prompt, category, complexity, expected_phases, expected_step_types, tags, ground_truth_json, enabled, status, needs_review, created_at, updated_at
+             FROM test_prompts WHERE id=?1",
+            params![id],
+            Self::row_to_test_prompt,
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    /// Prompts the engine evaluates: enabled and in the `active` state.
     pub fn list_enabled_test_prompts(&self) -> anyhow::Result<Vec<TestPrompt>> {
         let all = self.list_test_prompts()?;
-        Ok(all.into_iter().filter(|p| p.enabled).collect())
+        Ok(all
+            .into_iter()
+            .filter(|p| p.enabled && p.status == PromptStatus::Active)
+            .collect())
     }
 
     pub fn insert_test_prompt(&self, prompt: &TestPrompt) -> anyhow::Result<()> {
+        self.write_test_prompt(prompt, "INSERT")
+    }
+
+    /// Insert or fully replace (including status) — used by bundle import.
+    pub fn replace_test_prompt(&self, prompt: &TestPrompt) -> anyhow::Result<()> {
+        self.write_test_prompt(prompt, "INSERT OR REPLACE")
+    }
+
+    fn write_test_prompt(&self, prompt: &TestPrompt, verb: &str) -> anyhow::Result<()> {
         let conn = self.conn();
         conn.execute(
-            "INSERT INTO test_prompts (id, prompt, category, complexity, expected_phases, expected_step_types, tags, ground_truth_json, enabled, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            &format!("{} INTO test_prompts (id, prompt, category, complexity, expected_phases, expected_step_types, tags, ground_truth_json, enabled, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)", verb),
             params![
                 prompt.id,
                 prompt.prompt,
@@ -444,6 +805,7 @@ impl EvalDb {
                 prompt.tags.as_ref().map(|v| serde_json::to_string(v).unwrap()),
                 prompt.ground_truth_json,
                 prompt.enabled as i64,
+                prompt.status.as_str(),
                 prompt.created_at,
                 prompt.updated_at,
             ],
@@ -487,12 +849,413 @@ impl EvalDb {
         Ok(updated > 0)
     }
 
+    pub fn set_prompt_status(&self, id: &str, status: PromptStatus) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let now = Utc::now().to_rfc3339();
+        let updated = conn.execute(
+            "UPDATE test_prompts SET status=?2, needs_review=0, updated_at=?3 WHERE id=?1",
+            params![id, status.as_str(), now],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn clear_needs_review(&self, id: &str) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let now = Utc::now().to_rfc3339();
+        let updated = conn.execute(
+            "UPDATE test_prompts SET needs_review=0, updated_at=?2 WHERE id=?1",
+            params![id, now],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Flag prompts from `run_id` whose last `window` scored results all
+    /// reached `min_score`. Returns how many prompts were newly flagged.
+    pub fn flag_saturated_prompts(
+        &self,
+        run_id: &str,
+        window: i64,
+        min_score: f64,
+    ) -> anyhow::Result<usize> {
+        let conn = self.conn();
+        let flagged = conn.execute(
+            "UPDATE test_prompts SET needs_review = 1
+             WHERE needs_review = 0
+               AND id IN (SELECT test_prompt_id FROM eval_results WHERE run_id = ?1)
+               AND (SELECT COUNT(*) FROM (
+                        SELECT overall_score FROM eval_results r
+                        WHERE r.test_prompt_id = test_prompts.id AND r.overall_score IS NOT NULL
+                        ORDER BY r.id DESC LIMIT ?2
+                    ) WHERE overall_score >= ?3) = ?2",
+            params![run_id, window, min_score],
+        )?;
+        Ok(flagged)
+    }
+
     pub fn delete_test_prompt(&self, id: &str) -> anyhow::Result<bool> {
         let conn = self.conn();
         let deleted = conn.execute("DELETE FROM test_prompts WHERE id=?1", params![id])?;
         Ok(deleted > 0)
     }
 
+    /// Checksum recorded at the last directory sync, by prompt ID.
+    pub fn prompt_source_checksums(&self) -> anyhow::Result<HashMap<String, String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT prompt_id, checksum FROM prompt_sources")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub fn record_prompt_source(
+        &self,
+        prompt_id: &str,
+        source_path: &str,
+        checksum: &str,
+        synced_at: &str,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO prompt_sources (prompt_id, source_path, checksum, synced_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![prompt_id, source_path, checksum, synced_at],
+        )?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // Prompt suite CRUD
+    // ========================================================================
+
+    fn row_to_suite(row: &rusqlite::Row<'_>) -> rusqlite::Result<PromptSuite> {
+        Ok(PromptSuite {
+            name: row.get(0)?,
+            description: row.get(1)?,
+            prompt_ids: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    }
+
+    pub fn list_suites(&self) -> anyhow::Result<Vec<PromptSuite>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT name, description, prompt_ids, created_at, updated_at
+             FROM prompt_suites ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_suite)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn get_suite(&self, name: &str) -> anyhow::Result<Option<PromptSuite>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT name, description, prompt_ids, created_at, updated_at
+             FROM prompt_suites WHERE name=?1",
+            params![name],
+            Self::row_to_suite,
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    pub fn insert_suite(&self, suite: &PromptSuite) -> anyhow::Result<()> {
+        self.write_suite(suite, "INSERT")
+    }
+
+    pub fn replace_suite(&self, suite: &PromptSuite) -> anyhow::Result<()> {
+        self.write_suite(suite, "INSERT OR REPLACE")
+    }
+
+    fn write_suite(&self, suite: &PromptSuite, verb: &str) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            &format!(
+                "{} INTO prompt_suites (name, description, prompt_ids, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                verb
+            ),
+            params![
+                suite.name,
+                suite.description,
+                serde_json::to_string(&suite.prompt_ids)?,
+                suite.created_at,
+                suite.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_suite(&self, name: &str, suite: &PromptSuite) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE prompt_suites SET description=?2, prompt_ids=?3, updated_at=?4 WHERE name=?1",
+            params![
+                name,
+                suite.description,
+                serde_json::to_string(&suite.prompt_ids)?,
+                suite.updated_at,
+            ],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn delete_suite(&self, name: &str) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let deleted = conn.execute("DELETE FROM prompt_suites WHERE name=?1", params![name])?;
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Judge prompt templates
+    // ========================================================================
+
+    fn row_to_judge_template(row: &rusqlite::Row<'_>) -> rusqlite::Result<JudgeTemplate> {
+        Ok(JudgeTemplate {
+            version: row.get(0)?,
+            system_prompt: row.get(1)?,
+            ground_truth_template: row.get(2)?,
+            generic_template: row.get(3)?,
+            notes: row.get(4)?,
+            active: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    }
+
+    pub fn list_judge_templates(&self) -> anyhow::Result<Vec<JudgeTemplate>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT version, system_prompt, ground_truth_template, generic_template, notes,
+                    active, created_at
+             FROM judge_prompts ORDER BY version",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_judge_template)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn get_judge_template(&self, version: i64) -> anyhow::Result<Option<JudgeTemplate>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT version, system_prompt, ground_truth_template, generic_template, notes,
+                    active, created_at
+             FROM judge_prompts WHERE version=?1",
+            params![version],
+            Self::row_to_judge_template,
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    pub fn active_judge_template(&self) -> anyhow::Result<Option<JudgeTemplate>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT version, system_prompt, ground_truth_template, generic_template, notes,
+                    active, created_at
+             FROM judge_prompts WHERE active=1 ORDER BY version DESC LIMIT 1",
+            [],
+            Self::row_to_judge_template,
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    /// Store `template` as the next version; returns the new version number.
+    pub fn insert_judge_template(&self, template: &JudgeTemplate) -> anyhow::Result<i64> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        if template.active {
+            tx.execute("UPDATE judge_prompts SET active=0", [])?;
+        }
+        tx.execute(
+            "INSERT INTO judge_prompts
+                (system_prompt, ground_truth_template, generic_template, notes, active, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                template.system_prompt,
+                template.ground_truth_template,
+                template.generic_template,
+                template.notes,
+                template.active,
+                template.created_at,
+            ],
+        )?;
+        let version = tx.last_insert_rowid();
+        tx.commit()?;
+        Ok(version)
+    }
+
+    /// Make `version` the active template. Returns false if it doesn't exist.
+    pub fn activate_judge_template(&self, version: i64) -> anyhow::Result<bool> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let exists: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM judge_prompts WHERE version=?1)",
+            params![version],
+            |row| row.get(0),
+        )?;
+        if exists {
+            tx.execute(
+                "UPDATE judge_prompts SET active = (version = ?1)",
+                params![version],
+            )?;
+        }
+        tx.commit()?;
+        Ok(exists)
+    }
+
+    /// Runs scored with `version`.
+    pub fn judge_template_run_count(&self, version: i64) -> anyhow::Result<i64> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT COUNT(*) FROM eval_runs WHERE judge_template_version=?1",
+            params![version],
+            |row| row.get(0),
+        )
+        .map_err(Into::into)
+    }
+
+    pub fn delete_judge_template(&self, version: i64) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let deleted = conn.execute(
+            "DELETE FROM judge_prompts WHERE version=?1",
+            params![version],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Baseline pin
+    // ========================================================================
+
+    pub fn get_baseline(&self) -> anyhow::Result<Option<BaselinePin>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT run_id, pinned_at, max_avg_drop, max_regressions, webhook_url
+             FROM eval_baseline WHERE id=1",
+            [],
+            |row| {
+                Ok(BaselinePin {
+                    run_id: row.get(0)?,
+                    pinned_at: row.get(1)?,
+                    max_avg_drop: row.get(2)?,
+                    max_regressions: row.get::<_, i64>(3)? as usize,
+                    webhook_url: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    pub fn set_baseline(&self, pin: &BaselinePin) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO eval_baseline
+                (id, run_id, pinned_at, max_avg_drop, max_regressions, webhook_url)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+            params![
+                pin.run_id,
+                pin.pinned_at,
+                pin.max_avg_drop,
+                pin.max_regressions as i64,
+                pin.webhook_url,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear_baseline(&self) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let deleted = conn.execute("DELETE FROM eval_baseline WHERE id=1", [])?;
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Eval schedule CRUD
+    // ========================================================================
+
+    fn row_to_schedule(row: &rusqlite::Row<'_>) -> rusqlite::Result<EvalSchedule> {
+        Ok(EvalSchedule {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            cron: row.get(2)?,
+            enabled: row.get::<_, i64>(3)? != 0,
+            suite: row.get(4)?,
+            prompt_ids: row
+                .get::<_, Option<String>>(5)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            concurrency: row.get::<_, Option<i64>>(6)?.map(|c| c as usize),
+            judge_provider: row.get(7)?,
+            judge_model: row.get(8)?,
+            last_fired_at: row.get(9)?,
+            created_at: row.get(10)?,
+            updated_at: row.get(11)?,
+        })
+    }
+
+    pub fn list_schedules(&self) -> anyhow::Result<Vec<EvalSchedule>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, cron, enabled, suite, prompt_ids, concurrency,
+                    judge_provider, judge_model, last_fired_at, created_at, updated_at
+             FROM eval_schedules ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_schedule)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn insert_schedule(&self, schedule: &EvalSchedule) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO eval_schedules (id, name, cron, enabled, suite, prompt_ids, concurrency,
+                judge_provider, judge_model, last_fired_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                schedule.id,
+                schedule.name,
+                schedule.cron,
+                schedule.enabled as i64,
+                schedule.suite,
+                schedule
+                    .prompt_ids
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                schedule.concurrency.map(|c| c as i64),
+                schedule.judge_provider,
+                schedule.judge_model,
+                schedule.last_fired_at,
+                schedule.created_at,
+                schedule.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_schedule_enabled(&self, id: &str, enabled: bool) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE eval_schedules SET enabled=?2, updated_at=?3 WHERE id=?1",
+            params![id, enabled as i64, Utc::now().to_rfc3339()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn mark_schedule_fired(&self, id: &str, fired_at: &str) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE eval_schedules SET last_fired_at=?2 WHERE id=?1",
+            params![id, fired_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_schedule(&self, id: &str) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let deleted = conn.execute("DELETE FROM eval_schedules WHERE id=?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
     // ========================================================================
     // Eval run CRUD
     // ========================================================================
@@ -500,8 +1263,8 @@ impl EvalDb {
     pub fn insert_eval_run(&self, run: &EvalRunSummary) -> anyhow::Result<()> {
         let conn = self.conn();
         conn.execute(
-            "INSERT INTO eval_runs (id, mode, status, prompts_total, prompts_completed, started_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO eval_runs (id, mode, status, prompts_total, prompts_completed, started_at, judge_provider, judge_model, parent_run_id, judge_template_version, matrix_id, samples_per_prompt, generation_provider, generation_model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 run.id,
                 run.mode,
@@ -509,11 +1272,45 @@ impl EvalDb {
                 run.prompts_total,
                 run.prompts_completed,
                 run.started_at,
+                run.judge_provider,
+                run.judge_model,
+                run.parent_run_id,
+                run.judge_template_version,
+                run.matrix_id,
+                run.samples_per_prompt,
+                run.generation_provider,
+                run.generation_model,
             ],
         )?;
         Ok(())
     }
 
+    pub fn set_run_environment(
+        &self,
+        run_id: &str,
+        snapshot: &EnvironmentSnapshot,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE eval_runs SET environment_json=?2 WHERE id=?1",
+            params![run_id, serde_json::to_string(snapshot)?],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_run_environment(&self, run_id: &str) -> anyhow::Result<Option<EnvironmentSnapshot>> {
+        let conn = self.conn();
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT environment_json FROM eval_runs WHERE id=?1",
+                params![run_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(json.and_then(|s| serde_json::from_str(&s).ok()))
+    }
+
     pub fn update_eval_run_progress(
         &self,
         run_id: &str,
@@ -535,14 +1332,20 @@ impl EvalDb {
     ) -> anyhow::Result<()> {
         let conn = self.conn();
         let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "UPDATE eval_runs SET status=?2, error=?3, completed_at=?4 WHERE id=?1",
+            params![run_id, status, error, now],
+        )?;
+        Self::recompute_aggregates(&conn, run_id)
+    }
 
+    /// Recompute a run's score aggregates and per-category scores from its
+    /// current result rows.
+    fn recompute_aggregates(conn: &Connection, run_id: &str) -> anyhow::Result<()> {
         // Compute aggregates from results — combined, ground-truth, and generic
         // Ground-truth prompts have IDs starting with "gt-"
         conn.execute(
             "UPDATE eval_runs SET
-                status=?2,
-                error=?3,
-                completed_at=?4,
                 -- Combined averages
                 avg_overall_score = (SELECT AVG(overall_score) FROM eval_results WHERE run_id=?1 AND overall_score IS NOT NULL),
                 avg_structural = (SELECT AVG(structural_correctness) FROM eval_results WHERE run_id=?1 AND structural_correctness IS NOT NULL),
@@ -568,13 +1371,266 @@ impl EvalDb {
                 gen_avg_step_completeness = (SELECT AVG(step_completeness) FROM eval_results WHERE run_id=?1 AND step_completeness IS NOT NULL AND test_prompt_id NOT LIKE 'gt-%'),
                 gen_avg_prompt_quality = (SELECT AVG(prompt_quality) FROM eval_results WHERE run_id=?1 AND prompt_quality IS NOT NULL AND test_prompt_id NOT LIKE 'gt-%'),
                 gen_avg_determinism = (SELECT AVG(determinism) FROM eval_results WHERE run_id=?1 AND determinism IS NOT NULL AND test_prompt_id NOT LIKE 'gt-%'),
-                gen_count = (SELECT COUNT(*) FROM eval_results WHERE run_id=?1 AND overall_score IS NOT NULL AND test_prompt_id NOT LIKE 'gt-%')
+                gen_count = (SELECT COUNT(*) FROM eval_results WHERE run_id=?1 AND overall_score IS NOT NULL AND test_prompt_id NOT LIKE 'gt-%'),
+                -- Results whose scores a human overrode
+                overridden_count = (SELECT COUNT(*) FROM eval_results WHERE run_id=?1 AND score_override IS NOT NULL)
              WHERE id=?1",
-            params![run_id, status, error, now],
+            params![run_id],
+        )?;
+
+        // Per-category averages. Category is taken from the prompt as it
+        // stands at completion time; prompts deleted mid-run fall under
+        // "uncategorized".
+        conn.execute(
+            "DELETE FROM eval_run_category_scores WHERE run_id=?1",
+            params![run_id],
+        )?;
+        conn.execute(
+            "INSERT INTO eval_run_category_scores (run_id, category, count,
+                avg_overall, avg_structural, avg_command_accuracy, avg_phase_flow,
+                avg_step_completeness, avg_prompt_quality, avg_determinism)
+             SELECT r.run_id, COALESCE(p.category, 'uncategorized'), COUNT(*),
+                AVG(r.overall_score), AVG(r.structural_correctness), AVG(r.command_accuracy),
+                AVG(r.phase_flow_logic), AVG(r.step_completeness), AVG(r.prompt_quality),
+                AVG(r.determinism)
+             FROM eval_results r LEFT JOIN test_prompts p ON p.id = r.test_prompt_id
+             WHERE r.run_id=?1 AND r.overall_score IS NOT NULL
+             GROUP BY r.run_id, COALESCE(p.category, 'uncategorized')",
+            params![run_id],
         )?;
         Ok(())
     }
 
+    /// Overall score of the most recent completed, non-retry run.
+    pub fn latest_completed_score(&self) -> anyhow::Result<Option<f64>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT avg_overall_score FROM eval_runs
+             WHERE status='completed' AND mode != 'retry'
+             ORDER BY started_at DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map(Option::flatten)
+        .map_err(Into::into)
+    }
+
+    /// Record the prompts a run will evaluate, in order.
+    pub fn set_planned_prompts(&self, run_id: &str, prompt_ids: &[String]) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE eval_runs SET planned_prompt_ids=?2 WHERE id=?1",
+            params![run_id, serde_json::to_string(prompt_ids)?],
+        )?;
+        Ok(())
+    }
+
+    /// `None` for runs started before plans were recorded.
+    pub fn planned_prompts(&self, run_id: &str) -> anyhow::Result<Option<Vec<String>>> {
+        let conn = self.conn();
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT planned_prompt_ids FROM eval_runs WHERE id=?1",
+                params![run_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+    }
+
+    /// Result rows per prompt already recorded in `run_id`.
+    pub fn result_counts(&self, run_id: &str) -> anyhow::Result<HashMap<String, usize>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT test_prompt_id, COUNT(*) FROM eval_results WHERE run_id=?1
+             GROUP BY test_prompt_id",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| {
+            Ok((row.get(0)?, row.get::<_, i64>(1)? as usize))
+        })?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Put a stopped run back into `running` for [`super::engine::run_eval`]
+    /// to resume. Only `interrupted` and `aborted` runs qualify; a cancelled
+    /// run was stopped on purpose.
+    pub fn reopen_eval_run(&self, run_id: &str) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let n = conn.execute(
+            "UPDATE eval_runs SET status='running', error=NULL, completed_at=NULL
+             WHERE id=?1 AND status IN ('interrupted', 'aborted')",
+            params![run_id],
+        )?;
+        Ok(n > 0)
+    }
+
+    /// Most recent run cut off by a supervisor exit.
+    pub fn latest_interrupted_run(&self) -> anyhow::Result<Option<String>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT id FROM eval_runs WHERE status='interrupted'
+             ORDER BY started_at DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    /// Replace the judge's scores on result `result_id` with `patch`'s set
+    /// dimensions and recompute its run's aggregates. The judge's original
+    /// scores are kept from the first override on. Returns the run ID and
+    /// the stored override, or `None` if the result doesn't exist.
+    pub fn override_scores(
+        &self,
+        result_id: i64,
+        patch: &DimensionScores,
+        reason: &str,
+    ) -> anyhow::Result<Option<(String, ScoreOverride)>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let row = tx
+            .query_row(
+                "SELECT run_id, structural_correctness, command_accuracy, phase_flow_logic,
+                        step_completeness, prompt_quality, determinism, overall_score,
+                        score_override
+                 FROM eval_results WHERE id=?1",
+                params![result_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        DimensionScores {
+                            structural_correctness: row.get(1)?,
+                            command_accuracy: row.get(2)?,
+                            phase_flow_logic: row.get(3)?,
+                            step_completeness: row.get(4)?,
+                            prompt_quality: row.get(5)?,
+                            determinism: row.get(6)?,
+                        },
+                        row.get::<_, Option<f64>>(7)?,
+                        row.get::<_, Option<String>>(8)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((run_id, current, overall, previous)) = row else {
+            return Ok(None);
+        };
+
+        let updated = current.merged(patch);
+        let Some(new_overall) = updated.overall() else {
+            anyhow::bail!("the judge left some dimensions unscored; override all six");
+        };
+        let previous: Option<ScoreOverride> = previous.and_then(|s| serde_json::from_str(&s).ok());
+        let record = match previous {
+            Some(p) => ScoreOverride {
+                reason: reason.to_string(),
+                overridden_at: Utc::now().to_rfc3339(),
+                ..p
+            },
+            None => ScoreOverride {
+                original: current,
+                original_overall: overall,
+                reason: reason.to_string(),
+                overridden_at: Utc::now().to_rfc3339(),
+            },
+        };
+        tx.execute(
+            "UPDATE eval_results SET structural_correctness=?2, command_accuracy=?3,
+                phase_flow_logic=?4, step_completeness=?5, prompt_quality=?6, determinism=?7,
+                overall_score=?8, score_override=?9
+             WHERE id=?1",
+            params![
+                result_id,
+                updated.structural_correctness,
+                updated.command_accuracy,
+                updated.phase_flow_logic,
+                updated.step_completeness,
+                updated.prompt_quality,
+                updated.determinism,
+                new_overall,
+                serde_json::to_string(&record)?
+            ],
+        )?;
+        Self::recompute_aggregates(&tx, &run_id)?;
+        tx.commit()?;
+        Ok(Some((run_id, record)))
+    }
+
+    /// Prompt IDs whose result in `run_id` hit a generation or scoring error.
+    pub fn failed_prompt_ids(&self, run_id: &str) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT test_prompt_id FROM eval_results
+             WHERE run_id=?1 AND (generation_error IS NOT NULL OR scoring_error IS NOT NULL)
+             ORDER BY test_prompt_id",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| row.get(0))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Fold the successful results of retry run `child_id` into `parent_id`:
+    /// for each prompt, that many of the parent's failed rows are replaced by
+    /// copies of the retry results (its successful samples are left alone),
+    /// then the parent's aggregates are recomputed. The child run keeps its
+    /// own rows. Returns the number of results merged.
+    pub fn merge_retry_results(&self, parent_id: &str, child_id: &str) -> anyhow::Result<usize> {
+        const SUCCEEDED: &str = "overall_score IS NOT NULL
+               AND generation_error IS NULL AND scoring_error IS NULL";
+        const FAILED: &str = "(generation_error IS NOT NULL OR scoring_error IS NOT NULL
+               OR overall_score IS NULL)";
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let succeeded: Vec<(String, i64)> = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT test_prompt_id, COUNT(*) FROM eval_results
+                 WHERE run_id=?1 AND {}
+                 GROUP BY test_prompt_id",
+                SUCCEEDED
+            ))?;
+            let rows = stmt.query_map(params![child_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut merged = 0;
+        for (prompt_id, count) in succeeded {
+            let replaced = tx.execute(
+                &format!(
+                    "DELETE FROM eval_results WHERE id IN (
+                        SELECT id FROM eval_results
+                        WHERE run_id=?1 AND test_prompt_id=?2 AND {}
+                        ORDER BY id LIMIT ?3)",
+                    FAILED
+                ),
+                params![parent_id, prompt_id, count],
+            )?;
+            merged += tx.execute(
+                &format!(
+                    "INSERT INTO eval_results (run_id, test_prompt_id, generated_workflow_json, task_run_id, workflow_id,
+                        structural_correctness, command_accuracy, phase_flow_logic, step_completeness, prompt_quality, determinism, overall_score,
+                        score_rationales, generation_error, scoring_error, generation_duration_ms, scoring_duration_ms, started_at, completed_at,
+                        structural_metrics, structural_f1)
+                     SELECT ?1, test_prompt_id, generated_workflow_json, task_run_id, workflow_id,
+                        structural_correctness, command_accuracy, phase_flow_logic, step_completeness, prompt_quality, determinism, overall_score,
+                        score_rationales, generation_error, scoring_error, generation_duration_ms, scoring_duration_ms, started_at, completed_at,
+                        structural_metrics, structural_f1
+                     FROM eval_results
+                     WHERE run_id=?2 AND test_prompt_id=?3 AND {}
+                     ORDER BY id LIMIT ?4",
+                    SUCCEEDED
+                ),
+                params![parent_id, child_id, prompt_id, replaced as i64],
+            )?;
+        }
+        Self::recompute_aggregates(&tx, parent_id)?;
+        tx.commit()?;
+        Ok(merged)
+    }
+
     // ========================================================================
     // Eval result CRUD
     // ========================================================================
@@ -584,8 +1640,9 @@ impl EvalDb {
         conn.execute(
             "INSERT INTO eval_results (run_id, test_prompt_id, generated_workflow_json, task_run_id, workflow_id,
                 structural_correctness, command_accuracy, phase_flow_logic, step_completeness, prompt_quality, determinism, overall_score,
-                score_rationales, generation_error, scoring_error, generation_duration_ms, scoring_duration_ms, started_at, completed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+                score_rationales, generation_error, scoring_error, generation_duration_ms, scoring_duration_ms, started_at, completed_at,
+                structural_metrics, structural_f1)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
             params![
                 result.run_id,
                 result.test_prompt_id,
@@ -606,6 +1663,11 @@ impl EvalDb {
                 result.scoring_duration_ms,
                 result.started_at,
                 result.completed_at,
+                result
+                    .structural_metrics
+                    .as_ref()
+                    .and_then(|m| serde_json::to_string(m).ok()),
+                result.structural_metrics.as_ref().and_then(|m| m.f1),
             ],
         )?;
         Ok(conn.last_insert_rowid())
@@ -626,7 +1688,9 @@ impl EvalDb {
                     gt_avg_step_completeness, gt_avg_prompt_quality, gt_avg_determinism, gt_count,
                     gen_avg_overall, gen_avg_structural, gen_avg_command_accuracy, gen_avg_phase_flow,
                     gen_avg_step_completeness, gen_avg_prompt_quality, gen_avg_determinism, gen_count,
-                    error, started_at, completed_at
+                    error, started_at, completed_at, judge_provider, judge_model, parent_run_id,
+                    judge_template_version, matrix_id, overridden_count, samples_per_prompt,
+                    generation_provider, generation_model
                  FROM eval_runs WHERE id=?1",
                 params![run_id],
                 |row| {
@@ -662,47 +1726,191 @@ impl EvalDb {
                         error: row.get(28)?,
                         started_at: row.get(29)?,
                         completed_at: row.get(30)?,
+                        judge_provider: row.get(31)?,
+                        judge_model: row.get(32)?,
+                        parent_run_id: row.get(33)?,
+                        judge_template_version: row.get(34)?,
+                        matrix_id: row.get(35)?,
+                        overridden_count: row.get(36)?,
+                        samples_per_prompt: row.get(37)?,
+                        generation_provider: row.get(38)?,
+                        generation_model: row.get(39)?,
+                        category_scores: Vec::new(),
                     })
                 },
             )
             .optional()?;
-        Ok(result)
+        let Some(mut run) = result else {
+            return Ok(None);
+        };
+        run.category_scores = Self::query_category_scores(&conn, run_id)?;
+        Ok(Some(run))
     }
 
-    pub fn get_results_for_run(&self, run_id: &str) -> anyhow::Result<Vec<EvalResult>> {
-        let conn = self.conn();
+    /// Map a `category, count, avg_overall, ... avg_determinism` row
+    /// starting at column `base`.
+    pub(super) fn row_to_category_score(
+        row: &rusqlite::Row<'_>,
+        base: usize,
+    ) -> rusqlite::Result<CategoryScore> {
+        Ok(CategoryScore {
+            category: row.get(base)?,
+            count: row.get(base + 1)?,
+            avg_overall: row.get(base + 2)?,
+            avg_structural: row.get(base + 3)?,
+            avg_command_accuracy: row.get(base + 4)?,
+            avg_phase_flow: row.get(base + 5)?,
+            avg_step_completeness: row.get(base + 6)?,
+            avg_prompt_quality: row.get(base + 7)?,
+            avg_determinism: row.get(base + 8)?,
+        })
+    }
+
+    fn query_category_scores(
+        conn: &Connection,
+        run_id: &str,
+    ) -> anyhow::Result<Vec<CategoryScore>> {
         let mut stmt = conn.prepare(
-            "SELECT id, run_id, test_prompt_id, generated_workflow_json, task_run_id, workflow_id,
-                    structural_correctness, command_accuracy, phase_flow_logic, step_completeness,
-                    prompt_quality, determinism, overall_score, score_rationales,
-                    generation_error, scoring_error, generation_duration_ms, scoring_duration_ms,
-                    started_at, completed_at
-             FROM eval_results WHERE run_id=?1 ORDER BY id",
+            "SELECT category, count, avg_overall, avg_structural, avg_command_accuracy,
+                    avg_phase_flow, avg_step_completeness, avg_prompt_quality, avg_determinism
+             FROM eval_run_category_scores WHERE run_id=?1 ORDER BY category",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| Self::row_to_category_score(row, 0))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Results of a run matching `filter`, ordered by ID and paged.
+    pub fn query_results(
+        &self,
+        run_id: &str,
+        filter: &ResultFilter,
+    ) -> anyhow::Result<Vec<EvalResult>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.id, r.run_id, r.test_prompt_id,
+                    CASE WHEN ?6 THEN r.generated_workflow_json END, r.task_run_id, r.workflow_id,
+                    r.structural_correctness, r.command_accuracy, r.phase_flow_logic,
+                    r.step_completeness, r.prompt_quality, r.determinism, r.overall_score,
+                    r.score_rationales, r.generation_error, r.scoring_error,
+                    r.generation_duration_ms, r.scoring_duration_ms, r.started_at, r.completed_at,
+                    r.structural_metrics, r.score_override
+             {}
+             ORDER BY r.id LIMIT ?7 OFFSET ?8",
+            RESULT_FILTER_SQL
+        ))?;
+        let limit = filter.limit.map_or(-1, |l| l as i64);
+        let rows = stmt.query_map(
+            params![
+                run_id,
+                filter.min_score,
+                filter.max_score,
+                filter.category,
+                filter.has_error,
+                filter.include_workflow_json,
+                limit,
+                filter.offset as i64
+            ],
+            |row| {
+                Ok(EvalResult {
+                    id: row.get(0)?,
+                    run_id: row.get(1)?,
+                    test_prompt_id: row.get(2)?,
+                    generated_workflow_json: row.get(3)?,
+                    task_run_id: row.get(4)?,
+                    workflow_id: row.get(5)?,
+                    structural_correctness: row.get(6)?,
+                    command_accuracy: row.get(7)?,
+                    phase_flow_logic: row.get(8)?,
+                    step_completeness: row.get(9)?,
+                    prompt_quality: row.get(10)?,
+                    determinism: row.get(11)?,
+                    overall_score: row.get(12)?,
+                    score_rationales: row.get(13)?,
+                    structural_metrics: row
+                        .get::<_, Option<String>>(20)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                    generation_error: row.get(14)?,
+                    scoring_error: row.get(15)?,
+                    generation_duration_ms: row.get(16)?,
+                    scoring_duration_ms: row.get(17)?,
+                    score_override: row
+                        .get::<_, Option<String>>(21)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                    started_at: row.get(18)?,
+                    completed_at: row.get(19)?,
+                })
+            },
         )?;
-        let rows = stmt.query_map(params![run_id], |row| {
-            Ok(EvalResult {
-                id: row.get(0)?,
-                run_id: row.get(1)?,
-                test_prompt_id: row.get(2)?,
-                generated_workflow_json: row.get(3)?,
-                task_run_id: row.get(4)?,
-                workflow_id: row.get(5)?,
-                structural_correctness: row.get(6)?,
-                command_accuracy: row.get(7)?,
-                phase_flow_logic: row.get(8)?,
-                step_completeness: row.get(9)?,
-                prompt_quality: row.get(10)?,
-                determinism: row.get(11)?,
-                overall_score: row.get(12)?,
-                score_rationales: row.get(13)?,
-                generation_error: row.get(14)?,
-                scoring_error: row.get(15)?,
-                generation_duration_ms: row.get(16)?,
-                scoring_duration_ms: row.get(17)?,
-                started_at: row.get(18)?,
-                completed_at: row.get(19)?,
-            })
-        })?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
+
+    /// Number of results matching `filter`, ignoring `limit`/`offset`.
+    pub fn count_results(&self, run_id: &str, filter: &ResultFilter) -> anyhow::Result<usize> {
+        let conn = self.conn();
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) {}", RESULT_FILTER_SQL),
+            params![
+                run_id,
+                filter.min_score,
+                filter.max_score,
+                filter.category,
+                filter.has_error
+            ],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_run(db: &EvalDb, id: &str) {
+        db.conn()
+            .execute(
+                "INSERT INTO eval_runs (id, mode, status, prompts_total, prompts_completed, started_at)
+                 VALUES (?1, 'single', 'completed', 1, 1, '2026-06-01T00:00:00Z')",
+                params![id],
+            )
+            .unwrap();
+    }
+
+    fn insert_result(db: &EvalDb, run_id: &str, score: Option<f64>, error: Option<&str>) {
+        db.conn()
+            .execute(
+                "INSERT INTO eval_results (run_id, test_prompt_id, overall_score, generation_error, started_at)
+                 VALUES (?1, 'p1', ?2, ?3, '2026-06-01T00:00:00Z')",
+                params![run_id, score, error],
+            )
+            .unwrap();
+    }
+
+    fn scores(db: &EvalDb, run_id: &str) -> Vec<Option<f64>> {
+        let conn = db.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT overall_score FROM eval_results WHERE run_id=?1 ORDER BY overall_score",
+            )
+            .unwrap();
+        let rows = stmt.query_map(params![run_id], |row| row.get(0)).unwrap();
+        rows.collect::<Result<Vec<_>, _>>().unwrap()
+    }
+
+    #[test]
+    fn retry_merge_replaces_only_the_failed_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = EvalDb::new(dir.path(), false).unwrap();
+        insert_run(&db, "parent");
+        insert_run(&db, "retry");
+        // Three samples of one prompt: two scored, one failed to generate.
+        insert_result(&db, "parent", Some(4.0), None);
+        insert_result(&db, "parent", Some(3.0), None);
+        insert_result(&db, "parent", None, Some("runner timed out"));
+        insert_result(&db, "retry", Some(5.0), None);
+
+        assert_eq!(db.merge_retry_results("parent", "retry").unwrap(), 1);
+        assert_eq!(scores(&db, "parent"), vec![Some(3.0), Some(4.0), Some(5.0)]);
+        assert_eq!(scores(&db, "retry"), vec![Some(5.0)]);
+    }
 }
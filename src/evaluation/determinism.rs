@@ -0,0 +1,141 @@
+//! Measured determinism for runs with `samples_per_prompt` > 1.
+//!
+//! The judge's `determinism` dimension is a guess from a single workflow.
+//! A sampled run generates each prompt several times instead, so
+//! `GET /eval/runs/{id}/determinism` can report what actually varied: the
+//! spread of the overall score across a prompt's samples, and how often two
+//! samples differ in structure (phases, step types, commands — see
+//! [`super::structural::signature`]).
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use super::db::EvalDb;
+use super::significance::std_dev;
+use super::structural;
+use super::ResultFilter;
+
+/// Upper bound on `samples_per_prompt`.
+pub const MAX_SAMPLES_PER_PROMPT: u32 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptDeterminism {
+    pub test_prompt_id: String,
+    pub samples: usize,
+    pub scored: usize,
+    pub mean_overall: Option<f64>,
+    /// Sample variance of the overall score; `None` below two scores.
+    pub overall_variance: Option<f64>,
+    /// Structurally distinct workflows among the generated samples.
+    pub distinct_structures: usize,
+    /// Share of sample pairs whose workflows differ in structure; `None`
+    /// below two generated workflows.
+    pub structural_diff_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeterminismReport {
+    pub run_id: String,
+    pub samples_per_prompt: i64,
+    pub prompts: Vec<PromptDeterminism>,
+    /// Means over the prompts that have a value.
+    pub avg_overall_variance: Option<f64>,
+    pub avg_structural_diff_rate: Option<f64>,
+}
+
+/// One sample: its overall score and generated workflow.
+pub type Sample = (Option<f64>, Option<String>);
+
+pub fn prompt_determinism(test_prompt_id: &str, samples: &[Sample]) -> PromptDeterminism {
+    let scores: Vec<f64> = samples.iter().filter_map(|s| s.0).collect();
+    let signatures: Vec<String> = samples
+        .iter()
+        .filter_map(|s| s.1.as_deref().and_then(structural::signature))
+        .collect();
+
+    let mut pairs = 0usize;
+    let mut differing = 0usize;
+    for (i, a) in signatures.iter().enumerate() {
+        for b in &signatures[i + 1..] {
+            pairs += 1;
+            if a != b {
+                differing += 1;
+            }
+        }
+    }
+
+    PromptDeterminism {
+        test_prompt_id: test_prompt_id.to_string(),
+        samples: samples.len(),
+        scored: scores.len(),
+        mean_overall: (!scores.is_empty())
+            .then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+        overall_variance: std_dev(&scores).map(|sd| sd * sd),
+        distinct_structures: signatures.iter().collect::<HashSet<_>>().len(),
+        structural_diff_rate: (pairs > 0).then(|| differing as f64 / pairs as f64),
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    (n > 0).then(|| sum / n as f64)
+}
+
+/// `None` when the run doesn't exist.
+pub fn report(db: &EvalDb, run_id: &str) -> anyhow::Result<Option<DeterminismReport>> {
+    let Some(run) = db.get_eval_run(run_id)? else {
+        return Ok(None);
+    };
+    let mut by_prompt: BTreeMap<String, Vec<Sample>> = BTreeMap::new();
+    for r in db.query_results(run_id, &ResultFilter::default())? {
+        by_prompt
+            .entry(r.test_prompt_id)
+            .or_default()
+            .push((r.overall_score, r.generated_workflow_json));
+    }
+    let prompts: Vec<PromptDeterminism> = by_prompt
+        .iter()
+        .map(|(id, samples)| prompt_determinism(id, samples))
+        .collect();
+
+    Ok(Some(DeterminismReport {
+        run_id: run_id.to_string(),
+        samples_per_prompt: run.samples_per_prompt.unwrap_or(1),
+        avg_overall_variance: mean(prompts.iter().filter_map(|p| p.overall_variance)),
+        avg_structural_diff_rate: mean(prompts.iter().filter_map(|p| p.structural_diff_rate)),
+        prompts,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = r#"{"setup_steps": [{"type": "command", "command": "npm install"}]}"#;
+    const B: &str = r#"{"setup_steps": [{"type": "command", "command": "yarn"}]}"#;
+
+    #[test]
+    fn measures_score_spread_and_structure_changes() {
+        let samples = [
+            (Some(4.0), Some(A.to_string())),
+            (Some(3.0), Some(A.to_string())),
+            (Some(5.0), Some(B.to_string())),
+            (None, None),
+        ];
+        let d = prompt_determinism("p", &samples);
+        assert_eq!(d.samples, 4);
+        assert_eq!(d.scored, 3);
+        assert_eq!(d.mean_overall, Some(4.0));
+        assert!((d.overall_variance.unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(d.distinct_structures, 2);
+        // pairs: (A,A) same, (A,B) x2 differ
+        assert!((d.structural_diff_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_sample_has_no_spread() {
+        let d = prompt_determinism("p", &[(Some(4.0), Some(A.to_string()))]);
+        assert_eq!(d.overall_variance, None);
+        assert_eq!(d.structural_diff_rate, None);
+    }
+}
@@ -1,22 +1,41 @@
 use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
-use tokio::sync::watch;
+use tokio::sync::{broadcast, watch, Semaphore};
 use tracing::{error, info, warn};
 
 use super::db::EvalDb;
-use super::{EvalResult, EvalRunSummary};
-use crate::config::RUNNER_API_PORT;
+use super::judge::{JudgeModel, JudgeTemplate};
+use super::leaderboard::MatrixModel;
+use super::progress::{self, EvalProgressEvent};
+use super::{EvalResult, EvalRunSummary, ResultFilter, TestPrompt};
+use crate::config::{resolve_model_id, RUNNER_API_PORT};
 use crate::log_capture::{LogLevel, LogSource};
 use crate::state::SharedState;
+use crate::trace_propagation::TraceparentExt;
+
+/// Upper bound on prompts evaluated at once. Each one holds a runner
+/// generation task and a judge call, so the runner is the real limit.
+pub const MAX_EVAL_CONCURRENCY: usize = 8;
+
+/// A prompt whose last `SATURATION_WINDOW` scored results all reach
+/// `SATURATION_MIN_SCORE` (on the judge's 1-5 scale) is flagged
+/// `needs_review` — it is probably too easy to tell models apart.
+const SATURATION_WINDOW: i64 = 3;
+const SATURATION_MIN_SCORE: f64 = 4.9;
 
 /// After the meta-workflow completes, find the generated output workflow.
 /// Primary method: read the generated_workflow_id from the task run's result_data.
 /// Fallback: list all workflows and find the most recently created non-meta one.
+/// The fallback can't tell generations apart, so it is only used when
+/// `allow_listing` is set — i.e. when no other prompt is generating at once.
 async fn find_generated_workflow(
     http_client: &reqwest::Client,
     runner_url: &str,
     task_run_id: &str,
     meta_workflow_id: &str,
+    allow_listing: bool,
 ) -> anyhow::Result<(String, String)> {
     // Primary: get generated_workflow_id from task run result_data
     let result_data_resp = http_client
@@ -25,7 +44,7 @@ async fn find_generated_workflow(
             runner_url, task_run_id
         ))
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_traced()
         .await;
 
     if let Ok(resp) = result_data_resp {
@@ -58,6 +77,14 @@ async fn find_generated_workflow(
         }
     }
 
+    if !allow_listing {
+        anyhow::bail!(
+            "task run {} reported no generated_workflow_id, and the workflow list \
+             can't attribute workflows when prompts are evaluated concurrently",
+            task_run_id
+        );
+    }
+
     // Fallback: list all workflows and find the generated one by timestamp
     info!(
         "Falling back to workflow list search for meta {}",
@@ -75,7 +102,7 @@ async fn fetch_workflow_json(
     let wf_resp = http_client
         .get(format!("{}/unified-workflows/{}", runner_url, workflow_id))
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_traced()
         .await?;
 
     if !wf_resp.status().is_success() {
@@ -107,7 +134,7 @@ async fn find_generated_workflow_by_listing(
             runner_url, meta_workflow_id
         ))
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_traced()
         .await?;
 
     if !meta_resp.status().is_success() {
@@ -127,7 +154,7 @@ async fn find_generated_workflow_by_listing(
     let list_resp = http_client
         .get(format!("{}/unified-workflows", runner_url))
         .timeout(std::time::Duration::from_secs(10))
-        .send()
+        .send_traced()
         .await?;
 
     if !list_resp.status().is_success() {
@@ -189,21 +216,32 @@ async fn find_generated_workflow_by_listing(
 }
 
 /// Generate a workflow via the runner API and return (task_run_id, generated_workflow_id, workflow_json).
+/// `concurrent` is set when other prompts may be generating at the same time;
+/// `generation` overrides the model the runner generates with.
 async fn generate_workflow_for_eval(
     http_client: &reqwest::Client,
     prompt: &str,
+    concurrent: bool,
+    generation: Option<&MatrixModel>,
 ) -> anyhow::Result<(String, String, String)> {
     let runner_url = format!("http://127.0.0.1:{}", RUNNER_API_PORT);
 
     // Start async generation — runner expects both "prompt" and "description" fields
+    let mut body = serde_json::json!({
+        "prompt": prompt,
+        "description": prompt,
+    });
+    if let Some(m) = generation {
+        body["provider"] = serde_json::json!(m.provider);
+        body["model"] = serde_json::json!(
+            resolve_model_id(&m.provider, &m.model).unwrap_or_else(|| m.model.clone())
+        );
+    }
     let resp = http_client
         .post(format!("{}/unified-workflows/generate-async", runner_url))
-        .json(&serde_json::json!({
-            "prompt": prompt,
-            "description": prompt,
-        }))
+        .json(&body)
         .timeout(std::time::Duration::from_secs(30))
-        .send()
+        .send_traced()
         .await?;
 
     if !resp.status().is_success() {
@@ -244,7 +282,7 @@ async fn generate_workflow_for_eval(
                 runner_url, task_run_id
             ))
             .timeout(std::time::Duration::from_secs(10))
-            .send()
+            .send_traced()
             .await;
 
         match state_resp {
@@ -266,6 +304,7 @@ async fn generate_workflow_for_eval(
                         &runner_url,
                         &task_run_id,
                         &meta_workflow_id,
+                        !concurrent,
                     )
                     .await?;
 
@@ -282,17 +321,332 @@ async fn generate_workflow_for_eval(
     }
 }
 
-/// Run a single evaluation pass over all enabled test prompts.
+/// Per-run knobs from `POST /eval/start` / `POST /eval/continuous/start`.
+#[derive(Debug, Clone, Default)]
+pub struct EvalRunOptions {
+    /// Restrict the run to these prompt IDs (still active-only).
+    pub prompt_ids: Option<Vec<String>>,
+    /// Prompts evaluated at once; clamped to `1..=MAX_EVAL_CONCURRENCY`.
+    pub concurrency: usize,
+    /// Judge override. Both must be set to take effect; otherwise the
+    /// global AI settings at run start are used.
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+    /// Makes this a `retry` run of the given run: successful results are
+    /// merged back into it on completion.
+    pub retry_of: Option<String>,
+    /// `judge_prompts` version to score with; the active template if unset.
+    pub judge_template_version: Option<i64>,
+    /// Makes this a `rescore` run of the given run: its stored workflows are
+    /// judged again instead of generating new ones.
+    pub rescore_of: Option<String>,
+    /// Set on the runs of a model matrix (see [`run_matrix`]).
+    pub matrix_id: Option<String>,
+    /// Continue this stopped run instead of starting a new one: its planned
+    /// prompts that have no result yet are evaluated into it. Build with
+    /// [`resume_options`].
+    pub resume: Option<String>,
+    /// Generations (each scored) per prompt; 0 and 1 both mean one. Capped
+    /// at [`super::determinism::MAX_SAMPLES_PER_PROMPT`].
+    pub samples_per_prompt: u32,
+    /// Generation override sent to the runner with each prompt. Both must be
+    /// set to take effect; otherwise the runner generates with its own
+    /// configured model.
+    pub generation_provider: Option<String>,
+    pub generation_model: Option<String>,
+}
+
+/// Options that resume `run` as it was started.
+pub fn resume_options(run: &EvalRunSummary) -> EvalRunOptions {
+    let parent = |mode: &str| {
+        if run.mode == mode {
+            run.parent_run_id.clone()
+        } else {
+            None
+        }
+    };
+    EvalRunOptions {
+        prompt_ids: None,
+        concurrency: 1,
+        judge_provider: run.judge_provider.clone(),
+        judge_model: run.judge_model.clone(),
+        retry_of: parent("retry"),
+        judge_template_version: run.judge_template_version,
+        rescore_of: parent("rescore"),
+        matrix_id: run.matrix_id.clone(),
+        resume: Some(run.id.clone()),
+        samples_per_prompt: run.samples_per_prompt.unwrap_or(1) as u32,
+        generation_provider: run.generation_provider.clone(),
+        generation_model: run.generation_model.clone(),
+    }
+}
+
+/// Env var that, when `1`/`true`, resumes the latest interrupted run once
+/// the runner API answers after a supervisor restart.
+pub const RESUME_ON_STARTUP_ENV: &str = "QONTINUI_SUPERVISOR_EVAL_RESUME_ON_STARTUP";
+
+pub fn resume_on_startup() -> bool {
+    std::env::var(RESUME_ON_STARTUP_ENV)
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// A workflow generated by an earlier run: `(task_run_id, workflow_id,
+/// workflow_json)`, as returned by `generate_workflow_for_eval`.
+type PriorWorkflow = (String, String, String);
+
+/// The pinned template if it exists, else the active one, else the built-in
+/// rubric.
+fn resolve_judge_template(db: &EvalDb, pinned: Option<i64>) -> JudgeTemplate {
+    if let Some(version) = pinned {
+        match db.get_judge_template(version) {
+            Ok(Some(t)) => return t,
+            Ok(None) => warn!("Judge template v{} not found, using active", version),
+            Err(e) => warn!("Failed to load judge template v{}: {}", version, e),
+        }
+    }
+    match db.active_judge_template() {
+        Ok(Some(t)) => t,
+        Ok(None) => JudgeTemplate::builtin(),
+        Err(e) => {
+            warn!("Failed to load active judge template: {}", e);
+            JudgeTemplate::builtin()
+        }
+    }
+}
+
+/// Stored workflows of `run_id`'s results, keyed by prompt ID. Results that
+/// never produced a workflow are left out.
+fn prior_workflows(db: &EvalDb, run_id: &str) -> anyhow::Result<HashMap<String, PriorWorkflow>> {
+    Ok(db
+        .query_results(run_id, &ResultFilter::default())?
+        .into_iter()
+        .filter_map(|r| {
+            let json = r.generated_workflow_json?;
+            Some((
+                r.test_prompt_id,
+                (
+                    r.task_run_id.unwrap_or_default(),
+                    r.workflow_id.unwrap_or_default(),
+                    json,
+                ),
+            ))
+        })
+        .collect())
+}
+
+/// Generate (or reuse `prior`) and score a single prompt, recording the
+/// result row.
+#[allow(clippy::too_many_arguments)]
+async fn evaluate_prompt(
+    db: &EvalDb,
+    state: &SharedState,
+    judge: &JudgeModel,
+    template: &JudgeTemplate,
+    progress_tx: &broadcast::Sender<EvalProgressEvent>,
+    run_id: &str,
+    test_prompt: &TestPrompt,
+    prior: Option<PriorWorkflow>,
+    concurrent: bool,
+    generation: Option<&MatrixModel>,
+) {
+    let result_started = Utc::now().to_rfc3339();
+
+    // Generate workflow
+    let (gen_result, gen_duration) = match prior {
+        Some(workflow) => (Ok(workflow), None),
+        None => {
+            let gen_start = std::time::Instant::now();
+            let result = generate_workflow_for_eval(
+                &state.http_client,
+                &test_prompt.prompt,
+                concurrent,
+                generation,
+            )
+            .await;
+            (result, Some(gen_start.elapsed().as_millis() as i64))
+        }
+    };
+    progress::emit(
+        progress_tx,
+        EvalProgressEvent::GenerationFinished {
+            run_id: run_id.to_string(),
+            test_prompt_id: test_prompt.id.clone(),
+            duration_ms: gen_duration,
+            error: gen_result.as_ref().err().map(|e| e.to_string()),
+        },
+    );
+
+    match gen_result {
+        Ok((task_run_id, workflow_id, workflow_json)) => {
+            // Objective metrics against the reference, independent of the judge
+            let structural_metrics = match test_prompt.ground_truth_json.as_deref() {
+                Some(gt) => match super::structural::score(&workflow_json, gt) {
+                    Ok(m) => Some(m),
+                    Err(e) => {
+                        warn!("Structural scoring failed for '{}': {}", test_prompt.id, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            // Score the workflow
+            let score_start = std::time::Instant::now();
+            let score_result =
+                super::judge::score_workflow(judge, template, test_prompt, &workflow_json).await;
+            let score_duration = score_start.elapsed().as_millis() as i64;
+            let scored = EvalProgressEvent::ScoringFinished {
+                run_id: run_id.to_string(),
+                test_prompt_id: test_prompt.id.clone(),
+                duration_ms: score_duration,
+                overall_score: score_result.as_ref().ok().map(|s| s.overall()),
+                scores: score_result.as_ref().ok().cloned(),
+                structural_f1: structural_metrics.as_ref().and_then(|m| m.f1),
+                error: score_result.as_ref().err().map(|e| e.to_string()),
+            };
+
+            let result = match score_result {
+                Ok(scores) => EvalResult {
+                    id: 0,
+                    run_id: run_id.to_string(),
+                    test_prompt_id: test_prompt.id.clone(),
+                    generated_workflow_json: Some(workflow_json),
+                    task_run_id: Some(task_run_id),
+                    workflow_id: Some(workflow_id),
+                    structural_correctness: Some(scores.structural_correctness.score),
+                    command_accuracy: Some(scores.command_accuracy.score),
+                    phase_flow_logic: Some(scores.phase_flow_logic.score),
+                    step_completeness: Some(scores.step_completeness.score),
+                    prompt_quality: Some(scores.prompt_quality.score),
+                    determinism: Some(scores.determinism.score),
+                    overall_score: Some(scores.overall()),
+                    score_rationales: serde_json::to_string(&scores).ok(),
+                    structural_metrics,
+                    generation_error: None,
+                    scoring_error: None,
+                    generation_duration_ms: gen_duration,
+                    scoring_duration_ms: Some(score_duration),
+                    score_override: None,
+                    started_at: result_started,
+                    completed_at: Some(Utc::now().to_rfc3339()),
+                },
+                Err(e) => {
+                    warn!("Scoring failed for '{}': {}", test_prompt.id, e);
+                    EvalResult {
+                        id: 0,
+                        run_id: run_id.to_string(),
+                        test_prompt_id: test_prompt.id.clone(),
+                        generated_workflow_json: Some(workflow_json),
+                        task_run_id: Some(task_run_id),
+                        workflow_id: Some(workflow_id),
+                        structural_correctness: None,
+                        command_accuracy: None,
+                        phase_flow_logic: None,
+                        step_completeness: None,
+                        prompt_quality: None,
+                        determinism: None,
+                        overall_score: None,
+                        score_rationales: None,
+                        structural_metrics,
+                        generation_error: None,
+                        scoring_error: Some(e.to_string()),
+                        generation_duration_ms: gen_duration,
+                        scoring_duration_ms: Some(score_duration),
+                        score_override: None,
+                        started_at: result_started,
+                        completed_at: Some(Utc::now().to_rfc3339()),
+                    }
+                }
+            };
+
+            let _ = db.insert_eval_result(&result);
+            progress::emit(progress_tx, scored);
+        }
+        Err(e) => {
+            warn!("Generation failed for '{}': {}", test_prompt.id, e);
+            let result = EvalResult {
+                id: 0,
+                run_id: run_id.to_string(),
+                test_prompt_id: test_prompt.id.clone(),
+                generated_workflow_json: None,
+                task_run_id: None,
+                workflow_id: None,
+                structural_correctness: None,
+                command_accuracy: None,
+                phase_flow_logic: None,
+                step_completeness: None,
+                prompt_quality: None,
+                determinism: None,
+                overall_score: None,
+                score_rationales: None,
+                structural_metrics: None,
+                generation_error: Some(e.to_string()),
+                scoring_error: None,
+                generation_duration_ms: gen_duration,
+                scoring_duration_ms: None,
+                score_override: None,
+                started_at: result_started,
+                completed_at: Some(Utc::now().to_rfc3339()),
+            };
+            let _ = db.insert_eval_result(&result);
+        }
+    }
+}
+
+/// Run a single evaluation pass over all enabled test prompts, evaluating up
+/// to `opts.concurrency` prompts at once.
+///
+/// Callers reserve the engine (`running`) before spawning this; it is
+/// released when the pass ends, including when it bails out early.
 pub async fn run_eval(
     db: Arc<EvalDb>,
     state: SharedState,
-    prompt_ids: Option<Vec<String>>,
+    opts: EvalRunOptions,
+    stop_rx: watch::Receiver<bool>,
+) {
+    run_eval_pass(db, state.clone(), opts, stop_rx).await;
+
+    // Clear in-memory state
+    let mut eval = state.evaluation.write().await;
+    eval.running = false;
+    eval.current_run_id = None;
+    eval.current_prompt_index = 0;
+    eval.total_prompts = 0;
+}
+
+async fn run_eval_pass(
+    db: Arc<EvalDb>,
+    state: SharedState,
+    opts: EvalRunOptions,
     stop_rx: watch::Receiver<bool>,
 ) {
-    let run_id = uuid::Uuid::new_v4().to_string();
+    let run_id = opts
+        .resume
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    // A rescore judges the source run's workflows, so it covers exactly the
+    // prompts that produced one — including ones disabled since.
+    let mut prior = match &opts.rescore_of {
+        Some(source) => match prior_workflows(&db, source) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                error!("Failed to load workflows of run {}: {}", source, e);
+                return;
+            }
+        },
+        None => None,
+    };
 
     // Load test prompts
-    let prompts = match db.list_enabled_test_prompts() {
+    let prompts = match &prior {
+        Some(p) => db
+            .list_test_prompts()
+            .map(|all| all.into_iter().filter(|t| p.contains_key(&t.id)).collect()),
+        None => db.list_enabled_test_prompts(),
+    };
+    let prompts: Vec<TestPrompt> = match prompts {
         Ok(p) => p,
         Err(e) => {
             error!("Failed to load test prompts: {}", e);
@@ -300,8 +654,19 @@ pub async fn run_eval(
         }
     };
 
-    // Filter to requested prompt IDs if specified
-    let prompts: Vec<_> = if let Some(ref ids) = prompt_ids {
+    // Filter to requested prompt IDs if specified; a resumed run sticks to
+    // the prompts it was started with.
+    let planned = match &opts.resume {
+        Some(id) => match db.planned_prompts(id) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to load planned prompts of run {}: {}", id, e);
+                return;
+            }
+        },
+        None => opts.prompt_ids.clone(),
+    };
+    let prompts: Vec<_> = if let Some(ref ids) = planned {
         prompts
             .into_iter()
             .filter(|p| ids.contains(&p.id))
@@ -316,11 +681,43 @@ pub async fn run_eval(
     }
 
     let total = prompts.len() as i64;
+    let samples = opts
+        .samples_per_prompt
+        .clamp(1, super::determinism::MAX_SAMPLES_PER_PROMPT) as usize;
+    // On resume, only the samples a prompt is still missing.
+    let recorded = match &opts.resume {
+        Some(id) => match db.result_counts(id) {
+            Ok(counts) => counts,
+            Err(e) => {
+                error!("Failed to load results of run {}: {}", id, e);
+                return;
+            }
+        },
+        None => HashMap::new(),
+    };
+    let missing =
+        |p: &TestPrompt| samples.saturating_sub(recorded.get(&p.id).copied().unwrap_or(0));
+    let already_done = prompts.iter().filter(|p| missing(p) == 0).count();
+    let judge = JudgeModel::resolve(&state, opts.judge_provider, opts.judge_model).await;
+    let generation = match (opts.generation_provider, opts.generation_model) {
+        (Some(provider), Some(model)) => Some(MatrixModel { provider, model }),
+        _ => None,
+    };
+    let template = Arc::new(resolve_judge_template(&db, opts.judge_template_version));
 
     // Create run record
     let run = EvalRunSummary {
         id: run_id.clone(),
-        mode: "on_demand".to_string(),
+        mode: if opts.retry_of.is_some() {
+            "retry"
+        } else if opts.rescore_of.is_some() {
+            "rescore"
+        } else if opts.matrix_id.is_some() {
+            "matrix"
+        } else {
+            "on_demand"
+        }
+        .to_string(),
         status: "running".to_string(),
         prompts_total: total,
         prompts_completed: 0,
@@ -350,21 +747,55 @@ pub async fn run_eval(
         error: None,
         started_at: Utc::now().to_rfc3339(),
         completed_at: None,
+        judge_provider: Some(judge.provider.clone()),
+        judge_model: Some(judge.model.clone()),
+        parent_run_id: opts.retry_of.clone().or_else(|| opts.rescore_of.clone()),
+        judge_template_version: Some(template.version),
+        matrix_id: opts.matrix_id.clone(),
+        overridden_count: None,
+        samples_per_prompt: Some(samples as i64),
+        generation_provider: generation.as_ref().map(|g| g.provider.clone()),
+        generation_model: generation.as_ref().map(|g| g.model.clone()),
+        category_scores: Vec::new(),
     };
 
-    if let Err(e) = db.insert_eval_run(&run) {
-        error!("Failed to create eval run: {}", e);
-        return;
+    if opts.resume.is_some() {
+        match db.reopen_eval_run(&run_id) {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!("Eval run {} is not interrupted or aborted", run_id);
+                return;
+            }
+            Err(e) => {
+                error!("Failed to reopen eval run {}: {}", run_id, e);
+                return;
+            }
+        }
+    } else {
+        if let Err(e) = db.insert_eval_run(&run) {
+            error!("Failed to create eval run: {}", e);
+            return;
+        }
+        let ids: Vec<String> = prompts.iter().map(|p| p.id.clone()).collect();
+        if let Err(e) = db.set_planned_prompts(&run_id, &ids) {
+            warn!("Failed to record planned prompts: {}", e);
+        }
+
+        let environment = crate::run_environment::capture(&state).await;
+        if let Err(e) = db.set_run_environment(&run_id, &environment) {
+            warn!("Failed to record eval run environment: {}", e);
+        }
     }
 
     // Update in-memory state
-    {
+    let progress_tx = {
         let mut eval = state.evaluation.write().await;
         eval.running = true;
         eval.current_run_id = Some(run_id.clone());
         eval.current_prompt_index = 0;
         eval.total_prompts = prompts.len();
-    }
+        eval.progress_tx.clone()
+    };
 
     state
         .logs
@@ -372,20 +803,42 @@ pub async fn run_eval(
             LogSource::Supervisor,
             LogLevel::Info,
             format!(
-                "Eval benchmark started: run_id={}, prompts={}",
+                "Eval benchmark {}: run_id={}, prompts={}, judge={}/{}, template=v{}",
+                if opts.resume.is_some() {
+                    "resumed"
+                } else {
+                    "started"
+                },
                 run_id,
-                prompts.len()
+                prompts.len() - already_done,
+                judge.provider,
+                judge.model,
+                template.version
             ),
         )
         .await;
 
-    let http_client = state.http_client.clone();
+    let concurrency = opts.concurrency.clamp(1, MAX_EVAL_CONCURRENCY);
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let completed = Arc::new(AtomicI64::new(already_done as i64));
+    let mut in_flight = Vec::with_capacity(prompts.len() - already_done);
 
     for (i, test_prompt) in prompts.iter().enumerate() {
+        let sample_count = missing(test_prompt);
+        if sample_count == 0 {
+            continue;
+        }
+
+        // Wait for a free slot before checking cancellation, so a stop that
+        // arrives while all slots are busy still prevents the next launch.
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(permit) => permit,
+            Err(_) => break,
+        };
+
         // Check for cancellation
         if *stop_rx.borrow() {
             info!("Eval run cancelled at prompt {}/{}", i, prompts.len());
-            let _ = db.complete_eval_run(&run_id, "cancelled", None);
             break;
         }
 
@@ -395,127 +848,134 @@ pub async fn run_eval(
             eval.current_prompt_index = i;
         }
 
-        let result_started = Utc::now().to_rfc3339();
-        let gen_start = std::time::Instant::now();
-
         info!(
             "Evaluating prompt {}/{}: '{}'",
             i + 1,
             prompts.len(),
             test_prompt.id
         );
+        progress::emit(
+            &progress_tx,
+            EvalProgressEvent::PromptStarted {
+                run_id: run_id.clone(),
+                test_prompt_id: test_prompt.id.clone(),
+                index: i,
+                total: prompts.len(),
+            },
+        );
 
-        // Generate workflow
-        let gen_result = generate_workflow_for_eval(&http_client, &test_prompt.prompt).await;
-
-        let gen_duration = gen_start.elapsed().as_millis() as i64;
+        let db = db.clone();
+        let state = state.clone();
+        let run_id = run_id.clone();
+        let judge = judge.clone();
+        let template = template.clone();
+        let progress_tx = progress_tx.clone();
+        let test_prompt = test_prompt.clone();
+        let workflow = prior.as_mut().and_then(|p| p.remove(&test_prompt.id));
+        let completed = completed.clone();
+        let stop_rx = stop_rx.clone();
+        let generation = generation.clone();
+        in_flight.push(tokio::spawn(async move {
+            for sample in 0..sample_count {
+                if sample > 0 && *stop_rx.borrow() {
+                    break;
+                }
+                evaluate_prompt(
+                    &db,
+                    &state,
+                    &judge,
+                    &template,
+                    &progress_tx,
+                    &run_id,
+                    &test_prompt,
+                    workflow.clone(),
+                    concurrency > 1,
+                    generation.as_ref(),
+                )
+                .await;
+            }
+            drop(permit);
 
-        match gen_result {
-            Ok((task_run_id, workflow_id, workflow_json)) => {
-                // Score the workflow
-                let score_start = std::time::Instant::now();
-                let score_result =
-                    super::judge::score_workflow(&state, test_prompt, &workflow_json).await;
-                let score_duration = score_start.elapsed().as_millis() as i64;
+            // Update progress in DB
+            let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+            let _ = db.update_eval_run_progress(&run_id, done);
+        }));
+    }
 
-                let result = match score_result {
-                    Ok(scores) => EvalResult {
-                        id: 0,
-                        run_id: run_id.clone(),
-                        test_prompt_id: test_prompt.id.clone(),
-                        generated_workflow_json: Some(workflow_json),
-                        task_run_id: Some(task_run_id),
-                        workflow_id: Some(workflow_id),
-                        structural_correctness: Some(scores.structural_correctness.score),
-                        command_accuracy: Some(scores.command_accuracy.score),
-                        phase_flow_logic: Some(scores.phase_flow_logic.score),
-                        step_completeness: Some(scores.step_completeness.score),
-                        prompt_quality: Some(scores.prompt_quality.score),
-                        determinism: Some(scores.determinism.score),
-                        overall_score: Some(scores.overall()),
-                        score_rationales: serde_json::to_string(&scores).ok(),
-                        generation_error: None,
-                        scoring_error: None,
-                        generation_duration_ms: Some(gen_duration),
-                        scoring_duration_ms: Some(score_duration),
-                        started_at: result_started,
-                        completed_at: Some(Utc::now().to_rfc3339()),
-                    },
-                    Err(e) => {
-                        warn!("Scoring failed for '{}': {}", test_prompt.id, e);
-                        EvalResult {
-                            id: 0,
-                            run_id: run_id.clone(),
-                            test_prompt_id: test_prompt.id.clone(),
-                            generated_workflow_json: Some(workflow_json),
-                            task_run_id: Some(task_run_id),
-                            workflow_id: Some(workflow_id),
-                            structural_correctness: None,
-                            command_accuracy: None,
-                            phase_flow_logic: None,
-                            step_completeness: None,
-                            prompt_quality: None,
-                            determinism: None,
-                            overall_score: None,
-                            score_rationales: None,
-                            generation_error: None,
-                            scoring_error: Some(e.to_string()),
-                            generation_duration_ms: Some(gen_duration),
-                            scoring_duration_ms: Some(score_duration),
-                            started_at: result_started,
-                            completed_at: Some(Utc::now().to_rfc3339()),
-                        }
-                    }
-                };
+    // Let prompts already in flight finish (and record their results) even
+    // when cancelled — the run's aggregates are computed from what landed.
+    for handle in in_flight {
+        if let Err(e) = handle.await {
+            error!("Eval prompt task failed: {}", e);
+        }
+    }
 
-                let _ = db.insert_eval_result(&result);
+    // Complete the run. A stop that arrives during the last prompt still
+    // lands here, so the status is decided after the loop rather than at
+    // the cancellation check.
+    let status = if *stop_rx.borrow() {
+        let abort_reason = state.evaluation.write().await.abort_reason.take();
+        match abort_reason {
+            Some(reason) => {
+                warn!("Eval run {} aborted: {}", run_id, reason);
+                let _ = db.complete_eval_run(&run_id, "aborted", Some(&reason));
+                "aborted"
             }
-            Err(e) => {
-                warn!("Generation failed for '{}': {}", test_prompt.id, e);
-                let result = EvalResult {
-                    id: 0,
-                    run_id: run_id.clone(),
-                    test_prompt_id: test_prompt.id.clone(),
-                    generated_workflow_json: None,
-                    task_run_id: None,
-                    workflow_id: None,
-                    structural_correctness: None,
-                    command_accuracy: None,
-                    phase_flow_logic: None,
-                    step_completeness: None,
-                    prompt_quality: None,
-                    determinism: None,
-                    overall_score: None,
-                    score_rationales: None,
-                    generation_error: Some(e.to_string()),
-                    scoring_error: None,
-                    generation_duration_ms: Some(gen_duration),
-                    scoring_duration_ms: None,
-                    started_at: result_started,
-                    completed_at: Some(Utc::now().to_rfc3339()),
-                };
-                let _ = db.insert_eval_result(&result);
+            None => {
+                let _ = db.complete_eval_run(&run_id, "cancelled", None);
+                "cancelled"
             }
         }
-
-        // Update progress in DB
-        let _ = db.update_eval_run_progress(&run_id, (i + 1) as i64);
-    }
-
-    // Complete the run (unless it was cancelled above)
-    if !*stop_rx.borrow() {
+    } else {
         let _ = db.complete_eval_run(&run_id, "completed", None);
-    }
+        "completed"
+    };
 
-    // Clear in-memory state
-    {
-        let mut eval = state.evaluation.write().await;
-        eval.running = false;
-        eval.current_run_id = None;
-        eval.current_prompt_index = 0;
-        eval.total_prompts = 0;
+    // Successful retries count toward the parent even if the retry run was
+    // stopped part-way.
+    if let Some(parent_id) = &opts.retry_of {
+        match db.merge_retry_results(parent_id, &run_id) {
+            Ok(n) => info!(
+                "Merged {} retried result(s) from {} into run {}",
+                n, run_id, parent_id
+            ),
+            Err(e) => warn!("Failed to merge retry results into {}: {}", parent_id, e),
+        }
+    } else if opts.rescore_of.is_none() && !*stop_rx.borrow() {
+        // A rescore measures the judge, not the generator, so it neither
+        // feeds the regression alert nor flags prompts as saturated.
+        match db.get_eval_run(&run_id) {
+            Ok(Some(run)) => {
+                state.evaluation.write().await.last_completed_score = run.avg_overall_score
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to reload completed run {}: {}", run_id, e),
+        }
+        super::baseline::alert_on_regression(&db, &state, &run_id).await;
+        match db.flag_saturated_prompts(&run_id, SATURATION_WINDOW, SATURATION_MIN_SCORE) {
+            Ok(0) => {}
+            Ok(n) => info!(
+                "Flagged {} test prompt(s) for review: last {} scores all >= {}",
+                n, SATURATION_WINDOW, SATURATION_MIN_SCORE
+            ),
+            Err(e) => warn!("Failed to flag saturated prompts: {}", e),
+        }
     }
 
+    let avg_overall_score = match db.get_eval_run(&run_id) {
+        Ok(run) => run.and_then(|r| r.avg_overall_score),
+        Err(_) => None,
+    };
+    progress::emit(
+        &progress_tx,
+        EvalProgressEvent::RunCompleted {
+            run_id: run_id.clone(),
+            status: status.to_string(),
+            prompts_completed: completed.load(Ordering::SeqCst),
+            avg_overall_score,
+        },
+    );
+
     state
         .logs
         .emit(
@@ -526,11 +986,73 @@ pub async fn run_eval(
         .await;
 }
 
+/// Run the same prompts once per provider/model pair, one run at a time,
+/// each generated by its pair and all scored by the same judge (resolved
+/// once, so a change to the global AI settings mid-matrix can't skew it).
+/// The runs share `matrix_id`; a stop ends the current run and skips the
+/// rest.
+pub async fn run_matrix(
+    db: Arc<EvalDb>,
+    state: SharedState,
+    matrix_id: String,
+    models: Vec<MatrixModel>,
+    opts: EvalRunOptions,
+    stop_rx: watch::Receiver<bool>,
+) {
+    state.evaluation.write().await.matrix_id = Some(matrix_id.clone());
+    let judge = JudgeModel::resolve(
+        &state,
+        opts.judge_provider.clone(),
+        opts.judge_model.clone(),
+    )
+    .await;
+    info!(
+        "Starting model matrix {} over {} model(s), judged by {}/{}",
+        matrix_id,
+        models.len(),
+        judge.provider,
+        judge.model
+    );
+
+    for (i, model) in models.into_iter().enumerate() {
+        if *stop_rx.borrow() {
+            info!("Model matrix {} stopped after {} run(s)", matrix_id, i);
+            break;
+        }
+        state
+            .logs
+            .emit(
+                LogSource::Supervisor,
+                LogLevel::Info,
+                format!(
+                    "Model matrix {}: run {} with {}/{}",
+                    matrix_id,
+                    i + 1,
+                    model.provider,
+                    model.model
+                ),
+            )
+            .await;
+        let run_opts = EvalRunOptions {
+            judge_provider: Some(judge.provider.clone()),
+            judge_model: Some(judge.model.clone()),
+            generation_provider: Some(model.provider),
+            generation_model: Some(model.model),
+            matrix_id: Some(matrix_id.clone()),
+            ..opts.clone()
+        };
+        run_eval(db.clone(), state.clone(), run_opts, stop_rx.clone()).await;
+    }
+
+    state.evaluation.write().await.matrix_id = None;
+}
+
 /// Run eval in a continuous loop with configurable interval.
 pub async fn run_continuous(
     db: Arc<EvalDb>,
     state: SharedState,
     interval_secs: u64,
+    opts: EvalRunOptions,
     stop_rx: watch::Receiver<bool>,
 ) {
     info!(
@@ -546,7 +1068,7 @@ pub async fn run_continuous(
 
         // Run one eval pass
         let inner_stop_rx = stop_rx.clone();
-        run_eval(db.clone(), state.clone(), None, inner_stop_rx).await;
+        run_eval(db.clone(), state.clone(), opts.clone(), inner_stop_rx).await;
 
         // Sleep for interval, checking stop signal
         let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(interval_secs);
@@ -0,0 +1,277 @@
+//! Flat per-result export of an eval run (`GET /eval/runs/{id}/export`).
+//!
+//! One row per result with scores, durations, errors and the judge's
+//! per-dimension rationales, as CSV for spreadsheets or JSON Lines for
+//! analysis tooling. Generated workflow JSON is left out.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{EvalResult, ScoreResponse};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportRow {
+    pub run_id: String,
+    pub result_id: i64,
+    pub test_prompt_id: String,
+    pub category: Option<String>,
+    pub overall_score: Option<f64>,
+    pub structural_correctness: Option<i64>,
+    pub command_accuracy: Option<i64>,
+    pub phase_flow_logic: Option<i64>,
+    pub step_completeness: Option<i64>,
+    pub prompt_quality: Option<i64>,
+    pub determinism: Option<i64>,
+    /// Mean F1 against the ground truth, for prompts that have one.
+    pub structural_f1: Option<f64>,
+    pub generation_duration_ms: Option<i64>,
+    pub scoring_duration_ms: Option<i64>,
+    pub generation_error: Option<String>,
+    pub scoring_error: Option<String>,
+    pub structural_correctness_rationale: Option<String>,
+    pub command_accuracy_rationale: Option<String>,
+    pub phase_flow_logic_rationale: Option<String>,
+    pub step_completeness_rationale: Option<String>,
+    pub prompt_quality_rationale: Option<String>,
+    pub determinism_rationale: Option<String>,
+    pub task_run_id: Option<String>,
+    pub workflow_id: Option<String>,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+}
+
+const CSV_HEADER: [&str; 26] = [
+    "run_id",
+    "result_id",
+    "test_prompt_id",
+    "category",
+    "overall_score",
+    "structural_correctness",
+    "command_accuracy",
+    "phase_flow_logic",
+    "step_completeness",
+    "prompt_quality",
+    "determinism",
+    "structural_f1",
+    "generation_duration_ms",
+    "scoring_duration_ms",
+    "generation_error",
+    "scoring_error",
+    "structural_correctness_rationale",
+    "command_accuracy_rationale",
+    "phase_flow_logic_rationale",
+    "step_completeness_rationale",
+    "prompt_quality_rationale",
+    "determinism_rationale",
+    "task_run_id",
+    "workflow_id",
+    "started_at",
+    "completed_at",
+];
+
+/// Flatten results; `categories` maps prompt ID to its current category.
+pub fn rows(results: Vec<EvalResult>, categories: &HashMap<String, String>) -> Vec<ExportRow> {
+    results
+        .into_iter()
+        .map(|r| {
+            let rationales: Option<ScoreResponse> = r
+                .score_rationales
+                .as_deref()
+                .and_then(|s| serde_json::from_str(s).ok());
+            let rationale =
+                |pick: fn(&ScoreResponse) -> &str| rationales.as_ref().map(|s| pick(s).to_string());
+            ExportRow {
+                category: categories.get(&r.test_prompt_id).cloned(),
+                overall_score: r.overall_score,
+                structural_correctness: r.structural_correctness,
+                command_accuracy: r.command_accuracy,
+                phase_flow_logic: r.phase_flow_logic,
+                step_completeness: r.step_completeness,
+                prompt_quality: r.prompt_quality,
+                determinism: r.determinism,
+                structural_f1: r.structural_metrics.as_ref().and_then(|m| m.f1),
+                generation_duration_ms: r.generation_duration_ms,
+                scoring_duration_ms: r.scoring_duration_ms,
+                structural_correctness_rationale: rationale(|s| {
+                    s.structural_correctness.rationale.as_str()
+                }),
+                command_accuracy_rationale: rationale(|s| s.command_accuracy.rationale.as_str()),
+                phase_flow_logic_rationale: rationale(|s| s.phase_flow_logic.rationale.as_str()),
+                step_completeness_rationale: rationale(|s| s.step_completeness.rationale.as_str()),
+                prompt_quality_rationale: rationale(|s| s.prompt_quality.rationale.as_str()),
+                determinism_rationale: rationale(|s| s.determinism.rationale.as_str()),
+                run_id: r.run_id,
+                result_id: r.id,
+                test_prompt_id: r.test_prompt_id,
+                generation_error: r.generation_error,
+                scoring_error: r.scoring_error,
+                task_run_id: r.task_run_id,
+                workflow_id: r.workflow_id,
+                started_at: r.started_at,
+                completed_at: r.completed_at,
+            }
+        })
+        .collect()
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+pub fn to_csv(rows: &[ExportRow]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push_str("\r\n");
+    for r in rows {
+        let fields = [
+            r.run_id.clone(),
+            r.result_id.to_string(),
+            r.test_prompt_id.clone(),
+            opt(&r.category),
+            opt(&r.overall_score),
+            opt(&r.structural_correctness),
+            opt(&r.command_accuracy),
+            opt(&r.phase_flow_logic),
+            opt(&r.step_completeness),
+            opt(&r.prompt_quality),
+            opt(&r.determinism),
+            opt(&r.structural_f1),
+            opt(&r.generation_duration_ms),
+            opt(&r.scoring_duration_ms),
+            opt(&r.generation_error),
+            opt(&r.scoring_error),
+            opt(&r.structural_correctness_rationale),
+            opt(&r.command_accuracy_rationale),
+            opt(&r.phase_flow_logic_rationale),
+            opt(&r.step_completeness_rationale),
+            opt(&r.prompt_quality_rationale),
+            opt(&r.determinism_rationale),
+            opt(&r.task_run_id),
+            opt(&r.workflow_id),
+            r.started_at.clone(),
+            opt(&r.completed_at),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+pub fn to_jsonl(rows: &[ExportRow]) -> String {
+    let mut out = String::new();
+    for r in rows {
+        if let Ok(line) = serde_json::to_string(r) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+pub fn render(format: ExportFormat, rows: &[ExportRow]) -> String {
+    match format {
+        ExportFormat::Csv => to_csv(rows),
+        ExportFormat::Jsonl => to_jsonl(rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(rationales: Option<&str>, error: Option<&str>) -> EvalResult {
+        EvalResult {
+            id: 7,
+            run_id: "run-1".to_string(),
+            test_prompt_id: "p1".to_string(),
+            generated_workflow_json: None,
+            task_run_id: None,
+            workflow_id: None,
+            structural_correctness: Some(4),
+            command_accuracy: None,
+            phase_flow_logic: None,
+            step_completeness: None,
+            prompt_quality: None,
+            determinism: None,
+            overall_score: Some(4.0),
+            score_rationales: rationales.map(str::to_string),
+            structural_metrics: None,
+            generation_error: error.map(str::to_string),
+            scoring_error: None,
+            generation_duration_ms: Some(1200),
+            scoring_duration_ms: None,
+            score_override: None,
+            started_at: "2026-03-01T00:00:00Z".to_string(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_delimiters() {
+        let categories = HashMap::from([("p1".to_string(), "navigation".to_string())]);
+        let rows = rows(
+            vec![result(None, Some("runner said \"no\",\nthen died"))],
+            &categories,
+        );
+        let csv = to_csv(&rows);
+        let mut lines = csv.split("\r\n");
+        assert_eq!(lines.next().unwrap().split(',').count(), CSV_HEADER.len());
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("run-1,7,p1,navigation,4,4,,,,,,,1200,,"));
+        assert!(row.contains("\"runner said \"\"no\"\",\nthen died\""));
+    }
+
+    #[test]
+    fn jsonl_carries_parsed_rationales() {
+        let rationale = |r: &str| format!("{{\"score\": 4, \"rationale\": \"{}\"}}", r);
+        let json = format!(
+            "{{\"structural_correctness\": {}, \"command_accuracy\": {}, \"phase_flow_logic\": {}, \
+             \"step_completeness\": {}, \"prompt_quality\": {}, \"determinism\": {}}}",
+            rationale("valid ids"),
+            rationale("b"),
+            rationale("c"),
+            rationale("d"),
+            rationale("e"),
+            rationale("f")
+        );
+        let rows = rows(vec![result(Some(&json), None)], &HashMap::new());
+        let out = to_jsonl(&rows);
+        assert_eq!(out.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(out.trim_end()).unwrap();
+        assert_eq!(value["structural_correctness_rationale"], "valid ids");
+        assert_eq!(value["category"], serde_json::Value::Null);
+    }
+}
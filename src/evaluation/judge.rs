@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::process::Stdio;
 use tracing::{info, warn};
 
@@ -11,25 +12,11 @@ Your ONLY job is to read the scoring rubric and respond with EXACTLY the JSON ob
 Do NOT describe the task, do NOT explain the rubric, do NOT add any text before or after the JSON. \
 Respond with ONLY the JSON object.";
 
-/// Build the scoring prompt for the LLM judge.
-/// When ground truth is available, uses diff-based comparison.
-/// Otherwise, uses generic quality rubric.
-pub fn build_scoring_prompt(test_prompt: &TestPrompt, workflow_json: &str) -> String {
-    if let Some(ref ground_truth) = test_prompt.ground_truth_json {
-        build_ground_truth_prompt(test_prompt, workflow_json, ground_truth)
-    } else {
-        build_generic_prompt(test_prompt, workflow_json)
-    }
-}
+/// Version number of the built-in rubric when seeded into `judge_prompts`.
+pub const BUILTIN_TEMPLATE_VERSION: i64 = 1;
 
 /// Diff-based scoring: compare generated workflow against known-good reference.
-fn build_ground_truth_prompt(
-    test_prompt: &TestPrompt,
-    workflow_json: &str,
-    ground_truth: &str,
-) -> String {
-    format!(
-        r#"You are an expert workflow quality evaluator. Compare the generated workflow against the known-correct reference workflow and score how closely it matches.
+const GROUND_TRUTH_TEMPLATE: &str = r#"You are an expert workflow quality evaluator. Compare the generated workflow against the known-correct reference workflow and score how closely it matches.
 
 ## Original Prompt
 {prompt}
@@ -66,27 +53,10 @@ Score each dimension 1-5:
    5 = equally deterministic, 4 = one minor non-determinism added, 3 = some flaky steps, 2 = relies heavily on AI where reference is deterministic, 1 = non-reproducible
 
 Respond with ONLY valid JSON (no markdown fences, no extra text):
-{{"structural_correctness": {{"score": N, "rationale": "..."}}, "command_accuracy": {{"score": N, "rationale": "..."}}, "phase_flow_logic": {{"score": N, "rationale": "..."}}, "step_completeness": {{"score": N, "rationale": "..."}}, "prompt_quality": {{"score": N, "rationale": "..."}}, "determinism": {{"score": N, "rationale": "..."}}}}"#,
-        prompt = test_prompt.prompt,
-    )
-}
+{"structural_correctness": {"score": N, "rationale": "..."}, "command_accuracy": {"score": N, "rationale": "..."}, "phase_flow_logic": {"score": N, "rationale": "..."}, "step_completeness": {"score": N, "rationale": "..."}, "prompt_quality": {"score": N, "rationale": "..."}, "determinism": {"score": N, "rationale": "..."}}"#;
 
 /// Generic quality scoring without a reference workflow.
-fn build_generic_prompt(test_prompt: &TestPrompt, workflow_json: &str) -> String {
-    let expected_phases = test_prompt
-        .expected_phases
-        .as_ref()
-        .map(|v| format!("{:?}", v))
-        .unwrap_or_else(|| "not specified".to_string());
-
-    let expected_step_types = test_prompt
-        .expected_step_types
-        .as_ref()
-        .map(|v| format!("{:?}", v))
-        .unwrap_or_else(|| "not specified".to_string());
-
-    format!(
-        r#"You are an expert workflow quality evaluator. Score the following generated workflow.
+const GENERIC_TEMPLATE: &str = r#"You are an expert workflow quality evaluator. Score the following generated workflow.
 
 ## Original Prompt
 {prompt}
@@ -111,35 +81,207 @@ Score each dimension 1-5:
 6. determinism — Reproducible outcomes, no flaky steps
 
 Respond with ONLY valid JSON (no markdown fences, no extra text):
-{{"structural_correctness": {{"score": N, "rationale": "..."}}, "command_accuracy": {{"score": N, "rationale": "..."}}, "phase_flow_logic": {{"score": N, "rationale": "..."}}, "step_completeness": {{"score": N, "rationale": "..."}}, "prompt_quality": {{"score": N, "rationale": "..."}}, "determinism": {{"score": N, "rationale": "..."}}}}"#,
-        prompt = test_prompt.prompt,
-        category = test_prompt.category,
-        complexity = test_prompt.complexity,
-    )
+{"structural_correctness": {"score": N, "rationale": "..."}, "command_accuracy": {"score": N, "rationale": "..."}, "phase_flow_logic": {"score": N, "rationale": "..."}, "step_completeness": {"score": N, "rationale": "..."}, "prompt_quality": {"score": N, "rationale": "..."}, "determinism": {"score": N, "rationale": "..."}}"#;
+
+/// A versioned judge prompt, stored in `judge_prompts`. Templates are filled
+/// by [`render`] from `{prompt}`, `{workflow_json}`, `{ground_truth}`,
+/// `{category}`, `{complexity}`, `{expected_phases}` and
+/// `{expected_step_types}`; any other braces are left as written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JudgeTemplate {
+    #[serde(default)]
+    pub version: i64,
+    pub system_prompt: String,
+    /// Used for prompts with a reference workflow.
+    pub ground_truth_template: String,
+    pub generic_template: String,
+    pub notes: Option<String>,
+    /// The version new runs are scored with. Exactly one is active.
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+impl JudgeTemplate {
+    /// The rubric that shipped in code, seeded as version 1.
+    pub fn builtin() -> Self {
+        Self {
+            version: BUILTIN_TEMPLATE_VERSION,
+            system_prompt: JUDGE_SYSTEM_PROMPT.to_string(),
+            ground_truth_template: GROUND_TRUTH_TEMPLATE.to_string(),
+            generic_template: GENERIC_TEMPLATE.to_string(),
+            notes: Some("Built-in rubric".to_string()),
+            active: true,
+            created_at: String::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.system_prompt.trim().is_empty() {
+            return Err("system_prompt must not be empty".to_string());
+        }
+        for (name, template) in [
+            ("ground_truth_template", &self.ground_truth_template),
+            ("generic_template", &self.generic_template),
+        ] {
+            if !template.contains("{workflow_json}") {
+                return Err(format!("{} must contain {{workflow_json}}", name));
+            }
+        }
+        if !self.ground_truth_template.contains("{ground_truth}") {
+            return Err("ground_truth_template must contain {ground_truth}".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Substitute `{name}` placeholders in one pass, so placeholder-like text
+/// inside a substituted value (e.g. a prompt mentioning `{workflow_json}`)
+/// is never expanded.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| (close, *v))
+        });
+        match value {
+            Some((close, v)) => {
+                out.push_str(v);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Build the scoring prompt for the LLM judge.
+/// When ground truth is available, uses diff-based comparison.
+/// Otherwise, uses generic quality rubric.
+pub fn build_scoring_prompt(
+    template: &JudgeTemplate,
+    test_prompt: &TestPrompt,
+    workflow_json: &str,
+) -> String {
+    let expected_phases = test_prompt
+        .expected_phases
+        .as_ref()
+        .map(|v| format!("{:?}", v))
+        .unwrap_or_else(|| "not specified".to_string());
+
+    let expected_step_types = test_prompt
+        .expected_step_types
+        .as_ref()
+        .map(|v| format!("{:?}", v))
+        .unwrap_or_else(|| "not specified".to_string());
+
+    let vars = [
+        ("prompt", test_prompt.prompt.as_str()),
+        ("workflow_json", workflow_json),
+        (
+            "ground_truth",
+            test_prompt.ground_truth_json.as_deref().unwrap_or_default(),
+        ),
+        ("category", test_prompt.category.as_str()),
+        ("complexity", test_prompt.complexity.as_str()),
+        ("expected_phases", expected_phases.as_str()),
+        ("expected_step_types", expected_step_types.as_str()),
+    ];
+    if test_prompt.ground_truth_json.is_some() {
+        render(&template.ground_truth_template, &vars)
+    } else {
+        render(&template.generic_template, &vars)
+    }
+}
+
+/// Provider/model pair used to score a run.
+#[derive(Debug, Clone)]
+pub struct JudgeModel {
+    pub provider: String,
+    pub model: String,
+}
+
+impl JudgeModel {
+    /// The explicit override if one was given, else the supervisor's current
+    /// global AI settings.
+    pub async fn resolve(
+        state: &SharedState,
+        provider: Option<String>,
+        model: Option<String>,
+    ) -> Self {
+        match (provider, model) {
+            (Some(provider), Some(model)) => Self { provider, model },
+            _ => {
+                let ai = state.ai.read().await;
+                Self {
+                    provider: ai.provider.clone(),
+                    model: ai.model.clone(),
+                }
+            }
+        }
+    }
 }
 
 /// Score a workflow by spawning `claude --print` with a system prompt override.
 pub async fn score_workflow(
-    state: &SharedState,
+    judge: &JudgeModel,
+    template: &JudgeTemplate,
     test_prompt: &TestPrompt,
     workflow_json: &str,
 ) -> anyhow::Result<ScoreResponse> {
-    let prompt = build_scoring_prompt(test_prompt, workflow_json);
+    let prompt = build_scoring_prompt(template, test_prompt, workflow_json);
     let has_ground_truth = test_prompt.ground_truth_json.is_some();
 
-    // Resolve model from supervisor's AI state
-    let (provider, model_key) = {
-        let ai = state.ai.read().await;
-        (ai.provider.clone(), ai.model.clone())
-    };
-    let model_id =
-        resolve_model_id(&provider, &model_key).unwrap_or_else(|| "claude-opus-4-6".to_string());
-
     info!(
-        "Scoring workflow for prompt '{}' with {}/{} (ground_truth={})",
-        test_prompt.id, provider, model_key, has_ground_truth
+        "Scoring workflow for prompt '{}' with {}/{}, judge prompt v{} (ground_truth={})",
+        test_prompt.id, judge.provider, judge.model, template.version, has_ground_truth
     );
 
+    let output = run_model(
+        judge,
+        &template.system_prompt,
+        &prompt,
+        "qontinui-eval-scoring",
+    )
+    .await?;
+    parse_score_response(&output)
+}
+
+/// Prepend `system_prompt` to `prompt` for CLIs without a system-prompt flag.
+fn inline_system_prompt(system_prompt: &str, prompt: &str) -> String {
+    if system_prompt.trim().is_empty() {
+        prompt.to_string()
+    } else {
+        format!("{}\n\n{}", system_prompt.trim_end(), prompt)
+    }
+}
+
+/// Send `prompt` to `judge`'s CLI and return its text output. Providers that
+/// read the prompt from a file use per-call temp files named after
+/// `scratch_name`, removed afterwards; those have no system-prompt flag, so
+/// `system_prompt` is prepended to the prompt instead.
+pub async fn run_model(
+    judge: &JudgeModel,
+    system_prompt: &str,
+    prompt: &str,
+    scratch_name: &str,
+) -> anyhow::Result<String> {
+    let provider = judge.provider.clone();
+    let model_key = judge.model.clone();
+    let model_id =
+        resolve_model_id(&provider, &model_key).unwrap_or_else(|| "claude-opus-4-6".to_string());
+
     let temp_dir = std::env::temp_dir();
 
     let output = match provider.as_str() {
@@ -155,7 +297,7 @@ pub async fn score_workflow(
                 "--model",
                 &model_id,
                 "--system-prompt",
-                JUDGE_SYSTEM_PROMPT,
+                system_prompt,
                 "--tools",
                 "",
             ])
@@ -177,38 +319,45 @@ pub async fn score_workflow(
             String::from_utf8_lossy(&result.stdout).to_string()
         }
         "gemini" => {
-            let prompt_file = temp_dir.join("qontinui-eval-scoring-prompt.md");
-            tokio::fs::write(&prompt_file, &prompt).await?;
-
-            let script_path = temp_dir.join("qontinui-eval-scoring.ps1");
-            let script = format!(
-                "Get-Content -Raw '{}' | gemini --yolo -o text -m '{}'",
-                prompt_file.display(),
-                model_id,
-            );
-            tokio::fs::write(&script_path, &script).await?;
-
-            let mut cmd = tokio::process::Command::new("powershell.exe");
-            cmd.args([
-                "-ExecutionPolicy",
-                "Bypass",
-                "-File",
-                &script_path.display().to_string(),
-            ])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-            #[cfg(windows)]
-            cmd.creation_flags(0x0800_0000); // CREATE_NO_WINDOW
-            let child = cmd.output().await?;
+            // Concurrent scorings each get their own files.
+            let scratch = format!("{}-{}", scratch_name, uuid::Uuid::new_v4());
+            let prompt_file = temp_dir.join(format!("{}-prompt.md", scratch));
+            let script_path = temp_dir.join(format!("{}.ps1", scratch));
+
+            let result = async {
+                tokio::fs::write(&prompt_file, inline_system_prompt(system_prompt, prompt)).await?;
+                let script = format!(
+                    "Get-Content -Raw '{}' | gemini --yolo -o text -m '{}'",
+                    prompt_file.display(),
+                    model_id,
+                );
+                tokio::fs::write(&script_path, &script).await?;
+
+                let mut cmd = tokio::process::Command::new("powershell.exe");
+                cmd.args([
+                    "-ExecutionPolicy",
+                    "Bypass",
+                    "-File",
+                    &script_path.display().to_string(),
+                ])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+                #[cfg(windows)]
+                cmd.creation_flags(0x0800_0000); // CREATE_NO_WINDOW
+                cmd.output().await
+            }
+            .await;
+            let _ = tokio::fs::remove_file(&prompt_file).await;
+            let _ = tokio::fs::remove_file(&script_path).await;
 
-            String::from_utf8_lossy(&child.stdout).to_string()
+            String::from_utf8_lossy(&result?.stdout).to_string()
         }
         _ => {
             anyhow::bail!("Unsupported provider: {}", provider);
         }
     };
 
-    parse_score_response(&output)
+    Ok(output)
 }
 
 /// Parse the LLM's JSON response into a ScoreResponse.
@@ -243,6 +392,7 @@ pub fn parse_score_response(raw: &str) -> anyhow::Result<ScoreResponse> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::evaluation::PromptStatus;
 
     #[test]
     fn test_parse_valid_json() {
@@ -263,6 +413,35 @@ mod tests {
         assert_eq!(result.structural_correctness.score, 4);
     }
 
+    #[test]
+    fn system_prompt_is_inlined_for_file_based_providers() {
+        assert_eq!(
+            inline_system_prompt("Be strict.\n", "Score this."),
+            "Be strict.\n\nScore this."
+        );
+        assert_eq!(inline_system_prompt("  ", "Score this."), "Score this.");
+    }
+
+    #[test]
+    fn render_is_single_pass_and_keeps_unknown_braces() {
+        let out = render(
+            "{prompt} -> {workflow_json} {\"score\": N}",
+            &[
+                ("prompt", "mention {workflow_json}"),
+                ("workflow_json", "{}"),
+            ],
+        );
+        assert_eq!(out, "mention {workflow_json} -> {} {\"score\": N}");
+    }
+
+    #[test]
+    fn builtin_template_is_valid() {
+        assert!(JudgeTemplate::builtin().validate().is_ok());
+        let mut t = JudgeTemplate::builtin();
+        t.ground_truth_template = "no placeholders".to_string();
+        assert!(t.validate().is_err());
+    }
+
     #[test]
     fn test_ground_truth_prompt_used_when_available() {
         let prompt = TestPrompt {
@@ -275,10 +454,16 @@ mod tests {
             tags: None,
             ground_truth_json: Some(r#"{"name":"test"}"#.to_string()),
             enabled: true,
+            status: PromptStatus::Active,
+            needs_review: false,
             created_at: "2025-01-01T00:00:00Z".to_string(),
             updated_at: "2025-01-01T00:00:00Z".to_string(),
         };
-        let result = build_scoring_prompt(&prompt, r#"{"name":"generated"}"#);
+        let result = build_scoring_prompt(
+            &JudgeTemplate::builtin(),
+            &prompt,
+            r#"{"name":"generated"}"#,
+        );
         assert!(result.contains("Reference Workflow (Ground Truth)"));
         assert!(result.contains("Compare Against Reference"));
     }
@@ -295,10 +480,16 @@ mod tests {
             tags: None,
             ground_truth_json: None,
             enabled: true,
+            status: PromptStatus::Active,
+            needs_review: false,
             created_at: "2025-01-01T00:00:00Z".to_string(),
             updated_at: "2025-01-01T00:00:00Z".to_string(),
         };
-        let result = build_scoring_prompt(&prompt, r#"{"name":"generated"}"#);
+        let result = build_scoring_prompt(
+            &JudgeTemplate::builtin(),
+            &prompt,
+            r#"{"name":"generated"}"#,
+        );
         assert!(!result.contains("Ground Truth"));
         assert!(result.contains("Expected Characteristics"));
     }
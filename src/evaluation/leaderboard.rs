@@ -0,0 +1,230 @@
+//! Model matrix runs and the per-model leaderboard.
+//!
+//! `POST /eval/matrix/start` runs the same prompts once per provider/model
+//! pair, one run after another. Each pair is sent to the runner as the model
+//! to generate with, and every run is scored by the same judge, so the runs
+//! differ only in the generator. They share a `matrix_id`.
+//! `GET /eval/leaderboard` ranks the pairs of one matrix (the latest by
+//! default) by average overall score, and names each pair's strongest and
+//! weakest dimension plus the dimensions it leads the board in.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::db::EvalDb;
+
+/// Judge dimensions, in [`LeaderboardEntry::dimension_averages`] order.
+pub const DIMENSIONS: [&str; 6] = [
+    "structural_correctness",
+    "command_accuracy",
+    "phase_flow_logic",
+    "step_completeness",
+    "prompt_quality",
+    "determinism",
+];
+
+/// Upper bound on provider/model pairs in one matrix.
+pub const MAX_MATRIX_MODELS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatrixModel {
+    pub provider: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    /// 1-based; pairs without a scored result rank last.
+    pub rank: usize,
+    pub provider: String,
+    pub model: String,
+    pub runs: i64,
+    pub results_scored: i64,
+    pub avg_overall: Option<f64>,
+    /// Per-dimension averages, in [`DIMENSIONS`] order.
+    pub dimension_averages: Vec<Option<f64>>,
+    pub strongest_dimension: Option<String>,
+    pub weakest_dimension: Option<String>,
+    /// Dimensions where no other pair averages higher.
+    pub leads_in: Vec<String>,
+    pub last_run_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leaderboard {
+    /// `None` when no matrix run exists yet.
+    pub matrix_id: Option<String>,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// Leaderboard for `matrix_id`, or for the most recently started matrix.
+/// Only completed runs count.
+pub fn leaderboard(db: &EvalDb, matrix_id: Option<&str>) -> anyhow::Result<Leaderboard> {
+    let matrix_id = match matrix_id {
+        Some(id) => Some(id.to_string()),
+        None => latest_matrix_id(db)?,
+    };
+    let Some(matrix_id) = matrix_id else {
+        return Ok(Leaderboard {
+            matrix_id: None,
+            entries: Vec::new(),
+        });
+    };
+
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT r.generation_provider, r.generation_model, COUNT(DISTINCT r.id), COUNT(e.overall_score),
+                AVG(e.overall_score), AVG(e.structural_correctness), AVG(e.command_accuracy),
+                AVG(e.phase_flow_logic), AVG(e.step_completeness), AVG(e.prompt_quality),
+                AVG(e.determinism), MAX(r.started_at)
+         FROM eval_runs r LEFT JOIN eval_results e ON e.run_id = r.id
+         WHERE r.matrix_id = ?1 AND r.status = 'completed'
+           AND r.generation_provider IS NOT NULL AND r.generation_model IS NOT NULL
+         GROUP BY r.generation_provider, r.generation_model",
+    )?;
+    let rows = stmt.query_map(params![matrix_id], |row| {
+        Ok(LeaderboardEntry {
+            rank: 0,
+            provider: row.get(0)?,
+            model: row.get(1)?,
+            runs: row.get(2)?,
+            results_scored: row.get(3)?,
+            avg_overall: row.get(4)?,
+            dimension_averages: (5..11)
+                .map(|i| row.get(i))
+                .collect::<rusqlite::Result<_>>()?,
+            strongest_dimension: None,
+            weakest_dimension: None,
+            leads_in: Vec::new(),
+            last_run_at: row.get(11)?,
+        })
+    })?;
+    let mut entries = rows.collect::<Result<Vec<_>, _>>()?;
+    rank(&mut entries);
+    Ok(Leaderboard {
+        matrix_id: Some(matrix_id),
+        entries,
+    })
+}
+
+fn latest_matrix_id(db: &EvalDb) -> anyhow::Result<Option<String>> {
+    use rusqlite::OptionalExtension;
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT matrix_id FROM eval_runs WHERE matrix_id IS NOT NULL
+         ORDER BY started_at DESC LIMIT 1",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(Into::into)
+}
+
+/// Sort by average overall score (unscored last) and fill in ranks and
+/// per-dimension strengths.
+pub fn rank(entries: &mut [LeaderboardEntry]) {
+    entries.sort_by(|a, b| match (a.avg_overall, b.avg_overall) {
+        (Some(x), Some(y)) => y.total_cmp(&x),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+
+    let best: Vec<Option<f64>> = (0..DIMENSIONS.len())
+        .map(|d| {
+            entries
+                .iter()
+                .filter_map(|e| e.dimension_averages.get(d).copied().flatten())
+                .max_by(f64::total_cmp)
+        })
+        .collect();
+
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.rank = i + 1;
+        let scored: Vec<(usize, f64)> = entry
+            .dimension_averages
+            .iter()
+            .enumerate()
+            .filter_map(|(d, v)| v.map(|v| (d, v)))
+            .collect();
+        entry.strongest_dimension = scored
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(d, _)| DIMENSIONS[*d].to_string());
+        entry.weakest_dimension = scored
+            .iter()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(d, _)| DIMENSIONS[*d].to_string());
+        entry.leads_in = scored
+            .iter()
+            .filter(|(d, v)| best[*d] == Some(*v))
+            .map(|(d, _)| DIMENSIONS[*d].to_string())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(model: &str, overall: Option<f64>, dims: [f64; 6]) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank: 0,
+            provider: "claude".to_string(),
+            model: model.to_string(),
+            runs: 1,
+            results_scored: 1,
+            avg_overall: overall,
+            dimension_averages: dims.iter().map(|d| Some(*d)).collect(),
+            strongest_dimension: None,
+            weakest_dimension: None,
+            leads_in: Vec::new(),
+            last_run_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn ranks_by_overall_with_unscored_last() {
+        let mut entries = vec![
+            entry("none", None, [0.0; 6]),
+            entry("low", Some(3.0), [3.0; 6]),
+            entry("high", Some(4.5), [4.5; 6]),
+        ];
+        rank(&mut entries);
+        let order: Vec<(&str, usize)> =
+            entries.iter().map(|e| (e.model.as_str(), e.rank)).collect();
+        assert_eq!(order, vec![("high", 1), ("low", 2), ("none", 3)]);
+    }
+
+    #[test]
+    fn names_strengths_and_leads() {
+        let mut entries = vec![
+            entry("a", Some(4.0), [5.0, 3.0, 4.0, 4.0, 4.0, 4.0]),
+            entry("b", Some(3.9), [4.0, 4.5, 4.0, 3.0, 3.5, 3.5]),
+        ];
+        rank(&mut entries);
+        assert_eq!(
+            entries[0].strongest_dimension.as_deref(),
+            Some("structural_correctness")
+        );
+        assert_eq!(
+            entries[0].weakest_dimension.as_deref(),
+            Some("command_accuracy")
+        );
+        assert_eq!(
+            entries[0].leads_in,
+            vec![
+                "structural_correctness",
+                "phase_flow_logic",
+                "step_completeness",
+                "prompt_quality",
+                "determinism"
+            ]
+        );
+        // Ties lead for both
+        assert_eq!(
+            entries[1].leads_in,
+            vec!["command_accuracy", "phase_flow_logic"]
+        );
+    }
+}
@@ -1,7 +1,20 @@
+pub mod baseline;
+pub mod bundle;
 pub mod db;
+pub mod determinism;
 pub mod engine;
+pub mod export;
 pub mod judge;
+pub mod leaderboard;
+pub mod mutation;
+pub mod progress;
+pub mod prompt_source;
 pub mod queries;
+pub mod reliability;
+pub mod retention;
+pub mod schedule;
+pub mod significance;
+pub mod structural;
 
 use serde::{Deserialize, Serialize};
 
@@ -20,10 +33,70 @@ pub struct TestPrompt {
     pub tags: Option<Vec<String>>,
     pub ground_truth_json: Option<String>,
     pub enabled: bool,
+    /// Lifecycle state. Only `active` prompts are evaluated. Changed via
+    /// `POST /eval/test-suite/{id}/status`, not by prompt updates.
+    #[serde(default)]
+    pub status: PromptStatus,
+    /// Set automatically when a prompt's recent scores are consistently
+    /// maxed out — a hint that it no longer discriminates between models.
+    #[serde(default)]
+    pub needs_review: bool,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Named group of test prompt IDs, selectable with `suite` on `POST /eval/start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptSuite {
+    pub name: String,
+    pub description: Option<String>,
+    pub prompt_ids: Vec<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptStatus {
+    Draft,
+    #[default]
+    Active,
+    Deprecated,
+}
+
+impl PromptStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Active => "active",
+            Self::Deprecated => "deprecated",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "draft" => Some(Self::Draft),
+            "active" => Some(Self::Active),
+            "deprecated" => Some(Self::Deprecated),
+            _ => None,
+        }
+    }
+
+    /// Allowed lifecycle moves. Prompts never go back to draft once they
+    /// have been part of the benchmark, so historical runs stay comparable.
+    pub fn can_transition_to(&self, next: PromptStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Draft, Self::Active)
+                | (Self::Draft, Self::Deprecated)
+                | (Self::Active, Self::Deprecated)
+                | (Self::Deprecated, Self::Active)
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvalRunSummary {
     pub id: String,
@@ -61,6 +134,39 @@ pub struct EvalRunSummary {
     pub error: Option<String>,
     pub started_at: String,
     pub completed_at: Option<String>,
+    /// Judge used to score this run (resolved at start, so a later change
+    /// to the global AI settings doesn't alter how the run was scored).
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+    /// Set on `retry` runs: the run whose failed prompts this one re-ran,
+    /// and on `rescore` runs: the run whose workflows were re-scored.
+    #[serde(default)]
+    pub parent_run_id: Option<String>,
+    /// `judge_prompts` version the run was scored with; `None` for runs
+    /// from before templates were versioned (the built-in rubric).
+    #[serde(default)]
+    pub judge_template_version: Option<i64>,
+    /// Shared by the runs of one model matrix (`POST /eval/matrix/start`).
+    #[serde(default)]
+    pub matrix_id: Option<String>,
+    /// Results whose scores were overridden by hand, so the aggregates are
+    /// not purely the judge's. Filled in when aggregates are computed.
+    #[serde(default)]
+    pub overridden_count: Option<i64>,
+    /// Generations per prompt; above 1 the run measures determinism (see
+    /// [`determinism`]). `None` on runs from before sampling existed.
+    #[serde(default)]
+    pub samples_per_prompt: Option<i64>,
+    /// Model the runner was asked to generate with (model matrix runs);
+    /// `None` when it used its own configured model.
+    #[serde(default)]
+    pub generation_provider: Option<String>,
+    #[serde(default)]
+    pub generation_model: Option<String>,
+    /// Per-category averages (by `test_prompts.category`), filled in when
+    /// the run completes. Empty while running.
+    #[serde(default)]
+    pub category_scores: Vec<CategoryScore>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,21 +189,162 @@ pub struct EvalResult {
 
     pub score_rationales: Option<String>,
 
+    /// Deterministic precision/recall against the prompt's ground truth;
+    /// `None` for prompts without one or when generation failed.
+    #[serde(default)]
+    pub structural_metrics: Option<structural::StructuralMetrics>,
+
     pub generation_error: Option<String>,
     pub scoring_error: Option<String>,
 
     pub generation_duration_ms: Option<i64>,
     pub scoring_duration_ms: Option<i64>,
 
+    /// Set when a human replaced the judge's scores.
+    #[serde(default)]
+    pub score_override: Option<ScoreOverride>,
+
     pub started_at: String,
     pub completed_at: Option<String>,
 }
 
+/// The six judge dimensions of one result, any of which may be unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DimensionScores {
+    pub structural_correctness: Option<i64>,
+    pub command_accuracy: Option<i64>,
+    pub phase_flow_logic: Option<i64>,
+    pub step_completeness: Option<i64>,
+    pub prompt_quality: Option<i64>,
+    pub determinism: Option<i64>,
+}
+
+impl DimensionScores {
+    pub fn values(&self) -> [Option<i64>; 6] {
+        [
+            self.structural_correctness,
+            self.command_accuracy,
+            self.phase_flow_logic,
+            self.step_completeness,
+            self.prompt_quality,
+            self.determinism,
+        ]
+    }
+
+    /// Mean of the six, as [`ScoreResponse::overall`]; `None` unless all
+    /// are set.
+    pub fn overall(&self) -> Option<f64> {
+        let values = self.values();
+        let sum = values.iter().copied().sum::<Option<i64>>()?;
+        Some(sum as f64 / values.len() as f64)
+    }
+
+    /// `self` with every dimension `patch` sets replaced.
+    pub fn merged(&self, patch: &DimensionScores) -> DimensionScores {
+        DimensionScores {
+            structural_correctness: patch.structural_correctness.or(self.structural_correctness),
+            command_accuracy: patch.command_accuracy.or(self.command_accuracy),
+            phase_flow_logic: patch.phase_flow_logic.or(self.phase_flow_logic),
+            step_completeness: patch.step_completeness.or(self.step_completeness),
+            prompt_quality: patch.prompt_quality.or(self.prompt_quality),
+            determinism: patch.determinism.or(self.determinism),
+        }
+    }
+}
+
+/// Audit record of a manual score override (`PATCH /eval/results/{id}/scores`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreOverride {
+    /// The judge's scores, before the first override.
+    pub original: DimensionScores,
+    pub original_overall: Option<f64>,
+    /// Reason given for the latest override.
+    pub reason: String,
+    pub overridden_at: String,
+}
+
+/// Per-category averages for a run, computed on completion by joining
+/// results against `test_prompts.category`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryScore {
+    pub category: String,
+    pub count: i64,
+    pub avg_overall: Option<f64>,
+    pub avg_structural: Option<f64>,
+    pub avg_command_accuracy: Option<f64>,
+    pub avg_phase_flow: Option<f64>,
+    pub avg_step_completeness: Option<f64>,
+    pub avg_prompt_quality: Option<f64>,
+    pub avg_determinism: Option<f64>,
+}
+
+/// One point in a per-category score trend (one run, one category).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTrendPoint {
+    pub run_id: String,
+    pub started_at: String,
+    pub category: String,
+    pub count: i64,
+    pub avg_overall: Option<f64>,
+}
+
+/// One full-text search hit from `GET /search`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// Which store the hit came from (currently always `eval_result`).
+    pub source: String,
+    pub result_id: i64,
+    pub run_id: String,
+    pub test_prompt_id: String,
+    pub overall_score: Option<f64>,
+    pub started_at: String,
+    /// Matching text with the matched terms wrapped in `[` `]`.
+    pub snippet: String,
+    /// FTS5 bm25 rank; lower is a better match.
+    pub rank: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvalRunWithResults {
     #[serde(flatten)]
     pub run: EvalRunSummary,
+    pub environment: Option<crate::run_environment::EnvironmentSnapshot>,
     pub results: Vec<EvalResult>,
+    /// Results matching the filter before `limit`/`offset` were applied.
+    #[serde(default)]
+    pub results_total: usize,
+}
+
+/// Filter and page over a run's results. The default returns everything,
+/// workflow JSON included.
+#[derive(Debug, Clone)]
+pub struct ResultFilter {
+    pub limit: Option<usize>,
+    pub offset: usize,
+    /// When false `generated_workflow_json` is left out (`None`), which is
+    /// most of the payload.
+    pub include_workflow_json: bool,
+    /// Inclusive bounds on `overall_score`; unscored results never match.
+    pub min_score: Option<f64>,
+    pub max_score: Option<f64>,
+    /// Matches the prompt's current `test_prompts.category`.
+    pub category: Option<String>,
+    /// Only results with (true) or without (false) a generation/scoring error.
+    pub has_error: Option<bool>,
+}
+
+impl Default for ResultFilter {
+    fn default() -> Self {
+        Self {
+            limit: None,
+            offset: 0,
+            include_workflow_json: true,
+            min_score: None,
+            max_score: None,
+            category: None,
+            has_error: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +355,22 @@ pub struct EvalStatus {
     pub continuous_interval_secs: u64,
     pub current_prompt_index: usize,
     pub total_prompts: usize,
+    pub runner_restart_policy: RunnerRestartPolicy,
+}
+
+/// What the crash-only watchdog does when it wants to restart the primary
+/// runner while an eval run is polling it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunnerRestartPolicy {
+    /// Restart immediately; in-flight prompts fail against the down runner.
+    Proceed,
+    /// Hold the restart until the current run finishes.
+    Defer,
+    /// Stop the run, record it as `aborted` with the restart as the reason,
+    /// then restart.
+    #[default]
+    Abort,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,6 +390,13 @@ pub struct PromptComparison {
     pub regression: bool,
     pub improvement: bool,
     pub dimension_deltas: DimensionDeltas,
+    /// Past scores of this prompt (other runs) used to judge its noise.
+    #[serde(default)]
+    pub history_samples: usize,
+    /// Whether `delta` exceeds the prompt's usual run-to-run spread; `None`
+    /// without enough history (see [`significance`]).
+    #[serde(default)]
+    pub significant: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,6 +415,19 @@ pub struct AggregateDelta {
     pub regressions: usize,
     pub improvements: usize,
     pub unchanged: usize,
+    /// Regressions/improvements whose delta is outside the prompt's noise.
+    #[serde(default)]
+    pub significant_regressions: usize,
+    #[serde(default)]
+    pub significant_improvements: usize,
+    /// 95% bootstrap interval of `avg_overall_delta`.
+    #[serde(default)]
+    pub avg_delta_ci_low: Option<f64>,
+    #[serde(default)]
+    pub avg_delta_ci_high: Option<f64>,
+    /// Whether the interval excludes zero; `None` with under two paired prompts.
+    #[serde(default)]
+    pub significant: Option<bool>,
 }
 
 /// Scores parsed from the LLM judge response.
@@ -175,3 +458,52 @@ impl ScoreResponse {
         sum as f64 / 6.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_status_round_trips_through_str() {
+        for status in [
+            PromptStatus::Draft,
+            PromptStatus::Active,
+            PromptStatus::Deprecated,
+        ] {
+            assert_eq!(PromptStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(PromptStatus::parse("archived"), None);
+    }
+
+    #[test]
+    fn dimension_scores_merge_and_average() {
+        let judged = DimensionScores {
+            structural_correctness: Some(2),
+            command_accuracy: Some(3),
+            phase_flow_logic: Some(3),
+            step_completeness: Some(4),
+            prompt_quality: Some(4),
+            determinism: Some(2),
+        };
+        let patch = DimensionScores {
+            structural_correctness: Some(5),
+            determinism: Some(5),
+            ..Default::default()
+        };
+        let merged = judged.merged(&patch);
+        assert_eq!(merged.structural_correctness, Some(5));
+        assert_eq!(merged.command_accuracy, Some(3));
+        assert_eq!(merged.overall(), Some(4.0));
+        assert_eq!(patch.overall(), None);
+    }
+
+    #[test]
+    fn prompt_status_transitions() {
+        assert!(PromptStatus::Draft.can_transition_to(PromptStatus::Active));
+        assert!(PromptStatus::Active.can_transition_to(PromptStatus::Deprecated));
+        assert!(PromptStatus::Deprecated.can_transition_to(PromptStatus::Active));
+        assert!(!PromptStatus::Active.can_transition_to(PromptStatus::Draft));
+        assert!(!PromptStatus::Deprecated.can_transition_to(PromptStatus::Draft));
+        assert!(!PromptStatus::Active.can_transition_to(PromptStatus::Active));
+    }
+}
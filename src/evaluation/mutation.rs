@@ -0,0 +1,161 @@
+//! Prompt mutations for robustness testing.
+//!
+//! `POST /eval/test-suite/{id}/mutations` asks the AI provider for reworded
+//! variants of a test prompt — paraphrases, reordered clauses, terser or
+//! chattier phrasing, small typos — that ask for the same workflow. Each
+//! variant is inserted as its own test prompt with the source's category,
+//! expectations and ground truth, tagged [`MUTATION_TAG_PREFIX`]`{id}`.
+//! Variants start as drafts, so they only join the benchmark once activated;
+//! comparing their scores with the source's shows how much generation quality
+//! depends on wording.
+
+use chrono::Utc;
+
+use super::judge::{self, JudgeModel};
+use super::{PromptStatus, TestPrompt};
+
+pub const MUTATION_TAG_PREFIX: &str = "mutation-of:";
+
+pub const DEFAULT_VARIANTS: usize = 3;
+
+pub const MAX_VARIANTS: usize = 10;
+
+const MUTATION_SYSTEM_PROMPT: &str = "You rewrite automation requests for robustness testing. \
+Respond with a JSON array of strings only.";
+
+pub fn mutation_tag(source_id: &str) -> String {
+    format!("{}{}", MUTATION_TAG_PREFIX, source_id)
+}
+
+pub fn mutation_prompt(prompt: &str, count: usize) -> String {
+    format!(
+        "Rewrite the automation request below {count} different ways. Every variant must ask \
+         for exactly the same workflow: keep every target, value, condition and step, and add \
+         nothing. Vary only the wording — paraphrase, reorder clauses, make it terser or \
+         chattier, switch between imperative and descriptive phrasing, or introduce a typo or \
+         two.\n\nRespond with a JSON array of {count} strings and nothing else.\n\n\
+         Request:\n{prompt}"
+    )
+}
+
+/// Variants from the model's reply, trimmed and deduplicated, without any
+/// that merely repeat `original`.
+pub fn parse_variants(raw: &str, original: &str) -> anyhow::Result<Vec<String>> {
+    let trimmed = raw.trim();
+    let parsed: Vec<String> = match serde_json::from_str(trimmed) {
+        Ok(v) => v,
+        Err(_) => {
+            // Tolerate code fences or prose around the array.
+            let (Some(start), Some(end)) = (trimmed.find('['), trimmed.rfind(']')) else {
+                anyhow::bail!(
+                    "No JSON array in mutation response: {}",
+                    &trimmed[..trimmed.len().min(100)]
+                );
+            };
+            serde_json::from_str(&trimmed[start..=end])?
+        }
+    };
+
+    let mut variants: Vec<String> = Vec::new();
+    for v in parsed {
+        let v = v.trim().to_string();
+        if v.is_empty() || v == original.trim() || variants.contains(&v) {
+            continue;
+        }
+        variants.push(v);
+    }
+    Ok(variants)
+}
+
+/// A test prompt for `text`, linked to `source`.
+pub fn variant_of(source: &TestPrompt, text: String, status: PromptStatus) -> TestPrompt {
+    let now = Utc::now().to_rfc3339();
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let mut tags: Vec<String> = source
+        .tags
+        .iter()
+        .flatten()
+        .filter(|t| !t.starts_with(MUTATION_TAG_PREFIX))
+        .cloned()
+        .collect();
+    tags.push(mutation_tag(&source.id));
+    TestPrompt {
+        id: format!("{}-mut-{}", source.id, &suffix[..8]),
+        prompt: text,
+        category: source.category.clone(),
+        complexity: source.complexity.clone(),
+        expected_phases: source.expected_phases.clone(),
+        expected_step_types: source.expected_step_types.clone(),
+        tags: Some(tags),
+        ground_truth_json: source.ground_truth_json.clone(),
+        enabled: true,
+        status,
+        needs_review: false,
+        created_at: now.clone(),
+        updated_at: now,
+    }
+}
+
+/// Ask `model` for up to `count` variants of `source`.
+pub async fn generate(
+    model: &JudgeModel,
+    source: &TestPrompt,
+    count: usize,
+    status: PromptStatus,
+) -> anyhow::Result<Vec<TestPrompt>> {
+    let raw = judge::run_model(
+        model,
+        MUTATION_SYSTEM_PROMPT,
+        &mutation_prompt(&source.prompt, count),
+        "qontinui-eval-mutation",
+    )
+    .await?;
+    Ok(parse_variants(&raw, &source.prompt)?
+        .into_iter()
+        .take(count)
+        .map(|text| variant_of(source, text, status))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fenced_arrays_and_drops_repeats() {
+        let raw = "```json\n[\"Open the app\", \" open settings \", \"Open the app\", \"\", \"Click save\"]\n```";
+        assert_eq!(
+            parse_variants(raw, "Click save").unwrap(),
+            vec!["Open the app", "open settings"]
+        );
+        assert!(parse_variants("no array here", "x").is_err());
+    }
+
+    #[test]
+    fn variant_keeps_expectations_and_links_source() {
+        let source = TestPrompt {
+            id: "login".to_string(),
+            prompt: "Log in".to_string(),
+            category: "auth".to_string(),
+            complexity: "simple".to_string(),
+            expected_phases: Some(vec!["setup".to_string()]),
+            expected_step_types: None,
+            tags: Some(vec!["smoke".to_string()]),
+            ground_truth_json: Some("{}".to_string()),
+            enabled: true,
+            status: PromptStatus::Active,
+            needs_review: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let v = variant_of(&source, "Sign in".to_string(), PromptStatus::Draft);
+        assert!(v.id.starts_with("login-mut-"));
+        assert_eq!(v.category, "auth");
+        assert_eq!(v.ground_truth_json.as_deref(), Some("{}"));
+        assert_eq!(v.status, PromptStatus::Draft);
+        assert_eq!(
+            v.tags,
+            Some(vec!["smoke".to_string(), "mutation-of:login".to_string()])
+        );
+    }
+}
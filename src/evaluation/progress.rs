@@ -0,0 +1,113 @@
+//! Per-prompt progress events for `GET /eval/stream`.
+//!
+//! `GET /eval/status` only carries the current prompt index. The engine also
+//! publishes an [`EvalProgressEvent`] on [`crate::state::EvaluationState`]'s
+//! `progress_tx` as each prompt starts, finishes generating and finishes
+//! scoring, and once the run completes. With no stream open the events are
+//! simply dropped.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::ScoreResponse;
+
+/// Events buffered for a subscriber before it starts lagging.
+pub const PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EvalProgressEvent {
+    PromptStarted {
+        run_id: String,
+        test_prompt_id: String,
+        /// 0-based position in the run.
+        index: usize,
+        total: usize,
+    },
+    GenerationFinished {
+        run_id: String,
+        test_prompt_id: String,
+        /// `None` when the workflow was reused from the source of a rescore.
+        duration_ms: Option<i64>,
+        error: Option<String>,
+    },
+    ScoringFinished {
+        run_id: String,
+        test_prompt_id: String,
+        duration_ms: i64,
+        overall_score: Option<f64>,
+        scores: Option<ScoreResponse>,
+        structural_f1: Option<f64>,
+        error: Option<String>,
+    },
+    RunCompleted {
+        run_id: String,
+        /// `completed`, `cancelled` or `aborted`.
+        status: String,
+        prompts_completed: i64,
+        avg_overall_score: Option<f64>,
+    },
+}
+
+impl EvalProgressEvent {
+    /// SSE event name; matches the serialized `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PromptStarted { .. } => "prompt_started",
+            Self::GenerationFinished { .. } => "generation_finished",
+            Self::ScoringFinished { .. } => "scoring_finished",
+            Self::RunCompleted { .. } => "run_completed",
+        }
+    }
+}
+
+pub fn channel() -> broadcast::Sender<EvalProgressEvent> {
+    broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0
+}
+
+/// Publish `event`; a send with no subscribers is not an error.
+pub fn emit(tx: &broadcast::Sender<EvalProgressEvent>, event: EvalProgressEvent) {
+    let _ = tx.send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_matches_serialized_type() {
+        let events = [
+            EvalProgressEvent::PromptStarted {
+                run_id: "r".to_string(),
+                test_prompt_id: "p".to_string(),
+                index: 0,
+                total: 1,
+            },
+            EvalProgressEvent::GenerationFinished {
+                run_id: "r".to_string(),
+                test_prompt_id: "p".to_string(),
+                duration_ms: None,
+                error: None,
+            },
+            EvalProgressEvent::ScoringFinished {
+                run_id: "r".to_string(),
+                test_prompt_id: "p".to_string(),
+                duration_ms: 5,
+                overall_score: Some(4.0),
+                scores: None,
+                structural_f1: None,
+                error: None,
+            },
+            EvalProgressEvent::RunCompleted {
+                run_id: "r".to_string(),
+                status: "completed".to_string(),
+                prompts_completed: 1,
+                avg_overall_score: Some(4.0),
+            },
+        ];
+        for event in events {
+            let value = serde_json::to_value(&event).unwrap();
+            assert_eq!(value["type"], event.name());
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Seeding and syncing test prompts from a directory of JSON files.
+//!
+//! Teams version their benchmark in the runner repo under `eval-prompts/`
+//! (override with `QONTINUI_EVAL_PROMPTS_DIR`). Each `*.json` file holds one
+//! prompt object or an array of them. When the directory exists the
+//! hardcoded default prompts are not seeded; instead the directory is synced
+//! into the eval DB at startup and on `POST /eval/prompts/sync`.
+//!
+//! Every synced prompt's content checksum is recorded in `prompt_sources`.
+//! On the next sync a prompt whose DB row no longer matches the recorded
+//! checksum was edited through the API since — that is reported as drift
+//! and the file wins. Prompts whose file disappeared are reported, never
+//! deleted. YAML is not supported (no YAML parser in this build); `.yaml`
+//! files are reported as skipped.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::db::EvalDb;
+use super::TestPrompt;
+use crate::config::SupervisorConfig;
+
+pub const PROMPTS_DIR_ENV: &str = "QONTINUI_EVAL_PROMPTS_DIR";
+
+/// Default location, relative to the runner npm dir.
+pub const DEFAULT_PROMPTS_DIR: &str = "eval-prompts";
+
+/// The versioned, content-bearing fields of a test prompt. The lifecycle
+/// `status` stays DB-owned and is kept across syncs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourcePrompt {
+    pub id: String,
+    pub prompt: String,
+    pub category: String,
+    pub complexity: String,
+    #[serde(default)]
+    pub expected_phases: Option<Vec<String>>,
+    #[serde(default)]
+    pub expected_step_types: Option<Vec<String>>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Reference workflow as a JSON value (stored as `ground_truth_json`).
+    #[serde(default)]
+    pub ground_truth: Option<serde_json::Value>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PromptFile {
+    Many(Vec<SourcePrompt>),
+    One(Box<SourcePrompt>),
+}
+
+impl SourcePrompt {
+    /// SHA-256 over the canonical JSON of the content fields, so file
+    /// formatting and key order don't register as changes.
+    pub fn checksum(&self) -> String {
+        let json = serde_json::to_vec(self).expect("SourcePrompt serializes");
+        hex::encode(Sha256::digest(&json))
+    }
+
+    pub fn from_test_prompt(p: &TestPrompt) -> Self {
+        Self {
+            id: p.id.clone(),
+            prompt: p.prompt.clone(),
+            category: p.category.clone(),
+            complexity: p.complexity.clone(),
+            expected_phases: p.expected_phases.clone(),
+            expected_step_types: p.expected_step_types.clone(),
+            tags: p.tags.clone(),
+            ground_truth: p.ground_truth_json.as_deref().map(|s| {
+                serde_json::from_str(s).unwrap_or_else(|_| serde_json::Value::String(s.to_string()))
+            }),
+            enabled: p.enabled,
+        }
+    }
+
+    /// Build the DB row, keeping lifecycle fields from `existing`.
+    fn to_test_prompt(&self, existing: Option<&TestPrompt>, now: &str) -> TestPrompt {
+        TestPrompt {
+            id: self.id.clone(),
+            prompt: self.prompt.clone(),
+            category: self.category.clone(),
+            complexity: self.complexity.clone(),
+            expected_phases: self.expected_phases.clone(),
+            expected_step_types: self.expected_step_types.clone(),
+            tags: self.tags.clone(),
+            ground_truth_json: self.ground_truth.as_ref().map(|v| v.to_string()),
+            enabled: self.enabled,
+            status: existing.map(|e| e.status).unwrap_or_default(),
+            needs_review: existing.is_some_and(|e| e.needs_review),
+            created_at: existing
+                .map(|e| e.created_at.clone())
+                .unwrap_or_else(|| now.to_string()),
+            updated_at: now.to_string(),
+        }
+    }
+}
+
+/// Where prompt files are read from.
+pub fn prompts_dir(config: &SupervisorConfig) -> PathBuf {
+    match std::env::var(PROMPTS_DIR_ENV) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => config.runner_npm_dir().join(DEFAULT_PROMPTS_DIR),
+    }
+}
+
+#[derive(Debug)]
+pub struct LoadedPrompt {
+    /// Path relative to the prompts dir.
+    pub source_path: String,
+    pub prompt: SourcePrompt,
+}
+
+/// Read every `*.json` file in `dir` (sorted, non-recursive). Per-file
+/// problems and duplicate IDs are returned as errors rather than aborting;
+/// the first file to define an ID wins.
+pub fn load_dir(dir: &Path) -> std::io::Result<(Vec<LoadedPrompt>, Vec<String>)> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort();
+
+    let mut prompts = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => {}
+            Some("yaml" | "yml") => {
+                errors.push(format!("{}: YAML is not supported, use JSON", name));
+                continue;
+            }
+            _ => continue,
+        }
+        let parsed = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                serde_json::from_slice::<PromptFile>(&bytes).map_err(|e| e.to_string())
+            });
+        let entries = match parsed {
+            Ok(PromptFile::Many(v)) => v,
+            Ok(PromptFile::One(p)) => vec![*p],
+            Err(e) => {
+                errors.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        for prompt in entries {
+            if !seen.insert(prompt.id.clone()) {
+                errors.push(format!(
+                    "{}: duplicate prompt id '{}' ignored",
+                    name, prompt.id
+                ));
+                continue;
+            }
+            prompts.push(LoadedPrompt {
+                source_path: name.clone(),
+                prompt,
+            });
+        }
+    }
+    Ok((prompts, errors))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Add,
+    Unchanged,
+    /// `drift` when the DB row was edited after the last sync.
+    Update {
+        drift: bool,
+    },
+}
+
+/// Decide what to do with one file prompt given the checksum of its current
+/// DB row (if any) and the checksum recorded at the last sync (if any).
+pub fn plan(file: &str, db: Option<&str>, recorded: Option<&str>) -> SyncAction {
+    match db {
+        None => SyncAction::Add,
+        Some(db) if db == file => SyncAction::Unchanged,
+        Some(db) => SyncAction::Update {
+            drift: recorded.is_some_and(|r| r != db),
+        },
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub dir: String,
+    pub dry_run: bool,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: usize,
+    /// Prompts edited in the DB since the last sync; the file version wins.
+    pub drifted: Vec<String>,
+    /// Previously synced prompts whose file entry is gone (left in place).
+    pub missing_source: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+pub fn sync(db: &EvalDb, dir: &Path, dry_run: bool) -> anyhow::Result<SyncReport> {
+    let mut report = SyncReport {
+        dir: dir.display().to_string(),
+        dry_run,
+        ..Default::default()
+    };
+    let (loaded, errors) = load_dir(dir)?;
+    report.errors = errors;
+
+    let existing: HashMap<String, TestPrompt> = db
+        .list_test_prompts()?
+        .into_iter()
+        .map(|p| (p.id.clone(), p))
+        .collect();
+    let recorded = db.prompt_source_checksums()?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    for LoadedPrompt {
+        source_path,
+        prompt,
+    } in &loaded
+    {
+        let file_sum = prompt.checksum();
+        let current = existing.get(&prompt.id);
+        let db_sum = current.map(|p| SourcePrompt::from_test_prompt(p).checksum());
+        let action = plan(
+            &file_sum,
+            db_sum.as_deref(),
+            recorded.get(&prompt.id).map(String::as_str),
+        );
+        match action {
+            SyncAction::Unchanged => report.unchanged += 1,
+            SyncAction::Add => report.added.push(prompt.id.clone()),
+            SyncAction::Update { drift } => {
+                report.updated.push(prompt.id.clone());
+                if drift {
+                    report.drifted.push(prompt.id.clone());
+                }
+            }
+        }
+        if dry_run {
+            continue;
+        }
+        match action {
+            SyncAction::Add => db.insert_test_prompt(&prompt.to_test_prompt(None, &now))?,
+            SyncAction::Update { .. } => {
+                db.replace_test_prompt(&prompt.to_test_prompt(current, &now))?
+            }
+            SyncAction::Unchanged => {}
+        }
+        db.record_prompt_source(&prompt.id, source_path, &file_sum, &now)?;
+    }
+
+    let in_dir: HashSet<&str> = loaded.iter().map(|l| l.prompt.id.as_str()).collect();
+    report.missing_source = recorded
+        .keys()
+        .filter(|id| !in_dir.contains(id.as_str()))
+        .cloned()
+        .collect();
+    report.missing_source.sort();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(id: &str) -> SourcePrompt {
+        SourcePrompt {
+            id: id.to_string(),
+            prompt: "Build the project".to_string(),
+            category: "build".to_string(),
+            complexity: "simple".to_string(),
+            expected_phases: None,
+            expected_step_types: None,
+            tags: None,
+            ground_truth: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn plan_covers_add_unchanged_update_and_drift() {
+        assert_eq!(plan("a", None, None), SyncAction::Add);
+        assert_eq!(plan("a", Some("a"), Some("a")), SyncAction::Unchanged);
+        // File edited, DB untouched since last sync
+        assert_eq!(
+            plan("b", Some("a"), Some("a")),
+            SyncAction::Update { drift: false }
+        );
+        // DB edited since last sync
+        assert_eq!(
+            plan("a", Some("c"), Some("a")),
+            SyncAction::Update { drift: true }
+        );
+        // Existing prompt not previously synced from a file
+        assert_eq!(
+            plan("a", Some("c"), None),
+            SyncAction::Update { drift: false }
+        );
+    }
+
+    #[test]
+    fn checksum_ignores_formatting_but_not_content() {
+        let a: SourcePrompt = serde_json::from_str(
+            r#"{"id":"p1","prompt":"Build the project","category":"build","complexity":"simple"}"#,
+        )
+        .unwrap();
+        let b: SourcePrompt = serde_json::from_str(
+            r#"{
+                "complexity": "simple",
+                "category": "build",
+                "prompt": "Build the project",
+                "id": "p1"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(a.checksum(), b.checksum());
+        let mut c = a.clone();
+        c.prompt.push('!');
+        assert_ne!(a.checksum(), c.checksum());
+    }
+
+    #[test]
+    fn checksum_round_trips_through_test_prompt() {
+        let mut p = prompt("p1");
+        p.ground_truth = Some(serde_json::json!({"setup_steps": []}));
+        let row = p.to_test_prompt(None, "2026-01-01T00:00:00Z");
+        assert_eq!(
+            SourcePrompt::from_test_prompt(&row).checksum(),
+            p.checksum()
+        );
+    }
+
+    #[test]
+    fn load_dir_reads_objects_and_arrays_and_reports_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.json"),
+            serde_json::to_string(&prompt("p1")).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.json"),
+            serde_json::to_string(&vec![prompt("p2"), prompt("p1")]).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("c.yaml"), "id: p3").unwrap();
+        std::fs::write(dir.path().join("d.json"), "{not json").unwrap();
+        std::fs::write(dir.path().join("README.md"), "docs").unwrap();
+
+        let (loaded, errors) = load_dir(dir.path()).unwrap();
+        let ids: Vec<&str> = loaded.iter().map(|l| l.prompt.id.as_str()).collect();
+        assert_eq!(ids, vec!["p1", "p2"]);
+        assert_eq!(loaded[0].source_path, "a.json");
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].contains("duplicate prompt id 'p1'"));
+        assert!(errors[1].starts_with("c.yaml"));
+        assert!(errors[2].starts_with("d.json"));
+    }
+}
@@ -1,7 +1,12 @@
 use rusqlite::params;
+use std::collections::HashMap;
 
 use super::db::EvalDb;
-use super::{AggregateDelta, CompareReport, DimensionDeltas, EvalRunSummary, PromptComparison};
+use super::significance::{self, MAX_HISTORY_SAMPLES};
+use super::{
+    AggregateDelta, CategoryScore, CategoryTrendPoint, CompareReport, DimensionDeltas,
+    EvalRunSummary, PromptComparison, SearchHit,
+};
 
 /// List all eval runs, most recent first.
 pub fn list_runs(db: &EvalDb) -> anyhow::Result<Vec<EvalRunSummary>> {
@@ -14,7 +19,9 @@ pub fn list_runs(db: &EvalDb) -> anyhow::Result<Vec<EvalRunSummary>> {
                 gt_avg_step_completeness, gt_avg_prompt_quality, gt_avg_determinism, gt_count,
                 gen_avg_overall, gen_avg_structural, gen_avg_command_accuracy, gen_avg_phase_flow,
                 gen_avg_step_completeness, gen_avg_prompt_quality, gen_avg_determinism, gen_count,
-                error, started_at, completed_at
+                error, started_at, completed_at, judge_provider, judge_model, parent_run_id,
+                judge_template_version, matrix_id, overridden_count, samples_per_prompt,
+                generation_provider, generation_model
          FROM eval_runs ORDER BY started_at DESC",
     )?;
     let rows = stmt.query_map([], |row| {
@@ -50,11 +57,111 @@ pub fn list_runs(db: &EvalDb) -> anyhow::Result<Vec<EvalRunSummary>> {
             error: row.get(28)?,
             started_at: row.get(29)?,
             completed_at: row.get(30)?,
+            judge_provider: row.get(31)?,
+            judge_model: row.get(32)?,
+            parent_run_id: row.get(33)?,
+            judge_template_version: row.get(34)?,
+            matrix_id: row.get(35)?,
+            overridden_count: row.get(36)?,
+            samples_per_prompt: row.get(37)?,
+            generation_provider: row.get(38)?,
+            generation_model: row.get(39)?,
+            category_scores: Vec::new(),
+        })
+    })?;
+    let mut runs = rows.collect::<Result<Vec<_>, _>>()?;
+
+    // One pass over the category table rather than a query per run.
+    let mut stmt = conn.prepare(
+        "SELECT run_id, category, count, avg_overall, avg_structural, avg_command_accuracy,
+                avg_phase_flow, avg_step_completeness, avg_prompt_quality, avg_determinism
+         FROM eval_run_category_scores ORDER BY category",
+    )?;
+    let mut by_run: HashMap<String, Vec<CategoryScore>> = HashMap::new();
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            EvalDb::row_to_category_score(row, 1)?,
+        ))
+    })?;
+    for row in rows {
+        let (run_id, score) = row?;
+        by_run.entry(run_id).or_default().push(score);
+    }
+    for run in &mut runs {
+        run.category_scores = by_run.remove(&run.id).unwrap_or_default();
+    }
+    Ok(runs)
+}
+
+/// Per-category overall averages across the most recent completed runs,
+/// oldest first. `category` narrows the trend to a single category.
+pub fn category_trends(
+    db: &EvalDb,
+    category: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<Vec<CategoryTrendPoint>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT c.run_id, r.started_at, c.category, c.count, c.avg_overall
+         FROM eval_run_category_scores c
+         JOIN (SELECT id, started_at FROM eval_runs WHERE status='completed'
+               ORDER BY started_at DESC LIMIT ?2) r ON r.id = c.run_id
+         WHERE ?1 IS NULL OR c.category = ?1
+         ORDER BY r.started_at ASC, c.category",
+    )?;
+    let rows = stmt.query_map(params![category, limit as i64], |row| {
+        Ok(CategoryTrendPoint {
+            run_id: row.get(0)?,
+            started_at: row.get(1)?,
+            category: row.get(2)?,
+            count: row.get(3)?,
+            avg_overall: row.get(4)?,
         })
     })?;
     rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
 }
 
+/// Full-text search over judge rationales and generation/scoring errors.
+///
+/// `query` is FTS5 syntax (`missing AND verification`, `"exact phrase"`,
+/// `verif*`). If it doesn't parse, it is retried as a quoted phrase so plain
+/// text with punctuation still works.
+pub fn search_results(db: &EvalDb, query: &str, limit: usize) -> anyhow::Result<Vec<SearchHit>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.run_id, r.test_prompt_id, r.overall_score, r.started_at,
+                snippet(eval_results_fts, -1, '[', ']', '…', 16),
+                bm25(eval_results_fts)
+         FROM eval_results_fts JOIN eval_results r ON r.id = eval_results_fts.rowid
+         WHERE eval_results_fts MATCH ?1
+         ORDER BY bm25(eval_results_fts)
+         LIMIT ?2",
+    )?;
+    let run = |stmt: &mut rusqlite::Statement<'_>, q: &str| -> rusqlite::Result<Vec<SearchHit>> {
+        let rows = stmt.query_map(params![q, limit as i64], |row| {
+            Ok(SearchHit {
+                source: "eval_result".to_string(),
+                result_id: row.get(0)?,
+                run_id: row.get(1)?,
+                test_prompt_id: row.get(2)?,
+                overall_score: row.get(3)?,
+                started_at: row.get(4)?,
+                snippet: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                rank: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    };
+    match run(&mut stmt, query) {
+        Ok(hits) => Ok(hits),
+        Err(_) => {
+            let phrase = format!("\"{}\"", query.replace('"', "\"\""));
+            run(&mut stmt, &phrase).map_err(Into::into)
+        }
+    }
+}
+
 /// Compare two runs by matching results on test_prompt_id.
 pub fn compare_runs(
     db: &EvalDb,
@@ -108,10 +215,34 @@ pub fn compare_runs(
         rows.filter_map(|r| r.ok()).collect()
     };
 
+    // Recent scores of each prompt from other runs, newest first. Retry
+    // results are already merged into their parent run.
+    let mut history: HashMap<String, Vec<f64>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT r.test_prompt_id, r.overall_score
+             FROM eval_results r JOIN eval_runs u ON u.id = r.run_id
+             WHERE u.status = 'completed' AND u.mode != 'retry'
+               AND r.overall_score IS NOT NULL AND r.run_id != ?1
+             ORDER BY r.started_at DESC",
+        )?;
+        let rows = stmt.query_map(params![current_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?;
+        for (prompt_id, score) in rows.filter_map(|r| r.ok()) {
+            let samples = history.entry(prompt_id).or_default();
+            if samples.len() < MAX_HISTORY_SAMPLES {
+                samples.push(score);
+            }
+        }
+    }
+
     let mut per_prompt = Vec::new();
     let mut regressions = 0usize;
     let mut improvements = 0usize;
     let mut unchanged = 0usize;
+    let mut significant_regressions = 0usize;
+    let mut significant_improvements = 0usize;
 
     for (prompt_id, current_overall, current_dims) in &current_results {
         let (baseline_overall, baseline_dims) = baseline_results
@@ -127,6 +258,12 @@ pub fn compare_runs(
         let regression = delta.map(|d| d <= -1.0).unwrap_or(false);
         let improvement = delta.map(|d| d >= 1.0).unwrap_or(false);
 
+        let samples = history
+            .get(prompt_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let significant = delta.and_then(|d| significance::prompt_delta_significant(d, samples));
+
         if regression {
             regressions += 1;
         } else if improvement {
@@ -134,6 +271,13 @@ pub fn compare_runs(
         } else {
             unchanged += 1;
         }
+        if significant == Some(true) {
+            if regression {
+                significant_regressions += 1;
+            } else if improvement {
+                significant_improvements += 1;
+            }
+        }
 
         let dim_delta = |idx: usize| -> Option<f64> {
             match (current_dims[idx], baseline_dims[idx]) {
@@ -157,17 +301,18 @@ pub fn compare_runs(
                 prompt_quality: dim_delta(4),
                 determinism: dim_delta(5),
             },
+            history_samples: samples.len(),
+            significant,
         });
     }
 
-    let avg_overall_delta = {
-        let deltas: Vec<f64> = per_prompt.iter().filter_map(|p| p.delta).collect();
-        if deltas.is_empty() {
-            None
-        } else {
-            Some(deltas.iter().sum::<f64>() / deltas.len() as f64)
-        }
+    let deltas: Vec<f64> = per_prompt.iter().filter_map(|p| p.delta).collect();
+    let avg_overall_delta = if deltas.is_empty() {
+        None
+    } else {
+        Some(deltas.iter().sum::<f64>() / deltas.len() as f64)
     };
+    let ci = significance::bootstrap_mean_ci(&deltas);
 
     Ok(CompareReport {
         current_run_id: current_id.to_string(),
@@ -178,6 +323,11 @@ pub fn compare_runs(
             regressions,
             improvements,
             unchanged,
+            significant_regressions,
+            significant_improvements,
+            avg_delta_ci_low: ci.map(|(lo, _)| lo),
+            avg_delta_ci_high: ci.map(|(_, hi)| hi),
+            significant: ci.map(|(lo, hi)| lo > 0.0 || hi < 0.0),
         },
     })
 }
@@ -0,0 +1,220 @@
+//! Judge reliability: agreement between a run's judge and a second one.
+//!
+//! `POST /eval/runs/{id}/rescore-sample` re-scores a random share of a run's
+//! scored results with another judge, as a `rescore` run of the source.
+//! `GET /eval/runs/{id}/agreement` then pairs each re-scored result with the
+//! original by prompt and reports, overall and per dimension, the Pearson
+//! correlation, mean absolute difference and exact-match rate. Low
+//! correlation means the scores say as much about the judge as about the
+//! workflow.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::db::EvalDb;
+use super::leaderboard::DIMENSIONS;
+use super::{EvalResult, ResultFilter};
+
+pub const DEFAULT_SAMPLE_PERCENT: f64 = 20.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DimensionAgreement {
+    pub dimension: String,
+    /// Results both judges scored on this dimension.
+    pub pairs: usize,
+    /// Pearson correlation; `None` below two pairs or when either judge
+    /// gave every pair the same score.
+    pub correlation: Option<f64>,
+    pub mean_abs_diff: Option<f64>,
+    /// Share of pairs scored identically.
+    pub exact_agreement: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgreementReport {
+    pub run_id: String,
+    pub rescore_run_id: String,
+    /// `completed` once the re-score finished; agreement is partial before.
+    pub rescore_status: String,
+    /// `provider/model` of each judge.
+    pub original_judge: Option<String>,
+    pub second_judge: Option<String>,
+    pub overall: DimensionAgreement,
+    /// In [`DIMENSIONS`] order.
+    pub dimensions: Vec<DimensionAgreement>,
+}
+
+/// Pick `percent`% of `ids` (at least one), in their original order.
+pub fn sample(ids: &[String], percent: f64, seed: u64) -> Vec<String> {
+    if ids.is_empty() {
+        return Vec::new();
+    }
+    let n = ((ids.len() as f64 * percent / 100.0).ceil() as usize).clamp(1, ids.len());
+    let mut picked: Vec<usize> = (0..ids.len()).collect();
+    picked.shuffle(&mut StdRng::seed_from_u64(seed));
+    picked.truncate(n);
+    picked.sort_unstable();
+    picked.into_iter().map(|i| ids[i].clone()).collect()
+}
+
+pub fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+fn dimension_agreement(dimension: &str, pairs: &[(f64, f64)]) -> DimensionAgreement {
+    let n = pairs.len() as f64;
+    DimensionAgreement {
+        dimension: dimension.to_string(),
+        pairs: pairs.len(),
+        correlation: pearson(pairs),
+        mean_abs_diff: (!pairs.is_empty())
+            .then(|| pairs.iter().map(|(x, y)| (x - y).abs()).sum::<f64>() / n),
+        exact_agreement: (!pairs.is_empty())
+            .then(|| pairs.iter().filter(|(x, y)| x == y).count() as f64 / n),
+    }
+}
+
+fn dimension_score(result: &EvalResult, dimension: usize) -> Option<i64> {
+    match dimension {
+        0 => result.structural_correctness,
+        1 => result.command_accuracy,
+        2 => result.phase_flow_logic,
+        3 => result.step_completeness,
+        4 => result.prompt_quality,
+        _ => result.determinism,
+    }
+}
+
+/// Agreement between `run_id` and one of its rescore runs — `rescore_run_id`
+/// or the latest. `None` when the run or the rescore doesn't exist.
+pub fn agreement(
+    db: &EvalDb,
+    run_id: &str,
+    rescore_run_id: Option<&str>,
+) -> anyhow::Result<Option<AgreementReport>> {
+    let rescore_run_id = match rescore_run_id {
+        Some(id) => Some(id.to_string()),
+        None => latest_rescore(db, run_id)?,
+    };
+    let Some(rescore_run_id) = rescore_run_id else {
+        return Ok(None);
+    };
+    let (Some(source), Some(rescore)) =
+        (db.get_eval_run(run_id)?, db.get_eval_run(&rescore_run_id)?)
+    else {
+        return Ok(None);
+    };
+    if rescore.parent_run_id.as_deref() != Some(run_id) {
+        return Ok(None);
+    }
+
+    let filter = ResultFilter {
+        include_workflow_json: false,
+        ..Default::default()
+    };
+    // Later rows win, so a merged retry replaces the failed attempt.
+    let originals: HashMap<String, EvalResult> = db
+        .query_results(run_id, &filter)?
+        .into_iter()
+        .map(|r| (r.test_prompt_id.clone(), r))
+        .collect();
+    let pairs: Vec<(&EvalResult, EvalResult)> = db
+        .query_results(&rescore_run_id, &filter)?
+        .into_iter()
+        .filter_map(|r| originals.get(&r.test_prompt_id).map(|o| (o, r)))
+        .collect();
+
+    let overall: Vec<(f64, f64)> = pairs
+        .iter()
+        .filter_map(|(o, r)| Some((o.overall_score?, r.overall_score?)))
+        .collect();
+    let dimensions = DIMENSIONS
+        .iter()
+        .enumerate()
+        .map(|(d, name)| {
+            let scores: Vec<(f64, f64)> = pairs
+                .iter()
+                .filter_map(|(o, r)| {
+                    Some((dimension_score(o, d)? as f64, dimension_score(r, d)? as f64))
+                })
+                .collect();
+            dimension_agreement(name, &scores)
+        })
+        .collect();
+
+    let judge =
+        |provider: Option<String>, model: Option<String>| Some(format!("{}/{}", provider?, model?));
+    Ok(Some(AgreementReport {
+        run_id: run_id.to_string(),
+        rescore_run_id,
+        rescore_status: rescore.status,
+        original_judge: judge(source.judge_provider, source.judge_model),
+        second_judge: judge(rescore.judge_provider, rescore.judge_model),
+        overall: dimension_agreement("overall", &overall),
+        dimensions,
+    }))
+}
+
+fn latest_rescore(db: &EvalDb, run_id: &str) -> anyhow::Result<Option<String>> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT id FROM eval_runs WHERE parent_run_id = ?1 AND mode = 'rescore'
+         ORDER BY started_at DESC LIMIT 1",
+        params![run_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_takes_at_least_one_in_order() {
+        let ids: Vec<String> = (0..10).map(|i| format!("p{}", i)).collect();
+        let picked = sample(&ids, 25.0, 7);
+        assert_eq!(picked.len(), 3);
+        assert!(picked.windows(2).all(|w| {
+            let pos = |id: &String| ids.iter().position(|i| i == id);
+            pos(&w[0]) < pos(&w[1])
+        }));
+        assert_eq!(picked, sample(&ids, 25.0, 7));
+        assert_eq!(sample(&ids, 0.1, 7).len(), 1);
+        assert_eq!(sample(&ids, 100.0, 7), ids);
+    }
+
+    #[test]
+    fn agreement_statistics() {
+        let same = [(1.0, 1.0), (3.0, 3.0), (5.0, 5.0)];
+        let a = dimension_agreement("overall", &same);
+        assert!((a.correlation.unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(a.mean_abs_diff, Some(0.0));
+        assert_eq!(a.exact_agreement, Some(1.0));
+
+        let inverse = [(1.0, 5.0), (3.0, 3.0), (5.0, 1.0)];
+        assert!((pearson(&inverse).unwrap() + 1.0).abs() < 1e-9);
+        assert_eq!(pearson(&[(4.0, 1.0), (4.0, 5.0)]), None);
+        assert_eq!(dimension_agreement("x", &[]).mean_abs_diff, None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Retention policy for `eval-benchmark.db`.
+//!
+//! Each run carries its full result rows, workflow JSON included, so the DB
+//! only grows. A [`RetentionPolicy`] keeps the last `keep_last` runs and/or
+//! runs newer than `max_age_days` — a run survives if any configured rule
+//! keeps it — and deletes the rest with their results. The pinned baseline,
+//! runs still in progress, and the parent of any kept retry/rescore run are
+//! always kept. With neither rule set nothing is pruned.
+//!
+//! The policy comes from [`KEEP_RUNS_ENV`] / [`KEEP_DAYS_ENV`] and is applied
+//! at startup; `POST /eval/prune` applies it (or a one-off override) on
+//! demand.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::db::EvalDb;
+
+pub const KEEP_RUNS_ENV: &str = "QONTINUI_SUPERVISOR_EVAL_KEEP_RUNS";
+pub const KEEP_DAYS_ENV: &str = "QONTINUI_SUPERVISOR_EVAL_KEEP_DAYS";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub keep_last: Option<usize>,
+    pub max_age_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    /// From [`KEEP_RUNS_ENV`] / [`KEEP_DAYS_ENV`]; unset or malformed values
+    /// leave that rule off.
+    pub fn from_env() -> Self {
+        let read = |var: &str| std::env::var(var).ok().and_then(|s| s.trim().parse().ok());
+        Self {
+            keep_last: read(KEEP_RUNS_ENV).map(|n: u64| n as usize),
+            max_age_days: read(KEEP_DAYS_ENV).map(|n: u64| n.min(u32::MAX as u64) as u32),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.keep_last.is_some() || self.max_age_days.is_some()
+    }
+}
+
+/// The parts of a run retention looks at.
+#[derive(Debug, Clone)]
+pub struct RunRow {
+    pub id: String,
+    pub status: String,
+    pub started_at: String,
+    pub parent_run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    pub policy: RetentionPolicy,
+    pub dry_run: bool,
+    /// Runs deleted (or that would be, on a dry run).
+    pub pruned: Vec<String>,
+    pub kept: usize,
+    pub vacuumed: bool,
+}
+
+/// IDs of runs the policy drops. `runs` must be newest first.
+pub fn prunable(
+    runs: &[RunRow],
+    policy: RetentionPolicy,
+    baseline_run_id: Option<&str>,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    if !policy.is_enabled() {
+        return Vec::new();
+    }
+    let cutoff = policy
+        .max_age_days
+        .map(|d| now - chrono::Duration::days(d as i64));
+
+    let mut keep: HashSet<&str> = runs
+        .iter()
+        .enumerate()
+        .filter(|(i, run)| {
+            let recent = policy.keep_last.is_some_and(|n| *i < n);
+            let young = cutoff.is_some_and(|cutoff| {
+                DateTime::parse_from_rfc3339(&run.started_at)
+                    .map(|t| t.with_timezone(&Utc) >= cutoff)
+                    // Unparseable timestamps are never aged out.
+                    .unwrap_or(true)
+            });
+            recent || young || run.status == "running" || Some(run.id.as_str()) == baseline_run_id
+        })
+        .map(|(_, run)| run.id.as_str())
+        .collect();
+
+    let parents: Vec<&str> = runs
+        .iter()
+        .filter(|r| keep.contains(r.id.as_str()))
+        .filter_map(|r| r.parent_run_id.as_deref())
+        .collect();
+    keep.extend(parents);
+
+    runs.iter()
+        .filter(|r| !keep.contains(r.id.as_str()))
+        .map(|r| r.id.clone())
+        .collect()
+}
+
+/// Apply `policy` to the DB. `vacuum` rebuilds the file afterwards so the
+/// freed space goes back to the OS.
+pub fn prune(
+    db: &EvalDb,
+    policy: RetentionPolicy,
+    dry_run: bool,
+    vacuum: bool,
+) -> anyhow::Result<PruneReport> {
+    let runs = db.list_runs_for_retention()?;
+    let baseline = db.get_baseline()?.map(|pin| pin.run_id);
+    let pruned = prunable(&runs, policy, baseline.as_deref(), Utc::now());
+
+    let mut report = PruneReport {
+        policy,
+        dry_run,
+        kept: runs.len() - pruned.len(),
+        ..Default::default()
+    };
+    if !dry_run && !pruned.is_empty() {
+        db.delete_runs(&pruned)?;
+        if vacuum {
+            db.vacuum()?;
+            report.vacuumed = true;
+        }
+    }
+    report.pruned = pruned;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, days_ago: i64, parent: Option<&str>) -> RunRow {
+        RunRow {
+            id: id.to_string(),
+            status: "completed".to_string(),
+            started_at: (now() - chrono::Duration::days(days_ago)).to_rfc3339(),
+            parent_run_id: parent.map(str::to_string),
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn disabled_policy_prunes_nothing() {
+        let runs = [run("a", 400, None)];
+        assert!(prunable(&runs, RetentionPolicy::default(), None, now()).is_empty());
+    }
+
+    #[test]
+    fn any_rule_keeps_a_run() {
+        let runs = [
+            run("new", 1, None),
+            run("recent", 5, None),
+            run("old", 100, None),
+            run("older", 200, None),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            max_age_days: Some(30),
+        };
+        assert_eq!(prunable(&runs, policy, None, now()), vec!["old", "older"]);
+    }
+
+    #[test]
+    fn baseline_running_and_parents_are_kept() {
+        let mut running = run("running", 90, None);
+        running.status = "running".to_string();
+        let runs = [
+            run("rescore", 1, Some("source")),
+            running,
+            run("source", 60, None),
+            run("baseline", 80, None),
+            run("stale", 70, None),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            max_age_days: None,
+        };
+        assert_eq!(
+            prunable(&runs, policy, Some("baseline"), now()),
+            vec!["stale"]
+        );
+    }
+}
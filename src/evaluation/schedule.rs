@@ -0,0 +1,267 @@
+//! Cron-style schedules for recurring eval runs.
+//!
+//! Standard five-field expressions (`minute hour day-of-month month
+//! day-of-week`), evaluated in the supervisor's local time. Each field takes
+//! `*`, a number, `a-b` ranges, `,` lists, and `/n` steps. Day-of-week is
+//! `0-7` with both `0` and `7` meaning Sunday. As in classic cron, when both
+//! day fields are restricted a day matches if *either* does.
+//!
+//! Schedules are stored in `eval_schedules` and fired by the loop in
+//! `routes::evaluation`, which enqueues a regular eval job — a schedule that
+//! fires while a run is in progress waits its turn instead of being dropped.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on the search for the next fire time. Four years covers
+/// `0 0 29 2 *` across a leap cycle.
+const MAX_SEARCH_DAYS: i64 = 366 * 4 + 1;
+
+/// How often the scheduler loop looks for due schedules.
+pub const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// A persisted eval schedule. Run options mirror `POST /eval/start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalSchedule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub cron: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub suite: Option<String>,
+    pub prompt_ids: Option<Vec<String>>,
+    pub concurrency: Option<usize>,
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+    #[serde(default)]
+    pub last_fired_at: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl EvalSchedule {
+    /// Next fire time (local) after the later of the last firing and the
+    /// last update, so re-enabling a schedule doesn't replay the time it was
+    /// off. A time in the past means the schedule is due; firings missed
+    /// while the supervisor was down collapse into a single run.
+    pub fn next_fire(&self) -> Option<NaiveDateTime> {
+        next_fire(&self.cron, self.last_fired_at.as_deref(), &self.updated_at)
+    }
+}
+
+/// [`EvalSchedule::next_fire`] for any schedule with the same bookkeeping
+/// (RFC 3339 `last_fired_at` / `updated_at`).
+pub fn next_fire(
+    cron: &str,
+    last_fired_at: Option<&str>,
+    updated_at: &str,
+) -> Option<NaiveDateTime> {
+    let cron = CronExpr::parse(cron).ok()?;
+    let base = [last_fired_at, Some(updated_at)]
+        .into_iter()
+        .flatten()
+        .filter_map(|t| DateTime::parse_from_rfc3339(t).ok())
+        .max()?
+        .with_timezone(&Local)
+        .naive_local();
+    cron.next_after(base)
+}
+
+/// Parsed cron expression. Each field is a bitmask of allowed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => {
+                let step: u32 = s
+                    .parse()
+                    .map_err(|_| format!("invalid step '{}' in {} field", s, name))?;
+                if step == 0 {
+                    return Err(format!("step must be > 0 in {} field", name));
+                }
+                (r, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let a = a
+                .parse()
+                .map_err(|_| format!("invalid value '{}' in {} field", a, name))?;
+            let b = b
+                .parse()
+                .map_err(|_| format!("invalid value '{}' in {} field", b, name))?;
+            (a, b)
+        } else {
+            let v: u32 = range
+                .parse()
+                .map_err(|_| format!("invalid value '{}' in {} field", range, name))?;
+            // `5/15` means "from 5, every 15" as in Vixie cron.
+            (v, if step > 1 { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(format!(
+                "{} field value out of range {}-{}: '{}'",
+                name, min, max, part
+            ));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        };
+        let mut days_of_week = parse_field(dow, 0, 7, "day-of-week")? as u8;
+        // 7 is an alias for Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & 0x7f) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")? as u32,
+            days_of_month: parse_field(dom, 1, 31, "day-of-month")? as u32,
+            months: parse_field(month, 1, 12, "month")? as u16,
+            days_of_week,
+            dom_restricted: dom != "*",
+            dow_restricted: dow != "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// First matching minute strictly after `after`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        for day in 0..MAX_SEARCH_DAYS {
+            if self.day_matches(date) {
+                let from = if day == 0 {
+                    start.time()
+                } else {
+                    NaiveTime::MIN
+                };
+                for hour in from.hour()..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let first_minute = if hour == from.hour() {
+                        from.minute()
+                    } else {
+                        0
+                    };
+                    for minute in first_minute..60 {
+                        if self.minutes & (1 << minute) != 0 {
+                            return date.and_hms_opt(hour, minute, 0);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn nightly_fires_next_day_after_time_passed() {
+        let cron = CronExpr::parse("0 2 * * *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2026-03-10 01:59")),
+            Some(at("2026-03-10 02:00"))
+        );
+        assert_eq!(
+            cron.next_after(at("2026-03-10 02:00")),
+            Some(at("2026-03-11 02:00"))
+        );
+    }
+
+    #[test]
+    fn steps_lists_and_ranges() {
+        let cron = CronExpr::parse("*/15 9-17 * * 1-5").unwrap();
+        // 2026-03-13 is a Friday
+        assert_eq!(
+            cron.next_after(at("2026-03-13 17:50")),
+            Some(at("2026-03-16 09:00"))
+        );
+        let cron = CronExpr::parse("5,35 * * * *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2026-03-13 10:06")),
+            Some(at("2026-03-13 10:35"))
+        );
+    }
+
+    #[test]
+    fn sunday_accepts_zero_and_seven() {
+        let a = CronExpr::parse("0 0 * * 0").unwrap();
+        let b = CronExpr::parse("0 0 * * 7").unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn restricted_dom_and_dow_match_either() {
+        // 1st of the month OR any Monday
+        let cron = CronExpr::parse("0 0 1 * 1").unwrap();
+        // 2026-03-10 is a Tuesday; next Monday is the 16th, before April 1st
+        assert_eq!(
+            cron.next_after(at("2026-03-10 00:00")),
+            Some(at("2026-03-16 00:00"))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert!(CronExpr::parse("0 2 * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("a * * * *").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Noise-aware significance for run comparisons.
+//!
+//! Judge scores are noisy 1-5 judgments, so a raw delta alone doesn't say
+//! whether a prompt really regressed. Two checks are layered on top of
+//! [`super::queries::compare_runs`]:
+//!
+//! - Per prompt: the spread of the prompt's own past scores. With at least
+//!   [`MIN_HISTORY_SAMPLES`] of them, a delta is significant when it exceeds
+//!   [`NOISE_Z`] standard deviations of the difference of two draws
+//!   (`σ·√2`). Fewer samples leave the verdict unknown.
+//! - Per run: a seeded bootstrap over the paired per-prompt deltas. The mean
+//!   delta is significant when its 95% interval excludes zero.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Past scores needed before a prompt's noise is trusted.
+pub const MIN_HISTORY_SAMPLES: usize = 3;
+
+/// Most recent past scores per prompt used to estimate its noise.
+pub const MAX_HISTORY_SAMPLES: usize = 20;
+
+pub const NOISE_Z: f64 = 2.0;
+
+const BOOTSTRAP_ITERATIONS: usize = 2000;
+
+/// Fixed so the same pair of runs always yields the same interval.
+const BOOTSTRAP_SEED: u64 = 0x5eed;
+
+/// Sample standard deviation; `None` below two samples.
+pub fn std_dev(samples: &[f64]) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let var = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    Some(var.sqrt())
+}
+
+/// Whether `delta` stands out from a prompt's noise. `None` when the history
+/// is too short to say.
+pub fn prompt_delta_significant(delta: f64, history: &[f64]) -> Option<bool> {
+    if history.len() < MIN_HISTORY_SAMPLES {
+        return None;
+    }
+    let sd = std_dev(history)?;
+    // A perfectly stable prompt: any movement is real.
+    if sd == 0.0 {
+        return Some(delta != 0.0);
+    }
+    Some(delta.abs() > NOISE_Z * sd * std::f64::consts::SQRT_2)
+}
+
+/// 95% bootstrap interval for the mean of `deltas`; `None` below two.
+pub fn bootstrap_mean_ci(deltas: &[f64]) -> Option<(f64, f64)> {
+    if deltas.len() < 2 {
+        return None;
+    }
+    let mut rng = StdRng::seed_from_u64(BOOTSTRAP_SEED);
+    let n = deltas.len();
+    let mut means: Vec<f64> = (0..BOOTSTRAP_ITERATIONS)
+        .map(|_| (0..n).map(|_| deltas[rng.random_range(0..n)]).sum::<f64>() / n as f64)
+        .collect();
+    means.sort_by(f64::total_cmp);
+    let at = |q: f64| means[((BOOTSTRAP_ITERATIONS - 1) as f64 * q).round() as usize];
+    Some((at(0.025), at(0.975)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noisy_prompts_need_bigger_deltas() {
+        let noisy = [2.0, 4.0, 3.0, 5.0, 2.0];
+        let stable = [4.0, 4.1, 3.9, 4.0];
+        assert_eq!(prompt_delta_significant(-1.0, &noisy), Some(false));
+        assert_eq!(prompt_delta_significant(-1.0, &stable), Some(true));
+        assert_eq!(prompt_delta_significant(-1.0, &[4.0, 3.0]), None);
+        assert_eq!(prompt_delta_significant(0.0, &[4.0; 3]), Some(false));
+    }
+
+    #[test]
+    fn bootstrap_separates_shift_from_noise() {
+        let shifted = [-1.0, -0.8, -1.2, -0.9, -1.1, -1.0];
+        let (lo, hi) = bootstrap_mean_ci(&shifted).unwrap();
+        assert!(hi < 0.0, "interval {:?} should exclude zero", (lo, hi));
+
+        let noise = [-1.0, 1.0, -0.5, 0.5, 0.0, -0.2, 0.3];
+        let (lo, hi) = bootstrap_mean_ci(&noise).unwrap();
+        assert!(lo < 0.0 && hi > 0.0);
+
+        assert_eq!(bootstrap_mean_ci(&shifted), bootstrap_mean_ci(&shifted));
+        assert!(bootstrap_mean_ci(&[1.0]).is_none());
+    }
+}
@@ -0,0 +1,253 @@
+//! Deterministic structural scoring against a prompt's ground truth.
+//!
+//! Complements the LLM judge with objective numbers: the generated workflow
+//! and the reference are each reduced to multisets of phase names, step
+//! types, and command strings, and compared by precision/recall. Same input,
+//! same score — useful for telling judge drift apart from real regressions.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Precision/recall for one facet. Both are `None` when neither side has
+/// anything to compare (e.g. a workflow with no command steps).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FacetMetrics {
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+    pub f1: Option<f64>,
+    pub expected: usize,
+    pub generated: usize,
+    pub matched: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructuralMetrics {
+    pub phases: FacetMetrics,
+    pub step_types: FacetMetrics,
+    pub commands: FacetMetrics,
+    /// Mean F1 over the facets that apply; `None` if none do.
+    pub f1: Option<f64>,
+}
+
+/// The comparable parts of a workflow.
+#[derive(Debug, Default, PartialEq)]
+struct Shape {
+    phases: Vec<String>,
+    step_types: Vec<String>,
+    commands: Vec<String>,
+}
+
+/// Score `generated` against `ground_truth` (both workflow JSON).
+pub fn score(generated: &str, ground_truth: &str) -> Result<StructuralMetrics, String> {
+    let generated: Value = serde_json::from_str(generated)
+        .map_err(|e| format!("generated workflow is not valid JSON: {}", e))?;
+    let ground_truth: Value = serde_json::from_str(ground_truth)
+        .map_err(|e| format!("ground truth is not valid JSON: {}", e))?;
+
+    let got = extract(&generated);
+    let want = extract(&ground_truth);
+
+    let phases = compare(&want.phases, &got.phases);
+    let step_types = compare(&want.step_types, &got.step_types);
+    let commands = compare(&want.commands, &got.commands);
+
+    let f1s: Vec<f64> = [&phases, &step_types, &commands]
+        .iter()
+        .filter_map(|m| m.f1)
+        .collect();
+    let f1 = (!f1s.is_empty()).then(|| f1s.iter().sum::<f64>() / f1s.len() as f64);
+
+    Ok(StructuralMetrics {
+        phases,
+        step_types,
+        commands,
+        f1,
+    })
+}
+
+/// Canonical form of a workflow's phases, step types and commands, in
+/// order. Two workflows with equal signatures differ only in names, IDs
+/// and prose. `None` for invalid JSON.
+pub fn signature(workflow_json: &str) -> Option<String> {
+    let workflow: Value = serde_json::from_str(workflow_json).ok()?;
+    let shape = extract(&workflow);
+    Some(format!(
+        "{}|{}|{}",
+        shape.phases.join(","),
+        shape.step_types.join(","),
+        shape.commands.join("\u{1f}")
+    ))
+}
+
+/// Accepts both workflow layouts the generator emits: per-phase step arrays
+/// (`setup_steps`, `verification_steps`, ...) and a `phases` array of
+/// `{name, steps}` objects. Only phases with at least one step count.
+fn extract(workflow: &Value) -> Shape {
+    let mut shape = Shape::default();
+    let Some(obj) = workflow.as_object() else {
+        return shape;
+    };
+
+    let mut add_phase = |name: &str, steps: &[Value]| {
+        if steps.is_empty() {
+            return;
+        }
+        shape.phases.push(name.to_string());
+        for step in steps {
+            let step_type = step
+                .get("type")
+                .or_else(|| step.get("step_type"))
+                .and_then(|v| v.as_str());
+            if let Some(t) = step_type {
+                shape.step_types.push(format!("{}:{}", name, t));
+            }
+            if let Some(cmd) = step.get("command").and_then(|v| v.as_str()) {
+                shape.commands.push(normalize_command(cmd));
+            }
+        }
+    };
+
+    for (key, value) in obj {
+        if let (Some(name), Some(steps)) = (key.strip_suffix("_steps"), value.as_array()) {
+            add_phase(name, steps);
+        }
+    }
+    if let Some(phases) = obj.get("phases").and_then(|v| v.as_array()) {
+        for phase in phases {
+            let name = phase.get("name").and_then(|v| v.as_str());
+            let steps = phase.get("steps").and_then(|v| v.as_array());
+            if let (Some(name), Some(steps)) = (name, steps) {
+                add_phase(name, steps);
+            }
+        }
+    }
+    shape
+}
+
+/// Collapse whitespace so `npm  run build` and `npm run build` match.
+fn normalize_command(cmd: &str) -> String {
+    cmd.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Multiset precision/recall of `generated` against `expected`.
+fn compare(expected: &[String], generated: &[String]) -> FacetMetrics {
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for item in expected {
+        *remaining.entry(item.as_str()).or_default() += 1;
+    }
+    let mut matched = 0;
+    for item in generated {
+        if let Some(n) = remaining.get_mut(item.as_str()).filter(|n| **n > 0) {
+            *n -= 1;
+            matched += 1;
+        }
+    }
+
+    let ratio = |num: usize, den: usize| (den > 0).then_some(num as f64 / den as f64);
+    let (precision, recall) = if expected.is_empty() && generated.is_empty() {
+        (None, None)
+    } else {
+        (
+            ratio(matched, generated.len()).or(Some(0.0)),
+            ratio(matched, expected.len()).or(Some(0.0)),
+        )
+    };
+    let f1 = match (precision, recall) {
+        (Some(p), Some(r)) if p + r > 0.0 => Some(2.0 * p * r / (p + r)),
+        (Some(_), Some(_)) => Some(0.0),
+        _ => None,
+    };
+
+    FacetMetrics {
+        precision,
+        recall,
+        f1,
+        expected: expected.len(),
+        generated: generated.len(),
+        matched,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REFERENCE: &str = r#"{
+        "setup_steps": [{"type": "command", "command": "npm install"}],
+        "verification_steps": [
+            {"type": "command", "command": "npm run build"},
+            {"type": "http_check", "url": "http://localhost:3000"}
+        ],
+        "agentic_steps": [],
+        "completion_steps": []
+    }"#;
+
+    #[test]
+    fn identical_workflows_score_perfectly() {
+        let m = score(REFERENCE, REFERENCE).unwrap();
+        assert_eq!(m.phases.f1, Some(1.0));
+        assert_eq!(m.step_types.f1, Some(1.0));
+        assert_eq!(m.commands.f1, Some(1.0));
+        assert_eq!(m.f1, Some(1.0));
+    }
+
+    #[test]
+    fn missing_and_extra_steps_split_precision_and_recall() {
+        let generated = r#"{
+            "setup_steps": [{"type": "command", "command": "npm   install"}],
+            "verification_steps": [
+                {"type": "command", "command": "npm test"}
+            ]
+        }"#;
+        let m = score(generated, REFERENCE).unwrap();
+        assert_eq!(m.phases.f1, Some(1.0));
+        // setup:command matches, verification:command matches, http_check missing
+        assert_eq!(m.step_types.precision, Some(1.0));
+        assert_eq!(m.step_types.recall, Some(2.0 / 3.0));
+        // whitespace-normalized "npm install" matches; "npm test" doesn't
+        assert_eq!(m.commands.matched, 1);
+        assert_eq!(m.commands.precision, Some(0.5));
+        assert_eq!(m.commands.recall, Some(0.5));
+    }
+
+    #[test]
+    fn phases_array_layout_is_understood() {
+        let generated = r#"{"phases": [
+            {"name": "setup", "steps": [{"type": "command", "command": "npm install"}]},
+            {"name": "verification", "steps": [
+                {"type": "command", "command": "npm run build"},
+                {"type": "http_check"}
+            ]}
+        ]}"#;
+        assert_eq!(score(generated, REFERENCE).unwrap().f1, Some(1.0));
+    }
+
+    #[test]
+    fn facets_absent_on_both_sides_are_not_scored() {
+        let m = score(r#"{"setup_steps": []}"#, r#"{}"#).unwrap();
+        assert_eq!(m.phases.precision, None);
+        assert_eq!(m.f1, None);
+    }
+
+    #[test]
+    fn signature_ignores_prose_but_not_commands() {
+        let renamed = r#"{
+            "name": "Build it",
+            "setup_steps": [{"type": "command", "command": "npm  install", "id": "a"}],
+            "verification_steps": [
+                {"type": "command", "command": "npm run build"},
+                {"type": "http_check", "url": "http://localhost:3001"}
+            ]
+        }"#;
+        assert_eq!(signature(renamed), signature(REFERENCE));
+        let changed = REFERENCE.replace("npm run build", "npm test");
+        assert_ne!(signature(&changed), signature(REFERENCE));
+        assert_eq!(signature("not json"), None);
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(score("not json", REFERENCE).is_err());
+    }
+}
@@ -33,6 +33,7 @@
 
 use std::path::Path;
 
+use chrono::{DateTime, FixedOffset};
 use serde::Serialize;
 use tokio::process::Command;
 use tracing::debug;
@@ -101,6 +102,23 @@ pub async fn contains_commit(repo_root: &Path, ancestor: &str, descendant: &str)
     )
 }
 
+/// Committer time of `sha` — `git show -s --format=%cI`.
+///
+/// `None` when the sha does not resolve in `repo_root` (or git is missing),
+/// like [`contains_commit`]. Used by `GET /velocity/compare?commit=` to place
+/// its before/after windows around the commit.
+pub async fn commit_time(repo_root: &Path, sha: &str) -> Option<DateTime<FixedOffset>> {
+    let out = git(
+        &["show", "-s", "--format=%cI", &format!("{sha}^{{commit}}")],
+        repo_root,
+        false,
+    )
+    .await
+    .map_err(|e| debug!("commit_time: {sha} not resolvable in {repo_root:?}: {e}"))
+    .ok()?;
+    DateTime::parse_from_rfc3339(out.trim()).ok()
+}
+
 /// Drift of a built tree's SHA relative to `origin/main`.
 ///
 /// Computed by [`origin_main_drift`]. Serialized verbatim into the
@@ -413,6 +431,21 @@ mod tests {
         assert_eq!(contains_commit(empty.path(), &base, &later).await, None);
     }
 
+    #[tokio::test]
+    async fn commit_time_resolves_known_shas_only() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+        let sha = init_repo_with_self_origin(dir);
+
+        let expected = git_out(dir, &["show", "-s", "--format=%cI", &sha]);
+        let time = commit_time(dir, &sha).await.expect("known sha");
+        assert_eq!(time, DateTime::parse_from_rfc3339(&expected).unwrap());
+        // Abbreviated shas resolve too.
+        assert_eq!(commit_time(dir, &sha[..7]).await, Some(time));
+
+        assert_eq!(commit_time(dir, &"0".repeat(40)).await, None);
+    }
+
     /// Up-to-date: built SHA == origin/main ⇒ behind_count 0, is_ancestor true.
     #[tokio::test]
     async fn drift_up_to_date() {
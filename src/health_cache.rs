@@ -120,6 +120,12 @@ pub struct CachedRunnerHealth {
     /// consumers can surface live values without touching the per-runner
     /// `WatchdogState` lock.
     pub watchdog: crate::routes::health::WatchdogHealth,
+    /// When this runner was last probed successfully. `None` if no probe has
+    /// completed within budget since the supervisor started.
+    pub probed_at: Option<DateTime<Utc>>,
+    /// True when this cycle's probe exceeded its budget and the fields above
+    /// are carried over from an earlier cycle (or are process-state only).
+    pub stale: bool,
 }
 
 /// Truncate a string to at most `max_chars` chars, adding an ellipsis marker
@@ -232,6 +238,150 @@ fn derive_runner_status(
     }
 }
 
+/// Wall-clock budget for probing a single runner (port check + `/health` +
+/// body fetch). Runners are probed concurrently, so one wedged runner costs
+/// at most this much per cycle instead of delaying every other target.
+const PROBE_BUDGET: Duration = Duration::from_secs(5);
+
+/// Probe one runner and build its snapshot. Also syncs the observed
+/// liveness back into `RunnerState` for user-managed runners.
+async fn probe_runner(
+    state: &SupervisorState,
+    managed: &crate::state::ManagedRunner,
+) -> (CachedRunnerHealth, CachedPortHealth) {
+    let runner_port = managed.config.port;
+    let kind = managed.config.kind();
+
+    let runner_port_open = port::is_port_listening(runner_port);
+    let probe = managed.config.health_probe();
+    let runner_responding = probe.check(runner_port).await;
+
+    let new_health = CachedPortHealth {
+        runner_port_open,
+        runner_responding,
+    };
+
+    // For user-managed runners (not temp, not named), the supervisor only
+    // observes. The `running` flag is initialized once at startup from
+    // port-in-use and would otherwise go stale. Sync it to the observed
+    // API responsiveness so the header status reflects reality.
+    let runner_id = &managed.config.id;
+    let is_supervisor_managed = is_temp_runner(runner_id) || is_named_runner(runner_id);
+    if !is_supervisor_managed {
+        let needs_pid_recovery = {
+            let mut runner_state = managed.runner.write().await;
+            if runner_state.running != runner_responding {
+                runner_state.running = runner_responding;
+                if !runner_responding {
+                    runner_state.pid = None;
+                }
+            }
+            runner_responding && runner_state.pid.is_none()
+        };
+        // Recover the PID for a re-discovered runner after a
+        // supervisor restart: the process is still the one
+        // listening on the port, so netstat tells us which PID
+        // to track. Netstat is ~100ms on Windows, so guard on
+        // `pid.is_none()` to keep this out of the steady state.
+        #[cfg(target_os = "windows")]
+        if needs_pid_recovery {
+            if let Some(pid) = crate::process::windows::find_pid_on_port(runner_port).await {
+                let mut runner_state = managed.runner.write().await;
+                if runner_state.pid.is_none() {
+                    runner_state.pid = Some(pid);
+                }
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        let _ = needs_pid_recovery;
+    }
+
+    // If the runner passes its health probe over HTTP, GET its /health to
+    // extract the application-layer signals (ui_error,
+    // derived_status, recent_crash). Older runners that don't
+    // emit these fields still parse cleanly thanks to
+    // `serde(default)` on RunnerHealthBody — the missing fields
+    // stay `None` and `derived_status` is inferred from process
+    // state.
+    let health_body = if runner_responding && probe.is_http() {
+        fetch_runner_health_body(runner_port).await
+    } else {
+        None
+    };
+    let ui_error = health_body.as_ref().and_then(|b| b.ui_error.clone());
+    let recent_crash = health_body.as_ref().and_then(|b| b.recent_crash.clone());
+
+    // Snapshot the crash-only watchdog state for SSE consumers.
+    let watchdog = {
+        let wd = managed.watchdog.read().await;
+        crate::routes::health::WatchdogHealth::from_state(
+            &wd,
+            crate::process::manager::crash_restart_globally_armed(&state.config),
+        )
+    };
+
+    let runner_state = managed.runner.read().await;
+    let derived_status = derive_runner_status(
+        runner_state.running,
+        runner_responding,
+        health_body.as_ref(),
+    );
+    let snapshot = CachedRunnerHealth {
+        id: managed.config.id.clone(),
+        name: managed.config.name.clone(),
+        port: runner_port,
+        kind,
+        running: runner_state.running,
+        pid: runner_state.pid,
+        api_responding: runner_responding,
+        ui_error,
+        recent_crash,
+        derived_status,
+        watchdog,
+        probed_at: Some(Utc::now()),
+        stale: false,
+    };
+    (snapshot, new_health)
+}
+
+/// Snapshot for a runner whose probe blew its budget. Reuses the last good
+/// snapshot marked stale; a runner that has never completed a probe gets a
+/// process-state-only snapshot with `probed_at: None`.
+async fn stale_snapshot(
+    state: &SupervisorState,
+    managed: &crate::state::ManagedRunner,
+    previous: Option<&CachedRunnerHealth>,
+) -> CachedRunnerHealth {
+    if let Some(prev) = previous {
+        let mut snapshot = prev.clone();
+        snapshot.stale = true;
+        return snapshot;
+    }
+    let watchdog = {
+        let wd = managed.watchdog.read().await;
+        crate::routes::health::WatchdogHealth::from_state(
+            &wd,
+            crate::process::manager::crash_restart_globally_armed(&state.config),
+        )
+    };
+    let runner_state = managed.runner.read().await;
+    CachedRunnerHealth {
+        id: managed.config.id.clone(),
+        name: managed.config.name.clone(),
+        port: managed.config.port,
+        kind: managed.config.kind(),
+        running: runner_state.running,
+        pid: runner_state.pid,
+        api_responding: false,
+        ui_error: None,
+        recent_crash: None,
+        derived_status: derive_runner_status(runner_state.running, false, None),
+        watchdog,
+        probed_at: None,
+        stale: true,
+    }
+}
+
 pub fn spawn_health_cache_refresher(state: Arc<SupervisorState>) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         let mut ticker = interval(Duration::from_secs(2));
@@ -249,113 +399,61 @@ pub fn spawn_health_cache_refresher(state: Arc<SupervisorState>) -> tokio::task:
             // Refresh health for all managed runners
             let runners = state.get_all_runners().await;
 
+            // Previous snapshots, reused (marked stale) for runners whose
+            // probe exceeds PROBE_BUDGET this cycle.
+            let previous: std::collections::HashMap<String, CachedRunnerHealth> = state
+                .cached_runner_health
+                .read()
+                .await
+                .iter()
+                .map(|h| (h.id.clone(), h.clone()))
+                .collect();
+
+            // Probe every runner concurrently, each under its own budget.
+            let outcomes = futures::future::join_all(runners.iter().map(|managed| {
+                let state = &state;
+                async move {
+                    tokio::time::timeout(PROBE_BUDGET, probe_runner(state, managed))
+                        .await
+                        .ok()
+                }
+            }))
+            .await;
+
             // Primary runner's health also goes into the legacy cached_health
             let mut primary_health = CachedPortHealth::default();
             let mut runner_snapshots = Vec::with_capacity(runners.len());
 
-            for managed in &runners {
-                let runner_port = managed.config.port;
-                let kind = managed.config.kind();
-                let is_primary = kind.is_primary();
+            for (managed, outcome) in runners.iter().zip(outcomes) {
+                let is_primary = managed.config.kind().is_primary();
+                match outcome {
+                    Some((snapshot, new_health)) => {
+                        runner_snapshots.push(snapshot);
 
-                let runner_port_open = port::is_port_listening(runner_port);
-                let runner_responding = port::is_runner_responding(runner_port).await;
-
-                let new_health = CachedPortHealth {
-                    runner_port_open,
-                    runner_responding,
-                };
+                        // Update per-runner cache
+                        let mut cache = managed.cached_health.write().await;
+                        *cache = new_health.clone();
+                        drop(cache);
 
-                // For user-managed runners (not temp, not named), the supervisor only
-                // observes. The `running` flag is initialized once at startup from
-                // port-in-use and would otherwise go stale. Sync it to the observed
-                // API responsiveness so the header status reflects reality.
-                let runner_id = &managed.config.id;
-                let is_supervisor_managed = is_temp_runner(runner_id) || is_named_runner(runner_id);
-                if !is_supervisor_managed {
-                    let needs_pid_recovery = {
-                        let mut runner_state = managed.runner.write().await;
-                        if runner_state.running != runner_responding {
-                            runner_state.running = runner_responding;
-                            if !runner_responding {
-                                runner_state.pid = None;
-                            }
+                        if is_primary {
+                            primary_health = new_health;
                         }
-                        runner_responding && runner_state.pid.is_none()
-                    };
-                    // Recover the PID for a re-discovered runner after a
-                    // supervisor restart: the process is still the one
-                    // listening on the port, so netstat tells us which PID
-                    // to track. Netstat is ~100ms on Windows, so guard on
-                    // `pid.is_none()` to keep this out of the steady state.
-                    #[cfg(target_os = "windows")]
-                    if needs_pid_recovery {
-                        if let Some(pid) =
-                            crate::process::windows::find_pid_on_port(runner_port).await
-                        {
-                            let mut runner_state = managed.runner.write().await;
-                            if runner_state.pid.is_none() {
-                                runner_state.pid = Some(pid);
-                            }
+                    }
+                    None => {
+                        tracing::warn!(
+                            "Health probe for runner {} (port {}) exceeded {:?}; keeping stale snapshot",
+                            managed.config.id,
+                            managed.config.port,
+                            PROBE_BUDGET
+                        );
+                        runner_snapshots.push(
+                            stale_snapshot(&state, managed, previous.get(&managed.config.id)).await,
+                        );
+                        // Per-runner cache keeps its last value
+                        if is_primary {
+                            primary_health = managed.cached_health.read().await.clone();
                         }
                     }
-                    #[cfg(not(target_os = "windows"))]
-                    let _ = needs_pid_recovery;
-                }
-
-                // If the runner's TCP port is responsive, GET its /health to
-                // extract the application-layer signals (ui_error,
-                // derived_status, recent_crash). Older runners that don't
-                // emit these fields still parse cleanly thanks to
-                // `serde(default)` on RunnerHealthBody — the missing fields
-                // stay `None` and `derived_status` is inferred from process
-                // state.
-                let health_body = if runner_responding {
-                    fetch_runner_health_body(runner_port).await
-                } else {
-                    None
-                };
-                let ui_error = health_body.as_ref().and_then(|b| b.ui_error.clone());
-                let recent_crash = health_body.as_ref().and_then(|b| b.recent_crash.clone());
-
-                // Snapshot the crash-only watchdog state for SSE consumers.
-                let watchdog = {
-                    let wd = managed.watchdog.read().await;
-                    crate::routes::health::WatchdogHealth::from_state(
-                        &wd,
-                        crate::process::manager::crash_restart_globally_armed(&state.config),
-                    )
-                };
-
-                // Build runner snapshot for SSE consumers
-                let runner_state = managed.runner.read().await;
-                let derived_status = derive_runner_status(
-                    runner_state.running,
-                    runner_responding,
-                    health_body.as_ref(),
-                );
-                runner_snapshots.push(CachedRunnerHealth {
-                    id: managed.config.id.clone(),
-                    name: managed.config.name.clone(),
-                    port: runner_port,
-                    kind: kind.clone(),
-                    running: runner_state.running,
-                    pid: runner_state.pid,
-                    api_responding: runner_responding,
-                    ui_error,
-                    recent_crash,
-                    derived_status,
-                    watchdog,
-                });
-                drop(runner_state);
-
-                // Update per-runner cache
-                let mut cache = managed.cached_health.write().await;
-                *cache = new_health.clone();
-                drop(cache);
-
-                if is_primary {
-                    primary_health = new_health;
                 }
             }
 
@@ -0,0 +1,237 @@
+//! Start-request queue for eval runs, velocity test runs, and the velocity
+//! improvement loop.
+//!
+//! Each of those subsystems guards itself with a `running` flag in its
+//! `*State`. A start request that finds the flag set normally gets
+//! "already running"; with `?queue=true` it is parked here instead and the
+//! owning route module's dispatcher launches it once the flag clears.
+//! Higher `priority` runs first; equal priorities run in arrival order.
+
+use serde::{Deserialize, Serialize};
+
+use crate::evaluation::engine::EvalRunOptions;
+use crate::velocity_improvement::VelocityImprovementConfig;
+use crate::velocity_tests::engine::VelocityRunOptions;
+
+/// Upper bound on parked jobs across all kinds.
+pub const MAX_QUEUED_JOBS: usize = 32;
+
+/// How often each dispatcher checks whether its subsystem has gone idle.
+pub const DISPATCH_INTERVAL_SECS: u64 = 5;
+
+/// Query parameters accepted by the queue-aware start endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub struct QueueParams {
+    /// Enqueue instead of failing when the subsystem is busy.
+    #[serde(default)]
+    pub queue: bool,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Eval,
+    VelocityTests,
+    VelocityImprovement,
+}
+
+/// Everything needed to launch the job later.
+#[derive(Debug, Clone)]
+pub enum JobRequest {
+    Eval(EvalRunOptions),
+    VelocityTests(VelocityRunOptions),
+    VelocityImprovement(VelocityImprovementConfig),
+}
+
+impl JobRequest {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            JobRequest::Eval(_) => JobKind::Eval,
+            JobRequest::VelocityTests(_) => JobKind::VelocityTests,
+            JobRequest::VelocityImprovement(_) => JobKind::VelocityImprovement,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedJob {
+    pub id: String,
+    pub priority: i32,
+    pub enqueued_at: String,
+    pub request: JobRequest,
+}
+
+/// Public view of a queued job. `position` is 1-based among jobs of the same
+/// kind, i.e. how many launches of that subsystem happen before this one.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueEntry {
+    pub id: String,
+    pub kind: JobKind,
+    pub priority: i32,
+    pub enqueued_at: String,
+    pub position: usize,
+}
+
+/// Jobs kept in dispatch order: priority descending, then FIFO.
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    jobs: Vec<QueuedJob>,
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Park a request. Returns the job's entry, or an error when the queue
+    /// is full.
+    pub fn enqueue(&mut self, request: JobRequest, priority: i32) -> Result<QueueEntry, String> {
+        if self.jobs.len() >= MAX_QUEUED_JOBS {
+            return Err(format!(
+                "Job queue is full ({} jobs); try again later",
+                MAX_QUEUED_JOBS
+            ));
+        }
+        let job = QueuedJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            priority,
+            enqueued_at: chrono::Utc::now().to_rfc3339(),
+            request,
+        };
+        let id = job.id.clone();
+        // Insert after every job with priority >= ours so equal priorities
+        // stay FIFO.
+        let idx = self
+            .jobs
+            .iter()
+            .position(|j| j.priority < priority)
+            .unwrap_or(self.jobs.len());
+        self.jobs.insert(idx, job);
+        Ok(self.get(&id).expect("just inserted"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<QueueEntry> {
+        self.entries().into_iter().find(|e| e.id == id)
+    }
+
+    pub fn entries(&self) -> Vec<QueueEntry> {
+        let mut seen: Vec<JobKind> = Vec::new();
+        self.jobs
+            .iter()
+            .map(|job| {
+                let kind = job.request.kind();
+                seen.push(kind);
+                QueueEntry {
+                    id: job.id.clone(),
+                    kind,
+                    priority: job.priority,
+                    enqueued_at: job.enqueued_at.clone(),
+                    position: seen.iter().filter(|k| **k == kind).count(),
+                }
+            })
+            .collect()
+    }
+
+    /// Remove a queued job. Returns `false` if it was not queued (already
+    /// launched, cancelled, or never existed).
+    pub fn cancel(&mut self, id: &str) -> bool {
+        let before = self.jobs.len();
+        self.jobs.retain(|j| j.id != id);
+        self.jobs.len() != before
+    }
+
+    /// Put back a job that was popped but couldn't launch, ahead of others
+    /// at the same priority so it keeps its turn (and its id).
+    pub fn requeue(&mut self, job: QueuedJob) {
+        let idx = self
+            .jobs
+            .iter()
+            .position(|j| j.priority <= job.priority)
+            .unwrap_or(self.jobs.len());
+        self.jobs.insert(idx, job);
+    }
+
+    /// Take the next job of `kind`, if any.
+    pub fn pop_next(&mut self, kind: JobKind) -> Option<QueuedJob> {
+        let idx = self.jobs.iter().position(|j| j.request.kind() == kind)?;
+        Some(self.jobs.remove(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_priority_dispatches_first_and_ties_stay_fifo() {
+        let mut q = JobQueue::new();
+        let a = q
+            .enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .unwrap();
+        let b = q
+            .enqueue(JobRequest::VelocityTests(Default::default()), 5)
+            .unwrap();
+        let c = q
+            .enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .unwrap();
+
+        assert_eq!(b.position, 1);
+        assert_eq!(q.get(&a.id).unwrap().position, 2);
+        assert_eq!(c.position, 3);
+
+        let order: Vec<String> = std::iter::from_fn(|| q.pop_next(JobKind::VelocityTests))
+            .map(|j| j.id)
+            .collect();
+        assert_eq!(order, vec![b.id, a.id, c.id]);
+    }
+
+    #[test]
+    fn positions_are_per_kind() {
+        let mut q = JobQueue::new();
+        q.enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .unwrap();
+        let eval = q
+            .enqueue(JobRequest::Eval(EvalRunOptions::default()), 0)
+            .unwrap();
+        assert_eq!(eval.position, 1);
+        assert!(q.pop_next(JobKind::VelocityImprovement).is_none());
+    }
+
+    #[test]
+    fn cancel_removes_only_queued_jobs() {
+        let mut q = JobQueue::new();
+        let a = q
+            .enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .unwrap();
+        assert!(q.cancel(&a.id));
+        assert!(!q.cancel(&a.id));
+        assert!(q.entries().is_empty());
+    }
+
+    #[test]
+    fn requeue_keeps_turn_within_priority() {
+        let mut q = JobQueue::new();
+        let a = q
+            .enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .unwrap();
+        q.enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .unwrap();
+        let job = q.pop_next(JobKind::VelocityTests).unwrap();
+        q.requeue(job);
+        assert_eq!(q.get(&a.id).unwrap().position, 1);
+    }
+
+    #[test]
+    fn enqueue_rejects_when_full() {
+        let mut q = JobQueue::new();
+        for _ in 0..MAX_QUEUED_JOBS {
+            q.enqueue(JobRequest::VelocityTests(Default::default()), 0)
+                .unwrap();
+        }
+        assert!(q
+            .enqueue(JobRequest::VelocityTests(Default::default()), 0)
+            .is_err());
+    }
+}
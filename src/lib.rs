@@ -18,17 +18,25 @@ pub mod footprint;
 pub mod fs_atomic;
 pub mod git_provenance;
 pub mod health_cache;
+pub mod job_queue;
 pub mod log_capture;
+pub mod metrics;
 pub mod otel;
 pub mod pii_scrub;
 pub mod process;
 pub mod reapi;
+pub mod registration;
 pub mod routes;
+pub mod run_environment;
 pub mod sdk_features;
+pub mod self_profile;
+pub mod self_update;
 pub mod server;
 pub mod settings;
 pub mod spawn_worktree;
+pub mod stability;
 pub mod state;
+pub mod stream_clients;
 // Phase 4.1 (`plans/2026-05-21-coordination-improvements.md`): per-machine
 // tree-sitter symbol watcher daemon. Reports `ClaimKind::Symbol` claims to
 // coord via the existing `/claims/{acquire,release}` endpoints. Shipped as
@@ -38,7 +46,10 @@ pub mod state;
 pub mod symbol_watcher;
 pub mod trace_propagation;
 pub mod velocity;
+pub mod velocity_ab;
+pub mod velocity_checkpoint;
 pub mod velocity_improvement;
+pub mod velocity_improvement_progress;
 pub mod velocity_layer;
 pub mod velocity_tests;
 #[cfg(windows)]
@@ -28,20 +28,31 @@ mod flywheel;
 mod fs_atomic;
 mod git_provenance;
 mod health_cache;
+mod job_queue;
 mod log_capture;
+mod metrics;
 mod otel;
 mod pii_scrub;
 mod process;
 mod reapi;
+mod registration;
 mod routes;
+mod run_environment;
 mod sdk_features;
+mod self_profile;
+mod self_update;
 mod server;
 mod settings;
 mod spawn_worktree;
+mod stability;
 mod state;
+mod stream_clients;
 mod trace_propagation;
 mod velocity;
+mod velocity_ab;
+mod velocity_checkpoint;
 mod velocity_improvement;
+mod velocity_improvement_progress;
 mod velocity_layer;
 mod velocity_tests;
 #[cfg(windows)]
@@ -199,6 +210,39 @@ async fn main() -> anyhow::Result<()> {
     // through the same persistent file writer attached above.
     state.flush_pending_startup_logs().await;
 
+    // Open this process's stability session (closing any session a killed
+    // or crashed predecessor left open as unclean) and keep its heartbeat
+    // fresh so an unclean end is timestamped to within a minute.
+    if let Some(db) = state.stability.clone() {
+        match db.begin_session() {
+            Ok(0) => {}
+            Ok(n) => {
+                let msg = format!(
+                    "{} previous supervisor session(s) ended uncleanly (killed or crashed)",
+                    n
+                );
+                warn!("{}", msg);
+                state
+                    .logs
+                    .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+                    .await;
+            }
+            Err(e) => warn!("Failed to record supervisor session start: {}", e),
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(
+                stability::HEARTBEAT_INTERVAL_SECS,
+            ));
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = db.heartbeat() {
+                    warn!("Failed to record supervisor heartbeat: {}", e);
+                }
+            }
+        });
+    }
+
     // Visibility for the debug-endpoints gate. When enabled, log loudly so
     // an operator tailing the supervisor log can see that
     // `/control/dev/*` are accepting requests; when disabled (the default),
@@ -220,6 +264,11 @@ async fn main() -> anyhow::Result<()> {
     {
         let path = settings::settings_path(&state.config);
         let saved = settings::load_settings(&path);
+        if saved.restart_policy.validate().is_ok() {
+            *state.restart_policy.write().await = saved.restart_policy;
+        } else {
+            warn!("Ignoring invalid saved restart policy; using the default");
+        }
         let mut ai = state.ai.write().await;
         if let Some(provider) = saved.ai_provider {
             ai.provider = provider;
@@ -290,6 +339,9 @@ async fn main() -> anyhow::Result<()> {
         let state_clone = state.clone();
         tokio::spawn(async move {
             process::manager::cleanup_orphaned_runners(&state_clone).await;
+            // Only once user runners have been marked running, so their
+            // processes aren't reported.
+            process::orphan_cleanup::report_at_startup(&state_clone).await;
         });
     }
 
@@ -444,6 +496,16 @@ async fn main() -> anyhow::Result<()> {
         });
     }
 
+    // Sample CPU/memory of runners, Expo and the velocity fix agent for
+    // `GET /processes/{name}/resources`, warning when one goes over the
+    // memory ceiling. See `process::resources`.
+    {
+        let state_clone = state.clone();
+        tokio::spawn(async move {
+            process::resources::sample_loop(state_clone).await;
+        });
+    }
+
     // Build and start HTTP server (with SO_REUSEADDR to handle lingering sockets)
     let router = server::build_router(state.clone());
     let bind_addr: std::net::SocketAddr = format!("127.0.0.1:{}", port).parse()?;
@@ -478,7 +540,14 @@ async fn main() -> anyhow::Result<()> {
             }
         }
     };
+    // `--port 0` binds an ephemeral port; everything below uses the real one.
+    let port = listener.local_addr()?.port();
     info!("Supervisor listening on http://127.0.0.1:{}", port);
+    let service_reg = registration::Registration::for_this_process(port, &state.build_id);
+    match registration::write(&state.config.dev_logs_dir, &service_reg) {
+        Ok(path) => info!("Wrote service registration to {}", path.display()),
+        Err(e) => warn!("Failed to write service registration file: {}", e),
+    }
 
     // Spawn the ambient dashboard WebView2 window (item B of the post-3J UI
     // Bridge improvements plan). Runs on its own dedicated OS thread so it
@@ -572,6 +641,12 @@ async fn main() -> anyhow::Result<()> {
     serve_future.await?;
 
     info!("Supervisor shutting down");
+    if let Some(db) = &state.stability {
+        if let Err(e) = db.end_session() {
+            warn!("Failed to record clean shutdown: {}", e);
+        }
+    }
+    registration::remove_if_owned(&state.config.dev_logs_dir, service_reg.pid);
 
     // Hard-exit safety net: arm a watchdog that force-exits the process if
     // post-shutdown cleanup hangs for more than `HARD_EXIT_DEADLINE_SECS`
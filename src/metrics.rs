@@ -0,0 +1,165 @@
+//! Prometheus text exposition for `GET /metrics`.
+//!
+//! A minimal writer for the text format (version 0.0.4) — the supervisor
+//! exposes a few dozen series, gathered fresh on every scrape, so a metrics
+//! registry would only add state to keep in sync. Gathering lives in
+//! [`crate::routes::metrics`].
+
+use std::fmt::Write;
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds (seconds) of the eval run duration histogram.
+pub const EVAL_DURATION_BUCKETS_SECS: &[f64] =
+    &[60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0];
+
+/// Upper bounds (milliseconds) of the velocity span latency histogram.
+pub const SPAN_LATENCY_BUCKETS_MS: &[f64] = &[
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+#[derive(Debug, Clone, Copy)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Histogram => "histogram",
+        }
+    }
+}
+
+/// Observations bucketed against fixed upper bounds; `counts[i]` is the
+/// number of observations `<= bounds[i]` (cumulative, as exposed).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    pub bounds: Vec<f64>,
+    pub counts: Vec<u64>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(self.counts.iter_mut()) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Default)]
+pub struct MetricsWriter {
+    out: String,
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_labels(pairs: &[(&str, &str)]) -> String {
+    if pairs.is_empty() {
+        return String::new();
+    }
+    let inner: Vec<String> = pairs
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+        .collect();
+    format!("{{{}}}", inner.join(","))
+}
+
+fn number(value: f64) -> String {
+    if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+impl MetricsWriter {
+    /// `# HELP` / `# TYPE` header; write once before the metric's samples.
+    pub fn header(&mut self, name: &str, kind: MetricType, help: &str) {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help.replace('\n', " "));
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind.as_str());
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let _ = writeln!(
+            self.out,
+            "{}{} {}",
+            name,
+            format_labels(labels),
+            number(value)
+        );
+    }
+
+    /// Header plus a single unlabelled sample.
+    pub fn single(&mut self, name: &str, kind: MetricType, help: &str, value: f64) {
+        self.header(name, kind, help);
+        self.sample(name, &[], value);
+    }
+
+    /// `_bucket`, `_sum` and `_count` samples of one histogram series.
+    pub fn histogram(&mut self, name: &str, labels: &[(&str, &str)], h: &Histogram) {
+        let bucket = format!("{}_bucket", name);
+        for (bound, count) in h.bounds.iter().zip(&h.counts) {
+            let le = number(*bound);
+            let mut with_le = labels.to_vec();
+            with_le.push(("le", &le));
+            self.sample(&bucket, &with_le, *count as f64);
+        }
+        let mut with_le = labels.to_vec();
+        with_le.push(("le", "+Inf"));
+        self.sample(&bucket, &with_le, h.count as f64);
+        self.sample(&format!("{}_sum", name), labels, h.sum);
+        self.sample(&format!("{}_count", name), labels, h.count as f64);
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_histogram_and_escapes_labels() {
+        let mut h = Histogram::new(&[1.0, 5.0]);
+        for v in [0.5, 3.0, 9.0] {
+            h.observe(v);
+        }
+        let mut w = MetricsWriter::default();
+        w.header("lat", MetricType::Histogram, "Latency");
+        w.histogram("lat", &[("service", "a\"b")], &h);
+        let text = w.finish();
+        assert!(text.starts_with("# HELP lat Latency\n# TYPE lat histogram\n"));
+        assert!(text.contains("lat_bucket{service=\"a\\\"b\",le=\"1\"} 1\n"));
+        assert!(text.contains("lat_bucket{service=\"a\\\"b\",le=\"5\"} 2\n"));
+        assert!(text.contains("lat_bucket{service=\"a\\\"b\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("lat_sum{service=\"a\\\"b\"} 12.5\n"));
+        assert!(text.contains("lat_count{service=\"a\\\"b\"} 3\n"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Forensics bundle written when a runner crashes.
+//!
+//! Scrollback is gone by the time anyone looks into a crash, so when a
+//! supervised runner exits non-zero (or vanishes) without a stop having been
+//! requested, the exit monitor writes `{dev_logs_dir}/crashes/{timestamp}/`:
+//!
+//! - `crash.json` — runner id/name, pid, exit code and when it happened
+//! - `logs.txt` — the last [`log_tail_bytes`] of the runner's stdout/stderr
+//! - `ports.json` — the runner's ports and whether each is still held
+//! - `resources.json` — its CPU/memory samples leading up to the exit
+//! - `diagnostics.json` — the most recent diagnostics events
+//!
+//! The `runner_crashed` diagnostics event carries the bundle's path.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::log_capture::{LogEntry, LogLevel};
+use crate::state::{ManagedRunner, SharedState};
+
+pub const LOG_KB_ENV: &str = "QONTINUI_SUPERVISOR_CRASH_LOG_KB";
+
+pub const DEFAULT_LOG_KB: u64 = 256;
+
+/// Diagnostics events copied into a bundle.
+const DIAGNOSTICS_LIMIT: usize = 50;
+
+pub fn log_tail_bytes() -> usize {
+    let kb = std::env::var(LOG_KB_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_LOG_KB);
+    (kb * 1024) as usize
+}
+
+pub fn crashes_dir(dev_logs_dir: &Path) -> PathBuf {
+    dev_logs_dir.join("crashes")
+}
+
+#[derive(Debug, Serialize)]
+struct CrashSummary<'a> {
+    runner_id: &'a str,
+    runner_name: &'a str,
+    pid: Option<u32>,
+    exit_code: Option<i32>,
+    crashed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct PortState {
+    role: &'static str,
+    port: u16,
+    listening: bool,
+}
+
+/// Format `entries` one per line, keeping only the newest lines that fit in
+/// `max_bytes`.
+pub fn log_tail(entries: &[LogEntry], max_bytes: usize) -> String {
+    let mut lines = Vec::new();
+    let mut size = 0;
+    for e in entries.iter().rev() {
+        let level = match e.level {
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Debug => "DEBUG",
+        };
+        let line = format!("{} {} {}\n", e.timestamp.to_rfc3339(), level, e.message);
+        if size + line.len() > max_bytes {
+            break;
+        }
+        size += line.len();
+        lines.push(line);
+    }
+    lines.reverse();
+    lines.concat()
+}
+
+async fn write_json(dir: &Path, name: &str, value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    tokio::fs::write(dir.join(name), json)
+        .await
+        .map_err(|e| format!("failed to write {}: {}", name, e))
+}
+
+/// Write a bundle for `managed`, which just exited; returns its directory.
+pub async fn capture(
+    state: &SharedState,
+    managed: &ManagedRunner,
+    pid: Option<u32>,
+    exit_code: Option<i32>,
+) -> Result<PathBuf, String> {
+    let crashed_at = Utc::now();
+    let dir = crashes_dir(&state.config.dev_logs_dir)
+        .join(crashed_at.format("%Y%m%dT%H%M%S%.3fZ").to_string());
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+
+    let config = &managed.config;
+    write_json(
+        &dir,
+        "crash.json",
+        &CrashSummary {
+            runner_id: &config.id,
+            runner_name: &config.name,
+            pid,
+            exit_code,
+            crashed_at,
+        },
+    )
+    .await?;
+
+    let entries = managed.logs.history().await;
+    tokio::fs::write(dir.join("logs.txt"), log_tail(&entries, log_tail_bytes()))
+        .await
+        .map_err(|e| format!("failed to write logs.txt: {}", e))?;
+
+    let ports: Vec<PortState> = [
+        ("api", Some(config.port)),
+        ("restate_ingress", config.restate_ingress_port),
+        ("restate_admin", config.restate_admin_port),
+        ("restate_service", config.restate_service_port),
+    ]
+    .into_iter()
+    .filter_map(|(role, port)| {
+        port.map(|port| PortState {
+            role,
+            port,
+            listening: crate::process::port::is_port_listening(port),
+        })
+    })
+    .collect();
+    write_json(&dir, "ports.json", &ports).await?;
+
+    let resources = state.process_resources.read().await.get(
+        &config.id,
+        crate::process::resources::memory_ceiling_bytes(),
+    );
+    write_json(&dir, "resources.json", &resources).await?;
+
+    let events = state
+        .diagnostics
+        .read()
+        .await
+        .events(DIAGNOSTICS_LIMIT, None);
+    write_json(&dir, "diagnostics.json", &events).await?;
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_capture::LogSource;
+
+    fn entry(message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: Utc::now(),
+            source: LogSource::Runner,
+            level: LogLevel::Error,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn log_tail_keeps_the_newest_lines_that_fit() {
+        let entries = [entry("first"), entry("second"), entry("panicked at main")];
+        let all = log_tail(&entries, usize::MAX);
+        assert_eq!(all.lines().count(), 3);
+
+        let last_line = all.lines().last().unwrap().len() + 1;
+        let tail = log_tail(&entries, last_line + 1);
+        assert_eq!(tail.lines().count(), 1);
+        assert!(tail.ends_with("ERROR panicked at main\n"));
+        assert_eq!(log_tail(&entries, 0), "");
+    }
+}
@@ -7,16 +7,20 @@ use tracing::{debug, error, info, warn};
 use crate::config::{RUNNER_GRACEFUL_STOP_REQUEST_TIMEOUT_MS, RUNNER_GRACEFUL_STOP_TIMEOUT_MS};
 use crate::diagnostics::{DiagnosticEventKind, RestartSource};
 use crate::error::SupervisorError;
+use crate::evaluation::RunnerRestartPolicy;
 use crate::log_capture::{LogLevel, LogSource};
 use crate::process::env_forwarders;
 use crate::process::instance_config_dir;
 use crate::process::port::wait_for_port_free;
+use crate::process::restart_policy::RestartPolicy;
+use crate::process::watchdog_alert::{self, WatchdogAlertKind};
 #[cfg(target_os = "windows")]
 use crate::process::windows::{
     kill_by_pid, kill_by_port, remove_instance_config_dir, remove_runner_app_data_dirs,
     remove_webview2_user_data_folder, webview2_user_data_folder,
 };
 use crate::state::{ManagedRunner, SharedState};
+use crate::trace_propagation::TraceparentExt;
 
 // =============================================================================
 // Runner Category Helpers
@@ -1153,10 +1157,11 @@ pub async fn start_managed_runner(
     }
 
     {
-        let runner = managed.runner.read().await;
+        let mut runner = managed.runner.write().await;
         if runner.running {
             return Err(SupervisorError::RunnerAlreadyRunning);
         }
+        runner.starting = true;
     }
 
     let is_primary = managed.config.kind().is_primary();
@@ -1175,7 +1180,13 @@ pub async fn start_managed_runner(
     let SpawnResult {
         mut child,
         panic_log_dir,
-    } = start_exe_mode_for_runner(state, managed).await?;
+    } = match start_exe_mode_for_runner(state, managed).await {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            managed.runner.write().await.starting = false;
+            return Err(e);
+        }
+    };
 
     let pid = child.id();
     info!(
@@ -1307,6 +1318,7 @@ pub async fn start_managed_runner(
         let mut runner = managed.runner.write().await;
         runner.process = Some(child);
         runner.running = true;
+        runner.starting = false;
         runner.started_at = Some(chrono::Utc::now());
         runner.pid = pid;
         runner.stop_requested = false;
@@ -1998,17 +2010,9 @@ fn decide_stop_reap(attempt: u32, port_in_use: bool) -> StopReapOutcome {
 // Crash-Only Ambient Watchdog
 // =============================================================================
 
-/// Exponential backoff before each auto-restart attempt within one rolling
-/// crash window: 5s → 30s → 120s.
-const CRASH_RESTART_BACKOFF_SECS: [u64; 3] = [5, 30, 120];
-
-/// Maximum auto-restarts per rolling [`CRASH_RESTART_WINDOW_SECS`] window
-/// before the watchdog disarms itself and demands an operator.
-const CRASH_RESTART_MAX_PER_WINDOW: usize = 3;
-
-/// Rolling window (seconds) over which [`CRASH_RESTART_MAX_PER_WINDOW`] is
-/// evaluated, using `WatchdogState::crash_history` timestamps.
-const CRASH_RESTART_WINDOW_SECS: i64 = 30 * 60;
+// Backoff and the crash-loop guard come from the runtime-editable
+// `SupervisorState::restart_policy` (see `process::restart_policy`); the
+// rolling window is evaluated over `WatchdogState::crash_history`.
 
 /// `disabled_reason` set when the crash-loop guard trips. `enabled` stays
 /// true so the operator's intent remains visible — the reason field is what
@@ -2016,6 +2020,10 @@ const CRASH_RESTART_WINDOW_SECS: i64 = 30 * 60;
 /// `POST /runners/{id}/watchdog {reset_attempts: true}`).
 pub const CRASH_LOOP_DISABLED_REASON: &str = "crash loop — operator required";
 
+/// Upper bound on how long a [`RunnerRestartPolicy::Defer`] eval run can hold
+/// a watchdog restart. A wedged run must not keep the primary down forever.
+const EVAL_RESTART_DEFER_MAX_SECS: u64 = 30 * 60;
+
 /// Env kill-switch: `QONTINUI_SUPERVISOR_NO_CRASH_RESTART=1` disables all
 /// crash-only auto-restarts without a rebuild or code change.
 pub fn crash_restart_env_disabled() -> bool {
@@ -2070,6 +2078,7 @@ enum CrashRestartDecision {
 /// Priority is intentional: provenance and operator intent always win over
 /// arming state, and the crash-loop guard is evaluated last so `Disarm`
 /// only fires for an exit that would otherwise have restarted.
+#[allow(clippy::too_many_arguments)]
 fn decide_crash_restart(
     had_child_handle: bool,
     stop_requested: bool,
@@ -2078,6 +2087,7 @@ fn decide_crash_restart(
     per_runner_enabled: bool,
     already_disarmed: bool,
     restarts_in_window: usize,
+    policy: &RestartPolicy,
 ) -> CrashRestartDecision {
     if !had_child_handle {
         return CrashRestartDecision::SkipNoChildHandle;
@@ -2097,12 +2107,12 @@ fn decide_crash_restart(
     if already_disarmed {
         return CrashRestartDecision::SkipDisarmed;
     }
-    if restarts_in_window >= CRASH_RESTART_MAX_PER_WINDOW {
+    if restarts_in_window >= policy.crash_loop_threshold {
         return CrashRestartDecision::Disarm;
     }
     CrashRestartDecision::Restart {
         attempt: restarts_in_window as u32 + 1,
-        delay_secs: CRASH_RESTART_BACKOFF_SECS[restarts_in_window],
+        delay_secs: policy.delay_secs(restarts_in_window),
     }
 }
 
@@ -2112,6 +2122,71 @@ fn decide_crash_restart(
 /// a `Restart` — spawns a detached task that waits out the backoff and
 /// funnels through [`start_runner_by_id`] (so the provenance start gate
 /// applies). Never blocks the exit monitor.
+/// Apply the eval subsystem's [`RunnerRestartPolicy`] before the watchdog
+/// restarts the primary runner. Returns once the restart may proceed.
+async fn coordinate_eval_before_restart(state: &SharedState, runner_name: &str) {
+    let policy = {
+        let mut eval = state.evaluation.write().await;
+        if !eval.running {
+            return;
+        }
+        let policy = eval.runner_restart_policy;
+        if policy == RunnerRestartPolicy::Abort {
+            eval.abort_reason = Some(format!(
+                "runner '{}' crashed; run aborted so the watchdog could restart it",
+                runner_name
+            ));
+            if let Some(tx) = eval.stop_tx.take() {
+                let _ = tx.send(true);
+            }
+            eval.continuous_mode = false;
+        }
+        policy
+    };
+
+    let msg = match policy {
+        RunnerRestartPolicy::Proceed => format!(
+            "crash-only watchdog: restarting runner '{}' during an eval run (policy=proceed)",
+            runner_name
+        ),
+        RunnerRestartPolicy::Abort => format!(
+            "crash-only watchdog: aborted in-flight eval run before restarting runner '{}'",
+            runner_name
+        ),
+        RunnerRestartPolicy::Defer => format!(
+            "crash-only watchdog: deferring restart of runner '{}' until the eval run \
+             finishes (max {}s)",
+            runner_name, EVAL_RESTART_DEFER_MAX_SECS
+        ),
+    };
+    warn!("{}", msg);
+    state
+        .logs
+        .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+        .await;
+
+    if policy != RunnerRestartPolicy::Defer {
+        return;
+    }
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(EVAL_RESTART_DEFER_MAX_SECS);
+    while state.evaluation.read().await.running {
+        if tokio::time::Instant::now() >= deadline {
+            let msg = format!(
+                "crash-only watchdog: eval run still active after {}s; restarting runner '{}' anyway",
+                EVAL_RESTART_DEFER_MAX_SECS, runner_name
+            );
+            warn!("{}", msg);
+            state
+                .logs
+                .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+                .await;
+            return;
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
 async fn maybe_crash_restart(
     state: &SharedState,
     managed: &Arc<ManagedRunner>,
@@ -2122,6 +2197,8 @@ async fn maybe_crash_restart(
     let runner_id = managed.config.id.clone();
     let runner_name = managed.config.name.clone();
     let globally_armed = crash_restart_globally_armed(&state.config);
+    let policy = state.restart_policy.read().await.clone();
+    let max_restarts = policy.crash_loop_threshold;
     let now = chrono::Utc::now();
 
     // Decide + bookkeep under one watchdog write lock so two exits can't
@@ -2131,7 +2208,7 @@ async fn maybe_crash_restart(
         // Prune history that fell out of the rolling window; keeps the vec
         // bounded and makes `len()` the window count.
         wd.crash_history
-            .retain(|t| (now - *t).num_seconds() < CRASH_RESTART_WINDOW_SECS);
+            .retain(|t| (now - *t).num_seconds() < policy.crash_loop_window_secs as i64);
         let decision = decide_crash_restart(
             had_child_handle,
             stop_requested_at_exit,
@@ -2140,6 +2217,7 @@ async fn maybe_crash_restart(
             wd.enabled,
             wd.disabled_reason.is_some(),
             wd.crash_history.len(),
+            &policy,
         );
         match decision {
             CrashRestartDecision::Restart { .. } => {
@@ -2162,7 +2240,7 @@ async fn maybe_crash_restart(
         } => {
             let msg = format!(
                 "crash-only watchdog restarting runner '{}' (attempt {}/{}) after {}s backoff",
-                runner_name, attempt, CRASH_RESTART_MAX_PER_WINDOW, delay_secs
+                runner_name, attempt, max_restarts, delay_secs
             );
             warn!("{}", msg);
             state
@@ -2176,6 +2254,12 @@ async fn maybe_crash_restart(
             tokio::spawn(async move {
                 tokio::time::sleep(Duration::from_secs(delay_secs)).await;
 
+                // Eval runs poll the primary runner's API; settle any
+                // in-flight run per its policy before bringing it back.
+                if managed.config.kind().is_primary() {
+                    coordinate_eval_before_restart(&state, &runner_name).await;
+                }
+
                 // Re-check intent right before starting: the operator may
                 // have stopped, started, or disabled the runner during the
                 // backoff window.
@@ -2215,13 +2299,24 @@ async fn maybe_crash_restart(
                     Ok(()) => {
                         let msg = format!(
                             "crash-only watchdog restarted runner '{}' (attempt {}/{})",
-                            runner_name, attempt, CRASH_RESTART_MAX_PER_WINDOW
+                            runner_name, attempt, max_restarts
                         );
                         info!("{}", msg);
+                        watchdog_alert::send(
+                            &state,
+                            &runner_id,
+                            &runner_name,
+                            &msg,
+                            WatchdogAlertKind::Restarted {
+                                attempt,
+                                max_attempts: max_restarts,
+                            },
+                        );
                         state
                             .logs
                             .emit(LogSource::Supervisor, LogLevel::Info, msg)
                             .await;
+                        state.record_child_restart(&runner_id, &RestartSource::Watchdog);
                         state.diagnostics.write().await.emit(
                             DiagnosticEventKind::RestartCompleted {
                                 source: RestartSource::Watchdog,
@@ -2235,9 +2330,20 @@ async fn maybe_crash_restart(
                         let msg = format!(
                             "crash-only watchdog FAILED to restart runner '{}' \
                              (attempt {}/{}): {}",
-                            runner_name, attempt, CRASH_RESTART_MAX_PER_WINDOW, e
+                            runner_name, attempt, max_restarts, e
                         );
                         error!("{}", msg);
+                        watchdog_alert::send(
+                            &state,
+                            &runner_id,
+                            &runner_name,
+                            &msg,
+                            WatchdogAlertKind::RestartFailed {
+                                attempt,
+                                max_attempts: max_restarts,
+                                error: e.to_string(),
+                            },
+                        );
                         state
                             .logs
                             .emit(LogSource::Supervisor, LogLevel::Error, msg)
@@ -2260,12 +2366,22 @@ async fn maybe_crash_restart(
                  {} minutes — {}. Clear with POST /runners/{}/watchdog \
                  {{\"enabled\": true, \"reset_attempts\": true}}",
                 runner_name,
-                CRASH_RESTART_MAX_PER_WINDOW,
-                CRASH_RESTART_WINDOW_SECS / 60,
+                max_restarts,
+                policy.crash_loop_window_secs / 60,
                 CRASH_LOOP_DISABLED_REASON,
                 runner_id
             );
             error!("{}", msg);
+            watchdog_alert::send(
+                state,
+                &runner_id,
+                &runner_name,
+                &msg,
+                WatchdogAlertKind::CrashLoopDisarmed {
+                    restarts: max_restarts,
+                    window_secs: policy.crash_loop_window_secs,
+                },
+            );
             state
                 .logs
                 .emit(LogSource::Supervisor, LogLevel::Error, msg.clone())
@@ -2469,13 +2585,12 @@ async fn monitor_runner_process_exit_inner(
     // observes `running == false`, and the flag is only ever cleared by a
     // subsequent start — so the value read here is exactly the operator's
     // intent for THIS exit, race-free.
-    let stop_requested_at_exit = {
+    let (stop_requested_at_exit, exited_pid) = {
         let mut runner = managed.runner.write().await;
         let latched = runner.stop_requested;
         runner.running = false;
         runner.process = None;
-        runner.pid = None;
-        latched
+        (latched, runner.pid.take())
     };
 
     // Update legacy state for primary
@@ -2532,6 +2647,15 @@ async fn monitor_runner_process_exit_inner(
     // actually spawned is treated as a crash — the process is gone and we
     // can't prove it exited cleanly.
     let clean_exit = exit_status.map(|s| s.success()).unwrap_or(false);
+    if had_child_handle && !stop_requested_at_exit && !clean_exit {
+        record_crash(
+            &state,
+            &managed,
+            exited_pid,
+            exit_status.and_then(|s| s.code()),
+        )
+        .await;
+    }
     maybe_crash_restart(
         &state,
         &managed,
@@ -2542,6 +2666,49 @@ async fn monitor_runner_process_exit_inner(
     .await;
 }
 
+/// Write a crash forensics bundle (see [`crate::process::crash_bundle`]) and
+/// emit a `runner_crashed` diagnostics event pointing at it. A bundle that
+/// can't be written is logged; the event is emitted either way.
+async fn record_crash(
+    state: &SharedState,
+    managed: &Arc<ManagedRunner>,
+    pid: Option<u32>,
+    exit_code: Option<i32>,
+) {
+    let bundle_dir =
+        match crate::process::crash_bundle::capture(state, managed, pid, exit_code).await {
+            Ok(dir) => {
+                let msg = format!(
+                    "Runner '{}' crashed — forensics saved to {}",
+                    managed.config.name,
+                    dir.display()
+                );
+                warn!("{}", msg);
+                state
+                    .logs
+                    .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+                    .await;
+                Some(dir.display().to_string())
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to capture crash forensics for runner '{}': {}",
+                    managed.config.name, e
+                );
+                None
+            }
+        };
+    state
+        .diagnostics
+        .write()
+        .await
+        .emit(DiagnosticEventKind::RunnerCrashed {
+            runner_id: managed.config.id.clone(),
+            exit_code,
+            bundle_dir,
+        });
+}
+
 /// Look for `<panic_log_dir>/runner-panic.log`; if it exists and its
 /// timestamp is within [`PANIC_LOG_FRESHNESS_SECS`] of now, parse it and
 /// stash it on the managed runner so `GET /runners` can surface it. Also
@@ -2623,7 +2790,7 @@ async fn request_drain(state: &SharedState, port: u16, runner_name: &str) {
         .http_client
         .post(&url)
         .timeout(Duration::from_secs(DRAIN_REQUEST_TIMEOUT_SECS))
-        .send()
+        .send_traced()
         .await;
     match result {
         Ok(resp) if resp.status().is_success() => {
@@ -2679,7 +2846,7 @@ async fn request_graceful_stop(state: &SharedState, port: u16, runner_name: &str
         .timeout(Duration::from_millis(
             RUNNER_GRACEFUL_STOP_REQUEST_TIMEOUT_MS,
         ))
-        .send()
+        .send_traced()
         .await;
     match result {
         Ok(resp) if resp.status().is_success() => {
@@ -3110,12 +3277,15 @@ async fn primary_rebuild_from_origin_main(state: &SharedState) -> Result<(), Sup
 /// Restart a specific runner by ID.
 /// Automated sources (watchdog, workflow loop, smart rebuild) are rejected for
 /// non-temp runners — only manual API calls can restart user runners.
+///
+/// A running runner is first given the chance to finish its in-flight task
+/// runs (see [`crate::process::task_drain`]); `force` skips that wait.
 pub async fn restart_runner_by_id(
     state: &SharedState,
     runner_id: &str,
     rebuild: bool,
     source: RestartSource,
-    _force: bool,
+    force: bool,
     from_working_tree: bool,
 ) -> Result<(), SupervisorError> {
     if !is_temp_runner(runner_id) && !source.is_manual() {
@@ -3169,11 +3339,32 @@ pub async fn restart_runner_by_id(
         runner.restart_requested = true;
     }
 
-    // Stop if running
+    // Stop if running, once its task runs have drained
     {
         let runner = managed.runner.read().await;
         if runner.running {
             drop(runner);
+            let drain_secs = crate::process::task_drain::drain_timeout_secs();
+            if !force && drain_secs > 0 {
+                let outcome = crate::process::task_drain::drain_task_runs(
+                    state,
+                    managed.config.port,
+                    &managed.config.name,
+                    Duration::from_secs(drain_secs),
+                )
+                .await;
+                if let crate::process::task_drain::DrainOutcome::TimedOut(active) = outcome {
+                    let msg = format!(
+                        "Restarting runner '{}' with {} task run(s) still active after {}s drain",
+                        managed.config.name, active, drain_secs
+                    );
+                    warn!("{}", msg);
+                    state
+                        .logs
+                        .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+                        .await;
+                }
+            }
             if let Err(e) = stop_runner_by_id(state, runner_id).await {
                 state
                     .diagnostics
@@ -3248,6 +3439,8 @@ pub async fn restart_runner_by_id(
         runner.restart_requested = false;
     }
 
+    state.record_child_restart(runner_id, &source);
+
     state
         .diagnostics
         .write()
@@ -3821,6 +4014,50 @@ mod tests {
     // plans/2026-07-03-primary-runner-crash-resilience.md)
     // =========================================================================
 
+    /// [`super::decide_crash_restart`] under the default restart policy.
+    fn decide_crash_restart(
+        had_child_handle: bool,
+        stop_requested: bool,
+        clean_exit: bool,
+        globally_armed: bool,
+        per_runner_enabled: bool,
+        already_disarmed: bool,
+        restarts_in_window: usize,
+    ) -> CrashRestartDecision {
+        super::decide_crash_restart(
+            had_child_handle,
+            stop_requested,
+            clean_exit,
+            globally_armed,
+            per_runner_enabled,
+            already_disarmed,
+            restarts_in_window,
+            &RestartPolicy::default(),
+        )
+    }
+
+    /// A tighter policy disarms sooner and caps the backoff lower.
+    #[test]
+    fn crash_restart_follows_the_configured_policy() {
+        let policy = RestartPolicy {
+            initial_delay_secs: 2,
+            multiplier: 2.0,
+            max_delay_secs: 3,
+            crash_loop_window_secs: 60,
+            crash_loop_threshold: 2,
+        };
+        let decide =
+            |n| super::decide_crash_restart(true, false, false, true, true, false, n, &policy);
+        assert_eq!(
+            decide(1),
+            CrashRestartDecision::Restart {
+                attempt: 2,
+                delay_secs: 3
+            }
+        );
+        assert_eq!(decide(2), CrashRestartDecision::Disarm);
+    }
+
     /// Baseline crash: supervisor-spawned child, no stop intent, non-zero
     /// exit, everything armed, empty window → restart attempt 1 after 5s.
     #[test]
@@ -1,14 +1,21 @@
+pub mod crash_bundle;
 pub mod early_log;
 pub mod env_forwarders;
 pub mod guarded_command;
 pub mod health_probe;
 pub mod job;
 pub mod manager;
+pub mod orphan_cleanup;
 pub mod orphan_scan;
 pub mod panic_log;
 pub mod port;
+pub mod probe;
+pub mod resources;
+pub mod restart_policy;
 pub mod restate_port;
 pub mod stopped_cache;
+pub mod task_drain;
+pub mod watchdog_alert;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
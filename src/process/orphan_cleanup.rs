@@ -0,0 +1,253 @@
+//! Find and terminate stale processes holding managed ports.
+//!
+//! After an unclean shutdown a runner or Expo from the previous session can
+//! keep its port busy, so the next start fails to bind. The startup orphan
+//! scan (`process::orphan_scan`) adopts or kills leftover runner binaries it
+//! can tie to a slot; this covers what it doesn't: anything listening on the
+//! port of a runner the supervisor considers stopped, or on the Expo port
+//! while Expo is stopped, plus runner-binary processes nobody owns.
+//!
+//! Nothing is killed without confirmation. At startup the candidates are
+//! only logged; `POST /processes/cleanup` lists them, and with
+//! `confirm: true` terminates the ones whose pids the caller passes back
+//! (their whole tree) — still only if they are candidates at kill time.
+//! Processes the supervisor owns — runners and Expo it started, the fix
+//! agent, itself, and whatever holds the port of a runner it tracks as
+//! running, starting or restarting — are never candidates.
+//!
+//! Port and process enumeration are Windows-only, like the startup scan; on
+//! other platforms there are never any candidates.
+#![cfg_attr(not(target_os = "windows"), allow(unused))]
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::SharedState;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrphanCandidate {
+    pub pid: u32,
+    pub exe: Option<String>,
+    /// Why it was picked, e.g. `port 9877 (runner 'Test')`.
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KillFailure {
+    pub pid: u32,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    pub candidates: Vec<OrphanCandidate>,
+    pub killed: Vec<u32>,
+    pub failed: Vec<KillFailure>,
+}
+
+/// Merge port holders and runner-binary processes into candidates, dropping
+/// `owned` pids. `port_holders` is `(label, pids)`.
+pub fn collect_candidates(
+    port_holders: &[(String, Vec<u32>)],
+    runner_processes: &[(u32, String)],
+    owned: &HashSet<u32>,
+) -> Vec<OrphanCandidate> {
+    let mut by_pid: BTreeMap<u32, OrphanCandidate> = BTreeMap::new();
+    let mut add = |pid: u32, exe: Option<&str>, reason: String| {
+        if pid == 0 || owned.contains(&pid) {
+            return;
+        }
+        let candidate = by_pid.entry(pid).or_insert_with(|| OrphanCandidate {
+            pid,
+            exe: None,
+            reasons: Vec::new(),
+        });
+        if candidate.exe.is_none() {
+            candidate.exe = exe.map(str::to_string);
+        }
+        candidate.reasons.push(reason);
+    };
+    for (label, pids) in port_holders {
+        for pid in pids {
+            add(*pid, None, label.clone());
+        }
+    }
+    for (pid, exe) in runner_processes {
+        add(*pid, Some(exe), "runner binary".to_string());
+    }
+    by_pid.into_values().collect()
+}
+
+#[cfg(not(target_os = "windows"))]
+pub async fn find_candidates(_state: &SharedState) -> Vec<OrphanCandidate> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+pub async fn find_candidates(state: &SharedState) -> Vec<OrphanCandidate> {
+    use crate::process::windows::{find_pids_on_port, find_runner_processes, pid_exe_path};
+
+    let mut owned: HashSet<u32> = HashSet::from([std::process::id()]);
+    let mut port_holders = Vec::new();
+
+    for managed in state.get_all_runners().await {
+        let (active, pid) = {
+            let runner = managed.runner.read().await;
+            (
+                runner.running || runner.starting || runner.restart_requested,
+                runner.pid,
+            )
+        };
+        let holders = find_pids_on_port(managed.config.port).await;
+        if active {
+            // Health-only runners have no pid on record; their port holder
+            // is the runner itself. One that is starting or restarting may
+            // already hold its port before it is marked running.
+            owned.extend(pid);
+            owned.extend(holders);
+        } else {
+            port_holders.push((
+                format!(
+                    "port {} (runner '{}')",
+                    managed.config.port, managed.config.name
+                ),
+                holders,
+            ));
+        }
+    }
+
+    let (expo_running, expo_pid, expo_port) = {
+        let expo = state.expo.read().await;
+        (expo.running, expo.pid, expo.port)
+    };
+    owned.extend(expo_pid);
+    if !expo_running {
+        port_holders.push((
+            format!("port {} (expo)", expo_port),
+            find_pids_on_port(expo_port).await,
+        ));
+    }
+    owned.extend(state.velocity_improvement.read().await.fix_agent_pid);
+
+    let runner_processes: Vec<(u32, String)> = find_runner_processes()
+        .await
+        .into_iter()
+        .map(|(pid, exe)| (pid, exe.display().to_string()))
+        .collect();
+
+    let mut candidates = collect_candidates(&port_holders, &runner_processes, &owned);
+    for c in candidates.iter_mut().filter(|c| c.exe.is_none()) {
+        c.exe = pid_exe_path(c.pid).await.map(|p| p.display().to_string());
+    }
+    candidates
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn terminate(_pid: u32) -> Result<(), String> {
+    Err("process cleanup is only supported on Windows".to_string())
+}
+
+#[cfg(target_os = "windows")]
+async fn terminate(pid: u32) -> Result<(), String> {
+    match crate::process::windows::kill_by_pid_tree(pid).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("taskkill failed".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Find candidates and, when `confirm` is set, terminate those in `only` —
+/// the pids the caller confirmed from a listing. Candidates are re-checked
+/// at kill time, so a confirmed pid that is now owned is left alone.
+pub async fn cleanup(
+    state: &SharedState,
+    confirm: bool,
+    only: Option<&[u32]>,
+) -> Result<CleanupReport, String> {
+    let only = match (confirm, only) {
+        (true, None) => {
+            return Err("confirm requires pids: the candidates you confirmed".to_string())
+        }
+        (_, only) => only.unwrap_or_default(),
+    };
+    let mut report = CleanupReport {
+        candidates: find_candidates(state).await,
+        ..Default::default()
+    };
+    if !confirm {
+        return Ok(report);
+    }
+    for c in &report.candidates {
+        if !only.contains(&c.pid) {
+            continue;
+        }
+        match terminate(c.pid).await {
+            Ok(()) => report.killed.push(c.pid),
+            Err(error) => report.failed.push(KillFailure { pid: c.pid, error }),
+        }
+    }
+    if !report.killed.is_empty() {
+        let msg = format!("Process cleanup terminated PIDs {:?}", report.killed);
+        tracing::info!("{}", msg);
+        state
+            .logs
+            .emit(LogSource::Supervisor, LogLevel::Info, msg)
+            .await;
+    }
+    Ok(report)
+}
+
+/// Log the candidates at startup, leaving them running.
+pub async fn report_at_startup(state: &SharedState) {
+    let candidates = find_candidates(state).await;
+    if candidates.is_empty() {
+        return;
+    }
+    let listing: Vec<String> = candidates
+        .iter()
+        .map(|c| format!("{} [{}]", c.pid, c.reasons.join(", ")))
+        .collect();
+    let msg = format!(
+        "Found {} stale process(es) holding managed ports or running the runner binary: {}. \
+         Terminate with POST /processes/cleanup {{\"confirm\": true, \"pids\": [...]}}",
+        candidates.len(),
+        listing.join("; ")
+    );
+    tracing::warn!("{}", msg);
+    state
+        .logs
+        .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owned_pids_are_never_candidates() {
+        let port_holders = vec![
+            ("port 9877 (runner 'Test')".to_string(), vec![100, 200]),
+            ("port 8081 (expo)".to_string(), vec![300]),
+        ];
+        let runner_processes = vec![
+            (100, "C:/qontinui-runner-test.exe".to_string()),
+            (400, "C:/qontinui-runner.exe".to_string()),
+        ];
+        let owned = HashSet::from([200, 400]);
+
+        let candidates = collect_candidates(&port_holders, &runner_processes, &owned);
+        let pids: Vec<u32> = candidates.iter().map(|c| c.pid).collect();
+        assert_eq!(pids, vec![100, 300]);
+        assert_eq!(
+            candidates[0].reasons,
+            vec!["port 9877 (runner 'Test')", "runner binary"]
+        );
+        assert_eq!(
+            candidates[0].exe.as_deref(),
+            Some("C:/qontinui-runner-test.exe")
+        );
+        assert_eq!(candidates[1].exe, None);
+    }
+}
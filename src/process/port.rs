@@ -91,7 +91,6 @@ pub async fn wait_for_port(port: u16, timeout_secs: u64) -> bool {
 }
 
 /// Wait for the runner API to respond to health checks.
-#[allow(dead_code)]
 pub async fn wait_for_runner_api(port: u16) -> bool {
     let deadline = Duration::from_secs(PORT_WAIT_TIMEOUT_SECS);
     let interval = Duration::from_millis(PORT_CHECK_INTERVAL_MS);
@@ -0,0 +1,188 @@
+//! Configurable liveness checks for supervised runners.
+//!
+//! By default a runner counts as responding when `GET /health` on its port
+//! returns a 2xx ([`crate::process::port::is_runner_responding`]). A runner
+//! whose config sets `health_probe` is checked by that [`HealthProbe`]
+//! instead, so a service that doesn't speak the runner's HTTP API — a plain
+//! TCP server, or something only a script can vouch for — can still be
+//! supervised. The health cache refresher evaluates the probe every cycle;
+//! its result is what `api_responding` reports.
+
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Timeout for HTTP and TCP probes.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Keeps a command probe inside the health cache's per-runner budget.
+fn default_command_timeout_secs() -> u64 {
+    3
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthProbe {
+    /// `GET http://127.0.0.1:{port}{path}`; healthy on `expected_status`,
+    /// or any 2xx when unset.
+    Http {
+        path: String,
+        #[serde(default)]
+        expected_status: Option<u16>,
+    },
+    /// Healthy when a TCP connection to the port is accepted.
+    Tcp,
+    /// Run `program` with `args`; healthy when it exits with
+    /// `expected_exit_code` (default 0) within `timeout_secs`. `{port}` in
+    /// an argument is replaced with the runner's port.
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        expected_exit_code: Option<i32>,
+        #[serde(default = "default_command_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+impl Default for HealthProbe {
+    fn default() -> Self {
+        Self::Http {
+            path: "/health".to_string(),
+            expected_status: None,
+        }
+    }
+}
+
+impl HealthProbe {
+    /// Whether the service on `port` passes this probe.
+    pub async fn check(&self, port: u16) -> bool {
+        match self {
+            Self::Http {
+                path,
+                expected_status,
+            } => check_http(port, path, *expected_status).await,
+            Self::Tcp => matches!(
+                tokio::time::timeout(
+                    PROBE_TIMEOUT,
+                    tokio::net::TcpStream::connect(("127.0.0.1", port))
+                )
+                .await,
+                Ok(Ok(_))
+            ),
+            Self::Command {
+                program,
+                args,
+                expected_exit_code,
+                timeout_secs,
+            } => {
+                check_command(
+                    program,
+                    args,
+                    port,
+                    expected_exit_code.unwrap_or(0),
+                    Duration::from_secs(*timeout_secs),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Whether the probe talks to the runner's HTTP API, so its `/health`
+    /// body is worth fetching.
+    pub fn is_http(&self) -> bool {
+        matches!(self, Self::Http { .. })
+    }
+}
+
+async fn check_http(port: u16, path: &str, expected_status: Option<u16>) -> bool {
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    };
+    let Ok(client) = reqwest::Client::builder().timeout(PROBE_TIMEOUT).build() else {
+        return false;
+    };
+    match client
+        .get(format!("http://127.0.0.1:{}{}", port, path))
+        .send()
+        .await
+    {
+        Ok(resp) => match expected_status {
+            Some(code) => resp.status().as_u16() == code,
+            None => resp.status().is_success(),
+        },
+        Err(_) => false,
+    }
+}
+
+async fn check_command(
+    program: &str,
+    args: &[String],
+    port: u16,
+    expected: i32,
+    timeout: Duration,
+) -> bool {
+    let port = port.to_string();
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args.iter().map(|a| a.replace("{port}", &port)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    #[cfg(windows)]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::debug!("Health probe `{}` failed to spawn: {}", program, e);
+            return false;
+        }
+    };
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) => status.code() == Some(expected),
+        Ok(Err(_)) => false,
+        Err(_) => {
+            tracing::debug!("Health probe `{}` timed out", program);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_deserialize_from_config_json() {
+        let probe: HealthProbe = serde_json::from_str(r#"{"type": "tcp"}"#).unwrap();
+        assert_eq!(probe, HealthProbe::Tcp);
+
+        let probe: HealthProbe =
+            serde_json::from_str(r#"{"type": "command", "program": "pg_isready"}"#).unwrap();
+        assert_eq!(
+            probe,
+            HealthProbe::Command {
+                program: "pg_isready".to_string(),
+                args: Vec::new(),
+                expected_exit_code: None,
+                timeout_secs: 3,
+            }
+        );
+        assert!(HealthProbe::default().is_http());
+    }
+
+    #[tokio::test]
+    async fn tcp_probe_follows_the_listener() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(HealthProbe::Tcp.check(port).await);
+        drop(listener);
+        assert!(!HealthProbe::Tcp.check(port).await);
+    }
+}
@@ -0,0 +1,273 @@
+//! Recent CPU and memory of the processes the supervisor runs.
+//!
+//! Every [`sample_interval_secs`] a background task samples each running
+//! runner (named by its runner id), Expo (`expo`) and the velocity fix agent
+//! (`fix-agent`) while one is working, and keeps the last [`HISTORY_LEN`]
+//! samples of each for `GET /processes/{name}/resources`. A process whose
+//! memory rises above [`memory_ceiling_bytes`] gets a warning log and a
+//! `memory_ceiling_exceeded` diagnostics event, once until it drops back
+//! below. History of a process that hasn't been seen for
+//! [`STALE_AFTER_MINS`] is dropped.
+//!
+//! CPU is percent of one core, as in [`crate::velocity::resources`], whose
+//! sampler this reuses.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::diagnostics::DiagnosticEventKind;
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::SharedState;
+use crate::velocity::resources::{ResourceSampler, SampleTarget};
+
+pub const SAMPLE_SECS_ENV: &str = "QONTINUI_SUPERVISOR_PROCESS_SAMPLE_SECS";
+pub const MEMORY_CEILING_MB_ENV: &str = "QONTINUI_SUPERVISOR_PROCESS_MEMORY_CEILING_MB";
+
+/// `0` turns sampling off.
+pub const DEFAULT_SAMPLE_SECS: u64 = 10;
+
+/// `0` turns the ceiling off.
+pub const DEFAULT_MEMORY_CEILING_MB: u64 = 4096;
+
+/// Samples kept per process (an hour at the default interval).
+pub const HISTORY_LEN: usize = 360;
+
+pub const STALE_AFTER_MINS: i64 = 60;
+
+pub const EXPO: &str = "expo";
+pub const FIX_AGENT: &str = "fix-agent";
+
+pub fn sample_interval_secs() -> u64 {
+    std::env::var(SAMPLE_SECS_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_SAMPLE_SECS)
+}
+
+pub fn memory_ceiling_bytes() -> Option<u64> {
+    let mb = std::env::var(MEMORY_CEILING_MB_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_MEMORY_CEILING_MB);
+    (mb > 0).then_some(mb * 1024 * 1024)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSample {
+    pub sampled_at: DateTime<Utc>,
+    pub pid: u32,
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessResources {
+    pub name: String,
+    /// Pid of the latest sample.
+    pub pid: u32,
+    pub memory_ceiling_bytes: Option<u64>,
+    pub over_ceiling: bool,
+    /// Oldest first.
+    pub samples: Vec<ProcessSample>,
+}
+
+#[derive(Debug, Default)]
+pub struct ResourceHistory {
+    processes: HashMap<String, VecDeque<ProcessSample>>,
+    over_ceiling: HashSet<String>,
+}
+
+impl ResourceHistory {
+    /// Add a sample of `name`; true when it has just gone over `ceiling`.
+    pub fn record(&mut self, name: &str, sample: ProcessSample, ceiling: Option<u64>) -> bool {
+        let over = ceiling.is_some_and(|c| sample.memory_bytes > c);
+        let samples = self.processes.entry(name.to_string()).or_default();
+        if samples.len() >= HISTORY_LEN {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+        if over {
+            self.over_ceiling.insert(name.to_string())
+        } else {
+            self.over_ceiling.remove(name);
+            false
+        }
+    }
+
+    /// Forget processes last sampled before `cutoff`.
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) {
+        self.processes
+            .retain(|_, samples| samples.back().is_some_and(|s| s.sampled_at >= cutoff));
+        let processes = &self.processes;
+        self.over_ceiling
+            .retain(|name| processes.contains_key(name));
+    }
+
+    pub fn get(&self, name: &str, ceiling: Option<u64>) -> Option<ProcessResources> {
+        let samples = self.processes.get(name)?;
+        Some(ProcessResources {
+            name: name.to_string(),
+            pid: samples.back()?.pid,
+            memory_ceiling_bytes: ceiling,
+            over_ceiling: self.over_ceiling.contains(name),
+            samples: samples.iter().cloned().collect(),
+        })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.processes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+async fn sample_targets(state: &SharedState) -> Vec<SampleTarget> {
+    let mut targets = Vec::new();
+    let runners: Vec<_> = state.runners.read().await.values().cloned().collect();
+    for managed in runners {
+        if let Some(pid) = managed.runner.read().await.pid {
+            targets.push(SampleTarget {
+                service: "runner".to_string(),
+                instance: managed.config.id.clone(),
+                pid,
+            });
+        }
+    }
+    if let Some(pid) = state.expo.read().await.pid {
+        targets.push(SampleTarget {
+            service: EXPO.to_string(),
+            instance: EXPO.to_string(),
+            pid,
+        });
+    }
+    if let Some(pid) = state.velocity_improvement.read().await.fix_agent_pid {
+        targets.push(SampleTarget {
+            service: FIX_AGENT.to_string(),
+            instance: FIX_AGENT.to_string(),
+            pid,
+        });
+    }
+    targets
+}
+
+/// Sample forever at [`sample_interval_secs`]; returns at once when it is 0.
+pub async fn sample_loop(state: SharedState) {
+    let secs = sample_interval_secs();
+    if secs == 0 {
+        return;
+    }
+    let ceiling = memory_ceiling_bytes();
+    let mut sampler = ResourceSampler::default();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(secs));
+    loop {
+        interval.tick().await;
+        let targets = sample_targets(&state).await;
+        let samples = match tokio::task::spawn_blocking(move || {
+            let samples = sampler.sample(&targets);
+            (sampler, samples)
+        })
+        .await
+        {
+            Ok((s, samples)) => {
+                sampler = s;
+                samples
+            }
+            Err(e) => {
+                tracing::error!("Process resource sampling panicked: {}", e);
+                sampler = ResourceSampler::default();
+                continue;
+            }
+        };
+
+        let mut exceeded = Vec::new();
+        {
+            let mut history = state.process_resources.write().await;
+            for s in samples {
+                let sample = ProcessSample {
+                    sampled_at: Utc::now(),
+                    pid: s.pid,
+                    cpu_percent: s.cpu_percent,
+                    memory_bytes: s.memory_bytes,
+                };
+                if history.record(&s.instance, sample, ceiling) {
+                    exceeded.push((s.instance, s.pid, s.memory_bytes));
+                }
+            }
+            history.prune(Utc::now() - chrono::Duration::minutes(STALE_AFTER_MINS));
+        }
+
+        let Some(ceiling) = ceiling else { continue };
+        for (name, pid, memory_bytes) in exceeded {
+            let msg = format!(
+                "Process {} (pid {}) is using {} MB, over the {} MB ceiling",
+                name,
+                pid,
+                memory_bytes / (1024 * 1024),
+                ceiling / (1024 * 1024)
+            );
+            tracing::warn!("{}", msg);
+            state
+                .logs
+                .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+                .await;
+            state
+                .diagnostics
+                .write()
+                .await
+                .emit(DiagnosticEventKind::MemoryCeilingExceeded {
+                    process: name,
+                    pid,
+                    memory_bytes,
+                    ceiling_bytes: ceiling,
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(memory_bytes: u64, mins_ago: i64) -> ProcessSample {
+        ProcessSample {
+            sampled_at: Utc::now() - chrono::Duration::minutes(mins_ago),
+            pid: 42,
+            cpu_percent: 12.5,
+            memory_bytes,
+        }
+    }
+
+    #[test]
+    fn ceiling_is_reported_once_per_crossing() {
+        let mut history = ResourceHistory::default();
+        let ceiling = Some(1000);
+        assert!(!history.record("primary", sample(500, 0), ceiling));
+        assert!(history.record("primary", sample(1500, 0), ceiling));
+        assert!(!history.record("primary", sample(1600, 0), ceiling));
+        assert!(history.get("primary", ceiling).unwrap().over_ceiling);
+        assert!(!history.record("primary", sample(900, 0), ceiling));
+        assert!(history.record("primary", sample(1100, 0), ceiling));
+        assert!(!history.record("expo", sample(1100, 0), None));
+
+        let resources = history.get("primary", ceiling).unwrap();
+        assert_eq!(resources.samples.len(), 5);
+        assert_eq!(resources.pid, 42);
+    }
+
+    #[test]
+    fn history_is_capped_and_stale_processes_dropped() {
+        let mut history = ResourceHistory::default();
+        for _ in 0..HISTORY_LEN + 5 {
+            history.record("primary", sample(1, 0), None);
+        }
+        history.record("test-1", sample(1, STALE_AFTER_MINS + 1), None);
+        assert_eq!(
+            history.get("primary", None).unwrap().samples.len(),
+            HISTORY_LEN
+        );
+
+        history.prune(Utc::now() - chrono::Duration::minutes(STALE_AFTER_MINS));
+        assert_eq!(history.names(), vec!["primary"]);
+    }
+}
@@ -0,0 +1,101 @@
+//! Backoff and crash-loop limits of the crash-only watchdog.
+//!
+//! Persisted as `restart_policy` in the settings file and editable at runtime
+//! through `PUT /watchdog/restart-policy`; the watchdog reads the current
+//! policy on every crash. The default is the long-standing ladder of
+//! 5s → 30s → 120s with at most 3 auto-restarts per rolling 30 minutes.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Backoff before the first auto-restart in a window.
+    pub initial_delay_secs: u64,
+    /// Factor applied to the backoff for each further restart.
+    pub multiplier: f64,
+    /// Cap on the backoff.
+    pub max_delay_secs: u64,
+    /// Rolling window the crash-loop threshold is counted over.
+    pub crash_loop_window_secs: u64,
+    /// Auto-restarts allowed per window before the watchdog disarms.
+    pub crash_loop_threshold: usize,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay_secs: 5,
+            multiplier: 6.0,
+            max_delay_secs: 120,
+            crash_loop_window_secs: 30 * 60,
+            crash_loop_threshold: 3,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.multiplier.is_finite() || self.multiplier < 1.0 {
+            return Err("multiplier must be at least 1".to_string());
+        }
+        if self.max_delay_secs < self.initial_delay_secs {
+            return Err("max_delay_secs must not be below initial_delay_secs".to_string());
+        }
+        if self.crash_loop_window_secs == 0 {
+            return Err("crash_loop_window_secs must be positive".to_string());
+        }
+        if self.crash_loop_threshold == 0 {
+            return Err("crash_loop_threshold must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Backoff before the restart that follows `restarts_in_window` earlier
+    /// ones.
+    pub fn delay_secs(&self, restarts_in_window: usize) -> u64 {
+        let delay =
+            self.initial_delay_secs as f64 * self.multiplier.powi(restarts_in_window as i32);
+        if delay >= self.max_delay_secs as f64 {
+            self.max_delay_secs
+        } else {
+            delay.round() as u64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_keeps_the_original_ladder() {
+        let policy = RestartPolicy::default();
+        let ladder: Vec<u64> = (0..4).map(|n| policy.delay_secs(n)).collect();
+        assert_eq!(ladder, vec![5, 30, 120, 120]);
+        assert!(policy.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        let bad = [
+            RestartPolicy {
+                multiplier: 0.5,
+                ..Default::default()
+            },
+            RestartPolicy {
+                max_delay_secs: 1,
+                ..Default::default()
+            },
+            RestartPolicy {
+                crash_loop_threshold: 0,
+                ..Default::default()
+            },
+        ];
+        for policy in bad {
+            assert!(policy.validate().is_err(), "{:?}", policy);
+        }
+        let partial: RestartPolicy = serde_json::from_str(r#"{"max_delay_secs": 600}"#).unwrap();
+        assert_eq!(partial.delay_secs(5), 600);
+    }
+}
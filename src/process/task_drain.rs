@@ -0,0 +1,151 @@
+//! Let in-flight task runs finish before a runner restart.
+//!
+//! `restart_runner_by_id` used to stop a runner no matter what it was
+//! executing. Before stopping a running runner it now asks the runner to stop
+//! accepting new task runs (`POST /task-runs/pause-intake`), then polls
+//! `GET /task-runs` until none is active or [`drain_timeout_secs`] passes, and
+//! restarts either way. `force: true` on the restart skips the wait. A runner
+//! without the intake endpoint is still waited on; one whose task runs can't
+//! be listed is restarted at once, since there is nothing to wait for.
+
+use std::time::Duration;
+
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::SharedState;
+use crate::trace_propagation::TraceparentExt;
+
+pub const DRAIN_TIMEOUT_ENV: &str = "QONTINUI_SUPERVISOR_RESTART_DRAIN_TIMEOUT_SECS";
+
+/// `0` turns the drain off.
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 120;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Task run statuses that still hold work.
+const ACTIVE_STATUSES: [&str; 3] = ["running", "pending", "queued"];
+
+pub fn drain_timeout_secs() -> u64 {
+    std::env::var(DRAIN_TIMEOUT_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_DRAIN_TIMEOUT_SECS)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainOutcome {
+    /// No task run was left active.
+    Drained,
+    /// The timeout passed with this many still active.
+    TimedOut(usize),
+    /// The runner's task runs couldn't be listed.
+    Unavailable,
+}
+
+/// Active task runs in a `GET /task-runs` body: a bare array, or an object
+/// holding one under `task_runs`, `runs` or `items`.
+pub fn active_task_runs(body: &serde_json::Value) -> usize {
+    let runs = body.as_array().or_else(|| {
+        ["task_runs", "runs", "items"]
+            .iter()
+            .find_map(|key| body.get(key).and_then(|v| v.as_array()))
+    });
+    runs.map_or(0, |runs| {
+        runs.iter()
+            .filter(|run| {
+                run.get("status")
+                    .and_then(|s| s.as_str())
+                    .is_some_and(|s| ACTIVE_STATUSES.contains(&s.to_ascii_lowercase().as_str()))
+            })
+            .count()
+    })
+}
+
+async fn count_active(state: &SharedState, port: u16) -> Option<usize> {
+    let resp = state
+        .http_client
+        .get(format!("http://127.0.0.1:{}/task-runs", port))
+        .timeout(REQUEST_TIMEOUT)
+        .send_traced()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let body: serde_json::Value = resp.json().await.ok()?;
+    Some(active_task_runs(&body))
+}
+
+/// Pause intake on the runner at `port` and wait for its task runs.
+pub async fn drain_task_runs(
+    state: &SharedState,
+    port: u16,
+    runner_name: &str,
+    timeout: Duration,
+) -> DrainOutcome {
+    let paused = state
+        .http_client
+        .post(format!("http://127.0.0.1:{}/task-runs/pause-intake", port))
+        .timeout(REQUEST_TIMEOUT)
+        .send_traced()
+        .await
+        .is_ok_and(|r| r.status().is_success());
+    if !paused {
+        tracing::debug!(
+            "Runner '{}' (port {}) did not pause task intake — waiting on in-flight runs anyway",
+            runner_name,
+            port
+        );
+    }
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut announced = false;
+    loop {
+        let Some(active) = count_active(state, port).await else {
+            return DrainOutcome::Unavailable;
+        };
+        if active == 0 {
+            return DrainOutcome::Drained;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return DrainOutcome::TimedOut(active);
+        }
+        if !announced {
+            announced = true;
+            state
+                .logs
+                .emit(
+                    LogSource::Supervisor,
+                    LogLevel::Info,
+                    format!(
+                        "Waiting up to {}s for {} task run(s) on runner '{}' to finish before restart",
+                        timeout.as_secs(),
+                        active,
+                        runner_name
+                    ),
+                )
+                .await;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn only_active_task_runs_are_counted() {
+        let runs = json!([
+            {"id": "a", "status": "running"},
+            {"id": "b", "status": "completed"},
+            {"id": "c", "status": "Pending"},
+            {"id": "d"},
+        ]);
+        assert_eq!(active_task_runs(&runs), 2);
+        assert_eq!(active_task_runs(&json!({"task_runs": runs})), 2);
+        assert_eq!(active_task_runs(&json!({"items": []})), 0);
+        assert_eq!(active_task_runs(&json!({"error": "nope"})), 0);
+    }
+}
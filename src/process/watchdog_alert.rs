@@ -0,0 +1,126 @@
+//! Webhook alerts from the crash-only watchdog.
+//!
+//! On an unattended machine a crash-looping runner otherwise sits dead until
+//! someone reads the logs. When [`WEBHOOK_URLS_ENV`] lists one or more URLs
+//! (comma-separated), the watchdog POSTs a [`WatchdogAlert`] to each of them
+//! when it restarts a runner, when a restart fails, and when the crash-loop
+//! guard disarms it. Delivery is fire-and-forget; a failed POST is logged.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::state::SharedState;
+
+pub const WEBHOOK_URLS_ENV: &str = "QONTINUI_SUPERVISOR_WATCHDOG_WEBHOOK_URLS";
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WatchdogAlertKind {
+    Restarted {
+        attempt: u32,
+        max_attempts: usize,
+    },
+    RestartFailed {
+        attempt: u32,
+        max_attempts: usize,
+        error: String,
+    },
+    /// The crash-loop guard tripped; no further restarts until an operator
+    /// resets the watchdog.
+    CrashLoopDisarmed {
+        restarts: usize,
+        window_secs: u64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchdogAlert {
+    pub runner_id: String,
+    pub runner_name: String,
+    /// Port of the supervisor that raised the alert.
+    pub supervisor_port: u16,
+    pub message: String,
+    pub raised_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: WatchdogAlertKind,
+}
+
+pub fn webhook_urls() -> Vec<String> {
+    parse_urls(&std::env::var(WEBHOOK_URLS_ENV).unwrap_or_default())
+}
+
+fn parse_urls(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// POST an alert about `runner_id` to every configured webhook.
+pub fn send(
+    state: &SharedState,
+    runner_id: &str,
+    runner_name: &str,
+    message: &str,
+    kind: WatchdogAlertKind,
+) {
+    let urls = webhook_urls();
+    if urls.is_empty() {
+        return;
+    }
+    let alert = WatchdogAlert {
+        runner_id: runner_id.to_string(),
+        runner_name: runner_name.to_string(),
+        supervisor_port: state.config.port,
+        message: message.to_string(),
+        raised_at: Utc::now(),
+        kind,
+    };
+    let client = state.http_client.clone();
+    tokio::spawn(async move {
+        for url in urls {
+            let result = client
+                .post(&url)
+                .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                .json(&alert)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("Watchdog alert webhook {} failed: {}", url, e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_serializes_flat_with_event_tag() {
+        let alert = WatchdogAlert {
+            runner_id: "primary".to_string(),
+            runner_name: "Primary".to_string(),
+            supervisor_port: 9875,
+            message: "disarmed".to_string(),
+            raised_at: Utc::now(),
+            kind: WatchdogAlertKind::CrashLoopDisarmed {
+                restarts: 3,
+                window_secs: 1800,
+            },
+        };
+        let value = serde_json::to_value(&alert).unwrap();
+        assert_eq!(value["event"], "crash_loop_disarmed");
+        assert_eq!(value["restarts"], 3);
+        assert_eq!(value["runner_id"], "primary");
+
+        assert_eq!(
+            parse_urls(" https://a.example/hook, ,https://b.example "),
+            vec!["https://a.example/hook", "https://b.example"]
+        );
+    }
+}
@@ -35,8 +35,9 @@ fn paths_equal_ignore_case(a: &Path, b: &Path) -> bool {
 /// Return every distinct PID found LISTENING on `port`. Empty vec when the
 /// port is idle or netstat fails for any reason — callers treat an empty
 /// result as "nothing to do here". Shared by `find_pid_on_port` (first
-/// result) and `kill_by_port` (kill all results).
-async fn find_pids_on_port(port: u16) -> Vec<u32> {
+/// result), `kill_by_port` (kill all results) and the on-demand orphan
+/// cleanup (`process::orphan_cleanup`).
+pub async fn find_pids_on_port(port: u16) -> Vec<u32> {
     let Ok(output) = Command::new("cmd")
         .args([
             "/C",
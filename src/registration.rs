@@ -0,0 +1,85 @@
+//! Service registration file so companion tools can find a supervisor that
+//! isn't on the default port (e.g. started with `--port 0`).
+//!
+//! Written to `<dev_logs_dir>/supervisor.json` once the listener is bound and
+//! removed on clean shutdown — but only by the process that wrote it, so an
+//! old instance exiting late never deletes a newer instance's entry. A stale
+//! file left by a crash is detectable: its `pid` is no longer alive.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const REGISTRATION_FILE: &str = "supervisor.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Registration {
+    pub pid: u32,
+    pub port: u16,
+    pub url: String,
+    pub started_at: String,
+    pub version: String,
+    pub build_id: String,
+}
+
+impl Registration {
+    pub fn for_this_process(port: u16, build_id: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            port,
+            url: format!("http://127.0.0.1:{}", port),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            build_id: build_id.to_string(),
+        }
+    }
+}
+
+pub fn registration_path(dev_logs_dir: &Path) -> PathBuf {
+    dev_logs_dir.join(REGISTRATION_FILE)
+}
+
+pub fn write(dev_logs_dir: &Path, reg: &Registration) -> std::io::Result<PathBuf> {
+    let path = registration_path(dev_logs_dir);
+    let json = serde_json::to_vec_pretty(reg)?;
+    crate::fs_atomic::atomic_write(&path, &json)?;
+    Ok(path)
+}
+
+pub fn read(dev_logs_dir: &Path) -> Option<Registration> {
+    let bytes = std::fs::read(registration_path(dev_logs_dir)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Remove the registration file if it still belongs to `pid`.
+pub fn remove_if_owned(dev_logs_dir: &Path, pid: u32) {
+    if read(dev_logs_dir).is_some_and(|r| r.pid == pid) {
+        let _ = std::fs::remove_file(registration_path(dev_logs_dir));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_read_roundtrips() {
+        let dir = tempfile::tempdir().unwrap();
+        let reg = Registration::for_this_process(54321, "build-1");
+        write(dir.path(), &reg).unwrap();
+        assert_eq!(read(dir.path()), Some(reg));
+    }
+
+    #[test]
+    fn remove_only_deletes_own_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut reg = Registration::for_this_process(54321, "build-1");
+        reg.pid = reg.pid.wrapping_add(1);
+        write(dir.path(), &reg).unwrap();
+
+        remove_if_owned(dir.path(), std::process::id());
+        assert!(read(dir.path()).is_some());
+
+        remove_if_owned(dir.path(), reg.pid);
+        assert!(read(dir.path()).is_none());
+    }
+}
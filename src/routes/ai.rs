@@ -53,6 +53,9 @@ async fn persist_ai_settings(state: &SharedState) {
         ai_model: Some(ai.model.clone()),
         auto_debug_enabled: Some(ai.auto_debug_enabled),
         runners: existing.runners,
+        velocity_slos: existing.velocity_slos,
+        velocity_sampling: existing.velocity_sampling,
+        restart_policy: existing.restart_policy,
     };
     drop(ai);
     settings::save_settings(&path, &s);
@@ -35,6 +35,13 @@ pub async fn get_diagnostics(
     }))
 }
 
+/// GET /internal/profile — Memory/runtime self-profile (see `self_profile`).
+pub async fn get_profile(
+    State(state): State<SharedState>,
+) -> Json<crate::self_profile::ProfileReport> {
+    Json(crate::self_profile::capture(&state).await)
+}
+
 pub async fn clear_diagnostics(State(state): State<SharedState>) -> impl IntoResponse {
     state.diagnostics.write().await.clear();
     Json(serde_json::json!({
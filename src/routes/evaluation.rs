@@ -1,16 +1,44 @@
-use axum::extract::{Path, State};
-use axum::response::Json;
-use axum::routing::{delete, get, post, put};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{delete, get, patch, post, put};
 use axum::Router;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::watch;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
 
+use crate::config::resolve_model_id;
+use crate::evaluation::baseline::{self as eval_baseline, BaselinePin};
+use crate::evaluation::bundle::{
+    self, ConflictPolicy, ImportAction, ImportReport, PromptBundle, RenamedEntry,
+};
 use crate::evaluation::db::EvalDb;
-use crate::evaluation::{self, EvalRunWithResults, EvalStatus, TestPrompt};
-use crate::state::SharedState;
+use crate::evaluation::determinism::{self, DeterminismReport};
+use crate::evaluation::engine::EvalRunOptions;
+use crate::evaluation::export::{self, ExportFormat};
+use crate::evaluation::judge::JudgeModel;
+use crate::evaluation::judge::JudgeTemplate;
+use crate::evaluation::leaderboard::{self, Leaderboard, MatrixModel, MAX_MATRIX_MODELS};
+use crate::evaluation::mutation::{self, DEFAULT_VARIANTS, MAX_VARIANTS};
+use crate::evaluation::prompt_source::{self, SyncReport};
+use crate::evaluation::reliability::{self, AgreementReport, DEFAULT_SAMPLE_PERCENT};
+use crate::evaluation::retention::{self, PruneReport, RetentionPolicy};
+use crate::evaluation::schedule::{self, CronExpr, EvalSchedule};
+use crate::evaluation::{
+    self, DimensionScores, EvalRunWithResults, EvalStatus, PromptStatus, PromptSuite, ResultFilter,
+    RunnerRestartPolicy, TestPrompt,
+};
+use crate::job_queue::{self, JobKind, JobRequest, QueueParams};
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::{SharedState, SseConnectionGuard};
+use crate::stream_clients;
 
 // ============================================================================
 // State
@@ -30,11 +58,37 @@ pub struct EvalState {
 #[derive(Debug, Deserialize)]
 pub struct StartRequest {
     pub prompt_ids: Option<Vec<String>>,
+    /// Name of a saved prompt suite. Mutually exclusive with `prompt_ids`.
+    pub suite: Option<String>,
+    /// Prompts evaluated at once (default 1, max
+    /// [`evaluation::engine::MAX_EVAL_CONCURRENCY`]).
+    pub concurrency: Option<usize>,
+    /// Score with this provider/model instead of the global AI settings.
+    /// Must be given together.
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+    /// Generate and score each prompt this many times (default 1, max
+    /// [`determinism::MAX_SAMPLES_PER_PROMPT`]) to measure
+    /// determinism.
+    pub samples_per_prompt: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ContinuousStartRequest {
     pub interval_secs: Option<u64>,
+    pub concurrency: Option<usize>,
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestartPolicyRequest {
+    pub policy: RunnerRestartPolicy,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptStatusRequest {
+    pub status: PromptStatus,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,18 +96,193 @@ pub struct SetGroundTruthRequest {
     pub workflow_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TrendParams {
+    pub category: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportParams {
+    pub on_conflict: Option<ConflictPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MatrixStartRequest {
+    /// Provider/model pairs to generate with, run in this order.
+    pub models: Vec<MatrixModel>,
+    /// Judge for every run of the matrix. Defaults to the global AI settings.
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+    pub prompt_ids: Option<Vec<String>>,
+    pub suite: Option<String>,
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardParams {
+    /// Defaults to the most recent matrix.
+    pub matrix_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RescoreRequest {
+    /// Defaults to the active judge template.
+    pub judge_template_version: Option<i64>,
+    /// Defaults to the source run's judge.
+    pub judge_provider: Option<String>,
+    pub judge_model: Option<String>,
+}
+
+/// Overrides for one prune. Either rule given replaces the env policy.
+#[derive(Debug, Deserialize)]
+pub struct PruneParams {
+    pub keep_last: Option<usize>,
+    pub max_age_days: Option<u32>,
+    pub dry_run: Option<bool>,
+    /// Compact the DB file afterwards (slow on a large DB).
+    pub vacuum: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RescoreSampleRequest {
+    /// Share of the run's scored results to re-score, in `(0, 100]`.
+    pub percent: Option<f64>,
+    /// The second judge; must differ from the source run's.
+    pub judge_provider: String,
+    pub judge_model: String,
+    /// Defaults to the source run's judge prompt version.
+    pub judge_template_version: Option<i64>,
+    /// Fixes the sample; random when unset.
+    pub seed: Option<u64>,
+}
+
+/// Body of `PATCH /eval/results/{id}/scores`. Unset dimensions keep their
+/// current score.
+#[derive(Debug, Deserialize)]
+pub struct ScoreOverrideRequest {
+    #[serde(flatten)]
+    pub scores: DimensionScores,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MutationRequest {
+    /// Variants to ask for; 1 to `MAX_VARIANTS`.
+    pub count: Option<usize>,
+    /// Defaults to the global AI settings.
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Status of the new prompts; `draft` keeps them out of runs until
+    /// activated.
+    #[serde(default = "draft_status")]
+    pub status: PromptStatus,
+}
+
+fn draft_status() -> PromptStatus {
+    PromptStatus::Draft
+}
+
+#[derive(Debug, Serialize)]
+pub struct MutationResponse {
+    pub ok: bool,
+    pub message: String,
+    pub created: Vec<TestPrompt>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AgreementParams {
+    /// Defaults to the run's latest rescore.
+    pub rescore_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResultParams {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// Defaults to true; pass false to drop the generated workflows.
+    pub include_workflow_json: Option<bool>,
+    pub min_score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub category: Option<String>,
+    pub has_error: Option<bool>,
+}
+
+impl ResultParams {
+    fn into_filter(self) -> ResultFilter {
+        ResultFilter {
+            limit: self.limit,
+            offset: self.offset.unwrap_or(0),
+            include_workflow_json: self.include_workflow_json.unwrap_or(true),
+            min_score: self.min_score,
+            max_score: self.max_score,
+            category: self.category,
+            has_error: self.has_error,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncParams {
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinBaselineRequest {
+    pub run_id: String,
+    pub max_avg_drop: Option<f64>,
+    pub max_regressions: Option<usize>,
+    pub webhook_url: Option<String>,
+}
+
+/// A schedule plus when it will next fire.
+#[derive(Debug, Serialize)]
+pub struct ScheduleView {
+    #[serde(flatten)]
+    pub schedule: EvalSchedule,
+    pub next_fire_at: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MessageResponse {
     pub ok: bool,
     pub message: String,
 }
 
+/// Reject a judge override that is half-specified or names an unknown model.
+fn validate_judge(provider: &Option<String>, model: &Option<String>) -> Result<(), String> {
+    match (provider, model) {
+        (None, None) => Ok(()),
+        (Some(p), Some(m)) => {
+            if resolve_model_id(p, m).is_some() {
+                Ok(())
+            } else {
+                Err(format!("Unknown judge model {}/{}", p, m))
+            }
+        }
+        _ => Err("judge_provider and judge_model must be set together".to_string()),
+    }
+}
+
 // ============================================================================
 // Routes
 // ============================================================================
 
 pub fn eval_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> Router {
-    let db = match EvalDb::new(&dev_logs_dir) {
+    let prompts_dir = prompt_source::prompts_dir(&supervisor.config);
+    let db = match EvalDb::new(&dev_logs_dir, !prompts_dir.is_dir()) {
         Ok(db) => db,
         Err(e) => {
             tracing::error!("Failed to initialize eval database: {}", e);
@@ -61,20 +290,110 @@ pub fn eval_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> Router {
         }
     };
 
+    if prompts_dir.is_dir() {
+        match prompt_source::sync(&db, &prompts_dir, false) {
+            Ok(report) => {
+                tracing::info!(
+                    "Synced eval prompts from {}: {} added, {} updated, {} unchanged",
+                    report.dir,
+                    report.added.len(),
+                    report.updated.len(),
+                    report.unchanged
+                );
+                if !report.drifted.is_empty() {
+                    tracing::warn!(
+                        "Eval prompts edited in the DB were overwritten from files: {:?}",
+                        report.drifted
+                    );
+                }
+                for e in &report.errors {
+                    tracing::warn!("Eval prompt file error: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to sync eval prompts from {:?}: {}", prompts_dir, e),
+        }
+    }
+
+    let policy = RetentionPolicy::from_env();
+    if policy.is_enabled() {
+        match retention::prune(&db, policy, false, false) {
+            Ok(report) if !report.pruned.is_empty() => tracing::info!(
+                "Pruned {} eval run(s) per retention policy ({} kept)",
+                report.pruned.len(),
+                report.kept
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to prune eval runs: {}", e),
+        }
+    }
+
+    match db.latest_completed_score() {
+        Ok(score) => {
+            if let Ok(mut eval) = supervisor.evaluation.try_write() {
+                eval.last_completed_score = score;
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load latest eval score: {}", e),
+    }
+
     let state = Arc::new(EvalState {
         db: Arc::new(db),
         dev_logs_dir,
         supervisor,
     });
 
+    tokio::spawn(dispatch_queued_evals(state.clone()));
+    tokio::spawn(run_eval_schedules(state.clone()));
+    if evaluation::engine::resume_on_startup() {
+        tokio::spawn(resume_interrupted_run(state.clone()));
+    }
+
     Router::new()
         .route("/eval/status", get(status_handler))
+        .route("/eval/stream", get(stream_handler))
         .route("/eval/start", post(start_handler))
         .route("/eval/stop", post(stop_handler))
         .route("/eval/continuous/start", post(continuous_start_handler))
         .route("/eval/continuous/stop", post(continuous_stop_handler))
+        .route("/eval/matrix/start", post(matrix_start_handler))
+        .route("/eval/leaderboard", get(leaderboard_handler))
+        .route("/eval/restart-policy", put(restart_policy_handler))
         .route("/eval/runs", get(list_runs_handler))
         .route("/eval/runs/{id}", get(get_run_handler))
+        .route("/eval/results/{id}/scores", patch(override_scores_handler))
+        .route("/eval/prune", post(prune_handler))
+        .route(
+            "/eval/runs/{id}/retry-failures",
+            post(retry_failures_handler),
+        )
+        .route("/eval/runs/{id}/rescore", post(rescore_run_handler))
+        .route(
+            "/eval/runs/{id}/rescore-sample",
+            post(rescore_sample_handler),
+        )
+        .route("/eval/runs/{id}/agreement", get(agreement_handler))
+        .route("/eval/runs/{id}/determinism", get(determinism_handler))
+        .route("/eval/runs/{id}/resume", post(resume_run_handler))
+        .route("/eval/runs/{id}/export", get(export_run_handler))
+        .route("/eval/judge-prompts", get(list_judge_prompts_handler))
+        .route("/eval/judge-prompts", post(create_judge_prompt_handler))
+        .route(
+            "/eval/judge-prompts/{version}",
+            get(get_judge_prompt_handler),
+        )
+        .route(
+            "/eval/judge-prompts/{version}",
+            delete(delete_judge_prompt_handler),
+        )
+        .route(
+            "/eval/judge-prompts/{version}/activate",
+            post(activate_judge_prompt_handler),
+        )
+        .route("/eval/baseline", get(get_baseline_handler))
+        .route("/eval/baseline", put(pin_baseline_handler))
+        .route("/eval/baseline", delete(unpin_baseline_handler))
+        .route("/eval/trends", get(trends_handler))
+        .route("/search", get(search_handler))
         .route(
             "/eval/runs/{id}/compare/{baseline_id}",
             get(compare_handler),
@@ -83,6 +402,33 @@ pub fn eval_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> Router {
         .route("/eval/test-suite", post(add_test_prompt_handler))
         .route("/eval/test-suite/{id}", put(update_test_prompt_handler))
         .route("/eval/test-suite/{id}", delete(delete_test_prompt_handler))
+        .route(
+            "/eval/test-suite/{id}/status",
+            post(set_prompt_status_handler),
+        )
+        .route(
+            "/eval/test-suite/{id}/needs-review",
+            delete(clear_needs_review_handler),
+        )
+        .route(
+            "/eval/test-suite/{id}/mutations",
+            post(create_mutations_handler),
+        )
+        .route("/eval/prompts/export", get(export_prompts_handler))
+        .route("/eval/prompts/import", post(import_prompts_handler))
+        .route("/eval/prompts/sync", post(sync_prompts_handler))
+        .route("/eval/schedules", get(list_schedules_handler))
+        .route("/eval/schedules", post(create_schedule_handler))
+        .route("/eval/schedules/{id}", delete(delete_schedule_handler))
+        .route("/eval/schedules/{id}/enable", post(enable_schedule_handler))
+        .route(
+            "/eval/schedules/{id}/disable",
+            post(disable_schedule_handler),
+        )
+        .route("/eval/suites", get(list_suites_handler))
+        .route("/eval/suites", post(create_suite_handler))
+        .route("/eval/suites/{name}", put(update_suite_handler))
+        .route("/eval/suites/{name}", delete(delete_suite_handler))
         .route(
             "/eval/test-suite/{id}/ground-truth",
             put(set_ground_truth_handler),
@@ -107,50 +453,341 @@ async fn status_handler(State(state): State<Arc<EvalState>>) -> Json<EvalStatus>
         continuous_interval_secs: eval.continuous_interval_secs,
         current_prompt_index: eval.current_prompt_index,
         total_prompts: eval.total_prompts,
+        runner_restart_policy: eval.runner_restart_policy,
     })
 }
 
+/// GET /eval/stream — SSE stream of per-prompt progress for whichever run
+/// is active. Each event's name is its `type`.
+async fn stream_handler(
+    State(state): State<Arc<EvalState>>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
+    let supervisor = state.supervisor.clone();
+    let client = supervisor.stream_clients.register("/eval/stream");
+    let progress_rx = supervisor.evaluation.read().await.progress_tx.subscribe();
+    let rx = stream_clients::forward(supervisor.clone(), progress_rx, client);
+    let conn_guard = SseConnectionGuard::new(supervisor.active_sse_connections.clone());
+
+    let event_stream = ReceiverStream::new(rx).map(move |event| {
+        let _hold = &conn_guard;
+        let data = serde_json::to_string(&event).unwrap_or_default();
+        Ok(Event::default().event(event.name()).data(data))
+    });
+
+    let shutdown = Box::pin(async move { supervisor.shutdown_signal().await });
+    let event_stream = futures::StreamExt::take_until(event_stream, shutdown);
+
+    Sse::new(event_stream).keep_alive(KeepAlive::default())
+}
+
 async fn start_handler(
     State(state): State<Arc<EvalState>>,
+    Query(queue): Query<QueueParams>,
     Json(body): Json<StartRequest>,
 ) -> Json<MessageResponse> {
-    // Check if already running
-    {
-        let eval = state.supervisor.evaluation.read().await;
-        if eval.running {
+    if let Err(message) = validate_judge(&body.judge_provider, &body.judge_model) {
+        return Json(MessageResponse { ok: false, message });
+    }
+    let samples_per_prompt = body.samples_per_prompt.unwrap_or(1);
+    if !(1..=determinism::MAX_SAMPLES_PER_PROMPT).contains(&samples_per_prompt) {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!(
+                "samples_per_prompt must be between 1 and {}",
+                determinism::MAX_SAMPLES_PER_PROMPT
+            ),
+        });
+    }
+    let prompt_ids = match resolve_prompt_ids(&state, body.suite, body.prompt_ids) {
+        Ok(ids) => ids,
+        Err(message) => return Json(MessageResponse { ok: false, message }),
+    };
+
+    let opts = EvalRunOptions {
+        prompt_ids,
+        concurrency: body.concurrency.unwrap_or(1),
+        judge_provider: body.judge_provider,
+        judge_model: body.judge_model,
+        retry_of: None,
+        judge_template_version: None,
+        rescore_of: None,
+        matrix_id: None,
+        resume: None,
+        samples_per_prompt,
+        generation_provider: None,
+        generation_model: None,
+    };
+
+    let Some(stop_rx) = reserve_run(&state).await else {
+        if !queue.queue {
             return Json(MessageResponse {
                 ok: false,
                 message: "Eval run already in progress".to_string(),
             });
         }
-    }
+        let mut jobs = state.supervisor.job_queue.write().await;
+        return Json(match jobs.enqueue(JobRequest::Eval(opts), queue.priority) {
+            Ok(entry) => MessageResponse {
+                ok: true,
+                message: format!(
+                    "Eval run queued as job {} (position {})",
+                    entry.id, entry.position
+                ),
+            },
+            Err(message) => MessageResponse { ok: false, message },
+        });
+    };
+    let db = state.db.clone();
+    let supervisor = state.supervisor.clone();
 
-    // Create stop channel
-    let (stop_tx, stop_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        evaluation::engine::run_eval(db, supervisor, opts, stop_rx).await;
+    });
+
+    Json(MessageResponse {
+        ok: true,
+        message: "Eval run started".to_string(),
+    })
+}
+
+/// Prompt IDs for a run given either a saved suite name or explicit IDs.
+fn resolve_prompt_ids(
+    state: &EvalState,
+    suite: Option<String>,
+    prompt_ids: Option<Vec<String>>,
+) -> Result<Option<Vec<String>>, String> {
+    match (suite, prompt_ids) {
+        (Some(_), Some(_)) => Err("Pass either suite or prompt_ids, not both".to_string()),
+        (Some(name), None) => match state.db.get_suite(&name) {
+            Ok(Some(suite)) => Ok(Some(suite.prompt_ids)),
+            Ok(None) => Err(format!("Prompt suite '{}' not found", name)),
+            Err(e) => Err(format!("Failed to load prompt suite: {}", e)),
+        },
+        (None, ids) => Ok(ids),
+    }
+}
 
-    // Store stop sender
+async fn matrix_start_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(body): Json<MatrixStartRequest>,
+) -> Json<MessageResponse> {
+    if body.models.is_empty() || body.models.len() > MAX_MATRIX_MODELS {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!(
+                "models must list 1-{} provider/model pairs",
+                MAX_MATRIX_MODELS
+            ),
+        });
+    }
+    if let Some(m) = body
+        .models
+        .iter()
+        .find(|m| resolve_model_id(&m.provider, &m.model).is_none())
     {
-        let mut eval = state.supervisor.evaluation.write().await;
-        eval.stop_tx = Some(stop_tx);
+        return Json(MessageResponse {
+            ok: false,
+            message: format!("Unknown model {}/{}", m.provider, m.model),
+        });
+    }
+    if let Err(message) = validate_judge(&body.judge_provider, &body.judge_model) {
+        return Json(MessageResponse { ok: false, message });
     }
+    let prompt_ids = match resolve_prompt_ids(&state, body.suite, body.prompt_ids) {
+        Ok(ids) => ids,
+        Err(message) => return Json(MessageResponse { ok: false, message }),
+    };
+    let Some(stop_rx) = reserve_run(&state).await else {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Eval run already in progress".to_string(),
+        });
+    };
 
+    let matrix_id = uuid::Uuid::new_v4().to_string();
+    let count = body.models.len();
+    let opts = EvalRunOptions {
+        prompt_ids,
+        concurrency: body.concurrency.unwrap_or(1),
+        judge_provider: body.judge_provider,
+        judge_model: body.judge_model,
+        ..Default::default()
+    };
     let db = state.db.clone();
     let supervisor = state.supervisor.clone();
-    let prompt_ids = body.prompt_ids;
+    let id = matrix_id.clone();
 
     tokio::spawn(async move {
-        evaluation::engine::run_eval(db, supervisor, prompt_ids, stop_rx).await;
+        evaluation::engine::run_matrix(db, supervisor, id, body.models, opts, stop_rx).await;
     });
 
     Json(MessageResponse {
         ok: true,
-        message: "Eval run started".to_string(),
+        message: format!("Model matrix {} started over {} model(s)", matrix_id, count),
     })
 }
 
+async fn leaderboard_handler(
+    State(state): State<Arc<EvalState>>,
+    Query(params): Query<LeaderboardParams>,
+) -> Json<Leaderboard> {
+    match leaderboard::leaderboard(&state.db, params.matrix_id.as_deref()) {
+        Ok(board) => Json(board),
+        Err(e) => {
+            tracing::error!("Failed to build eval leaderboard: {}", e);
+            Json(Leaderboard {
+                matrix_id: params.matrix_id,
+                entries: Vec::new(),
+            })
+        }
+    }
+}
+
+/// Claim the engine for a run about to start: in one write lock, check that
+/// nothing owns it, mark it running and install a fresh stop channel. `None`
+/// when it is busy. The run releases it when it ends
+/// (`evaluation::engine::run_eval`).
+async fn reserve_run(state: &EvalState) -> Option<watch::Receiver<bool>> {
+    let mut eval = state.supervisor.evaluation.write().await;
+    if eval.busy() {
+        return None;
+    }
+    let (stop_tx, stop_rx) = watch::channel(false);
+    eval.running = true;
+    eval.stop_tx = Some(stop_tx);
+    Some(stop_rx)
+}
+
+/// Launch queued eval runs once the engine is idle. Runs are awaited inline,
+/// and a job that loses the reservation to a direct start keeps its place in
+/// line.
+async fn dispatch_queued_evals(state: Arc<EvalState>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        job_queue::DISPATCH_INTERVAL_SECS,
+    ));
+    loop {
+        interval.tick().await;
+        {
+            let eval = state.supervisor.evaluation.read().await;
+            if eval.busy() {
+                continue;
+            }
+        }
+        let job = state
+            .supervisor
+            .job_queue
+            .write()
+            .await
+            .pop_next(JobKind::Eval);
+        let Some(job) = job else { continue };
+        let JobRequest::Eval(opts) = job.request.clone() else {
+            continue;
+        };
+        let Some(stop_rx) = reserve_run(&state).await else {
+            state.supervisor.job_queue.write().await.requeue(job);
+            continue;
+        };
+
+        state
+            .supervisor
+            .logs
+            .emit(
+                LogSource::Supervisor,
+                LogLevel::Info,
+                format!("Starting queued eval run (job {})", job.id),
+            )
+            .await;
+
+        evaluation::engine::run_eval(state.db.clone(), state.supervisor.clone(), opts, stop_rx)
+            .await;
+    }
+}
+
+/// Fire due cron schedules by enqueueing an eval job, so a schedule that
+/// comes due mid-run starts as soon as the engine is idle.
+async fn run_eval_schedules(state: Arc<EvalState>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        schedule::SCHEDULE_CHECK_INTERVAL_SECS,
+    ));
+    loop {
+        interval.tick().await;
+        let schedules = match state.db.list_schedules() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::error!("Failed to load eval schedules: {}", e);
+                continue;
+            }
+        };
+        let now = chrono::Local::now().naive_local();
+        for sched in schedules.into_iter().filter(|s| s.enabled) {
+            if !sched.next_fire().is_some_and(|t| t <= now) {
+                continue;
+            }
+            // Record the firing first so a failure below can't re-fire every
+            // tick.
+            if let Err(e) = state
+                .db
+                .mark_schedule_fired(&sched.id, &Utc::now().to_rfc3339())
+            {
+                tracing::error!("Failed to mark eval schedule {} fired: {}", sched.id, e);
+                continue;
+            }
+            let (level, message) = match fire_schedule(&state, &sched).await {
+                Ok(job_id) => (
+                    LogLevel::Info,
+                    format!(
+                        "Eval schedule '{}' fired; queued as job {}",
+                        sched.name, job_id
+                    ),
+                ),
+                Err(e) => (
+                    LogLevel::Warn,
+                    format!("Eval schedule '{}' could not fire: {}", sched.name, e),
+                ),
+            };
+            state
+                .supervisor
+                .logs
+                .emit(LogSource::Supervisor, level, message)
+                .await;
+        }
+    }
+}
+
+async fn fire_schedule(state: &EvalState, sched: &EvalSchedule) -> Result<String, String> {
+    // Resolve the suite now, not at creation, so edits to it apply.
+    let prompt_ids = match &sched.suite {
+        Some(name) => match state.db.get_suite(name) {
+            Ok(Some(suite)) => Some(suite.prompt_ids),
+            Ok(None) => return Err(format!("prompt suite '{}' not found", name)),
+            Err(e) => return Err(format!("failed to load prompt suite: {}", e)),
+        },
+        None => sched.prompt_ids.clone(),
+    };
+    let opts = EvalRunOptions {
+        prompt_ids,
+        concurrency: sched.concurrency.unwrap_or(1),
+        judge_provider: sched.judge_provider.clone(),
+        judge_model: sched.judge_model.clone(),
+        retry_of: None,
+        judge_template_version: None,
+        rescore_of: None,
+        matrix_id: None,
+        resume: None,
+        samples_per_prompt: 1,
+        generation_provider: None,
+        generation_model: None,
+    };
+    let mut jobs = state.supervisor.job_queue.write().await;
+    jobs.enqueue(JobRequest::Eval(opts), 0)
+        .map(|entry| entry.id)
+}
+
 async fn stop_handler(State(state): State<Arc<EvalState>>) -> Json<MessageResponse> {
     let mut eval = state.supervisor.evaluation.write().await;
-    if !eval.running {
+    // A matrix is between runs while `running` is false; it still has to
+    // see the stop, or it goes on to the next pair.
+    if !eval.busy() {
         return Json(MessageResponse {
             ok: false,
             message: "No eval run in progress".to_string(),
@@ -171,22 +808,37 @@ async fn continuous_start_handler(
     State(state): State<Arc<EvalState>>,
     Json(body): Json<ContinuousStartRequest>,
 ) -> Json<MessageResponse> {
-    {
-        let eval = state.supervisor.evaluation.read().await;
-        if eval.running {
-            return Json(MessageResponse {
-                ok: false,
-                message: "Eval run already in progress".to_string(),
-            });
-        }
+    if let Err(message) = validate_judge(&body.judge_provider, &body.judge_model) {
+        return Json(MessageResponse { ok: false, message });
     }
 
     let interval_secs = body.interval_secs.unwrap_or(3600);
+    let opts = EvalRunOptions {
+        prompt_ids: None,
+        concurrency: body.concurrency.unwrap_or(1),
+        judge_provider: body.judge_provider,
+        judge_model: body.judge_model,
+        retry_of: None,
+        judge_template_version: None,
+        rescore_of: None,
+        matrix_id: None,
+        resume: None,
+        samples_per_prompt: 1,
+        generation_provider: None,
+        generation_model: None,
+    };
 
     let (stop_tx, stop_rx) = watch::channel(false);
 
+    // Check and claim in one write lock so two starts can't both pass.
     {
         let mut eval = state.supervisor.evaluation.write().await;
+        if eval.busy() {
+            return Json(MessageResponse {
+                ok: false,
+                message: "Eval run already in progress".to_string(),
+            });
+        }
         eval.continuous_mode = true;
         eval.continuous_interval_secs = interval_secs;
         eval.stop_tx = Some(stop_tx);
@@ -196,7 +848,7 @@ async fn continuous_start_handler(
     let supervisor = state.supervisor.clone();
 
     tokio::spawn(async move {
-        evaluation::engine::run_continuous(db, supervisor, interval_secs, stop_rx).await;
+        evaluation::engine::run_continuous(db, supervisor, interval_secs, opts, stop_rx).await;
     });
 
     Json(MessageResponse {
@@ -225,6 +877,22 @@ async fn continuous_stop_handler(State(state): State<Arc<EvalState>>) -> Json<Me
     })
 }
 
+async fn restart_policy_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(body): Json<RestartPolicyRequest>,
+) -> Json<MessageResponse> {
+    state
+        .supervisor
+        .evaluation
+        .write()
+        .await
+        .runner_restart_policy = body.policy;
+    Json(MessageResponse {
+        ok: true,
+        message: format!("Runner restart policy set to {:?}", body.policy),
+    })
+}
+
 async fn list_runs_handler(
     State(state): State<Arc<EvalState>>,
 ) -> Json<Vec<evaluation::EvalRunSummary>> {
@@ -237,92 +905,1293 @@ async fn list_runs_handler(
     }
 }
 
-async fn get_run_handler(
+async fn retry_failures_handler(
     State(state): State<Arc<EvalState>>,
     Path(id): Path<String>,
-) -> Json<Option<EvalRunWithResults>> {
-    let run = match state.db.get_eval_run(&id) {
-        Ok(Some(r)) => r,
-        Ok(None) => return Json(None),
+) -> Json<MessageResponse> {
+    {
+        let eval = state.supervisor.evaluation.read().await;
+        if eval.busy() {
+            return Json(MessageResponse {
+                ok: false,
+                message: "Eval run already in progress".to_string(),
+            });
+        }
+    }
+
+    let parent = match state.db.get_eval_run(&id) {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Eval run '{}' not found", id),
+            })
+        }
         Err(e) => {
-            tracing::error!("Failed to get eval run: {}", e);
-            return Json(None);
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load eval run: {}", e),
+            })
         }
     };
-
-    let results = match state.db.get_results_for_run(&id) {
-        Ok(r) => r,
+    let failed = match state.db.failed_prompt_ids(&id) {
+        Ok(ids) => ids,
         Err(e) => {
-            tracing::error!("Failed to get eval results: {}", e);
-            Vec::new()
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load failed results: {}", e),
+            })
         }
     };
+    if failed.is_empty() {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!("Eval run '{}' has no failed results", id),
+        });
+    }
+
+    // Same judge and template as the parent so merged scores are comparable.
+    let count = failed.len();
+    let opts = EvalRunOptions {
+        prompt_ids: Some(failed),
+        concurrency: 1,
+        judge_provider: parent.judge_provider,
+        judge_model: parent.judge_model,
+        retry_of: Some(id.clone()),
+        judge_template_version: parent.judge_template_version,
+        rescore_of: None,
+        matrix_id: None,
+        resume: None,
+        samples_per_prompt: 1,
+        generation_provider: parent.generation_provider,
+        generation_model: parent.generation_model,
+    };
+    let Some(stop_rx) = reserve_run(&state).await else {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Eval run already in progress".to_string(),
+        });
+    };
+    let db = state.db.clone();
+    let supervisor = state.supervisor.clone();
+
+    tokio::spawn(async move {
+        evaluation::engine::run_eval(db, supervisor, opts, stop_rx).await;
+    });
 
-    Json(Some(EvalRunWithResults { run, results }))
+    Json(MessageResponse {
+        ok: true,
+        message: format!("Retrying {} failed prompt(s) of run {}", count, id),
+    })
 }
 
-async fn compare_handler(
+async fn prune_handler(
     State(state): State<Arc<EvalState>>,
-    Path((id, baseline_id)): Path<(String, String)>,
-) -> Json<Option<evaluation::CompareReport>> {
-    match evaluation::queries::compare_runs(&state.db, &id, &baseline_id) {
-        Ok(report) => Json(Some(report)),
+    Query(params): Query<PruneParams>,
+) -> Json<PruneReport> {
+    let policy = if params.keep_last.is_some() || params.max_age_days.is_some() {
+        RetentionPolicy {
+            keep_last: params.keep_last,
+            max_age_days: params.max_age_days,
+        }
+    } else {
+        RetentionPolicy::from_env()
+    };
+    let dry_run = params.dry_run.unwrap_or(false);
+    let report = match retention::prune(&state.db, policy, dry_run, params.vacuum.unwrap_or(false))
+    {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!("Failed to prune eval runs: {}", e);
+            PruneReport {
+                policy,
+                dry_run,
+                ..Default::default()
+            }
+        }
+    };
+    if !dry_run && !report.pruned.is_empty() {
+        state
+            .supervisor
+            .logs
+            .emit(
+                LogSource::Supervisor,
+                LogLevel::Info,
+                format!(
+                    "Pruned {} eval run(s) ({} kept)",
+                    report.pruned.len(),
+                    report.kept
+                ),
+            )
+            .await;
+    }
+    Json(report)
+}
+
+async fn export_run_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Query(params): Query<ExportParams>,
+) -> Response {
+    match state.db.get_eval_run(&id) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("Eval run '{}' not found", id),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get eval run: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    }
+
+    let filter = ResultFilter {
+        include_workflow_json: false,
+        ..Default::default()
+    };
+    let results = match state.db.query_results(&id, &filter) {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!("Failed to get eval results for export: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let categories: HashMap<String, String> = state
+        .db
+        .list_test_prompts()
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to list test prompts for export: {}", e);
+            Vec::new()
+        })
+        .into_iter()
+        .map(|p| (p.id, p.category))
+        .collect();
+
+    let body = export::render(params.format, &export::rows(results, &categories));
+    let disposition = format!(
+        "attachment; filename=\"eval-run-{}.{}\"",
+        id,
+        params.format.extension()
+    );
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                params.format.content_type().to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn rescore_run_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Json(body): Json<RescoreRequest>,
+) -> Json<MessageResponse> {
+    if let Err(message) = validate_judge(&body.judge_provider, &body.judge_model) {
+        return Json(MessageResponse { ok: false, message });
+    }
+    {
+        let eval = state.supervisor.evaluation.read().await;
+        if eval.busy() {
+            return Json(MessageResponse {
+                ok: false,
+                message: "Eval run already in progress".to_string(),
+            });
+        }
+    }
+
+    let source = match state.db.get_eval_run(&id) {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Eval run '{}' not found", id),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load eval run: {}", e),
+            })
+        }
+    };
+    let template = match body.judge_template_version {
+        Some(version) => state.db.get_judge_template(version),
+        None => state.db.active_judge_template(),
+    };
+    let version = match template {
+        Ok(Some(t)) => t.version,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: match body.judge_template_version {
+                    Some(v) => format!("Judge prompt v{} not found", v),
+                    None => "No active judge prompt".to_string(),
+                },
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load judge prompt: {}", e),
+            })
+        }
+    };
+
+    let (judge_provider, judge_model) = match (body.judge_provider, body.judge_model) {
+        (Some(p), Some(m)) => (Some(p), Some(m)),
+        _ => (source.judge_provider, source.judge_model),
+    };
+    let opts = EvalRunOptions {
+        prompt_ids: None,
+        concurrency: 1,
+        judge_provider,
+        judge_model,
+        retry_of: None,
+        judge_template_version: Some(version),
+        rescore_of: Some(id.clone()),
+        matrix_id: None,
+        resume: None,
+        samples_per_prompt: 1,
+        generation_provider: None,
+        generation_model: None,
+    };
+    let Some(stop_rx) = reserve_run(&state).await else {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Eval run already in progress".to_string(),
+        });
+    };
+    let db = state.db.clone();
+    let supervisor = state.supervisor.clone();
+
+    tokio::spawn(async move {
+        evaluation::engine::run_eval(db, supervisor, opts, stop_rx).await;
+    });
+
+    Json(MessageResponse {
+        ok: true,
+        message: format!("Re-scoring run {} with judge prompt v{}", id, version),
+    })
+}
+
+async fn rescore_sample_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Json(body): Json<RescoreSampleRequest>,
+) -> Json<MessageResponse> {
+    let judge_provider = Some(body.judge_provider);
+    let judge_model = Some(body.judge_model);
+    if let Err(message) = validate_judge(&judge_provider, &judge_model) {
+        return Json(MessageResponse { ok: false, message });
+    }
+    let percent = body.percent.unwrap_or(DEFAULT_SAMPLE_PERCENT);
+    if percent <= 0.0 || percent > 100.0 {
+        return Json(MessageResponse {
+            ok: false,
+            message: "percent must be in (0, 100]".to_string(),
+        });
+    }
+    {
+        let eval = state.supervisor.evaluation.read().await;
+        if eval.busy() {
+            return Json(MessageResponse {
+                ok: false,
+                message: "Eval run already in progress".to_string(),
+            });
+        }
+    }
+
+    let source = match state.db.get_eval_run(&id) {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Eval run '{}' not found", id),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load eval run: {}", e),
+            })
+        }
+    };
+    if source.judge_provider == judge_provider && source.judge_model == judge_model {
+        return Json(MessageResponse {
+            ok: false,
+            message: "The second judge must differ from the run's judge".to_string(),
+        });
+    }
+
+    let filter = evaluation::ResultFilter {
+        include_workflow_json: false,
+        ..Default::default()
+    };
+    let scored: Vec<String> = match state.db.query_results(&id, &filter) {
+        Ok(results) => results
+            .into_iter()
+            .filter(|r| r.overall_score.is_some())
+            .map(|r| r.test_prompt_id)
+            .collect(),
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load eval results: {}", e),
+            })
+        }
+    };
+    let seed = body.seed.unwrap_or_else(rand::random);
+    let sample = reliability::sample(&scored, percent, seed);
+    if sample.is_empty() {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!("Eval run '{}' has no scored results", id),
+        });
+    }
+
+    let opts = EvalRunOptions {
+        prompt_ids: Some(sample.clone()),
+        concurrency: 1,
+        judge_provider,
+        judge_model,
+        retry_of: None,
+        judge_template_version: body
+            .judge_template_version
+            .or(source.judge_template_version),
+        rescore_of: Some(id.clone()),
+        matrix_id: None,
+        resume: None,
+        samples_per_prompt: 1,
+        generation_provider: None,
+        generation_model: None,
+    };
+    let Some(stop_rx) = reserve_run(&state).await else {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Eval run already in progress".to_string(),
+        });
+    };
+    let db = state.db.clone();
+    let supervisor = state.supervisor.clone();
+
+    tokio::spawn(async move {
+        evaluation::engine::run_eval(db, supervisor, opts, stop_rx).await;
+    });
+
+    Json(MessageResponse {
+        ok: true,
+        message: format!(
+            "Re-scoring {} of {} result(s) of run {} (seed {}); see /eval/runs/{}/agreement",
+            sample.len(),
+            scored.len(),
+            id,
+            seed,
+            id
+        ),
+    })
+}
+
+/// Start resuming run `id` in the background. Returns the message for the
+/// caller or why the run can't be resumed.
+async fn start_resume(state: &EvalState, id: &str) -> Result<String, String> {
+    if state.supervisor.evaluation.read().await.busy() {
+        return Err("Eval run already in progress".to_string());
+    }
+    let run = match state.db.get_eval_run(id) {
+        Ok(Some(run)) => run,
+        Ok(None) => return Err(format!("Eval run '{}' not found", id)),
+        Err(e) => return Err(format!("Failed to load eval run: {}", e)),
+    };
+    if run.status != "interrupted" && run.status != "aborted" {
+        return Err(format!(
+            "Eval run '{}' is {}; only interrupted or aborted runs can be resumed",
+            id, run.status
+        ));
+    }
+
+    let opts = evaluation::engine::resume_options(&run);
+    let stop_rx = reserve_run(state)
+        .await
+        .ok_or_else(|| "Eval run already in progress".to_string())?;
+    let db = state.db.clone();
+    let supervisor = state.supervisor.clone();
+    tokio::spawn(async move {
+        evaluation::engine::run_eval(db, supervisor, opts, stop_rx).await;
+    });
+
+    Ok(format!(
+        "Resuming run {} ({}/{} prompts done)",
+        id, run.prompts_completed, run.prompts_total
+    ))
+}
+
+async fn resume_run_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    Json(match start_resume(&state, &id).await {
+        Ok(message) => MessageResponse { ok: true, message },
+        Err(message) => MessageResponse { ok: false, message },
+    })
+}
+
+/// Resume the latest run cut off by the previous supervisor exit, once the
+/// runner API is up to generate workflows.
+async fn resume_interrupted_run(state: Arc<EvalState>) {
+    let id = match state.db.latest_interrupted_run() {
+        Ok(Some(id)) => id,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Failed to look up interrupted eval runs: {}", e);
+            return;
+        }
+    };
+    if !crate::process::port::wait_for_runner_api(crate::config::RUNNER_API_PORT).await {
+        tracing::warn!(
+            "Runner API not up; not resuming interrupted eval run {}",
+            id
+        );
+        return;
+    }
+    let (level, message) = match start_resume(&state, &id).await {
+        Ok(message) => (LogLevel::Info, message),
+        Err(message) => (
+            LogLevel::Warn,
+            format!("Could not resume eval run {}: {}", id, message),
+        ),
+    };
+    state
+        .supervisor
+        .logs
+        .emit(LogSource::Supervisor, level, message)
+        .await;
+}
+
+async fn override_scores_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<i64>,
+    Json(body): Json<ScoreOverrideRequest>,
+) -> Json<MessageResponse> {
+    let reason = body.reason.trim();
+    if reason.is_empty() {
+        return Json(MessageResponse {
+            ok: false,
+            message: "An override needs a reason".to_string(),
+        });
+    }
+    let values = body.scores.values();
+    if values.iter().all(Option::is_none) {
+        return Json(MessageResponse {
+            ok: false,
+            message: "No scores given".to_string(),
+        });
+    }
+    if values.iter().flatten().any(|s| !(1..=5).contains(s)) {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Scores must be between 1 and 5".to_string(),
+        });
+    }
+
+    let (run_id, record) = match state.db.override_scores(id, &body.scores, reason) {
+        Ok(Some(updated)) => updated,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Eval result {} not found", id),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to override scores: {}", e),
+            })
+        }
+    };
+
+    // The override may have moved the latest run's score.
+    match state.db.latest_completed_score() {
+        Ok(score) => {
+            state
+                .supervisor
+                .evaluation
+                .write()
+                .await
+                .last_completed_score = score
+        }
+        Err(e) => tracing::warn!("Failed to reload latest eval score: {}", e),
+    }
+    state
+        .supervisor
+        .logs
+        .emit(
+            LogSource::Supervisor,
+            LogLevel::Info,
+            format!(
+                "Eval result {} of run {} scores overridden: {}",
+                id, run_id, record.reason
+            ),
+        )
+        .await;
+
+    Json(MessageResponse {
+        ok: true,
+        message: format!("Overrode scores of result {} in run {}", id, run_id),
+    })
+}
+
+async fn agreement_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Query(params): Query<AgreementParams>,
+) -> Json<Option<AgreementReport>> {
+    match reliability::agreement(&state.db, &id, params.rescore_id.as_deref()) {
+        Ok(report) => Json(report),
+        Err(e) => {
+            tracing::error!("Failed to compute judge agreement: {}", e);
+            Json(None)
+        }
+    }
+}
+
+async fn determinism_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+) -> Json<Option<DeterminismReport>> {
+    match determinism::report(&state.db, &id) {
+        Ok(report) => Json(report),
+        Err(e) => {
+            tracing::error!("Failed to compute determinism report: {}", e);
+            Json(None)
+        }
+    }
+}
+
+async fn get_run_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Query(params): Query<ResultParams>,
+) -> Json<Option<EvalRunWithResults>> {
+    let run = match state.db.get_eval_run(&id) {
+        Ok(Some(r)) => r,
+        Ok(None) => return Json(None),
+        Err(e) => {
+            tracing::error!("Failed to get eval run: {}", e);
+            return Json(None);
+        }
+    };
+
+    let filter = params.into_filter();
+    let results = match state.db.query_results(&id, &filter) {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!("Failed to get eval results: {}", e);
+            Vec::new()
+        }
+    };
+    let results_total = match state.db.count_results(&id, &filter) {
+        Ok(n) => n,
+        Err(e) => {
+            tracing::error!("Failed to count eval results: {}", e);
+            results.len()
+        }
+    };
+
+    let environment = match state.db.get_run_environment(&id) {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::error!("Failed to get eval run environment: {}", e);
+            None
+        }
+    };
+
+    Json(Some(EvalRunWithResults {
+        run,
+        environment,
+        results,
+        results_total,
+    }))
+}
+
+async fn trends_handler(
+    State(state): State<Arc<EvalState>>,
+    Query(params): Query<TrendParams>,
+) -> Json<Vec<evaluation::CategoryTrendPoint>> {
+    let limit = params.limit.unwrap_or(20);
+    match evaluation::queries::category_trends(&state.db, params.category.as_deref(), limit) {
+        Ok(points) => Json(points),
+        Err(e) => {
+            tracing::error!("Failed to get eval category trends: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn compare_handler(
+    State(state): State<Arc<EvalState>>,
+    Path((id, baseline_id)): Path<(String, String)>,
+) -> Json<Option<evaluation::CompareReport>> {
+    match evaluation::queries::compare_runs(&state.db, &id, &baseline_id) {
+        Ok(report) => Json(Some(report)),
+        Err(e) => {
+            tracing::error!("Failed to compare runs: {}", e);
+            Json(None)
+        }
+    }
+}
+
+async fn get_baseline_handler(State(state): State<Arc<EvalState>>) -> Json<Option<BaselinePin>> {
+    match state.db.get_baseline() {
+        Ok(pin) => Json(pin),
+        Err(e) => {
+            tracing::error!("Failed to load eval baseline: {}", e);
+            Json(None)
+        }
+    }
+}
+
+async fn pin_baseline_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(body): Json<PinBaselineRequest>,
+) -> Json<MessageResponse> {
+    match state.db.get_eval_run(&body.run_id) {
+        Ok(Some(run)) if run.status == "completed" => {}
+        Ok(Some(run)) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!(
+                    "Run {} is {}; only completed runs can be pinned",
+                    run.id, run.status
+                ),
+            })
+        }
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Run {} not found", body.run_id),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load run: {}", e),
+            })
+        }
+    }
+
+    let pin = BaselinePin {
+        run_id: body.run_id,
+        pinned_at: Utc::now().to_rfc3339(),
+        max_avg_drop: body
+            .max_avg_drop
+            .unwrap_or(eval_baseline::DEFAULT_MAX_AVG_DROP),
+        max_regressions: body.max_regressions.unwrap_or(0),
+        webhook_url: body.webhook_url,
+    };
+    match state.db.set_baseline(&pin) {
+        Ok(()) => Json(MessageResponse {
+            ok: true,
+            message: format!("Run {} pinned as eval baseline", pin.run_id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to pin baseline: {}", e),
+        }),
+    }
+}
+
+async fn unpin_baseline_handler(State(state): State<Arc<EvalState>>) -> Json<MessageResponse> {
+    match state.db.clear_baseline() {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: "Eval baseline unpinned".to_string(),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: "No eval baseline is pinned".to_string(),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to unpin baseline: {}", e),
+        }),
+    }
+}
+
+async fn search_handler(
+    State(state): State<Arc<EvalState>>,
+    Query(params): Query<SearchParams>,
+) -> Json<Vec<evaluation::SearchHit>> {
+    let limit = params.limit.unwrap_or(50).min(500);
+    match evaluation::queries::search_results(&state.db, &params.q, limit) {
+        Ok(hits) => Json(hits),
+        Err(e) => {
+            tracing::error!("Failed to search eval results: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn list_test_suite_handler(State(state): State<Arc<EvalState>>) -> Json<Vec<TestPrompt>> {
+    match state.db.list_test_prompts() {
+        Ok(prompts) => Json(prompts),
+        Err(e) => {
+            tracing::error!("Failed to list test prompts: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn add_test_prompt_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(mut prompt): Json<TestPrompt>,
+) -> Json<MessageResponse> {
+    let now = Utc::now().to_rfc3339();
+    if prompt.created_at.is_empty() {
+        prompt.created_at = now.clone();
+    }
+    if prompt.updated_at.is_empty() {
+        prompt.updated_at = now;
+    }
+
+    match state.db.insert_test_prompt(&prompt) {
+        Ok(()) => Json(MessageResponse {
+            ok: true,
+            message: format!("Test prompt '{}' added", prompt.id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to add test prompt: {}", e),
+        }),
+    }
+}
+
+async fn create_mutations_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Json(body): Json<MutationRequest>,
+) -> Json<MutationResponse> {
+    let fail = |message: String| {
+        Json(MutationResponse {
+            ok: false,
+            message,
+            created: Vec::new(),
+        })
+    };
+    if let Err(message) = validate_judge(&body.provider, &body.model) {
+        return fail(message);
+    }
+    let count = body.count.unwrap_or(DEFAULT_VARIANTS);
+    if count == 0 || count > MAX_VARIANTS {
+        return fail(format!("count must be between 1 and {}", MAX_VARIANTS));
+    }
+    let source = match state.db.get_test_prompt(&id) {
+        Ok(Some(p)) => p,
+        Ok(None) => return fail(format!("Test prompt '{}' not found", id)),
+        Err(e) => return fail(format!("Failed to load test prompt: {}", e)),
+    };
+
+    let model = JudgeModel::resolve(&state.supervisor, body.provider, body.model).await;
+    let variants = match mutation::generate(&model, &source, count, body.status).await {
+        Ok(v) => v,
+        Err(e) => return fail(format!("Failed to generate variants: {}", e)),
+    };
+
+    let mut created = Vec::with_capacity(variants.len());
+    for variant in variants {
+        match state.db.insert_test_prompt(&variant) {
+            Ok(()) => created.push(variant),
+            Err(e) => tracing::error!("Failed to insert prompt variant {}: {}", variant.id, e),
+        }
+    }
+    Json(MutationResponse {
+        ok: !created.is_empty(),
+        message: format!(
+            "Created {} variant(s) of '{}' with {}/{}",
+            created.len(),
+            id,
+            model.provider,
+            model.model
+        ),
+        created,
+    })
+}
+
+async fn update_test_prompt_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Json(mut prompt): Json<TestPrompt>,
+) -> Json<MessageResponse> {
+    prompt.updated_at = Utc::now().to_rfc3339();
+
+    match state.db.update_test_prompt(&id, &prompt) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Test prompt '{}' updated", id),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Test prompt '{}' not found", id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to update: {}", e),
+        }),
+    }
+}
+
+async fn delete_test_prompt_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    match state.db.delete_test_prompt(&id) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Test prompt '{}' deleted", id),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Test prompt '{}' not found", id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to delete: {}", e),
+        }),
+    }
+}
+
+async fn set_prompt_status_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+    Json(body): Json<PromptStatusRequest>,
+) -> Json<MessageResponse> {
+    let current = match state.db.get_test_prompt(&id) {
+        Ok(Some(p)) => p.status,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Test prompt '{}' not found", id),
+            })
+        }
         Err(e) => {
-            tracing::error!("Failed to compare runs: {}", e);
-            Json(None)
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load test prompt: {}", e),
+            })
         }
+    };
+
+    if !current.can_transition_to(body.status) {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!(
+                "Cannot move test prompt '{}' from {} to {}",
+                id,
+                current.as_str(),
+                body.status.as_str()
+            ),
+        });
+    }
+
+    match state.db.set_prompt_status(&id, body.status) {
+        Ok(_) => Json(MessageResponse {
+            ok: true,
+            message: format!(
+                "Test prompt '{}' moved from {} to {}",
+                id,
+                current.as_str(),
+                body.status.as_str()
+            ),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to update status: {}", e),
+        }),
     }
 }
 
-async fn list_test_suite_handler(State(state): State<Arc<EvalState>>) -> Json<Vec<TestPrompt>> {
-    match state.db.list_test_prompts() {
-        Ok(prompts) => Json(prompts),
+async fn clear_needs_review_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    match state.db.clear_needs_review(&id) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Cleared review flag on test prompt '{}'", id),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Test prompt '{}' not found", id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to clear review flag: {}", e),
+        }),
+    }
+}
+
+async fn export_prompts_handler(State(state): State<Arc<EvalState>>) -> Json<PromptBundle> {
+    let prompts = state.db.list_test_prompts().unwrap_or_else(|e| {
+        tracing::error!("Failed to list test prompts for export: {}", e);
+        Vec::new()
+    });
+    let suites = state.db.list_suites().unwrap_or_else(|e| {
+        tracing::error!("Failed to list prompt suites for export: {}", e);
+        Vec::new()
+    });
+    Json(PromptBundle {
+        version: bundle::BUNDLE_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        prompts,
+        suites,
+    })
+}
+
+async fn import_prompts_handler(
+    State(state): State<Arc<EvalState>>,
+    Query(params): Query<ImportParams>,
+    Json(incoming): Json<PromptBundle>,
+) -> Json<ImportReport> {
+    let mut report = ImportReport::default();
+    if incoming.version != bundle::BUNDLE_VERSION {
+        report.errors.push(format!(
+            "Unsupported bundle version {} (expected {})",
+            incoming.version,
+            bundle::BUNDLE_VERSION
+        ));
+        return Json(report);
+    }
+    let policy = params.on_conflict.unwrap_or_default();
+    let now = Utc::now().to_rfc3339();
+
+    // Prompts
+    let existing: HashSet<String> = match state.db.list_test_prompts() {
+        Ok(p) => p.into_iter().map(|p| p.id).collect(),
         Err(e) => {
-            tracing::error!("Failed to list test prompts: {}", e);
+            report
+                .errors
+                .push(format!("Failed to list test prompts: {}", e));
+            return Json(report);
+        }
+    };
+    let incoming_ids: Vec<String> = incoming.prompts.iter().map(|p| p.id.clone()).collect();
+    let plan = bundle::plan_import(&existing, &incoming_ids, policy);
+    let mut renames = HashMap::new();
+
+    for (mut prompt, action) in incoming.prompts.into_iter().zip(plan) {
+        if prompt.created_at.is_empty() {
+            prompt.created_at = now.clone();
+        }
+        prompt.updated_at = now.clone();
+        let result = match &action {
+            ImportAction::Insert(id) => state.db.insert_test_prompt(&prompt).map(|_| {
+                report.inserted.push(id.clone());
+            }),
+            ImportAction::Overwrite(id) => state.db.replace_test_prompt(&prompt).map(|_| {
+                report.overwritten.push(id.clone());
+            }),
+            ImportAction::Rename { from, to } => {
+                prompt.id = to.clone();
+                state.db.insert_test_prompt(&prompt).map(|_| {
+                    renames.insert(from.clone(), to.clone());
+                    report.renamed.push(RenamedEntry {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                })
+            }
+            ImportAction::Skip(id) => {
+                report.skipped.push(id.clone());
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("{}: {}", prompt.id, e));
+        }
+    }
+
+    // Suites — same policy, keyed by name, membership follows prompt renames
+    let existing: HashSet<String> = match state.db.list_suites() {
+        Ok(s) => s.into_iter().map(|s| s.name).collect(),
+        Err(e) => {
+            report.errors.push(format!("Failed to list suites: {}", e));
+            return Json(report);
+        }
+    };
+    let incoming_names: Vec<String> = incoming.suites.iter().map(|s| s.name.clone()).collect();
+    let plan = bundle::plan_import(&existing, &incoming_names, policy);
+
+    for (mut suite, action) in incoming.suites.into_iter().zip(plan) {
+        bundle::remap_suite_ids(&mut suite, &renames);
+        if suite.created_at.is_empty() {
+            suite.created_at = now.clone();
+        }
+        suite.updated_at = now.clone();
+        let result = match &action {
+            ImportAction::Insert(_) => state.db.insert_suite(&suite),
+            ImportAction::Overwrite(_) => state.db.replace_suite(&suite),
+            ImportAction::Rename { to, .. } => {
+                suite.name = to.clone();
+                state.db.insert_suite(&suite)
+            }
+            ImportAction::Skip(_) => Ok(()),
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("suite {}: {}", suite.name, e));
+        }
+    }
+
+    state
+        .supervisor
+        .logs
+        .emit(
+            LogSource::Supervisor,
+            LogLevel::Info,
+            format!(
+                "Imported test prompts: {} inserted, {} overwritten, {} renamed, {} skipped, {} errors",
+                report.inserted.len(),
+                report.overwritten.len(),
+                report.renamed.len(),
+                report.skipped.len(),
+                report.errors.len()
+            ),
+        )
+        .await;
+
+    Json(report)
+}
+
+async fn sync_prompts_handler(
+    State(state): State<Arc<EvalState>>,
+    Query(params): Query<SyncParams>,
+) -> Json<SyncReport> {
+    let dir = prompt_source::prompts_dir(&state.supervisor.config);
+    let dry_run = params.dry_run.unwrap_or(false);
+    let report = match prompt_source::sync(&state.db, &dir, dry_run) {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!("Failed to sync eval prompts from {:?}: {}", dir, e);
+            SyncReport {
+                dir: dir.display().to_string(),
+                dry_run,
+                errors: vec![e.to_string()],
+                ..Default::default()
+            }
+        }
+    };
+    if !dry_run && report.errors.is_empty() {
+        state
+            .supervisor
+            .logs
+            .emit(
+                LogSource::Supervisor,
+                LogLevel::Info,
+                format!(
+                    "Synced test prompts from {}: {} added, {} updated, {} drifted",
+                    report.dir,
+                    report.added.len(),
+                    report.updated.len(),
+                    report.drifted.len()
+                ),
+            )
+            .await;
+    }
+    Json(report)
+}
+
+async fn list_judge_prompts_handler(
+    State(state): State<Arc<EvalState>>,
+) -> Json<Vec<JudgeTemplate>> {
+    match state.db.list_judge_templates() {
+        Ok(templates) => Json(templates),
+        Err(e) => {
+            tracing::error!("Failed to list judge prompts: {}", e);
             Json(Vec::new())
         }
     }
 }
 
-async fn add_test_prompt_handler(
+async fn get_judge_prompt_handler(
     State(state): State<Arc<EvalState>>,
-    Json(mut prompt): Json<TestPrompt>,
+    Path(version): Path<i64>,
+) -> Json<Option<JudgeTemplate>> {
+    match state.db.get_judge_template(version) {
+        Ok(t) => Json(t),
+        Err(e) => {
+            tracing::error!("Failed to get judge prompt: {}", e);
+            Json(None)
+        }
+    }
+}
+
+/// Templates are immutable once stored; an edit is a new version. Pass
+/// `active: true` to switch new runs to it immediately.
+async fn create_judge_prompt_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(mut template): Json<JudgeTemplate>,
 ) -> Json<MessageResponse> {
-    let now = Utc::now().to_rfc3339();
-    if prompt.created_at.is_empty() {
-        prompt.created_at = now.clone();
+    if let Err(message) = template.validate() {
+        return Json(MessageResponse { ok: false, message });
     }
-    if prompt.updated_at.is_empty() {
-        prompt.updated_at = now;
+    template.created_at = Utc::now().to_rfc3339();
+
+    match state.db.insert_judge_template(&template) {
+        Ok(version) => Json(MessageResponse {
+            ok: true,
+            message: if template.active {
+                format!("Judge prompt v{} created and activated", version)
+            } else {
+                format!("Judge prompt v{} created", version)
+            },
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to create judge prompt: {}", e),
+        }),
+    }
+}
+
+async fn activate_judge_prompt_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(version): Path<i64>,
+) -> Json<MessageResponse> {
+    match state.db.activate_judge_template(version) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Judge prompt v{} is now active", version),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Judge prompt v{} not found", version),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to activate judge prompt: {}", e),
+        }),
     }
+}
 
-    match state.db.insert_test_prompt(&prompt) {
+/// Only unused, inactive versions can be deleted, so every run's
+/// `judge_template_version` keeps resolving.
+async fn delete_judge_prompt_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(version): Path<i64>,
+) -> Json<MessageResponse> {
+    match state.db.get_judge_template(version) {
+        Ok(Some(t)) if t.active => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Judge prompt v{} is active", version),
+            })
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Judge prompt v{} not found", version),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load judge prompt: {}", e),
+            })
+        }
+    }
+    match state.db.judge_template_run_count(version) {
+        Ok(0) => {}
+        Ok(n) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Judge prompt v{} scored {} run(s)", version, n),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to check judge prompt usage: {}", e),
+            })
+        }
+    }
+
+    match state.db.delete_judge_template(version) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Judge prompt v{} deleted", version),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Judge prompt v{} not found", version),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to delete judge prompt: {}", e),
+        }),
+    }
+}
+
+async fn list_suites_handler(State(state): State<Arc<EvalState>>) -> Json<Vec<PromptSuite>> {
+    match state.db.list_suites() {
+        Ok(suites) => Json(suites),
+        Err(e) => {
+            tracing::error!("Failed to list prompt suites: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn create_suite_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(mut suite): Json<PromptSuite>,
+) -> Json<MessageResponse> {
+    if suite.name.trim().is_empty() {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Suite name must not be empty".to_string(),
+        });
+    }
+    let now = Utc::now().to_rfc3339();
+    suite.created_at = now.clone();
+    suite.updated_at = now;
+
+    match state.db.insert_suite(&suite) {
         Ok(()) => Json(MessageResponse {
             ok: true,
-            message: format!("Test prompt '{}' added", prompt.id),
+            message: format!(
+                "Prompt suite '{}' created with {} prompts",
+                suite.name,
+                suite.prompt_ids.len()
+            ),
         }),
         Err(e) => Json(MessageResponse {
             ok: false,
-            message: format!("Failed to add test prompt: {}", e),
+            message: format!("Failed to create prompt suite: {}", e),
         }),
     }
 }
 
-async fn update_test_prompt_handler(
+async fn update_suite_handler(
     State(state): State<Arc<EvalState>>,
-    Path(id): Path<String>,
-    Json(mut prompt): Json<TestPrompt>,
+    Path(name): Path<String>,
+    Json(mut suite): Json<PromptSuite>,
 ) -> Json<MessageResponse> {
-    prompt.updated_at = Utc::now().to_rfc3339();
+    suite.updated_at = Utc::now().to_rfc3339();
 
-    match state.db.update_test_prompt(&id, &prompt) {
+    match state.db.update_suite(&name, &suite) {
         Ok(true) => Json(MessageResponse {
             ok: true,
-            message: format!("Test prompt '{}' updated", id),
+            message: format!("Prompt suite '{}' updated", name),
         }),
         Ok(false) => Json(MessageResponse {
             ok: false,
-            message: format!("Test prompt '{}' not found", id),
+            message: format!("Prompt suite '{}' not found", name),
         }),
         Err(e) => Json(MessageResponse {
             ok: false,
@@ -331,18 +2200,142 @@ async fn update_test_prompt_handler(
     }
 }
 
-async fn delete_test_prompt_handler(
+async fn delete_suite_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(name): Path<String>,
+) -> Json<MessageResponse> {
+    match state.db.delete_suite(&name) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Prompt suite '{}' deleted", name),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Prompt suite '{}' not found", name),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to delete: {}", e),
+        }),
+    }
+}
+
+async fn list_schedules_handler(State(state): State<Arc<EvalState>>) -> Json<Vec<ScheduleView>> {
+    match state.db.list_schedules() {
+        Ok(schedules) => Json(
+            schedules
+                .into_iter()
+                .map(|schedule| {
+                    let next_fire_at = schedule
+                        .enabled
+                        .then(|| schedule.next_fire())
+                        .flatten()
+                        .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+                        .map(|t| t.to_rfc3339());
+                    ScheduleView {
+                        schedule,
+                        next_fire_at,
+                    }
+                })
+                .collect(),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to list eval schedules: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn create_schedule_handler(
+    State(state): State<Arc<EvalState>>,
+    Json(mut sched): Json<EvalSchedule>,
+) -> Json<MessageResponse> {
+    if sched.name.trim().is_empty() {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Schedule name must not be empty".to_string(),
+        });
+    }
+    if let Err(e) = CronExpr::parse(&sched.cron) {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!("Invalid cron expression: {}", e),
+        });
+    }
+    if let Err(message) = validate_judge(&sched.judge_provider, &sched.judge_model) {
+        return Json(MessageResponse { ok: false, message });
+    }
+    if sched.suite.is_some() && sched.prompt_ids.is_some() {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Pass either suite or prompt_ids, not both".to_string(),
+        });
+    }
+    let now = Utc::now().to_rfc3339();
+    sched.id = uuid::Uuid::new_v4().to_string();
+    sched.last_fired_at = None;
+    sched.created_at = now.clone();
+    sched.updated_at = now;
+
+    match state.db.insert_schedule(&sched) {
+        Ok(()) => Json(MessageResponse {
+            ok: true,
+            message: format!("Eval schedule '{}' created as {}", sched.name, sched.id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to create eval schedule: {}", e),
+        }),
+    }
+}
+
+fn set_schedule_enabled(state: &EvalState, id: &str, enabled: bool) -> MessageResponse {
+    match state.db.set_schedule_enabled(id, enabled) {
+        Ok(true) => MessageResponse {
+            ok: true,
+            message: format!(
+                "Eval schedule {} {}",
+                id,
+                if enabled { "enabled" } else { "disabled" }
+            ),
+        },
+        Ok(false) => MessageResponse {
+            ok: false,
+            message: format!("Eval schedule {} not found", id),
+        },
+        Err(e) => MessageResponse {
+            ok: false,
+            message: format!("Failed to update: {}", e),
+        },
+    }
+}
+
+async fn enable_schedule_handler(
     State(state): State<Arc<EvalState>>,
     Path(id): Path<String>,
 ) -> Json<MessageResponse> {
-    match state.db.delete_test_prompt(&id) {
+    Json(set_schedule_enabled(&state, &id, true))
+}
+
+async fn disable_schedule_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    Json(set_schedule_enabled(&state, &id, false))
+}
+
+async fn delete_schedule_handler(
+    State(state): State<Arc<EvalState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    match state.db.delete_schedule(&id) {
         Ok(true) => Json(MessageResponse {
             ok: true,
-            message: format!("Test prompt '{}' deleted", id),
+            message: format!("Eval schedule {} deleted", id),
         }),
         Ok(false) => Json(MessageResponse {
             ok: false,
-            message: format!("Test prompt '{}' not found", id),
+            message: format!("Eval schedule {} not found", id),
         }),
         Err(e) => Json(MessageResponse {
             ok: false,
@@ -3,11 +3,12 @@ use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::Json;
 use serde::Serialize;
 use std::convert::Infallible;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 
 use crate::log_capture::LogSource;
 use crate::state::{SharedState, SseConnectionGuard};
+use crate::stream_clients;
 
 #[derive(Serialize)]
 pub struct ExpoStatusResponse {
@@ -75,24 +76,23 @@ pub async fn status(State(state): State<SharedState>) -> Json<ExpoStatusResponse
 pub async fn logs_stream(
     State(state): State<SharedState>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
-    let rx = state.logs.subscribe();
-    let stream = BroadcastStream::new(rx);
+    let client = state.stream_clients.register("/expo/logs/stream");
+    let rx = stream_clients::forward(state.clone(), state.logs.subscribe(), client);
+    let stream = ReceiverStream::new(rx);
 
     // Track this connection in `state.active_sse_connections`. Captured
     // by-move into the per-event closure below so it lives exactly as long
     // as the stream — drop happens when axum tears down the response.
     let conn_guard = SseConnectionGuard::new(state.active_sse_connections.clone());
 
-    let event_stream = stream.filter_map(move |result| {
+    let event_stream = stream.filter_map(move |entry| {
         // Hold the guard for every yielded event so the stream owns it.
         let _hold = &conn_guard;
-        match result {
-            Ok(entry) if entry.source == LogSource::Expo => {
-                let data = serde_json::to_string(&entry).unwrap_or_default();
-                Some(Ok(Event::default().event("log").data(data)))
-            }
-            _ => None,
+        if entry.source != LogSource::Expo {
+            return None;
         }
+        let data = serde_json::to_string(&entry).unwrap_or_default();
+        Some(Ok(Event::default().event("log").data(data)))
     });
 
     let shutdown_state = state.clone();
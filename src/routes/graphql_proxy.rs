@@ -17,6 +17,7 @@ use tracing::{debug, warn};
 
 use crate::config::RUNNER_API_PORT;
 use crate::state::SharedState;
+use crate::trace_propagation::TraceparentExt;
 
 /// Default timeout for GraphQL proxy requests (seconds).
 /// GraphQL queries may be heavier than simple REST calls, so allow more time.
@@ -80,7 +81,7 @@ pub async fn graphql_proxy(State(state): State<SharedState>, req: Request) -> Re
         outgoing = outgoing.body(body_bytes);
     }
 
-    match outgoing.send().await {
+    match outgoing.send_traced().await {
         Ok(resp) => {
             let status =
                 StatusCode::from_u16(resp.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
@@ -1,8 +1,10 @@
-use axum::extract::State;
+use axum::extract::{Query, State};
+use axum::http::header;
 use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::Json;
+use chrono::{DateTime, Utc};
 use futures::stream::Stream;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -12,7 +14,9 @@ use tokio_stream::StreamExt;
 use crate::config::RUNNER_API_PORT;
 use crate::health_cache::{CachedRunnerHealth, RecentCrashSummary, RunnerStatus, UiErrorSummary};
 use crate::sdk_features::{SDK_FEATURES, SDK_FEATURE_DOC_URL};
+use crate::stability::{self, StabilityReport};
 use crate::state::{SharedState, SseConnectionGuard};
+use crate::stream_clients::StreamClientInfo;
 use qontinui_types::wire::runner_kind::RunnerKind;
 
 #[derive(Serialize)]
@@ -48,7 +52,7 @@ pub struct HealthResponse {
     pub build_id: String,
     /// Live count of in-flight SSE connections across every long-lived
     /// streaming endpoint (`/health/stream`, `/logs/stream`,
-    /// `/expo/logs/stream`, `/runners/{id}/logs/stream`,
+    /// `/expo/logs/stream`, `/runners/{id}/logs/stream`, `/eval/stream`,
     /// `/supervisor-bridge/commands/stream`). Each handler holds an
     /// [`crate::state::SseConnectionGuard`] for the lifetime of its
     /// response stream; the count drops to 0 when the graceful-shutdown
@@ -85,6 +89,12 @@ pub struct RunnerInstanceHealth {
     /// starting). Combines process liveness with the runner's self-reported
     /// `ui_error` + `derived_status` + `recent_crash`.
     pub derived_status: RunnerStatus,
+    /// When the health refresher last completed a probe of this runner
+    /// within its per-target budget.
+    pub probed_at: Option<DateTime<Utc>>,
+    /// True when the latest probe exceeded its budget and the fields above
+    /// are carried over from an earlier cycle.
+    pub health_stale: bool,
 }
 
 #[derive(Serialize)]
@@ -256,6 +266,8 @@ fn build_sse_runners(state: &SharedState) -> Vec<RunnerInstanceHealth> {
                 ui_error: r.ui_error.clone(),
                 recent_crash: r.recent_crash.clone(),
                 derived_status: r.derived_status.clone(),
+                probed_at: r.probed_at,
+                health_stale: r.stale,
             })
             .collect(),
         Err(_) => Vec::new(), // Lock contended, skip this tick
@@ -266,6 +278,58 @@ pub async fn health(State(state): State<SharedState>) -> Json<HealthResponse> {
     Json(build_health_response(&state).await)
 }
 
+/// Fixed-shape payload for `GET /status/compact`.
+#[derive(Serialize)]
+pub struct CompactStatus {
+    /// `"up"` when the primary runner's API answers, else `"down"`.
+    pub runner: &'static str,
+    /// Velocity improvement loop phase (`idle` when not running).
+    pub loop_phase: crate::velocity_improvement::VelocityImprovementPhase,
+    pub latest_eval_score: Option<f64>,
+    pub latest_velocity_score: Option<f64>,
+    /// Runners currently degraded/errored or with a disarmed watchdog.
+    pub active_incidents: usize,
+}
+
+/// Tiny status for editor status bars polling every few seconds. Reads
+/// only in-memory caches — no port checks, DB queries, or per-runner locks.
+pub async fn compact_status(
+    State(state): State<SharedState>,
+) -> ([(header::HeaderName, &'static str); 1], Json<CompactStatus>) {
+    let runner = if state.cached_health.read().await.runner_responding {
+        "up"
+    } else {
+        "down"
+    };
+    let active_incidents = state
+        .cached_runner_health
+        .read()
+        .await
+        .iter()
+        .filter(|r| {
+            matches!(
+                r.derived_status,
+                RunnerStatus::Degraded { .. } | RunnerStatus::Errored { .. }
+            ) || r.watchdog.disabled_reason.is_some()
+        })
+        .count();
+    let status = CompactStatus {
+        runner,
+        loop_phase: state.velocity_improvement.read().await.phase.clone(),
+        latest_eval_score: state.evaluation.read().await.last_completed_score,
+        latest_velocity_score: state.velocity_tests.read().await.last_completed_score,
+        active_incidents,
+    };
+    (
+        [(header::CACHE_CONTROL, COMPACT_STATUS_CACHE_CONTROL)],
+        Json(status),
+    )
+}
+
+/// Matches the health cache refresh period, so pollers can't see anything
+/// fresher by skipping the cache.
+const COMPACT_STATUS_CACHE_CONTROL: &str = "private, max-age=2";
+
 pub async fn build_health_response(state: &SharedState) -> HealthResponse {
     let build = state.build.read().await;
     let expo = state.expo.read().await;
@@ -351,6 +415,8 @@ pub async fn build_health_response(state: &SharedState) -> HealthResponse {
             ui_error: cached.and_then(|c| c.ui_error.clone()),
             recent_crash: cached.and_then(|c| c.recent_crash.clone()),
             derived_status: cached.map(|c| c.derived_status.clone()).unwrap_or_default(),
+            probed_at: cached.and_then(|c| c.probed_at),
+            health_stale: cached.map(|c| c.stale).unwrap_or(false),
         });
     }
     drop(cached_snapshots);
@@ -607,6 +673,37 @@ pub async fn health_stream(
     Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
 }
 
+/// GET /streams/clients — live log-stream/WebSocket connections with their
+/// delivered and dropped message counts.
+pub async fn stream_clients(State(state): State<SharedState>) -> Json<Vec<StreamClientInfo>> {
+    Json(state.stream_clients.snapshot())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StabilityParams {
+    pub days: Option<u32>,
+}
+
+/// GET /analytics/stability — supervisor sessions (clean vs unclean ends)
+/// and child restarts per day and ISO week over the last `days` (default
+/// [`stability::DEFAULT_WINDOW_DAYS`]).
+pub async fn stability_report(
+    State(state): State<SharedState>,
+    Query(params): Query<StabilityParams>,
+) -> Json<Option<StabilityReport>> {
+    let Some(db) = &state.stability else {
+        return Json(None);
+    };
+    let days = params.days.unwrap_or(stability::DEFAULT_WINDOW_DAYS);
+    match db.report(days) {
+        Ok(report) => Json(Some(report)),
+        Err(e) => {
+            tracing::error!("Failed to build stability report: {}", e);
+            Json(None)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
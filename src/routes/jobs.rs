@@ -0,0 +1,51 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Serialize;
+
+use crate::job_queue::QueueEntry;
+use crate::state::SharedState;
+
+#[derive(Serialize)]
+pub struct CancelResponse {
+    pub ok: bool,
+    pub message: String,
+}
+
+/// GET /jobs/queue — Queued start requests in dispatch order.
+pub async fn list(State(state): State<SharedState>) -> Json<Vec<QueueEntry>> {
+    Json(state.job_queue.read().await.entries())
+}
+
+/// GET /jobs/queue/{id} — One queued job with its current position. 404 once
+/// the job has been launched or cancelled.
+pub async fn get_job(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+) -> Result<Json<QueueEntry>, StatusCode> {
+    state
+        .job_queue
+        .read()
+        .await
+        .get(&id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// DELETE /jobs/queue/{id} — Cancel a queued job before it launches.
+pub async fn cancel(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+) -> Json<CancelResponse> {
+    if state.job_queue.write().await.cancel(&id) {
+        Json(CancelResponse {
+            ok: true,
+            message: format!("Cancelled queued job {}", id),
+        })
+    } else {
+        Json(CancelResponse {
+            ok: false,
+            message: format!("Job {} is not queued", id),
+        })
+    }
+}
@@ -1,11 +1,12 @@
 use crate::state::{SharedState, SseConnectionGuard};
+use crate::stream_clients;
 use axum::extract::{Path, Query, State};
 use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
 use axum::Json;
 use serde::Deserialize;
 use std::convert::Infallible;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 
 #[derive(Deserialize)]
@@ -67,24 +68,21 @@ pub async fn build_log_history(
 pub async fn log_stream(
     State(state): State<SharedState>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
-    let rx = state.logs.subscribe();
-    let stream = BroadcastStream::new(rx);
+    // Bounded per-client queue; a client that keeps lagging is cut off.
+    let client = state.stream_clients.register("/logs/stream");
+    let rx = stream_clients::forward(state.clone(), state.logs.subscribe(), client);
+    let stream = ReceiverStream::new(rx);
 
     // Track this connection in `state.active_sse_connections`. Captured
     // by-move into the per-event closure below so it lives exactly as long
     // as the stream — drop happens when axum tears down the response.
     let conn_guard = SseConnectionGuard::new(state.active_sse_connections.clone());
 
-    let event_stream = stream.filter_map(move |result| {
+    let event_stream = stream.map(move |entry| {
         // Hold the guard for every yielded event so the stream owns it.
         let _hold = &conn_guard;
-        match result {
-            Ok(entry) => {
-                let data = serde_json::to_string(&entry).unwrap_or_default();
-                Some(Ok(Event::default().event("log").data(data)))
-            }
-            Err(_) => None, // Skip lagged messages
-        }
+        let data = serde_json::to_string(&entry).unwrap_or_default();
+        Ok(Event::default().event("log").data(data))
     });
 
     let shutdown_state = state.clone();
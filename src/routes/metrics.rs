@@ -0,0 +1,263 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::evaluation::db::EvalDb;
+use crate::metrics::{
+    Histogram, MetricType, MetricsWriter, CONTENT_TYPE, EVAL_DURATION_BUCKETS_SECS,
+    SPAN_LATENCY_BUCKETS_MS,
+};
+use crate::state::SharedState;
+use crate::velocity::db::VelocityDb;
+use crate::velocity::{queries, writer};
+
+pub struct MetricsState {
+    pub supervisor: SharedState,
+    /// `None` when the DB could not be opened; its metrics are left out.
+    pub velocity: Option<VelocityDb>,
+    pub eval: Option<EvalDb>,
+}
+
+pub fn metrics_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> Router {
+    let velocity = VelocityDb::new(&dev_logs_dir)
+        .inspect_err(|e| tracing::error!("Metrics: failed to open velocity database: {}", e))
+        .ok();
+    let eval = EvalDb::new(&dev_logs_dir, false)
+        .inspect_err(|e| tracing::error!("Metrics: failed to open eval database: {}", e))
+        .ok();
+    let state = Arc::new(MetricsState {
+        supervisor,
+        velocity,
+        eval,
+    });
+
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(state)
+}
+
+async fn metrics_handler(State(state): State<Arc<MetricsState>>) -> impl IntoResponse {
+    let mut w = MetricsWriter::default();
+    write_runner_metrics(&mut w, &state.supervisor).await;
+    write_loop_metrics(&mut w, &state.supervisor).await;
+    if let Some(db) = &state.eval {
+        write_eval_metrics(&mut w, db);
+    }
+    if let Some(db) = &state.velocity {
+        write_velocity_metrics(&mut w, db);
+    }
+    write_span_writer_metrics(&mut w);
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], w.finish())
+}
+
+async fn write_runner_metrics(w: &mut MetricsWriter, state: &SharedState) {
+    let runners = state.cached_runner_health.read().await.clone();
+
+    w.header(
+        "qontinui_runner_up",
+        MetricType::Gauge,
+        "Whether the runner's API answered the last health probe.",
+    );
+    for r in &runners {
+        w.sample(
+            "qontinui_runner_up",
+            &[("runner_id", &r.id), ("name", &r.name)],
+            r.api_responding as u8 as f64,
+        );
+    }
+
+    w.header(
+        "qontinui_runner_process_running",
+        MetricType::Gauge,
+        "Whether the runner process is running.",
+    );
+    for r in &runners {
+        w.sample(
+            "qontinui_runner_process_running",
+            &[("runner_id", &r.id)],
+            r.running as u8 as f64,
+        );
+    }
+
+    w.header(
+        "qontinui_runner_watchdog_restart_attempts",
+        MetricType::Gauge,
+        "Consecutive crash restarts attempted by the runner's watchdog.",
+    );
+    for r in &runners {
+        w.sample(
+            "qontinui_runner_watchdog_restart_attempts",
+            &[("runner_id", &r.id)],
+            r.watchdog.restart_attempts as f64,
+        );
+    }
+
+    if let Some(stability) = &state.stability {
+        match stability.restart_counts() {
+            Ok(counts) => {
+                w.header(
+                    "qontinui_runner_restarts_total",
+                    MetricType::Counter,
+                    "Runner restarts recorded in the stability history, by cause.",
+                );
+                for (runner_id, source, count) in &counts {
+                    w.sample(
+                        "qontinui_runner_restarts_total",
+                        &[("runner_id", runner_id), ("source", source)],
+                        *count as f64,
+                    );
+                }
+            }
+            Err(e) => tracing::error!("Metrics: failed to count runner restarts: {}", e),
+        }
+    }
+}
+
+async fn write_loop_metrics(w: &mut MetricsWriter, state: &SharedState) {
+    let (vi_running, vi_iteration, vi_started) = {
+        let vi = state.velocity_improvement.read().await;
+        (vi.running, vi.current_iteration, vi.iterations_started)
+    };
+    w.single(
+        "qontinui_velocity_improvement_running",
+        MetricType::Gauge,
+        "Whether the velocity improvement loop is running.",
+        vi_running as u8 as f64,
+    );
+    w.single(
+        "qontinui_velocity_improvement_iteration",
+        MetricType::Gauge,
+        "Iteration of the current (or last) velocity improvement loop.",
+        vi_iteration as f64,
+    );
+    w.single(
+        "qontinui_velocity_improvement_iterations_total",
+        MetricType::Counter,
+        "Velocity improvement iterations started since the supervisor started.",
+        vi_started as f64,
+    );
+
+    let (eval_running, eval_continuous) = {
+        let eval = state.evaluation.read().await;
+        (eval.running, eval.continuous_mode)
+    };
+    w.single(
+        "qontinui_eval_running",
+        MetricType::Gauge,
+        "Whether an eval run is in progress.",
+        eval_running as u8 as f64,
+    );
+    w.single(
+        "qontinui_eval_continuous_mode",
+        MetricType::Gauge,
+        "Whether continuous eval mode is on.",
+        eval_continuous as u8 as f64,
+    );
+}
+
+fn write_eval_metrics(w: &mut MetricsWriter, db: &EvalDb) {
+    let durations = match db.run_durations() {
+        Ok(d) => d,
+        Err(e) => {
+            tracing::error!("Metrics: failed to load eval run durations: {}", e);
+            return;
+        }
+    };
+    let mut by_status: std::collections::BTreeMap<String, Histogram> = Default::default();
+    for (status, secs) in durations {
+        by_status
+            .entry(status)
+            .or_insert_with(|| Histogram::new(EVAL_DURATION_BUCKETS_SECS))
+            .observe(secs);
+    }
+    w.header(
+        "qontinui_eval_run_duration_seconds",
+        MetricType::Histogram,
+        "Wall-clock duration of finished eval runs, by final status.",
+    );
+    for (status, h) in &by_status {
+        w.histogram(
+            "qontinui_eval_run_duration_seconds",
+            &[("status", status)],
+            h,
+        );
+    }
+}
+
+fn write_velocity_metrics(w: &mut MetricsWriter, db: &VelocityDb) {
+    let services = match queries::get_latency_histograms(db, SPAN_LATENCY_BUCKETS_MS) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("Metrics: failed to load velocity histograms: {}", e);
+            return;
+        }
+    };
+    w.header(
+        "qontinui_velocity_spans_total",
+        MetricType::Counter,
+        "HTTP spans stored in the velocity database.",
+    );
+    for s in &services {
+        w.sample(
+            "qontinui_velocity_spans_total",
+            &[("service", &s.service)],
+            s.spans as f64,
+        );
+    }
+    w.header(
+        "qontinui_velocity_span_errors_total",
+        MetricType::Counter,
+        "Failed HTTP spans stored in the velocity database.",
+    );
+    for s in &services {
+        w.sample(
+            "qontinui_velocity_span_errors_total",
+            &[("service", &s.service)],
+            s.errors as f64,
+        );
+    }
+    w.header(
+        "qontinui_velocity_span_duration_milliseconds",
+        MetricType::Histogram,
+        "Latency of stored HTTP spans.",
+    );
+    for s in &services {
+        w.histogram(
+            "qontinui_velocity_span_duration_milliseconds",
+            &[("service", &s.service)],
+            &s.latency_ms,
+        );
+    }
+}
+
+fn write_span_writer_metrics(w: &mut MetricsWriter) {
+    let stats = writer::STATS.snapshot();
+    w.single(
+        "qontinui_velocity_write_queue_depth",
+        MetricType::Gauge,
+        "OTLP spans waiting for the velocity span writer.",
+        stats.queue_depth as f64,
+    );
+    w.single(
+        "qontinui_velocity_write_batches_total",
+        MetricType::Counter,
+        "Span batches written by the velocity span writer.",
+        stats.batches as f64,
+    );
+    w.single(
+        "qontinui_velocity_write_rejected_total",
+        MetricType::Counter,
+        "OTLP spans the velocity span writer failed to store.",
+        stats.rejected as f64,
+    );
+    w.single(
+        "qontinui_velocity_write_backpressure_total",
+        MetricType::Counter,
+        "OTLP exports that waited for room in the velocity write queue.",
+        stats.backpressure_waits as f64,
+    );
+}
@@ -9,9 +9,12 @@ pub mod evaluation;
 pub mod expo;
 pub mod graphql_proxy;
 pub mod health;
+pub mod jobs;
 pub mod lineage;
 pub mod lkg_coverage;
 pub mod logs;
+pub mod metrics;
+pub mod processes;
 pub mod runner;
 pub mod runner_monitor;
 pub mod runners;
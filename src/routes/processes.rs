@@ -0,0 +1,53 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+
+use crate::process::{orphan_cleanup, resources};
+use crate::state::SharedState;
+
+/// GET /processes/{name}/resources — recent CPU/memory samples of a runner
+/// (by id), `expo` or `fix-agent`.
+pub async fn get_resources(State(state): State<SharedState>, Path(name): Path<String>) -> Response {
+    let history = state.process_resources.read().await;
+    match history.get(&name, resources::memory_ceiling_bytes()) {
+        Some(report) => Json(report).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("No resource samples for process '{}'", name),
+                "known": history.names(),
+            })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct CleanupRequest {
+    /// Terminate the candidates in `pids`; without it they are only listed.
+    #[serde(default)]
+    pub confirm: bool,
+    /// Candidate PIDs to terminate, from a previous listing. Required with
+    /// `confirm`.
+    #[serde(default)]
+    pub pids: Option<Vec<u32>>,
+}
+
+/// POST /processes/cleanup — stale processes holding managed ports or running
+/// the runner binary, the confirmed ones terminated when `confirm` is set.
+pub async fn cleanup(
+    State(state): State<SharedState>,
+    body: Option<Json<CleanupRequest>>,
+) -> Response {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    match orphan_cleanup::cleanup(&state, body.confirm, body.pids.as_deref()).await {
+        Ok(report) => Json(report).into_response(),
+        Err(error) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": error })),
+        )
+            .into_response(),
+    }
+}
@@ -1,5 +1,5 @@
 use axum::extract::{Query, State};
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Deserialize;
@@ -13,6 +13,8 @@ use crate::error::SupervisorError;
 use crate::log_capture::{LogLevel, LogSource};
 use crate::process::health_probe::{wait_for_runner_healthy_default, HealthProbeFailure};
 use crate::process::manager;
+use crate::process::restart_policy::RestartPolicy;
+use crate::settings;
 use crate::state::SharedState;
 
 /// Query string for endpoints that opt-OUT of the post-spawn health wait.
@@ -625,6 +627,46 @@ pub async fn control_watchdog(
     Ok(Json(response))
 }
 
+/// GET /watchdog/restart-policy — the crash-only watchdog's backoff policy.
+pub async fn get_restart_policy(State(state): State<SharedState>) -> Json<RestartPolicy> {
+    Json(state.restart_policy.read().await.clone())
+}
+
+/// PUT /watchdog/restart-policy — save a new backoff policy and apply it to
+/// the next crash.
+pub async fn set_restart_policy(
+    State(state): State<SharedState>,
+    Json(policy): Json<RestartPolicy>,
+) -> Response {
+    if let Err(message) = policy.validate() {
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    let path = settings::settings_path(&state.config);
+    let mut saved = settings::load_settings(&path);
+    saved.restart_policy = policy.clone();
+    if let Err(e) = settings::try_save_settings(&path, &saved) {
+        tracing::error!("Failed to save restart policy: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+    }
+    *state.restart_policy.write().await = policy.clone();
+    state
+        .logs
+        .emit(
+            LogSource::Supervisor,
+            LogLevel::Info,
+            format!(
+                "Watchdog restart policy updated: {}s x{} up to {}s, {} restarts per {}s",
+                policy.initial_delay_secs,
+                policy.multiplier,
+                policy.max_delay_secs,
+                policy.crash_loop_threshold,
+                policy.crash_loop_window_secs
+            ),
+        )
+        .await;
+    Json(policy).into_response()
+}
+
 /// POST /build/reset — force-release every build slot and clear the legacy flag.
 ///
 /// Under the parallel build pool a "stuck build" is most likely a `slot.busy`
@@ -909,6 +951,96 @@ pub async fn supervisor_restart(
         )
         .await;
 
+    spawn_replacement_and_exit(&state).await?;
+    Ok(Json(serde_json::json!({
+        "status": "restarting",
+        "message": "Supervisor is restarting"
+    })))
+}
+
+/// Whether a request's `Origin` header (if any) is the supervisor's own UI.
+/// Requests without one come from non-browser clients and are allowed.
+fn is_own_origin(headers: &HeaderMap, port: u16) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    [
+        format!("http://127.0.0.1:{}", port),
+        format!("http://localhost:{}", port),
+    ]
+    .iter()
+    .any(|own| own == origin)
+}
+
+/// Download and verify a supervisor release, swap it in place of the running
+/// exe, then restart onto it via the same path as `POST /supervisor/restart`.
+///
+/// CORS is wide open for the dashboard's sake, so cross-origin requests are
+/// refused here: otherwise any web page could trigger an update.
+pub async fn supervisor_update(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Option<Json<crate::self_update::UpdateRequest>>,
+) -> Result<Response, SupervisorError> {
+    if !is_own_origin(&headers, state.config.port) {
+        return Ok((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": "cross_origin",
+                "message": "POST /update is not allowed from another origin",
+            })),
+        )
+            .into_response());
+    }
+    let req = body.map(|Json(b)| b).unwrap_or_default();
+    state
+        .logs
+        .emit(
+            LogSource::Supervisor,
+            LogLevel::Info,
+            format!(
+                "Supervisor self-update requested (version={})",
+                req.version.as_deref().unwrap_or("latest")
+            ),
+        )
+        .await;
+
+    let installed = crate::self_update::download_and_install(&state.http_client, &req)
+        .await
+        .map_err(|e| SupervisorError::Other(format!("Self-update failed: {}", e)))?;
+
+    state
+        .logs
+        .emit(
+            LogSource::Supervisor,
+            LogLevel::Info,
+            format!(
+                "Installed supervisor update from {} (sha256 {}, {} bytes); previous binary kept at {}",
+                installed.source_url,
+                installed.sha256,
+                installed.size_bytes,
+                installed.backup_path.display()
+            ),
+        )
+        .await;
+
+    spawn_replacement_and_exit(&state).await?;
+    Ok(Json(serde_json::json!({
+        "status": "restarting",
+        "message": "Supervisor updated and restarting",
+        "update": installed,
+    }))
+    .into_response())
+}
+
+/// Spawn a detached copy of the supervisor with the same CLI args, then
+/// schedule this process to exit shortly after the caller's response is sent.
+/// Temp runners are stopped first; user runners survive. Shared by
+/// `POST /supervisor/restart` and `POST /update`.
+pub(crate) async fn spawn_replacement_and_exit(state: &SharedState) -> Result<(), SupervisorError> {
     let args = state.config.cli_args.clone();
     let exe = args.first().cloned().unwrap_or_else(|| {
         std::env::current_exe()
@@ -919,7 +1051,7 @@ pub async fn supervisor_restart(
     let remaining_args: Vec<String> = args.into_iter().skip(1).collect();
 
     // Only stop temp runners — user runners survive supervisor restarts
-    let _ = manager::stop_all_temp_runners(&state).await;
+    let _ = manager::stop_all_temp_runners(state).await;
 
     // Spawn replacement process
     let mut cmd = std::process::Command::new(&exe);
@@ -939,19 +1071,13 @@ pub async fn supervisor_restart(
             // Give the new process a moment to start
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
 
-            // Send response before exiting
-            let response = Json(serde_json::json!({
-                "status": "restarting",
-                "message": "Supervisor is restarting"
-            }));
-
             // Schedule exit after response is sent
             tokio::spawn(async {
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
                 std::process::exit(0);
             });
 
-            Ok(response)
+            Ok(())
         }
         Err(e) => Err(SupervisorError::Process(format!(
             "Failed to spawn replacement supervisor: {}",
@@ -1147,4 +1273,16 @@ mod tests {
             "the detached task must run to a terminal status independently of the handler"
         );
     }
+
+    #[test]
+    fn update_is_refused_from_other_origins() {
+        let mut headers = HeaderMap::new();
+        assert!(is_own_origin(&headers, 9875));
+        headers.insert(header::ORIGIN, "http://localhost:9875".parse().unwrap());
+        assert!(is_own_origin(&headers, 9875));
+        headers.insert(header::ORIGIN, "https://evil.example".parse().unwrap());
+        assert!(!is_own_origin(&headers, 9875));
+        headers.insert(header::ORIGIN, "http://127.0.0.1:9876".parse().unwrap());
+        assert!(!is_own_origin(&headers, 9875));
+    }
 }
@@ -12,7 +12,7 @@ use serde_json::json;
 use axum::extract::Query;
 use axum::response::sse::{Event, KeepAlive, Sse};
 use std::convert::Infallible;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 
 use crate::config::RunnerConfig;
@@ -25,6 +25,8 @@ use crate::log_capture::{LogLevel, LogSource};
 use crate::process::manager;
 use crate::settings;
 use crate::state::{ManagedRunner, SharedState, SseConnectionGuard};
+use crate::stream_clients;
+use crate::trace_propagation::TraceparentExt;
 use qontinui_types::wire::runner_kind::RunnerKind;
 use std::sync::Arc;
 use tracing::{info, warn};
@@ -45,6 +47,10 @@ pub struct AddRunnerRequest {
     pub external_restate_admin_url: Option<String>,
     #[serde(default)]
     pub external_restate_ingress_url: Option<String>,
+    /// Liveness check for a service that doesn't serve the runner's
+    /// `/health`; see [`crate::process::probe::HealthProbe`].
+    #[serde(default)]
+    pub health_probe: Option<crate::process::probe::HealthProbe>,
 }
 
 #[derive(Deserialize)]
@@ -54,7 +60,8 @@ pub struct RestartRunnerRequest {
     /// Source of the restart request. Defaults to "manual".
     #[serde(default = "default_source_manual")]
     pub source: String,
-    /// Force restart even if the runner is protected.
+    /// Force restart even if the runner is protected, without waiting for
+    /// in-flight task runs to drain.
     #[serde(default)]
     pub force: bool,
 }
@@ -570,6 +577,7 @@ pub async fn add_runner(
             external_restate_admin_url: resolved.external_admin_url,
             external_restate_ingress_url: resolved.external_ingress_url,
             extra_env: Default::default(),
+            health_probe: body.health_probe.clone(),
         };
 
         // External runners are never crash-auto-restarted (the supervisor
@@ -1342,7 +1350,7 @@ pub async fn proxy_ui_bridge(
         outgoing = outgoing.body(body_bytes);
     }
 
-    match outgoing.send().await {
+    match outgoing.send_traced().await {
         Ok(resp) => {
             let status = axum::http::StatusCode::from_u16(resp.status().as_u16())
                 .unwrap_or(axum::http::StatusCode::BAD_GATEWAY);
@@ -2365,6 +2373,7 @@ pub async fn spawn_test(
             external_restate_admin_url: resolved.external_admin_url,
             external_restate_ingress_url: resolved.external_ingress_url,
             extra_env: body.extra_env.clone(),
+            health_probe: None,
         };
         let managed = Arc::new(ManagedRunner::new_with_log_dir(
             runner_config,
@@ -4348,6 +4357,7 @@ pub async fn spawn_named(
             external_restate_admin_url: resolved.external_admin_url,
             external_restate_ingress_url: resolved.external_ingress_url,
             extra_env: Default::default(),
+            health_probe: None,
         };
         let managed = Arc::new(ManagedRunner::new_with_log_dir(
             runner_config,
@@ -5965,24 +5975,20 @@ pub async fn runner_log_stream(
         .await
         .ok_or_else(|| SupervisorError::RunnerNotFound(id.clone()))?;
 
-    let rx = managed.logs.subscribe();
-    let stream = BroadcastStream::new(rx);
+    let client = state.stream_clients.register("/runners/{id}/logs/stream");
+    let rx = stream_clients::forward(state.clone(), managed.logs.subscribe(), client);
+    let stream = ReceiverStream::new(rx);
 
     // Track this connection in `state.active_sse_connections`. Captured
     // by-move into the per-event closure below so it lives exactly as long
     // as the stream — drop happens when axum tears down the response.
     let conn_guard = SseConnectionGuard::new(state.active_sse_connections.clone());
 
-    let event_stream = stream.filter_map(move |result| {
+    let event_stream = stream.map(move |entry| {
         // Hold the guard for every yielded event so the stream owns it.
         let _hold = &conn_guard;
-        match result {
-            Ok(entry) => {
-                let data = serde_json::to_string(&entry).unwrap_or_default();
-                Some(Ok(Event::default().event("log").data(data)))
-            }
-            Err(_) => None,
-        }
+        let data = serde_json::to_string(&entry).unwrap_or_default();
+        Ok(Event::default().event("log").data(data))
     });
 
     let shutdown_state = state.clone();
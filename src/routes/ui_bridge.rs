@@ -13,6 +13,7 @@ use tracing::{debug, warn};
 
 use crate::config::RUNNER_API_PORT;
 use crate::state::SharedState;
+use crate::trace_propagation::TraceparentExt;
 
 /// Default timeout for UI Bridge proxy requests (seconds).
 const PROXY_TIMEOUT_SECS: u64 = 15;
@@ -79,7 +80,7 @@ pub async fn proxy(State(state): State<SharedState>, req: Request) -> Response {
     }
 
     // Send the request
-    match outgoing.send().await {
+    match outgoing.send_traced().await {
         Ok(resp) => {
             let status =
                 StatusCode::from_u16(resp.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
@@ -1,22 +1,47 @@
+use axum::body::{Body, Bytes};
 use axum::extract::{Query, State};
-use axum::response::Json;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Json, Response};
 use axum::routing::{get, post};
 use axum::Router;
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
 
+use crate::settings;
+use crate::state::{SharedState, SseConnectionGuard};
+use crate::stream_clients;
+use crate::velocity::anomaly::{self, AnomalyConfig, AnomalyTracker};
+use crate::velocity::apdex::ApdexConfig;
 use crate::velocity::db::VelocityDb;
+use crate::velocity::export::{self, ExportFormat};
 use crate::velocity::ingest;
+use crate::velocity::live::StreamFilter;
+use crate::velocity::otlp::{self, Encoding};
 use crate::velocity::queries::{self, QueryFilter};
+use crate::velocity::resources::{self, ResourceSampler, SampleTarget};
+use crate::velocity::rollup::{self, RollupPolicy};
+use crate::velocity::search::{self, SpanSearch};
+use crate::velocity::slo::{self, SloDefinition, SloStatus, SloViolation};
+use crate::velocity::waterfall::{self, Waterfall};
+use crate::velocity::writer::{self, SpanWriter, WriterStatsSnapshot};
+use crate::velocity_layer::{self, VelocitySampling};
 
 // ============================================================================
 // State
 // ============================================================================
 
 pub struct VelocityState {
-    pub db: VelocityDb,
+    pub db: Arc<VelocityDb>,
+    /// Batches OTLP spans into the database off the request path.
+    pub writer: SpanWriter,
     pub dev_logs_dir: PathBuf,
+    pub apdex: ApdexConfig,
+    pub supervisor: SharedState,
 }
 
 // ============================================================================
@@ -28,6 +53,17 @@ pub struct FilterParams {
     pub since: Option<String>,
     pub until: Option<String>,
     pub service: Option<String>,
+    /// Apdex threshold for every service, overriding the configured ones.
+    pub apdex_t_ms: Option<f64>,
+}
+
+impl FilterParams {
+    fn apdex(&self, configured: &ApdexConfig) -> ApdexConfig {
+        match self.apdex_t_ms {
+            Some(t) if t > 0.0 => ApdexConfig::uniform(t),
+            _ => configured.clone(),
+        }
+    }
 }
 
 impl From<&FilterParams> for QueryFilter {
@@ -40,13 +76,40 @@ impl From<&FilterParams> for QueryFilter {
     }
 }
 
+/// Either the four window bounds, or `commit` to derive them from its
+/// commit time.
 #[derive(Debug, Deserialize)]
 pub struct CompareParams {
-    pub before_start: String,
-    pub before_end: String,
-    pub after_start: String,
-    pub after_end: String,
+    pub before_start: Option<String>,
+    pub before_end: Option<String>,
+    pub after_start: Option<String>,
+    pub after_end: Option<String>,
+    pub commit: Option<String>,
+    /// Length of each window around `commit`; default
+    /// [`DEFAULT_COMMIT_WINDOW_MINS`].
+    pub window_mins: Option<i64>,
+    pub service: Option<String>,
+}
+
+pub const DEFAULT_COMMIT_WINDOW_MINS: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub service: Option<String>,
+    /// `csv` (default) or `parquet`.
+    pub format: Option<String>,
+    /// Comma-separated column names; every column but `attributes` if absent.
+    pub columns: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistogramParams {
+    pub since: Option<String>,
+    pub until: Option<String>,
     pub service: Option<String>,
+    pub route: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -75,11 +138,18 @@ pub struct FileIngestResponse {
     pub errors: usize,
 }
 
+#[derive(Serialize)]
+pub struct SloReport {
+    pub window_mins: i64,
+    pub slos: Vec<SloStatus>,
+    pub violations: Vec<SloViolation>,
+}
+
 // ============================================================================
 // Routes
 // ============================================================================
 
-pub fn velocity_routes(dev_logs_dir: PathBuf) -> Router {
+pub fn velocity_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> Router {
     let db = match VelocityDb::new(&dev_logs_dir) {
         Ok(db) => db,
         Err(e) => {
@@ -89,16 +159,190 @@ pub fn velocity_routes(dev_logs_dir: PathBuf) -> Router {
         }
     };
 
-    let state = Arc::new(VelocityState { db, dev_logs_dir });
+    let db = Arc::new(db);
+    let state = Arc::new(VelocityState {
+        writer: SpanWriter::spawn(db.clone()),
+        db,
+        dev_logs_dir,
+        apdex: ApdexConfig::from_env(),
+        supervisor,
+    });
+
+    // The tracing layer keeps every span until the saved config is applied.
+    velocity_layer::set_sampling(
+        settings::load_settings(&settings::settings_path(&state.supervisor.config))
+            .velocity_sampling,
+    );
+
+    let policy = RollupPolicy::from_env();
+    if policy.is_enabled() {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(rollup::ROLLUP_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                let state = state.clone();
+                let result =
+                    tokio::task::spawn_blocking(move || rollup::roll_up(&state.db, policy)).await;
+                match result {
+                    Ok(Ok(r)) if r.spans_rolled > 0 => tracing::info!(
+                        "Rolled {} velocity span(s) older than {}h into {} hourly bucket(s)",
+                        r.spans_rolled,
+                        policy.after_hours,
+                        r.buckets
+                    ),
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => tracing::error!("Velocity span rollup failed: {}", e),
+                    Err(e) => tracing::error!("Velocity span rollup task panicked: {}", e),
+                }
+            }
+        });
+    }
+
+    let anomaly_config = AnomalyConfig::from_env();
+    if anomaly_config.is_enabled() {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut tracker = AnomalyTracker::default();
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                anomaly::ANOMALY_INTERVAL_SECS,
+            ));
+            loop {
+                interval.tick().await;
+                let task_state = state.clone();
+                let config = anomaly_config.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let found = tracker.check(&task_state.db, &config);
+                    (tracker, found)
+                })
+                .await;
+                let found = match result {
+                    Ok((t, found)) => {
+                        tracker = t;
+                        found
+                    }
+                    Err(e) => {
+                        tracing::error!("Velocity anomaly check panicked: {}", e);
+                        tracker = AnomalyTracker::default();
+                        continue;
+                    }
+                };
+                match found {
+                    Ok(anomalies) => {
+                        for a in anomalies {
+                            anomaly::raise(
+                                &state.supervisor,
+                                a,
+                                anomaly_config.webhook_url.clone(),
+                            )
+                            .await;
+                        }
+                    }
+                    Err(e) => tracing::error!("Velocity anomaly check failed: {}", e),
+                }
+            }
+        });
+    }
+
+    {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(slo::SLO_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                let task_state = state.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let slos = load_slos(&task_state.supervisor);
+                    let statuses = slo::evaluate(&task_state.db, &slos, slo::window_mins())?;
+                    slo::record(&task_state.db, &statuses)
+                })
+                .await;
+                match result {
+                    Ok(Ok(started)) => {
+                        for s in started {
+                            tracing::warn!(
+                                "Velocity SLO '{}' violated: p95 {:?}ms (target {:?}), error rate {:?} (target {:?}) over {} request(s)",
+                                s.slo.name,
+                                s.p95_ms,
+                                s.slo.target_p95_ms,
+                                s.error_rate,
+                                s.slo.target_error_rate,
+                                s.request_count
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => tracing::error!("Velocity SLO evaluation failed: {}", e),
+                    Err(e) => tracing::error!("Velocity SLO evaluation panicked: {}", e),
+                }
+            }
+        });
+    }
+
+    let sample_secs = resources::sample_interval_secs();
+    if sample_secs > 0 {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut sampler = ResourceSampler::default();
+            let mut frontend_pid = None;
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(sample_secs));
+            loop {
+                interval.tick().await;
+                if frontend_pid.is_none() {
+                    frontend_pid = resources::frontend_pid().await;
+                }
+                let targets = sample_targets(&state.supervisor, frontend_pid).await;
+                let task_state = state.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let samples = sampler.sample(&targets);
+                    let recorded = resources::record(&task_state.db, &samples);
+                    (sampler, samples, recorded)
+                })
+                .await;
+                match result {
+                    Ok((s, samples, recorded)) => {
+                        sampler = s;
+                        // Look the frontend up again once it's gone
+                        if !samples.iter().any(|s| s.service == "frontend") {
+                            frontend_pid = None;
+                        }
+                        if let Err(e) = recorded {
+                            tracing::error!("Recording resource samples failed: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Resource sampling panicked: {}", e);
+                        sampler = ResourceSampler::default();
+                    }
+                }
+            }
+        });
+    }
 
     Router::new()
         .route("/velocity/ingest", post(ingest_handler))
+        .route("/velocity/otlp", post(otlp_handler))
+        .route("/velocity/ingest/stats", get(ingest_stats_handler))
         .route("/velocity/summary", get(summary_handler))
         .route("/velocity/endpoints", get(endpoints_handler))
         .route("/velocity/slow", get(slow_handler))
+        .route("/velocity/histogram", get(histogram_handler))
         .route("/velocity/timeline", get(timeline_handler))
         .route("/velocity/compare", get(compare_handler))
         .route("/velocity/trace/{request_id}", get(trace_handler))
+        .route(
+            "/velocity/trace/{request_id}/waterfall",
+            get(waterfall_handler),
+        )
+        .route("/velocity/stream", get(stream_handler))
+        .route("/velocity/spans/search", get(search_handler))
+        .route("/velocity/slo", get(slo_handler).put(set_slo_handler))
+        .route("/velocity/export", get(export_handler))
+        .route(
+            "/velocity/sampling",
+            get(sampling_handler).put(set_sampling_handler),
+        )
         .with_state(state)
 }
 
@@ -107,7 +351,13 @@ pub fn velocity_routes(dev_logs_dir: PathBuf) -> Router {
 // ============================================================================
 
 async fn ingest_handler(State(state): State<Arc<VelocityState>>) -> Json<IngestResponse> {
-    match ingest::ingest_all(&state.db, &state.dev_logs_dir) {
+    let task_state = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        ingest::ingest_all(&task_state.db, &task_state.dev_logs_dir)
+    })
+    .await
+    .unwrap_or_else(|e| Err(anyhow::anyhow!("ingestion task panicked: {}", e)));
+    match result {
         Ok(result) => Json(IngestResponse {
             total_new_spans: result.total_new_spans,
             files_processed: result
@@ -130,12 +380,67 @@ async fn ingest_handler(State(state): State<Arc<VelocityState>>) -> Json<IngestR
     }
 }
 
+async fn otlp_handler(
+    State(state): State<Arc<VelocityState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let Some(encoding) = Encoding::from_content_type(content_type) else {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Expected application/x-protobuf or application/json",
+        )
+            .into_response();
+    };
+    if headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|e| e != "identity")
+    {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Compressed OTLP exports are not supported; set OTEL_EXPORTER_OTLP_COMPRESSION=none",
+        )
+            .into_response();
+    }
+
+    let spans = match otlp::decode(&body, encoding) {
+        Ok(spans) => spans,
+        Err(e) => {
+            tracing::warn!("Rejected OTLP export: {}", e);
+            return (StatusCode::BAD_REQUEST, format!("Invalid OTLP body: {}", e)).into_response();
+        }
+    };
+    // Stored asynchronously: failures show up in /velocity/ingest/stats,
+    // not as a partial success.
+    match state.writer.submit(spans).await {
+        Ok(()) => (
+            [(header::CONTENT_TYPE, encoding.content_type())],
+            otlp::encode_response(encoding, &otlp::OtlpIngestResult::default()),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("OTLP ingestion failed: {}", e);
+            StatusCode::SERVICE_UNAVAILABLE.into_response()
+        }
+    }
+}
+
+/// GET /velocity/ingest/stats — queue depth, throughput and backpressure of
+/// the OTLP span writer.
+async fn ingest_stats_handler() -> Json<WriterStatsSnapshot> {
+    Json(writer::STATS.snapshot())
+}
+
 async fn summary_handler(
     State(state): State<Arc<VelocityState>>,
     Query(params): Query<FilterParams>,
 ) -> Json<Vec<queries::ServiceSummary>> {
     let filter = QueryFilter::from(&params);
-    match queries::get_summary(&state.db, &filter) {
+    match queries::get_summary(&state.db, &filter, &params.apdex(&state.apdex)) {
         Ok(results) => Json(results),
         Err(e) => {
             tracing::error!("Summary query failed: {}", e);
@@ -149,7 +454,7 @@ async fn endpoints_handler(
     Query(params): Query<FilterParams>,
 ) -> Json<Vec<queries::EndpointSummary>> {
     let filter = QueryFilter::from(&params);
-    match queries::get_endpoints(&state.db, &filter) {
+    match queries::get_endpoints(&state.db, &filter, &params.apdex(&state.apdex)) {
         Ok(results) => Json(results),
         Err(e) => {
             tracing::error!("Endpoints query failed: {}", e);
@@ -158,6 +463,27 @@ async fn endpoints_handler(
     }
 }
 
+async fn histogram_handler(
+    State(state): State<Arc<VelocityState>>,
+    Query(params): Query<HistogramParams>,
+) -> Json<queries::LatencyHistogram> {
+    let filter = QueryFilter {
+        since: params.since,
+        until: params.until,
+        service: params.service,
+    };
+    match queries::get_histogram(&state.db, &filter, params.route.as_deref()) {
+        Ok(histogram) => Json(histogram),
+        Err(e) => {
+            tracing::error!("Histogram query failed: {}", e);
+            Json(queries::LatencyHistogram {
+                total: 0,
+                buckets: Vec::new(),
+            })
+        }
+    }
+}
+
 async fn slow_handler(
     State(state): State<Arc<VelocityState>>,
     Query(params): Query<SlowParams>,
@@ -183,7 +509,7 @@ async fn timeline_handler(
     Query(params): Query<FilterParams>,
 ) -> Json<Vec<queries::TimelineBucket>> {
     let filter = QueryFilter::from(&params);
-    match queries::get_timeline(&state.db, &filter) {
+    match queries::get_timeline(&state.db, &filter, &params.apdex(&state.apdex)) {
         Ok(results) => Json(results),
         Err(e) => {
             tracing::error!("Timeline query failed: {}", e);
@@ -192,22 +518,97 @@ async fn timeline_handler(
     }
 }
 
+/// Git repos a `commit` may belong to: the runner's first, then every other
+/// repo in the workspace root the runner sits in.
+fn workspace_repos(supervisor: &SharedState) -> Vec<PathBuf> {
+    let project_dir = &supervisor.config.project_dir;
+    let runner_repo = project_dir.parent().unwrap_or(project_dir).to_path_buf();
+    let mut repos = vec![runner_repo.clone()];
+    if let Some(Ok(entries)) = runner_repo.parent().map(std::fs::read_dir) {
+        let mut siblings: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| *p != runner_repo && p.join(".git").exists())
+            .collect();
+        siblings.sort();
+        repos.extend(siblings);
+    }
+    repos
+}
+
+/// `(before_start, before_end, after_start, after_end)` for a compare
+/// request, or the reason they can't be had.
+async fn compare_windows(
+    supervisor: &SharedState,
+    params: &CompareParams,
+) -> Result<(String, String, String, String), (StatusCode, String)> {
+    let Some(commit) = params.commit.as_deref().filter(|c| !c.trim().is_empty()) else {
+        return match (
+            &params.before_start,
+            &params.before_end,
+            &params.after_start,
+            &params.after_end,
+        ) {
+            (Some(bs), Some(be), Some(a_s), Some(ae)) => {
+                Ok((bs.clone(), be.clone(), a_s.clone(), ae.clone()))
+            }
+            _ => Err((
+                StatusCode::BAD_REQUEST,
+                "Pass either commit or all of before_start, before_end, after_start, after_end"
+                    .to_string(),
+            )),
+        };
+    };
+    let window_mins = params.window_mins.unwrap_or(DEFAULT_COMMIT_WINDOW_MINS);
+    if window_mins <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "window_mins must be positive".to_string(),
+        ));
+    }
+    let commit = commit.trim();
+    let mut committed_at = None;
+    for repo in workspace_repos(supervisor) {
+        if let Some(t) = crate::git_provenance::commit_time(&repo, commit).await {
+            committed_at = Some(t.with_timezone(&chrono::Utc));
+            break;
+        }
+    }
+    let Some(t) = committed_at else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Commit '{}' not found in any workspace repo", commit),
+        ));
+    };
+    let window = chrono::Duration::minutes(window_mins);
+    Ok((
+        (t - window).to_rfc3339(),
+        t.to_rfc3339(),
+        t.to_rfc3339(),
+        (t + window).to_rfc3339(),
+    ))
+}
+
 async fn compare_handler(
     State(state): State<Arc<VelocityState>>,
     Query(params): Query<CompareParams>,
-) -> Json<Vec<queries::CompareResult>> {
+) -> Response {
+    let (before_start, before_end, after_start, after_end) =
+        match compare_windows(&state.supervisor, &params).await {
+            Ok(windows) => windows,
+            Err(rejection) => return rejection.into_response(),
+        };
     match queries::get_compare(
         &state.db,
-        &params.before_start,
-        &params.before_end,
-        &params.after_start,
-        &params.after_end,
+        &before_start,
+        &before_end,
+        &after_start,
+        &after_end,
         params.service.as_deref(),
     ) {
-        Ok(results) => Json(results),
+        Ok(results) => Json(results).into_response(),
         Err(e) => {
             tracing::error!("Compare query failed: {}", e);
-            Json(Vec::new())
+            Json(Vec::<queries::CompareResult>::new()).into_response()
         }
     }
 }
@@ -224,3 +625,215 @@ async fn trace_handler(
         }
     }
 }
+
+async fn waterfall_handler(
+    State(state): State<Arc<VelocityState>>,
+    axum::extract::Path(request_id): axum::extract::Path<String>,
+) -> Json<Waterfall> {
+    let spans = queries::get_trace(&state.db, &request_id).unwrap_or_else(|e| {
+        tracing::error!("Trace query failed: {}", e);
+        Vec::new()
+    });
+    Json(waterfall::build(&request_id, spans))
+}
+
+async fn search_handler(
+    State(state): State<Arc<VelocityState>>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Response {
+    let search = match SpanSearch::from_query(params) {
+        Ok(s) => s,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    match search::search(&state.db, &search) {
+        Ok(hits) => Json(hits).into_response(),
+        Err(e) => {
+            tracing::error!("Span search failed: {}", e);
+            Json(Vec::<search::SpanSearchHit>::new()).into_response()
+        }
+    }
+}
+
+/// GET /velocity/stream — SSE stream of spans as they are ingested, one
+/// `span` event each, optionally filtered by service, route and minimum
+/// duration.
+async fn stream_handler(
+    State(state): State<Arc<VelocityState>>,
+    Query(filter): Query<StreamFilter>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
+    let supervisor = state.supervisor.clone();
+    let client = supervisor.stream_clients.register("/velocity/stream");
+    let rx = stream_clients::forward(supervisor.clone(), state.db.subscribe(), client);
+    let conn_guard = SseConnectionGuard::new(supervisor.active_sse_connections.clone());
+
+    let event_stream = ReceiverStream::new(rx).filter_map(move |span| {
+        let _hold = &conn_guard;
+        if !filter.matches(&span) {
+            return None;
+        }
+        let data = serde_json::to_string(&span).unwrap_or_default();
+        Some(Ok(Event::default().event("span").data(data)))
+    });
+
+    let shutdown = Box::pin(async move { supervisor.shutdown_signal().await });
+    let event_stream = futures::StreamExt::take_until(event_stream, shutdown);
+
+    Sse::new(event_stream).keep_alive(KeepAlive::default())
+}
+
+/// The supervisor, every runner with a live process, and the frontend.
+async fn sample_targets(supervisor: &SharedState, frontend_pid: Option<u32>) -> Vec<SampleTarget> {
+    let mut targets = vec![SampleTarget {
+        service: "supervisor".to_string(),
+        instance: "supervisor".to_string(),
+        pid: std::process::id(),
+    }];
+    let runners: Vec<_> = supervisor.runners.read().await.values().cloned().collect();
+    for managed in runners {
+        if let Some(pid) = managed.runner.read().await.pid {
+            targets.push(SampleTarget {
+                service: "runner".to_string(),
+                instance: managed.config.id.clone(),
+                pid,
+            });
+        }
+    }
+    if let Some(pid) = frontend_pid {
+        targets.push(SampleTarget {
+            service: "frontend".to_string(),
+            instance: "frontend".to_string(),
+            pid,
+        });
+    }
+    targets
+}
+
+fn load_slos(supervisor: &SharedState) -> Vec<SloDefinition> {
+    settings::load_settings(&settings::settings_path(&supervisor.config)).velocity_slos
+}
+
+/// GET /velocity/slo — current compliance of every configured SLO plus the
+/// recorded violation history.
+async fn slo_handler(State(state): State<Arc<VelocityState>>) -> Json<SloReport> {
+    let window_mins = slo::window_mins();
+    let slos = load_slos(&state.supervisor);
+    let statuses = slo::evaluate(&state.db, &slos, window_mins).unwrap_or_else(|e| {
+        tracing::error!("SLO evaluation failed: {}", e);
+        Vec::new()
+    });
+    let violations = slo::violations(&state.db, slo::VIOLATION_HISTORY_LIMIT).unwrap_or_else(|e| {
+        tracing::error!("SLO violation query failed: {}", e);
+        Vec::new()
+    });
+    Json(SloReport {
+        window_mins,
+        slos: statuses,
+        violations,
+    })
+}
+
+/// PUT /velocity/slo — replace the SLO definitions in the settings file.
+async fn set_slo_handler(
+    State(state): State<Arc<VelocityState>>,
+    Json(slos): Json<Vec<SloDefinition>>,
+) -> Response {
+    if let Err(message) = slo::validate(&slos) {
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    let path = settings::settings_path(&state.supervisor.config);
+    let mut saved = settings::load_settings(&path);
+    saved.velocity_slos = slos;
+    match settings::try_save_settings(&path, &saved) {
+        Ok(()) => Json(saved.velocity_slos).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to save SLOs: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+        }
+    }
+}
+
+/// GET /velocity/sampling — the velocity layer's current sampling config.
+async fn sampling_handler() -> Json<VelocitySampling> {
+    Json(velocity_layer::sampling())
+}
+
+/// PUT /velocity/sampling — save the sampling config and apply it to the
+/// layer.
+async fn set_sampling_handler(
+    State(state): State<Arc<VelocityState>>,
+    Json(sampling): Json<VelocitySampling>,
+) -> Response {
+    if let Err(message) = sampling.validate() {
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    let path = settings::settings_path(&state.supervisor.config);
+    let mut saved = settings::load_settings(&path);
+    saved.velocity_sampling = sampling.clone();
+    if let Err(e) = settings::try_save_settings(&path, &saved) {
+        tracing::error!("Failed to save velocity sampling config: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+    }
+    velocity_layer::set_sampling(sampling.clone());
+    Json(sampling).into_response()
+}
+
+/// GET /velocity/export — spans as a CSV stream or a Parquet file.
+async fn export_handler(
+    State(state): State<Arc<VelocityState>>,
+    Query(params): Query<ExportParams>,
+) -> Response {
+    let Some(format) = ExportFormat::parse(params.format.as_deref().unwrap_or("csv")) else {
+        return (StatusCode::BAD_REQUEST, "format must be csv or parquet").into_response();
+    };
+    let columns = match export::parse_columns(params.columns.as_deref()) {
+        Ok(c) => c,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    let filter = QueryFilter {
+        since: params.since,
+        until: params.until,
+        service: params.service,
+    };
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"velocity-spans.{}\"",
+                format.extension()
+            ),
+        ),
+    ];
+
+    match format {
+        ExportFormat::Csv => {
+            let (tx, rx) = tokio::sync::mpsc::channel::<Result<Bytes, Infallible>>(4);
+            tokio::task::spawn_blocking(move || {
+                let result = export::write_csv(&state.db, &filter, &columns, |chunk| {
+                    tx.blocking_send(Ok(Bytes::from(chunk))).is_ok()
+                });
+                if let Err(e) = result {
+                    tracing::error!("CSV export failed: {}", e);
+                }
+            });
+            (headers, Body::from_stream(ReceiverStream::new(rx))).into_response()
+        }
+        ExportFormat::Parquet => {
+            let result = tokio::task::spawn_blocking(move || {
+                export::write_parquet(&state.db, &filter, &columns)
+            })
+            .await;
+            match result {
+                Ok(Ok(file)) => (headers, file).into_response(),
+                Ok(Err(e)) => {
+                    tracing::error!("Parquet export failed: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+                Err(e) => {
+                    tracing::error!("Parquet export task panicked: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }
+    }
+}
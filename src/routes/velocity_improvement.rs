@@ -1,16 +1,25 @@
-use axum::extract::State;
-use axum::response::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Json, Response};
 use axum::routing::{get, post};
 use axum::Router;
+use serde::Deserialize;
+use std::convert::Infallible;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::watch;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
 
+use crate::job_queue::{self, JobKind, JobRequest, QueueParams};
 use crate::log_capture::{LogLevel, LogSource};
-use crate::state::SharedState;
+use crate::state::{SharedState, SseConnectionGuard};
+use crate::stream_clients;
+use crate::velocity::db::VelocityDb;
 use crate::velocity_improvement::{
-    VelocityImprovementConfig, VelocityImprovementHistory, VelocityImprovementPhase,
-    VelocityImprovementStatus,
+    PendingFix, ResumableLoop, VelocityImprovementConfig, VelocityImprovementHistory,
+    VelocityImprovementLoop, VelocityImprovementPhase, VelocityImprovementStatus,
 };
 use crate::velocity_tests::db::VelocityTestDb;
 
@@ -20,6 +29,10 @@ use crate::velocity_tests::db::VelocityTestDb;
 
 pub struct ViRouteState {
     pub db: Arc<VelocityTestDb>,
+    /// Span store used to pull backend evidence into the fix prompt. `None`
+    /// if it failed to open — the loop then treats Backend Slow pages as
+    /// unfixable, as before.
+    pub span_db: Option<Arc<VelocityDb>>,
     #[allow(dead_code)]
     pub dev_logs_dir: PathBuf,
     pub supervisor: SharedState,
@@ -35,6 +48,19 @@ pub struct MessageResponse {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LoopsQuery {
+    /// Most recent loops to return (default 20).
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// Stored loop to look the iteration up in; the current (or last) loop
+    /// when omitted.
+    pub loop_id: Option<String>,
+}
+
 // ============================================================================
 // Routes
 // ============================================================================
@@ -51,17 +77,44 @@ pub fn velocity_improvement_routes(dev_logs_dir: PathBuf, supervisor: SharedStat
         }
     };
 
+    let span_db = match VelocityDb::new(&dev_logs_dir) {
+        Ok(db) => Some(Arc::new(db)),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to open velocity span database for improvement loop: {}",
+                e
+            );
+            None
+        }
+    };
+
     let state = Arc::new(ViRouteState {
         db: Arc::new(db),
+        span_db,
         dev_logs_dir,
         supervisor,
     });
 
+    tokio::spawn(dispatch_queued_loops(state.clone()));
+
     Router::new()
         .route("/velocity-improvement/start", post(start_handler))
         .route("/velocity-improvement/stop", post(stop_handler))
+        .route("/velocity-improvement/resume", post(resume_handler))
+        .route(
+            "/velocity-improvement/pending-fix",
+            get(pending_fix_handler),
+        )
+        .route("/velocity-improvement/approve", post(approve_handler))
+        .route("/velocity-improvement/reject", post(reject_handler))
         .route("/velocity-improvement/status", get(status_handler))
         .route("/velocity-improvement/history", get(history_handler))
+        .route("/velocity-improvement/stream", get(stream_handler))
+        .route("/velocity-improvement/loops", get(loops_handler))
+        .route(
+            "/velocity-improvement/iterations/{n}/diff",
+            get(iteration_diff_handler),
+        )
         .with_state(state)
 }
 
@@ -71,8 +124,47 @@ pub fn velocity_improvement_routes(dev_logs_dir: PathBuf, supervisor: SharedStat
 
 async fn start_handler(
     State(state): State<Arc<ViRouteState>>,
+    Query(queue): Query<QueueParams>,
     Json(config): Json<VelocityImprovementConfig>,
 ) -> Json<MessageResponse> {
+    let config = match try_launch(&state, config, None).await {
+        Ok(()) => {
+            return Json(MessageResponse {
+                ok: true,
+                message: "Velocity improvement loop started".to_string(),
+            })
+        }
+        Err(config) => config,
+    };
+
+    if !queue.queue {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Velocity improvement loop is already running".to_string(),
+        });
+    }
+    let mut jobs = state.supervisor.job_queue.write().await;
+    Json(
+        match jobs.enqueue(JobRequest::VelocityImprovement(config), queue.priority) {
+            Ok(entry) => MessageResponse {
+                ok: true,
+                message: format!(
+                    "Velocity improvement loop queued as job {} (position {})",
+                    entry.id, entry.position
+                ),
+            },
+            Err(message) => MessageResponse { ok: false, message },
+        },
+    )
+}
+
+/// Start the loop, or resume `resume` with it, unless it is already running;
+/// hands the config back if so.
+async fn try_launch(
+    state: &ViRouteState,
+    config: VelocityImprovementConfig,
+    resume: Option<ResumableLoop>,
+) -> Result<(), VelocityImprovementConfig> {
     // Create stop channel
     let (stop_tx, stop_rx) = watch::channel(false);
 
@@ -80,10 +172,7 @@ async fn start_handler(
     {
         let mut vi = state.supervisor.velocity_improvement.write().await;
         if vi.running {
-            return Json(MessageResponse {
-                ok: false,
-                message: "Velocity improvement loop is already running".to_string(),
-            });
+            return Err(config);
         }
         vi.running = true;
         vi.phase = VelocityImprovementPhase::RunningTests;
@@ -92,7 +181,10 @@ async fn start_handler(
         vi.target_score = config.target_score;
         vi.started_at = Some(chrono::Utc::now());
         vi.error = None;
-        vi.iterations.clear();
+        vi.iterations = resume
+            .as_ref()
+            .map(|r| r.iterations.clone())
+            .unwrap_or_default();
         vi.stop_tx = Some(stop_tx);
     }
 
@@ -102,24 +194,143 @@ async fn start_handler(
         .emit(
             LogSource::Supervisor,
             LogLevel::Info,
-            format!(
-                "Velocity improvement loop started: max_iterations={}, target_score={:.0}",
-                config.max_iterations, config.target_score
-            ),
+            match &resume {
+                Some(r) => format!(
+                    "Velocity improvement loop {} resumed at iteration {}/{}",
+                    r.loop_id, r.progress.iteration, config.max_iterations
+                ),
+                None => format!(
+                    "Velocity improvement loop started: max_iterations={}, target_score={:.0}",
+                    config.max_iterations, config.target_score
+                ),
+            },
         )
         .await;
 
     let db = state.db.clone();
+    let span_db = state.span_db.clone();
     let supervisor = state.supervisor.clone();
 
     tokio::spawn(async move {
-        crate::velocity_improvement::run_velocity_improvement_loop(db, supervisor, config, stop_rx)
-            .await;
+        crate::velocity_improvement::run_velocity_improvement_loop(
+            db,
+            span_db,
+            supervisor,
+            config,
+            stop_rx,
+            resume.map(|r| (r.loop_id, r.progress)),
+        )
+        .await;
     });
+    Ok(())
+}
+
+/// Launch queued improvement loops once the previous loop has finished.
+async fn dispatch_queued_loops(state: Arc<ViRouteState>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        job_queue::DISPATCH_INTERVAL_SECS,
+    ));
+    loop {
+        interval.tick().await;
+        if state.supervisor.velocity_improvement.read().await.running {
+            continue;
+        }
+        let job = state
+            .supervisor
+            .job_queue
+            .write()
+            .await
+            .pop_next(JobKind::VelocityImprovement);
+        let Some(job) = job else { continue };
+        let JobRequest::VelocityImprovement(config) = job.request.clone() else {
+            continue;
+        };
+
+        if try_launch(&state, config, None).await.is_err() {
+            // Lost a race with a direct start — keep its place in line.
+            state.supervisor.job_queue.write().await.requeue(job);
+        }
+    }
+}
 
+/// Pick up the newest loop a supervisor restart cut short.
+async fn resume_handler(State(state): State<Arc<ViRouteState>>) -> Json<MessageResponse> {
+    let resumable = match state.db.get_resumable_loop() {
+        Ok(Some(resumable)) => resumable,
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: "No interrupted velocity improvement loop to resume".to_string(),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load the interrupted loop: {}", e),
+            })
+        }
+    };
+    let message = format!(
+        "Velocity improvement loop {} resumed at iteration {}",
+        resumable.loop_id, resumable.progress.iteration
+    );
+    let config = resumable.config.clone();
+    Json(match try_launch(&state, config, Some(resumable)).await {
+        Ok(()) => MessageResponse { ok: true, message },
+        Err(_) => MessageResponse {
+            ok: false,
+            message: "Velocity improvement loop is already running".to_string(),
+        },
+    })
+}
+
+async fn pending_fix_handler(State(state): State<Arc<ViRouteState>>) -> Json<Option<PendingFix>> {
+    Json(
+        state
+            .supervisor
+            .velocity_improvement
+            .read()
+            .await
+            .pending_fix
+            .clone(),
+    )
+}
+
+async fn approve_handler(State(state): State<Arc<ViRouteState>>) -> Json<MessageResponse> {
+    answer_pending_fix(&state, true).await
+}
+
+async fn reject_handler(State(state): State<Arc<ViRouteState>>) -> Json<MessageResponse> {
+    answer_pending_fix(&state, false).await
+}
+
+async fn answer_pending_fix(state: &ViRouteState, approved: bool) -> Json<MessageResponse> {
+    let mut vi = state.supervisor.velocity_improvement.write().await;
+    let Some(iteration) = vi.pending_fix.as_ref().map(|p| p.iteration) else {
+        return Json(MessageResponse {
+            ok: false,
+            message: "No fix is waiting for approval".to_string(),
+        });
+    };
+    let Some(tx) = vi.approval_tx.take() else {
+        return Json(MessageResponse {
+            ok: false,
+            message: "The pending fix was already answered".to_string(),
+        });
+    };
+    if tx.send(approved).is_err() {
+        return Json(MessageResponse {
+            ok: false,
+            message: "The loop is no longer waiting for approval".to_string(),
+        });
+    }
     Json(MessageResponse {
         ok: true,
-        message: "Velocity improvement loop started".to_string(),
+        message: if approved {
+            format!("Iteration {} fix approved", iteration)
+        } else {
+            format!("Iteration {} fix rejected; the loop will end", iteration)
+        },
     })
 }
 
@@ -163,3 +374,81 @@ async fn history_handler(
         iterations: vi.iterations.clone(),
     })
 }
+
+/// GET /velocity-improvement/stream — SSE stream of the running loop's
+/// progress. Each event's name is its `type`.
+async fn stream_handler(
+    State(state): State<Arc<ViRouteState>>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
+    let supervisor = state.supervisor.clone();
+    let client = supervisor
+        .stream_clients
+        .register("/velocity-improvement/stream");
+    let progress_rx = supervisor
+        .velocity_improvement
+        .read()
+        .await
+        .progress_tx
+        .subscribe();
+    let rx = stream_clients::forward(supervisor.clone(), progress_rx, client);
+    let conn_guard = SseConnectionGuard::new(supervisor.active_sse_connections.clone());
+
+    let event_stream = ReceiverStream::new(rx).map(move |event| {
+        let _hold = &conn_guard;
+        let data = serde_json::to_string(&event).unwrap_or_default();
+        Ok(Event::default().event(event.name()).data(data))
+    });
+
+    let shutdown = Box::pin(async move { supervisor.shutdown_signal().await });
+    let event_stream = futures::StreamExt::take_until(event_stream, shutdown);
+
+    Sse::new(event_stream).keep_alive(KeepAlive::default())
+}
+
+async fn loops_handler(
+    State(state): State<Arc<ViRouteState>>,
+    Query(query): Query<LoopsQuery>,
+) -> Json<Vec<VelocityImprovementLoop>> {
+    match state.db.list_improvement_loops(query.limit.unwrap_or(20)) {
+        Ok(loops) => Json(loops),
+        Err(e) => {
+            tracing::error!("Failed to load velocity improvement loops: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+/// The patch the fix agent made in iteration `n`, as `text/x-diff`.
+async fn iteration_diff_handler(
+    State(state): State<Arc<ViRouteState>>,
+    Path(n): Path<u32>,
+    Query(query): Query<DiffQuery>,
+) -> Response {
+    let diff = match &query.loop_id {
+        Some(loop_id) => match state.db.get_improvement_diff(loop_id, n) {
+            Ok(diff) => diff,
+            Err(e) => {
+                tracing::error!("Failed to load velocity improvement diff: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        },
+        None => {
+            let vi = state.supervisor.velocity_improvement.read().await;
+            vi.iterations
+                .iter()
+                .rev()
+                .find(|it| it.iteration == n)
+                .and_then(|it| it.diff.clone())
+        }
+    };
+    match diff {
+        Some(diff) => {
+            ([(header::CONTENT_TYPE, "text/x-diff; charset=utf-8")], diff).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("No diff recorded for iteration {}", n),
+        )
+            .into_response(),
+    }
+}
@@ -1,16 +1,28 @@
-use axum::extract::{Path, State};
-use axum::response::Json;
-use axum::routing::{get, post};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{delete, get, post};
 use axum::Router;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::watch;
 
+use crate::evaluation::schedule::{CronExpr, SCHEDULE_CHECK_INTERVAL_SECS};
+use crate::job_queue::{self, JobKind, JobRequest, QueueParams};
+use crate::log_capture::{LogLevel, LogSource};
 use crate::state::SharedState;
+use crate::velocity_tests::api_sweep;
+use crate::velocity_tests::baseline::{self, BaselinePin};
+use crate::velocity_tests::budget;
 use crate::velocity_tests::db::VelocityTestDb;
+use crate::velocity_tests::engine::VelocityRunOptions;
+use crate::velocity_tests::schedule::{RunSource, VelocityTestSchedule};
+use crate::velocity_tests::screenshots;
+use crate::velocity_tests::tests::TestCase;
+use crate::velocity_tests::trends::{self, PageTrend};
 use crate::velocity_tests::{
-    VelocityTestRunWithResults, VelocityTestStatus, VelocityTestTrendPoint,
+    VelocityTestRun, VelocityTestRunWithResults, VelocityTestStatus, VelocityTestTrendPoint,
 };
 
 // ============================================================================
@@ -37,6 +49,38 @@ pub struct MessageResponse {
 #[derive(Debug, Deserialize)]
 pub struct TrendQuery {
     pub limit: Option<i64>,
+    /// `manual` or `scheduled`; all runs when omitted.
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinBaselineRequest {
+    pub run_id: String,
+    pub tolerance_pct: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    /// Run to compare; the most recent completed run when omitted.
+    pub run_id: Option<String>,
+}
+
+/// A schedule plus when it will next fire.
+#[derive(Debug, Serialize)]
+pub struct ScheduleView {
+    #[serde(flatten)]
+    pub schedule: VelocityTestSchedule,
+    pub next_fire_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrendsQuery {
+    /// Test case name; all pages when omitted.
+    pub page: Option<String>,
+    /// Completed runs to look back over (default 20).
+    pub limit: Option<i64>,
+    /// `manual` or `scheduled`; all runs when omitted.
+    pub source: Option<String>,
 }
 
 // ============================================================================
@@ -52,19 +96,74 @@ pub fn velocity_test_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> R
         }
     };
 
+    match db.get_trend(1, None) {
+        Ok(points) => {
+            if let Ok(mut vt) = supervisor.velocity_tests.try_write() {
+                vt.last_completed_score = points.last().and_then(|p| p.overall_score);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to load latest velocity test score: {}", e),
+    }
+
     let state = Arc::new(VtRouteState {
         db: Arc::new(db),
         dev_logs_dir,
         supervisor,
     });
 
+    tokio::spawn(dispatch_queued_runs(state.clone()));
+    tokio::spawn(run_schedules(state.clone()));
+
     Router::new()
         .route("/velocity-tests/start", post(start_handler))
         .route("/velocity-tests/stop", post(stop_handler))
         .route("/velocity-tests/status", get(status_handler))
         .route("/velocity-tests/runs", get(list_runs_handler))
         .route("/velocity-tests/runs/{id}", get(get_run_handler))
+        .route("/velocity-tests/runs/{id}/budget", get(budget_handler))
+        .route(
+            "/velocity-tests/runs/{id}/api-report",
+            get(api_report_handler),
+        )
+        .route(
+            "/velocity-tests/runs/{id}/screenshots/{file}",
+            get(screenshot_handler),
+        )
         .route("/velocity-tests/trend", get(trend_handler))
+        .route("/velocity-tests/trends", get(trends_handler))
+        .route(
+            "/velocity-tests/baseline",
+            get(get_baseline_handler)
+                .put(pin_baseline_handler)
+                .delete(unpin_baseline_handler),
+        )
+        .route("/velocity-tests/compare", get(compare_handler))
+        .route(
+            "/velocity-tests/schedules",
+            get(list_schedules_handler).post(create_schedule_handler),
+        )
+        .route(
+            "/velocity-tests/schedules/{id}",
+            delete(delete_schedule_handler),
+        )
+        .route(
+            "/velocity-tests/schedules/{id}/enable",
+            post(enable_schedule_handler),
+        )
+        .route(
+            "/velocity-tests/schedules/{id}/disable",
+            post(disable_schedule_handler),
+        )
+        .route(
+            "/velocity-tests/cases",
+            get(list_cases_handler).post(create_case_handler),
+        )
+        .route(
+            "/velocity-tests/cases/{id}",
+            get(get_case_handler)
+                .put(update_case_handler)
+                .delete(delete_case_handler),
+        )
         .with_state(state)
 }
 
@@ -72,7 +171,41 @@ pub fn velocity_test_routes(dev_logs_dir: PathBuf, supervisor: SharedState) -> R
 // Handlers
 // ============================================================================
 
-async fn start_handler(State(state): State<Arc<VtRouteState>>) -> Json<MessageResponse> {
+async fn start_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Query(queue): Query<QueueParams>,
+    Query(options): Query<VelocityRunOptions>,
+) -> Json<MessageResponse> {
+    if try_launch(&state, options).await {
+        return Json(MessageResponse {
+            ok: true,
+            message: "Velocity tests started".to_string(),
+        });
+    }
+
+    if !queue.queue {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Velocity tests already running".to_string(),
+        });
+    }
+    let mut jobs = state.supervisor.job_queue.write().await;
+    Json(
+        match jobs.enqueue(JobRequest::VelocityTests(options), queue.priority) {
+            Ok(entry) => MessageResponse {
+                ok: true,
+                message: format!(
+                    "Velocity tests queued as job {} (position {})",
+                    entry.id, entry.position
+                ),
+            },
+            Err(message) => MessageResponse { ok: false, message },
+        },
+    )
+}
+
+/// Start a run unless one is in progress. Returns `false` if busy.
+async fn try_launch(state: &VtRouteState, options: VelocityRunOptions) -> bool {
     // Create stop channel
     let (stop_tx, stop_rx) = watch::channel(false);
 
@@ -80,10 +213,7 @@ async fn start_handler(State(state): State<Arc<VtRouteState>>) -> Json<MessageRe
     {
         let mut vt = state.supervisor.velocity_tests.write().await;
         if vt.running {
-            return Json(MessageResponse {
-                ok: false,
-                message: "Velocity tests already running".to_string(),
-            });
+            return false;
         }
         vt.running = true;
         vt.stop_tx = Some(stop_tx);
@@ -93,13 +223,116 @@ async fn start_handler(State(state): State<Arc<VtRouteState>>) -> Json<MessageRe
     let supervisor = state.supervisor.clone();
 
     tokio::spawn(async move {
-        crate::velocity_tests::engine::run_velocity_tests(db, supervisor, stop_rx).await;
+        crate::velocity_tests::engine::run_velocity_tests(db, supervisor, stop_rx, options).await;
     });
+    true
+}
 
-    Json(MessageResponse {
-        ok: true,
-        message: "Velocity tests started".to_string(),
-    })
+/// Launch queued velocity test runs once the engine is idle.
+async fn dispatch_queued_runs(state: Arc<VtRouteState>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        job_queue::DISPATCH_INTERVAL_SECS,
+    ));
+    loop {
+        interval.tick().await;
+        if state.supervisor.velocity_tests.read().await.running {
+            continue;
+        }
+        let job = state
+            .supervisor
+            .job_queue
+            .write()
+            .await
+            .pop_next(JobKind::VelocityTests);
+        let Some(job) = job else { continue };
+        let JobRequest::VelocityTests(options) = job.request else {
+            continue;
+        };
+
+        if try_launch(&state, options).await {
+            state
+                .supervisor
+                .logs
+                .emit(
+                    LogSource::Supervisor,
+                    LogLevel::Info,
+                    format!("Started queued velocity test run (job {})", job.id),
+                )
+                .await;
+        } else {
+            // Lost a race with a direct start — keep its place in line.
+            state.supervisor.job_queue.write().await.requeue(job);
+        }
+    }
+}
+
+/// Fire due cron schedules by enqueueing a scheduled velocity test job, so a
+/// schedule that comes due mid-run starts as soon as the engine is idle.
+async fn run_schedules(state: Arc<VtRouteState>) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let schedules = match state.db.list_schedules() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::error!("Failed to load velocity test schedules: {}", e);
+                continue;
+            }
+        };
+        let now = chrono::Local::now().naive_local();
+        for sched in schedules.into_iter().filter(|s| s.enabled) {
+            if !sched.next_fire().is_some_and(|t| t <= now) {
+                continue;
+            }
+            // Record the firing first so a failure below can't re-fire every
+            // tick.
+            if let Err(e) = state
+                .db
+                .mark_schedule_fired(&sched.id, &chrono::Utc::now().to_rfc3339())
+            {
+                tracing::error!(
+                    "Failed to mark velocity test schedule {} fired: {}",
+                    sched.id,
+                    e
+                );
+                continue;
+            }
+            let options = VelocityRunOptions {
+                concurrency: sched.concurrency,
+                strict: sched.strict,
+                source: RunSource::Scheduled,
+                ..Default::default()
+            };
+            let enqueued = state
+                .supervisor
+                .job_queue
+                .write()
+                .await
+                .enqueue(JobRequest::VelocityTests(options), 0);
+            let (level, message) = match enqueued {
+                Ok(entry) => (
+                    LogLevel::Info,
+                    format!(
+                        "Velocity test schedule '{}' fired; queued as job {}",
+                        sched.name, entry.id
+                    ),
+                ),
+                Err(e) => (
+                    LogLevel::Warn,
+                    format!(
+                        "Velocity test schedule '{}' could not fire: {}",
+                        sched.name, e
+                    ),
+                ),
+            };
+            state
+                .supervisor
+                .logs
+                .emit(LogSource::Supervisor, level, message)
+                .await;
+        }
+    }
 }
 
 async fn stop_handler(State(state): State<Arc<VtRouteState>>) -> Json<MessageResponse> {
@@ -164,7 +397,214 @@ async fn get_run_handler(
         }
     };
 
-    Json(Some(VelocityTestRunWithResults { run, results }))
+    let environment = match state.db.get_run_environment(&id) {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::error!("Failed to get velocity test run environment: {}", e);
+            None
+        }
+    };
+
+    Json(Some(VelocityTestRunWithResults {
+        run,
+        environment,
+        results,
+    }))
+}
+
+/// GET /velocity-tests/runs/{id}/budget — budget compliance of a run;
+/// `latest` is the most recent completed run.
+async fn screenshot_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path((id, file)): Path<(String, String)>,
+) -> Response {
+    if !screenshots::is_safe_file_name(&id) || !screenshots::is_safe_file_name(&file) {
+        return (StatusCode::BAD_REQUEST, "Invalid screenshot path").into_response();
+    }
+    let path = screenshots::run_dir(&state.dev_logs_dir, &id).join(&file);
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => ([(axum::http::header::CONTENT_TYPE, "image/png")], bytes).into_response(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            (StatusCode::NOT_FOUND, "No such screenshot").into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Look up run `id`, or the most recent completed run for `latest`.
+fn resolve_run(state: &VtRouteState, id: &str) -> Result<VelocityTestRun, Response> {
+    if id == "latest" {
+        match state.db.list_runs() {
+            Ok(runs) => runs
+                .into_iter()
+                .find(|r| r.status == "completed")
+                .ok_or_else(|| {
+                    (StatusCode::NOT_FOUND, "No completed velocity test run").into_response()
+                }),
+            Err(e) => {
+                tracing::error!("Failed to list velocity test runs: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+            }
+        }
+    } else {
+        match state.db.get_run(id) {
+            Ok(Some(run)) => Ok(run),
+            Ok(None) => Err((StatusCode::NOT_FOUND, "No such run").into_response()),
+            Err(e) => {
+                tracing::error!("Failed to get velocity test run: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+            }
+        }
+    }
+}
+
+async fn budget_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let run_id = match resolve_run(&state, &id) {
+        Ok(run) => run.id,
+        Err(resp) => return resp,
+    };
+    match state.db.get_results_for_run(&run_id) {
+        Ok(results) => Json(budget::compliance(&run_id, &results)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get velocity test results: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+async fn api_report_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let run_id = match resolve_run(&state, &id) {
+        Ok(run) => run.id,
+        Err(resp) => return resp,
+    };
+    match state.db.get_api_results(&run_id) {
+        Ok(routes) => Json(api_sweep::report(&run_id, routes)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get API sweep results: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+async fn get_baseline_handler(State(state): State<Arc<VtRouteState>>) -> Json<Option<BaselinePin>> {
+    match state.db.get_baseline() {
+        Ok(pin) => Json(pin),
+        Err(e) => {
+            tracing::error!("Failed to load velocity test baseline: {}", e);
+            Json(None)
+        }
+    }
+}
+
+async fn pin_baseline_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Json(body): Json<PinBaselineRequest>,
+) -> Json<MessageResponse> {
+    match state.db.get_run(&body.run_id) {
+        Ok(Some(run)) if run.status == "completed" => {}
+        Ok(Some(run)) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!(
+                    "Run {} is {}; only completed runs can be pinned",
+                    run.id, run.status
+                ),
+            })
+        }
+        Ok(None) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Run {} not found", body.run_id),
+            })
+        }
+        Err(e) => {
+            return Json(MessageResponse {
+                ok: false,
+                message: format!("Failed to load run: {}", e),
+            })
+        }
+    }
+    let tolerance_pct = body
+        .tolerance_pct
+        .unwrap_or(baseline::DEFAULT_TOLERANCE_PCT);
+    if !tolerance_pct.is_finite() || tolerance_pct < 0.0 {
+        return Json(MessageResponse {
+            ok: false,
+            message: "tolerance_pct must not be negative".to_string(),
+        });
+    }
+
+    let pin = BaselinePin {
+        run_id: body.run_id,
+        pinned_at: chrono::Utc::now().to_rfc3339(),
+        tolerance_pct,
+    };
+    match state.db.set_baseline(&pin) {
+        Ok(()) => Json(MessageResponse {
+            ok: true,
+            message: format!("Run {} pinned as velocity test baseline", pin.run_id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to pin baseline: {}", e),
+        }),
+    }
+}
+
+async fn unpin_baseline_handler(State(state): State<Arc<VtRouteState>>) -> Json<MessageResponse> {
+    match state.db.clear_baseline() {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: "Velocity test baseline unpinned".to_string(),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: "No velocity test baseline is pinned".to_string(),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to unpin baseline: {}", e),
+        }),
+    }
+}
+
+async fn compare_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Query(query): Query<CompareQuery>,
+) -> Response {
+    let pin = match state.db.get_baseline() {
+        Ok(Some(pin)) => pin,
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, "No velocity test baseline is pinned").into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to load velocity test baseline: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let run_id = match resolve_run(&state, query.run_id.as_deref().unwrap_or("latest")) {
+        Ok(run) => run.id,
+        Err(resp) => return resp,
+    };
+    let results = state
+        .db
+        .get_results_for_run(&run_id)
+        .and_then(|current| Ok((current, state.db.get_results_for_run(&pin.run_id)?)));
+    match results {
+        Ok((current, baseline)) => {
+            Json(baseline::compare(&pin, &run_id, &current, &baseline)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get velocity test results: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
 }
 
 async fn trend_handler(
@@ -172,7 +612,7 @@ async fn trend_handler(
     axum::extract::Query(query): axum::extract::Query<TrendQuery>,
 ) -> Json<Vec<VelocityTestTrendPoint>> {
     let limit = query.limit.unwrap_or(20);
-    match state.db.get_trend(limit) {
+    match state.db.get_trend(limit, query.source.as_deref()) {
         Ok(points) => Json(points),
         Err(e) => {
             tracing::error!("Failed to get velocity test trend: {}", e);
@@ -180,3 +620,229 @@ async fn trend_handler(
         }
     }
 }
+
+/// GET /velocity-tests/trends — per-page metric series with regression flags.
+async fn trends_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Query(query): Query<TrendsQuery>,
+) -> Json<Vec<PageTrend>> {
+    let limit = query.limit.unwrap_or(20);
+    match state
+        .db
+        .get_page_history(limit, query.page.as_deref(), query.source.as_deref())
+    {
+        Ok(rows) => Json(trends::analyze(rows)),
+        Err(e) => {
+            tracing::error!("Failed to get velocity test page history: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn list_schedules_handler(State(state): State<Arc<VtRouteState>>) -> Json<Vec<ScheduleView>> {
+    match state.db.list_schedules() {
+        Ok(schedules) => Json(
+            schedules
+                .into_iter()
+                .map(|schedule| {
+                    let next_fire_at = schedule
+                        .enabled
+                        .then(|| schedule.next_fire())
+                        .flatten()
+                        .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+                        .map(|t| t.to_rfc3339());
+                    ScheduleView {
+                        schedule,
+                        next_fire_at,
+                    }
+                })
+                .collect(),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to list velocity test schedules: {}", e);
+            Json(Vec::new())
+        }
+    }
+}
+
+async fn create_schedule_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Json(mut sched): Json<VelocityTestSchedule>,
+) -> Json<MessageResponse> {
+    if sched.name.trim().is_empty() {
+        return Json(MessageResponse {
+            ok: false,
+            message: "Schedule name must not be empty".to_string(),
+        });
+    }
+    if let Err(e) = CronExpr::parse(&sched.cron) {
+        return Json(MessageResponse {
+            ok: false,
+            message: format!("Invalid cron expression: {}", e),
+        });
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    sched.id = uuid::Uuid::new_v4().to_string();
+    sched.last_fired_at = None;
+    sched.created_at = now.clone();
+    sched.updated_at = now;
+
+    match state.db.insert_schedule(&sched) {
+        Ok(()) => Json(MessageResponse {
+            ok: true,
+            message: format!(
+                "Velocity test schedule '{}' created as {}",
+                sched.name, sched.id
+            ),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to create velocity test schedule: {}", e),
+        }),
+    }
+}
+
+fn set_schedule_enabled(state: &VtRouteState, id: &str, enabled: bool) -> MessageResponse {
+    match state.db.set_schedule_enabled(id, enabled) {
+        Ok(true) => MessageResponse {
+            ok: true,
+            message: format!(
+                "Velocity test schedule {} {}",
+                id,
+                if enabled { "enabled" } else { "disabled" }
+            ),
+        },
+        Ok(false) => MessageResponse {
+            ok: false,
+            message: format!("Velocity test schedule {} not found", id),
+        },
+        Err(e) => MessageResponse {
+            ok: false,
+            message: format!("Failed to update: {}", e),
+        },
+    }
+}
+
+async fn enable_schedule_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    Json(set_schedule_enabled(&state, &id, true))
+}
+
+async fn disable_schedule_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    Json(set_schedule_enabled(&state, &id, false))
+}
+
+async fn delete_schedule_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<String>,
+) -> Json<MessageResponse> {
+    match state.db.delete_schedule(&id) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Velocity test schedule {} deleted", id),
+        }),
+        Ok(false) => Json(MessageResponse {
+            ok: false,
+            message: format!("Velocity test schedule {} not found", id),
+        }),
+        Err(e) => Json(MessageResponse {
+            ok: false,
+            message: format!("Failed to delete: {}", e),
+        }),
+    }
+}
+
+async fn list_cases_handler(State(state): State<Arc<VtRouteState>>) -> Response {
+    match state.db.list_test_cases() {
+        Ok(cases) => Json(cases).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list velocity test cases: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+async fn get_case_handler(State(state): State<Arc<VtRouteState>>, Path(id): Path<i64>) -> Response {
+    match state.db.get_test_case(id) {
+        Ok(Some(tc)) => Json(tc).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "No such test case").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get velocity test case: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+async fn create_case_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Json(mut tc): Json<TestCase>,
+) -> Response {
+    if let Err(message) = tc.validate() {
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    match state.db.create_test_case(&tc) {
+        Ok(id) => {
+            tc.id = id;
+            (StatusCode::CREATED, Json(tc)).into_response()
+        }
+        Err(e) => case_write_error(&tc, e),
+    }
+}
+
+async fn update_case_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<i64>,
+    Json(mut tc): Json<TestCase>,
+) -> Response {
+    if let Err(message) = tc.validate() {
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    match state.db.update_test_case(id, &tc) {
+        Ok(true) => {
+            tc.id = id;
+            Json(tc).into_response()
+        }
+        Ok(false) => (StatusCode::NOT_FOUND, "No such test case").into_response(),
+        Err(e) => case_write_error(&tc, e),
+    }
+}
+
+async fn delete_case_handler(
+    State(state): State<Arc<VtRouteState>>,
+    Path(id): Path<i64>,
+) -> Response {
+    match state.db.delete_test_case(id) {
+        Ok(true) => Json(MessageResponse {
+            ok: true,
+            message: format!("Deleted test case {}", id),
+        })
+        .into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, "No such test case").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to delete velocity test case: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+/// 409 for a name that is already taken, 500 otherwise.
+fn case_write_error(tc: &TestCase, e: anyhow::Error) -> Response {
+    let duplicate = e
+        .downcast_ref::<rusqlite::Error>()
+        .and_then(rusqlite::Error::sqlite_error_code)
+        == Some(rusqlite::ErrorCode::ConstraintViolation);
+    if duplicate {
+        return (
+            StatusCode::CONFLICT,
+            format!("A test case named '{}' already exists", tc.name),
+        )
+            .into_response();
+    }
+    tracing::error!("Failed to save velocity test case: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+}
@@ -6,6 +6,7 @@ use tracing::debug;
 
 use crate::routes::health::build_health_response;
 use crate::state::SharedState;
+use crate::stream_clients;
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
@@ -22,7 +23,10 @@ async fn handle_socket(mut socket: WebSocket, state: SharedState) {
         }
     }
 
-    let mut rx = state.health_tx.subscribe();
+    // Health notifications go through a bounded per-client queue; the queue
+    // closes if this client keeps lagging (see `stream_clients`).
+    let client = state.stream_clients.register("/ws");
+    let mut rx = stream_clients::forward(state.clone(), state.health_tx.subscribe(), client);
     // Use the latched shutdown helper instead of a raw broadcast subscribe:
     // the helper resolves immediately if the WS connects *after* shutdown
     // already fired, which protects axum's graceful drain from being held
@@ -44,7 +48,7 @@ async fn handle_socket(mut socket: WebSocket, state: SharedState) {
             // Health change notification — debounce 100ms then send
             result = rx.recv() => {
                 match result {
-                    Ok(()) => {
+                    Some(()) => {
                         // Debounce: drain any queued notifications within 100ms
                         sleep(Duration::from_millis(100)).await;
                         while rx.try_recv().is_ok() {}
@@ -56,7 +60,10 @@ async fn handle_socket(mut socket: WebSocket, state: SharedState) {
                             }
                         }
                     }
-                    Err(_) => break,
+                    None => {
+                        let _ = socket.send(Message::Close(None)).await;
+                        break;
+                    }
                 }
             }
             // Client messages — handle ping/pong/close
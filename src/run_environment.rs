@@ -0,0 +1,113 @@
+//! Environment snapshot recorded with each eval and velocity test run, so a
+//! score can be traced back to the system state that produced it.
+//!
+//! Everything here is best-effort: a repo that isn't checked out or a runner
+//! that hasn't been probed yet shows up as `None`, never as a failed run.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::health_cache::RunnerStatus;
+use crate::state::SharedState;
+
+/// Sibling checkouts (next to the runner repo) whose HEAD is recorded.
+pub const WATCHED_REPOS: &[&str] = &[
+    "qontinui-runner",
+    "qontinui-web",
+    "qontinui-schemas",
+    "qontinui-supervisor",
+];
+
+const GIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentSnapshot {
+    pub captured_at: String,
+    pub supervisor_version: String,
+    pub supervisor_build_id: String,
+    /// Primary runner as last seen by the health cache.
+    pub runner: Option<RunnerSnapshot>,
+    /// `git rev-parse HEAD` per entry in [`WATCHED_REPOS`].
+    pub repo_shas: BTreeMap<String, Option<String>>,
+    pub ai_provider: String,
+    pub ai_model: String,
+    pub auto_debug_enabled: bool,
+    pub crash_restart_armed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerSnapshot {
+    pub id: String,
+    pub name: String,
+    pub port: u16,
+    pub running: bool,
+    pub api_responding: bool,
+    pub derived_status: RunnerStatus,
+}
+
+async fn head_sha(repo: &Path) -> Option<String> {
+    if !repo.join(".git").exists() {
+        return None;
+    }
+    let output = tokio::time::timeout(
+        GIT_TIMEOUT,
+        tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["rev-parse", "HEAD"])
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!sha.is_empty()).then_some(sha)
+}
+
+/// Capture the current environment. Cheap enough to call at every run start.
+pub async fn capture(state: &SharedState) -> EnvironmentSnapshot {
+    let runner = state
+        .cached_runner_health
+        .read()
+        .await
+        .iter()
+        .find(|r| r.kind.is_primary())
+        .map(|r| RunnerSnapshot {
+            id: r.id.clone(),
+            name: r.name.clone(),
+            port: r.port,
+            running: r.running,
+            api_responding: r.api_responding,
+            derived_status: r.derived_status.clone(),
+        });
+
+    let runner_repo = state.config.runner_npm_dir();
+    let workspace_root = runner_repo.parent().unwrap_or(&runner_repo);
+    let mut repo_shas = BTreeMap::new();
+    for name in WATCHED_REPOS {
+        let sha = head_sha(&workspace_root.join(name)).await;
+        repo_shas.insert(name.to_string(), sha);
+    }
+
+    let (ai_provider, ai_model, auto_debug_enabled) = {
+        let ai = state.ai.read().await;
+        (ai.provider.clone(), ai.model.clone(), ai.auto_debug_enabled)
+    };
+
+    EnvironmentSnapshot {
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        supervisor_version: env!("CARGO_PKG_VERSION").to_string(),
+        supervisor_build_id: state.build_id.clone(),
+        runner,
+        repo_shas,
+        ai_provider,
+        ai_model,
+        auto_debug_enabled,
+        crash_restart_armed: crate::process::manager::crash_restart_globally_armed(&state.config),
+    }
+}
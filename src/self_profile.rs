@@ -0,0 +1,256 @@
+//! On-demand self-profile for `GET /internal/profile`.
+//!
+//! The supervisor has been seen to grow after days of velocity ingestion and
+//! eval runs. This report answers "where did it go?" without attaching a
+//! profiler: process RSS, tokio runtime counters, the long-running activities
+//! the supervisor itself is driving, and a rough byte estimate for every
+//! in-memory store plus the SQLite files it writes.
+//!
+//! Stable tokio has no per-task dump (that needs `tokio_unstable` +
+//! `taskdump`), so `activities` lists supervisor-level work instead — which is
+//! what usually matters when something is stuck. Byte figures for in-memory
+//! stores are approximations (serialized size or summed string lengths), not
+//! allocator truth.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+use crate::state::SharedState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileReport {
+    pub captured_at: String,
+    pub process: Option<ProcessMemory>,
+    pub runtime: RuntimeStats,
+    pub activities: Vec<Activity>,
+    pub subsystems: Vec<SubsystemEstimate>,
+    pub sqlite: Vec<DbFileSize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessMemory {
+    pub pid: u32,
+    pub rss_bytes: u64,
+    pub virtual_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeStats {
+    pub workers: usize,
+    pub alive_tasks: usize,
+    pub global_queue_depth: usize,
+    pub sse_connections: usize,
+}
+
+/// A long-running piece of supervisor work and how long it has been going.
+#[derive(Debug, Clone, Serialize)]
+pub struct Activity {
+    pub kind: String,
+    pub detail: String,
+    pub started_at: Option<String>,
+    pub elapsed_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubsystemEstimate {
+    pub name: String,
+    pub items: usize,
+    /// `None` where no cheap estimate exists.
+    pub approx_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbFileSize {
+    pub name: String,
+    pub bytes: u64,
+    pub wal_bytes: u64,
+}
+
+/// SQLite files under `.dev-logs/` written by the supervisor.
+const SQLITE_FILES: &[&str] = &["eval-benchmark.db", "velocity.db"];
+
+/// Per-entry overhead added to summed string lengths for log buffers
+/// (timestamp, enums, `String` header, `VecDeque` slot).
+const LOG_ENTRY_OVERHEAD: u64 = 64;
+
+fn process_memory() -> Option<ProcessMemory> {
+    use sysinfo::{ProcessRefreshKind, RefreshKind, System};
+    let pid = std::process::id();
+    let system = System::new_with_specifics(
+        RefreshKind::new().with_processes(ProcessRefreshKind::new().with_memory()),
+    );
+    let process = system.process(sysinfo::Pid::from_u32(pid))?;
+    Some(ProcessMemory {
+        pid,
+        rss_bytes: process.memory(),
+        virtual_bytes: process.virtual_memory(),
+    })
+}
+
+fn activity(kind: &str, detail: String, started_at: Option<DateTime<Utc>>) -> Activity {
+    Activity {
+        kind: kind.to_string(),
+        detail,
+        started_at: started_at.map(|t| t.to_rfc3339()),
+        elapsed_secs: started_at.map(|t| (Utc::now() - t).num_seconds()),
+    }
+}
+
+fn log_bytes(entries: &[crate::log_capture::LogEntry]) -> u64 {
+    entries
+        .iter()
+        .map(|e| e.message.len() as u64 + LOG_ENTRY_OVERHEAD)
+        .sum()
+}
+
+fn json_bytes<T: Serialize>(value: &T) -> Option<u64> {
+    serde_json::to_vec(value).ok().map(|v| v.len() as u64)
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+pub async fn capture(state: &SharedState) -> ProfileReport {
+    let metrics = tokio::runtime::Handle::current().metrics();
+    let runtime = RuntimeStats {
+        workers: metrics.num_workers(),
+        alive_tasks: metrics.num_alive_tasks(),
+        global_queue_depth: metrics.global_queue_depth(),
+        sse_connections: state.active_sse_connections.load(Ordering::Relaxed),
+    };
+
+    // Activities
+    let mut activities = Vec::new();
+    {
+        let eval = state.evaluation.read().await;
+        if eval.running || eval.continuous_mode {
+            activities.push(activity(
+                "eval",
+                format!(
+                    "run={} prompt {}/{}{}",
+                    eval.current_run_id.as_deref().unwrap_or("-"),
+                    eval.current_prompt_index,
+                    eval.total_prompts,
+                    if eval.continuous_mode {
+                        " (continuous)"
+                    } else {
+                        ""
+                    }
+                ),
+                None,
+            ));
+        }
+    }
+    {
+        let vt = state.velocity_tests.read().await;
+        if vt.running {
+            activities.push(activity(
+                "velocity_tests",
+                format!(
+                    "run={} test {}/{}",
+                    vt.current_run_id.as_deref().unwrap_or("-"),
+                    vt.current_test_index,
+                    vt.total_tests
+                ),
+                None,
+            ));
+        }
+    }
+    {
+        let vi = state.velocity_improvement.read().await;
+        if vi.running {
+            activities.push(activity(
+                "velocity_improvement",
+                format!(
+                    "iteration {}/{} phase={:?}",
+                    vi.current_iteration, vi.max_iterations, vi.phase
+                ),
+                vi.started_at,
+            ));
+        }
+    }
+    for slot in &state.build_pool.slots {
+        if let Some(info) = slot.busy.read().await.as_ref() {
+            activities.push(activity(
+                "build",
+                format!("slot {} ({})", slot.id, info.rebuild_kind),
+                Some(info.started_at),
+            ));
+        }
+    }
+
+    // In-memory stores
+    let mut subsystems = Vec::new();
+    let history = state.logs.history().await;
+    subsystems.push(SubsystemEstimate {
+        name: "log_buffer".to_string(),
+        items: history.len(),
+        approx_bytes: Some(log_bytes(&history)),
+    });
+    drop(history);
+    let build_history = state.logs.build_history().await;
+    subsystems.push(SubsystemEstimate {
+        name: "build_log_buffer".to_string(),
+        items: build_history.len(),
+        approx_bytes: Some(log_bytes(&build_history)),
+    });
+    drop(build_history);
+    {
+        let events = state.diagnostics.read().await.events(usize::MAX, None);
+        subsystems.push(SubsystemEstimate {
+            name: "diagnostics".to_string(),
+            items: events.len(),
+            approx_bytes: json_bytes(&events),
+        });
+    }
+    {
+        let actions = state.dev_actions.read().await.recent(usize::MAX);
+        let records: Vec<&crate::dev_action::ActionRecord> =
+            actions.iter().map(|a| a.as_ref()).collect();
+        subsystems.push(SubsystemEstimate {
+            name: "dev_actions".to_string(),
+            items: actions.len(),
+            approx_bytes: json_bytes(&records),
+        });
+    }
+    subsystems.push(SubsystemEstimate {
+        name: "stopped_runners".to_string(),
+        items: state.stopped_runners.read().await.len(),
+        approx_bytes: None,
+    });
+    subsystems.push(SubsystemEstimate {
+        name: "runner_health_cache".to_string(),
+        items: state.cached_runner_health.read().await.len(),
+        approx_bytes: None,
+    });
+    subsystems.push(SubsystemEstimate {
+        name: "job_queue".to_string(),
+        items: state.job_queue.read().await.entries().len(),
+        approx_bytes: None,
+    });
+
+    // On-disk stores
+    let sqlite = SQLITE_FILES
+        .iter()
+        .map(|name| {
+            let path = state.config.dev_logs_dir.join(name);
+            DbFileSize {
+                name: name.to_string(),
+                bytes: file_size(&path),
+                wal_bytes: file_size(&path.with_extension("db-wal")),
+            }
+        })
+        .collect();
+
+    ProfileReport {
+        captured_at: Utc::now().to_rfc3339(),
+        process: process_memory(),
+        runtime,
+        activities,
+        subsystems,
+        sqlite,
+    }
+}
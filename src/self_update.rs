@@ -0,0 +1,309 @@
+//! Supervisor self-update: download a release binary, verify its SHA-256,
+//! and swap it in place of the running exe.
+//!
+//! The restart itself is `routes::runner::spawn_replacement_and_exit` — the
+//! same path `POST /supervisor/restart` uses — so user runners, the settings
+//! file, and the SQLite stores all survive an update exactly as they survive
+//! a plain restart.
+//!
+//! Only checksum verification is performed. Releases do not currently ship
+//! detached signatures, so the binary and its checksum both come from the
+//! release itself: the download must be an asset of [`RELEASE_REPO`], and the
+//! expected digest is always read from that release's `<asset>.sha256`
+//! sidecar — never from the request. A release without a sidecar is refused
+//! rather than installed unverified.
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// GitHub repository that publishes supervisor releases.
+const RELEASE_REPO: &str = "qontinui/qontinui-supervisor";
+
+/// Download budget for the release binary (the shared client's 10s default
+/// is far too short for a ~50 MB exe).
+const DOWNLOAD_TIMEOUT_SECS: u64 = 300;
+
+/// Set while an update is downloading/swapping so two concurrent
+/// `POST /update` calls can't interleave their renames.
+static UPDATE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Body for `POST /update`. An empty body installs the latest GitHub
+/// release for this platform.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateRequest {
+    /// Release tag to install (e.g. `v0.9.2`).
+    pub version: Option<String>,
+}
+
+/// Outcome of a successful download + swap, returned before restarting.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InstalledUpdate {
+    pub source_url: String,
+    pub sha256: String,
+    pub size_bytes: usize,
+    pub exe_path: PathBuf,
+    pub backup_path: PathBuf,
+}
+
+/// Release asset name for the platform this supervisor was built for.
+pub fn platform_asset_name() -> String {
+    format!(
+        "qontinui-supervisor-{}-{}{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Parse a `sha256sum`-style sidecar (`<hex>  <filename>`, or just `<hex>`).
+pub fn parse_sha256_sidecar(text: &str) -> Option<String> {
+    let token = text.split_whitespace().next()?;
+    if token.len() == 64 && token.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(token.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Check `bytes` against an expected hex digest (case-insensitive).
+pub fn verify_sha256(bytes: &[u8], expected: &str) -> Result<String, String> {
+    let actual = hex::encode(Sha256::digest(bytes));
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(actual)
+    } else {
+        Err(format!(
+            "checksum mismatch: expected {}, got {}",
+            expected.trim(),
+            actual
+        ))
+    }
+}
+
+/// Whether `url` is a release asset download of [`RELEASE_REPO`].
+pub fn is_release_asset_url(url: &str) -> bool {
+    let prefix = format!("https://github.com/{}/releases/download/", RELEASE_REPO);
+    url.strip_prefix(&prefix)
+        .is_some_and(|rest| !rest.is_empty() && !rest.contains(".."))
+}
+
+/// Whether `tag` is safe to splice into the release lookup path.
+fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag != "."
+        && tag != ".."
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+}
+
+/// Resolve `(download_url, expected_sha256)` for a request.
+async fn resolve_source(
+    client: &reqwest::Client,
+    req: &UpdateRequest,
+) -> Result<(String, String), String> {
+    let release_url = match &req.version {
+        Some(tag) if !is_valid_tag(tag) => {
+            return Err(format!("invalid release tag {:?}", tag));
+        }
+        Some(tag) => format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            RELEASE_REPO, tag
+        ),
+        None => format!(
+            "https://api.github.com/repos/{}/releases/latest",
+            RELEASE_REPO
+        ),
+    };
+    let release: serde_json::Value = client
+        .get(&release_url)
+        .header("User-Agent", "qontinui-supervisor")
+        .header("Accept", "application/vnd.github+json")
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| format!("release lookup failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("release lookup failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("release lookup returned invalid JSON: {}", e))?;
+
+    let asset_name = platform_asset_name();
+    let assets = release
+        .get("assets")
+        .and_then(|a| a.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let asset_url = |name: &str| {
+        assets
+            .iter()
+            .find(|a| a.get("name").and_then(|n| n.as_str()) == Some(name))
+            .and_then(|a| a.get("browser_download_url"))
+            .and_then(|u| u.as_str())
+            .map(|s| s.to_string())
+    };
+
+    let download_url = asset_url(&asset_name)
+        .ok_or_else(|| format!("release has no asset named {}", asset_name))?;
+    let sidecar_url = asset_url(&format!("{}.sha256", asset_name))
+        .ok_or_else(|| format!("release has no {}.sha256 sidecar", asset_name))?;
+    for url in [&download_url, &sidecar_url] {
+        if !is_release_asset_url(url) {
+            return Err(format!("refusing asset outside {}: {}", RELEASE_REPO, url));
+        }
+    }
+
+    let text = client
+        .get(&sidecar_url)
+        .header("User-Agent", "qontinui-supervisor")
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| format!("checksum download failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("checksum download failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("checksum download failed: {}", e))?;
+    let sha = parse_sha256_sidecar(&text)
+        .ok_or_else(|| "checksum sidecar is not a SHA-256 digest".to_string())?;
+
+    Ok((download_url, sha))
+}
+
+/// Replace `exe` with `bytes`, keeping the previous binary at `<exe>.old`.
+///
+/// The running exe is renamed rather than overwritten — Windows refuses to
+/// write to a mapped image but allows renaming it. If the final rename fails
+/// the backup is moved back so the supervisor is never left without a binary.
+fn swap_in_place(exe: &Path, bytes: &[u8]) -> std::io::Result<PathBuf> {
+    let staged = PathBuf::from(format!("{}.new", exe.display()));
+    let backup = PathBuf::from(format!("{}.old", exe.display()));
+
+    std::fs::write(&staged, bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // A leftover backup from a previous update is safe to drop.
+    let _ = std::fs::remove_file(&backup);
+    std::fs::rename(exe, &backup)?;
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::rename(&backup, exe);
+        let _ = std::fs::remove_file(&staged);
+        return Err(e);
+    }
+    Ok(backup)
+}
+
+/// Download, verify, and install an update. Does NOT restart — the caller
+/// does that once the response is ready.
+pub async fn download_and_install(
+    client: &reqwest::Client,
+    req: &UpdateRequest,
+) -> Result<InstalledUpdate, String> {
+    if UPDATE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Err("an update is already in progress".to_string());
+    }
+    let result = download_and_install_inner(client, req).await;
+    UPDATE_IN_PROGRESS.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn download_and_install_inner(
+    client: &reqwest::Client,
+    req: &UpdateRequest,
+) -> Result<InstalledUpdate, String> {
+    // Resolve before anything is renamed: on Linux `current_exe` follows the
+    // inode, so after the swap it would point at the `.old` backup.
+    let exe_path =
+        std::env::current_exe().map_err(|e| format!("cannot locate running exe: {}", e))?;
+
+    let (source_url, expected_sha) = resolve_source(client, req).await?;
+
+    let bytes = client
+        .get(&source_url)
+        .header("User-Agent", "qontinui-supervisor")
+        .timeout(std::time::Duration::from_secs(DOWNLOAD_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("download failed: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+
+    let sha256 = verify_sha256(&bytes, &expected_sha)?;
+
+    let backup_path = swap_in_place(&exe_path, &bytes)
+        .map_err(|e| format!("failed to swap binary at {}: {}", exe_path.display(), e))?;
+
+    Ok(InstalledUpdate {
+        source_url,
+        sha256,
+        size_bytes: bytes.len(),
+        exe_path,
+        backup_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_parses_sha256sum_format() {
+        let digest = "a".repeat(64);
+        let text = format!("{}  qontinui-supervisor-linux-x86_64\n", digest);
+        assert_eq!(parse_sha256_sidecar(&text), Some(digest));
+    }
+
+    #[test]
+    fn sidecar_rejects_non_digest() {
+        assert_eq!(parse_sha256_sidecar("not-a-hash file"), None);
+        assert_eq!(parse_sha256_sidecar(""), None);
+    }
+
+    #[test]
+    fn verify_sha256_matches_case_insensitively() {
+        // sha256("abc")
+        let expected = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        assert!(verify_sha256(b"abc", expected).is_ok());
+        assert!(verify_sha256(b"abd", expected).is_err());
+    }
+
+    #[test]
+    fn only_release_assets_of_this_repo_are_accepted() {
+        assert!(is_release_asset_url(
+            "https://github.com/qontinui/qontinui-supervisor/releases/download/v0.9.2/qontinui-supervisor-windows-x86_64.exe"
+        ));
+        assert!(!is_release_asset_url(
+            "https://github.com/someone/else/releases/download/v1/qontinui-supervisor.exe"
+        ));
+        assert!(!is_release_asset_url(
+            "http://github.com/qontinui/qontinui-supervisor/releases/download/v1/x"
+        ));
+        assert!(!is_release_asset_url(
+            "https://github.com/qontinui/qontinui-supervisor/releases/download/../../x"
+        ));
+        assert!(is_valid_tag("v0.9.2"));
+        assert!(!is_valid_tag("../../../repos/evil/x"));
+    }
+
+    #[test]
+    fn swap_keeps_backup_of_previous_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("supervisor");
+        std::fs::write(&exe, b"old").unwrap();
+
+        let backup = swap_in_place(&exe, b"new").unwrap();
+
+        assert_eq!(std::fs::read(&exe).unwrap(), b"new");
+        assert_eq!(std::fs::read(&backup).unwrap(), b"old");
+    }
+}
@@ -37,6 +37,26 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/health/stream",
         summary: "SSE stream of real-time health data",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/status/compact",
+        summary: "Fixed-shape status for editor status bars (cached, cheap to poll)",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/metrics",
+        summary: "Prometheus text exposition of runner, loop, eval and velocity metrics",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/analytics/stability",
+        summary: "Supervisor uptime, clean/unclean shutdowns and restarts per day and week",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/streams/clients",
+        summary: "Live SSE/WebSocket stream clients with sent and dropped message counts",
+    },
     EndpointEntry {
         method: "POST",
         path: "/supervisor/restart",
@@ -47,6 +67,11 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/supervisor/shutdown",
         summary: "Graceful supervisor shutdown",
     },
+    EndpointEntry {
+        method: "POST",
+        path: "/update",
+        summary: "Download a verified supervisor release and self-restart onto it",
+    },
     EndpointEntry {
         method: "GET",
         path: "/help",
@@ -291,6 +316,16 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/velocity/ingest",
         summary: "Ingest HTTP span data",
     },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity/otlp",
+        summary: "OTLP/HTTP trace export endpoint (protobuf or JSON)",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/ingest/stats",
+        summary: "Queue depth and backpressure of the OTLP span writer",
+    },
     EndpointEntry {
         method: "GET",
         path: "/velocity/summary",
@@ -306,6 +341,11 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/velocity/slow",
         summary: "Slowest requests",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/histogram",
+        summary: "Latency distribution in log-scale buckets",
+    },
     EndpointEntry {
         method: "GET",
         path: "/velocity/timeline",
@@ -314,13 +354,53 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
     EndpointEntry {
         method: "GET",
         path: "/velocity/compare",
-        summary: "Before/after comparison",
+        summary: "Before/after comparison, by time windows or around a commit",
     },
     EndpointEntry {
         method: "GET",
         path: "/velocity/trace/{request_id}",
         summary: "Detailed trace for a single request",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/trace/{request_id}/waterfall",
+        summary: "Trace as a parent/child span tree with self times",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/stream",
+        summary: "SSE live tail of ingested spans",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/spans/search",
+        summary: "Search spans by attributes, status range and error text",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/slo",
+        summary: "Per-route SLO compliance and violation history",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/velocity/slo",
+        summary: "Replace the per-route SLO definitions",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/export",
+        summary: "Export spans as CSV or Parquet",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity/sampling",
+        summary: "Sampling config of the supervisor's velocity layer",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/velocity/sampling",
+        summary: "Set velocity span sampling, duration floor and route exclusions",
+    },
     // Velocity Tests
     EndpointEntry {
         method: "POST",
@@ -347,11 +427,101 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/velocity-tests/runs/{id}",
         summary: "Get a specific run",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/runs/{id}/budget",
+        summary: "Budget compliance of a run (id or latest)",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/runs/{id}/api-report",
+        summary: "Backend API latency report of a run's API sweep (id or latest)",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/runs/{id}/screenshots/{file}",
+        summary: "PNG screenshot of a page taken after its velocity test",
+    },
     EndpointEntry {
         method: "GET",
         path: "/velocity-tests/trend",
         summary: "Performance trend across runs",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/trends",
+        summary: "Per-page metric trends with regression detection",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/baseline",
+        summary: "Pinned velocity test baseline run",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/velocity-tests/baseline",
+        summary: "Pin a completed velocity test run as baseline",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/velocity-tests/baseline",
+        summary: "Unpin the velocity test baseline",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/compare",
+        summary: "Per-page metric deltas of a run against the baseline",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/schedules",
+        summary: "List cron schedules for velocity test runs with next fire time",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-tests/schedules",
+        summary: "Create a cron schedule for velocity test runs",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/velocity-tests/schedules/{id}",
+        summary: "Delete a velocity test schedule",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-tests/schedules/{id}/enable",
+        summary: "Enable a velocity test schedule",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-tests/schedules/{id}/disable",
+        summary: "Disable a velocity test schedule",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/cases",
+        summary: "List velocity test cases",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-tests/cases",
+        summary: "Add a velocity test case",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-tests/cases/{id}",
+        summary: "Get a velocity test case",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/velocity-tests/cases/{id}",
+        summary: "Replace a velocity test case",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/velocity-tests/cases/{id}",
+        summary: "Delete a velocity test case",
+    },
     // Velocity Improvement
     EndpointEntry {
         method: "POST",
@@ -363,6 +533,26 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/velocity-improvement/stop",
         summary: "Stop running analysis",
     },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-improvement/resume",
+        summary: "Resume a loop interrupted by a supervisor restart",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-improvement/pending-fix",
+        summary: "Fix prompt waiting for approval",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-improvement/approve",
+        summary: "Apply the fix waiting for approval",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/velocity-improvement/reject",
+        summary: "Reject the fix waiting for approval and end the loop",
+    },
     EndpointEntry {
         method: "GET",
         path: "/velocity-improvement/status",
@@ -373,6 +563,21 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/velocity-improvement/history",
         summary: "Past improvement results",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-improvement/stream",
+        summary: "SSE stream of improvement loop progress",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-improvement/loops",
+        summary: "Stored improvement loops with their iterations",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/velocity-improvement/iterations/{n}/diff",
+        summary: "Patch the fix agent made in an iteration",
+    },
     // Evaluation
     EndpointEntry {
         method: "POST",
@@ -389,6 +594,11 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/eval/status",
         summary: "Current evaluation status",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/stream",
+        summary: "SSE stream of per-prompt eval progress",
+    },
     EndpointEntry {
         method: "POST",
         path: "/eval/continuous/start",
@@ -399,6 +609,21 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/eval/continuous/stop",
         summary: "Stop continuous evaluation",
     },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/matrix/start",
+        summary: "Generate the same prompts once per provider/model pair",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/leaderboard",
+        summary: "Rank a model matrix's provider/model pairs by score",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/eval/restart-policy",
+        summary: "Set how watchdog runner restarts treat an in-flight eval run",
+    },
     EndpointEntry {
         method: "GET",
         path: "/eval/runs",
@@ -407,7 +632,107 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
     EndpointEntry {
         method: "GET",
         path: "/eval/runs/{id}",
-        summary: "Get a specific evaluation run",
+        summary: "Get a specific evaluation run (results paged/filtered by query params)",
+    },
+    EndpointEntry {
+        method: "PATCH",
+        path: "/eval/results/{id}/scores",
+        summary: "Override a result's judge scores, keeping the originals for audit",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/runs/{id}/retry-failures",
+        summary: "Re-run only the failed prompts of a run and merge successes back",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/prune",
+        summary: "Delete old eval runs per the retention policy",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/runs/{id}/compare/{baseline_id}",
+        summary: "Compare two runs per prompt, with noise-aware significance",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/runs/{id}/export",
+        summary: "Download a run's results as CSV or JSON Lines",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/runs/{id}/rescore",
+        summary: "Re-judge a run's stored workflows with another judge prompt version",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/runs/{id}/rescore-sample",
+        summary: "Re-score a random sample of a run's results with a second judge",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/runs/{id}/agreement",
+        summary: "Agreement between a run's judge and a rescore's judge",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/runs/{id}/determinism",
+        summary: "Per-prompt score variance and structural diff rate of a sampled run",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/runs/{id}/resume",
+        summary: "Continue an interrupted or aborted run from its missing prompts",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/judge-prompts",
+        summary: "List versioned judge prompt templates",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/judge-prompts",
+        summary: "Store a new judge prompt template version",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/judge-prompts/{version}",
+        summary: "Get one judge prompt template version",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/eval/judge-prompts/{version}",
+        summary: "Delete an inactive, unused judge prompt version",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/judge-prompts/{version}/activate",
+        summary: "Score new runs with this judge prompt version",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/baseline",
+        summary: "Get the pinned baseline run and regression alert thresholds",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/eval/baseline",
+        summary: "Pin a completed run as the regression baseline",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/eval/baseline",
+        summary: "Unpin the eval baseline",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/trends",
+        summary: "Per-category eval score trends across recent runs",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/search",
+        summary: "Full-text search over judge rationales and eval errors",
     },
     EndpointEntry {
         method: "GET",
@@ -429,6 +754,96 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/eval/test-suite/{id}",
         summary: "Delete a test prompt",
     },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/test-suite/{id}/status",
+        summary: "Move a test prompt between draft, active, and deprecated",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/eval/test-suite/{id}/needs-review",
+        summary: "Dismiss a test prompt's needs-review flag",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/test-suite/{id}/mutations",
+        summary: "Generate reworded variants of a test prompt for robustness testing",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/jobs/queue",
+        summary: "Start requests queued with ?queue=true, in dispatch order",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/jobs/queue/{id}",
+        summary: "Queued job with its current position",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/jobs/queue/{id}",
+        summary: "Cancel a queued job before it launches",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/prompts/export",
+        summary: "Export test prompts and suites as a JSON bundle",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/prompts/import",
+        summary: "Import a prompt bundle (?on_conflict=skip|overwrite|rename)",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/prompts/sync",
+        summary: "Sync test prompts from the prompts directory (?dry_run=true)",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/suites",
+        summary: "List prompt suites",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/suites",
+        summary: "Create a prompt suite",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/eval/suites/{name}",
+        summary: "Update a prompt suite",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/eval/suites/{name}",
+        summary: "Delete a prompt suite",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/eval/schedules",
+        summary: "List cron schedules for eval runs with next fire time",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/schedules",
+        summary: "Create a cron schedule for eval runs",
+    },
+    EndpointEntry {
+        method: "DELETE",
+        path: "/eval/schedules/{id}",
+        summary: "Delete an eval schedule",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/schedules/{id}/enable",
+        summary: "Enable an eval schedule",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/eval/schedules/{id}/disable",
+        summary: "Disable an eval schedule",
+    },
     // AI Provider/Model Config
     EndpointEntry {
         method: "GET",
@@ -588,6 +1003,21 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/diagnostics/clear",
         summary: "Clear diagnostic events",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/internal/profile",
+        summary: "Supervisor self-profile: RSS, runtime tasks, per-store memory estimates",
+    },
+    EndpointEntry {
+        method: "GET",
+        path: "/processes/{name}/resources",
+        summary: "Recent CPU/memory samples of a runner, Expo or the fix agent",
+    },
+    EndpointEntry {
+        method: "POST",
+        path: "/processes/cleanup",
+        summary: "List (or with confirm, terminate) stale processes holding managed ports",
+    },
     // Test login
     EndpointEntry {
         method: "GET",
@@ -637,6 +1067,16 @@ pub static ENDPOINT_MANIFEST: &[EndpointEntry] = &[
         path: "/runner/watchdog",
         summary: "Control watchdog (legacy single-runner endpoint)",
     },
+    EndpointEntry {
+        method: "GET",
+        path: "/watchdog/restart-policy",
+        summary: "Crash-only watchdog backoff and crash-loop policy",
+    },
+    EndpointEntry {
+        method: "PUT",
+        path: "/watchdog/restart-policy",
+        summary: "Update and persist the watchdog restart policy",
+    },
     EndpointEntry {
         method: "POST",
         path: "/runner/fix-and-rebuild",
@@ -709,6 +1149,11 @@ pub fn build_router(state: SharedState) -> Router {
     let eval_state = state.clone();
     let vt_state = state.clone();
     let vi_state = state.clone();
+    let velocity_state = state.clone();
+    // Built before the eval router: opening the eval DB marks `running` runs
+    // interrupted, which must not race a run the eval router resumes.
+    let metrics_routes =
+        crate::routes::metrics::metrics_routes(dev_logs_dir.clone(), state.clone());
     let spa_state = state.clone();
     // Debug-only endpoints (gated by QONTINUI_SUPERVISOR_DEBUG_ENDPOINTS=1).
     // Always merged into the router; the gate is enforced inside each
@@ -725,6 +1170,18 @@ pub fn build_router(state: SharedState) -> Router {
         // Health
         .route("/health", get(crate::routes::health::health))
         .route("/health/stream", get(crate::routes::health::health_stream))
+        .route(
+            "/status/compact",
+            get(crate::routes::health::compact_status),
+        )
+        .route(
+            "/analytics/stability",
+            get(crate::routes::health::stability_report),
+        )
+        .route(
+            "/streams/clients",
+            get(crate::routes::health::stream_clients),
+        )
         // LKG coverage helper for agents — see routes/lkg_coverage.rs.
         // Single-call collapse of the manual "is my fix in the LKG?" rule
         // documented under "Last-known-good (LKG) fallback for agents" in
@@ -743,6 +1200,11 @@ pub fn build_router(state: SharedState) -> Router {
             "/runner/watchdog",
             post(crate::routes::runner::control_watchdog),
         )
+        .route(
+            "/watchdog/restart-policy",
+            get(crate::routes::runner::get_restart_policy)
+                .put(crate::routes::runner::set_restart_policy),
+        )
         .route(
             "/runner/fix-and-rebuild",
             post(crate::routes::runner::fix_and_rebuild),
@@ -768,6 +1230,8 @@ pub fn build_router(state: SharedState) -> Router {
             "/supervisor/shutdown",
             post(crate::routes::runner::supervisor_shutdown),
         )
+        // Supervisor self-update (download + verify + swap, then self-restart)
+        .route("/update", post(crate::routes::runner::supervisor_update))
         // AI provider/model management
         .route("/ai/provider", get(crate::routes::ai::get_provider))
         .route("/ai/provider", post(crate::routes::ai::set_provider))
@@ -889,6 +1353,18 @@ pub fn build_router(state: SharedState) -> Router {
             "/diagnostics/clear",
             post(crate::routes::diagnostics::clear_diagnostics),
         )
+        .route(
+            "/internal/profile",
+            get(crate::routes::diagnostics::get_profile),
+        )
+        .route(
+            "/processes/{name}/resources",
+            get(crate::routes::processes::get_resources),
+        )
+        .route(
+            "/processes/cleanup",
+            post(crate::routes::processes::cleanup),
+        )
         // Dev-action snapshots (Phase 1 of the dev-event cause-effect ledger).
         // `GET /actions/{id}/outcome` is the one-call restart-archeology
         // replacement; `GET /actions` is a cheap recent list. axum 0.8
@@ -913,6 +1389,11 @@ pub fn build_router(state: SharedState) -> Router {
         .route("/expo/stop", post(crate::routes::expo::stop))
         .route("/expo/status", get(crate::routes::expo::status))
         .route("/expo/logs/stream", get(crate::routes::expo::logs_stream))
+        .route("/jobs/queue", get(crate::routes::jobs::list))
+        .route(
+            "/jobs/queue/{id}",
+            get(crate::routes::jobs::get_job).delete(crate::routes::jobs::cancel),
+        )
         // Multi-runner management
         .route("/runners", get(crate::routes::runners::list_runners))
         .route("/runners", post(crate::routes::runners::add_runner))
@@ -1054,11 +1535,13 @@ pub fn build_router(state: SharedState) -> Router {
     main_routes
         .merge(crate::routes::velocity::velocity_routes(
             dev_logs_dir.clone(),
+            velocity_state,
         ))
         .merge(crate::routes::evaluation::eval_routes(
             dev_logs_dir.clone(),
             eval_state,
         ))
+        .merge(metrics_routes)
         .merge(crate::routes::velocity_tests::velocity_test_routes(
             dev_logs_dir.clone(),
             vt_state,
@@ -4,6 +4,9 @@ use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
 use crate::config::{RunnerConfig, SupervisorConfig};
+use crate::process::restart_policy::RestartPolicy;
+use crate::velocity::slo::SloDefinition;
+use crate::velocity_layer::VelocitySampling;
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct PersistentSettings {
@@ -13,6 +16,15 @@ pub struct PersistentSettings {
     /// Multi-runner configurations. Empty means use default single primary runner.
     #[serde(default)]
     pub runners: Vec<RunnerConfig>,
+    /// Per-route latency/error-rate objectives evaluated over velocity spans.
+    #[serde(default)]
+    pub velocity_slos: Vec<SloDefinition>,
+    /// Sampling and route exclusions for the supervisor's own velocity spans.
+    #[serde(default)]
+    pub velocity_sampling: VelocitySampling,
+    /// Backoff and crash-loop limits of the crash-only watchdog.
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
 }
 
 /// Basename a legacy flat settings file is migrate-claimed by. The flat
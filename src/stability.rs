@@ -0,0 +1,333 @@
+//! Persistent supervisor uptime and restart history.
+//!
+//! Every supervisor process records a session row in `stability.db` under the
+//! dev-logs dir: start time, a heartbeat refreshed every
+//! [`HEARTBEAT_INTERVAL_SECS`], and how it ended. A graceful shutdown closes
+//! the session as clean; a session still open when the next supervisor starts
+//! was killed or crashed and is closed as unclean at its last heartbeat.
+//! Child-process (runner) restarts are logged alongside. `GET
+//! /analytics/stability` rolls both up per UTC day and per ISO week.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 60;
+
+/// Default window for `GET /analytics/stability`.
+pub const DEFAULT_WINDOW_DAYS: u32 = 56;
+
+pub struct StabilityDb {
+    conn: Mutex<Connection>,
+    /// ID of this process's session row.
+    session_id: String,
+    started_at: DateTime<Utc>,
+}
+
+/// One supervisor process lifetime.
+#[derive(Debug, Clone)]
+pub struct SessionRow {
+    pub started_at: String,
+    pub last_seen_at: String,
+    pub ended_at: Option<String>,
+    /// `None` while the session is still running.
+    pub clean: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StabilityBucket {
+    /// `YYYY-MM-DD` for days, `YYYY-Www` for ISO weeks.
+    pub period: String,
+    pub supervisor_starts: usize,
+    pub clean_shutdowns: usize,
+    pub unclean_shutdowns: usize,
+    pub child_restarts: usize,
+    /// Supervisor uptime of sessions started in this period.
+    pub uptime_secs: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StabilityReport {
+    pub session_id: String,
+    pub started_at: String,
+    pub uptime_secs: f64,
+    pub window_days: u32,
+    pub days: Vec<StabilityBucket>,
+    pub weeks: Vec<StabilityBucket>,
+}
+
+impl StabilityDb {
+    pub fn open(dev_logs_dir: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open(dev_logs_dir.join("stability.db"))?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS supervisor_sessions (
+                id TEXT PRIMARY KEY,
+                pid INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                last_seen_at TEXT NOT NULL,
+                ended_at TEXT,
+                clean INTEGER
+            );
+
+            CREATE TABLE IF NOT EXISTS child_restarts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                runner_id TEXT NOT NULL,
+                source TEXT NOT NULL,
+                occurred_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_ss_started ON supervisor_sessions(started_at);
+            CREATE INDEX IF NOT EXISTS idx_cr_occurred ON child_restarts(occurred_at);
+            ",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            started_at: Utc::now(),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Close sessions left open by a previous process as unclean, then open
+    /// this process's session. Returns how many were closed as unclean.
+    pub fn begin_session(&self) -> anyhow::Result<usize> {
+        let conn = self.conn();
+        let unclean = conn.execute(
+            "UPDATE supervisor_sessions SET ended_at = last_seen_at, clean = 0
+             WHERE ended_at IS NULL AND id != ?1",
+            params![self.session_id],
+        )?;
+        let now = self.started_at.to_rfc3339();
+        conn.execute(
+            "INSERT OR IGNORE INTO supervisor_sessions (id, pid, started_at, last_seen_at)
+             VALUES (?1, ?2, ?3, ?3)",
+            params![self.session_id, std::process::id(), now],
+        )?;
+        Ok(unclean)
+    }
+
+    pub fn heartbeat(&self) -> anyhow::Result<()> {
+        self.conn().execute(
+            "UPDATE supervisor_sessions SET last_seen_at = ?2 WHERE id = ?1",
+            params![self.session_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Mark this process's session as cleanly shut down.
+    pub fn end_session(&self) -> anyhow::Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn().execute(
+            "UPDATE supervisor_sessions SET last_seen_at = ?2, ended_at = ?2, clean = 1
+             WHERE id = ?1",
+            params![self.session_id, now],
+        )?;
+        Ok(())
+    }
+
+    /// All-time `(runner_id, source, count)` of logged child restarts.
+    pub fn restart_counts(&self) -> anyhow::Result<Vec<(String, String, u64)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT runner_id, source, COUNT(*) FROM child_restarts
+             GROUP BY runner_id, source ORDER BY runner_id, source",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn record_restart(&self, runner_id: &str, source: &str) -> anyhow::Result<()> {
+        self.conn().execute(
+            "INSERT INTO child_restarts (runner_id, source, occurred_at) VALUES (?1, ?2, ?3)",
+            params![runner_id, source, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn report(&self, window_days: u32) -> anyhow::Result<StabilityReport> {
+        let since = (Utc::now() - chrono::Duration::days(window_days as i64)).to_rfc3339();
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT started_at, last_seen_at, ended_at, clean FROM supervisor_sessions
+             WHERE COALESCE(ended_at, last_seen_at) >= ?1 ORDER BY started_at",
+        )?;
+        let sessions = stmt
+            .query_map(params![since], |row| {
+                Ok(SessionRow {
+                    started_at: row.get(0)?,
+                    last_seen_at: row.get(1)?,
+                    ended_at: row.get(2)?,
+                    clean: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = conn.prepare(
+            "SELECT occurred_at FROM child_restarts WHERE occurred_at >= ?1 ORDER BY occurred_at",
+        )?;
+        let restarts = stmt
+            .query_map(params![since], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let days = daily(&sessions, &restarts);
+        Ok(StabilityReport {
+            session_id: self.session_id.clone(),
+            started_at: self.started_at.to_rfc3339(),
+            uptime_secs: (Utc::now() - self.started_at).num_milliseconds() as f64 / 1000.0,
+            window_days,
+            weeks: weekly(&days),
+            days,
+        })
+    }
+}
+
+fn day_of(ts: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc).date_naive())
+}
+
+fn bucket(days: &mut BTreeMap<NaiveDate, StabilityBucket>, day: NaiveDate) -> &mut StabilityBucket {
+    days.entry(day).or_insert_with(|| StabilityBucket {
+        period: day.format("%Y-%m-%d").to_string(),
+        ..Default::default()
+    })
+}
+
+/// Per-day buckets, oldest first. Starts and uptime count on the day a
+/// session started, shutdowns on the day it ended.
+pub fn daily(sessions: &[SessionRow], restarts: &[String]) -> Vec<StabilityBucket> {
+    let mut days = BTreeMap::new();
+    for s in sessions {
+        let Ok(started) = DateTime::parse_from_rfc3339(&s.started_at) else {
+            continue;
+        };
+        let end_ts = s.ended_at.as_deref().unwrap_or(&s.last_seen_at);
+        let uptime = DateTime::parse_from_rfc3339(end_ts)
+            .map(|end| (end - started).num_milliseconds().max(0) as f64 / 1000.0)
+            .unwrap_or(0.0);
+        let b = bucket(&mut days, started.with_timezone(&Utc).date_naive());
+        b.supervisor_starts += 1;
+        b.uptime_secs += uptime;
+        if let (Some(clean), Some(end)) = (s.clean, s.ended_at.as_deref().and_then(day_of)) {
+            let b = bucket(&mut days, end);
+            if clean {
+                b.clean_shutdowns += 1;
+            } else {
+                b.unclean_shutdowns += 1;
+            }
+        }
+    }
+    for day in restarts.iter().filter_map(|t| day_of(t)) {
+        bucket(&mut days, day).child_restarts += 1;
+    }
+    days.into_values().collect()
+}
+
+/// Roll daily buckets up into ISO weeks (`YYYY-Www`), oldest first.
+pub fn weekly(days: &[StabilityBucket]) -> Vec<StabilityBucket> {
+    let mut weeks: BTreeMap<String, StabilityBucket> = BTreeMap::new();
+    for d in days {
+        let Ok(date) = NaiveDate::parse_from_str(&d.period, "%Y-%m-%d") else {
+            continue;
+        };
+        let iso = date.iso_week();
+        let period = format!("{}-W{:02}", iso.year(), iso.week());
+        let w = weeks
+            .entry(period.clone())
+            .or_insert_with(|| StabilityBucket {
+                period,
+                ..Default::default()
+            });
+        w.supervisor_starts += d.supervisor_starts;
+        w.clean_shutdowns += d.clean_shutdowns;
+        w.unclean_shutdowns += d.unclean_shutdowns;
+        w.child_restarts += d.child_restarts;
+        w.uptime_secs += d.uptime_secs;
+    }
+    weeks.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(started: &str, ended: Option<&str>, clean: Option<bool>) -> SessionRow {
+        SessionRow {
+            started_at: started.to_string(),
+            last_seen_at: ended.unwrap_or(started).to_string(),
+            ended_at: ended.map(str::to_string),
+            clean,
+        }
+    }
+
+    #[test]
+    fn daily_attributes_starts_and_shutdowns_to_their_days() {
+        let sessions = [
+            session(
+                "2026-03-09T22:00:00+00:00",
+                Some("2026-03-10T01:00:00+00:00"),
+                Some(false),
+            ),
+            session(
+                "2026-03-10T08:00:00+00:00",
+                Some("2026-03-10T09:00:00+00:00"),
+                Some(true),
+            ),
+        ];
+        let restarts = ["2026-03-10T08:30:00+00:00".to_string()];
+        let days = daily(&sessions, &restarts);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].period, "2026-03-09");
+        assert_eq!(days[0].supervisor_starts, 1);
+        assert_eq!(days[0].uptime_secs, 3.0 * 3600.0);
+        assert_eq!(days[1].supervisor_starts, 1);
+        assert_eq!(days[1].clean_shutdowns, 1);
+        assert_eq!(days[1].unclean_shutdowns, 1);
+        assert_eq!(days[1].child_restarts, 1);
+    }
+
+    #[test]
+    fn weekly_groups_by_iso_week() {
+        let day = |period: &str, restarts| StabilityBucket {
+            period: period.to_string(),
+            child_restarts: restarts,
+            ..Default::default()
+        };
+        // 2026-03-15 is a Sunday, 2026-03-16 a Monday
+        let weeks = weekly(&[
+            day("2026-03-14", 1),
+            day("2026-03-15", 2),
+            day("2026-03-16", 4),
+        ]);
+        let summary: Vec<(&str, usize)> = weeks
+            .iter()
+            .map(|w| (w.period.as_str(), w.child_restarts))
+            .collect();
+        assert_eq!(summary, vec![("2026-W11", 3), ("2026-W12", 4)]);
+    }
+
+    #[test]
+    fn open_sessions_are_closed_unclean_on_next_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = StabilityDb::open(dir.path()).unwrap();
+        first.begin_session().unwrap();
+        drop(first);
+        let second = StabilityDb::open(dir.path()).unwrap();
+        assert_eq!(second.begin_session().unwrap(), 1);
+        second.record_restart("primary", "watchdog").unwrap();
+        second.end_session().unwrap();
+        let report = second.report(DEFAULT_WINDOW_DAYS).unwrap();
+        let total = |f: fn(&StabilityBucket) -> usize| report.days.iter().map(f).sum::<usize>();
+        assert_eq!(total(|d| d.supervisor_starts), 2);
+        assert_eq!(total(|d| d.unclean_shutdowns), 1);
+        assert_eq!(total(|d| d.clean_shutdowns), 1);
+        assert_eq!(total(|d| d.child_restarts), 1);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::build_submissions::BuildSubmissionStore;
 use crate::ci_runner_probe::CiRunnerState;
 use crate::config::{RunnerConfig, SupervisorConfig};
-use crate::diagnostics::DiagnosticsState;
+use crate::diagnostics::{DiagnosticsState, RestartSource};
+use crate::evaluation::progress::{self as eval_progress, EvalProgressEvent};
+use crate::evaluation::RunnerRestartPolicy;
 use crate::health_cache::{CachedPortHealth, CachedRunnerHealth};
 use crate::log_capture::{LogLevel, LogSource, LogState};
 use crate::process::job::RunnerJob;
@@ -228,6 +230,9 @@ pub struct SupervisorState {
     pub evaluation: RwLock<EvaluationState>,
     pub velocity_tests: RwLock<VelocityTestState>,
     pub velocity_improvement: RwLock<VelocityImprovementState>,
+    /// Start requests parked with `?queue=true` while their subsystem was
+    /// busy. Drained by per-subsystem dispatchers in the route modules.
+    pub job_queue: RwLock<crate::job_queue::JobQueue>,
     pub command_relay: Arc<CommandRelay>,
     pub logs: LogState,
     pub health_tx: broadcast::Sender<()>,
@@ -291,6 +296,9 @@ pub struct SupervisorState {
     /// `process::job`'s cross-platform shim). Spawning continues either
     /// way — without the safety net, but functional.
     pub runner_job: Option<Arc<RunnerJob>>,
+    /// Persistent session/restart history behind `GET /analytics/stability`.
+    /// `None` if `stability.db` could not be opened; recording is best-effort.
+    pub stability: Option<Arc<crate::stability::StabilityDb>>,
     /// Log messages captured during synchronous `SupervisorState::new`
     /// construction that need to be routed through `state.logs.emit` once
     /// async context is available. The `logs` field is initialized inside the
@@ -311,6 +319,9 @@ pub struct SupervisorState {
     /// verify the graceful-shutdown drain is actually releasing connections
     /// without having to open a stream + trigger shutdown by hand.
     pub active_sse_connections: Arc<AtomicUsize>,
+    /// Per-connection queues and drop counters for the log SSE streams and
+    /// the health WebSocket. Listed by `GET /streams/clients`.
+    pub stream_clients: Arc<crate::stream_clients::StreamClients>,
     /// True when debug-only HTTP endpoints (under `/control/dev/*`) are
     /// admitted. Cached at startup from
     /// `QONTINUI_SUPERVISOR_DEBUG_ENDPOINTS=1` so handlers don't re-read the
@@ -392,6 +403,12 @@ pub struct SupervisorState {
     /// `None` until the first refresh completes. Each snapshot carries its own
     /// `computed_at` so readers can judge staleness.
     pub footprint: RwLock<Option<crate::footprint::FootprintSnapshot>>,
+    /// Recent CPU/memory samples of runners, Expo and the fix agent, for
+    /// `GET /processes/{name}/resources` (see `process::resources`).
+    pub process_resources: RwLock<crate::process::resources::ResourceHistory>,
+    /// Crash-only watchdog backoff, loaded from settings at startup and
+    /// replaced by `PUT /watchdog/restart-policy`.
+    pub restart_policy: RwLock<crate::process::restart_policy::RestartPolicy>,
 }
 
 /// RAII guard that increments [`SupervisorState::active_sse_connections`]
@@ -425,6 +442,9 @@ pub struct RunnerState {
     pub started_at: Option<DateTime<Utc>>,
     pub restart_requested: bool,
     pub stop_requested: bool,
+    /// Set while `start_managed_runner` is spawning the process, before
+    /// `running` is.
+    pub starting: bool,
     pub pid: Option<u32>,
 }
 
@@ -1067,6 +1087,16 @@ impl SupervisorState {
             }
         };
 
+        let stability = match crate::stability::StabilityDb::open(&config.dev_logs_dir) {
+            Ok(db) => Some(Arc::new(db)),
+            Err(e) => {
+                let msg = format!("Failed to open stability DB: {}", e);
+                tracing::warn!("{}", msg);
+                startup_logs.push((LogLevel::Warn, msg));
+                None
+            }
+        };
+
         Self {
             config,
             runners: RwLock::new(runners_map),
@@ -1084,6 +1114,7 @@ impl SupervisorState {
             evaluation: RwLock::new(EvaluationState::new()),
             velocity_tests: RwLock::new(VelocityTestState::new()),
             velocity_improvement: RwLock::new(VelocityImprovementState::new()),
+            job_queue: RwLock::new(crate::job_queue::JobQueue::new()),
             command_relay: CommandRelay::new(),
             logs: LogState::new(),
             health_tx,
@@ -1098,8 +1129,10 @@ impl SupervisorState {
             boot_id: load_or_create_boot_id(),
             build_id: compute_build_id(),
             runner_job,
+            stability,
             pending_startup_logs: std::sync::Mutex::new(startup_logs),
             active_sse_connections: Arc::new(AtomicUsize::new(0)),
+            stream_clients: Arc::new(crate::stream_clients::StreamClients::new()),
             debug_endpoints_enabled,
             supervisor_started_at: std::time::SystemTime::now(),
             synthetic_build_id_tx,
@@ -1108,6 +1141,8 @@ impl SupervisorState {
             active_spawn_worktrees: std::sync::Mutex::new(std::collections::HashSet::new()),
             spawn_container_locks: std::sync::Mutex::new(std::collections::HashMap::new()),
             footprint: RwLock::new(None),
+            process_resources: RwLock::new(Default::default()),
+            restart_policy: RwLock::new(Default::default()),
         }
     }
 
@@ -1224,6 +1259,15 @@ impl SupervisorState {
     }
 
     /// Get the primary runner.
+    /// Log a completed child-process restart to the stability history.
+    pub fn record_child_restart(&self, runner_id: &str, source: &RestartSource) {
+        if let Some(db) = &self.stability {
+            if let Err(e) = db.record_restart(runner_id, source.as_str()) {
+                tracing::warn!("Failed to record restart of {}: {}", runner_id, e);
+            }
+        }
+    }
+
     pub async fn get_primary(&self) -> Option<Arc<ManagedRunner>> {
         let runners = self.runners.read().await;
         runners
@@ -1253,6 +1297,7 @@ impl RunnerState {
             started_at: None,
             restart_requested: false,
             stop_requested: false,
+            starting: false,
             pid: None,
         }
     }
@@ -1318,9 +1363,29 @@ pub struct EvaluationState {
     pub current_prompt_index: usize,
     pub total_prompts: usize,
     pub stop_tx: Option<watch::Sender<bool>>,
+    /// How a watchdog restart of the primary runner treats an in-flight run.
+    /// Set via `PUT /eval/restart-policy`.
+    pub runner_restart_policy: RunnerRestartPolicy,
+    /// Set by the watchdog before it stops a run under
+    /// [`RunnerRestartPolicy::Abort`]; consumed by the engine so the run is
+    /// recorded as `aborted` with this reason instead of `cancelled`.
+    pub abort_reason: Option<String>,
+    /// Overall score of the latest completed (non-retry) run. Seeded from
+    /// the eval DB at startup so `GET /status/compact` never queries it.
+    pub last_completed_score: Option<f64>,
+    /// Set while a model matrix is running, so nothing else starts in the
+    /// gap between two of its runs.
+    pub matrix_id: Option<String>,
+    /// Per-prompt progress of the current run, for `GET /eval/stream`.
+    pub progress_tx: broadcast::Sender<EvalProgressEvent>,
 }
 
 impl EvaluationState {
+    /// Whether a run, continuous loop or model matrix owns the engine.
+    pub fn busy(&self) -> bool {
+        self.running || self.continuous_mode || self.matrix_id.is_some()
+    }
+
     pub fn new() -> Self {
         Self {
             running: false,
@@ -1330,6 +1395,11 @@ impl EvaluationState {
             current_prompt_index: 0,
             total_prompts: 0,
             stop_tx: None,
+            runner_restart_policy: RunnerRestartPolicy::default(),
+            abort_reason: None,
+            last_completed_score: None,
+            matrix_id: None,
+            progress_tx: eval_progress::channel(),
         }
     }
 }
@@ -1346,6 +1416,9 @@ pub struct VelocityTestState {
     pub current_test_index: usize,
     pub total_tests: usize,
     pub stop_tx: Option<watch::Sender<bool>>,
+    /// Overall score of the latest completed run; seeded at startup like
+    /// [`EvaluationState::last_completed_score`].
+    pub last_completed_score: Option<f64>,
 }
 
 impl VelocityTestState {
@@ -1356,6 +1429,7 @@ impl VelocityTestState {
             current_test_index: 0,
             total_tests: 0,
             stop_tx: None,
+            last_completed_score: None,
         }
     }
 }
@@ -1501,6 +1575,10 @@ mod tests {
         assert_eq!(state.current_prompt_index, 0);
         assert_eq!(state.total_prompts, 0);
         assert!(state.stop_tx.is_none());
+        assert_eq!(state.runner_restart_policy, RunnerRestartPolicy::Abort);
+        assert!(state.abort_reason.is_none());
+        assert!(state.matrix_id.is_none());
+        assert!(!state.busy());
     }
 
     #[test]
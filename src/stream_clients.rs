@@ -0,0 +1,267 @@
+//! Per-connection backpressure and drop accounting for streaming endpoints.
+//!
+//! The log SSE streams and the health WebSocket fan out from tokio broadcast
+//! channels, which silently skip messages for a receiver that falls behind
+//! (`RecvError::Lagged`). Connections registered here instead read from their
+//! own bounded queue ([`CLIENT_QUEUE_CAPACITY`]) fed by a forwarding task.
+//! Every message lost to broadcast lag or a full queue is counted against the
+//! connection. A client that keeps falling behind — [`SLOW_CLIENT_MAX_LAGS`]
+//! separate lag episodes within [`SLOW_CLIENT_WINDOW`] — is disconnected and
+//! recorded as a `slow_client_disconnected` diagnostics event.
+//! `GET /streams/clients` lists live connections with their counters.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::diagnostics::DiagnosticEventKind;
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::SharedState;
+
+/// Messages buffered per connection before new ones are dropped.
+pub const CLIENT_QUEUE_CAPACITY: usize = 256;
+
+/// Lag episodes within [`SLOW_CLIENT_WINDOW`] that get a client disconnected.
+pub const SLOW_CLIENT_MAX_LAGS: usize = 3;
+
+pub const SLOW_CLIENT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Registry of live streaming connections.
+#[derive(Default)]
+pub struct StreamClients {
+    next_id: AtomicU64,
+    clients: Mutex<HashMap<u64, Arc<ClientStats>>>,
+}
+
+struct ClientStats {
+    endpoint: &'static str,
+    connected_at: DateTime<Utc>,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    lag_episodes: Mutex<VecDeque<Instant>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamClientInfo {
+    pub id: u64,
+    pub endpoint: &'static str,
+    pub connected_at: DateTime<Utc>,
+    pub sent: u64,
+    pub dropped: u64,
+    /// Lag episodes inside the current [`SLOW_CLIENT_WINDOW`].
+    pub recent_lag_episodes: usize,
+}
+
+impl StreamClients {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a connection. It is listed until the returned handle drops.
+    pub fn register(self: &Arc<Self>, endpoint: &'static str) -> StreamClient {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let stats = Arc::new(ClientStats {
+            endpoint,
+            connected_at: Utc::now(),
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            lag_episodes: Mutex::new(VecDeque::new()),
+        });
+        self.lock().insert(id, stats.clone());
+        StreamClient {
+            id,
+            stats,
+            registry: self.clone(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<StreamClientInfo> {
+        let now = Instant::now();
+        let mut clients: Vec<StreamClientInfo> = self
+            .lock()
+            .iter()
+            .map(|(id, s)| StreamClientInfo {
+                id: *id,
+                endpoint: s.endpoint,
+                connected_at: s.connected_at,
+                sent: s.sent.load(Ordering::Relaxed),
+                dropped: s.dropped.load(Ordering::Relaxed),
+                recent_lag_episodes: s
+                    .lag_episodes
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .iter()
+                    .filter(|t| now.duration_since(**t) < SLOW_CLIENT_WINDOW)
+                    .count(),
+            })
+            .collect();
+        clients.sort_by_key(|c| c.id);
+        clients
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Arc<ClientStats>>> {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// One registered connection. Deregisters on drop.
+pub struct StreamClient {
+    id: u64,
+    stats: Arc<ClientStats>,
+    registry: Arc<StreamClients>,
+}
+
+impl StreamClient {
+    pub fn record_sent(&self) {
+        self.stats.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count `n` dropped messages. `new_episode` marks the first drop after
+    /// a successful delivery, so a single burst counts as one episode.
+    /// Returns true once the client should be disconnected.
+    pub fn record_dropped(&self, n: u64, new_episode: bool) -> bool {
+        self.record_dropped_at(n, new_episode, Instant::now())
+    }
+
+    fn record_dropped_at(&self, n: u64, new_episode: bool, now: Instant) -> bool {
+        self.stats.dropped.fetch_add(n, Ordering::Relaxed);
+        let mut episodes = self
+            .stats
+            .lag_episodes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if new_episode {
+            episodes.push_back(now);
+        }
+        while episodes
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= SLOW_CLIENT_WINDOW)
+        {
+            episodes.pop_front();
+        }
+        episodes.len() >= SLOW_CLIENT_MAX_LAGS
+    }
+
+    /// Log and record the disconnect of a client that couldn't keep up.
+    async fn report_slow(&self, state: &SharedState) {
+        let dropped = self.stats.dropped.load(Ordering::Relaxed);
+        let msg = format!(
+            "Disconnected slow {} client #{} ({} message(s) dropped)",
+            self.stats.endpoint, self.id, dropped
+        );
+        tracing::warn!("{}", msg);
+        state
+            .logs
+            .emit(LogSource::Supervisor, LogLevel::Warn, msg)
+            .await;
+        state
+            .diagnostics
+            .write()
+            .await
+            .emit(DiagnosticEventKind::SlowClientDisconnected {
+                endpoint: self.stats.endpoint.to_string(),
+                client_id: self.id,
+                dropped,
+            });
+    }
+}
+
+impl Drop for StreamClient {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
+
+/// Feed `rx` into a bounded per-client queue. The queue closes (ending the
+/// consumer's stream) when the broadcast channel closes or the client is
+/// disconnected as too slow; the forwarding task exits once the consumer
+/// drops its receiver.
+pub fn forward<T: Clone + Send + 'static>(
+    state: SharedState,
+    mut rx: broadcast::Receiver<T>,
+    client: StreamClient,
+) -> mpsc::Receiver<T> {
+    let (tx, out) = mpsc::channel(CLIENT_QUEUE_CAPACITY);
+    tokio::spawn(async move {
+        let mut lagging = false;
+        loop {
+            let dropped = tokio::select! {
+                _ = tx.closed() => break,
+                msg = rx.recv() => match msg {
+                    Ok(msg) => match tx.try_send(msg) {
+                        Ok(()) => {
+                            client.record_sent();
+                            lagging = false;
+                            continue;
+                        }
+                        Err(TrySendError::Full(_)) => 1,
+                        Err(TrySendError::Closed(_)) => break,
+                    },
+                    Err(RecvError::Lagged(n)) => n,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            let new_episode = !lagging;
+            lagging = true;
+            if client.record_dropped(dropped, new_episode) {
+                client.report_slow(&state).await;
+                break;
+            }
+        }
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clients_deregister_on_drop() {
+        let registry = Arc::new(StreamClients::new());
+        let a = registry.register("/logs/stream");
+        let b = registry.register("/ws");
+        a.record_sent();
+        assert_eq!(registry.snapshot().len(), 2);
+        assert_eq!(registry.snapshot()[0].sent, 1);
+        drop(a);
+        let left = registry.snapshot();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].endpoint, "/ws");
+        drop(b);
+        assert!(registry.snapshot().is_empty());
+    }
+
+    #[test]
+    fn repeated_lag_episodes_within_window_disconnect() {
+        let registry = Arc::new(StreamClients::new());
+        let client = registry.register("/logs/stream");
+        let t0 = Instant::now();
+        assert!(!client.record_dropped_at(10, true, t0));
+        // Same burst: counted, but not a new episode
+        assert!(!client.record_dropped_at(5, false, t0));
+        assert!(!client.record_dropped_at(1, true, t0 + Duration::from_secs(10)));
+        assert!(client.record_dropped_at(1, true, t0 + Duration::from_secs(20)));
+        assert_eq!(registry.snapshot()[0].dropped, 17);
+    }
+
+    #[test]
+    fn old_lag_episodes_expire() {
+        let registry = Arc::new(StreamClients::new());
+        let client = registry.register("/logs/stream");
+        let t0 = Instant::now();
+        assert!(!client.record_dropped_at(1, true, t0));
+        assert!(!client.record_dropped_at(1, true, t0 + Duration::from_secs(30)));
+        assert!(!client.record_dropped_at(
+            1,
+            true,
+            t0 + SLOW_CLIENT_WINDOW + Duration::from_secs(1)
+        ));
+    }
+}
@@ -7,12 +7,12 @@
 use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
 use opentelemetry::propagation::{Extractor, Injector};
 use std::collections::HashMap;
-use tracing::Span;
+use tracing::{Instrument, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::pii_scrub;
+use crate::velocity_layer;
 
-#[allow(dead_code)] // wired in tests + by future outbound HTTP call sites
 pub const TRACEPARENT_HEADER: &str = "traceparent";
 
 struct HeaderMapExtractor<'a>(&'a HeaderMap);
@@ -27,19 +27,97 @@ impl<'a> Extractor for HeaderMapExtractor<'a> {
 }
 
 /// Axum middleware — extracts W3C trace context from request headers
-/// and attaches it as the OTel parent of the request-handler span.
+/// and attaches it as the OTel parent of the request-handler span. A valid
+/// `traceparent` also moves the request's velocity span into the caller's
+/// trace, so `/velocity/trace` can follow it across services.
 pub async fn extract_trace_context(req: Request, next: Next) -> Response {
     let extracted_cx = opentelemetry::global::get_text_map_propagator(|prop| {
         prop.extract(&HeaderMapExtractor(req.headers()))
     });
     Span::current().set_parent(extracted_cx);
+    if let Some(parent) = req
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(velocity_layer::parse_traceparent)
+    {
+        velocity_layer::adopt_remote_parent(&Span::current(), parent);
+    }
     next.run(req).await
 }
 
+/// Outbound tracing for reqwest calls (runner proxies, runner API clients).
+///
+/// `send_traced` sends the request inside a velocity client span that
+/// records method, URL (without its query), status and duration, and sends a
+/// `traceparent` naming that span — or the OTel context when the velocity
+/// layer isn't installed. 4xx/5xx responses and transport errors mark the
+/// span failed.
+pub trait TraceparentExt {
+    fn send_traced(
+        self,
+    ) -> impl std::future::Future<Output = reqwest::Result<reqwest::Response>> + Send;
+}
+
+impl TraceparentExt for reqwest::RequestBuilder {
+    async fn send_traced(self) -> reqwest::Result<reqwest::Response> {
+        let (client, request) = self.build_split();
+        let mut request = request?;
+        let url = request.url();
+        // The query is left out; it can carry tokens.
+        let span = tracing::info_span!(
+            velocity_layer::CLIENT_SPAN_NAME,
+            http.method = %request.method(),
+            http.url = %format!("{}{}", url.origin().ascii_serialization(), url.path()),
+            http.route = url.path(),
+            server.address = url.host_str().unwrap_or_default(),
+            server.port = url.port_or_known_default().unwrap_or_default(),
+            http.status_code = tracing::field::Empty,
+            otel.status_code = tracing::field::Empty,
+            error.message = tracing::field::Empty,
+        );
+        for (key, value) in span.in_scope(outbound_headers) {
+            if let (Ok(key), Ok(value)) = (
+                reqwest::header::HeaderName::try_from(key),
+                reqwest::header::HeaderValue::try_from(value),
+            ) {
+                request.headers_mut().insert(key, value);
+            }
+        }
+        let result = client.execute(request).instrument(span.clone()).await;
+        match &result {
+            Ok(resp) => {
+                let status = resp.status();
+                span.record("http.status_code", status.as_u16());
+                if status.is_client_error() || status.is_server_error() {
+                    span.record("otel.status_code", "ERROR");
+                    span.record("error.message", status.to_string().as_str());
+                }
+            }
+            Err(e) => {
+                span.record("otel.status_code", "ERROR");
+                span.record("error.message", e.to_string().as_str());
+            }
+        }
+        result
+    }
+}
+
+/// Trace-context headers for an outbound call made in the current span.
+fn outbound_headers() -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    match velocity_layer::current_traceparent() {
+        Some(traceparent) => {
+            headers.insert(TRACEPARENT_HEADER.to_string(), traceparent);
+        }
+        None => inject_into_map(&mut headers),
+    }
+    headers
+}
+
 /// Inject the **current** trace context into an arbitrary
 /// `HashMap<String, String>` — for any non-HTTP carrier (e.g.
 /// background-worker dispatch, future NATS publish).
-#[allow(dead_code)] // wired by future cross-process dispatch sites
 pub fn inject_into_map(map: &mut HashMap<String, String>) {
     let cx = Span::current().context();
     let mut adapter = MapInjector(map);
@@ -0,0 +1,294 @@
+//! Background latency anomaly detection.
+//!
+//! Every [`ANOMALY_INTERVAL_SECS`] the analyzer compares each endpoint's p95
+//! over the last `window_mins` against its p95 over the `baseline_hours`
+//! before that. An endpoint whose window p95 exceeds `factor` times its
+//! baseline — with at least `min_samples` spans on both sides — raises an
+//! alert: a warning log, a `latency_anomaly` diagnostics event and, if
+//! [`ANOMALY_WEBHOOK_ENV`] is set, a POST of the alert. An endpoint alerts
+//! once per episode; it can alert again after its p95 has recovered.
+
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use super::apdex::ApdexConfig;
+use super::db::VelocityDb;
+use super::queries::{self, EndpointSummary, QueryFilter};
+use crate::diagnostics::DiagnosticEventKind;
+use crate::log_capture::{LogLevel, LogSource};
+use crate::state::SharedState;
+
+pub const ANOMALY_FACTOR_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_ANOMALY_FACTOR";
+pub const ANOMALY_WINDOW_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_ANOMALY_WINDOW_MINS";
+pub const ANOMALY_BASELINE_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_ANOMALY_BASELINE_HOURS";
+pub const ANOMALY_MIN_SAMPLES_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_ANOMALY_MIN_SAMPLES";
+pub const ANOMALY_WEBHOOK_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_ANOMALY_WEBHOOK_URL";
+
+/// `0` turns the analyzer off.
+pub const DEFAULT_ANOMALY_FACTOR: f64 = 2.0;
+pub const DEFAULT_ANOMALY_WINDOW_MINS: i64 = 15;
+pub const DEFAULT_ANOMALY_BASELINE_HOURS: i64 = 24;
+pub const DEFAULT_ANOMALY_MIN_SAMPLES: i64 = 20;
+
+pub const ANOMALY_INTERVAL_SECS: u64 = 300;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyConfig {
+    pub factor: f64,
+    pub window_mins: i64,
+    pub baseline_hours: i64,
+    pub min_samples: i64,
+    pub webhook_url: Option<String>,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            factor: DEFAULT_ANOMALY_FACTOR,
+            window_mins: DEFAULT_ANOMALY_WINDOW_MINS,
+            baseline_hours: DEFAULT_ANOMALY_BASELINE_HOURS,
+            min_samples: DEFAULT_ANOMALY_MIN_SAMPLES,
+            webhook_url: None,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    pub fn from_env() -> Self {
+        fn parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|s| s.trim().parse().ok())
+        }
+        let d = Self::default();
+        Self {
+            factor: parse(ANOMALY_FACTOR_ENV).unwrap_or(d.factor),
+            window_mins: parse(ANOMALY_WINDOW_ENV)
+                .filter(|m: &i64| *m > 0)
+                .unwrap_or(d.window_mins),
+            baseline_hours: parse(ANOMALY_BASELINE_ENV)
+                .filter(|h: &i64| *h > 0)
+                .unwrap_or(d.baseline_hours),
+            min_samples: parse(ANOMALY_MIN_SAMPLES_ENV).unwrap_or(d.min_samples),
+            webhook_url: std::env::var(ANOMALY_WEBHOOK_ENV)
+                .ok()
+                .filter(|u| !u.trim().is_empty()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.factor > 0.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyAnomaly {
+    pub service: String,
+    pub http_method: String,
+    pub http_route: String,
+    pub baseline_p95_ms: f64,
+    pub current_p95_ms: f64,
+    /// `current_p95_ms / baseline_p95_ms`.
+    pub ratio: f64,
+    pub baseline_count: i64,
+    pub current_count: i64,
+    pub window_mins: i64,
+}
+
+type EndpointKey = (String, String, String);
+
+impl LatencyAnomaly {
+    fn key(&self) -> EndpointKey {
+        (
+            self.service.clone(),
+            self.http_method.clone(),
+            self.http_route.clone(),
+        )
+    }
+}
+
+fn key(e: &EndpointSummary) -> EndpointKey {
+    (
+        e.service.clone(),
+        e.http_method.clone(),
+        e.http_route.clone(),
+    )
+}
+
+/// Endpoints of `current` whose p95 breaches `factor` times their
+/// `baseline` p95.
+pub fn detect(
+    baseline: &[EndpointSummary],
+    current: &[EndpointSummary],
+    config: &AnomalyConfig,
+) -> Vec<LatencyAnomaly> {
+    current
+        .iter()
+        .filter(|c| c.request_count >= config.min_samples)
+        .filter_map(|c| {
+            let b = baseline.iter().find(|b| key(b) == key(c))?;
+            if b.request_count < config.min_samples || b.p95_duration_ms <= 0.0 {
+                return None;
+            }
+            let ratio = c.p95_duration_ms / b.p95_duration_ms;
+            (ratio > config.factor).then(|| LatencyAnomaly {
+                service: c.service.clone(),
+                http_method: c.http_method.clone(),
+                http_route: c.http_route.clone(),
+                baseline_p95_ms: b.p95_duration_ms,
+                current_p95_ms: c.p95_duration_ms,
+                ratio,
+                baseline_count: b.request_count,
+                current_count: c.request_count,
+                window_mins: config.window_mins,
+            })
+        })
+        .collect()
+}
+
+/// Detection state across checks.
+#[derive(Debug, Default)]
+pub struct AnomalyTracker {
+    active: HashSet<EndpointKey>,
+}
+
+impl AnomalyTracker {
+    /// Run one check and return the anomalies that just started.
+    pub fn check(
+        &mut self,
+        db: &VelocityDb,
+        config: &AnomalyConfig,
+    ) -> anyhow::Result<Vec<LatencyAnomaly>> {
+        let now = Utc::now();
+        let window_start = now - Duration::minutes(config.window_mins);
+        let baseline_start = window_start - Duration::hours(config.baseline_hours);
+        let filter = |since: chrono::DateTime<Utc>, until: chrono::DateTime<Utc>| QueryFilter {
+            since: Some(since.to_rfc3339()),
+            until: Some(until.to_rfc3339()),
+            service: None,
+        };
+        // Only the p95s are compared; Apdex thresholds don't matter here.
+        let apdex = ApdexConfig::default();
+        let baseline = queries::get_endpoints(db, &filter(baseline_start, window_start), &apdex)?;
+        let current = queries::get_endpoints(db, &filter(window_start, now), &apdex)?;
+        Ok(self.update(detect(&baseline, &current, config)))
+    }
+
+    /// Record the anomalous endpoints of a check; returns those that weren't
+    /// anomalous in the previous one.
+    pub fn update(&mut self, anomalies: Vec<LatencyAnomaly>) -> Vec<LatencyAnomaly> {
+        let keys: HashSet<EndpointKey> = anomalies.iter().map(LatencyAnomaly::key).collect();
+        let new = anomalies
+            .into_iter()
+            .filter(|a| !self.active.contains(&a.key()))
+            .collect();
+        self.active = keys;
+        new
+    }
+}
+
+/// Log, record and (if configured) POST a new anomaly.
+pub async fn raise(state: &SharedState, anomaly: LatencyAnomaly, webhook_url: Option<String>) {
+    let message = format!(
+        "Latency anomaly on {} {} {}: p95 {:.0}ms over the last {} min vs {:.0}ms baseline ({:.1}x)",
+        anomaly.service,
+        anomaly.http_method,
+        anomaly.http_route,
+        anomaly.current_p95_ms,
+        anomaly.window_mins,
+        anomaly.baseline_p95_ms,
+        anomaly.ratio
+    );
+    tracing::warn!("{}", message);
+    state
+        .logs
+        .emit(LogSource::Supervisor, LogLevel::Warn, message)
+        .await;
+    state
+        .diagnostics
+        .write()
+        .await
+        .emit(DiagnosticEventKind::LatencyAnomaly {
+            service: anomaly.service.clone(),
+            http_method: anomaly.http_method.clone(),
+            http_route: anomaly.http_route.clone(),
+            baseline_p95_ms: anomaly.baseline_p95_ms,
+            current_p95_ms: anomaly.current_p95_ms,
+            ratio: anomaly.ratio,
+        });
+
+    if let Some(url) = webhook_url {
+        tokio::spawn(async move {
+            let result = reqwest::Client::new()
+                .post(&url)
+                .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                .json(&anomaly)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("Latency anomaly webhook {} failed: {}", url, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(route: &str, count: i64, p95: f64) -> EndpointSummary {
+        EndpointSummary {
+            service: "backend".to_string(),
+            http_method: "GET".to_string(),
+            http_route: route.to_string(),
+            request_count: count,
+            avg_duration_ms: 0.0,
+            p50_duration_ms: 0.0,
+            p95_duration_ms: p95,
+            p99_duration_ms: 0.0,
+            error_count: 0,
+            apdex: None,
+            apdex_t_ms: 500.0,
+            avg_db_time_ms: 0.0,
+            db_query_count: 0,
+        }
+    }
+
+    #[test]
+    fn detects_p95_breaches_with_enough_samples() {
+        let config = AnomalyConfig::default();
+        let baseline = vec![
+            endpoint("/slow", 100, 100.0),
+            endpoint("/fine", 100, 100.0),
+            endpoint("/rare", 5, 100.0),
+        ];
+        let current = vec![
+            endpoint("/slow", 30, 250.0),
+            endpoint("/fine", 30, 150.0),
+            endpoint("/rare", 30, 900.0),
+            endpoint("/new", 30, 900.0),
+        ];
+        let found = detect(&baseline, &current, &config);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].http_route, "/slow");
+        assert_eq!(found[0].ratio, 2.5);
+    }
+
+    #[test]
+    fn alerts_once_per_episode() {
+        let config = AnomalyConfig::default();
+        let baseline = vec![endpoint("/slow", 100, 100.0)];
+        let slow = vec![endpoint("/slow", 30, 300.0)];
+        let recovered = vec![endpoint("/slow", 30, 110.0)];
+        let mut tracker = AnomalyTracker::default();
+        assert_eq!(tracker.update(detect(&baseline, &slow, &config)).len(), 1);
+        assert_eq!(tracker.update(detect(&baseline, &slow, &config)).len(), 0);
+        assert_eq!(
+            tracker.update(detect(&baseline, &recovered, &config)).len(),
+            0
+        );
+        assert_eq!(tracker.update(detect(&baseline, &slow, &config)).len(), 1);
+    }
+}
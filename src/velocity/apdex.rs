@@ -0,0 +1,164 @@
+//! Apdex scores for the velocity summaries.
+//!
+//! Apdex folds a latency distribution into one user-satisfaction number:
+//! `(satisfied + tolerating / 2) / total`, where a request is satisfied at
+//! or under the service's target time T, tolerating up to 4T, and
+//! frustrated beyond that or when it failed. 1.0 is perfect, below 0.7 is
+//! usually considered poor.
+//!
+//! T is per service. [`APDEX_T_ENV`] holds a comma-separated list of
+//! `service=ms` entries plus an optional bare number for everything else,
+//! e.g. `backend=300,frontend=1000,500`; without it every service uses
+//! [`DEFAULT_APDEX_T_MS`].
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::rollup::RollupStats;
+
+pub const APDEX_T_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_APDEX_T_MS";
+
+pub const DEFAULT_APDEX_T_MS: f64 = 500.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApdexConfig {
+    pub default_t_ms: f64,
+    pub per_service: HashMap<String, f64>,
+}
+
+impl Default for ApdexConfig {
+    fn default() -> Self {
+        Self {
+            default_t_ms: DEFAULT_APDEX_T_MS,
+            per_service: HashMap::new(),
+        }
+    }
+}
+
+impl ApdexConfig {
+    pub fn from_env() -> Self {
+        std::env::var(APDEX_T_ENV)
+            .map(|s| Self::parse(&s))
+            .unwrap_or_default()
+    }
+
+    /// Malformed or non-positive entries are ignored.
+    pub fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        let positive = |s: &str| s.trim().parse::<f64>().ok().filter(|t| *t > 0.0);
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=') {
+                Some((service, t)) => {
+                    if let Some(t) = positive(t) {
+                        config.per_service.insert(service.trim().to_string(), t);
+                    }
+                }
+                None => {
+                    if let Some(t) = positive(entry) {
+                        config.default_t_ms = t;
+                    }
+                }
+            }
+        }
+        config
+    }
+
+    /// One T for every service, for a request's `apdex_t_ms` override.
+    pub fn uniform(t_ms: f64) -> Self {
+        Self {
+            default_t_ms: t_ms,
+            per_service: HashMap::new(),
+        }
+    }
+
+    pub fn t_ms(&self, service: &str) -> f64 {
+        self.per_service
+            .get(service)
+            .copied()
+            .unwrap_or(self.default_t_ms)
+    }
+}
+
+/// Request counts by Apdex zone. Fractional once rollups are mixed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ApdexCounts {
+    pub satisfied: f64,
+    pub tolerating: f64,
+    pub total: f64,
+}
+
+impl ApdexCounts {
+    pub fn add(&mut self, other: ApdexCounts) {
+        self.satisfied += other.satisfied;
+        self.tolerating += other.tolerating;
+        self.total += other.total;
+    }
+
+    /// Zones of a rollup, from its percentile points. Failed spans are taken
+    /// out of the satisfied and tolerating zones in proportion.
+    pub fn from_rollup(stats: &RollupStats, t_ms: f64) -> Self {
+        let mut counts = Self {
+            total: stats.count.max(0) as f64,
+            ..Default::default()
+        };
+        for (value, weight) in stats.points() {
+            if value <= t_ms {
+                counts.satisfied += weight;
+            } else if value <= 4.0 * t_ms {
+                counts.tolerating += weight;
+            }
+        }
+        if counts.total > 0.0 {
+            let ok_share = 1.0 - (stats.error_count as f64 / counts.total).min(1.0);
+            counts.satisfied *= ok_share;
+            counts.tolerating *= ok_share;
+        }
+        counts
+    }
+
+    /// `None` without requests.
+    pub fn score(&self) -> Option<f64> {
+        (self.total > 0.0).then(|| (self.satisfied + self.tolerating / 2.0) / self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_per_service_thresholds() {
+        let config = ApdexConfig::parse("backend=300, frontend=1000,250,bad=x,neg=-1");
+        assert_eq!(config.default_t_ms, 250.0);
+        assert_eq!(config.t_ms("backend"), 300.0);
+        assert_eq!(config.t_ms("frontend"), 1000.0);
+        assert_eq!(config.t_ms("bad"), 250.0);
+        assert_eq!(config.t_ms("runner"), 250.0);
+        assert_eq!(ApdexConfig::parse("").default_t_ms, DEFAULT_APDEX_T_MS);
+    }
+
+    #[test]
+    fn scores_zones() {
+        let counts = ApdexCounts {
+            satisfied: 60.0,
+            tolerating: 30.0,
+            total: 100.0,
+        };
+        assert_eq!(counts.score(), Some(0.75));
+        assert_eq!(ApdexCounts::default().score(), None);
+
+        let stats = RollupStats {
+            count: 100,
+            error_count: 10,
+            sum_ms: 0.0,
+            p50_ms: 100.0,
+            p95_ms: 1000.0,
+            p99_ms: 5000.0,
+            max_ms: 9000.0,
+        };
+        let c = ApdexCounts::from_rollup(&stats, 500.0);
+        assert!((c.satisfied - 45.0).abs() < 1e-9);
+        assert!((c.tolerating - 40.5).abs() < 1e-9);
+        assert_eq!(c.total, 100.0);
+    }
+}
@@ -1,19 +1,29 @@
 use rusqlite::Connection;
 use std::path::Path;
 use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use super::live::{LiveSpan, LIVE_CHANNEL_CAPACITY};
+use super::search;
 
 pub struct VelocityDb {
     conn: Mutex<Connection>,
+    live: broadcast::Sender<LiveSpan>,
+    /// Attribute keys with an indexed generated column.
+    indexed_attributes: Vec<String>,
 }
 
 impl VelocityDb {
     pub fn new(dev_logs_dir: &Path) -> anyhow::Result<Self> {
         let db_path = dev_logs_dir.join("velocity.db");
         let conn = Connection::open(&db_path)?;
-        let db = Self {
+        let mut db = Self {
             conn: Mutex::new(conn),
+            live: broadcast::channel(LIVE_CHANNEL_CAPACITY).0,
+            indexed_attributes: Vec::new(),
         };
         db.init_schema()?;
+        db.indexed_attributes = db.index_attributes(&search::indexed_attributes())?;
         Ok(db)
     }
 
@@ -44,6 +54,55 @@ impl VelocityDb {
                 ingested_at TEXT NOT NULL
             );
 
+            CREATE TABLE IF NOT EXISTS velocity_rollups (
+                hour TEXT NOT NULL,
+                service TEXT NOT NULL,
+                http_method TEXT NOT NULL,
+                http_route TEXT NOT NULL,
+                count INTEGER NOT NULL,
+                error_count INTEGER NOT NULL,
+                sum_ms REAL NOT NULL,
+                p50_ms REAL NOT NULL,
+                p95_ms REAL NOT NULL,
+                p99_ms REAL NOT NULL,
+                max_ms REAL NOT NULL,
+                PRIMARY KEY (hour, service, http_method, http_route)
+            );
+
+            CREATE TABLE IF NOT EXISTS velocity_db_spans (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                service TEXT NOT NULL,
+                trace_id TEXT,
+                span_id TEXT,
+                parent_span_id TEXT,
+                request_id TEXT,
+                statement TEXT NOT NULL,
+                start_ts TEXT NOT NULL,
+                duration_ms REAL,
+                http_span_id INTEGER,
+                ingested_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS velocity_slo_violations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                worst_p95_ms REAL,
+                worst_error_rate REAL
+            );
+
+            CREATE TABLE IF NOT EXISTS resource_samples (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ts TEXT NOT NULL,
+                service TEXT NOT NULL,
+                instance TEXT NOT NULL,
+                pid INTEGER NOT NULL,
+                cpu_percent REAL NOT NULL,
+                memory_bytes INTEGER NOT NULL,
+                open_fds INTEGER
+            );
+
             CREATE TABLE IF NOT EXISTS ingestion_state (
                 file_path TEXT PRIMARY KEY,
                 last_byte_offset INTEGER NOT NULL DEFAULT 0,
@@ -56,12 +115,69 @@ impl VelocityDb {
             CREATE INDEX IF NOT EXISTS idx_vs_route ON velocity_spans(http_route);
             CREATE INDEX IF NOT EXISTS idx_vs_request_id ON velocity_spans(request_id);
             CREATE INDEX IF NOT EXISTS idx_vs_service_route ON velocity_spans(service, http_route);
+            CREATE INDEX IF NOT EXISTS idx_vs_status ON velocity_spans(http_status_code);
+            CREATE INDEX IF NOT EXISTS idx_vs_span_id ON velocity_spans(span_id);
+            CREATE INDEX IF NOT EXISTS idx_vs_trace_id ON velocity_spans(trace_id);
+            CREATE INDEX IF NOT EXISTS idx_vr_service ON velocity_rollups(service);
+            CREATE INDEX IF NOT EXISTS idx_vds_http_span ON velocity_db_spans(http_span_id);
+            CREATE INDEX IF NOT EXISTS idx_vds_start ON velocity_db_spans(start_ts);
+            CREATE INDEX IF NOT EXISTS idx_vsv_open ON velocity_slo_violations(name, ended_at);
+            CREATE INDEX IF NOT EXISTS idx_rs_ts ON resource_samples(ts);
         ",
         )?;
         Ok(())
     }
 
+    /// Add an indexed virtual column for each attribute key that lacks one.
+    /// Returns the keys that have a column; when two keys map to the same
+    /// column name the first one gets it and the other is left to
+    /// `json_extract`.
+    fn index_attributes(&self, keys: &[String]) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn();
+        let mut columns: Vec<String> = conn
+            .prepare("SELECT name FROM pragma_table_xinfo('velocity_spans')")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        let mut indexed: Vec<String> = Vec::new();
+        for key in keys {
+            let column = search::column_for(key);
+            if indexed.iter().any(|k| search::column_for(k) == column) {
+                continue;
+            }
+            if !columns.contains(&column) {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE velocity_spans ADD COLUMN {column} TEXT
+                         GENERATED ALWAYS AS (json_extract(attributes, '{path}')) VIRTUAL;",
+                    path = search::json_path(key),
+                ))?;
+                columns.push(column.clone());
+            }
+            conn.execute_batch(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_vs_{column} ON velocity_spans({column});"
+            ))?;
+            indexed.push(key.clone());
+        }
+        Ok(indexed)
+    }
+
+    pub fn indexed_attributes(&self) -> &[String] {
+        &self.indexed_attributes
+    }
+
     pub fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
         self.conn.lock().unwrap_or_else(|e| e.into_inner())
     }
+
+    /// Spans stored from now on, as they are ingested.
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveSpan> {
+        self.live.subscribe()
+    }
+
+    /// Announce committed spans to stream subscribers.
+    pub(crate) fn publish(&self, spans: Vec<LiveSpan>) {
+        for span in spans {
+            // No subscribers is not an error.
+            let _ = self.live.send(span);
+        }
+    }
 }
@@ -0,0 +1,98 @@
+//! Database query spans.
+//!
+//! Spans carrying `db.statement` (or the newer `db.query.text`) are stored in
+//! `velocity_db_spans` rather than with the HTTP spans, by both ingestion
+//! paths. Their duration is the `db.duration` attribute (ms) when present,
+//! the span's own duration otherwise. After every ingest [`link`] attaches
+//! each new query to the HTTP span it ran under — by parent span, then by
+//! request id, then by the latest HTTP span of the same service and trace
+//! that started before it — so `/velocity/endpoints` can report DB time per
+//! endpoint.
+
+use chrono::{Duration, Utc};
+use serde_json::Value;
+
+pub(crate) const INSERT_DB_SPAN_SQL: &str = "INSERT INTO velocity_db_spans (
+    service, trace_id, span_id, parent_span_id, request_id, statement, start_ts,
+    duration_ms, ingested_at
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
+
+/// Unlinked queries are retried for this long, in case their HTTP span is
+/// ingested later (children usually end, and are written, first).
+const LINK_RETRY_HOURS: i64 = 1;
+
+pub(crate) struct DbFields<'a> {
+    pub statement: &'a str,
+    pub duration_ms: Option<f64>,
+}
+
+/// The query fields of a span, or `None` when it isn't a DB span.
+pub(crate) fn db_fields(
+    attributes: Option<&Value>,
+    span_duration_ms: Option<f64>,
+) -> Option<DbFields<'_>> {
+    let attributes = attributes?;
+    let statement = ["db.statement", "db.query.text"]
+        .iter()
+        .find_map(|k| attributes.get(*k))
+        .and_then(|v| v.as_str())?;
+    let duration_ms = attributes
+        .get("db.duration")
+        .and_then(|v| v.as_f64().or_else(|| v.as_str()?.parse().ok()))
+        .or(span_duration_ms);
+    Some(DbFields {
+        statement,
+        duration_ms,
+    })
+}
+
+/// Attach recently ingested, unlinked queries to their HTTP span.
+pub fn link(conn: &rusqlite::Connection) -> rusqlite::Result<usize> {
+    let since = (Utc::now() - Duration::hours(LINK_RETRY_HOURS)).to_rfc3339();
+    conn.execute(
+        "UPDATE velocity_db_spans SET http_span_id = COALESCE(
+             (SELECT s.id FROM velocity_spans s
+              WHERE s.span_id = velocity_db_spans.parent_span_id
+              LIMIT 1),
+             (SELECT s.id FROM velocity_spans s
+              WHERE s.request_id = velocity_db_spans.request_id
+              ORDER BY s.start_ts DESC LIMIT 1),
+             (SELECT s.id FROM velocity_spans s
+              WHERE s.trace_id = velocity_db_spans.trace_id
+                AND s.service = velocity_db_spans.service
+                AND s.start_ts <= velocity_db_spans.start_ts
+              ORDER BY s.start_ts DESC LIMIT 1)
+         )
+         WHERE http_span_id IS NULL AND ingested_at >= ?1",
+        [&since],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn recognises_db_spans() {
+        let attrs = json!({"db.statement": "SELECT 1", "db.duration": 12.5});
+        let f = db_fields(Some(&attrs), Some(40.0)).unwrap();
+        assert_eq!(f.statement, "SELECT 1");
+        assert_eq!(f.duration_ms, Some(12.5));
+
+        let attrs = json!({"db.query.text": "SELECT 2", "db.duration": "3"});
+        assert_eq!(
+            db_fields(Some(&attrs), None).unwrap().duration_ms,
+            Some(3.0)
+        );
+
+        let attrs = json!({"db.query.text": "SELECT 3"});
+        assert_eq!(
+            db_fields(Some(&attrs), Some(7.0)).unwrap().duration_ms,
+            Some(7.0)
+        );
+
+        assert!(db_fields(Some(&json!({"http.method": "GET"})), None).is_none());
+        assert!(db_fields(None, None).is_none());
+    }
+}
@@ -0,0 +1,352 @@
+//! Span export for `GET /velocity/export`, for offline analysis in
+//! pandas/DuckDB.
+//!
+//! Spans are read in id order, [`EXPORT_BATCH_ROWS`] at a time, taking the
+//! DB lock per batch so a large export doesn't stall ingestion. CSV is
+//! streamed batch by batch; Parquet needs its footer written last, so the
+//! file is built in memory with one row group per batch. The `attributes`
+//! JSON is left out unless asked for by name in `columns`.
+
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rusqlite::types::Value;
+use std::sync::Arc;
+
+use super::db::VelocityDb;
+use super::queries::{bind_params, build_where_clause, QueryFilter};
+
+pub const EXPORT_BATCH_ROWS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Int,
+    Real,
+    Bool,
+    Text,
+}
+
+/// Exportable `velocity_spans` columns, in output order.
+const COLUMNS: &[(&str, Kind)] = &[
+    ("id", Kind::Int),
+    ("service", Kind::Text),
+    ("trace_id", Kind::Text),
+    ("span_id", Kind::Text),
+    ("parent_span_id", Kind::Text),
+    ("name", Kind::Text),
+    ("start_ts", Kind::Text),
+    ("end_ts", Kind::Text),
+    ("duration_ms", Kind::Real),
+    ("http_method", Kind::Text),
+    ("http_route", Kind::Text),
+    ("http_status_code", Kind::Int),
+    ("request_id", Kind::Text),
+    ("attributes", Kind::Text),
+    ("success", Kind::Bool),
+    ("error", Kind::Text),
+    ("ingested_at", Kind::Text),
+];
+
+/// Left out of the default selection; it dwarfs the other columns.
+const OPT_IN_COLUMNS: &[&str] = &["attributes"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    name: &'static str,
+    kind: Kind,
+}
+
+/// Columns named in a comma-separated `columns` parameter, or every column
+/// but `attributes` when it's absent. Unknown names are an error.
+pub fn parse_columns(param: Option<&str>) -> Result<Vec<Column>, String> {
+    let all = COLUMNS.iter().map(|&(name, kind)| Column { name, kind });
+    let Some(param) = param.filter(|p| !p.trim().is_empty()) else {
+        return Ok(all.filter(|c| !OPT_IN_COLUMNS.contains(&c.name)).collect());
+    };
+    let mut columns = Vec::new();
+    for name in param.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let column = all
+            .clone()
+            .find(|c| c.name == name)
+            .ok_or_else(|| format!("Unknown column '{}'", name))?;
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    Ok(columns)
+}
+
+/// Next batch of rows after span `after_id`, each with its id first.
+fn fetch_batch(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    columns: &[Column],
+    after_id: i64,
+) -> anyhow::Result<Vec<(i64, Vec<Value>)>> {
+    let (where_clause, mut params) = build_where_clause(filter);
+    params.push(after_id.to_string());
+    let sql = format!(
+        "SELECT id, {} FROM velocity_spans{} AND id > CAST(?{} AS INTEGER) ORDER BY id LIMIT {}",
+        columns
+            .iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        },
+        params.len(),
+        EXPORT_BATCH_ROWS
+    );
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let rows = stmt
+        .raw_query()
+        .mapped(|row| {
+            let values = (1..=columns.len())
+                .map(|i| row.get::<_, Value>(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((row.get(0)?, values))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Call `batch` with successive batches of rows until the export is done
+/// or it returns `false`.
+fn for_each_batch(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    columns: &[Column],
+    mut batch: impl FnMut(Vec<Vec<Value>>) -> anyhow::Result<bool>,
+) -> anyhow::Result<()> {
+    let mut after_id = 0;
+    loop {
+        let rows = fetch_batch(db, filter, columns, after_id)?;
+        let Some(&(last_id, _)) = rows.last() else {
+            return Ok(());
+        };
+        after_id = last_id;
+        let done = rows.len() < EXPORT_BATCH_ROWS;
+        if !batch(rows.into_iter().map(|(_, values)| values).collect())? || done {
+            return Ok(());
+        }
+    }
+}
+
+fn csv_field(out: &mut String, value: &Value, kind: Kind) {
+    match (value, kind) {
+        (Value::Null, _) | (Value::Blob(_), _) => {}
+        (Value::Integer(i), Kind::Bool) => out.push_str(if *i != 0 { "true" } else { "false" }),
+        (Value::Integer(i), _) => out.push_str(&i.to_string()),
+        (Value::Real(f), _) => out.push_str(&f.to_string()),
+        (Value::Text(s), _) => {
+            if s.contains([',', '"', '\n', '\r']) {
+                out.push('"');
+                out.push_str(&s.replace('"', "\"\""));
+                out.push('"');
+            } else {
+                out.push_str(s);
+            }
+        }
+    }
+}
+
+/// Write the export as CSV, handing `chunk` the header and then one chunk
+/// per batch; stops early when `chunk` returns `false` (client gone).
+pub fn write_csv(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    columns: &[Column],
+    mut chunk: impl FnMut(String) -> bool,
+) -> anyhow::Result<()> {
+    let header = columns.iter().map(|c| c.name).collect::<Vec<_>>().join(",");
+    if !chunk(header + "\n") {
+        return Ok(());
+    }
+    for_each_batch(db, filter, columns, |rows| {
+        let mut out = String::new();
+        for row in rows {
+            for (i, (value, column)) in row.iter().zip(columns).enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                csv_field(&mut out, value, column.kind);
+            }
+            out.push('\n');
+        }
+        Ok(chunk(out))
+    })
+}
+
+fn arrow_column(rows: &[Vec<Value>], i: usize, kind: Kind) -> ArrayRef {
+    let values = rows.iter().map(|r| &r[i]);
+    match kind {
+        Kind::Int => Arc::new(Int64Array::from(
+            values
+                .map(|v| match v {
+                    Value::Integer(i) => Some(*i),
+                    Value::Real(f) => Some(*f as i64),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        )),
+        Kind::Real => Arc::new(Float64Array::from(
+            values
+                .map(|v| match v {
+                    Value::Integer(i) => Some(*i as f64),
+                    Value::Real(f) => Some(*f),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        )),
+        Kind::Bool => Arc::new(BooleanArray::from(
+            values
+                .map(|v| match v {
+                    Value::Integer(i) => Some(*i != 0),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        )),
+        Kind::Text => Arc::new(StringArray::from(
+            values
+                .map(|v| match v {
+                    Value::Text(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
+/// The export as a Parquet file.
+pub fn write_parquet(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    columns: &[Column],
+) -> anyhow::Result<Vec<u8>> {
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|c| {
+                let data_type = match c.kind {
+                    Kind::Int => DataType::Int64,
+                    Kind::Real => DataType::Float64,
+                    Kind::Bool => DataType::Boolean,
+                    Kind::Text => DataType::Utf8,
+                };
+                Field::new(c.name, data_type, true)
+            })
+            .collect::<Vec<_>>(),
+    ));
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(Vec::new(), schema.clone(), Some(props))?;
+    for_each_batch(db, filter, columns, |rows| {
+        let arrays = columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| arrow_column(&rows, i, c.kind))
+            .collect();
+        writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+        // One row group per batch keeps memory to the encoded output.
+        writer.flush()?;
+        Ok(true)
+    })?;
+    Ok(writer.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(db: &VelocityDb, name: &str, error: Option<&str>) {
+        db.conn()
+            .execute(
+                "INSERT INTO velocity_spans
+                    (service, name, start_ts, duration_ms, success, error, attributes, ingested_at)
+                 VALUES ('backend', ?1, '2026-03-01T10:00:00+00:00', 12.5, ?2, ?3, '{}', '')",
+                rusqlite::params![name, error.is_none() as i32, error],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn selects_columns() {
+        let default = parse_columns(None).unwrap();
+        assert_eq!(default.len(), COLUMNS.len() - 1);
+        assert!(!default.iter().any(|c| c.name == "attributes"));
+
+        let picked = parse_columns(Some("service, duration_ms,service,attributes")).unwrap();
+        let names: Vec<_> = picked.iter().map(|c| c.name).collect();
+        assert_eq!(names, ["service", "duration_ms", "attributes"]);
+
+        assert!(parse_columns(Some("service,nope")).is_err());
+    }
+
+    #[test]
+    fn exports_csv_and_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityDb::new(dir.path()).unwrap();
+        insert(&db, "HTTP GET", None);
+        insert(&db, "HTTP POST", Some("boom, \"bad\""));
+        let filter = QueryFilter {
+            since: None,
+            until: None,
+            service: Some("backend".to_string()),
+        };
+        let columns = parse_columns(Some("name,duration_ms,success,error")).unwrap();
+
+        let mut csv = String::new();
+        write_csv(&db, &filter, &columns, |chunk| {
+            csv.push_str(&chunk);
+            true
+        })
+        .unwrap();
+        assert_eq!(
+            csv,
+            "name,duration_ms,success,error\n\
+             HTTP GET,12.5,true,\n\
+             HTTP POST,12.5,false,\"boom, \"\"bad\"\"\"\n"
+        );
+
+        let parquet = write_parquet(&db, &filter, &columns).unwrap();
+        assert!(parquet.starts_with(b"PAR1") && parquet.ends_with(b"PAR1"));
+    }
+}
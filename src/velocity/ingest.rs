@@ -1,10 +1,39 @@
 use super::db::VelocityDb;
+use super::db_spans;
+use super::live::LiveSpan;
 use chrono::Utc;
 use serde_json::Value;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::Path;
 
+/// Insert statement shared by the file and OTLP ingestion paths.
+pub(crate) const INSERT_SPAN_SQL: &str = "INSERT INTO velocity_spans (
+    service, trace_id, span_id, parent_span_id, name, start_ts, end_ts,
+    duration_ms, http_method, http_route, http_status_code, request_id,
+    attributes, success, error, ingested_at
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)";
+
+/// HTTP columns denormalized from a span's attributes, accepting both the
+/// old and the stable OpenTelemetry semantic-convention names.
+pub(crate) struct HttpFields<'a> {
+    pub method: Option<&'a str>,
+    pub route: Option<&'a str>,
+    pub status_code: Option<i64>,
+    pub request_id: Option<&'a str>,
+}
+
+pub(crate) fn http_fields(attributes: Option<&Value>) -> HttpFields<'_> {
+    let attr = |keys: &[&str]| attributes.and_then(|a| keys.iter().find_map(|k| a.get(*k)));
+    HttpFields {
+        method: attr(&["http.method", "http.request.method"]).and_then(|v| v.as_str()),
+        route: attr(&["http.route", "http.target"]).and_then(|v| v.as_str()),
+        status_code: attr(&["http.status_code", "http.response.status_code"])
+            .and_then(|v| v.as_i64()),
+        request_id: attr(&["request_id", "http.request_id"]).and_then(|v| v.as_str()),
+    }
+}
+
 pub struct IngestResult {
     pub total_new_spans: usize,
     pub files_processed: Vec<FileIngestResult>,
@@ -76,6 +105,7 @@ fn ingest_file(
     let mut errors = 0;
     let now = Utc::now().to_rfc3339();
     let mut current_offset = seek_offset;
+    let mut stored = Vec::new();
 
     // Collect lines first so we don't hold the file open during DB operations
     let lines: Vec<String> = reader
@@ -87,13 +117,8 @@ fn ingest_file(
     {
         let conn = db.conn();
         conn.execute_batch("BEGIN")?;
-        let mut stmt = conn.prepare(
-            "INSERT INTO velocity_spans (
-                service, trace_id, span_id, parent_span_id, name, start_ts, end_ts,
-                duration_ms, http_method, http_route, http_status_code, request_id,
-                attributes, success, error, ingested_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
-        )?;
+        let mut stmt = conn.prepare(INSERT_SPAN_SQL)?;
+        let mut db_stmt = conn.prepare(db_spans::INSERT_DB_SPAN_SQL)?;
 
         for line in &lines {
             current_offset += line.len() as i64 + 1; // +1 for newline
@@ -110,19 +135,8 @@ fn ingest_file(
                 }
             };
 
-            // Determine if this is an HTTP span worth ingesting
             let name = entry.get("name").and_then(|v| v.as_str()).unwrap_or("");
             let attributes = entry.get("attributes");
-            let target = entry.get("target").and_then(|v| v.as_str()).unwrap_or("");
-            let is_http_span = name.contains("HTTP")
-                || name.contains("request")
-                || target.contains("tower_http")
-                || attributes.is_some_and(|a| a.get("http.method").is_some());
-
-            if !is_http_span {
-                continue;
-            }
-
             let service = entry
                 .get("service")
                 .and_then(|v| v.as_str())
@@ -133,6 +147,38 @@ fn ingest_file(
             let start_ts = entry.get("start_ts").and_then(|v| v.as_str()).unwrap_or("");
             let end_ts = entry.get("end_ts").and_then(|v| v.as_str());
             let duration_ms = entry.get("duration_ms").and_then(|v| v.as_f64());
+
+            // Database queries go to their own table, linked to their
+            // HTTP span after the commit
+            if let Some(query) = db_spans::db_fields(attributes, duration_ms) {
+                match db_stmt.execute(rusqlite::params![
+                    service,
+                    trace_id,
+                    span_id,
+                    parent_span_id,
+                    http_fields(attributes).request_id,
+                    query.statement,
+                    start_ts,
+                    query.duration_ms,
+                    &now,
+                ]) {
+                    Ok(_) => new_spans += 1,
+                    Err(_) => errors += 1,
+                }
+                continue;
+            }
+
+            // Determine if this is an HTTP span worth ingesting
+            let target = entry.get("target").and_then(|v| v.as_str()).unwrap_or("");
+            let is_http_span = name.contains("HTTP")
+                || name.contains("request")
+                || target.contains("tower_http")
+                || attributes.is_some_and(|a| a.get("http.method").is_some());
+
+            if !is_http_span {
+                continue;
+            }
+
             let success = entry
                 .get("success")
                 .and_then(|v| v.as_bool())
@@ -140,24 +186,7 @@ fn ingest_file(
             let error = entry.get("error").and_then(|v| v.as_str());
 
             // Denormalize HTTP attributes from the attributes object
-            let http_method = attributes
-                .and_then(|a| {
-                    a.get("http.method")
-                        .or_else(|| a.get("http.request.method"))
-                })
-                .and_then(|v| v.as_str());
-            let http_route = attributes
-                .and_then(|a| a.get("http.route").or_else(|| a.get("http.target")))
-                .and_then(|v| v.as_str());
-            let http_status_code = attributes
-                .and_then(|a| {
-                    a.get("http.status_code")
-                        .or_else(|| a.get("http.response.status_code"))
-                })
-                .and_then(|v| v.as_i64());
-            let request_id = attributes
-                .and_then(|a| a.get("request_id").or_else(|| a.get("http.request_id")))
-                .and_then(|v| v.as_str());
+            let http = http_fields(attributes);
 
             let attrs_str = attributes.map(|a| a.to_string());
 
@@ -170,16 +199,31 @@ fn ingest_file(
                 start_ts,
                 end_ts,
                 duration_ms,
-                http_method,
-                http_route,
-                http_status_code,
-                request_id,
+                http.method,
+                http.route,
+                http.status_code,
+                http.request_id,
                 attrs_str,
                 success as i32,
                 error,
                 &now,
             ]) {
-                Ok(_) => new_spans += 1,
+                Ok(_) => {
+                    new_spans += 1;
+                    stored.push(LiveSpan {
+                        id: conn.last_insert_rowid(),
+                        service: service.to_string(),
+                        name: name.to_string(),
+                        start_ts: start_ts.to_string(),
+                        duration_ms,
+                        http_method: http.method.map(str::to_string),
+                        http_route: http.route.map(str::to_string),
+                        http_status_code: http.status_code,
+                        request_id: http.request_id.map(str::to_string),
+                        success,
+                        error: error.map(str::to_string),
+                    });
+                }
                 Err(_) => errors += 1,
             }
         }
@@ -190,7 +234,9 @@ fn ingest_file(
             rusqlite::params![&file_path_str, current_offset, &now],
         )?;
         conn.execute_batch("COMMIT")?;
+        db_spans::link(&conn)?;
     }
+    db.publish(stored);
 
     Ok(FileIngestResult {
         file: file_path_str,
@@ -0,0 +1,99 @@
+//! Live tail of ingested spans for `GET /velocity/stream`.
+//!
+//! Both ingestion paths (`/velocity/ingest` and `/velocity/otlp`) publish
+//! each stored span on [`VelocityDb`]'s broadcast channel once their
+//! transaction commits; stream clients filter it with [`StreamFilter`].
+//!
+//! [`VelocityDb`]: super::db::VelocityDb
+
+use serde::{Deserialize, Serialize};
+
+/// Spans buffered for slow subscribers before they start lagging.
+pub const LIVE_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSpan {
+    pub id: i64,
+    pub service: String,
+    pub name: String,
+    pub start_ts: String,
+    pub duration_ms: Option<f64>,
+    pub http_method: Option<String>,
+    pub http_route: Option<String>,
+    pub http_status_code: Option<i64>,
+    pub request_id: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct StreamFilter {
+    pub service: Option<String>,
+    /// Exact route, or a prefix when it ends in `*` (`/api/v1/runs*`).
+    pub route: Option<String>,
+    /// Spans without a duration never pass a minimum.
+    pub min_duration_ms: Option<f64>,
+}
+
+impl StreamFilter {
+    pub fn matches(&self, span: &LiveSpan) -> bool {
+        if self.service.as_ref().is_some_and(|s| *s != span.service) {
+            return false;
+        }
+        if let Some(pattern) = &self.route {
+            if !route_matches(pattern, span.http_route.as_deref().unwrap_or("")) {
+                return false;
+            }
+        }
+        match self.min_duration_ms {
+            Some(min) => span.duration_ms.is_some_and(|d| d >= min),
+            None => true,
+        }
+    }
+}
+
+/// `route == pattern`, or a prefix match when `pattern` ends in `*`.
+pub fn route_matches(pattern: &str, route: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => route.starts_with(prefix),
+        None => route == pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_service_route_and_duration() {
+        let span = LiveSpan {
+            id: 1,
+            service: "backend".to_string(),
+            name: "HTTP GET".to_string(),
+            start_ts: "2026-03-01T10:00:00+00:00".to_string(),
+            duration_ms: Some(250.0),
+            http_method: Some("GET".to_string()),
+            http_route: Some("/api/v1/runs/{id}".to_string()),
+            http_status_code: Some(200),
+            request_id: None,
+            success: true,
+            error: None,
+        };
+        let filter = |service: Option<&str>, route: Option<&str>, min: Option<f64>| StreamFilter {
+            service: service.map(str::to_string),
+            route: route.map(str::to_string),
+            min_duration_ms: min,
+        };
+        assert!(StreamFilter::default().matches(&span));
+        assert!(filter(Some("backend"), Some("/api/v1/runs*"), Some(200.0)).matches(&span));
+        assert!(!filter(Some("runner"), None, None).matches(&span));
+        assert!(!filter(None, Some("/api/v1/runs"), None).matches(&span));
+        assert!(!filter(None, None, Some(300.0)).matches(&span));
+
+        let no_duration = LiveSpan {
+            duration_ms: None,
+            ..span
+        };
+        assert!(!filter(None, None, Some(0.0)).matches(&no_duration));
+    }
+}
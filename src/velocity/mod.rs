@@ -1,3 +1,15 @@
+pub mod anomaly;
+pub mod apdex;
 pub mod db;
+pub mod db_spans;
+pub mod export;
 pub mod ingest;
+pub mod live;
+pub mod otlp;
 pub mod queries;
+pub mod resources;
+pub mod rollup;
+pub mod search;
+pub mod slo;
+pub mod waterfall;
+pub mod writer;
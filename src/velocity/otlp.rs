@@ -0,0 +1,505 @@
+//! OTLP/HTTP trace ingestion for `POST /velocity/otlp`.
+//!
+//! The Python backend and the frontend can export spans with their stock
+//! OpenTelemetry SDKs (`OTEL_EXPORTER_OTLP_TRACES_ENDPOINT=
+//! http://localhost:9875/velocity/otlp`) instead of writing velocity JSONL.
+//! Both OTLP/HTTP encodings are accepted — binary protobuf
+//! (`application/x-protobuf`) and JSON (`application/json`). Each span's
+//! service comes from the `service.name` resource attribute; like file
+//! ingestion, only HTTP spans (those with an `http.method` /
+//! `http.request.method` attribute) and database query spans (see
+//! [`db_spans`]) are stored. The handler queues decoded spans for the
+//! batched [`writer`](super::writer), which calls [`store`].
+
+use chrono::{DateTime, Utc};
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, KeyValue};
+use prost::Message;
+use serde_json::{json, Map, Value};
+
+use super::db::VelocityDb;
+use super::db_spans;
+use super::ingest::{http_fields, INSERT_SPAN_SQL};
+use super::live::LiveSpan;
+
+/// Service recorded for spans whose resource has no `service.name`.
+const UNKNOWN_SERVICE: &str = "otlp";
+
+/// `Status.code` of a failed span.
+const STATUS_CODE_ERROR: i64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Protobuf,
+    Json,
+}
+
+impl Encoding {
+    /// OTLP/HTTP defaults to protobuf when no content type is given.
+    pub fn from_content_type(content_type: Option<&str>) -> Option<Self> {
+        let mime = content_type
+            .map(|ct| ct.split(';').next().unwrap_or("").trim())
+            .unwrap_or("application/x-protobuf");
+        match mime {
+            "application/x-protobuf" | "application/protobuf" => Some(Self::Protobuf),
+            "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Protobuf => "application/x-protobuf",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// A decoded span in velocity terms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpSpan {
+    pub service: String,
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start_ts: String,
+    pub end_ts: Option<String>,
+    pub duration_ms: Option<f64>,
+    /// JSON object of the span's attributes.
+    pub attributes: Value,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct OtlpIngestResult {
+    pub stored: usize,
+    /// Decoded fine but neither HTTP nor database spans.
+    pub skipped: usize,
+    /// Failed to insert.
+    pub rejected: usize,
+}
+
+pub fn decode(body: &[u8], encoding: Encoding) -> anyhow::Result<Vec<OtlpSpan>> {
+    match encoding {
+        Encoding::Protobuf => decode_protobuf(body),
+        Encoding::Json => decode_json(body),
+    }
+}
+
+fn nanos_to_rfc3339(nanos: u64) -> Option<String> {
+    if nanos == 0 {
+        return None;
+    }
+    DateTime::<Utc>::from_timestamp(
+        (nanos / 1_000_000_000) as i64,
+        (nanos % 1_000_000_000) as u32,
+    )
+    .map(|t| t.to_rfc3339())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn span(
+    service: &str,
+    trace_id: String,
+    span_id: String,
+    parent_span_id: String,
+    name: String,
+    start_nanos: u64,
+    end_nanos: u64,
+    attributes: Map<String, Value>,
+    status_code: i64,
+    status_message: String,
+) -> OtlpSpan {
+    let failed = status_code == STATUS_CODE_ERROR;
+    OtlpSpan {
+        service: service.to_string(),
+        trace_id,
+        span_id,
+        parent_span_id: (!parent_span_id.is_empty()).then_some(parent_span_id),
+        name,
+        start_ts: nanos_to_rfc3339(start_nanos).unwrap_or_else(|| Utc::now().to_rfc3339()),
+        end_ts: nanos_to_rfc3339(end_nanos),
+        duration_ms: (end_nanos >= start_nanos && end_nanos > 0)
+            .then(|| (end_nanos - start_nanos) as f64 / 1_000_000.0),
+        attributes: Value::Object(attributes),
+        success: !failed,
+        error: (failed && !status_message.is_empty()).then_some(status_message),
+    }
+}
+
+// ============================================================================
+// Protobuf
+// ============================================================================
+
+fn proto_value(value: &AnyValue) -> Value {
+    match &value.value {
+        Some(any_value::Value::StringValue(s)) => Value::String(s.clone()),
+        Some(any_value::Value::BoolValue(b)) => Value::Bool(*b),
+        Some(any_value::Value::IntValue(i)) => json!(i),
+        Some(any_value::Value::DoubleValue(d)) => json!(d),
+        Some(any_value::Value::BytesValue(b)) => Value::String(hex::encode(b)),
+        Some(any_value::Value::ArrayValue(a)) => {
+            Value::Array(a.values.iter().map(proto_value).collect())
+        }
+        Some(any_value::Value::KvlistValue(kv)) => Value::Object(proto_attributes(&kv.values)),
+        None => Value::Null,
+    }
+}
+
+fn proto_attributes(attributes: &[KeyValue]) -> Map<String, Value> {
+    attributes
+        .iter()
+        .map(|kv| {
+            let value = kv.value.as_ref().map(proto_value).unwrap_or(Value::Null);
+            (kv.key.clone(), value)
+        })
+        .collect()
+}
+
+fn decode_protobuf(body: &[u8]) -> anyhow::Result<Vec<OtlpSpan>> {
+    let request = ExportTraceServiceRequest::decode(body)?;
+    let mut spans = Vec::new();
+    for resource_spans in &request.resource_spans {
+        let resource = resource_spans
+            .resource
+            .as_ref()
+            .map(|r| proto_attributes(&r.attributes))
+            .unwrap_or_default();
+        let service = resource
+            .get("service.name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(UNKNOWN_SERVICE);
+        for s in resource_spans.scope_spans.iter().flat_map(|ss| &ss.spans) {
+            let (code, message) = s
+                .status
+                .as_ref()
+                .map(|st| (st.code as i64, st.message.clone()))
+                .unwrap_or_default();
+            spans.push(span(
+                service,
+                hex::encode(&s.trace_id),
+                hex::encode(&s.span_id),
+                hex::encode(&s.parent_span_id),
+                s.name.clone(),
+                s.start_time_unix_nano,
+                s.end_time_unix_nano,
+                proto_attributes(&s.attributes),
+                code,
+                message,
+            ));
+        }
+    }
+    Ok(spans)
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+/// OTLP/JSON encodes 64-bit integers as strings.
+fn json_u64(value: Option<&Value>) -> u64 {
+    match value {
+        Some(Value::String(s)) => s.parse().unwrap_or(0),
+        Some(v) => v.as_u64().unwrap_or(0),
+        None => 0,
+    }
+}
+
+fn json_str(value: Option<&Value>) -> String {
+    value.and_then(|v| v.as_str()).unwrap_or("").to_string()
+}
+
+fn json_value(value: &Value) -> Value {
+    if let Some(s) = value.get("stringValue") {
+        s.clone()
+    } else if let Some(b) = value.get("boolValue") {
+        b.clone()
+    } else if let Some(i) = value.get("intValue") {
+        match i {
+            Value::String(s) => s.parse::<i64>().map(|n| json!(n)).unwrap_or(i.clone()),
+            _ => i.clone(),
+        }
+    } else if let Some(d) = value.get("doubleValue") {
+        d.clone()
+    } else if let Some(b) = value.get("bytesValue") {
+        b.clone()
+    } else if let Some(a) = value.get("arrayValue") {
+        Value::Array(
+            a.get("values")
+                .and_then(|v| v.as_array())
+                .map(|vs| vs.iter().map(json_value).collect())
+                .unwrap_or_default(),
+        )
+    } else if let Some(kv) = value.get("kvlistValue") {
+        Value::Object(json_attributes(kv.get("values")))
+    } else {
+        Value::Null
+    }
+}
+
+fn json_attributes(attributes: Option<&Value>) -> Map<String, Value> {
+    attributes
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|kv| {
+            let key = kv.get("key")?.as_str()?.to_string();
+            let value = kv.get("value").map(json_value).unwrap_or(Value::Null);
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn json_array<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+}
+
+fn decode_json(body: &[u8]) -> anyhow::Result<Vec<OtlpSpan>> {
+    let request: Value = serde_json::from_slice(body)?;
+    if !request.is_object() {
+        anyhow::bail!("OTLP/JSON body must be an object");
+    }
+    let mut spans = Vec::new();
+    for resource_spans in json_array(&request, "resourceSpans") {
+        let resource = json_attributes(
+            resource_spans
+                .get("resource")
+                .and_then(|r| r.get("attributes")),
+        );
+        let service = resource
+            .get("service.name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(UNKNOWN_SERVICE);
+        for s in json_array(resource_spans, "scopeSpans").flat_map(|ss| json_array(ss, "spans")) {
+            let status = s.get("status");
+            spans.push(span(
+                service,
+                json_str(s.get("traceId")),
+                json_str(s.get("spanId")),
+                json_str(s.get("parentSpanId")),
+                json_str(s.get("name")),
+                json_u64(s.get("startTimeUnixNano")),
+                json_u64(s.get("endTimeUnixNano")),
+                json_attributes(s.get("attributes")),
+                status
+                    .and_then(|st| st.get("code"))
+                    .and_then(|c| c.as_i64())
+                    .unwrap_or(0),
+                json_str(status.and_then(|st| st.get("message"))),
+            ));
+        }
+    }
+    Ok(spans)
+}
+
+// ============================================================================
+// Storage and response
+// ============================================================================
+
+pub fn store(db: &VelocityDb, spans: &[OtlpSpan]) -> anyhow::Result<OtlpIngestResult> {
+    let mut result = OtlpIngestResult::default();
+    let now = Utc::now().to_rfc3339();
+    let mut stored = Vec::new();
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(INSERT_SPAN_SQL)?;
+        let mut db_stmt = tx.prepare(db_spans::INSERT_DB_SPAN_SQL)?;
+        for span in spans {
+            let http = http_fields(Some(&span.attributes));
+            if let Some(query) = db_spans::db_fields(Some(&span.attributes), span.duration_ms) {
+                match db_stmt.execute(rusqlite::params![
+                    span.service,
+                    span.trace_id,
+                    span.span_id,
+                    span.parent_span_id,
+                    http.request_id,
+                    query.statement,
+                    span.start_ts,
+                    query.duration_ms,
+                    &now,
+                ]) {
+                    Ok(_) => result.stored += 1,
+                    Err(_) => result.rejected += 1,
+                }
+                continue;
+            }
+            if http.method.is_none() {
+                result.skipped += 1;
+                continue;
+            }
+            match stmt.execute(rusqlite::params![
+                span.service,
+                span.trace_id,
+                span.span_id,
+                span.parent_span_id,
+                span.name,
+                span.start_ts,
+                span.end_ts,
+                span.duration_ms,
+                http.method,
+                http.route,
+                http.status_code,
+                http.request_id,
+                span.attributes.to_string(),
+                span.success as i32,
+                span.error,
+                &now,
+            ]) {
+                Ok(_) => {
+                    result.stored += 1;
+                    stored.push(LiveSpan {
+                        id: tx.last_insert_rowid(),
+                        service: span.service.clone(),
+                        name: span.name.clone(),
+                        start_ts: span.start_ts.clone(),
+                        duration_ms: span.duration_ms,
+                        http_method: http.method.map(str::to_string),
+                        http_route: http.route.map(str::to_string),
+                        http_status_code: http.status_code,
+                        request_id: http.request_id.map(str::to_string),
+                        success: span.success,
+                        error: span.error.clone(),
+                    });
+                }
+                Err(_) => result.rejected += 1,
+            }
+        }
+    }
+    tx.commit()?;
+    db_spans::link(&conn)?;
+    drop(conn);
+    db.publish(stored);
+    Ok(result)
+}
+
+/// `ExportTraceServiceResponse` in `encoding`, reporting rejected spans as a
+/// partial success.
+pub fn encode_response(encoding: Encoding, result: &OtlpIngestResult) -> Vec<u8> {
+    let partial_success = (result.rejected > 0).then(|| ExportTracePartialSuccess {
+        rejected_spans: result.rejected as i64,
+        error_message: format!("{} span(s) could not be stored", result.rejected),
+    });
+    match encoding {
+        Encoding::Protobuf => ExportTraceServiceResponse { partial_success }.encode_to_vec(),
+        Encoding::Json => {
+            let body = match partial_success {
+                Some(p) => json!({
+                    "partialSuccess": {
+                        "rejectedSpans": p.rejected_spans.to_string(),
+                        "errorMessage": p.error_message,
+                    }
+                }),
+                None => json!({}),
+            };
+            body.to_string().into_bytes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::resource::v1::Resource;
+    use opentelemetry_proto::tonic::trace::v1::{ResourceSpans, ScopeSpans, Span, Status};
+
+    fn string_attr(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    #[test]
+    fn decodes_protobuf_export() {
+        let request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: Some(Resource {
+                    attributes: vec![string_attr("service.name", "backend")],
+                    ..Default::default()
+                }),
+                scope_spans: vec![ScopeSpans {
+                    spans: vec![Span {
+                        trace_id: vec![0xab; 16],
+                        span_id: vec![0x01; 8],
+                        name: "GET /api/projects".to_string(),
+                        start_time_unix_nano: 1_700_000_000_000_000_000,
+                        end_time_unix_nano: 1_700_000_000_250_000_000,
+                        attributes: vec![
+                            string_attr("http.request.method", "GET"),
+                            KeyValue {
+                                key: "http.response.status_code".to_string(),
+                                value: Some(AnyValue {
+                                    value: Some(any_value::Value::IntValue(500)),
+                                }),
+                            },
+                        ],
+                        status: Some(Status {
+                            message: "boom".to_string(),
+                            code: 2,
+                        }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        let spans = decode(&request.encode_to_vec(), Encoding::Protobuf).unwrap();
+        assert_eq!(spans.len(), 1);
+        let s = &spans[0];
+        assert_eq!(s.service, "backend");
+        assert_eq!(s.trace_id, "ab".repeat(16));
+        assert_eq!(s.parent_span_id, None);
+        assert_eq!(s.duration_ms, Some(250.0));
+        assert!(!s.success);
+        assert_eq!(s.error.as_deref(), Some("boom"));
+        let http = http_fields(Some(&s.attributes));
+        assert_eq!(http.method, Some("GET"));
+        assert_eq!(http.status_code, Some(500));
+    }
+
+    #[test]
+    fn decodes_json_export() {
+        let body = r#"{"resourceSpans": [{
+            "resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "frontend"}}]},
+            "scopeSpans": [{"spans": [{
+                "traceId": "5b8efff798038103d269b633813fc60c",
+                "spanId": "eee19b7ec3c1b174",
+                "parentSpanId": "eee19b7ec3c1b173",
+                "name": "fetch",
+                "startTimeUnixNano": "1700000000000000000",
+                "endTimeUnixNano": "1700000000010000000",
+                "attributes": [
+                    {"key": "http.method", "value": {"stringValue": "POST"}},
+                    {"key": "http.status_code", "value": {"intValue": "201"}}
+                ]
+            }]}]
+        }]}"#;
+        let spans = decode(body.as_bytes(), Encoding::Json).unwrap();
+        assert_eq!(spans.len(), 1);
+        let s = &spans[0];
+        assert_eq!(s.service, "frontend");
+        assert_eq!(s.parent_span_id.as_deref(), Some("eee19b7ec3c1b173"));
+        assert_eq!(s.duration_ms, Some(10.0));
+        assert!(s.success);
+        assert_eq!(http_fields(Some(&s.attributes)).status_code, Some(201));
+
+        assert_eq!(
+            Encoding::from_content_type(Some("application/json; charset=utf-8")),
+            Some(Encoding::Json)
+        );
+        assert_eq!(Encoding::from_content_type(None), Some(Encoding::Protobuf));
+        assert!(decode(b"[]", Encoding::Json).is_err());
+    }
+}
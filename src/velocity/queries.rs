@@ -1,5 +1,10 @@
+use super::apdex::{ApdexConfig, ApdexCounts};
 use super::db::VelocityDb;
+use super::resources::{self, ResourcePeak};
+use super::rollup::{self, Distribution, RollupStats};
+use crate::metrics::Histogram;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 
 // ============================================================================
 // Result types
@@ -15,6 +20,9 @@ pub struct ServiceSummary {
     pub p99_duration_ms: f64,
     pub error_count: i64,
     pub error_rate: f64,
+    /// `None` when no span has a duration.
+    pub apdex: Option<f64>,
+    pub apdex_t_ms: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -28,6 +36,12 @@ pub struct EndpointSummary {
     pub p95_duration_ms: f64,
     pub p99_duration_ms: f64,
     pub error_count: i64,
+    pub apdex: Option<f64>,
+    pub apdex_t_ms: f64,
+    /// Mean time per request spent in linked database queries. Covers raw
+    /// spans only; rolled-up hours keep no query data.
+    pub avg_db_time_ms: f64,
+    pub db_query_count: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -51,6 +65,10 @@ pub struct TimelineBucket {
     pub avg_duration_ms: f64,
     pub p95_duration_ms: f64,
     pub error_count: i64,
+    pub apdex: Option<f64>,
+    /// Peak CPU/memory of the service's processes during the bucket, when
+    /// it was sampled.
+    pub resources: Option<ResourcePeak>,
 }
 
 #[derive(Debug, Serialize)]
@@ -72,6 +90,8 @@ pub struct TraceSpan {
     pub id: i64,
     pub service: String,
     pub name: String,
+    pub span_id: Option<String>,
+    pub parent_span_id: Option<String>,
     pub start_ts: String,
     pub end_ts: Option<String>,
     pub duration_ms: Option<f64>,
@@ -98,34 +118,28 @@ pub struct QueryFilter {
 // Helpers
 // ============================================================================
 
-fn percentile(sorted_values: &[f64], p: f64) -> f64 {
-    if sorted_values.is_empty() {
-        return 0.0;
-    }
-    let idx = ((p / 100.0) * (sorted_values.len() as f64 - 1.0)).round() as usize;
-    sorted_values[idx.min(sorted_values.len() - 1)]
-}
-
-fn avg(values: &[f64]) -> f64 {
-    if values.is_empty() {
-        return 0.0;
-    }
-    values.iter().sum::<f64>() / values.len() as f64
-}
-
 /// Build a WHERE clause fragment and corresponding parameter values from a QueryFilter.
 /// Returns (clause_string, params_vec) where clause_string starts with " WHERE " or is empty.
-fn build_where_clause(filter: &QueryFilter) -> (String, Vec<String>) {
+pub(super) fn build_where_clause(filter: &QueryFilter) -> (String, Vec<String>) {
+    build_where_clause_on(filter, "start_ts")
+}
+
+/// [`build_where_clause`] with the time bounds applied to `ts_column`
+/// (`hour` for rollups).
+pub(super) fn build_where_clause_on(
+    filter: &QueryFilter,
+    ts_column: &str,
+) -> (String, Vec<String>) {
     let mut conditions = Vec::new();
     let mut params = Vec::new();
 
     if let Some(ref since) = filter.since {
         params.push(since.clone());
-        conditions.push(format!("start_ts >= ?{}", params.len()));
+        conditions.push(format!("{} >= ?{}", ts_column, params.len()));
     }
     if let Some(ref until) = filter.until {
         params.push(until.clone());
-        conditions.push(format!("start_ts <= ?{}", params.len()));
+        conditions.push(format!("{} <= ?{}", ts_column, params.len()));
     }
     if let Some(ref service) = filter.service {
         params.push(service.clone());
@@ -141,198 +155,438 @@ fn build_where_clause(filter: &QueryFilter) -> (String, Vec<String>) {
 
 /// Bind string params to a rusqlite statement. This is a convenience helper
 /// since we build dynamic WHERE clauses with variable param counts.
-fn bind_params(stmt: &mut rusqlite::Statement<'_>, params: &[String]) -> rusqlite::Result<()> {
+pub(super) fn bind_params(
+    stmt: &mut rusqlite::Statement<'_>,
+    params: &[String],
+) -> rusqlite::Result<()> {
     for (i, param) in params.iter().enumerate() {
         stmt.raw_bind_parameter(i + 1, param)?;
     }
     Ok(())
 }
 
-// ============================================================================
-// Queries
-// ============================================================================
-
-/// Per-service summary with percentile breakdowns.
-pub fn get_summary(db: &VelocityDb, filter: &QueryFilter) -> anyhow::Result<Vec<ServiceSummary>> {
-    let conn = db.conn();
-
-    let (where_clause, params) = build_where_clause(filter);
+/// Rollups within `filter` whose `column = value` for every condition.
+/// Columns may be expressions over `velocity_rollups`.
+fn rollups_matching(
+    conn: &rusqlite::Connection,
+    filter: &QueryFilter,
+    conditions: &[(&str, &str)],
+) -> anyhow::Result<Vec<RollupStats>> {
+    let (where_clause, mut params) = build_where_clause_on(filter, "hour");
+    let mut sql = format!(
+        "SELECT count, error_count, sum_ms, p50_ms, p95_ms, p99_ms, max_ms FROM velocity_rollups{}",
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        }
+    );
+    for (column, value) in conditions {
+        params.push(value.to_string());
+        sql.push_str(&format!(" AND {} = ?{}", column, params.len()));
+    }
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let rows = stmt
+        .raw_query()
+        .mapped(rollup::read_stats)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
 
-    // First, get the distinct services
-    let services_sql = format!(
-        "SELECT DISTINCT service FROM velocity_spans{}",
-        where_clause
+/// Distinct `columns` of the rollups within `filter` and `extra` conditions
+/// (SQL appended to the WHERE clause).
+fn rollup_groups<T>(
+    conn: &rusqlite::Connection,
+    filter: &QueryFilter,
+    columns: &str,
+    extra: &str,
+    map: impl FnMut(&rusqlite::Row<'_>) -> rusqlite::Result<T>,
+) -> anyhow::Result<Vec<T>> {
+    let (where_clause, params) = build_where_clause_on(filter, "hour");
+    let sql = format!(
+        "SELECT DISTINCT {} FROM velocity_rollups{}{}",
+        columns,
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        },
+        extra
     );
-    let mut services_stmt = conn.prepare(&services_sql)?;
-    bind_params(&mut services_stmt, &params)?;
-    let services: Vec<String> = services_stmt
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let rows = stmt
         .raw_query()
-        .mapped(|row| row.get(0))
+        .mapped(map)
         .filter_map(|r| r.ok())
         .collect();
+    Ok(rows)
+}
 
-    let mut results = Vec::new();
+/// Aggregates of one group of spans, raw spans and rollups merged.
+struct GroupAggregate {
+    count: i64,
+    error_count: i64,
+    avg_ms: f64,
+    p50_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+    apdex: ApdexCounts,
+}
 
-    for service in &services {
-        // Get all durations for this service
-        let dur_sql = format!(
-            "SELECT duration_ms FROM velocity_spans{} AND service = ?{}",
-            if where_clause.is_empty() {
-                " WHERE 1=1"
-            } else {
-                &where_clause
-            },
-            params.len() + 1
+/// SQL expression for the Apdex threshold of a row's `service`. Service
+/// names come from configuration, not requests; quotes are escaped anyway.
+fn apdex_t_sql(apdex: &ApdexConfig) -> String {
+    if apdex.per_service.is_empty() {
+        return apdex.default_t_ms.to_string();
+    }
+    let mut sql = "CASE service".to_string();
+    for (service, t_ms) in &apdex.per_service {
+        sql.push_str(&format!(
+            " WHEN '{}' THEN {}",
+            service.replace('\'', "''"),
+            t_ms
+        ));
+    }
+    sql.push_str(&format!(" ELSE {} END", apdex.default_t_ms));
+    sql
+}
+
+/// How an aggregate query groups spans.
+struct Grouping<'a> {
+    /// Group key expressions over `velocity_spans`.
+    raw: &'a [&'a str],
+    /// Conditions appended to the raw WHERE clause.
+    raw_extra: &'a str,
+    /// The same key over `velocity_rollups`.
+    rollup: &'a [&'a str],
+    rollup_extra: &'a str,
+    /// Position of the service in the key, for its Apdex threshold.
+    service_col: usize,
+}
+
+/// Aggregate spans within `filter` per group in one query, then merge the
+/// rollups of each group.
+///
+/// Raw percentiles are nearest-rank over `ROW_NUMBER()`, as the in-memory
+/// version computed them. Groups with rollups treat their raw spans as one
+/// more rollup, so their percentiles are approximate.
+fn aggregate_groups(
+    conn: &rusqlite::Connection,
+    filter: &QueryFilter,
+    grouping: &Grouping<'_>,
+    apdex: &ApdexConfig,
+) -> anyhow::Result<BTreeMap<Vec<String>, GroupAggregate>> {
+    let (where_clause, params) = build_where_clause(filter);
+    let scope = format!(
+        "{}{}",
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        },
+        grouping.raw_extra
+    );
+    let key_cols: Vec<String> = (0..grouping.raw.len()).map(|i| format!("g{}", i)).collect();
+    let keys = key_cols.join(", ");
+    let select_keys = grouping
+        .raw
+        .iter()
+        .zip(&key_cols)
+        .map(|(expr, col)| format!("{} AS {}", expr, col))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rank = |p: f64| {
+        format!(
+            "MAX(CASE WHEN idx = CAST({} * (n - 1) + 0.5 AS INTEGER) THEN duration_ms END)",
+            p
+        )
+    };
+    let sql = format!(
+        "WITH scoped AS (
+            SELECT {select_keys}, duration_ms, success, {t} AS t
+            FROM velocity_spans{scope}
+         ),
+         ranked AS (
+            SELECT {keys}, duration_ms,
+                   ROW_NUMBER() OVER (PARTITION BY {keys} ORDER BY duration_ms) - 1 AS idx,
+                   COUNT(*) OVER (PARTITION BY {keys}) AS n
+            FROM scoped WHERE duration_ms IS NOT NULL
+         ),
+         pct AS (
+            SELECT {keys}, {p50} AS p50, {p95} AS p95, {p99} AS p99
+            FROM ranked GROUP BY {keys}
+         ),
+         agg AS (
+            SELECT {keys},
+                   COUNT(duration_ms) AS count,
+                   COALESCE(SUM(duration_ms), 0) AS sum_ms,
+                   COALESCE(MAX(duration_ms), 0) AS max_ms,
+                   SUM(CASE WHEN success = 0 THEN 1 ELSE 0 END) AS errors,
+                   SUM(CASE WHEN COALESCE(success, 1) != 0 AND duration_ms <= t
+                            THEN 1 ELSE 0 END) AS satisfied,
+                   SUM(CASE WHEN COALESCE(success, 1) != 0 AND duration_ms > t
+                             AND duration_ms <= 4 * t THEN 1 ELSE 0 END) AS tolerating
+            FROM scoped GROUP BY {keys}
+         )
+         SELECT agg.count, agg.errors, agg.sum_ms,
+                COALESCE(pct.p50, 0), COALESCE(pct.p95, 0), COALESCE(pct.p99, 0),
+                agg.max_ms, agg.satisfied, agg.tolerating, {keys}
+         FROM agg LEFT JOIN pct USING ({keys})",
+        t = apdex_t_sql(apdex),
+        p50 = rank(0.50),
+        p95 = rank(0.95),
+        p99 = rank(0.99),
+    );
+    let width = grouping.raw.len();
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let raw: Vec<(Vec<String>, RollupStats, ApdexCounts)> = stmt
+        .raw_query()
+        .mapped(|row| {
+            let stats = rollup::read_stats(row)?;
+            let counts = ApdexCounts {
+                satisfied: row.get::<_, i64>(7)? as f64,
+                tolerating: row.get::<_, i64>(8)? as f64,
+                total: stats.count as f64,
+            };
+            let key = (0..width)
+                .map(|i| row.get(9 + i))
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            Ok((key, stats, counts))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let (where_clause, params) = build_where_clause_on(filter, "hour");
+    let sql = format!(
+        "SELECT count, error_count, sum_ms, p50_ms, p95_ms, p99_ms, max_ms, {}
+         FROM velocity_rollups{}{}",
+        grouping.rollup.join(", "),
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        },
+        grouping.rollup_extra
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let mut rollups: BTreeMap<Vec<String>, Vec<RollupStats>> = BTreeMap::new();
+    for row in stmt.raw_query().mapped(|row| {
+        let key = (0..width)
+            .map(|i| row.get(7 + i))
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok((key, rollup::read_stats(row)?))
+    }) {
+        let (key, stats) = row?;
+        rollups.entry(key).or_default().push(stats);
+    }
+
+    let mut groups = BTreeMap::new();
+    for (key, stats, counts) in raw {
+        let rolled = rollups.remove(&key).unwrap_or_default();
+        let t_ms = apdex.t_ms(&key[grouping.service_col]);
+        groups.insert(key, merge_group(stats, counts, &rolled, t_ms));
+    }
+    for (key, rolled) in rollups {
+        let t_ms = apdex.t_ms(&key[grouping.service_col]);
+        groups.insert(
+            key,
+            merge_group(
+                RollupStats::default(),
+                ApdexCounts::default(),
+                &rolled,
+                t_ms,
+            ),
         );
-        let mut dur_stmt = conn.prepare(&dur_sql)?;
-        let mut all_params = params.clone();
-        all_params.push(service.clone());
-        bind_params(&mut dur_stmt, &all_params)?;
-
-        let mut durations: Vec<f64> = dur_stmt
-            .raw_query()
-            .mapped(|row| row.get::<_, Option<f64>>(0))
-            .filter_map(|r| r.ok())
-            .flatten()
-            .collect();
-        durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Get error count
-        let err_sql = format!(
-            "SELECT COUNT(*) FROM velocity_spans{} AND service = ?{} AND success = 0",
-            if where_clause.is_empty() {
-                " WHERE 1=1"
+    }
+    Ok(groups)
+}
+
+/// A group's raw aggregates (shaped as a rollup) merged with its rollups.
+fn merge_group(
+    raw: RollupStats,
+    mut apdex: ApdexCounts,
+    rollups: &[RollupStats],
+    t_ms: f64,
+) -> GroupAggregate {
+    if rollups.is_empty() {
+        return GroupAggregate {
+            count: raw.count,
+            error_count: raw.error_count,
+            avg_ms: if raw.count > 0 {
+                raw.sum_ms / raw.count as f64
             } else {
-                &where_clause
+                0.0
             },
-            params.len() + 1
-        );
-        let mut err_stmt = conn.prepare(&err_sql)?;
-        bind_params(&mut err_stmt, &all_params)?;
-        let error_count: i64 = err_stmt
-            .raw_query()
-            .mapped(|row| row.get(0))
-            .filter_map(|r| r.ok())
-            .next()
-            .unwrap_or(0);
-
-        let total = durations.len() as i64;
-        let error_rate = if total > 0 {
-            error_count as f64 / total as f64
-        } else {
-            0.0
+            p50_ms: raw.p50_ms,
+            p95_ms: raw.p95_ms,
+            p99_ms: raw.p99_ms,
+            apdex,
         };
+    }
+    let mut d = Distribution::default();
+    d.add_rollup(&raw);
+    let mut error_count = raw.error_count;
+    for r in rollups {
+        d.add_rollup(r);
+        error_count += r.error_count;
+        apdex.add(ApdexCounts::from_rollup(r, t_ms));
+    }
+    GroupAggregate {
+        count: d.count(),
+        error_count,
+        avg_ms: d.avg(),
+        p50_ms: d.percentile(50.0),
+        p95_ms: d.percentile(95.0),
+        p99_ms: d.percentile(99.0),
+        apdex,
+    }
+}
 
-        results.push(ServiceSummary {
-            service: service.clone(),
-            total_requests: total,
-            avg_duration_ms: avg(&durations),
-            p50_duration_ms: percentile(&durations, 50.0),
-            p95_duration_ms: percentile(&durations, 95.0),
-            p99_duration_ms: percentile(&durations, 99.0),
-            error_count,
-            error_rate,
-        });
+/// Raw durations and matching rollups combined.
+fn distribution(raw: Vec<f64>, rollups: &[RollupStats]) -> (Distribution, i64) {
+    let mut d = Distribution::default();
+    for v in raw {
+        d.add_raw(v);
     }
+    let mut rollup_errors = 0;
+    for r in rollups {
+        d.add_rollup(r);
+        rollup_errors += r.error_count;
+    }
+    (d, rollup_errors)
+}
 
-    Ok(results)
+// ============================================================================
+// Queries
+// ============================================================================
+
+/// Per-service summary with percentile breakdowns and Apdex.
+pub fn get_summary(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    apdex: &ApdexConfig,
+) -> anyhow::Result<Vec<ServiceSummary>> {
+    let conn = db.conn();
+    let grouping = Grouping {
+        raw: &["service"],
+        raw_extra: "",
+        rollup: &["service"],
+        rollup_extra: "",
+        service_col: 0,
+    };
+    let groups = aggregate_groups(&conn, filter, &grouping, apdex)?;
+
+    Ok(groups
+        .into_iter()
+        .map(|(mut key, g)| {
+            let service = key.remove(0);
+            let error_rate = if g.count > 0 {
+                g.error_count as f64 / g.count as f64
+            } else {
+                0.0
+            };
+            ServiceSummary {
+                apdex_t_ms: apdex.t_ms(&service),
+                service,
+                total_requests: g.count,
+                avg_duration_ms: g.avg_ms,
+                p50_duration_ms: g.p50_ms,
+                p95_duration_ms: g.p95_ms,
+                p99_duration_ms: g.p99_ms,
+                error_count: g.error_count,
+                error_rate,
+                apdex: g.apdex.score(),
+            }
+        })
+        .collect())
 }
 
-/// Per-endpoint summary grouped by (service, method, route).
+/// Per-endpoint summary grouped by (service, method, route), with Apdex at
+/// the service's threshold.
 pub fn get_endpoints(
     db: &VelocityDb,
     filter: &QueryFilter,
+    apdex: &ApdexConfig,
 ) -> anyhow::Result<Vec<EndpointSummary>> {
     let conn = db.conn();
-
-    let (where_clause, params) = build_where_clause(filter);
-    let base_where = if where_clause.is_empty() {
-        " WHERE http_method IS NOT NULL AND http_route IS NOT NULL".to_string()
-    } else {
-        format!(
-            "{} AND http_method IS NOT NULL AND http_route IS NOT NULL",
-            where_clause
-        )
+    let grouping = Grouping {
+        raw: &["service", "http_method", "http_route"],
+        raw_extra: " AND http_method IS NOT NULL AND http_route IS NOT NULL",
+        rollup: &["service", "http_method", "http_route"],
+        rollup_extra: " AND http_method != '' AND http_route != ''",
+        service_col: 0,
     };
+    let groups = aggregate_groups(&conn, filter, &grouping, apdex)?;
+    let db_time = db_time_by_endpoint(&conn, filter)?;
+
+    let mut results: Vec<EndpointSummary> = groups
+        .into_iter()
+        .map(|(key, g)| {
+            let (requests, db_query_count, db_ms) = db_time.get(&key).copied().unwrap_or_default();
+            let [service, http_method, http_route]: [String; 3] =
+                key.try_into().unwrap_or_default();
+            EndpointSummary {
+                apdex_t_ms: apdex.t_ms(&service),
+                service,
+                http_method,
+                http_route,
+                request_count: g.count,
+                avg_duration_ms: g.avg_ms,
+                p50_duration_ms: g.p50_ms,
+                p95_duration_ms: g.p95_ms,
+                p99_duration_ms: g.p99_ms,
+                error_count: g.error_count,
+                apdex: g.apdex.score(),
+                avg_db_time_ms: if requests > 0 {
+                    db_ms / requests as f64
+                } else {
+                    0.0
+                },
+                db_query_count,
+            }
+        })
+        .collect();
 
-    // Get distinct endpoint groups
-    let groups_sql = format!(
-        "SELECT DISTINCT service, http_method, http_route FROM velocity_spans{}",
-        base_where
-    );
-    let mut groups_stmt = conn.prepare(&groups_sql)?;
-    bind_params(&mut groups_stmt, &params)?;
+    // Sort by request count descending for convenience
+    results.sort_by_key(|r| std::cmp::Reverse(r.request_count));
+    Ok(results)
+}
 
-    let groups: Vec<(String, String, String)> = groups_stmt
+/// Per (service, method, route) of the raw spans in `filter`: the request
+/// count and the number and total duration of the queries linked to them.
+fn db_time_by_endpoint(
+    conn: &rusqlite::Connection,
+    filter: &QueryFilter,
+) -> anyhow::Result<HashMap<Vec<String>, (i64, i64, f64)>> {
+    let (where_clause, params) = build_where_clause(filter);
+    let sql = format!(
+        "SELECT s.service, s.http_method, s.http_route, COUNT(DISTINCT s.id), COUNT(d.id), \
+         COALESCE(SUM(d.duration_ms), 0) \
+         FROM (SELECT id, service, http_method, http_route FROM velocity_spans{} \
+               AND http_method IS NOT NULL AND http_route IS NOT NULL) s \
+         LEFT JOIN velocity_db_spans d ON d.http_span_id = s.id \
+         GROUP BY s.service, s.http_method, s.http_route",
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        }
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let rows = stmt
         .raw_query()
         .mapped(|row| {
             Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
+                vec![row.get(0)?, row.get(1)?, row.get(2)?],
+                (row.get(3)?, row.get(4)?, row.get(5)?),
             ))
         })
-        .filter_map(|r| r.ok())
-        .collect();
-
-    let mut results = Vec::new();
-
-    for (service, method, route) in &groups {
-        let dur_sql = format!(
-            "SELECT duration_ms FROM velocity_spans{} AND service = ?{} AND http_method = ?{} AND http_route = ?{}",
-            if where_clause.is_empty() { " WHERE 1=1" } else { &where_clause },
-            params.len() + 1,
-            params.len() + 2,
-            params.len() + 3,
-        );
-        let mut dur_stmt = conn.prepare(&dur_sql)?;
-        let mut all_params = params.clone();
-        all_params.push(service.clone());
-        all_params.push(method.clone());
-        all_params.push(route.clone());
-        bind_params(&mut dur_stmt, &all_params)?;
-
-        let mut durations: Vec<f64> = dur_stmt
-            .raw_query()
-            .mapped(|row| row.get::<_, Option<f64>>(0))
-            .filter_map(|r| r.ok())
-            .flatten()
-            .collect();
-        durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Error count for this endpoint
-        let err_sql = format!(
-            "SELECT COUNT(*) FROM velocity_spans{} AND service = ?{} AND http_method = ?{} AND http_route = ?{} AND success = 0",
-            if where_clause.is_empty() { " WHERE 1=1" } else { &where_clause },
-            params.len() + 1,
-            params.len() + 2,
-            params.len() + 3,
-        );
-        let mut err_stmt = conn.prepare(&err_sql)?;
-        bind_params(&mut err_stmt, &all_params)?;
-        let error_count: i64 = err_stmt
-            .raw_query()
-            .mapped(|row| row.get(0))
-            .filter_map(|r| r.ok())
-            .next()
-            .unwrap_or(0);
-
-        results.push(EndpointSummary {
-            service: service.clone(),
-            http_method: method.clone(),
-            http_route: route.clone(),
-            request_count: durations.len() as i64,
-            avg_duration_ms: avg(&durations),
-            p50_duration_ms: percentile(&durations, 50.0),
-            p95_duration_ms: percentile(&durations, 95.0),
-            p99_duration_ms: percentile(&durations, 99.0),
-            error_count,
-        });
-    }
-
-    // Sort by request count descending for convenience
-    results.sort_by_key(|r| std::cmp::Reverse(r.request_count));
-    Ok(results)
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(rows)
 }
 
 /// Slowest requests above a given threshold (ms), ordered by duration descending.
@@ -387,85 +641,100 @@ pub fn get_slow_requests(
     Ok(results)
 }
 
-/// Timeline bucketed by 1-minute intervals.
-pub fn get_timeline(db: &VelocityDb, filter: &QueryFilter) -> anyhow::Result<Vec<TimelineBucket>> {
+/// Slowest spans for a single backend route, matched with and without a
+/// trailing slash (route templates and test-case endpoints disagree on it).
+/// Used by the velocity improvement loop to give the fix agent concrete
+/// evidence for "Backend Slow" pages.
+pub fn get_slow_spans_for_route(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    route: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<SlowRequest>> {
     let conn = db.conn();
 
     let (where_clause, params) = build_where_clause(filter);
+    let route_condition = format!(
+        " AND (http_route = ?{} OR http_route = ?{})",
+        params.len() + 1,
+        params.len() + 2
+    );
 
-    // Get distinct (bucket, service) groups
-    let groups_sql = format!(
-        "SELECT DISTINCT substr(start_ts, 1, 16) AS bucket, service \
-         FROM velocity_spans{} \
-         ORDER BY bucket, service",
-        if where_clause.is_empty() {
-            " WHERE 1=1"
-        } else {
-            &where_clause
-        },
+    let sql = format!(
+        "SELECT id, service, COALESCE(http_method, ''), COALESCE(http_route, ''), duration_ms, http_status_code, start_ts, request_id, error \
+         FROM velocity_spans{}{} AND duration_ms IS NOT NULL ORDER BY duration_ms DESC LIMIT ?{}",
+        if where_clause.is_empty() { " WHERE 1=1" } else { &where_clause },
+        route_condition,
+        params.len() + 3,
     );
-    let mut groups_stmt = conn.prepare(&groups_sql)?;
-    bind_params(&mut groups_stmt, &params)?;
 
-    let groups: Vec<(String, String)> = groups_stmt
+    let bare = route.trim_end_matches('/');
+    let slashed = format!("{}/", bare);
+
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    stmt.raw_bind_parameter(params.len() + 1, bare)?;
+    stmt.raw_bind_parameter(params.len() + 2, slashed.as_str())?;
+    stmt.raw_bind_parameter(params.len() + 3, limit as i64)?;
+
+    let results: Vec<SlowRequest> = stmt
         .raw_query()
-        .mapped(|row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .mapped(|row| {
+            Ok(SlowRequest {
+                id: row.get(0)?,
+                service: row.get(1)?,
+                http_method: row.get(2)?,
+                http_route: row.get(3)?,
+                duration_ms: row.get(4)?,
+                http_status_code: row.get(5)?,
+                start_ts: row.get(6)?,
+                request_id: row.get(7)?,
+                error: row.get(8)?,
+            })
+        })
         .filter_map(|r| r.ok())
         .collect();
 
-    let mut results = Vec::new();
-
-    for (bucket, service) in &groups {
-        // Get durations for this bucket+service
-        let dur_sql = format!(
-            "SELECT duration_ms FROM velocity_spans{} AND substr(start_ts, 1, 16) = ?{} AND service = ?{}",
-            if where_clause.is_empty() { " WHERE 1=1" } else { &where_clause },
-            params.len() + 1,
-            params.len() + 2,
-        );
-        let mut dur_stmt = conn.prepare(&dur_sql)?;
-        let mut all_params = params.clone();
-        all_params.push(bucket.clone());
-        all_params.push(service.clone());
-        bind_params(&mut dur_stmt, &all_params)?;
-
-        let mut durations: Vec<f64> = dur_stmt
-            .raw_query()
-            .mapped(|row| row.get::<_, Option<f64>>(0))
-            .filter_map(|r| r.ok())
-            .flatten()
-            .collect();
-        durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Error count for this bucket+service
-        let err_sql = format!(
-            "SELECT COUNT(*) FROM velocity_spans{} AND substr(start_ts, 1, 16) = ?{} AND service = ?{} AND success = 0",
-            if where_clause.is_empty() { " WHERE 1=1" } else { &where_clause },
-            params.len() + 1,
-            params.len() + 2,
-        );
-        let mut err_stmt = conn.prepare(&err_sql)?;
-        bind_params(&mut err_stmt, &all_params)?;
-        let error_count: i64 = err_stmt
-            .raw_query()
-            .mapped(|row| row.get(0))
-            .filter_map(|r| r.ok())
-            .next()
-            .unwrap_or(0);
-
-        results.push(TimelineBucket {
-            bucket: bucket.clone(),
-            service: service.clone(),
-            request_count: durations.len() as i64,
-            avg_duration_ms: avg(&durations),
-            p95_duration_ms: percentile(&durations, 95.0),
-            error_count,
-        });
-    }
-
     Ok(results)
 }
 
+/// Timeline bucketed by 1-minute intervals, with Apdex and resource peaks
+/// per bucket. Rolled-up hours show as one bucket at the top of the hour.
+pub fn get_timeline(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    apdex: &ApdexConfig,
+) -> anyhow::Result<Vec<TimelineBucket>> {
+    let conn = db.conn();
+    let grouping = Grouping {
+        raw: &["substr(start_ts, 1, 16)", "service"],
+        raw_extra: "",
+        rollup: &["substr(hour, 1, 16)", "service"],
+        rollup_extra: "",
+        service_col: 1,
+    };
+    let groups = aggregate_groups(&conn, filter, &grouping, apdex)?;
+    let mut peaks = resources::peaks_by_minute(&conn, filter)?;
+
+    Ok(groups
+        .into_iter()
+        .map(|(key, g)| {
+            let [bucket, service]: [String; 2] = key.try_into().unwrap_or_default();
+            let resources = peaks.remove(&(bucket.clone(), service.clone()));
+            TimelineBucket {
+                bucket,
+                service,
+                request_count: g.count,
+                avg_duration_ms: g.avg_ms,
+                p95_duration_ms: g.p95_ms,
+                error_count: g.error_count,
+                apdex: g.apdex.score(),
+                resources,
+            }
+        })
+        .collect())
+}
+
 /// Compare two time windows per-endpoint to detect regressions.
 pub fn get_compare(
     db: &VelocityDb,
@@ -501,27 +770,45 @@ pub fn get_compare(
         endpoints_stmt.raw_bind_parameter(5, svc)?;
     }
 
-    let endpoints: Vec<(String, String)> = endpoints_stmt
+    let mut endpoints: Vec<(String, String)> = endpoints_stmt
         .raw_query()
         .mapped(|row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
         .filter_map(|r| r.ok())
         .collect();
+    let mut rolled: Vec<(String, String)> = Vec::new();
+    for (start, end) in [(before_start, before_end), (after_start, after_end)] {
+        rolled.extend(rollup_groups(
+            &conn,
+            &window_filter(start, end, service),
+            "http_method, http_route",
+            " AND http_method != '' AND http_route != ''",
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?);
+    }
+    if !rolled.is_empty() {
+        for endpoint in rolled {
+            if !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+        endpoints.sort();
+    }
 
     let mut results = Vec::new();
 
     for (method, route) in &endpoints {
         // Before window durations
-        let before_durations =
+        let mut before_durations =
             fetch_durations_for_window(&conn, before_start, before_end, method, route, service)?;
 
         // After window durations
-        let after_durations =
+        let mut after_durations =
             fetch_durations_for_window(&conn, after_start, after_end, method, route, service)?;
 
-        let before_p50 = percentile(&before_durations, 50.0);
-        let before_p95 = percentile(&before_durations, 95.0);
-        let after_p50 = percentile(&after_durations, 50.0);
-        let after_p95 = percentile(&after_durations, 95.0);
+        let before_p50 = before_durations.percentile(50.0);
+        let before_p95 = before_durations.percentile(95.0);
+        let after_p50 = after_durations.percentile(50.0);
+        let after_p95 = after_durations.percentile(95.0);
 
         let p50_change_pct = if before_p50 > 0.0 {
             ((after_p50 - before_p50) / before_p50) * 100.0
@@ -537,10 +824,10 @@ pub fn get_compare(
         results.push(CompareResult {
             http_method: method.clone(),
             http_route: route.clone(),
-            before_count: before_durations.len() as i64,
+            before_count: before_durations.count(),
             before_p50,
             before_p95,
-            after_count: after_durations.len() as i64,
+            after_count: after_durations.count(),
             after_p50,
             after_p95,
             p50_change_pct,
@@ -558,7 +845,15 @@ pub fn get_compare(
     Ok(results)
 }
 
-/// Fetch sorted durations for a specific endpoint within a time window.
+fn window_filter(start: &str, end: &str, service: Option<&str>) -> QueryFilter {
+    QueryFilter {
+        since: Some(start.to_string()),
+        until: Some(end.to_string()),
+        service: service.map(str::to_string),
+    }
+}
+
+/// Durations (raw and rolled up) for a specific endpoint within a time window.
 fn fetch_durations_for_window(
     conn: &rusqlite::Connection,
     start: &str,
@@ -566,7 +861,7 @@ fn fetch_durations_for_window(
     method: &str,
     route: &str,
     service: Option<&str>,
-) -> anyhow::Result<Vec<f64>> {
+) -> anyhow::Result<Distribution> {
     let service_clause = if service.is_some() {
         " AND service = ?5"
     } else {
@@ -590,24 +885,34 @@ fn fetch_durations_for_window(
         stmt.raw_bind_parameter(5, svc)?;
     }
 
-    let mut durations: Vec<f64> = stmt
+    let durations: Vec<f64> = stmt
         .raw_query()
         .mapped(|row| row.get::<_, f64>(0))
         .filter_map(|r| r.ok())
         .collect();
 
-    durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-    Ok(durations)
+    let rollups = rollups_matching(
+        conn,
+        &window_filter(start, end, service),
+        &[("http_method", method), ("http_route", route)],
+    )?;
+    Ok(distribution(durations, &rollups).0)
 }
 
 /// Look up all spans sharing the same request_id (trace reconstruction).
 pub fn get_trace(db: &VelocityDb, request_id: &str) -> anyhow::Result<Vec<TraceSpan>> {
     let conn = db.conn();
 
+    // Spans tagged with the request id, plus every span sharing a trace id
+    // with them — services that only propagate `traceparent` don't tag
+    // request ids. The id may also be a trace id itself.
     let mut stmt = conn.prepare(
         "SELECT id, service, name, start_ts, end_ts, duration_ms, http_method, http_route, \
-         http_status_code, success, error, attributes \
-         FROM velocity_spans WHERE request_id = ?1 ORDER BY start_ts ASC",
+         http_status_code, success, error, attributes, span_id, parent_span_id \
+         FROM velocity_spans WHERE request_id = ?1 OR trace_id = ?1 \
+            OR trace_id IN (SELECT trace_id FROM velocity_spans \
+                            WHERE request_id = ?1 AND trace_id IS NOT NULL) \
+         ORDER BY start_ts ASC",
     )?;
 
     let results: Vec<TraceSpan> = stmt
@@ -616,6 +921,8 @@ pub fn get_trace(db: &VelocityDb, request_id: &str) -> anyhow::Result<Vec<TraceS
                 id: row.get(0)?,
                 service: row.get(1)?,
                 name: row.get(2)?,
+                span_id: row.get(12)?,
+                parent_span_id: row.get(13)?,
                 start_ts: row.get(3)?,
                 end_ts: row.get(4)?,
                 duration_ms: row.get(5)?,
@@ -632,3 +939,268 @@ pub fn get_trace(db: &VelocityDb, request_id: &str) -> anyhow::Result<Vec<TraceS
 
     Ok(results)
 }
+
+/// All-time span count, error count and latency histogram of one service.
+#[derive(Debug)]
+pub struct ServiceLatency {
+    pub service: String,
+    pub spans: i64,
+    pub errors: i64,
+    pub latency_ms: Histogram,
+}
+
+/// Per-service latency histograms over every stored span, bucketed in SQL so
+/// a scrape doesn't load the durations. Rollups are folded in with their
+/// spans split across their percentile points.
+pub fn get_latency_histograms(
+    db: &VelocityDb,
+    bounds_ms: &[f64],
+) -> anyhow::Result<Vec<ServiceLatency>> {
+    let conn = db.conn();
+    let bucket_cols: String = bounds_ms
+        .iter()
+        .map(|b| format!(", SUM(CASE WHEN duration_ms <= {} THEN 1 ELSE 0 END)", b))
+        .collect();
+    let sql = format!(
+        "SELECT service, COUNT(*), SUM(CASE WHEN success = 0 THEN 1 ELSE 0 END),
+                COUNT(duration_ms), COALESCE(SUM(duration_ms), 0){}
+         FROM velocity_spans GROUP BY service ORDER BY service",
+        bucket_cols
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row| {
+        let mut latency_ms = Histogram::new(bounds_ms);
+        latency_ms.count = row.get::<_, i64>(3)? as u64;
+        latency_ms.sum = row.get(4)?;
+        for (i, count) in latency_ms.counts.iter_mut().enumerate() {
+            *count = row.get::<_, i64>(5 + i)? as u64;
+        }
+        Ok(ServiceLatency {
+            service: row.get(0)?,
+            spans: row.get(1)?,
+            errors: row.get(2)?,
+            latency_ms,
+        })
+    })?;
+    let mut services = rows.collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT count, error_count, sum_ms, p50_ms, p95_ms, p99_ms, max_ms, service
+         FROM velocity_rollups",
+    )?;
+    let rollups = stmt.query_map([], |row| Ok((rollup::read_stats(row)?, row.get(7)?)))?;
+    for r in rollups {
+        let (stats, service): (RollupStats, String) = r?;
+        let idx = match services.iter().position(|s| s.service == service) {
+            Some(i) => i,
+            None => {
+                services.push(ServiceLatency {
+                    service,
+                    spans: 0,
+                    errors: 0,
+                    latency_ms: Histogram::new(bounds_ms),
+                });
+                services.len() - 1
+            }
+        };
+        let entry = &mut services[idx];
+        entry.spans += stats.count;
+        entry.errors += stats.error_count;
+        let h = &mut entry.latency_ms;
+        h.count += stats.count as u64;
+        h.sum += stats.sum_ms;
+        for (value, n) in stats.point_counts() {
+            for (bound, count) in h.bounds.iter().zip(h.counts.iter_mut()) {
+                if value <= *bound {
+                    *count += n;
+                }
+            }
+        }
+    }
+    services.sort_by(|a, b| a.service.cmp(&b.service));
+    Ok(services)
+}
+
+/// Upper bounds (ms) of the `/velocity/histogram` buckets: a 1-2-5 log
+/// scale, with everything slower in a final open bucket.
+pub const HISTOGRAM_BOUNDS_MS: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0, 10000.0, 20000.0,
+    60000.0,
+];
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HistogramBucket {
+    /// Exclusive; `0` for the first bucket.
+    pub lower_ms: f64,
+    /// Inclusive; `None` for the open-ended last bucket.
+    pub upper_ms: Option<f64>,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LatencyHistogram {
+    pub total: i64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+fn histogram_bucket(value: f64) -> usize {
+    HISTOGRAM_BOUNDS_MS
+        .iter()
+        .position(|b| value <= *b)
+        .unwrap_or(HISTOGRAM_BOUNDS_MS.len())
+}
+
+/// Span durations within `filter` (and `route`, if given) counted into the
+/// fixed [`HISTOGRAM_BOUNDS_MS`] buckets. Raw spans are bucketed in SQL;
+/// rollups add their spans at their percentile points.
+pub fn get_histogram(
+    db: &VelocityDb,
+    filter: &QueryFilter,
+    route: Option<&str>,
+) -> anyhow::Result<LatencyHistogram> {
+    let conn = db.conn();
+    let (where_clause, mut params) = build_where_clause(filter);
+    let mut conditions = String::from(" AND duration_ms IS NOT NULL");
+    if let Some(route) = route {
+        params.push(route.to_string());
+        conditions.push_str(&format!(" AND http_route = ?{}", params.len()));
+    }
+    let bucket_expr: String = HISTOGRAM_BOUNDS_MS
+        .iter()
+        .enumerate()
+        .map(|(i, b)| format!(" WHEN duration_ms <= {} THEN {}", b, i))
+        .collect();
+    let sql = format!(
+        "SELECT CASE{} ELSE {} END AS bucket, COUNT(*) FROM velocity_spans{}{} GROUP BY bucket",
+        bucket_expr,
+        HISTOGRAM_BOUNDS_MS.len(),
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        },
+        conditions
+    );
+    let mut counts = vec![0i64; HISTOGRAM_BOUNDS_MS.len() + 1];
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let rows = stmt
+        .raw_query()
+        .mapped(|row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (bucket, count) in rows {
+        if let Some(c) = counts.get_mut(bucket as usize) {
+            *c += count;
+        }
+    }
+
+    let route_condition = route.map(|r| ("http_route", r));
+    for stats in rollups_matching(&conn, filter, route_condition.as_slice())? {
+        for (value, n) in stats.point_counts() {
+            counts[histogram_bucket(value)] += n as i64;
+        }
+    }
+
+    let buckets = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| HistogramBucket {
+            lower_ms: if i == 0 {
+                0.0
+            } else {
+                HISTOGRAM_BOUNDS_MS[i - 1]
+            },
+            upper_ms: HISTOGRAM_BOUNDS_MS.get(i).copied(),
+            count,
+        })
+        .collect();
+    Ok(LatencyHistogram {
+        total: counts.iter().sum(),
+        buckets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(db: &VelocityDb, service: &str, route: &str, duration_ms: f64, success: bool) {
+        db.conn()
+            .execute(
+                "INSERT INTO velocity_spans
+                    (service, name, start_ts, duration_ms, http_method, http_route,
+                     success, ingested_at)
+                 VALUES (?1, 'HTTP GET', '2026-03-01T10:00:00+00:00', ?2, 'GET', ?3, ?4,
+                         '2026-03-01T10:00:00+00:00')",
+                rusqlite::params![service, duration_ms, route, success as i32],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn sql_aggregates_match_nearest_rank() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityDb::new(dir.path()).unwrap();
+        for i in 1..=100 {
+            insert(&db, "backend", "/a", f64::from(i), i != 100);
+        }
+        for d in [10.0, 2000.0, 5000.0] {
+            insert(&db, "backend", "/b", d, true);
+        }
+        let apdex = ApdexConfig::uniform(50.0);
+
+        let summary = get_summary(&db, &QueryFilter::default(), &apdex).unwrap();
+        assert_eq!(summary.len(), 1);
+        let s = &summary[0];
+        assert_eq!(s.total_requests, 103);
+        assert_eq!(s.error_count, 1);
+        assert_eq!(s.p50_duration_ms, 51.0);
+        assert_eq!(s.p99_duration_ms, 2000.0);
+
+        let endpoints = get_endpoints(&db, &QueryFilter::default(), &apdex).unwrap();
+        let a = endpoints.iter().find(|e| e.http_route == "/a").unwrap();
+        assert_eq!(a.request_count, 100);
+        assert_eq!(a.p50_duration_ms, 51.0);
+        assert_eq!(a.p95_duration_ms, 95.0);
+        assert!((a.avg_duration_ms - 50.5).abs() < 1e-9);
+        // 50 satisfied, 49 tolerating (51..=99), the failed 100ms span frustrated.
+        assert_eq!(a.apdex, Some(0.745));
+        let b = endpoints.iter().find(|e| e.http_route == "/b").unwrap();
+        assert_eq!(b.request_count, 3);
+        assert_eq!(b.p50_duration_ms, 2000.0);
+
+        let timeline = get_timeline(&db, &QueryFilter::default(), &apdex).unwrap();
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0].bucket, "2026-03-01T10:00");
+        assert_eq!(timeline[0].request_count, 103);
+    }
+
+    #[test]
+    fn buckets_durations_on_a_log_scale() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityDb::new(dir.path()).unwrap();
+        for d in [0.5, 1.0, 3.0, 150.0, 200.0, 90000.0] {
+            insert(&db, "backend", "/a", d, true);
+        }
+        insert(&db, "backend", "/b", 3.0, true);
+
+        let h = get_histogram(&db, &QueryFilter::default(), Some("/a")).unwrap();
+        assert_eq!(h.total, 6);
+        assert_eq!(h.buckets.len(), HISTOGRAM_BOUNDS_MS.len() + 1);
+        let count = |upper: Option<f64>| {
+            h.buckets
+                .iter()
+                .find(|b| b.upper_ms == upper)
+                .unwrap()
+                .count
+        };
+        assert_eq!(count(Some(1.0)), 2);
+        assert_eq!(count(Some(5.0)), 1);
+        assert_eq!(count(Some(200.0)), 2);
+        assert_eq!(count(None), 1);
+        assert_eq!(h.buckets[3].lower_ms, 5.0);
+
+        let all = get_histogram(&db, &QueryFilter::default(), None).unwrap();
+        assert_eq!(all.total, 7);
+    }
+}
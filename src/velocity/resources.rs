@@ -0,0 +1,237 @@
+//! Process resource samples, for correlating latency with resource pressure.
+//!
+//! Every [`sample_interval_secs`] a background task records CPU, memory and
+//! open file descriptors of the supervisor, each running runner and, on
+//! Windows, the web frontend dev server (whatever listens on
+//! [`FRONTEND_PORT`]) into
+//! `resource_samples`, under the service names their spans use.
+//! `/velocity/timeline` reports the per-minute peak of each service's
+//! samples next to its latency. Samples age out with the span rollup.
+//!
+//! CPU is percent of one core (so it can exceed 100), measured since the
+//! previous sample. Open descriptors are read from `/proc` and are `None` on
+//! platforms without it.
+
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use super::db::VelocityDb;
+use super::queries::{bind_params, build_where_clause_on, QueryFilter};
+
+pub const RESOURCE_SAMPLE_SECS_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_RESOURCE_SAMPLE_SECS";
+
+/// `0` turns sampling off.
+pub const DEFAULT_RESOURCE_SAMPLE_SECS: u64 = 15;
+
+/// Port of the web frontend dev server (see `velocity_tests::engine::WEB_FRONTEND_BASE`).
+pub const FRONTEND_PORT: u16 = 3001;
+
+/// Pid of the frontend dev server, by its listening port.
+pub async fn frontend_pid() -> Option<u32> {
+    #[cfg(target_os = "windows")]
+    {
+        crate::process::windows::find_pid_on_port(FRONTEND_PORT).await
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
+pub fn sample_interval_secs() -> u64 {
+    std::env::var(RESOURCE_SAMPLE_SECS_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_RESOURCE_SAMPLE_SECS)
+}
+
+/// A process to sample; `service` matches the service of its spans.
+#[derive(Debug, Clone)]
+pub struct SampleTarget {
+    pub service: String,
+    /// Runner id for runners; the service name otherwise.
+    pub instance: String,
+    pub pid: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceSample {
+    pub ts: String,
+    pub service: String,
+    pub instance: String,
+    pub pid: u32,
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+    pub open_fds: Option<u64>,
+}
+
+/// Keeps the process table between samples, which CPU usage is measured
+/// against.
+pub struct ResourceSampler {
+    system: System,
+}
+
+impl Default for ResourceSampler {
+    fn default() -> Self {
+        Self {
+            system: System::new(),
+        }
+    }
+}
+
+impl ResourceSampler {
+    /// Samples of the targets that are still alive.
+    pub fn sample(&mut self, targets: &[SampleTarget]) -> Vec<ResourceSample> {
+        let pids: Vec<Pid> = targets.iter().map(|t| Pid::from_u32(t.pid)).collect();
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&pids),
+            true,
+            ProcessRefreshKind::new().with_cpu().with_memory(),
+        );
+        let ts = Utc::now().to_rfc3339();
+        targets
+            .iter()
+            .filter_map(|t| {
+                let process = self.system.process(Pid::from_u32(t.pid))?;
+                Some(ResourceSample {
+                    ts: ts.clone(),
+                    service: t.service.clone(),
+                    instance: t.instance.clone(),
+                    pid: t.pid,
+                    cpu_percent: process.cpu_usage() as f64,
+                    memory_bytes: process.memory(),
+                    open_fds: open_fds(t.pid),
+                })
+            })
+            .collect()
+    }
+}
+
+fn open_fds(pid: u32) -> Option<u64> {
+    let entries = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+    Some(entries.count() as u64)
+}
+
+pub fn record(db: &VelocityDb, samples: &[ResourceSample]) -> anyhow::Result<()> {
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO resource_samples
+                (ts, service, instance, pid, cpu_percent, memory_bytes, open_fds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for s in samples {
+            stmt.execute(rusqlite::params![
+                s.ts,
+                s.service,
+                s.instance,
+                s.pid,
+                s.cpu_percent,
+                s.memory_bytes as i64,
+                s.open_fds.map(|n| n as i64),
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Highest readings of one service's processes within a timeline bucket.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResourcePeak {
+    pub cpu_percent: f64,
+    pub memory_bytes: i64,
+    pub open_fds: Option<i64>,
+}
+
+/// Peaks keyed by `(minute, service)`, the minute in the timeline's
+/// `YYYY-MM-DDTHH:MM` form.
+pub(super) fn peaks_by_minute(
+    conn: &Connection,
+    filter: &QueryFilter,
+) -> anyhow::Result<HashMap<(String, String), ResourcePeak>> {
+    let (where_clause, params) = build_where_clause_on(filter, "ts");
+    let sql = format!(
+        "SELECT substr(ts, 1, 16), service, MAX(cpu_percent), MAX(memory_bytes), MAX(open_fds)
+         FROM resource_samples{}
+         GROUP BY 1, 2",
+        where_clause
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let rows = stmt
+        .raw_query()
+        .mapped(|row| {
+            Ok((
+                (row.get(0)?, row.get(1)?),
+                ResourcePeak {
+                    cpu_percent: row.get(2)?,
+                    memory_bytes: row.get(3)?,
+                    open_fds: row.get(4)?,
+                },
+            ))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_own_process_and_skips_dead_ones() {
+        let mut sampler = ResourceSampler::default();
+        let targets = [
+            SampleTarget {
+                service: "supervisor".to_string(),
+                instance: "supervisor".to_string(),
+                pid: std::process::id(),
+            },
+            SampleTarget {
+                service: "runner".to_string(),
+                instance: "gone".to_string(),
+                pid: u32::MAX - 1,
+            },
+        ];
+        let samples = sampler.sample(&targets);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].service, "supervisor");
+        assert!(samples[0].memory_bytes > 0);
+    }
+
+    #[test]
+    fn peaks_group_by_minute_and_service() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityDb::new(dir.path()).unwrap();
+        let sample = |ts: &str, cpu: f64, memory: u64| ResourceSample {
+            ts: ts.to_string(),
+            service: "runner".to_string(),
+            instance: "primary".to_string(),
+            pid: 1,
+            cpu_percent: cpu,
+            memory_bytes: memory,
+            open_fds: None,
+        };
+        record(
+            &db,
+            &[
+                sample("2026-03-01T10:00:05+00:00", 80.0, 100),
+                sample("2026-03-01T10:00:35+00:00", 20.0, 300),
+                sample("2026-03-01T10:01:05+00:00", 5.0, 50),
+            ],
+        )
+        .unwrap();
+
+        let peaks = peaks_by_minute(&db.conn(), &QueryFilter::default()).unwrap();
+        assert_eq!(peaks.len(), 2);
+        let first = &peaks[&("2026-03-01T10:00".to_string(), "runner".to_string())];
+        assert_eq!(first.cpu_percent, 80.0);
+        assert_eq!(first.memory_bytes, 300);
+        assert_eq!(first.open_fds, None);
+    }
+}
@@ -0,0 +1,355 @@
+//! Hourly rollups of old velocity spans.
+//!
+//! Raw spans older than [`RollupPolicy::after_hours`] are folded into
+//! `velocity_rollups` — one row per UTC hour and (service, method, route)
+//! holding count, errors, sum, p50/p95/p99 and max — and deleted. A
+//! background task does this every [`ROLLUP_INTERVAL_SECS`].
+//!
+//! The aggregate queries (`summary`, `endpoints`, `timeline`, `compare`)
+//! merge rollups with raw spans through [`Distribution`]. A rollup is
+//! reconstructed as weighted points at its percentiles — half its count at
+//! p50, 45% at p95, 4% at p99 and 1% at max — so one rollup reproduces its
+//! own percentiles exactly and merged percentiles are close approximations.
+//! Rolled-up hours appear in the timeline as a single `HH:00` bucket, and
+//! `slow` / `trace` only ever see raw spans. Database query spans and resource
+//! samples of the same age are deleted without being rolled up.
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use std::collections::BTreeMap;
+
+use super::db::VelocityDb;
+
+pub const ROLLUP_AFTER_HOURS_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_ROLLUP_AFTER_HOURS";
+
+/// Raw spans are kept for a week unless [`ROLLUP_AFTER_HOURS_ENV`] says
+/// otherwise; `0` turns rollups off.
+pub const DEFAULT_ROLLUP_AFTER_HOURS: u64 = 24 * 7;
+
+pub const ROLLUP_INTERVAL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollupPolicy {
+    pub after_hours: u64,
+}
+
+impl RollupPolicy {
+    pub fn from_env() -> Self {
+        let after_hours = std::env::var(ROLLUP_AFTER_HOURS_ENV)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(DEFAULT_ROLLUP_AFTER_HOURS);
+        Self { after_hours }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.after_hours > 0
+    }
+}
+
+/// Aggregates of one rollup row.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RollupStats {
+    pub count: i64,
+    pub error_count: i64,
+    pub sum_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl RollupStats {
+    fn from_durations(mut durations: Vec<f64>, error_count: i64) -> Self {
+        durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let mut d = Distribution::default();
+        for v in &durations {
+            d.add_raw(*v);
+        }
+        Self {
+            count: durations.len() as i64,
+            error_count,
+            sum_ms: durations.iter().sum(),
+            p50_ms: d.percentile(50.0),
+            p95_ms: d.percentile(95.0),
+            p99_ms: d.percentile(99.0),
+            max_ms: durations.last().copied().unwrap_or(0.0),
+        }
+    }
+
+    /// The weighted points this rollup stands for: `(duration, spans)`.
+    pub fn points(&self) -> [(f64, f64); 4] {
+        let n = self.count.max(0) as f64;
+        [
+            (self.p50_ms, n * 0.50),
+            (self.p95_ms, n * 0.45),
+            (self.p99_ms, n * 0.04),
+            (self.max_ms, n * 0.01),
+        ]
+    }
+
+    /// [`points`](Self::points) as whole span counts adding up to `count`,
+    /// for bucketing.
+    pub fn point_counts(&self) -> Vec<(f64, u64)> {
+        let mut remaining = self.count.max(0) as u64;
+        let points = self.points();
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, (value, weight))| {
+                let n = if i + 1 == points.len() {
+                    remaining
+                } else {
+                    (weight.round() as u64).min(remaining)
+                };
+                remaining -= n;
+                (*value, n)
+            })
+            .collect()
+    }
+
+    /// Fold `other` into `self`; percentiles are re-derived from both.
+    fn merge(&mut self, other: &RollupStats) {
+        let mut d = Distribution::default();
+        d.add_rollup(self);
+        d.add_rollup(other);
+        self.count += other.count;
+        self.error_count += other.error_count;
+        self.sum_ms += other.sum_ms;
+        self.p50_ms = d.percentile(50.0);
+        self.p95_ms = d.percentile(95.0);
+        self.p99_ms = d.percentile(99.0);
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+}
+
+/// Durations from raw spans and rollups, for percentiles over both.
+#[derive(Debug, Clone, Default)]
+pub struct Distribution {
+    /// `(value, weight)`; raw spans weigh 1.
+    points: Vec<(f64, f64)>,
+    count: i64,
+    sum: f64,
+    has_rollups: bool,
+}
+
+impl Distribution {
+    pub fn add_raw(&mut self, value: f64) {
+        self.points.push((value, 1.0));
+        self.count += 1;
+        self.sum += value;
+    }
+
+    pub fn add_rollup(&mut self, stats: &RollupStats) {
+        if stats.count <= 0 {
+            return;
+        }
+        self.points.extend(stats.points());
+        self.count += stats.count;
+        self.sum += stats.sum_ms;
+        self.has_rollups = true;
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    pub fn avg(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+
+    /// Nearest-rank over raw spans alone (the historical behaviour);
+    /// weighted once rollups are mixed in.
+    pub fn percentile(&mut self, p: f64) -> f64 {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        self.points
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        if !self.has_rollups {
+            let n = self.points.len();
+            let idx = ((p / 100.0) * (n as f64 - 1.0)).round() as usize;
+            return self.points[idx.min(n - 1)].0;
+        }
+        let total: f64 = self.points.iter().map(|(_, w)| w).sum();
+        let target = total * p / 100.0;
+        let mut cumulative = 0.0;
+        for (value, weight) in &self.points {
+            cumulative += weight;
+            if cumulative + 1e-9 >= target {
+                return *value;
+            }
+        }
+        self.points.last().map(|(v, _)| *v).unwrap_or(0.0)
+    }
+}
+
+/// Hour bucket of an RFC 3339 timestamp, in the format spans are written
+/// in, so it compares correctly against `start_ts` filters.
+pub fn hour_of(ts: &str) -> Option<String> {
+    let t = DateTime::parse_from_rfc3339(ts).ok()?.with_timezone(&Utc);
+    let hour = t
+        .with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))?;
+    Some(hour.to_rfc3339())
+}
+
+#[derive(Debug, Default)]
+pub struct RollupResult {
+    pub spans_rolled: usize,
+    pub buckets: usize,
+}
+
+type BucketKey = (String, String, String, String);
+
+/// Roll raw spans that started before `now - after_hours` into hourly
+/// buckets and delete them, in one transaction.
+pub fn roll_up(db: &VelocityDb, policy: RollupPolicy) -> anyhow::Result<RollupResult> {
+    let mut result = RollupResult::default();
+    if !policy.is_enabled() {
+        return Ok(result);
+    }
+    let cutoff = (Utc::now() - Duration::hours(policy.after_hours as i64)).to_rfc3339();
+
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    let mut buckets: BTreeMap<BucketKey, (Vec<f64>, i64)> = BTreeMap::new();
+    {
+        let mut stmt = tx.prepare(
+            "SELECT start_ts, service, COALESCE(http_method, ''), COALESCE(http_route, ''),
+                    duration_ms, success
+             FROM velocity_spans WHERE start_ts < ?1",
+        )?;
+        let rows = stmt.query_map([&cutoff], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<f64>>(4)?,
+                row.get::<_, Option<i64>>(5)?.unwrap_or(1) != 0,
+            ))
+        })?;
+        for row in rows {
+            let (start_ts, service, method, route, duration, success) = row?;
+            result.spans_rolled += 1;
+            // Unparseable timestamps can't be bucketed; they are dropped
+            // with the rest.
+            let Some(hour) = hour_of(&start_ts) else {
+                continue;
+            };
+            let entry = buckets.entry((hour, service, method, route)).or_default();
+            if let Some(d) = duration {
+                entry.0.push(d);
+            }
+            if !success {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    {
+        let mut select = tx.prepare(
+            "SELECT count, error_count, sum_ms, p50_ms, p95_ms, p99_ms, max_ms
+             FROM velocity_rollups
+             WHERE hour = ?1 AND service = ?2 AND http_method = ?3 AND http_route = ?4",
+        )?;
+        let mut upsert = tx.prepare(
+            "INSERT OR REPLACE INTO velocity_rollups
+                (hour, service, http_method, http_route, count, error_count, sum_ms,
+                 p50_ms, p95_ms, p99_ms, max_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+        for ((hour, service, method, route), (durations, errors)) in buckets {
+            let mut stats = RollupStats::from_durations(durations, errors);
+            let existing = select
+                .query_map(rusqlite::params![hour, service, method, route], read_stats)?
+                .next()
+                .transpose()?;
+            if let Some(existing) = existing {
+                stats.merge(&existing);
+            }
+            upsert.execute(rusqlite::params![
+                hour,
+                service,
+                method,
+                route,
+                stats.count,
+                stats.error_count,
+                stats.sum_ms,
+                stats.p50_ms,
+                stats.p95_ms,
+                stats.p99_ms,
+                stats.max_ms,
+            ])?;
+            result.buckets += 1;
+        }
+    }
+
+    tx.execute("DELETE FROM velocity_spans WHERE start_ts < ?1", [&cutoff])?;
+    tx.execute(
+        "DELETE FROM velocity_db_spans WHERE start_ts < ?1",
+        [&cutoff],
+    )?;
+    tx.execute("DELETE FROM resource_samples WHERE ts < ?1", [&cutoff])?;
+    tx.commit()?;
+    Ok(result)
+}
+
+/// Reads `count, error_count, sum_ms, p50_ms, p95_ms, p99_ms, max_ms` from
+/// the first seven columns.
+pub fn read_stats(row: &rusqlite::Row<'_>) -> rusqlite::Result<RollupStats> {
+    Ok(RollupStats {
+        count: row.get(0)?,
+        error_count: row.get(1)?,
+        sum_ms: row.get(2)?,
+        p50_ms: row.get(3)?,
+        p95_ms: row.get(4)?,
+        p99_ms: row.get(5)?,
+        max_ms: row.get(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_rollup_keeps_its_percentiles() {
+        let durations: Vec<f64> = (1..=100).map(f64::from).collect();
+        let stats = RollupStats::from_durations(durations.clone(), 3);
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.max_ms, 100.0);
+
+        let mut d = Distribution::default();
+        d.add_rollup(&stats);
+        assert_eq!(d.percentile(50.0), stats.p50_ms);
+        assert_eq!(d.percentile(95.0), stats.p95_ms);
+        assert_eq!(d.percentile(99.0), stats.p99_ms);
+        assert!((d.avg() - 50.5).abs() < 1e-9);
+
+        // Raw-only distributions keep the nearest-rank behaviour.
+        let mut raw = Distribution::default();
+        for v in durations {
+            raw.add_raw(v);
+        }
+        assert_eq!(raw.percentile(50.0), 51.0);
+    }
+
+    #[test]
+    fn hour_bucket_matches_span_timestamp_format() {
+        assert_eq!(
+            hour_of("2026-03-01T10:42:17.123456+00:00").as_deref(),
+            Some("2026-03-01T10:00:00+00:00")
+        );
+        assert_eq!(
+            hour_of("2026-03-01T12:05:00+02:00").as_deref(),
+            Some("2026-03-01T10:00:00+00:00")
+        );
+        assert_eq!(hour_of("not a time"), None);
+    }
+}
@@ -0,0 +1,296 @@
+//! Span search for `GET /velocity/spans/search`.
+//!
+//! Besides the usual time/service filters, spans can be matched on HTTP
+//! method and route, a status code range, an error substring and any key of
+//! the `attributes` JSON (`attr.<key>=<value>`, compared as text). Keys
+//! listed in [`INDEXED_ATTRIBUTES_ENV`] (default [`DEFAULT_INDEXED_ATTRIBUTES`])
+//! get an indexed virtual generated column when the database is opened, so
+//! filtering on them doesn't scan every span's JSON; other keys still work
+//! through `json_extract`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::db::VelocityDb;
+use super::queries::{bind_params, build_where_clause, QueryFilter};
+
+pub const INDEXED_ATTRIBUTES_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_INDEXED_ATTRIBUTES";
+
+pub const DEFAULT_INDEXED_ATTRIBUTES: &[&str] = &["user_id", "workflow_id", "run_id", "session_id"];
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 100;
+pub const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// Query-string prefix of attribute filters.
+const ATTR_PREFIX: &str = "attr.";
+
+/// Attribute keys to index: the defaults plus a comma-separated env list.
+pub fn indexed_attributes() -> Vec<String> {
+    let mut keys: Vec<String> = DEFAULT_INDEXED_ATTRIBUTES
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+    if let Ok(extra) = std::env::var(INDEXED_ATTRIBUTES_ENV) {
+        for key in extra.split(',').map(str::trim) {
+            if is_valid_key(key) && !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+    }
+    keys
+}
+
+/// Attribute keys are spliced into JSON paths and column names, so they are
+/// limited to letters, digits, `_`, `.` and `-`.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 64
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Generated column holding attribute `key`.
+pub fn column_for(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("attr_{}", name.to_ascii_lowercase())
+}
+
+/// JSON path of a top-level attribute; keys may contain dots
+/// (`http.method`), so they are quoted.
+pub fn json_path(key: &str) -> String {
+    format!("$.\"{}\"", key)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SpanSearch {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub service: Option<String>,
+    pub method: Option<String>,
+    pub route: Option<String>,
+    pub status_min: Option<i64>,
+    pub status_max: Option<i64>,
+    pub error_contains: Option<String>,
+    pub attributes: Vec<(String, String)>,
+    pub limit: usize,
+}
+
+impl SpanSearch {
+    /// Parse the query string; unknown keys are rejected so a typo doesn't
+    /// silently widen the search.
+    pub fn from_query(params: HashMap<String, String>) -> Result<Self, String> {
+        let mut search = Self {
+            limit: DEFAULT_SEARCH_LIMIT,
+            ..Default::default()
+        };
+        let number = |key: &str, value: &str| {
+            value
+                .parse::<i64>()
+                .map_err(|_| format!("{} must be an integer", key))
+        };
+        for (key, value) in params {
+            match key.as_str() {
+                "since" => search.since = Some(value),
+                "until" => search.until = Some(value),
+                "service" => search.service = Some(value),
+                "method" => search.method = Some(value.to_ascii_uppercase()),
+                "route" => search.route = Some(value),
+                "status_min" => search.status_min = Some(number(&key, &value)?),
+                "status_max" => search.status_max = Some(number(&key, &value)?),
+                "error_contains" => search.error_contains = Some(value),
+                "limit" => {
+                    search.limit = (number(&key, &value)?.max(1) as usize).min(MAX_SEARCH_LIMIT)
+                }
+                _ => match key.strip_prefix(ATTR_PREFIX) {
+                    Some(attr) if is_valid_key(attr) => {
+                        search.attributes.push((attr.to_string(), value))
+                    }
+                    Some(attr) => return Err(format!("Invalid attribute key '{}'", attr)),
+                    None => return Err(format!("Unknown parameter '{}'", key)),
+                },
+            }
+        }
+        search.attributes.sort();
+        Ok(search)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpanSearchHit {
+    pub id: i64,
+    pub service: String,
+    pub name: String,
+    pub start_ts: String,
+    pub duration_ms: Option<f64>,
+    pub http_method: Option<String>,
+    pub http_route: Option<String>,
+    pub http_status_code: Option<i64>,
+    pub request_id: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub attributes: Option<String>,
+}
+
+/// Matching spans, newest first.
+pub fn search(db: &VelocityDb, search: &SpanSearch) -> anyhow::Result<Vec<SpanSearchHit>> {
+    let indexed = db.indexed_attributes();
+    let conn = db.conn();
+
+    let filter = QueryFilter {
+        since: search.since.clone(),
+        until: search.until.clone(),
+        service: search.service.clone(),
+    };
+    let (where_clause, mut params) = build_where_clause(&filter);
+    let mut sql = format!(
+        "SELECT id, service, name, start_ts, duration_ms, http_method, http_route, \
+         http_status_code, request_id, success, error, attributes FROM velocity_spans{}",
+        if where_clause.is_empty() {
+            " WHERE 1=1"
+        } else {
+            &where_clause
+        }
+    );
+    let mut condition = |sql_fragment: &str, value: String| {
+        params.push(value);
+        sql.push_str(&sql_fragment.replace('?', &format!("?{}", params.len())));
+    };
+    if let Some(method) = &search.method {
+        condition(" AND http_method = ?", method.clone());
+    }
+    if let Some(route) = &search.route {
+        condition(" AND http_route = ?", route.clone());
+    }
+    if let Some(min) = search.status_min {
+        condition(
+            " AND http_status_code >= CAST(? AS INTEGER)",
+            min.to_string(),
+        );
+    }
+    if let Some(max) = search.status_max {
+        condition(
+            " AND http_status_code <= CAST(? AS INTEGER)",
+            max.to_string(),
+        );
+    }
+    if let Some(needle) = &search.error_contains {
+        condition(" AND instr(error, ?) > 0", needle.clone());
+    }
+    for (key, value) in &search.attributes {
+        if indexed.contains(key) {
+            condition(&format!(" AND {} = ?", column_for(key)), value.clone());
+        } else {
+            condition(
+                &format!(
+                    " AND CAST(json_extract(attributes, '{}') AS TEXT) = ?",
+                    json_path(key)
+                ),
+                value.clone(),
+            );
+        }
+    }
+    params.push(search.limit.to_string());
+    sql.push_str(&format!(
+        " ORDER BY start_ts DESC LIMIT CAST(?{} AS INTEGER)",
+        params.len()
+    ));
+
+    let mut stmt = conn.prepare(&sql)?;
+    bind_params(&mut stmt, &params)?;
+    let hits = stmt
+        .raw_query()
+        .mapped(|row| {
+            Ok(SpanSearchHit {
+                id: row.get(0)?,
+                service: row.get(1)?,
+                name: row.get(2)?,
+                start_ts: row.get(3)?,
+                duration_ms: row.get(4)?,
+                http_method: row.get(5)?,
+                http_route: row.get(6)?,
+                http_status_code: row.get(7)?,
+                request_id: row.get(8)?,
+                success: row.get::<_, Option<i64>>(9)?.unwrap_or(1) != 0,
+                error: row.get(10)?,
+                attributes: row.get(11)?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_search_params() {
+        let search = SpanSearch::from_query(query(&[
+            ("service", "backend"),
+            ("method", "post"),
+            ("status_min", "500"),
+            ("attr.workflow.id", "wf-1"),
+            ("attr.user_id", "42"),
+            ("limit", "5000"),
+        ]))
+        .unwrap();
+        assert_eq!(search.method.as_deref(), Some("POST"));
+        assert_eq!(search.status_min, Some(500));
+        assert_eq!(search.limit, MAX_SEARCH_LIMIT);
+        assert_eq!(
+            search.attributes,
+            vec![
+                ("user_id".to_string(), "42".to_string()),
+                ("workflow.id".to_string(), "wf-1".to_string()),
+            ]
+        );
+
+        assert!(SpanSearch::from_query(query(&[("status_min", "5xx")])).is_err());
+        assert!(SpanSearch::from_query(query(&[("attr.a'b", "x")])).is_err());
+        assert!(SpanSearch::from_query(query(&[("servce", "x")])).is_err());
+    }
+
+    #[test]
+    fn searches_indexed_and_plain_attributes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityDb::new(dir.path()).unwrap();
+        for (attrs, status) in [
+            (r#"{"user_id":42,"workflow.id":"wf-1"}"#, 200),
+            (r#"{"user_id":7,"workflow.id":"wf-1"}"#, 503),
+        ] {
+            db.conn()
+                .execute(
+                    "INSERT INTO velocity_spans
+                        (service, name, start_ts, http_status_code, attributes, ingested_at)
+                     VALUES ('backend', 'HTTP GET', '2026-03-01T10:00:00+00:00', ?1, ?2, '')",
+                    rusqlite::params![status, attrs],
+                )
+                .unwrap();
+        }
+        assert_eq!(column_for("workflow.id"), "attr_workflow_id");
+
+        let find = |pairs: &[(&str, &str)]| {
+            search(&db, &SpanSearch::from_query(query(pairs)).unwrap())
+                .unwrap()
+                .len()
+        };
+        assert_eq!(find(&[("attr.user_id", "42")]), 1);
+        assert_eq!(find(&[("attr.workflow.id", "wf-1")]), 2);
+        assert_eq!(
+            find(&[("attr.workflow.id", "wf-1"), ("status_min", "500")]),
+            1
+        );
+        assert_eq!(find(&[("attr.missing", "x")]), 0);
+    }
+}
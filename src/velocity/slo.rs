@@ -0,0 +1,363 @@
+//! Per-route service level objectives for `GET /velocity/slo`.
+//!
+//! SLOs are kept in the supervisor settings file (`velocity_slos`, replaced
+//! through `PUT /velocity/slo`). Each one names a route pattern — exact, or a
+//! prefix ending in `*` — optionally narrowed to a service and method, with a
+//! target p95 and/or error rate. Every [`SLO_INTERVAL_SECS`] the evaluator
+//! measures each SLO over the raw spans of the last [`SLO_WINDOW_ENV`]
+//! minutes and records violations in `velocity_slo_violations`: a row opens
+//! when an SLO starts failing, keeps the worst values seen, and closes once
+//! the SLO passes again (or is removed).
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::db::VelocityDb;
+use super::live::route_matches;
+use super::rollup::Distribution;
+
+pub const SLO_WINDOW_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_SLO_WINDOW_MINS";
+
+pub const DEFAULT_SLO_WINDOW_MINS: i64 = 60;
+
+pub const SLO_INTERVAL_SECS: u64 = 300;
+
+/// Violations returned by `GET /velocity/slo`, newest first.
+pub const VIOLATION_HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SloDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub service: Option<String>,
+    #[serde(default)]
+    pub http_method: Option<String>,
+    /// Exact route, or a prefix when it ends in `*` (`/api/v1/runs*`).
+    pub route: String,
+    #[serde(default)]
+    pub target_p95_ms: Option<f64>,
+    /// Highest acceptable fraction of failed requests, `0.0..=1.0`.
+    #[serde(default)]
+    pub target_error_rate: Option<f64>,
+}
+
+impl SloDefinition {
+    fn matches(&self, service: &str, method: &str, route: &str) -> bool {
+        self.service.as_ref().is_none_or(|s| s == service)
+            && self
+                .http_method
+                .as_ref()
+                .is_none_or(|m| m.eq_ignore_ascii_case(method))
+            && route_matches(&self.route, route)
+    }
+}
+
+/// Reject SLO sets that can't be evaluated: blank or duplicate names, empty
+/// routes, no target, or targets out of range.
+pub fn validate(slos: &[SloDefinition]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for slo in slos {
+        if slo.name.trim().is_empty() {
+            return Err("SLO name must not be empty".to_string());
+        }
+        if !names.insert(slo.name.as_str()) {
+            return Err(format!("Duplicate SLO name '{}'", slo.name));
+        }
+        if slo.route.is_empty() {
+            return Err(format!("SLO '{}' has no route", slo.name));
+        }
+        if slo.target_p95_ms.is_none() && slo.target_error_rate.is_none() {
+            return Err(format!(
+                "SLO '{}' needs target_p95_ms and/or target_error_rate",
+                slo.name
+            ));
+        }
+        if slo.target_p95_ms.is_some_and(|t| t <= 0.0) {
+            return Err(format!("SLO '{}': target_p95_ms must be > 0", slo.name));
+        }
+        if slo
+            .target_error_rate
+            .is_some_and(|r| !(0.0..=1.0).contains(&r))
+        {
+            return Err(format!(
+                "SLO '{}': target_error_rate must be between 0 and 1",
+                slo.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn window_mins() -> i64 {
+    std::env::var(SLO_WINDOW_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .filter(|m: &i64| *m > 0)
+        .unwrap_or(DEFAULT_SLO_WINDOW_MINS)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloStatus {
+    #[serde(flatten)]
+    pub slo: SloDefinition,
+    pub request_count: i64,
+    pub p95_ms: Option<f64>,
+    pub error_rate: Option<f64>,
+    /// `None` when no request matched in the window.
+    pub compliant: Option<bool>,
+}
+
+/// A raw span as the evaluator sees it.
+struct Sample {
+    service: String,
+    method: String,
+    route: String,
+    duration_ms: Option<f64>,
+    success: bool,
+}
+
+fn measure(slo: &SloDefinition, samples: &[Sample]) -> SloStatus {
+    let mut durations = Distribution::default();
+    let mut count = 0i64;
+    let mut errors = 0i64;
+    for s in samples
+        .iter()
+        .filter(|s| slo.matches(&s.service, &s.method, &s.route))
+    {
+        count += 1;
+        if !s.success {
+            errors += 1;
+        }
+        if let Some(d) = s.duration_ms {
+            durations.add_raw(d);
+        }
+    }
+    let p95_ms = (durations.count() > 0).then(|| durations.percentile(95.0));
+    let error_rate = (count > 0).then(|| errors as f64 / count as f64);
+    let compliant = (count > 0).then(|| {
+        let p95_ok = match (slo.target_p95_ms, p95_ms) {
+            (Some(target), Some(p95)) => p95 <= target,
+            _ => true,
+        };
+        let errors_ok = match (slo.target_error_rate, error_rate) {
+            (Some(target), Some(rate)) => rate <= target,
+            _ => true,
+        };
+        p95_ok && errors_ok
+    });
+    SloStatus {
+        slo: slo.clone(),
+        request_count: count,
+        p95_ms,
+        error_rate,
+        compliant,
+    }
+}
+
+/// Measure every SLO over the raw spans of the last `window_mins`.
+pub fn evaluate(
+    db: &VelocityDb,
+    slos: &[SloDefinition],
+    window_mins: i64,
+) -> anyhow::Result<Vec<SloStatus>> {
+    if slos.is_empty() {
+        return Ok(Vec::new());
+    }
+    let since = (Utc::now() - Duration::minutes(window_mins)).to_rfc3339();
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT service, http_method, http_route, duration_ms, success FROM velocity_spans
+         WHERE start_ts >= ?1 AND http_method IS NOT NULL AND http_route IS NOT NULL",
+    )?;
+    let samples = stmt
+        .query_map([&since], |row| {
+            Ok(Sample {
+                service: row.get(0)?,
+                method: row.get(1)?,
+                route: row.get(2)?,
+                duration_ms: row.get(3)?,
+                success: row.get::<_, Option<i64>>(4)?.unwrap_or(1) != 0,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(slos.iter().map(|slo| measure(slo, &samples)).collect())
+}
+
+#[derive(Debug, Serialize)]
+pub struct SloViolation {
+    pub id: i64,
+    pub name: String,
+    pub started_at: String,
+    /// `None` while the violation is ongoing.
+    pub ended_at: Option<String>,
+    pub worst_p95_ms: Option<f64>,
+    pub worst_error_rate: Option<f64>,
+}
+
+/// Open, extend or close violation rows for one evaluation. Returns the
+/// SLOs that just started failing. SLOs without traffic in the window keep
+/// their current state.
+pub fn record(db: &VelocityDb, statuses: &[SloStatus]) -> anyhow::Result<Vec<SloStatus>> {
+    let now = Utc::now().to_rfc3339();
+    let mut started = Vec::new();
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    let open: Vec<String> = tx
+        .prepare("SELECT name FROM velocity_slo_violations WHERE ended_at IS NULL")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    for status in statuses {
+        let name = &status.slo.name;
+        let is_open = open.contains(name);
+        match status.compliant {
+            Some(false) if is_open => {
+                tx.execute(
+                    "UPDATE velocity_slo_violations SET
+                         worst_p95_ms = MAX(COALESCE(worst_p95_ms, ?2), COALESCE(?2, worst_p95_ms)),
+                         worst_error_rate = MAX(COALESCE(worst_error_rate, ?3), COALESCE(?3, worst_error_rate))
+                     WHERE name = ?1 AND ended_at IS NULL",
+                    rusqlite::params![name, status.p95_ms, status.error_rate],
+                )?;
+            }
+            Some(false) => {
+                tx.execute(
+                    "INSERT INTO velocity_slo_violations
+                         (name, started_at, worst_p95_ms, worst_error_rate)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    rusqlite::params![name, &now, status.p95_ms, status.error_rate],
+                )?;
+                started.push(status.clone());
+            }
+            Some(true) if is_open => {
+                tx.execute(
+                    "UPDATE velocity_slo_violations SET ended_at = ?2
+                     WHERE name = ?1 AND ended_at IS NULL",
+                    rusqlite::params![name, &now],
+                )?;
+            }
+            _ => {}
+        }
+    }
+    // Removed SLOs can't recover; close what they left open.
+    for name in open
+        .iter()
+        .filter(|n| !statuses.iter().any(|s| &s.slo.name == *n))
+    {
+        tx.execute(
+            "UPDATE velocity_slo_violations SET ended_at = ?2
+             WHERE name = ?1 AND ended_at IS NULL",
+            rusqlite::params![name, &now],
+        )?;
+    }
+    tx.commit()?;
+    Ok(started)
+}
+
+/// Recorded violations, newest first.
+pub fn violations(db: &VelocityDb, limit: usize) -> anyhow::Result<Vec<SloViolation>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, name, started_at, ended_at, worst_p95_ms, worst_error_rate
+         FROM velocity_slo_violations ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt
+        .query_map([limit as i64], |row| {
+            Ok(SloViolation {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                started_at: row.get(2)?,
+                ended_at: row.get(3)?,
+                worst_p95_ms: row.get(4)?,
+                worst_error_rate: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slo(route: &str, p95: Option<f64>, errors: Option<f64>) -> SloDefinition {
+        SloDefinition {
+            name: route.to_string(),
+            service: Some("backend".to_string()),
+            http_method: None,
+            route: route.to_string(),
+            target_p95_ms: p95,
+            target_error_rate: errors,
+        }
+    }
+
+    fn sample(route: &str, duration_ms: f64, success: bool) -> Sample {
+        Sample {
+            service: "backend".to_string(),
+            method: "GET".to_string(),
+            route: route.to_string(),
+            duration_ms: Some(duration_ms),
+            success,
+        }
+    }
+
+    #[test]
+    fn measures_p95_and_error_rate_per_pattern() {
+        let samples: Vec<Sample> = (1..=20)
+            .map(|i| sample("/api/v1/runs/{id}", i as f64 * 10.0, i != 20))
+            .chain(std::iter::once(sample("/health", 5000.0, false)))
+            .collect();
+
+        let status = measure(&slo("/api/v1/runs*", Some(200.0), Some(0.1)), &samples);
+        assert_eq!(status.request_count, 20);
+        assert_eq!(status.p95_ms, Some(190.0));
+        assert_eq!(status.error_rate, Some(0.05));
+        assert_eq!(status.compliant, Some(true));
+
+        let status = measure(&slo("/api/v1/runs*", Some(150.0), None), &samples);
+        assert_eq!(status.compliant, Some(false));
+        let status = measure(&slo("/api/v1/runs*", None, Some(0.01)), &samples);
+        assert_eq!(status.compliant, Some(false));
+        assert_eq!(
+            measure(&slo("/other", Some(1.0), None), &samples).compliant,
+            None
+        );
+    }
+
+    #[test]
+    fn tracks_violation_episodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityDb::new(dir.path()).unwrap();
+        let status = |compliant: Option<bool>, p95: f64| SloStatus {
+            slo: slo("/runs", Some(100.0), None),
+            request_count: 10,
+            p95_ms: Some(p95),
+            error_rate: Some(0.0),
+            compliant,
+        };
+
+        assert_eq!(record(&db, &[status(Some(false), 150.0)]).unwrap().len(), 1);
+        assert!(record(&db, &[status(Some(false), 300.0)])
+            .unwrap()
+            .is_empty());
+        assert!(record(&db, &[status(None, 0.0)]).unwrap().is_empty());
+        let v = violations(&db, 10).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].worst_p95_ms, Some(300.0));
+        assert!(v[0].ended_at.is_none());
+
+        record(&db, &[status(Some(true), 50.0)]).unwrap();
+        assert!(violations(&db, 10).unwrap()[0].ended_at.is_some());
+        assert_eq!(record(&db, &[status(Some(false), 150.0)]).unwrap().len(), 1);
+        assert_eq!(violations(&db, 10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        assert!(validate(&[slo("/a", Some(100.0), None), slo("/b", None, Some(0.01))]).is_ok());
+        assert!(validate(&[slo("/a", None, None)]).is_err());
+        assert!(validate(&[slo("/a", Some(100.0), None), slo("/a", Some(1.0), None)]).is_err());
+        assert!(validate(&[slo("/a", None, Some(2.0))]).is_err());
+    }
+}
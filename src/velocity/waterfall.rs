@@ -0,0 +1,259 @@
+//! Parent/child tree of a request's spans for
+//! `GET /velocity/trace/{request_id}/waterfall`.
+//!
+//! Spans are nested under their `parent_span_id`; a span whose parent isn't
+//! part of the trace becomes a root. Each node carries its offset from the
+//! start of the trace and its self time — its duration minus the time
+//! covered by its children (overlapping children are counted once), so the
+//! frontend → backend → DB split of a slow request is visible.
+
+use chrono::DateTime;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use super::queries::TraceSpan;
+
+#[derive(Debug, Serialize)]
+pub struct Waterfall {
+    pub request_id: String,
+    /// From the earliest span start to the latest span end.
+    pub total_duration_ms: f64,
+    pub roots: Vec<WaterfallNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WaterfallNode {
+    #[serde(flatten)]
+    pub span: TraceSpan,
+    /// `None` when the span's start time can't be parsed.
+    pub offset_ms: Option<f64>,
+    pub self_time_ms: Option<f64>,
+    pub children: Vec<WaterfallNode>,
+}
+
+fn millis(ts: &str) -> Option<f64> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.timestamp_micros() as f64 / 1000.0)
+}
+
+/// `(start, end)` in epoch milliseconds.
+fn interval(span: &TraceSpan) -> Option<(f64, f64)> {
+    let start = millis(&span.start_ts)?;
+    let end = match (span.duration_ms, span.end_ts.as_deref().and_then(millis)) {
+        (Some(d), _) => start + d,
+        (None, Some(end)) => end,
+        (None, None) => return None,
+    };
+    Some((start, end.max(start)))
+}
+
+/// Length of the union of `intervals` clipped to `bounds`.
+fn covered(mut intervals: Vec<(f64, f64)>, bounds: (f64, f64)) -> f64 {
+    intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut total = 0.0;
+    let mut current: Option<(f64, f64)> = None;
+    for (start, end) in intervals {
+        let (start, end) = (start.max(bounds.0), end.min(bounds.1));
+        if end <= start {
+            continue;
+        }
+        current = match current {
+            Some((s, e)) if start <= e => Some((s, e.max(end))),
+            Some((s, e)) => {
+                total += e - s;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((s, e)) = current {
+        total += e - s;
+    }
+    total
+}
+
+/// Nest `spans` (in start order, as `get_trace` returns them).
+pub fn build(request_id: &str, spans: Vec<TraceSpan>) -> Waterfall {
+    let intervals: Vec<Option<(f64, f64)>> = spans.iter().map(interval).collect();
+    let trace_start = intervals.iter().flatten().map(|i| i.0).reduce(f64::min);
+    let trace_end = intervals.iter().flatten().map(|i| i.1).reduce(f64::max);
+    let total_duration_ms = match (trace_start, trace_end) {
+        (Some(s), Some(e)) => e - s,
+        _ => 0.0,
+    };
+
+    let by_span_id: HashMap<&str, usize> = spans
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.span_id.as_deref().map(|id| (id, i)))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); spans.len()];
+    let mut roots = Vec::new();
+    for (i, span) in spans.iter().enumerate() {
+        match span
+            .parent_span_id
+            .as_deref()
+            .and_then(|p| by_span_id.get(p))
+        {
+            Some(&parent) if parent != i => children[parent].push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    // Self time of every span, before the spans are moved into nodes.
+    let self_times: Vec<Option<f64>> = (0..spans.len())
+        .map(|i| {
+            let duration = spans[i].duration_ms?;
+            let child_time = match intervals[i] {
+                Some(bounds) => covered(
+                    children[i].iter().filter_map(|&c| intervals[c]).collect(),
+                    bounds,
+                ),
+                // Without timestamps, assume the children ran back to back.
+                None => children[i]
+                    .iter()
+                    .filter_map(|&c| spans[c].duration_ms)
+                    .sum(),
+            };
+            Some((duration - child_time).max(0.0))
+        })
+        .collect();
+
+    let mut slots: Vec<Option<TraceSpan>> = spans.into_iter().map(Some).collect();
+    let mut visited = HashSet::new();
+    let mut nodes: Vec<WaterfallNode> = roots
+        .iter()
+        .filter_map(|&i| {
+            node(
+                i,
+                &mut slots,
+                &children,
+                &intervals,
+                &self_times,
+                trace_start,
+                &mut visited,
+            )
+        })
+        .collect();
+    // Spans caught in a parent cycle are unreachable from any root; show
+    // them at the top level rather than dropping them.
+    for i in 0..slots.len() {
+        if let Some(n) = node(
+            i,
+            &mut slots,
+            &children,
+            &intervals,
+            &self_times,
+            trace_start,
+            &mut visited,
+        ) {
+            nodes.push(n);
+        }
+    }
+
+    Waterfall {
+        request_id: request_id.to_string(),
+        total_duration_ms,
+        roots: nodes,
+    }
+}
+
+fn node(
+    i: usize,
+    slots: &mut [Option<TraceSpan>],
+    children: &[Vec<usize>],
+    intervals: &[Option<(f64, f64)>],
+    self_times: &[Option<f64>],
+    trace_start: Option<f64>,
+    visited: &mut HashSet<usize>,
+) -> Option<WaterfallNode> {
+    if !visited.insert(i) {
+        return None;
+    }
+    let span = slots[i].take()?;
+    let kids = children[i]
+        .iter()
+        .filter_map(|&c| {
+            node(
+                c,
+                slots,
+                children,
+                intervals,
+                self_times,
+                trace_start,
+                visited,
+            )
+        })
+        .collect();
+    Some(WaterfallNode {
+        span,
+        offset_ms: intervals[i].zip(trace_start).map(|((s, _), t)| s - t),
+        self_time_ms: self_times[i],
+        children: kids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(id: &str, parent: Option<&str>, start: &str, duration_ms: f64) -> TraceSpan {
+        TraceSpan {
+            id: 0,
+            service: "backend".to_string(),
+            name: id.to_string(),
+            span_id: Some(id.to_string()),
+            parent_span_id: parent.map(str::to_string),
+            start_ts: start.to_string(),
+            end_ts: None,
+            duration_ms: Some(duration_ms),
+            http_method: None,
+            http_route: None,
+            http_status_code: None,
+            success: true,
+            error: None,
+            attributes: None,
+        }
+    }
+
+    #[test]
+    fn nests_spans_and_computes_self_time() {
+        let spans = vec![
+            span("front", None, "2026-03-01T10:00:00.000+00:00", 500.0),
+            span("api", Some("front"), "2026-03-01T10:00:00.050+00:00", 300.0),
+            span("db1", Some("api"), "2026-03-01T10:00:00.100+00:00", 100.0),
+            // Overlaps db1; only the extra 50ms counts against the parent.
+            span("db2", Some("api"), "2026-03-01T10:00:00.150+00:00", 100.0),
+            span(
+                "orphan",
+                Some("gone"),
+                "2026-03-01T10:00:00.600+00:00",
+                10.0,
+            ),
+        ];
+        let w = build("req-1", spans);
+        assert_eq!(w.total_duration_ms, 610.0);
+        assert_eq!(w.roots.len(), 2);
+
+        let front = &w.roots[0];
+        assert_eq!(front.offset_ms, Some(0.0));
+        assert_eq!(front.self_time_ms, Some(200.0));
+        let api = &front.children[0];
+        assert_eq!(api.offset_ms, Some(50.0));
+        assert_eq!(api.self_time_ms, Some(150.0));
+        assert_eq!(api.children.len(), 2);
+        assert_eq!(w.roots[1].span.name, "orphan");
+    }
+
+    #[test]
+    fn keeps_spans_in_parent_cycles() {
+        let spans = vec![
+            span("a", Some("b"), "2026-03-01T10:00:00+00:00", 10.0),
+            span("b", Some("a"), "2026-03-01T10:00:00+00:00", 10.0),
+        ];
+        let w = build("req-2", spans);
+        assert_eq!(w.roots.len(), 1);
+        assert_eq!(w.roots[0].children.len(), 1);
+    }
+}
@@ -0,0 +1,225 @@
+//! Batched, off-request writes of OTLP spans.
+//!
+//! `POST /velocity/otlp` hands decoded spans to a [`SpanWriter`] instead of
+//! inserting them itself. A single writer task drains the bounded queue and
+//! stores up to [`WRITE_BATCH_SPANS`] spans per transaction, flushing a
+//! partial batch [`WRITE_FLUSH_MS`] after its first span arrived. When the
+//! queue is full, submitters wait for room — the backpressure is counted in
+//! [`STATS`], which `/velocity/ingest/stats` and `/metrics` report.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use super::db::VelocityDb;
+use super::otlp::{self, OtlpSpan};
+
+/// Spans the queue holds before submitters have to wait.
+pub const WRITE_QUEUE_CAPACITY: usize = 10_000;
+
+pub const WRITE_BATCH_SPANS: usize = 100;
+
+pub const WRITE_FLUSH_MS: u64 = 500;
+
+/// Counters of the process's span writer.
+pub struct WriterStats {
+    enqueued: AtomicU64,
+    stored: AtomicU64,
+    skipped: AtomicU64,
+    rejected: AtomicU64,
+    batches: AtomicU64,
+    failed_batches: AtomicU64,
+    backpressure_waits: AtomicU64,
+    queue_depth: AtomicU64,
+    last_batch_micros: AtomicU64,
+}
+
+pub static STATS: WriterStats = WriterStats {
+    enqueued: AtomicU64::new(0),
+    stored: AtomicU64::new(0),
+    skipped: AtomicU64::new(0),
+    rejected: AtomicU64::new(0),
+    batches: AtomicU64::new(0),
+    failed_batches: AtomicU64::new(0),
+    backpressure_waits: AtomicU64::new(0),
+    queue_depth: AtomicU64::new(0),
+    last_batch_micros: AtomicU64::new(0),
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WriterStatsSnapshot {
+    pub queue_depth: u64,
+    pub queue_capacity: usize,
+    pub enqueued: u64,
+    pub stored: u64,
+    /// Neither HTTP nor database spans.
+    pub skipped: u64,
+    /// Failed to insert, including every span of a failed batch.
+    pub rejected: u64,
+    pub batches: u64,
+    pub failed_batches: u64,
+    /// Submissions that found the queue full and had to wait.
+    pub backpressure_waits: u64,
+    pub last_batch_ms: f64,
+}
+
+impl WriterStats {
+    pub fn snapshot(&self) -> WriterStatsSnapshot {
+        WriterStatsSnapshot {
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            queue_capacity: WRITE_QUEUE_CAPACITY,
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            stored: self.stored.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+            failed_batches: self.failed_batches.load(Ordering::Relaxed),
+            backpressure_waits: self.backpressure_waits.load(Ordering::Relaxed),
+            last_batch_ms: self.last_batch_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SpanWriter {
+    tx: mpsc::Sender<OtlpSpan>,
+}
+
+impl SpanWriter {
+    /// Start the writer task. It runs until every `SpanWriter` clone is
+    /// dropped, flushing what is left in the queue.
+    pub fn spawn(db: Arc<VelocityDb>) -> Self {
+        let (tx, rx) = mpsc::channel(WRITE_QUEUE_CAPACITY);
+        tokio::spawn(run(db, rx));
+        Self { tx }
+    }
+
+    /// Queue `spans` for writing, waiting while the queue is full.
+    pub async fn submit(&self, spans: Vec<OtlpSpan>) -> anyhow::Result<()> {
+        let mut waited = false;
+        for span in spans {
+            let span = match self.tx.try_send(span) {
+                Ok(()) => {
+                    self.enqueued();
+                    continue;
+                }
+                Err(mpsc::error::TrySendError::Full(span)) => span,
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    anyhow::bail!("velocity span writer has stopped")
+                }
+            };
+            if !waited {
+                STATS.backpressure_waits.fetch_add(1, Ordering::Relaxed);
+                waited = true;
+            }
+            self.tx
+                .send(span)
+                .await
+                .map_err(|_| anyhow::anyhow!("velocity span writer has stopped"))?;
+            self.enqueued();
+        }
+        Ok(())
+    }
+
+    fn enqueued(&self) {
+        STATS.enqueued.fetch_add(1, Ordering::Relaxed);
+        let depth = self.tx.max_capacity() - self.tx.capacity();
+        STATS.queue_depth.store(depth as u64, Ordering::Relaxed);
+    }
+}
+
+async fn run(db: Arc<VelocityDb>, mut rx: mpsc::Receiver<OtlpSpan>) {
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(WRITE_FLUSH_MS);
+        while batch.len() < WRITE_BATCH_SPANS {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(span)) => batch.push(span),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        STATS.queue_depth.store(rx.len() as u64, Ordering::Relaxed);
+        write_batch(db.clone(), batch).await;
+    }
+}
+
+async fn write_batch(db: Arc<VelocityDb>, batch: Vec<OtlpSpan>) {
+    let len = batch.len() as u64;
+    let started = Instant::now();
+    let result = tokio::task::spawn_blocking(move || otlp::store(&db, &batch)).await;
+    STATS
+        .last_batch_micros
+        .store(started.elapsed().as_micros() as u64, Ordering::Relaxed);
+    STATS.batches.fetch_add(1, Ordering::Relaxed);
+    match result {
+        Ok(Ok(r)) => {
+            STATS.stored.fetch_add(r.stored as u64, Ordering::Relaxed);
+            STATS.skipped.fetch_add(r.skipped as u64, Ordering::Relaxed);
+            STATS
+                .rejected
+                .fetch_add(r.rejected as u64, Ordering::Relaxed);
+            if r.rejected > 0 {
+                tracing::warn!("Velocity span writer: {} span(s) rejected", r.rejected);
+            }
+        }
+        Ok(Err(e)) => {
+            STATS.failed_batches.fetch_add(1, Ordering::Relaxed);
+            STATS.rejected.fetch_add(len, Ordering::Relaxed);
+            tracing::error!("Velocity span batch of {} failed: {}", len, e);
+        }
+        Err(e) => {
+            STATS.failed_batches.fetch_add(1, Ordering::Relaxed);
+            STATS.rejected.fetch_add(len, Ordering::Relaxed);
+            tracing::error!("Velocity span writer panicked: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn span(i: usize) -> OtlpSpan {
+        OtlpSpan {
+            service: "backend".to_string(),
+            trace_id: format!("{:032x}", i),
+            span_id: format!("{:016x}", i),
+            parent_span_id: None,
+            name: "GET /items".to_string(),
+            start_ts: "2026-03-01T10:00:00+00:00".to_string(),
+            end_ts: None,
+            duration_ms: Some(5.0),
+            attributes: json!({"http.method": "GET", "http.route": "/items"}),
+            success: true,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_submitted_spans_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(VelocityDb::new(dir.path()).unwrap());
+        let writer = SpanWriter::spawn(db.clone());
+        writer
+            .submit((0..WRITE_BATCH_SPANS + 5).map(span).collect())
+            .await
+            .unwrap();
+
+        let count = || {
+            db.conn()
+                .query_row("SELECT COUNT(*) FROM velocity_spans", [], |r| {
+                    r.get::<_, i64>(0)
+                })
+                .unwrap()
+        };
+        // The full batch goes at once; the remainder after the flush delay.
+        let deadline = Instant::now() + Duration::from_millis(WRITE_FLUSH_MS * 10);
+        while count() < (WRITE_BATCH_SPANS + 5) as i64 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(count(), (WRITE_BATCH_SPANS + 5) as i64);
+    }
+}
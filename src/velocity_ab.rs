@@ -0,0 +1,169 @@
+//! A/B fix strategies for the velocity improvement loop.
+//!
+//! With `ab_strategies` set, an iteration doesn't trust a single fix: each
+//! [`FixStrategy`] gets its own branch of the `qontinui-web` checkout, cut
+//! from the same commit, where the fix agent works and its changes are
+//! committed. The dev servers are restarted on each branch and the velocity
+//! tests run against it; the checkout then goes back to the base branch,
+//! which is fast-forwarded to the better-scoring branch. The losing branch
+//! is left in place for inspection. The checkout must be clean and on a
+//! branch, otherwise the iteration falls back to a single fix.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::velocity_checkpoint::git;
+
+/// Branches are named `{BRANCH_PREFIX}{loop}-{iteration}-{strategy}`.
+pub const BRANCH_PREFIX: &str = "velocity-ab/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixStrategy {
+    /// The single highest-impact change, kept as small as possible.
+    Focused,
+    /// Every page below target, in one pass.
+    Broad,
+}
+
+impl FixStrategy {
+    pub const ALL: [FixStrategy; 2] = [FixStrategy::Focused, FixStrategy::Broad];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Focused => "focused",
+            Self::Broad => "broad",
+        }
+    }
+
+    /// `prompt` with this strategy's instructions appended.
+    pub fn apply(self, prompt: &str) -> String {
+        let instructions = match self {
+            Self::Focused => {
+                "Make ONE change: pick the single issue with the largest expected score gain \
+                 across all pages and fix only that, as narrowly as possible. Leave every other \
+                 issue alone."
+            }
+            Self::Broad => {
+                "Work through every page below the target score, applying the fix for its \
+                 bottleneck to each of them in this pass."
+            }
+        };
+        format!("{}\n## Strategy\n\n{}\n", prompt, instructions)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyOutcome {
+    pub strategy: FixStrategy,
+    pub branch: String,
+    pub fix_summary: Option<String>,
+    /// Velocity test run measured on the branch.
+    pub run_id: Option<String>,
+    pub score: Option<f64>,
+    /// Why the strategy has no score.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbComparison {
+    pub base_branch: String,
+    pub outcomes: Vec<StrategyOutcome>,
+    /// `None` when no strategy produced a score.
+    pub winner: Option<FixStrategy>,
+}
+
+pub fn branch_name(loop_id: &str, iteration: u32, strategy: FixStrategy) -> String {
+    let short = loop_id.get(..8).unwrap_or(loop_id);
+    format!(
+        "{}{}-{}-{}",
+        BRANCH_PREFIX,
+        short,
+        iteration,
+        strategy.as_str()
+    )
+}
+
+/// The best-scoring outcome; ties go to the earlier strategy.
+pub fn pick_winner(outcomes: &[StrategyOutcome]) -> Option<&StrategyOutcome> {
+    let mut best: Option<&StrategyOutcome> = None;
+    for o in outcomes {
+        let Some(score) = o.score else { continue };
+        if best.is_none_or(|b| score > b.score.unwrap_or(f64::MIN)) {
+            best = Some(o);
+        }
+    }
+    best
+}
+
+/// The branch the checkout is on, provided it has no changes.
+pub async fn base_branch(repo: &Path) -> Result<String, String> {
+    let status = git(repo, &["status", "--porcelain"]).await?;
+    if !status.is_empty() {
+        return Err("checkout has uncommitted changes".to_string());
+    }
+    let branch = git(repo, &["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .await
+        .map_err(|_| "checkout is not on a branch".to_string())?;
+    Ok(branch)
+}
+
+/// Create `branch` at `base` and switch to it.
+pub async fn start_branch(repo: &Path, branch: &str, base: &str) -> Result<(), String> {
+    git(repo, &["switch", "-c", branch, base]).await.map(drop)
+}
+
+/// Commit whatever the fix agent left uncommitted.
+pub async fn commit_all(repo: &Path, message: &str) -> Result<(), String> {
+    git(repo, &["add", "-A"]).await?;
+    if git(repo, &["diff", "--cached", "--quiet"]).await.is_err() {
+        git(repo, &["commit", "-q", "-m", message]).await?;
+    }
+    Ok(())
+}
+
+/// Drop anything left on the current branch and switch to `branch`.
+pub async fn switch_to(repo: &Path, branch: &str) -> Result<(), String> {
+    git(repo, &["reset", "-q", "--hard"]).await?;
+    git(repo, &["clean", "-fdq"]).await?;
+    git(repo, &["switch", branch]).await.map(drop)
+}
+
+/// Fast-forward the current branch to `winner` and delete `winner`.
+pub async fn adopt(repo: &Path, winner: &str) -> Result<(), String> {
+    git(repo, &["merge", "--ff-only", "-q", winner]).await?;
+    git(repo, &["branch", "-D", winner]).await.map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(strategy: FixStrategy, score: Option<f64>) -> StrategyOutcome {
+        StrategyOutcome {
+            strategy,
+            branch: branch_name("0123456789", 2, strategy),
+            fix_summary: None,
+            run_id: None,
+            score,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn winner_is_best_scored_strategy() {
+        let outcomes = [
+            outcome(FixStrategy::Focused, Some(71.0)),
+            outcome(FixStrategy::Broad, Some(74.5)),
+        ];
+        assert_eq!(pick_winner(&outcomes).unwrap().strategy, FixStrategy::Broad);
+        assert_eq!(outcomes[0].branch, "velocity-ab/01234567-2-focused");
+
+        let failed = [
+            outcome(FixStrategy::Focused, Some(60.0)),
+            outcome(FixStrategy::Broad, None),
+        ];
+        assert_eq!(pick_winner(&failed).unwrap().strategy, FixStrategy::Focused);
+        assert!(pick_winner(&[outcome(FixStrategy::Broad, None)]).is_none());
+    }
+}
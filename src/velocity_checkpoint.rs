@@ -0,0 +1,300 @@
+//! Git checkpoints of the web checkout around velocity fix-agent runs.
+//!
+//! With `git_checkpoint` set on the improvement loop, the state of
+//! `qontinui-web` is recorded before each fix agent runs: `HEAD`, any
+//! uncommitted changes (as a `git stash create` commit, which leaves the
+//! working tree alone) and the untracked files with their contents, which
+//! the stash commit does not cover. Reverting resets to `HEAD`, re-applies
+//! the uncommitted changes with what was staged still staged, writes the
+//! untracked files back as they were and deletes the ones the agent added,
+//! so work that was in the checkout beforehand survives.
+//!
+//! The same snapshot is taken before every fix regardless, to diff what the
+//! agent changed for review.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// The checkout the fix agent edits, next to the runner repo.
+pub fn web_repo_dir(config: &crate::config::SupervisorConfig) -> PathBuf {
+    let runner_repo = config.runner_npm_dir();
+    let workspace_root = runner_repo.parent().unwrap_or(&runner_repo);
+    workspace_root.join("qontinui-web")
+}
+
+#[derive(Debug, Clone)]
+pub struct FixCheckpoint {
+    repo: PathBuf,
+    pub head: String,
+    /// `git stash create` commit of the uncommitted changes, if any.
+    dirty: Option<String>,
+    /// Untracked files and their contents; `reset --hard` leaves these
+    /// alone, so the agent's edits to them are undone from here.
+    untracked: HashMap<String, Vec<u8>>,
+}
+
+impl FixCheckpoint {
+    pub async fn take(repo: &Path) -> Result<Self, String> {
+        let head = git(repo, &["rev-parse", "HEAD"]).await?;
+        let dirty = git(repo, &["stash", "create"]).await?;
+        let mut untracked = HashMap::new();
+        for file in untracked_files(repo).await? {
+            let contents = tokio::fs::read(repo.join(&file))
+                .await
+                .map_err(|e| format!("failed to snapshot {}: {}", file, e))?;
+            untracked.insert(file, contents);
+        }
+        Ok(Self {
+            repo: repo.to_path_buf(),
+            head,
+            dirty: (!dirty.is_empty()).then_some(dirty),
+            untracked,
+        })
+    }
+
+    /// Put the checkout back the way it was; returns the untracked files
+    /// that were removed. Untracked files that were already there are
+    /// restored to their checkpointed contents.
+    pub async fn revert(&self) -> Result<Vec<String>, String> {
+        let added: Vec<String> = untracked_files(&self.repo)
+            .await?
+            .into_iter()
+            .filter(|f| !self.untracked.contains_key(f))
+            .collect();
+
+        git(&self.repo, &["reset", "--hard", &self.head]).await?;
+        if let Some(stash) = &self.dirty {
+            git(&self.repo, &["stash", "apply", "--index", stash]).await?;
+        }
+        for (file, contents) in &self.untracked {
+            let path = self.repo.join(file);
+            if tokio::fs::read(&path).await.ok().as_ref() == Some(contents) {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                let _ = tokio::fs::create_dir_all(parent).await;
+            }
+            tokio::fs::write(&path, contents)
+                .await
+                .map_err(|e| format!("failed to restore {}: {}", file, e))?;
+        }
+        let mut removed = Vec::new();
+        for file in added {
+            match tokio::fs::remove_file(self.repo.join(&file)).await {
+                Ok(()) => removed.push(file),
+                Err(e) => tracing::warn!("Failed to remove {} after revert: {}", file, e),
+            }
+        }
+        removed.sort();
+        Ok(removed)
+    }
+
+    /// Patch of everything changed in the checkout since the checkpoint,
+    /// committed or not, including files added since. The whole worktree,
+    /// since a frontend fix may also touch backend handlers.
+    pub async fn diff(&self) -> Result<String, String> {
+        let base = self.dirty.as_deref().unwrap_or(&self.head);
+        let mut patch = git(&self.repo, &["diff", "--no-color", "--no-ext-diff", base]).await?;
+        let mut added: Vec<String> = untracked_files(&self.repo)
+            .await?
+            .into_iter()
+            .filter(|f| !self.untracked.contains_key(f))
+            .collect();
+        added.sort();
+        for file in added {
+            let file_patch = new_file_diff(&self.repo, &file).await?;
+            if !patch.is_empty() {
+                patch.push('\n');
+            }
+            patch.push_str(&file_patch);
+        }
+        if !patch.is_empty() {
+            patch.push('\n');
+        }
+        Ok(patch)
+    }
+}
+
+/// `git diff --no-index` of an untracked file against nothing; it exits 1
+/// when there is a difference, which is always.
+async fn new_file_diff(repo: &Path, file: &str) -> Result<String, String> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(crate::config::git_timeout_secs()),
+        Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["diff", "--no-color", "--no-index", "--", "/dev/null", file])
+            .output(),
+    )
+    .await
+    .map_err(|_| format!("diff of {} timed out", file))?
+    .map_err(|e| format!("failed to spawn git diff for {}: {}", file, e))?;
+    match output.status.code() {
+        Some(0) | Some(1) => Ok(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+        _ => Err(format!(
+            "diff of {} failed: {}",
+            file,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+async fn untracked_files(repo: &Path) -> Result<HashSet<String>, String> {
+    let out = git(repo, &["ls-files", "--others", "--exclude-standard"]).await?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Run git in `repo`, returning trimmed stdout.
+pub(crate) async fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(crate::config::git_timeout_secs()),
+        Command::new("git").arg("-C").arg(repo).args(args).output(),
+    )
+    .await
+    .map_err(|_| format!("`git {}` timed out", args.join(" ")))?
+    .map_err(|e| format!("failed to spawn `git {}`: {}", args.join(" "), e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(repo: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[tokio::test]
+    async fn revert_drops_agent_changes_and_keeps_prior_work() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        sh(repo, &["init", "-q"]);
+        sh(repo, &["config", "user.email", "dev@example.com"]);
+        sh(repo, &["config", "user.name", "dev"]);
+        std::fs::write(repo.join("page.tsx"), "v1").unwrap();
+        sh(repo, &["add", "."]);
+        sh(repo, &["commit", "-qm", "init"]);
+        // Work in progress before the fix
+        std::fs::write(repo.join("page.tsx"), "wip").unwrap();
+        std::fs::write(repo.join("notes.md"), "mine").unwrap();
+
+        let checkpoint = FixCheckpoint::take(repo).await.unwrap();
+
+        // The fix agent edits, adds a file and commits
+        std::fs::write(repo.join("page.tsx"), "agent").unwrap();
+        std::fs::write(repo.join("lazy.tsx"), "agent").unwrap();
+        sh(repo, &["add", "page.tsx"]);
+        sh(repo, &["commit", "-qm", "perf fix"]);
+
+        let removed = checkpoint.revert().await.unwrap();
+        assert_eq!(removed, vec!["lazy.tsx"]);
+        assert_eq!(
+            std::fs::read_to_string(repo.join("page.tsx")).unwrap(),
+            "wip"
+        );
+        assert!(repo.join("notes.md").exists());
+        let head = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&head.stdout).trim(),
+            checkpoint.head
+        );
+    }
+
+    #[tokio::test]
+    async fn diff_covers_only_the_agents_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        sh(repo, &["init", "-q"]);
+        sh(repo, &["config", "user.email", "dev@example.com"]);
+        sh(repo, &["config", "user.name", "dev"]);
+        std::fs::create_dir_all(repo.join("frontend")).unwrap();
+        std::fs::create_dir_all(repo.join("backend")).unwrap();
+        std::fs::write(repo.join("frontend/page.tsx"), "v1\n").unwrap();
+        std::fs::write(repo.join("backend/handler.py"), "v1\n").unwrap();
+        std::fs::write(repo.join("README.md"), "readme\n").unwrap();
+        sh(repo, &["add", "."]);
+        sh(repo, &["commit", "-qm", "init"]);
+        std::fs::write(repo.join("frontend/page.tsx"), "wip\n").unwrap();
+
+        let checkpoint = FixCheckpoint::take(repo).await.unwrap();
+        assert_eq!(checkpoint.diff().await.unwrap(), "");
+
+        std::fs::write(repo.join("frontend/page.tsx"), "agent\n").unwrap();
+        std::fs::write(repo.join("frontend/lazy.tsx"), "new\n").unwrap();
+        std::fs::write(repo.join("backend/handler.py"), "agent\n").unwrap();
+        sh(repo, &["commit", "-qam", "perf fix"]);
+
+        let patch = checkpoint.diff().await.unwrap();
+        assert!(patch.contains("-wip\n+agent"));
+        assert!(patch.contains("+++ b/frontend/lazy.tsx"));
+        assert!(patch.contains("+++ b/backend/handler.py"));
+        assert!(!patch.contains("README.md"));
+        assert!(patch.ends_with('\n'));
+    }
+
+    #[tokio::test]
+    async fn revert_restores_untracked_files_and_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        sh(repo, &["init", "-q"]);
+        sh(repo, &["config", "user.email", "dev@example.com"]);
+        sh(repo, &["config", "user.name", "dev"]);
+        std::fs::write(repo.join("page.tsx"), "v1").unwrap();
+        sh(repo, &["add", "."]);
+        sh(repo, &["commit", "-qm", "init"]);
+        // Staged work and an untracked file before the fix
+        std::fs::write(repo.join("page.tsx"), "staged").unwrap();
+        sh(repo, &["add", "page.tsx"]);
+        std::fs::create_dir_all(repo.join("drafts")).unwrap();
+        std::fs::write(repo.join("drafts/notes.md"), "mine").unwrap();
+
+        let checkpoint = FixCheckpoint::take(repo).await.unwrap();
+
+        // The fix agent rewrites the untracked file and unstages the work
+        std::fs::write(repo.join("drafts/notes.md"), "agent").unwrap();
+        sh(repo, &["reset", "-q"]);
+
+        checkpoint.revert().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("drafts/notes.md")).unwrap(),
+            "mine"
+        );
+        let staged = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["diff", "--cached", "--name-only"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&staged.stdout).trim(), "page.tsx");
+
+        // A deleted untracked file comes back too
+        std::fs::remove_dir_all(repo.join("drafts")).unwrap();
+        checkpoint.revert().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("drafts/notes.md")).unwrap(),
+            "mine"
+        );
+    }
+}
@@ -1,15 +1,24 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::watch;
+use tokio::sync::{broadcast, oneshot, watch};
 use tracing::{error, info, warn};
 
-use crate::config::resolve_model_id;
+use crate::config::{dev_orchestrator_url, normalize_base_url, resolve_model_id};
 use crate::log_capture::{LogLevel, LogSource};
 use crate::state::SharedState;
+use crate::velocity::db::VelocityDb;
+use crate::velocity::queries::{self, QueryFilter, SlowRequest};
+use crate::velocity_ab::{self, AbComparison, FixStrategy, StrategyOutcome};
+use crate::velocity_checkpoint::{self, FixCheckpoint};
+use crate::velocity_improvement_progress::{self as progress, ImprovementProgressEvent};
 use crate::velocity_tests::db::VelocityTestDb;
-use crate::velocity_tests::VelocityTestResult;
+use crate::velocity_tests::engine::VelocityRunOptions;
+use crate::velocity_tests::engine::{BACKEND_API_BASE, WEB_FRONTEND_BASE};
+use crate::velocity_tests::tests::TestCase;
+use crate::velocity_tests::{VelocityTestResult, VelocityTestRun};
 
 // ============================================================================
 // Config
@@ -27,18 +36,75 @@ fn default_fix_timeout() -> u64 {
     600
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// How far back to look for backend spans matching a "Backend Slow" page.
+const BACKEND_SPAN_WINDOW_HOURS: i64 = 24;
+
+/// Slow spans included in the fix prompt per "Backend Slow" page.
+const BACKEND_SPANS_PER_PAGE: usize = 5;
+
+/// Bottlenecks a backend fix can address.
+const BACKEND_BOTTLENECKS: &[&str] = &["Backend Slow", "TTFB Slow"];
+
+/// Score drop from one iteration to the next that ends the loop.
+const REGRESSION_POINTS: f64 = 5.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VelocityImprovementConfig {
     #[serde(default = "default_max_iterations")]
     pub max_iterations: u32,
     #[serde(default = "default_target_score")]
     pub target_score: f64,
+    /// Targets for individual pages, by test name, overriding
+    /// `target_score`. When set, the loop only ends on target once every
+    /// page has reached its own.
+    #[serde(default)]
+    pub page_targets: HashMap<String, f64>,
     pub provider: Option<String>,
     pub model: Option<String>,
     #[serde(default = "default_fix_timeout")]
     pub fix_timeout_secs: u64,
     #[serde(default)]
     pub restart_backend: bool,
+    /// Dev-server orchestrator to restart through, overriding
+    /// `QONTINUI_SUPERVISOR_DEV_ORCHESTRATOR_URL` for this run.
+    #[serde(default)]
+    pub dev_orchestrator_url: Option<String>,
+    /// Checkpoint the web checkout before each fix and revert the last fix
+    /// when the next run regresses ([`crate::velocity_checkpoint`]).
+    #[serde(default)]
+    pub git_checkpoint: bool,
+    /// When most slow pages are "Backend Slow" or "TTFB Slow", have the fix
+    /// agent work on the backend and restart only the backend afterwards.
+    #[serde(default)]
+    pub backend_fix: bool,
+    /// Experimental: try each [`FixStrategy`] on its own branch, measure
+    /// both and keep the better one ([`crate::velocity_ab`]).
+    #[serde(default)]
+    pub ab_strategies: bool,
+    /// Hold each fix until it is approved through
+    /// `POST /velocity-improvement/approve`; the prompt waits at
+    /// `GET /velocity-improvement/pending-fix`.
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
+impl VelocityImprovementConfig {
+    /// The score `page` has to reach.
+    pub fn target_for(&self, page: &str) -> f64 {
+        self.page_targets
+            .get(page)
+            .copied()
+            .unwrap_or(self.target_score)
+    }
+
+    /// Pages of `results` still below their target.
+    fn pages_below_target(&self, results: &[VelocityTestResult]) -> Vec<String> {
+        results
+            .iter()
+            .filter(|r| r.score.unwrap_or(0.0) < self.target_for(&r.test_name))
+            .map(|r| r.test_name.clone())
+            .collect()
+    }
 }
 
 // ============================================================================
@@ -55,6 +121,17 @@ pub struct VelocityImprovementState {
     pub error: Option<String>,
     pub iterations: Vec<VelocityImprovementIteration>,
     pub stop_tx: Option<watch::Sender<bool>>,
+    /// Iterations started by every loop since the supervisor started; not
+    /// reset between runs (exported by `GET /metrics`).
+    pub iterations_started: u64,
+    /// Live progress of the current loop, for `GET /velocity-improvement/stream`.
+    pub progress_tx: broadcast::Sender<ImprovementProgressEvent>,
+    /// Fix waiting for approval with `require_approval`.
+    pub pending_fix: Option<PendingFix>,
+    /// Answers the pending fix: `true` to apply it, `false` to end the loop.
+    pub approval_tx: Option<oneshot::Sender<bool>>,
+    /// Pid of the fix agent while one is running.
+    pub fix_agent_pid: Option<u32>,
 }
 
 impl VelocityImprovementState {
@@ -69,6 +146,11 @@ impl VelocityImprovementState {
             error: None,
             iterations: Vec::new(),
             stop_tx: None,
+            iterations_started: 0,
+            progress_tx: progress::channel(),
+            pending_fix: None,
+            approval_tx: None,
+            fix_agent_pid: None,
         }
     }
 }
@@ -89,9 +171,12 @@ pub enum VelocityImprovementPhase {
     Idle,
     RunningTests,
     Analyzing,
+    AwaitingApproval,
     Fixing,
     RestartingFrontend,
     WaitingFrontend,
+    RestartingBackend,
+    WaitingBackend,
     Complete,
     Stopped,
     Error,
@@ -103,9 +188,12 @@ impl std::fmt::Display for VelocityImprovementPhase {
             Self::Idle => write!(f, "idle"),
             Self::RunningTests => write!(f, "running_tests"),
             Self::Analyzing => write!(f, "analyzing"),
+            Self::AwaitingApproval => write!(f, "awaiting_approval"),
             Self::Fixing => write!(f, "fixing"),
             Self::RestartingFrontend => write!(f, "restarting_frontend"),
             Self::WaitingFrontend => write!(f, "waiting_frontend"),
+            Self::RestartingBackend => write!(f, "restarting_backend"),
+            Self::WaitingBackend => write!(f, "waiting_backend"),
             Self::Complete => write!(f, "complete"),
             Self::Stopped => write!(f, "stopped"),
             Self::Error => write!(f, "error"),
@@ -113,7 +201,7 @@ impl std::fmt::Display for VelocityImprovementPhase {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VelocityImprovementIteration {
     pub iteration: u32,
     pub started_at: String,
@@ -122,11 +210,58 @@ pub struct VelocityImprovementIteration {
     pub overall_score: Option<f64>,
     pub per_page_scores: Vec<PageScore>,
     pub fix_applied: bool,
+    /// Which code the fix agent worked on; `None` when no fix was tried.
+    #[serde(default)]
+    pub fix_mode: Option<FixMode>,
     pub fix_summary: Option<String>,
     pub exit_reason: Option<String>,
+    /// Both strategies' results when the iteration ran an A/B fix.
+    #[serde(default)]
+    pub ab_comparison: Option<AbComparison>,
+    /// Commit the web checkout was reset to after this iteration's run
+    /// regressed.
+    pub reverted_to: Option<String>,
+    /// Patch of what the fix agent changed; served by
+    /// `GET /velocity-improvement/iterations/{n}/diff` rather than with the
+    /// iteration.
+    #[serde(default, skip_serializing)]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixMode {
+    Frontend,
+    Backend,
+}
+
+impl FixMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Frontend => "frontend",
+            Self::Backend => "backend",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "frontend" => Some(Self::Frontend),
+            "backend" => Some(Self::Backend),
+            _ => None,
+        }
+    }
 }
 
+/// A generated fix held for review.
 #[derive(Debug, Clone, Serialize)]
+pub struct PendingFix {
+    pub iteration: u32,
+    pub fix_mode: FixMode,
+    pub prompt: String,
+    pub requested_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageScore {
     pub name: String,
     pub score: f64,
@@ -149,20 +284,138 @@ pub struct VelocityImprovementHistory {
     pub iterations: Vec<VelocityImprovementIteration>,
 }
 
+/// One run of the loop with its iterations, as stored in the velocity test
+/// database.
+#[derive(Debug, Clone, Serialize)]
+pub struct VelocityImprovementLoop {
+    pub id: String,
+    pub started_at: String,
+    pub max_iterations: u32,
+    pub target_score: f64,
+    /// When the last recorded iteration finished.
+    pub completed_at: Option<String>,
+    pub exit_reason: Option<String>,
+    pub first_score: Option<f64>,
+    pub last_score: Option<f64>,
+    pub iterations: Vec<VelocityImprovementIteration>,
+}
+
+/// Where a loop is, persisted at the start of each measurement so a loop cut
+/// short by a supervisor restart can be resumed from there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopProgress {
+    /// The iteration whose test run comes next.
+    pub iteration: u32,
+    pub previous_score: Option<f64>,
+    pub no_improvement_streak: u32,
+}
+
+impl LoopProgress {
+    fn start() -> Self {
+        Self {
+            iteration: 1,
+            previous_score: None,
+            no_improvement_streak: 0,
+        }
+    }
+}
+
+/// A stored loop that never ended, with what it needs to carry on.
+#[derive(Debug, Clone)]
+pub struct ResumableLoop {
+    pub loop_id: String,
+    pub config: VelocityImprovementConfig,
+    pub progress: LoopProgress,
+    /// Iterations recorded before the interruption.
+    pub iterations: Vec<VelocityImprovementIteration>,
+}
+
 // ============================================================================
 // Main loop
 // ============================================================================
 
+/// Run a new loop, or carry on with `resume` from the test run of the
+/// iteration it was interrupted in.
 pub async fn run_velocity_improvement_loop(
     db: Arc<VelocityTestDb>,
+    span_db: Option<Arc<VelocityDb>>,
     state: SharedState,
     config: VelocityImprovementConfig,
     stop_rx: watch::Receiver<bool>,
+    resume: Option<(String, LoopProgress)>,
 ) {
-    let mut no_improvement_streak: u32 = 0;
-    let mut previous_score: Option<f64> = None;
+    let (loop_id, progress) = match resume {
+        Some(resume) => resume,
+        None => {
+            let loop_id = uuid::Uuid::new_v4().to_string();
+            if let Err(e) = db.insert_improvement_loop(
+                &loop_id,
+                &Utc::now().to_rfc3339(),
+                config.max_iterations,
+                config.target_score,
+            ) {
+                warn!("Failed to record velocity improvement loop: {}", e);
+            }
+            (loop_id, LoopProgress::start())
+        }
+    };
 
-    for iteration in 1..=config.max_iterations {
+    let state_clone = state.clone();
+    run_loop(
+        db.clone(),
+        span_db,
+        state,
+        config,
+        stop_rx,
+        &loop_id,
+        progress,
+    )
+    .await;
+
+    {
+        let vi = state_clone.velocity_improvement.read().await;
+        progress::emit(
+            &vi.progress_tx,
+            ImprovementProgressEvent::LoopFinished {
+                phase: vi.phase.clone(),
+                iterations: vi.iterations.len(),
+                exit_reason: vi.iterations.last().and_then(|it| it.exit_reason.clone()),
+                error: vi.error.clone(),
+            },
+        );
+    }
+
+    // However it ended, it is no longer resumable
+    if let Err(e) = db.finish_improvement_loop(&loop_id, &Utc::now().to_rfc3339()) {
+        warn!("Failed to mark velocity improvement loop finished: {}", e);
+    }
+}
+
+async fn run_loop(
+    db: Arc<VelocityTestDb>,
+    span_db: Option<Arc<VelocityDb>>,
+    state: SharedState,
+    config: VelocityImprovementConfig,
+    stop_rx: watch::Receiver<bool>,
+    loop_id: &str,
+    progress: LoopProgress,
+) {
+    let mut no_improvement_streak = progress.no_improvement_streak;
+    let mut previous_score = progress.previous_score;
+    // State of the web checkout before the last fix agent ran
+    let mut checkpoint: Option<FixCheckpoint> = None;
+    let save_progress = |iteration: u32, previous_score: Option<f64>, streak: u32| {
+        let progress = LoopProgress {
+            iteration,
+            previous_score,
+            no_improvement_streak: streak,
+        };
+        if let Err(e) = db.save_improvement_progress(loop_id, &config, &progress) {
+            warn!("Failed to save velocity improvement progress: {}", e);
+        }
+    };
+
+    for iteration in progress.iteration..=config.max_iterations {
         if *stop_rx.borrow() {
             set_phase(&state, VelocityImprovementPhase::Stopped).await;
             log(
@@ -175,11 +428,13 @@ pub async fn run_velocity_improvement_loop(
         }
 
         let iter_started = Utc::now().to_rfc3339();
+        save_progress(iteration, previous_score, no_improvement_streak);
 
         // Update state
         {
             let mut vi = state.velocity_improvement.write().await;
             vi.current_iteration = iteration;
+            vi.iterations_started += 1;
         }
 
         log(
@@ -203,48 +458,9 @@ pub async fn run_velocity_improvement_loop(
         )
         .await;
 
-        // Check that velocity tests aren't already running
-        {
-            let vt = state.velocity_tests.read().await;
-            if vt.running {
-                set_error(
-                    &state,
-                    "Velocity tests are already running from another source",
-                )
-                .await;
-                return;
-            }
-        }
-
-        // Create a stop channel for velocity tests
-        let (vt_stop_tx, vt_stop_rx) = watch::channel(false);
-
-        // Mark velocity tests as running
-        {
-            let mut vt = state.velocity_tests.write().await;
-            vt.running = true;
-            vt.stop_tx = Some(vt_stop_tx);
-        }
-
-        // Run tests (this blocks until complete)
-        let db_clone = db.clone();
-        let state_clone = state.clone();
-
-        // Spawn tests as a background task
-        let test_handle = tokio::spawn(async move {
-            crate::velocity_tests::engine::run_velocity_tests(db_clone, state_clone, vt_stop_rx)
-                .await;
-        });
-
-        // Poll for completion, checking our stop signal periodically
-        loop {
-            if *stop_rx.borrow() {
-                // Stop signal received — cancel velocity tests
-                let mut vt = state.velocity_tests.write().await;
-                if let Some(tx) = vt.stop_tx.take() {
-                    let _ = tx.send(true);
-                }
-                let _ = test_handle.await;
+        let run = match run_velocity_tests_once(&db, &state, &stop_rx).await {
+            Ok(run) => run,
+            Err(LoopInterrupt::Stopped) => {
                 set_phase(&state, VelocityImprovementPhase::Stopped).await;
                 log(
                     &state,
@@ -255,53 +471,41 @@ pub async fn run_velocity_improvement_loop(
                 finalize(&state).await;
                 return;
             }
-
-            if test_handle.is_finished() {
-                break;
+            Err(LoopInterrupt::Failed(msg)) => {
+                set_error(&state, msg).await;
+                return;
             }
-
-            tokio::time::sleep(Duration::from_secs(2)).await;
-        }
+        };
 
         // ------------------------------------------------------------------
         // Phase 2: Analyze results
         // ------------------------------------------------------------------
         set_phase(&state, VelocityImprovementPhase::Analyzing).await;
 
-        // Find the most recent completed run
-        let runs = db.list_runs().unwrap_or_default();
-        let latest_run = runs.into_iter().find(|r| r.status == "completed");
-
-        let (run_id, overall_score, per_page_scores, results) = match latest_run {
-            Some(run) => {
-                let results = db.get_results_for_run(&run.id).unwrap_or_default();
-                let page_scores: Vec<PageScore> = results
-                    .iter()
-                    .map(|r| PageScore {
-                        name: r.test_name.clone(),
-                        score: r.score.unwrap_or(0.0),
-                        bottleneck: r
-                            .bottleneck
-                            .clone()
-                            .unwrap_or_else(|| "Unknown".to_string()),
-                    })
-                    .collect();
-                let score = run.overall_score.unwrap_or(0.0);
-                (Some(run.id.clone()), Some(score), page_scores, results)
-            }
-            None => {
-                set_error(&state, "No completed velocity test run found").await;
-                return;
-            }
-        };
-
-        let score = overall_score.unwrap_or(0.0);
+        let results = db.get_results_for_run(&run.id).unwrap_or_default();
+        let per_page_scores: Vec<PageScore> = results
+            .iter()
+            .map(|r| PageScore {
+                name: r.test_name.clone(),
+                score: r.score.unwrap_or(0.0),
+                bottleneck: r
+                    .bottleneck
+                    .clone()
+                    .unwrap_or_else(|| "Unknown".to_string()),
+            })
+            .collect();
+        let run_id = Some(run.id.clone());
+        let score = run.overall_score.unwrap_or(0.0);
+        let pages_below = config.pages_below_target(&results);
         log(
             &state,
             LogLevel::Info,
             format!(
-                "Iteration {} score: {:.1} (target: {:.1})",
-                iteration, score, config.target_score
+                "Iteration {} score: {:.1} (target: {:.1}), {} page(s) below target",
+                iteration,
+                score,
+                config.target_score,
+                pages_below.len()
             ),
         )
         .await;
@@ -310,6 +514,7 @@ pub async fn run_velocity_improvement_loop(
         let exit_reason = check_exit_conditions(
             score,
             config.target_score,
+            (!config.page_targets.is_empty()).then_some(pages_below.as_slice()),
             iteration,
             config.max_iterations,
             previous_score,
@@ -325,16 +530,40 @@ pub async fn run_velocity_improvement_loop(
             overall_score: Some(score),
             per_page_scores,
             fix_applied: false,
+            fix_mode: None,
             fix_summary: None,
             exit_reason: exit_reason.clone(),
+            ab_comparison: None,
+            reverted_to: None,
+            diff: None,
         };
 
         if let Some(reason) = &exit_reason {
-            iter_result.completed_at = Some(Utc::now().to_rfc3339());
-            {
-                let mut vi = state.velocity_improvement.write().await;
-                vi.iterations.push(iter_result);
+            let regressed = previous_score.is_some_and(|prev| score < prev - REGRESSION_POINTS);
+            if let (true, Some(cp)) = (regressed, checkpoint.as_ref()) {
+                match cp.revert().await {
+                    Ok(removed) => {
+                        log(
+                            &state,
+                            LogLevel::Warn,
+                            format!(
+                                "Reverted the last fix (web checkout reset to {}, {} new file(s) removed)",
+                                cp.head,
+                                removed.len()
+                            ),
+                        )
+                        .await;
+                        iter_result.reverted_to = Some(cp.head.clone());
+                    }
+                    Err(e) => {
+                        warn!("Failed to revert the last velocity fix: {}", e);
+                        iter_result.exit_reason =
+                            Some(format!("{} (revert failed: {})", reason, e));
+                    }
+                }
             }
+            iter_result.completed_at = Some(Utc::now().to_rfc3339());
+            record_iteration(&state, &db, loop_id, iter_result).await;
             log(&state, LogLevel::Info, format!("Exiting: {}", reason)).await;
             set_phase(&state, VelocityImprovementPhase::Complete).await;
             finalize(&state).await;
@@ -354,12 +583,163 @@ pub async fn run_velocity_improvement_loop(
         // ------------------------------------------------------------------
         // Phase 3: Fix issues
         // ------------------------------------------------------------------
+        let test_cases = db.list_test_cases().unwrap_or_default();
+        let fix_mode = match dominant_bottleneck(&results, |page| config.target_for(page)) {
+            Some(b) if config.backend_fix && BACKEND_BOTTLENECKS.contains(&b) => {
+                log(
+                    &state,
+                    LogLevel::Info,
+                    format!("Most slow pages are {}; fixing the backend", b),
+                )
+                .await;
+                FixMode::Backend
+            }
+            _ => FixMode::Frontend,
+        };
+        iter_result.fix_mode = Some(fix_mode);
+
+        let prompt = match fix_mode {
+            FixMode::Frontend => {
+                let backend_spans = collect_backend_spans(
+                    span_db.as_deref(),
+                    &test_cases,
+                    &results,
+                    &["Backend Slow"],
+                );
+                if !backend_spans.is_empty() {
+                    log(
+                        &state,
+                        LogLevel::Info,
+                        format!(
+                            "Including backend spans for {} Backend Slow page(s) in fix prompt",
+                            backend_spans.len()
+                        ),
+                    )
+                    .await;
+                }
+                build_velocity_fix_prompt(
+                    &results,
+                    &backend_spans,
+                    iteration,
+                    previous_score,
+                    |page| config.target_for(page),
+                )
+            }
+            FixMode::Backend => {
+                let backend_spans = collect_backend_spans(
+                    span_db.as_deref(),
+                    &test_cases,
+                    &results,
+                    BACKEND_BOTTLENECKS,
+                );
+                build_backend_fix_prompt(
+                    &results,
+                    &test_cases,
+                    &backend_spans,
+                    iteration,
+                    previous_score,
+                    |page| config.target_for(page),
+                )
+            }
+        };
+
+        if config.require_approval {
+            match await_approval(&state, iteration, fix_mode, &prompt, &stop_rx).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    let reason = "Fix rejected by reviewer".to_string();
+                    iter_result.exit_reason = Some(reason.clone());
+                    iter_result.completed_at = Some(Utc::now().to_rfc3339());
+                    record_iteration(&state, &db, loop_id, iter_result).await;
+                    log(&state, LogLevel::Info, format!("Exiting: {}", reason)).await;
+                    set_phase(&state, VelocityImprovementPhase::Complete).await;
+                    finalize(&state).await;
+                    return;
+                }
+                Err(_) => {
+                    iter_result.completed_at = Some(Utc::now().to_rfc3339());
+                    record_iteration(&state, &db, loop_id, iter_result).await;
+                    set_phase(&state, VelocityImprovementPhase::Stopped).await;
+                    log(
+                        &state,
+                        LogLevel::Info,
+                        "Velocity improvement stopped by user",
+                    )
+                    .await;
+                    finalize(&state).await;
+                    return;
+                }
+            }
+        }
         set_phase(&state, VelocityImprovementPhase::Fixing).await;
 
-        let prompt =
-            build_velocity_fix_prompt(&results, iteration, previous_score, config.target_score);
+        // Snapshot the checkout to diff the fix against (and to revert to)
+        let snapshot = {
+            let repo = velocity_checkpoint::web_repo_dir(&state.config);
+            match FixCheckpoint::take(&repo).await {
+                Ok(cp) => Some(cp),
+                Err(e) => {
+                    warn!("Failed to checkpoint {}: {}", repo.display(), e);
+                    None
+                }
+            }
+        };
+        if config.git_checkpoint {
+            checkpoint = snapshot.clone();
+        }
 
-        let fix_result = spawn_fix_agent(&state, &prompt, &config, &stop_rx).await;
+        let ab_base = if config.ab_strategies {
+            let repo = velocity_checkpoint::web_repo_dir(&state.config);
+            match velocity_ab::base_branch(&repo).await {
+                Ok(base) => Some((repo, base)),
+                Err(e) => {
+                    log(
+                        &state,
+                        LogLevel::Warn,
+                        format!("Skipping the A/B fix, {}: {}", e, repo.display()),
+                    )
+                    .await;
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let fix_result = match ab_base {
+            Some((repo, base)) => {
+                let ab = run_ab_fix(
+                    &db, &state, &config, &stop_rx, &repo, &base, &prompt, fix_mode, loop_id,
+                    iteration,
+                )
+                .await;
+                match ab {
+                    Ok(comparison) => {
+                        let winner = velocity_ab::pick_winner(&comparison.outcomes).cloned();
+                        iter_result.ab_comparison = Some(comparison);
+                        match winner {
+                            Some(w) => Ok(format!(
+                                "[{} strategy, scored {:.1}] {}",
+                                w.strategy.as_str(),
+                                w.score.unwrap_or(0.0),
+                                w.fix_summary.unwrap_or_default()
+                            )),
+                            None => Err("no A/B strategy produced a scored run".to_string()),
+                        }
+                    }
+                    Err(LoopInterrupt::Stopped) => Err("Stop requested during A/B fix".to_string()),
+                    Err(LoopInterrupt::Failed(msg)) => Err(msg),
+                }
+            }
+            None => spawn_fix_agent(&state, &prompt, &config, &stop_rx, None).await,
+        };
+
+        if let Some(snapshot) = &snapshot {
+            match snapshot.diff().await {
+                Ok(diff) => iter_result.diff = Some(diff),
+                Err(e) => warn!("Failed to diff the velocity fix: {}", e),
+            }
+        }
 
         match fix_result {
             Ok(summary) => {
@@ -369,10 +749,7 @@ pub async fn run_velocity_improvement_loop(
             Err(e) => {
                 if e.contains("Stop requested") {
                     iter_result.completed_at = Some(Utc::now().to_rfc3339());
-                    {
-                        let mut vi = state.velocity_improvement.write().await;
-                        vi.iterations.push(iter_result);
-                    }
+                    record_iteration(&state, &db, loop_id, iter_result).await;
                     set_phase(&state, VelocityImprovementPhase::Stopped).await;
                     log(
                         &state,
@@ -389,51 +766,26 @@ pub async fn run_velocity_improvement_loop(
         }
 
         iter_result.completed_at = Some(Utc::now().to_rfc3339());
-        {
-            let mut vi = state.velocity_improvement.write().await;
-            vi.iterations.push(iter_result);
-        }
+        record_iteration(&state, &db, loop_id, iter_result).await;
+        // This iteration is on record; a resume picks up at the next one
+        save_progress(iteration + 1, previous_score, no_improvement_streak);
 
         // ------------------------------------------------------------------
-        // Phase 4: Restart frontend
+        // Phase 4-5: Restart the dev servers and wait for them
         // ------------------------------------------------------------------
-        set_phase(&state, VelocityImprovementPhase::RestartingFrontend).await;
-        log(&state, LogLevel::Info, "Restarting frontend...").await;
-
-        let restart_result = restart_frontend(&state, config.restart_backend).await;
-        if let Err(e) = restart_result {
-            warn!("Frontend restart failed: {}", e);
-            // Continue anyway — the frontend might still be running with old code
-        }
-
-        // ------------------------------------------------------------------
-        // Phase 5: Wait for frontend
-        // ------------------------------------------------------------------
-        set_phase(&state, VelocityImprovementPhase::WaitingFrontend).await;
-        log(
-            &state,
-            LogLevel::Info,
-            "Waiting for frontend to become healthy...",
-        )
-        .await;
-
-        if let Err(e) = wait_for_frontend(&state, &stop_rx).await {
-            if e.contains("Stop requested") {
+        match restart_and_wait(&state, &config, fix_mode, &stop_rx).await {
+            Ok(()) => {}
+            Err(LoopInterrupt::Stopped) => {
                 set_phase(&state, VelocityImprovementPhase::Stopped).await;
                 finalize(&state).await;
                 return;
             }
-            set_error(&state, format!("Frontend health check failed: {}", e)).await;
-            return;
+            Err(LoopInterrupt::Failed(msg)) => {
+                set_error(&state, msg).await;
+                return;
+            }
         }
 
-        log(
-            &state,
-            LogLevel::Info,
-            "Frontend is healthy, proceeding to next iteration",
-        )
-        .await;
-
         // Brief pause before next iteration
         tokio::time::sleep(Duration::from_secs(2)).await;
     }
@@ -449,23 +801,414 @@ pub async fn run_velocity_improvement_loop(
     finalize(&state).await;
 }
 
+// ============================================================================
+// Test runs and dev-server restarts
+// ============================================================================
+
+/// Why a phase ended the loop early.
+enum LoopInterrupt {
+    Stopped,
+    Failed(String),
+}
+
+/// Hold the fix for review until it is approved (`true`) or rejected
+/// (`false`), or the loop is stopped.
+async fn await_approval(
+    state: &SharedState,
+    iteration: u32,
+    fix_mode: FixMode,
+    prompt: &str,
+    stop_rx: &watch::Receiver<bool>,
+) -> Result<bool, LoopInterrupt> {
+    let (tx, mut rx) = oneshot::channel();
+    {
+        let mut vi = state.velocity_improvement.write().await;
+        vi.pending_fix = Some(PendingFix {
+            iteration,
+            fix_mode,
+            prompt: prompt.to_string(),
+            requested_at: Utc::now().to_rfc3339(),
+        });
+        vi.approval_tx = Some(tx);
+    }
+    set_phase(state, VelocityImprovementPhase::AwaitingApproval).await;
+    log(
+        state,
+        LogLevel::Info,
+        format!(
+            "Iteration {} fix is waiting for approval (POST /velocity-improvement/approve)",
+            iteration
+        ),
+    )
+    .await;
+
+    let decision = loop {
+        if *stop_rx.borrow() {
+            break Err(LoopInterrupt::Stopped);
+        }
+        match rx.try_recv() {
+            Ok(approved) => break Ok(approved),
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            // The pending fix was cleared without an answer
+            Err(oneshot::error::TryRecvError::Closed) => break Ok(false),
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+
+    let mut vi = state.velocity_improvement.write().await;
+    vi.pending_fix = None;
+    vi.approval_tx = None;
+    decision
+}
+
+/// Run the velocity tests once and return the completed run.
+async fn run_velocity_tests_once(
+    db: &Arc<VelocityTestDb>,
+    state: &SharedState,
+    stop_rx: &watch::Receiver<bool>,
+) -> Result<VelocityTestRun, LoopInterrupt> {
+    set_phase(state, VelocityImprovementPhase::RunningTests).await;
+
+    // Check that velocity tests aren't already running
+    {
+        let vt = state.velocity_tests.read().await;
+        if vt.running {
+            return Err(LoopInterrupt::Failed(
+                "Velocity tests are already running from another source".to_string(),
+            ));
+        }
+    }
+
+    // Create a stop channel for velocity tests
+    let (vt_stop_tx, vt_stop_rx) = watch::channel(false);
+
+    // Mark velocity tests as running
+    {
+        let mut vt = state.velocity_tests.write().await;
+        vt.running = true;
+        vt.stop_tx = Some(vt_stop_tx);
+    }
+
+    let started_at = Utc::now().to_rfc3339();
+    let db_clone = db.clone();
+    let state_clone = state.clone();
+
+    // Spawn tests as a background task
+    let test_handle = tokio::spawn(async move {
+        crate::velocity_tests::engine::run_velocity_tests(
+            db_clone,
+            state_clone,
+            vt_stop_rx,
+            VelocityRunOptions::default(),
+        )
+        .await;
+    });
+
+    // Poll for completion, checking our stop signal periodically and
+    // streaming page results as they are recorded
+    let mut scored = std::collections::HashSet::new();
+    loop {
+        let run_id = state.velocity_tests.read().await.current_run_id.clone();
+        if let Some(run_id) = run_id {
+            emit_page_scores(db, state, &run_id, &mut scored).await;
+        }
+
+        if *stop_rx.borrow() {
+            // Stop signal received — cancel velocity tests
+            let mut vt = state.velocity_tests.write().await;
+            if let Some(tx) = vt.stop_tx.take() {
+                let _ = tx.send(true);
+            }
+            drop(vt);
+            let _ = test_handle.await;
+            return Err(LoopInterrupt::Stopped);
+        }
+
+        if test_handle.is_finished() {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    // The run that just finished, if it completed
+    let run = db
+        .list_runs()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|r| r.status == "completed" && r.started_at >= started_at)
+        .ok_or_else(|| LoopInterrupt::Failed("No completed velocity test run found".to_string()))?;
+    emit_page_scores(db, state, &run.id, &mut scored).await;
+    Ok(run)
+}
+
+/// Publish the results of `run_id` not in `scored` yet.
+async fn emit_page_scores(
+    db: &VelocityTestDb,
+    state: &SharedState,
+    run_id: &str,
+    scored: &mut std::collections::HashSet<i64>,
+) {
+    let Ok(results) = db.get_results_for_run(run_id) else {
+        return;
+    };
+    let vi = state.velocity_improvement.read().await;
+    for r in results {
+        if !scored.insert(r.id) {
+            continue;
+        }
+        progress::emit(
+            &vi.progress_tx,
+            ImprovementProgressEvent::PageScored {
+                iteration: vi.current_iteration,
+                run_id: run_id.to_string(),
+                test_name: r.test_name,
+                score: r.score,
+                bottleneck: r.bottleneck,
+                error: r.error,
+            },
+        );
+    }
+}
+
+/// Restart what the fix touched — the backend for a backend fix, else the
+/// frontend (plus the backend with `restart_backend`) — and wait for it.
+async fn restart_and_wait(
+    state: &SharedState,
+    config: &VelocityImprovementConfig,
+    fix_mode: FixMode,
+    stop_rx: &watch::Receiver<bool>,
+) -> Result<(), LoopInterrupt> {
+    let orchestrator =
+        normalize_base_url(config.dev_orchestrator_url.clone()).or_else(dev_orchestrator_url);
+
+    if fix_mode == FixMode::Backend {
+        set_phase(state, VelocityImprovementPhase::RestartingBackend).await;
+        match &orchestrator {
+            Some(orchestrator) => {
+                log(state, LogLevel::Info, "Restarting backend...").await;
+                if let Err(e) = restart_backend(state, orchestrator).await {
+                    warn!("Backend restart failed: {}", e);
+                }
+            }
+            None => {
+                log(
+                    state,
+                    LogLevel::Info,
+                    "No dev-server orchestrator configured; relying on backend auto-reload",
+                )
+                .await;
+            }
+        }
+
+        set_phase(state, VelocityImprovementPhase::WaitingBackend).await;
+        if let Err(e) = wait_for_backend(state, stop_rx).await {
+            if e.contains("Stop requested") {
+                return Err(LoopInterrupt::Stopped);
+            }
+            return Err(LoopInterrupt::Failed(format!(
+                "Backend health check failed: {}",
+                e
+            )));
+        }
+        log(state, LogLevel::Info, "Backend is up").await;
+        return Ok(());
+    }
+
+    set_phase(state, VelocityImprovementPhase::RestartingFrontend).await;
+    match orchestrator {
+        Some(orchestrator) => {
+            log(state, LogLevel::Info, "Restarting frontend...").await;
+            if let Err(e) = restart_frontend(state, &orchestrator, config.restart_backend).await {
+                warn!("Frontend restart failed: {}", e);
+                // Continue anyway — the frontend might still be running with old code
+            }
+        }
+        None => {
+            log(
+                state,
+                LogLevel::Info,
+                "No dev-server orchestrator configured; relying on hot reload",
+            )
+            .await;
+        }
+    }
+
+    set_phase(state, VelocityImprovementPhase::WaitingFrontend).await;
+    log(
+        state,
+        LogLevel::Info,
+        "Waiting for frontend to become healthy...",
+    )
+    .await;
+
+    if let Err(e) = wait_for_frontend(state, stop_rx).await {
+        if e.contains("Stop requested") {
+            return Err(LoopInterrupt::Stopped);
+        }
+        return Err(LoopInterrupt::Failed(format!(
+            "Frontend health check failed: {}",
+            e
+        )));
+    }
+
+    log(state, LogLevel::Info, "Frontend is healthy").await;
+    Ok(())
+}
+
+/// Apply each strategy on its own branch off `base`, measure it, and
+/// fast-forward `base` to the best-scoring one.
+#[allow(clippy::too_many_arguments)]
+async fn run_ab_fix(
+    db: &Arc<VelocityTestDb>,
+    state: &SharedState,
+    config: &VelocityImprovementConfig,
+    stop_rx: &watch::Receiver<bool>,
+    repo: &std::path::Path,
+    base: &str,
+    prompt: &str,
+    fix_mode: FixMode,
+    loop_id: &str,
+    iteration: u32,
+) -> Result<AbComparison, LoopInterrupt> {
+    let mut outcomes = Vec::new();
+    for strategy in FixStrategy::ALL {
+        let branch = velocity_ab::branch_name(loop_id, iteration, strategy);
+        log(
+            state,
+            LogLevel::Info,
+            format!(
+                "A/B fix: trying the {} strategy on {}",
+                strategy.as_str(),
+                branch
+            ),
+        )
+        .await;
+        let mut outcome = StrategyOutcome {
+            strategy,
+            branch: branch.clone(),
+            fix_summary: None,
+            run_id: None,
+            score: None,
+            error: None,
+        };
+        let measured = measure_strategy(
+            db,
+            state,
+            config,
+            stop_rx,
+            repo,
+            base,
+            prompt,
+            fix_mode,
+            &mut outcome,
+        )
+        .await;
+        if let Err(e) = velocity_ab::switch_to(repo, base).await {
+            return Err(LoopInterrupt::Failed(format!(
+                "A/B fix could not return to {}: {}",
+                base, e
+            )));
+        }
+        match measured {
+            Ok(()) => {}
+            Err(LoopInterrupt::Failed(e)) => outcome.error = Some(e),
+            Err(LoopInterrupt::Stopped) => return Err(LoopInterrupt::Stopped),
+        }
+        outcomes.push(outcome);
+        set_phase(state, VelocityImprovementPhase::Fixing).await;
+    }
+
+    let winner = velocity_ab::pick_winner(&outcomes).map(|w| (w.strategy, w.branch.clone()));
+    if let Some((strategy, branch)) = &winner {
+        velocity_ab::adopt(repo, branch).await.map_err(|e| {
+            LoopInterrupt::Failed(format!("A/B fix could not adopt {}: {}", branch, e))
+        })?;
+        log(
+            state,
+            LogLevel::Info,
+            format!("A/B fix: kept the {} strategy", strategy.as_str()),
+        )
+        .await;
+    }
+    Ok(AbComparison {
+        base_branch: base.to_string(),
+        outcomes,
+        winner: winner.map(|(s, _)| s),
+    })
+}
+
+/// Fix on a fresh branch with `outcome.strategy`, commit, restart and
+/// measure, filling in `outcome`.
+#[allow(clippy::too_many_arguments)]
+async fn measure_strategy(
+    db: &Arc<VelocityTestDb>,
+    state: &SharedState,
+    config: &VelocityImprovementConfig,
+    stop_rx: &watch::Receiver<bool>,
+    repo: &std::path::Path,
+    base: &str,
+    prompt: &str,
+    fix_mode: FixMode,
+    outcome: &mut StrategyOutcome,
+) -> Result<(), LoopInterrupt> {
+    velocity_ab::start_branch(repo, &outcome.branch, base)
+        .await
+        .map_err(LoopInterrupt::Failed)?;
+    let summary = spawn_fix_agent(
+        state,
+        &outcome.strategy.apply(prompt),
+        config,
+        stop_rx,
+        Some(outcome.strategy),
+    )
+    .await
+    .map_err(|e| {
+        if e.contains("Stop requested") {
+            LoopInterrupt::Stopped
+        } else {
+            LoopInterrupt::Failed(e)
+        }
+    })?;
+    outcome.fix_summary = Some(summary);
+    velocity_ab::commit_all(
+        repo,
+        &format!("velocity fix ({} strategy)", outcome.strategy.as_str()),
+    )
+    .await
+    .map_err(LoopInterrupt::Failed)?;
+    restart_and_wait(state, config, fix_mode, stop_rx).await?;
+    let run = run_velocity_tests_once(db, state, stop_rx).await?;
+    outcome.run_id = Some(run.id);
+    outcome.score = run.overall_score;
+    Ok(())
+}
+
 // ============================================================================
 // Exit condition checks
 // ============================================================================
 
+/// `pages_below` is given when the loop has per-page targets: the target is
+/// then reached once it is empty, rather than when `score` reaches `target`.
 fn check_exit_conditions(
     score: f64,
     target: f64,
+    pages_below: Option<&[String]>,
     iteration: u32,
     max_iterations: u32,
     previous_score: Option<f64>,
     no_improvement_streak: u32,
 ) -> Option<String> {
-    if score >= target {
-        return Some(format!(
-            "Target score reached: {:.1} >= {:.1}",
-            score, target
-        ));
+    match pages_below {
+        Some([]) => return Some("All pages reached their target scores".to_string()),
+        Some(_) => {}
+        None if score >= target => {
+            return Some(format!(
+                "Target score reached: {:.1} >= {:.1}",
+                score, target
+            ));
+        }
+        None => {}
     }
 
     if iteration >= max_iterations {
@@ -483,7 +1226,7 @@ fn check_exit_conditions(
     }
 
     if let Some(prev) = previous_score {
-        if score < prev - 5.0 {
+        if score < prev - REGRESSION_POINTS {
             return Some(format!(
                 "Score regression: {:.1} -> {:.1} (decreased by {:.1})",
                 prev,
@@ -500,11 +1243,85 @@ fn check_exit_conditions(
 // Prompt builder
 // ============================================================================
 
+/// Most common bottleneck among the pages below their target; ties go to
+/// the alphabetically first.
+fn dominant_bottleneck(
+    results: &[VelocityTestResult],
+    target_for: impl Fn(&str) -> f64,
+) -> Option<&str> {
+    let mut counts: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for r in results {
+        if r.score.unwrap_or(0.0) >= target_for(&r.test_name) {
+            continue;
+        }
+        if let Some(b) = r.bottleneck.as_deref() {
+            *counts.entry(b).or_default() += 1;
+        }
+    }
+    let mut best: Option<(&str, usize)> = None;
+    for (b, n) in counts {
+        if best.is_none_or(|(_, m)| n > m) {
+            best = Some((b, n));
+        }
+    }
+    best.map(|(b, _)| b)
+}
+
+/// Slowest recent backend spans for each page whose bottleneck is one of
+/// `bottlenecks`, keyed by test name. The page's API probe endpoint (from
+/// the test case) is the route the spans are matched on. Pages with no
+/// matching spans are left out, so an empty map means the fix stays
+/// frontend-only.
+fn collect_backend_spans(
+    span_db: Option<&VelocityDb>,
+    test_cases: &[TestCase],
+    results: &[VelocityTestResult],
+    bottlenecks: &[&str],
+) -> HashMap<String, Vec<SlowRequest>> {
+    let mut spans = HashMap::new();
+    let Some(span_db) = span_db else {
+        return spans;
+    };
+
+    let filter = QueryFilter {
+        since: Some((Utc::now() - chrono::Duration::hours(BACKEND_SPAN_WINDOW_HOURS)).to_rfc3339()),
+        ..Default::default()
+    };
+
+    for r in results {
+        if !r
+            .bottleneck
+            .as_deref()
+            .is_some_and(|b| bottlenecks.contains(&b))
+        {
+            continue;
+        }
+        let Some(test_case) = test_cases.iter().find(|tc| tc.name == r.test_name) else {
+            continue;
+        };
+        let route = test_case
+            .api_endpoint
+            .split('?')
+            .next()
+            .unwrap_or(&test_case.api_endpoint);
+        match queries::get_slow_spans_for_route(span_db, &filter, route, BACKEND_SPANS_PER_PAGE) {
+            Ok(found) if !found.is_empty() => {
+                spans.insert(r.test_name.clone(), found);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to query backend spans for {}: {}", route, e),
+        }
+    }
+
+    spans
+}
+
 fn build_velocity_fix_prompt(
     results: &[VelocityTestResult],
+    backend_spans: &HashMap<String, Vec<SlowRequest>>,
     iteration: u32,
     previous_score: Option<f64>,
-    target_score: f64,
+    target_for: impl Fn(&str) -> f64,
 ) -> String {
     let mut prompt = String::new();
 
@@ -515,17 +1332,15 @@ fn build_velocity_fix_prompt(
 
     // Section 2: Current Scores
     prompt.push_str("## Current Scores\n\n");
-    prompt.push_str(&format!(
-        "**Target score: {:.0}** | **Iteration: {}**\n\n",
-        target_score, iteration
-    ));
-    prompt.push_str("| Page | Score | Bottleneck |\n");
-    prompt.push_str("|------|-------|------------|\n");
+    prompt.push_str(&format!("**Iteration: {}**\n\n", iteration));
+    prompt.push_str("| Page | Score | Target | Bottleneck |\n");
+    prompt.push_str("|------|-------|--------|------------|\n");
     for r in results {
         prompt.push_str(&format!(
-            "| {} | {:.1} | {} |\n",
+            "| {} | {:.1} | {:.0} | {} |\n",
             r.test_name,
             r.score.unwrap_or(0.0),
+            target_for(&r.test_name),
             r.bottleneck.as_deref().unwrap_or("Unknown")
         ));
     }
@@ -535,7 +1350,7 @@ fn build_velocity_fix_prompt(
     prompt.push_str("## Per-Page Diagnostics\n\n");
     for r in results {
         let score = r.score.unwrap_or(0.0);
-        if score >= target_score {
+        if score >= target_for(&r.test_name) {
             continue; // Skip pages already meeting target
         }
 
@@ -637,6 +1452,27 @@ fn build_velocity_fix_prompt(
             }
         }
 
+        // Backend spans for the page's API endpoint
+        if let Some(spans) = backend_spans.get(&r.test_name) {
+            prompt.push_str("**Slow backend spans:**\n\n");
+            prompt.push_str("| Service | Method | Route | Duration (ms) | Status | Request ID |\n");
+            prompt.push_str("|---------|--------|-------|---------------|--------|------------|\n");
+            for span in spans {
+                prompt.push_str(&format!(
+                    "| {} | {} | {} | {:.0} | {} | {} |\n",
+                    span.service,
+                    span.http_method,
+                    span.http_route,
+                    span.duration_ms,
+                    span.http_status_code
+                        .map(|c| c.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    span.request_id.as_deref().unwrap_or("-")
+                ));
+            }
+            prompt.push('\n');
+        }
+
         // Console errors
         if r.console_errors > 0 {
             prompt.push_str(&format!("**Console errors:** {}\n\n", r.console_errors));
@@ -671,13 +1507,23 @@ fn build_velocity_fix_prompt(
     prompt.push_str("- **JS Blocking**: Code-split heavy components with `next/dynamic`, defer non-critical scripts, reduce synchronous work in component renders\n");
     prompt.push_str("- **Bundle Heavy**: Reduce imports (use specific subpath imports instead of barrel exports), lazy-load heavy dependencies, check for unnecessary polyfills\n");
     prompt.push_str("- **Render Slow**: Reduce DOM complexity, use `React.memo` for expensive renders, avoid layout thrashing\n");
-    prompt.push_str("- **TTFB Slow / Backend Slow**: Skip — this is a backend issue, not fixable from frontend code\n");
+    if backend_spans.is_empty() {
+        prompt.push_str("- **TTFB Slow / Backend Slow**: Skip — this is a backend issue, not fixable from frontend code\n");
+    } else {
+        prompt.push_str("- **Backend Slow**: Find the handler for each route listed under \"Slow backend spans\" in `qontinui-web/backend/` and optimize it (N+1 queries, missing indexes, unbounded result sets, redundant work). Do not change the response shape\n");
+        prompt.push_str("- **TTFB Slow**: Skip — this is a server rendering issue, not fixable from page code\n");
+    }
     prompt.push_str("- **Network Slow**: Check for unoptimized images, missing compression, or redundant network calls\n\n");
 
     // Section 6: Constraints
     prompt.push_str("## Constraints\n\n");
-    prompt.push_str("- Only modify files under `qontinui-web/frontend/`\n");
-    prompt.push_str("- Do NOT modify backend code\n");
+    if backend_spans.is_empty() {
+        prompt.push_str("- Only modify files under `qontinui-web/frontend/`\n");
+        prompt.push_str("- Do NOT modify backend code\n");
+    } else {
+        prompt.push_str("- Only modify files under `qontinui-web/frontend/`, plus the `qontinui-web/backend/` handlers (and the queries they call) for the routes listed under \"Slow backend spans\"\n");
+        prompt.push_str("- Do NOT modify any other backend code\n");
+    }
     prompt.push_str("- Focus on the highest-impact changes first\n");
     prompt.push_str("- Do NOT add new dependencies unless absolutely necessary\n");
     prompt.push_str("- Make targeted, surgical changes — do not refactor unrelated code\n");
@@ -685,15 +1531,152 @@ fn build_velocity_fix_prompt(
     prompt
 }
 
+/// Prompt for a fix aimed at the backend routes behind "Backend Slow" and
+/// "TTFB Slow" pages.
+fn build_backend_fix_prompt(
+    results: &[VelocityTestResult],
+    test_cases: &[TestCase],
+    backend_spans: &HashMap<String, Vec<SlowRequest>>,
+    iteration: u32,
+    previous_score: Option<f64>,
+    target_for: impl Fn(&str) -> f64,
+) -> String {
+    let mut prompt = String::new();
+
+    prompt.push_str("# Backend Performance Fix Request\n\n");
+    prompt.push_str("Pages of `qontinui-web` are slow because of the backend. You are fixing the API handlers in `qontinui-web/backend/`.\n");
+    prompt.push_str("The current working directory is the `qontinui-root` directory which contains `qontinui-web/backend/`.\n\n");
+
+    prompt.push_str("## Slow Pages\n\n");
+    prompt.push_str(&format!("**Iteration: {}**\n\n", iteration));
+    prompt.push_str(
+        "| Page | Score | Target | Bottleneck | API endpoint | API (ms) | Status | TTFB (ms) |\n",
+    );
+    prompt.push_str(
+        "|------|-------|--------|------------|--------------|----------|--------|-----------|\n",
+    );
+    let slow: Vec<&VelocityTestResult> = results
+        .iter()
+        .filter(|r| {
+            r.score.unwrap_or(0.0) < target_for(&r.test_name)
+                && r.bottleneck
+                    .as_deref()
+                    .is_some_and(|b| BACKEND_BOTTLENECKS.contains(&b))
+        })
+        .collect();
+    let fmt_ms = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{:.0}", v));
+    for r in &slow {
+        let endpoint = test_cases
+            .iter()
+            .find(|tc| tc.name == r.test_name)
+            .map_or("-", |tc| tc.api_endpoint.as_str());
+        prompt.push_str(&format!(
+            "| {} | {:.1} | {:.0} | {} | `{}` | {} | {} | {} |\n",
+            r.test_name,
+            r.score.unwrap_or(0.0),
+            target_for(&r.test_name),
+            r.bottleneck.as_deref().unwrap_or("Unknown"),
+            endpoint,
+            fmt_ms(r.api_response_time_ms),
+            r.api_status_code
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            fmt_ms(r.ttfb_ms)
+        ));
+    }
+    prompt.push('\n');
+
+    for r in &slow {
+        let Some(spans) = backend_spans.get(&r.test_name) else {
+            continue;
+        };
+        prompt.push_str(&format!("### Slow backend spans for {}\n\n", r.test_name));
+        prompt.push_str("| Service | Method | Route | Duration (ms) | Status | Request ID |\n");
+        prompt.push_str("|---------|--------|-------|---------------|--------|------------|\n");
+        for span in spans {
+            prompt.push_str(&format!(
+                "| {} | {} | {} | {:.0} | {} | {} |\n",
+                span.service,
+                span.http_method,
+                span.http_route,
+                span.duration_ms,
+                span.http_status_code
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                span.request_id.as_deref().unwrap_or("-")
+            ));
+        }
+        prompt.push('\n');
+    }
+
+    if let (true, Some(prev)) = (iteration > 1, previous_score) {
+        prompt.push_str("## Previous Attempt\n\n");
+        prompt.push_str(&format!(
+            "Previous iteration scored {:.1}. If these routes are still slow, try a different approach.\n\n",
+            prev
+        ));
+    }
+
+    prompt.push_str("## Fix Instructions\n\n");
+    prompt.push_str("- **Backend Slow**: Find the handler for each API endpoint above and optimize it: N+1 queries, missing indexes, unbounded result sets, redundant or sequential work that can run concurrently\n");
+    prompt.push_str("- **TTFB Slow**: The first byte of the page is late. Look at what the endpoint (and any middleware, auth or session lookup on its path) does before responding\n");
+    prompt.push_str(
+        "- Use the span durations to decide where the time goes before changing code\n\n",
+    );
+
+    prompt.push_str("## Constraints\n\n");
+    prompt.push_str("- Only modify files under `qontinui-web/backend/`\n");
+    prompt.push_str("- Do NOT change response shapes or status codes\n");
+    prompt.push_str("- Do NOT add migrations that drop or rewrite existing data\n");
+    prompt.push_str("- Make targeted, surgical changes — do not refactor unrelated code\n");
+
+    prompt
+}
+
 // ============================================================================
 // Fix agent
 // ============================================================================
 
+/// [`run_fix_agent`], announced on the progress stream.
 async fn spawn_fix_agent(
     state: &SharedState,
     prompt: &str,
     config: &VelocityImprovementConfig,
     stop_rx: &watch::Receiver<bool>,
+    strategy: Option<FixStrategy>,
+) -> Result<String, String> {
+    let (tx, iteration) = {
+        let vi = state.velocity_improvement.read().await;
+        (vi.progress_tx.clone(), vi.current_iteration)
+    };
+    progress::emit(
+        &tx,
+        ImprovementProgressEvent::FixAgentStarted {
+            iteration,
+            strategy,
+        },
+    );
+    let started = std::time::Instant::now();
+    let result = run_fix_agent(state, prompt, config, stop_rx).await;
+    state.velocity_improvement.write().await.fix_agent_pid = None;
+    progress::emit(
+        &tx,
+        ImprovementProgressEvent::FixAgentFinished {
+            iteration,
+            strategy,
+            duration_ms: started.elapsed().as_millis() as i64,
+            summary: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+        },
+    );
+    result
+}
+
+async fn run_fix_agent(
+    state: &SharedState,
+    prompt: &str,
+    config: &VelocityImprovementConfig,
+    stop_rx: &watch::Receiver<bool>,
 ) -> Result<String, String> {
     // Write prompt to temp file
     let prompt_path = std::env::temp_dir().join("qontinui-velocity-fix-prompt.md");
@@ -736,6 +1719,7 @@ async fn spawn_fix_agent(
     let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn Claude CLI: {}", e))?;
+    state.velocity_improvement.write().await.fix_agent_pid = child.id();
 
     // Wait with timeout and stop signal
     let timeout = Duration::from_secs(config.fix_timeout_secs);
@@ -807,19 +1791,25 @@ async fn spawn_fix_agent(
 // Frontend restart + health check
 // ============================================================================
 
-async fn restart_frontend(state: &SharedState, restart_backend: bool) -> Result<(), String> {
+/// Ask the orchestrator at `orchestrator` to restart the dev servers via its
+/// `POST /dev-start/{backend,frontend}` endpoints.
+async fn restart_frontend(
+    state: &SharedState,
+    orchestrator: &str,
+    restart_backend: bool,
+) -> Result<(), String> {
     let client = &state.http_client;
 
     if restart_backend {
         let _ = client
-            .post("http://localhost:9875/dev-start/backend")
+            .post(format!("{}/dev-start/backend", orchestrator))
             .timeout(Duration::from_secs(120))
             .send()
             .await;
     }
 
     let resp = client
-        .post("http://localhost:9875/dev-start/frontend")
+        .post(format!("{}/dev-start/frontend", orchestrator))
         .timeout(Duration::from_secs(180))
         .send()
         .await
@@ -835,6 +1825,54 @@ async fn restart_frontend(state: &SharedState, restart_backend: bool) -> Result<
     Ok(())
 }
 
+/// Ask the orchestrator to restart just the backend.
+async fn restart_backend(state: &SharedState, orchestrator: &str) -> Result<(), String> {
+    let resp = state
+        .http_client
+        .post(format!("{}/dev-start/backend", orchestrator))
+        .timeout(Duration::from_secs(120))
+        .send()
+        .await
+        .map_err(|e| format!("Backend restart request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Backend restart returned status {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// Wait until the backend answers at all; any non-5xx status counts, since
+/// its root route may not exist.
+async fn wait_for_backend(
+    state: &SharedState,
+    stop_rx: &watch::Receiver<bool>,
+) -> Result<(), String> {
+    let client = &state.http_client;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(120);
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    loop {
+        if *stop_rx.borrow() {
+            return Err("Stop requested while waiting for backend".to_string());
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err("Backend health check timed out after 120s".to_string());
+        }
+        match client
+            .get(BACKEND_API_BASE)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+        {
+            Ok(resp) if !resp.status().is_server_error() => {
+                info!("Backend is responding");
+                return Ok(());
+            }
+            _ => tokio::time::sleep(Duration::from_secs(3)).await,
+        }
+    }
+}
+
 async fn wait_for_frontend(
     state: &SharedState,
     stop_rx: &watch::Receiver<bool>,
@@ -856,7 +1894,7 @@ async fn wait_for_frontend(
         }
 
         match client
-            .get("http://localhost:3001")
+            .get(WEB_FRONTEND_BASE)
             .timeout(Duration::from_secs(5))
             .send()
             .await
@@ -876,8 +1914,34 @@ async fn wait_for_frontend(
 // Helpers
 // ============================================================================
 
+/// Keep a finished iteration in memory for `/history` and store it for
+/// `/loops`.
+async fn record_iteration(
+    state: &SharedState,
+    db: &VelocityTestDb,
+    loop_id: &str,
+    iteration: VelocityImprovementIteration,
+) {
+    if let Err(e) = db.insert_improvement_iteration(loop_id, &iteration) {
+        warn!("Failed to persist velocity improvement iteration: {}", e);
+    }
+    state
+        .velocity_improvement
+        .write()
+        .await
+        .iterations
+        .push(iteration);
+}
+
 async fn set_phase(state: &SharedState, phase: VelocityImprovementPhase) {
     let mut vi = state.velocity_improvement.write().await;
+    progress::emit(
+        &vi.progress_tx,
+        ImprovementProgressEvent::PhaseChanged {
+            iteration: vi.current_iteration,
+            phase: phase.clone(),
+        },
+    );
     vi.phase = phase;
 }
 
@@ -885,6 +1949,13 @@ async fn set_error(state: &SharedState, msg: impl Into<String>) {
     let msg = msg.into();
     error!("Velocity improvement error: {}", msg);
     let mut vi = state.velocity_improvement.write().await;
+    progress::emit(
+        &vi.progress_tx,
+        ImprovementProgressEvent::PhaseChanged {
+            iteration: vi.current_iteration,
+            phase: VelocityImprovementPhase::Error,
+        },
+    );
     vi.phase = VelocityImprovementPhase::Error;
     vi.error = Some(msg.clone());
     vi.running = false;
@@ -903,3 +1974,82 @@ async fn log(state: &SharedState, level: LogLevel, msg: impl Into<String>) {
         .emit(LogSource::Supervisor, level, msg.into())
         .await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, score: f64, bottleneck: &str) -> VelocityTestResult {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "run_id": "run",
+            "test_name": name,
+            "page_url": "/",
+            "load_time_ms": 1000.0,
+            "console_errors": 0,
+            "element_found": true,
+            "score": score,
+            "error": null,
+            "tested_at": "2026-03-01T10:00:00+00:00",
+            "api_response_time_ms": 900.0,
+            "api_status_code": 200,
+            "ttfb_ms": null,
+            "dom_interactive_ms": null,
+            "dom_complete_ms": null,
+            "fcp_ms": null,
+            "long_task_count": 0,
+            "long_task_total_ms": 0.0,
+            "resource_count": 0,
+            "total_transfer_size_bytes": 0,
+            "slowest_resource_ms": 0.0,
+            "bottleneck": bottleneck,
+            "diagnostics_json": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn dominant_bottleneck_counts_only_pages_below_target() {
+        let results = [
+            result("Dashboard", 40.0, "Backend Slow"),
+            result("Runs", 50.0, "Backend Slow"),
+            result("Settings", 60.0, "JS Blocking"),
+            result("Profile", 95.0, "JS Blocking"),
+            result("Docs", 90.0, "JS Blocking"),
+        ];
+        assert_eq!(
+            dominant_bottleneck(&results, |_| 80.0),
+            Some("Backend Slow")
+        );
+        assert_eq!(dominant_bottleneck(&results, |_| 99.0), Some("JS Blocking"));
+        assert_eq!(dominant_bottleneck(&results, |_| 10.0), None);
+    }
+
+    #[test]
+    fn page_targets_override_the_global_target() {
+        let config: VelocityImprovementConfig = serde_json::from_value(serde_json::json!({
+            "target_score": 90.0,
+            "page_targets": {"Editor": 70.0},
+        }))
+        .unwrap();
+        let results = [
+            result("Editor", 72.0, "JS Blocking"),
+            result("Runs", 88.0, "Backend Slow"),
+        ];
+        assert_eq!(config.pages_below_target(&results), vec!["Runs"]);
+
+        // Overall score 80 is below the global target, but every page is at
+        // its own.
+        let met = [
+            result("Editor", 72.0, "JS Blocking"),
+            result("Runs", 91.0, "-"),
+        ];
+        let below = config.pages_below_target(&met);
+        assert!(
+            check_exit_conditions(80.0, 90.0, Some(&below), 1, 5, None, 0)
+                .is_some_and(|r| r.starts_with("All pages"))
+        );
+        let below = config.pages_below_target(&results);
+        assert!(check_exit_conditions(95.0, 90.0, Some(&below), 1, 5, None, 0).is_none());
+    }
+}
@@ -0,0 +1,119 @@
+//! Progress events for `GET /velocity-improvement/stream`.
+//!
+//! `GET /velocity-improvement/status` only carries the current phase. The
+//! loop also publishes a [`ImprovementProgressEvent`] on
+//! [`crate::velocity_improvement::VelocityImprovementState`]'s `progress_tx`
+//! on every phase change, for each page result as the velocity tests record
+//! it, when a fix agent starts and finishes, and once the loop ends. With no
+//! stream open the events are simply dropped.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::velocity_ab::FixStrategy;
+use crate::velocity_improvement::VelocityImprovementPhase;
+
+/// Events buffered for a subscriber before it starts lagging.
+pub const PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImprovementProgressEvent {
+    PhaseChanged {
+        iteration: u32,
+        phase: VelocityImprovementPhase,
+    },
+    PageScored {
+        iteration: u32,
+        run_id: String,
+        test_name: String,
+        score: Option<f64>,
+        bottleneck: Option<String>,
+        error: Option<String>,
+    },
+    FixAgentStarted {
+        iteration: u32,
+        /// Set when the agent works on an A/B branch.
+        strategy: Option<FixStrategy>,
+    },
+    FixAgentFinished {
+        iteration: u32,
+        strategy: Option<FixStrategy>,
+        duration_ms: i64,
+        summary: Option<String>,
+        error: Option<String>,
+    },
+    LoopFinished {
+        /// `complete`, `stopped` or `error`.
+        phase: VelocityImprovementPhase,
+        iterations: usize,
+        exit_reason: Option<String>,
+        error: Option<String>,
+    },
+}
+
+impl ImprovementProgressEvent {
+    /// SSE event name; matches the serialized `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PhaseChanged { .. } => "phase_changed",
+            Self::PageScored { .. } => "page_scored",
+            Self::FixAgentStarted { .. } => "fix_agent_started",
+            Self::FixAgentFinished { .. } => "fix_agent_finished",
+            Self::LoopFinished { .. } => "loop_finished",
+        }
+    }
+}
+
+pub fn channel() -> broadcast::Sender<ImprovementProgressEvent> {
+    broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0
+}
+
+/// Publish `event`; a send with no subscribers is not an error.
+pub fn emit(tx: &broadcast::Sender<ImprovementProgressEvent>, event: ImprovementProgressEvent) {
+    let _ = tx.send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_matches_serialized_type() {
+        let events = [
+            ImprovementProgressEvent::PhaseChanged {
+                iteration: 1,
+                phase: VelocityImprovementPhase::RunningTests,
+            },
+            ImprovementProgressEvent::PageScored {
+                iteration: 1,
+                run_id: "r".to_string(),
+                test_name: "Dashboard".to_string(),
+                score: Some(72.0),
+                bottleneck: Some("JS Blocking".to_string()),
+                error: None,
+            },
+            ImprovementProgressEvent::FixAgentStarted {
+                iteration: 1,
+                strategy: None,
+            },
+            ImprovementProgressEvent::FixAgentFinished {
+                iteration: 1,
+                strategy: Some(FixStrategy::Broad),
+                duration_ms: 5,
+                summary: None,
+                error: Some("timed out".to_string()),
+            },
+            ImprovementProgressEvent::LoopFinished {
+                phase: VelocityImprovementPhase::Complete,
+                iterations: 1,
+                exit_reason: None,
+                error: None,
+            },
+        ];
+        for event in events {
+            let value = serde_json::to_value(&event).unwrap();
+            assert_eq!(value["type"], event.name());
+        }
+    }
+}
@@ -5,6 +5,21 @@
 //!
 //! This is a simplified version of the runner's `JsonlSpanLayer`, focused
 //! exclusively on HTTP request timing for the supervisor's Axum server.
+//!
+//! Trace and span ids are W3C trace-context shaped (32 / 16 hex digits) and
+//! live in each tracked span's extensions as [`TraceIds`]. A request that
+//! arrives with a `traceparent` header joins the caller's trace through
+//! [`adopt_remote_parent`], and [`current_traceparent`] gives outbound calls
+//! the header that makes the callee's spans children of ours.
+//!
+//! What gets written is governed by [`VelocitySampling`] (set through
+//! `PUT /velocity/sampling`): excluded routes are always dropped, and
+//! successful spans can be sampled per trace or dropped below a minimum
+//! duration. Failed spans are kept regardless.
+//!
+//! Outbound calls made through `send_traced` (see `trace_propagation`) open
+//! a [`CLIENT_SPAN_NAME`] span, which is written under the
+//! [`CLIENT_SERVICE`] service so it doesn't mix with the inbound endpoints.
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -12,18 +27,22 @@ use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
+
+use crate::velocity::live::route_matches;
 use tracing::span::{Attributes, Id, Record};
-use tracing::{Event, Subscriber};
+use tracing::{Event, Span, Subscriber};
 use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
-use tracing_subscriber::Layer;
+use tracing_subscriber::{Layer, Registry};
 
 const VELOCITY_FILENAME: &str = "supervisor-velocity.jsonl";
 
-// Global atomic counter for generating unique IDs.
-static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+/// Name of the spans `send_traced` opens around outbound requests.
+pub const CLIENT_SPAN_NAME: &str = "HTTP client request";
+
+/// Service of outbound request spans.
+pub const CLIENT_SERVICE: &str = "supervisor-client";
 
 // ============================================================================
 // Types
@@ -49,12 +68,182 @@ pub struct SpanEntry {
     pub error: Option<String>,
 }
 
+/// Trace position of a tracked span, kept in its registry extensions so
+/// inbound propagation can rewrite it and outbound calls can read it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceIds {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+}
+
+/// The caller's position from an inbound `traceparent` header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteParent {
+    pub trace_id: String,
+    pub parent_span_id: String,
+}
+
+/// Parse a W3C `traceparent` (`00-<32 hex trace>-<16 hex span>-<2 hex flags>`).
+/// All-zero ids and the reserved `ff` version are rejected.
+pub fn parse_traceparent(value: &str) -> Option<RemoteParent> {
+    let mut parts = value.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_span_id = parts.next()?;
+    let flags = parts.next()?;
+    let is_hex = |s: &str, len: usize| {
+        s.len() == len
+            && s.chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    };
+    if !is_hex(version, 2) || version == "ff" || !is_hex(flags, 2) {
+        return None;
+    }
+    // Version 00 has exactly four fields; later versions may append more.
+    if version == "00" && parts.next().is_some() {
+        return None;
+    }
+    if !is_hex(trace_id, 32) || !is_hex(parent_span_id, 16) {
+        return None;
+    }
+    if trace_id.bytes().all(|b| b == b'0') || parent_span_id.bytes().all(|b| b == b'0') {
+        return None;
+    }
+    Some(RemoteParent {
+        trace_id: trace_id.to_string(),
+        parent_span_id: parent_span_id.to_string(),
+    })
+}
+
+/// Run `f` on the registry entry of `span`, if it is a live span of the
+/// global subscriber.
+fn with_registry_span<T>(
+    span: &Span,
+    f: impl FnOnce(tracing_subscriber::registry::SpanRef<'_, Registry>) -> Option<T>,
+) -> Option<T> {
+    span.with_subscriber(|(id, dispatch)| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        f(registry.span(id)?)
+    })
+    .flatten()
+}
+
+/// Move the nearest tracked span at or above `span` into the caller's
+/// trace. Spans opened under it afterwards inherit the adopted trace id.
+pub fn adopt_remote_parent(span: &Span, parent: RemoteParent) {
+    with_registry_span(span, |s| {
+        let tracked = s
+            .scope()
+            .find(|s| s.extensions().get::<TraceIds>().is_some())?;
+        let mut extensions = tracked.extensions_mut();
+        let ids = extensions.get_mut::<TraceIds>()?;
+        ids.trace_id = parent.trace_id;
+        ids.parent_span_id = Some(parent.parent_span_id);
+        Some(())
+    });
+}
+
+/// `traceparent` naming the nearest tracked span around the current one,
+/// for outbound requests. `None` outside any tracked span.
+pub fn current_traceparent() -> Option<String> {
+    with_registry_span(&Span::current(), |s| {
+        s.scope().find_map(|s| {
+            s.extensions()
+                .get::<TraceIds>()
+                .map(|ids| format!("00-{}-{}-01", ids.trace_id, ids.span_id))
+        })
+    })
+}
+
+/// Which spans the layer writes. Persisted as `velocity_sampling` in the
+/// settings file; the default keeps everything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VelocitySampling {
+    /// Fraction of traces whose successful spans are kept, `0.0..=1.0`.
+    pub sample_rate: f64,
+    /// Successful spans faster than this are dropped.
+    pub min_duration_ms: f64,
+    /// Request paths never recorded; exact, or a prefix ending in `*`.
+    pub exclude_routes: Vec<String>,
+}
+
+impl VelocitySampling {
+    pub const KEEP_ALL: Self = Self {
+        sample_rate: 1.0,
+        min_duration_ms: 0.0,
+        exclude_routes: Vec::new(),
+    };
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            return Err("sample_rate must be between 0 and 1".to_string());
+        }
+        if self.min_duration_ms.is_nan() || self.min_duration_ms < 0.0 {
+            return Err("min_duration_ms must not be negative".to_string());
+        }
+        if self.exclude_routes.iter().any(|r| r.trim().is_empty()) {
+            return Err("exclude_routes must not contain empty patterns".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a finished span should be written. Sampling hashes the trace
+    /// id, so a trace is kept or dropped as a whole.
+    pub fn keeps(&self, path: &str, trace_id: &str, duration_ms: f64, success: bool) -> bool {
+        if self.exclude_routes.iter().any(|p| route_matches(p, path)) {
+            return false;
+        }
+        if !success {
+            return true;
+        }
+        if duration_ms < self.min_duration_ms {
+            return false;
+        }
+        if self.sample_rate >= 1.0 {
+            return true;
+        }
+        let bucket = trace_id
+            .get(..8)
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .unwrap_or(0);
+        (bucket as f64) < self.sample_rate * (u32::MAX as f64 + 1.0)
+    }
+}
+
+impl Default for VelocitySampling {
+    fn default() -> Self {
+        Self::KEEP_ALL
+    }
+}
+
+static SAMPLING: RwLock<VelocitySampling> = RwLock::new(VelocitySampling::KEEP_ALL);
+
+/// Replace the layer's sampling config.
+pub fn set_sampling(sampling: VelocitySampling) {
+    if let Ok(mut current) = SAMPLING.write() {
+        *current = sampling;
+    }
+}
+
+pub fn sampling() -> VelocitySampling {
+    SAMPLING.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Path part of a span's recorded `uri` (tower-http records the request
+/// URI, which may be absolute).
+fn uri_path(uri: &str) -> &str {
+    let path = match uri.find("://") {
+        Some(i) => uri[i + 3..].find('/').map_or("/", |j| &uri[i + 3 + j..]),
+        None => uri,
+    };
+    path.split(['?', '#']).next().unwrap_or(path)
+}
+
 /// Internal tracking data for an in-flight span.
 #[derive(Debug, Clone)]
 struct ActiveSpanData {
-    trace_id: String,
-    span_id: String,
-    parent_span_id: Option<String>,
     name: String,
     start_time: chrono::DateTime<Utc>,
     attributes: HashMap<String, serde_json::Value>,
@@ -90,25 +279,22 @@ impl VelocityLayer {
 
     /// Check whether a span should be tracked.
     ///
-    /// We only care about tower-http spans (HTTP request timing).
+    /// We only care about tower-http spans (HTTP request timing) and our
+    /// own outbound request spans.
     fn should_track(metadata: &tracing::Metadata<'_>) -> bool {
         let target = metadata.target();
         let name = metadata.name();
-        target.contains("tower_http") || name == "HTTP request"
+        target.contains("tower_http") || name == "HTTP request" || name == CLIENT_SPAN_NAME
     }
 
-    /// Generate a unique ID using the system timestamp and an atomic counter.
-    ///
-    /// Format: `{unix_millis_lower32}-{counter}` which is unique within a
-    /// single process and reasonably collision-free across restarts.
+    /// Generate a random W3C span id (16 lowercase hex digits).
     fn generate_id() -> String {
-        let ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
-        let count = ID_COUNTER.fetch_add(1, Ordering::Relaxed);
-        // Use lower 8 hex digits of timestamp + counter for brevity
-        format!("{:08x}-{:04x}", ts & 0xFFFF_FFFF, count & 0xFFFF)
+        uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+    }
+
+    /// Generate a random W3C trace id (32 lowercase hex digits).
+    fn generate_trace_id() -> String {
+        uuid::Uuid::new_v4().simple().to_string()
     }
 
     /// Get or lazily open the output file.
@@ -160,24 +346,22 @@ where
             return;
         }
 
-        // Resolve parent span ID and trace ID
-        let (parent_span_id, trace_id) = if let Some(parent) = span.parent() {
-            let parent_id = parent.id();
-            if let Ok(spans) = self.active_spans.read() {
-                if let Some(parent_data) = spans.get(&parent_id.into_u64()) {
-                    (
-                        Some(parent_data.span_id.clone()),
-                        parent_data.trace_id.clone(),
-                    )
-                } else {
-                    (None, Self::generate_id())
-                }
-            } else {
-                (None, Self::generate_id())
-            }
-        } else {
-            (None, Self::generate_id())
-        };
+        // Join the trace of the nearest tracked ancestor, or start one
+        let (parent_span_id, trace_id) = span
+            .scope()
+            .skip(1)
+            .find_map(|ancestor| {
+                ancestor
+                    .extensions()
+                    .get::<TraceIds>()
+                    .map(|ids| (Some(ids.span_id.clone()), ids.trace_id.clone()))
+            })
+            .unwrap_or_else(|| (None, Self::generate_trace_id()));
+        span.extensions_mut().insert(TraceIds {
+            trace_id,
+            span_id: Self::generate_id(),
+            parent_span_id,
+        });
 
         // Collect initial attributes from the span
         let mut attributes = HashMap::new();
@@ -185,9 +369,6 @@ where
         attrs.record(&mut visitor);
 
         let data = ActiveSpanData {
-            trace_id,
-            span_id: Self::generate_id(),
-            parent_span_id,
             name: metadata.name().to_string(),
             start_time: Utc::now(),
             attributes,
@@ -231,7 +412,7 @@ where
         }
     }
 
-    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
         let span_data = {
             if let Ok(mut spans) = self.active_spans.write() {
                 spans.remove(&id.into_u64())
@@ -239,25 +420,53 @@ where
                 None
             }
         };
+        let ids = ctx
+            .span(&id)
+            .and_then(|s| s.extensions().get::<TraceIds>().cloned());
 
-        if let Some(data) = span_data {
+        if let (Some(data), Some(ids)) = (span_data, ids) {
             let end_time = Utc::now();
             let duration = end_time
                 .signed_duration_since(data.start_time)
                 .num_milliseconds() as f64;
 
+            // Outbound spans report failure through `otel.status_code`
+            let attr_str = |key: &str| data.attributes.get(key).and_then(|v| v.as_str());
+            let success = !data.had_error && attr_str("otel.status_code") != Some("ERROR");
+            let error = data
+                .error_message
+                .clone()
+                .or_else(|| attr_str("error.message").map(str::to_string));
+            let service = if data.name == CLIENT_SPAN_NAME {
+                CLIENT_SERVICE
+            } else {
+                "supervisor"
+            };
+
+            let path = attr_str("uri")
+                .or_else(|| attr_str("http.url"))
+                .map(uri_path)
+                .unwrap_or_default();
+            let kept = SAMPLING
+                .read()
+                .map(|s| s.keeps(path, &ids.trace_id, duration, success))
+                .unwrap_or(true);
+            if !kept {
+                return;
+            }
+
             let entry = SpanEntry {
-                service: "supervisor".to_string(),
-                trace_id: data.trace_id,
-                span_id: data.span_id,
-                parent_span_id: data.parent_span_id,
+                service: service.to_string(),
+                trace_id: ids.trace_id,
+                span_id: ids.span_id,
+                parent_span_id: ids.parent_span_id,
                 name: data.name,
                 start_ts: data.start_time.to_rfc3339(),
                 end_ts: Some(end_time.to_rfc3339()),
                 duration_ms: Some(duration),
                 attributes: data.attributes,
-                success: !data.had_error,
-                error: data.error_message,
+                success,
+                error,
             };
 
             self.write_entry(&entry);
@@ -442,6 +651,73 @@ mod tests {
         assert_ne!(id1, id3);
     }
 
+    #[test]
+    fn test_generated_ids_are_w3c_shaped() {
+        let trace_id = VelocityLayer::generate_trace_id();
+        let span_id = VelocityLayer::generate_id();
+        let header = format!("00-{}-{}-01", trace_id, span_id);
+        let parent = parse_traceparent(&header).expect("generated ids form a valid traceparent");
+        assert_eq!(parent.trace_id, trace_id);
+        assert_eq!(parent.parent_span_id, span_id);
+    }
+
+    #[test]
+    fn test_parse_traceparent() {
+        let parent =
+            parse_traceparent("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01").unwrap();
+        assert_eq!(parent.trace_id, "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(parent.parent_span_id, "b7ad6b7169203331");
+
+        for bad in [
+            "",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "ff-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra",
+        ] {
+            assert!(
+                parse_traceparent(bad).is_none(),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sampling_keeps() {
+        let sampling = VelocitySampling {
+            sample_rate: 0.5,
+            min_duration_ms: 10.0,
+            exclude_routes: vec!["/health".to_string(), "/static/*".to_string()],
+        };
+        let low = "00000000000000000000000000000001";
+        let high = "ffffffff000000000000000000000001";
+
+        assert!(sampling.keeps("/runners", low, 20.0, true));
+        assert!(!sampling.keeps("/runners", high, 20.0, true));
+        assert!(!sampling.keeps("/runners", low, 5.0, true));
+        // Failures survive sampling and the duration floor, not exclusion.
+        assert!(sampling.keeps("/runners", high, 5.0, false));
+        assert!(!sampling.keeps("/health", low, 20.0, false));
+        assert!(!sampling.keeps("/static/app.js", low, 20.0, true));
+
+        assert!(VelocitySampling::default().keeps("/health", high, 0.0, true));
+        assert!(VelocitySampling {
+            sample_rate: 1.5,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_uri_path() {
+        assert_eq!(uri_path("/health?verbose=1"), "/health");
+        assert_eq!(uri_path("http://localhost:9875/runners/x"), "/runners/x");
+        assert_eq!(uri_path("http://localhost:9875"), "/");
+    }
+
     #[test]
     fn test_should_track_tower_http() {
         // We can't easily construct tracing::Metadata in tests, but we verify
@@ -0,0 +1,178 @@
+//! Backend API coverage sweep.
+//!
+//! Page tests only time the one endpoint each test case names. With
+//! `?api_sweep=true` a run, after its pages, calls every GET route of the
+//! backend once and records each route's status and response time, so a
+//! slow endpoint no page test names still shows up. Routes come from the
+//! backend's OpenAPI spec (`/openapi.json`), minus templated paths such as
+//! `/runs/{id}` that there is no value to fill in for, or from
+//! `QONTINUI_SUPERVISOR_API_SWEEP_ROUTES` (comma-separated paths) when set.
+//! `GET /velocity-tests/runs/{id}/api-report` reports the results.
+
+use serde::{Deserialize, Serialize};
+
+pub const ROUTES_ENV: &str = "QONTINUI_SUPERVISOR_API_SWEEP_ROUTES";
+
+/// Where the backend serves its OpenAPI spec.
+pub const SPEC_PATH: &str = "/openapi.json";
+
+/// Routes listed in a report's `slowest`.
+const SLOWEST_COUNT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiRouteResult {
+    pub run_id: String,
+    pub path: String,
+    /// `None` when the request got no response.
+    pub status_code: Option<i64>,
+    pub response_time_ms: Option<f64>,
+    pub tested_at: String,
+}
+
+impl ApiRouteResult {
+    /// Answered with a 2xx or 3xx.
+    pub fn is_ok(&self) -> bool {
+        self.status_code.is_some_and(|s| (200..400).contains(&s))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiLatencyReport {
+    pub run_id: String,
+    pub routes_total: usize,
+    /// 2xx and 3xx responses.
+    pub routes_ok: usize,
+    /// Error statuses and requests without a response.
+    pub routes_failed: usize,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    /// Slowest routes that responded, slowest first.
+    pub slowest: Vec<ApiRouteResult>,
+    /// Every route, by path.
+    pub routes: Vec<ApiRouteResult>,
+}
+
+/// Routes from `QONTINUI_SUPERVISOR_API_SWEEP_ROUTES`, if set.
+pub fn configured_routes() -> Option<Vec<String>> {
+    let raw = std::env::var(ROUTES_ENV).ok()?;
+    let routes: Vec<String> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|r| {
+            if r.starts_with('/') {
+                r.to_string()
+            } else {
+                format!("/{}", r)
+            }
+        })
+        .collect();
+    (!routes.is_empty()).then_some(routes)
+}
+
+/// GET paths of an OpenAPI spec that take no path parameters, sorted.
+pub fn get_paths(spec: &serde_json::Value) -> Vec<String> {
+    let Some(paths) = spec.get("paths").and_then(|p| p.as_object()) else {
+        return Vec::new();
+    };
+    let mut routes: Vec<String> = paths
+        .iter()
+        .filter(|(path, ops)| !path.contains('{') && ops.get("get").is_some())
+        .map(|(path, _)| path.clone())
+        .collect();
+    routes.sort();
+    routes
+}
+
+pub fn report(run_id: &str, mut routes: Vec<ApiRouteResult>) -> ApiLatencyReport {
+    routes.sort_by(|a, b| a.path.cmp(&b.path));
+    let routes_ok = routes.iter().filter(|r| r.is_ok()).count();
+
+    let mut timed: Vec<&ApiRouteResult> = routes
+        .iter()
+        .filter(|r| r.response_time_ms.is_some())
+        .collect();
+    timed.sort_by(|a, b| {
+        b.response_time_ms
+            .partial_cmp(&a.response_time_ms)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let times: Vec<f64> = timed
+        .iter()
+        .rev()
+        .filter_map(|r| r.response_time_ms)
+        .collect();
+
+    ApiLatencyReport {
+        run_id: run_id.to_string(),
+        routes_total: routes.len(),
+        routes_ok,
+        routes_failed: routes.len() - routes_ok,
+        p50_ms: percentile(&times, 50.0),
+        p95_ms: percentile(&times, 95.0),
+        max_ms: times.last().copied(),
+        slowest: timed.into_iter().take(SLOWEST_COUNT).cloned().collect(),
+        routes,
+    }
+}
+
+/// Nearest-rank percentile of ascending `sorted`.
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let idx = ((p / 100.0) * (sorted.len() as f64 - 1.0)).round() as usize;
+    Some(sorted[idx.min(sorted.len() - 1)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(path: &str, status: Option<i64>, ms: Option<f64>) -> ApiRouteResult {
+        ApiRouteResult {
+            run_id: "run".to_string(),
+            path: path.to_string(),
+            status_code: status,
+            response_time_ms: ms,
+            tested_at: "2026-03-01T10:00:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn spec_yields_untemplated_get_paths() {
+        let spec = serde_json::json!({
+            "openapi": "3.1.0",
+            "paths": {
+                "/api/v1/runs": {"get": {}, "post": {}},
+                "/api/v1/runs/{run_id}": {"get": {}},
+                "/api/v1/auth/login": {"post": {}},
+                "/health": {"get": {}},
+            }
+        });
+        assert_eq!(get_paths(&spec), vec!["/api/v1/runs", "/health"]);
+        assert!(get_paths(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn report_counts_failures_and_ranks_slowest() {
+        let report = report(
+            "run",
+            vec![
+                route("/b", Some(200), Some(300.0)),
+                route("/a", Some(200), Some(100.0)),
+                route("/c", Some(500), Some(900.0)),
+                route("/d", None, None),
+            ],
+        );
+        assert_eq!(report.routes_total, 4);
+        assert_eq!(report.routes_ok, 2);
+        assert_eq!(report.routes_failed, 2);
+        assert_eq!(report.routes[0].path, "/a");
+        assert_eq!(report.slowest[0].path, "/c");
+        assert_eq!(report.slowest.len(), 3);
+        assert_eq!(report.p50_ms, Some(300.0));
+        assert_eq!(report.max_ms, Some(900.0));
+    }
+}
@@ -0,0 +1,181 @@
+//! Post-load functional assertions for velocity tests.
+//!
+//! A test case can list [`Assertion`]s that are checked against a UI Bridge
+//! snapshot once the page has loaded. Each failure costs the test case's
+//! penalty (default [`ASSERTION_FAILURE_PENALTY`]) from its 0-100 score, so a
+//! page that loads fast but renders the wrong thing no longer scores well.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Points deducted per failed assertion unless a test case overrides it.
+pub const ASSERTION_FAILURE_PENALTY: f64 = 15.0;
+
+/// One check against the snapshot. Element selectors use the same
+/// case-insensitive substring match as `TestCase::key_element` (id, label,
+/// type, or text content).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Assertion {
+    ElementPresent(String),
+    ElementAbsent(String),
+    /// Some matching element's label or text contains `text`.
+    TextContains {
+        element: String,
+        text: String,
+    },
+    /// At least `min` elements match.
+    MinCount {
+        element: String,
+        min: usize,
+    },
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct AssertionOutcome {
+    pub passed: usize,
+    pub failures: Vec<String>,
+}
+
+/// Elements array from a snapshot response:
+/// `{ "success": true, "data": { "elements": [...] } }`.
+fn elements(snapshot: &Value) -> &[Value] {
+    snapshot
+        .get("data")
+        .and_then(|d| d.get("elements"))
+        .and_then(|e| e.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn element_text(element: &Value) -> impl Iterator<Item = &str> {
+    let state = element.get("state");
+    [
+        element.get("label"),
+        state.and_then(|s| s.get("text_content")),
+        state.and_then(|s| s.get("textContent")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|v| v.as_str())
+}
+
+/// Whether `element` matches a lowercased selector.
+fn element_matches(element: &Value, key_lower: &str) -> bool {
+    ["id", "type"]
+        .iter()
+        .filter_map(|f| element.get(*f).and_then(|v| v.as_str()))
+        .chain(element_text(element))
+        .any(|s| s.to_lowercase().contains(key_lower))
+}
+
+/// Elements in `snapshot` matching `key` (case-insensitive substring).
+pub fn matching_elements<'a>(snapshot: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    let key_lower = key.to_lowercase();
+    elements(snapshot)
+        .iter()
+        .filter(move |e| element_matches(e, &key_lower))
+}
+
+pub fn evaluate(snapshot: &Value, assertions: &[Assertion]) -> AssertionOutcome {
+    let mut outcome = AssertionOutcome::default();
+    for assertion in assertions {
+        let failure = match assertion {
+            Assertion::ElementPresent(el) => matching_elements(snapshot, el)
+                .next()
+                .is_none()
+                .then(|| format!("expected element '{}' to be present", el)),
+            Assertion::ElementAbsent(el) => matching_elements(snapshot, el)
+                .next()
+                .is_some()
+                .then(|| format!("expected element '{}' to be absent", el)),
+            Assertion::TextContains { element, text } => {
+                let text_lower = text.to_lowercase();
+                let found = matching_elements(snapshot, element)
+                    .any(|e| element_text(e).any(|t| t.to_lowercase().contains(&text_lower)));
+                (!found).then(|| format!("expected '{}' to contain text '{}'", element, text))
+            }
+            Assertion::MinCount { element, min } => {
+                let count = matching_elements(snapshot, element).count();
+                (count < *min).then(|| {
+                    format!(
+                        "expected at least {} '{}' element(s), found {}",
+                        min, element, count
+                    )
+                })
+            }
+        };
+        match failure {
+            Some(f) => outcome.failures.push(f),
+            None => outcome.passed += 1,
+        }
+    }
+    outcome
+}
+
+/// Deduct `penalty` per failure, flooring at 0.
+pub fn apply_penalty(score: f64, failures: usize, penalty: f64) -> f64 {
+    (score - failures as f64 * penalty).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot() -> Value {
+        json!({"success": true, "data": {"elements": [
+            {"id": "project-card-1", "type": "card", "label": "Alpha"},
+            {"id": "project-card-2", "type": "card", "state": {"textContent": "Beta project"}},
+            {"id": "nav", "type": "nav"}
+        ]}})
+    }
+
+    #[test]
+    fn passing_assertions_count_as_passed() {
+        let outcome = evaluate(
+            &snapshot(),
+            &[
+                Assertion::ElementPresent("project-card".into()),
+                Assertion::ElementAbsent("error-banner".into()),
+                Assertion::TextContains {
+                    element: "project-card".into(),
+                    text: "beta".into(),
+                },
+                Assertion::MinCount {
+                    element: "project-card".into(),
+                    min: 2,
+                },
+            ],
+        );
+        assert_eq!(outcome.passed, 4);
+        assert!(outcome.failures.is_empty());
+    }
+
+    #[test]
+    fn failures_are_described() {
+        let outcome = evaluate(
+            &snapshot(),
+            &[
+                Assertion::ElementAbsent("nav".into()),
+                Assertion::MinCount {
+                    element: "project-card".into(),
+                    min: 3,
+                },
+            ],
+        );
+        assert_eq!(outcome.passed, 0);
+        assert_eq!(
+            outcome.failures,
+            vec![
+                "expected element 'nav' to be absent".to_string(),
+                "expected at least 3 'project-card' element(s), found 2".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn penalty_floors_at_zero() {
+        assert_eq!(apply_penalty(80.0, 1, 15.0), 65.0);
+        assert_eq!(apply_penalty(20.0, 2, 15.0), 0.0);
+    }
+}
@@ -0,0 +1,215 @@
+//! Pinned baseline run and per-page comparison against it.
+//!
+//! One velocity test run can be pinned as the baseline
+//! (`PUT /velocity-tests/baseline`); `GET /velocity-tests/compare` then
+//! reports each page's load time, TTFB, transfer size and long tasks against
+//! it. A metric regresses when it got worse by more than the pin's
+//! `tolerance_pct` and by more than its noise floor, so a 2ms TTFB wobble on
+//! a fast page isn't flagged.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::VelocityTestResult;
+
+/// Default tolerated worsening of a metric, in percent of the baseline.
+pub const DEFAULT_TOLERANCE_PCT: f64 = 10.0;
+
+const LOAD_FLOOR_MS: f64 = 50.0;
+const TTFB_FLOOR_MS: f64 = 20.0;
+const TRANSFER_FLOOR_BYTES: f64 = 10.0 * 1024.0;
+const LONG_TASK_FLOOR: f64 = 0.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselinePin {
+    pub run_id: String,
+    pub pinned_at: String,
+    pub tolerance_pct: f64,
+}
+
+/// One metric of one page; lower is better for all of them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricDelta {
+    pub baseline: Option<f64>,
+    pub current: Option<f64>,
+    /// `current - baseline`.
+    pub delta: Option<f64>,
+    pub regressed: bool,
+}
+
+impl MetricDelta {
+    fn new(baseline: Option<f64>, current: Option<f64>, tolerance_pct: f64, floor: f64) -> Self {
+        let delta = current.zip(baseline).map(|(c, b)| c - b);
+        let regressed = match (baseline, delta) {
+            (Some(b), Some(d)) => d > floor && d > b.abs() * tolerance_pct / 100.0,
+            _ => false,
+        };
+        Self {
+            baseline,
+            current,
+            delta,
+            regressed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageComparison {
+    pub page: String,
+    pub load_time_ms: MetricDelta,
+    pub ttfb_ms: MetricDelta,
+    pub transfer_size_bytes: MetricDelta,
+    pub long_task_count: MetricDelta,
+    /// Any of the metrics regressed.
+    pub regressed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VelocityCompareReport {
+    pub run_id: String,
+    pub baseline_run_id: String,
+    pub tolerance_pct: f64,
+    /// Pages in either run, by name.
+    pub pages: Vec<PageComparison>,
+    pub regressed_pages: usize,
+}
+
+pub fn compare(
+    pin: &BaselinePin,
+    run_id: &str,
+    current: &[VelocityTestResult],
+    baseline: &[VelocityTestResult],
+) -> VelocityCompareReport {
+    let mut pages: BTreeMap<&str, (Option<&VelocityTestResult>, Option<&VelocityTestResult>)> =
+        BTreeMap::new();
+    for r in baseline {
+        pages.entry(&r.test_name).or_default().0 = Some(r);
+    }
+    for r in current {
+        pages.entry(&r.test_name).or_default().1 = Some(r);
+    }
+
+    let tol = pin.tolerance_pct;
+    let pages: Vec<PageComparison> = pages
+        .into_iter()
+        .map(|(page, (b, c))| {
+            let metric = |f: fn(&VelocityTestResult) -> Option<f64>, floor| {
+                MetricDelta::new(b.and_then(f), c.and_then(f), tol, floor)
+            };
+            let load_time_ms = metric(|r| r.load_time_ms, LOAD_FLOOR_MS);
+            let ttfb_ms = metric(|r| r.ttfb_ms, TTFB_FLOOR_MS);
+            let transfer_size_bytes = metric(
+                |r| Some(r.total_transfer_size_bytes as f64),
+                TRANSFER_FLOOR_BYTES,
+            );
+            let long_task_count = metric(|r| Some(r.long_task_count as f64), LONG_TASK_FLOOR);
+            let regressed = load_time_ms.regressed
+                || ttfb_ms.regressed
+                || transfer_size_bytes.regressed
+                || long_task_count.regressed;
+            PageComparison {
+                page: page.to_string(),
+                load_time_ms,
+                ttfb_ms,
+                transfer_size_bytes,
+                long_task_count,
+                regressed,
+            }
+        })
+        .collect();
+
+    VelocityCompareReport {
+        run_id: run_id.to_string(),
+        baseline_run_id: pin.run_id.clone(),
+        tolerance_pct: tol,
+        regressed_pages: pages.iter().filter(|p| p.regressed).count(),
+        pages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        page: &str,
+        load_ms: f64,
+        ttfb_ms: f64,
+        bytes: i64,
+        long_tasks: i64,
+    ) -> VelocityTestResult {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "run_id": "run",
+            "test_name": page,
+            "page_url": "/",
+            "load_time_ms": load_ms,
+            "console_errors": 0,
+            "element_found": true,
+            "score": 90.0,
+            "error": null,
+            "tested_at": "2026-03-01T10:00:00+00:00",
+            "api_response_time_ms": null,
+            "api_status_code": null,
+            "ttfb_ms": ttfb_ms,
+            "dom_interactive_ms": null,
+            "dom_complete_ms": null,
+            "fcp_ms": null,
+            "long_task_count": long_tasks,
+            "long_task_total_ms": 0.0,
+            "resource_count": 0,
+            "total_transfer_size_bytes": bytes,
+            "slowest_resource_ms": 0.0,
+            "bottleneck": null,
+            "diagnostics_json": null,
+        }))
+        .unwrap()
+    }
+
+    fn pin() -> BaselinePin {
+        BaselinePin {
+            run_id: "base".to_string(),
+            pinned_at: "2026-03-01T10:00:00+00:00".to_string(),
+            tolerance_pct: DEFAULT_TOLERANCE_PCT,
+        }
+    }
+
+    #[test]
+    fn flags_metrics_worse_than_tolerance_and_floor() {
+        let baseline = [
+            result("Dashboard", 1000.0, 100.0, 500_000, 1),
+            result("Runs", 1000.0, 10.0, 500_000, 1),
+        ];
+        let current = [
+            // Load +30%, transfer +40%, long tasks +1
+            result("Dashboard", 1300.0, 105.0, 700_000, 2),
+            // TTFB doubled but only by 10ms, load +4%
+            result("Runs", 1040.0, 20.0, 500_000, 1),
+        ];
+        let report = compare(&pin(), "run", &current, &baseline);
+        assert_eq!(report.regressed_pages, 1);
+
+        let dash = &report.pages[0];
+        assert_eq!(dash.page, "Dashboard");
+        assert_eq!(dash.load_time_ms.delta, Some(300.0));
+        assert!(dash.load_time_ms.regressed);
+        assert!(!dash.ttfb_ms.regressed);
+        assert!(dash.transfer_size_bytes.regressed);
+        assert!(dash.long_task_count.regressed);
+
+        let runs = &report.pages[1];
+        assert!(!runs.regressed);
+    }
+
+    #[test]
+    fn pages_missing_from_one_run_have_no_delta() {
+        let baseline = [result("Dashboard", 1000.0, 100.0, 0, 0)];
+        let current = [result("Settings", 1000.0, 100.0, 0, 0)];
+        let report = compare(&pin(), "run", &current, &baseline);
+        assert_eq!(report.pages.len(), 2);
+        assert!(report
+            .pages
+            .iter()
+            .all(|p| p.load_time_ms.delta.is_none() && !p.regressed));
+    }
+}
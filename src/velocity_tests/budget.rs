@@ -0,0 +1,186 @@
+//! Per-page performance budgets.
+//!
+//! A test case can carry a [`PageBudget`]; each of its limits that a result
+//! exceeds is recorded as a violation and the result is marked over budget.
+//! Unlike the 0-100 score this is a hard pass/fail, so CI can gate on
+//! `GET /velocity-tests/runs/{id}/budget` reporting no page over budget.
+
+use serde::{Deserialize, Serialize};
+
+use super::VelocityTestResult;
+
+/// Limits a page must stay within. Unset limits aren't checked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageBudget {
+    pub max_load_ms: Option<f64>,
+    pub max_transfer_kb: Option<f64>,
+    pub max_long_tasks: Option<i64>,
+}
+
+impl PageBudget {
+    pub fn validate(&self) -> Result<(), String> {
+        let limits = [self.max_load_ms, self.max_transfer_kb];
+        if limits.iter().flatten().any(|v| !v.is_finite() || *v < 0.0)
+            || self.max_long_tasks.is_some_and(|n| n < 0)
+        {
+            return Err("budget limits must not be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Violations of this budget by `result`. A page that failed to load
+    /// breaks its load-time limit.
+    pub fn violations(&self, result: &VelocityTestResult) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(max) = self.max_load_ms {
+            match result.load_time_ms {
+                Some(ms) if ms <= max => {}
+                Some(ms) => violations.push(format!("load {:.0}ms > {:.0}ms", ms, max)),
+                None => violations.push(format!("no load time (budget {:.0}ms)", max)),
+            }
+        }
+        if let Some(max) = self.max_transfer_kb {
+            let kb = result.total_transfer_size_bytes as f64 / 1024.0;
+            if kb > max {
+                violations.push(format!("transfer {:.0}KB > {:.0}KB", kb, max));
+            }
+        }
+        if let Some(max) = self.max_long_tasks {
+            if result.long_task_count > max {
+                violations.push(format!("{} long tasks > {}", result.long_task_count, max));
+            }
+        }
+        violations
+    }
+}
+
+/// Record `budget`'s verdict on `result`; results without a budget stay
+/// `None`.
+pub fn apply(budget: Option<&PageBudget>, result: &mut VelocityTestResult) {
+    let Some(budget) = budget else {
+        return;
+    };
+    let violations = budget.violations(result);
+    result.budget_passed = Some(violations.is_empty());
+    result.budget_violations = Some(violations);
+}
+
+/// A page that went over its budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetViolation {
+    pub test_name: String,
+    pub violations: Vec<String>,
+}
+
+/// Budget verdict for a whole run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetCompliance {
+    pub run_id: String,
+    /// No page with a budget went over it.
+    pub passed: bool,
+    /// Pages that had a budget.
+    pub pages_checked: usize,
+    pub over_budget: Vec<BudgetViolation>,
+}
+
+pub fn compliance(run_id: &str, results: &[VelocityTestResult]) -> BudgetCompliance {
+    let checked: Vec<_> = results
+        .iter()
+        .filter(|r| r.budget_passed.is_some())
+        .collect();
+    let over_budget: Vec<_> = checked
+        .iter()
+        .filter(|r| r.budget_passed == Some(false))
+        .map(|r| BudgetViolation {
+            test_name: r.test_name.clone(),
+            violations: r.budget_violations.clone().unwrap_or_default(),
+        })
+        .collect();
+    BudgetCompliance {
+        run_id: run_id.to_string(),
+        passed: over_budget.is_empty(),
+        pages_checked: checked.len(),
+        over_budget,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, load_ms: Option<f64>, bytes: i64, long_tasks: i64) -> VelocityTestResult {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "run_id": "run",
+            "test_name": name,
+            "page_url": "/",
+            "load_time_ms": load_ms,
+            "console_errors": 0,
+            "element_found": true,
+            "score": 90.0,
+            "error": null,
+            "tested_at": "2026-03-01T10:00:00+00:00",
+            "api_response_time_ms": null,
+            "api_status_code": null,
+            "ttfb_ms": null,
+            "dom_interactive_ms": null,
+            "dom_complete_ms": null,
+            "fcp_ms": null,
+            "long_task_count": long_tasks,
+            "long_task_total_ms": 0.0,
+            "resource_count": 0,
+            "total_transfer_size_bytes": bytes,
+            "slowest_resource_ms": 0.0,
+            "bottleneck": null,
+            "diagnostics_json": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn each_exceeded_limit_is_a_violation() {
+        let budget = PageBudget {
+            max_load_ms: Some(2000.0),
+            max_transfer_kb: Some(500.0),
+            max_long_tasks: Some(2),
+        };
+        assert!(budget
+            .violations(&result("ok", Some(1500.0), 100 * 1024, 2))
+            .is_empty());
+        assert_eq!(
+            budget.violations(&result("slow", Some(2500.0), 600 * 1024, 3)),
+            vec![
+                "load 2500ms > 2000ms".to_string(),
+                "transfer 600KB > 500KB".to_string(),
+                "3 long tasks > 2".to_string(),
+            ]
+        );
+        assert_eq!(budget.violations(&result("failed", None, 0, 0)).len(), 1);
+    }
+
+    #[test]
+    fn run_passes_only_without_pages_over_budget() {
+        let budget = PageBudget {
+            max_load_ms: Some(2000.0),
+            ..Default::default()
+        };
+        let mut results = vec![
+            result("Dashboard", Some(1000.0), 0, 0),
+            result("Runs", Some(3000.0), 0, 0),
+            result("Unbudgeted", Some(9000.0), 0, 0),
+        ];
+        apply(Some(&budget), &mut results[0]);
+        apply(Some(&budget), &mut results[1]);
+        apply(None, &mut results[2]);
+
+        let c = compliance("run", &results);
+        assert!(!c.passed);
+        assert_eq!(c.pages_checked, 2);
+        assert_eq!(c.over_budget.len(), 1);
+        assert_eq!(c.over_budget[0].test_name, "Runs");
+
+        results[1].budget_passed = None;
+        assert!(compliance("run", &results).passed);
+    }
+}
@@ -0,0 +1,95 @@
+//! Cold vs warm page loads.
+//!
+//! With `?cold_warm=true` each page is measured twice: first after clearing
+//! the browser cache (cold), then straight away again (warm). The cold pass
+//! is the stored result and feeds the score; the warm pass and the cold −
+//! warm deltas are attached as [`ColdWarm`]. Bundle weight only shows up
+//! cold, so a large load delta points at what is downloaded, while a warm
+//! load that stays slow points at rendering.
+
+use serde::{Deserialize, Serialize};
+
+use super::VelocityTestResult;
+
+/// The warm pass of a page and how much faster it was than the cold one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColdWarm {
+    /// The bridge confirmed the cache was cleared before the cold pass;
+    /// when `false` the cold pass may have been served from cache.
+    pub cache_cleared: bool,
+    pub warm_load_time_ms: Option<f64>,
+    pub warm_score: Option<f64>,
+    pub warm_ttfb_ms: Option<f64>,
+    pub warm_fcp_ms: Option<f64>,
+    pub warm_transfer_size_bytes: i64,
+    pub warm_long_task_count: i64,
+    /// Cold − warm load time.
+    pub load_delta_ms: Option<f64>,
+    /// Cold − warm bytes transferred.
+    pub transfer_delta_bytes: i64,
+}
+
+impl ColdWarm {
+    pub fn new(cold: &VelocityTestResult, warm: &VelocityTestResult, cache_cleared: bool) -> Self {
+        Self {
+            cache_cleared,
+            warm_load_time_ms: warm.load_time_ms,
+            warm_score: warm.score,
+            warm_ttfb_ms: warm.ttfb_ms,
+            warm_fcp_ms: warm.fcp_ms,
+            warm_transfer_size_bytes: warm.total_transfer_size_bytes,
+            warm_long_task_count: warm.long_task_count,
+            load_delta_ms: cold.load_time_ms.zip(warm.load_time_ms).map(|(c, w)| c - w),
+            transfer_delta_bytes: cold.total_transfer_size_bytes - warm.total_transfer_size_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(load_ms: Option<f64>, bytes: i64) -> VelocityTestResult {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "run_id": "run",
+            "test_name": "Dashboard",
+            "page_url": "/",
+            "load_time_ms": load_ms,
+            "console_errors": 0,
+            "element_found": true,
+            "score": 90.0,
+            "error": null,
+            "tested_at": "2026-03-01T10:00:00+00:00",
+            "api_response_time_ms": null,
+            "api_status_code": null,
+            "ttfb_ms": null,
+            "dom_interactive_ms": null,
+            "dom_complete_ms": null,
+            "fcp_ms": null,
+            "long_task_count": 1,
+            "long_task_total_ms": 0.0,
+            "resource_count": 0,
+            "total_transfer_size_bytes": bytes,
+            "slowest_resource_ms": 0.0,
+            "bottleneck": null,
+            "diagnostics_json": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn deltas_are_cold_minus_warm() {
+        let cw = ColdWarm::new(
+            &result(Some(2400.0), 900_000),
+            &result(Some(900.0), 50_000),
+            true,
+        );
+        assert_eq!(cw.load_delta_ms, Some(1500.0));
+        assert_eq!(cw.transfer_delta_bytes, 850_000);
+        assert_eq!(cw.warm_load_time_ms, Some(900.0));
+
+        let failed_cold = ColdWarm::new(&result(None, 0), &result(Some(900.0), 0), false);
+        assert_eq!(failed_cold.load_delta_ms, None);
+    }
+}
@@ -1,9 +1,20 @@
 use chrono::Utc;
 use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+use super::api_sweep::ApiRouteResult;
+use super::baseline::BaselinePin;
+use super::schedule::VelocityTestSchedule;
+use super::tests::{default_test_cases, TestCase};
+use super::trends::PageTrendPoint;
 use super::{VelocityTestResult, VelocityTestRun, VelocityTestTrendPoint};
+use crate::run_environment::EnvironmentSnapshot;
+use crate::velocity_improvement::{
+    FixMode, LoopProgress, ResumableLoop, VelocityImprovementConfig, VelocityImprovementIteration,
+    VelocityImprovementLoop,
+};
 
 pub struct VelocityTestDb {
     conn: Mutex<Connection>,
@@ -41,6 +52,10 @@ impl VelocityTestDb {
 
     fn init_schema(&self) -> anyhow::Result<()> {
         let conn = self.conn();
+        // Seed only a table created now, so deleting every case sticks.
+        let seed_cases = conn
+            .prepare("SELECT id FROM velocity_test_cases LIMIT 0")
+            .is_err();
         conn.execute_batch(
             "
             CREATE TABLE IF NOT EXISTS velocity_test_runs (
@@ -69,10 +84,256 @@ impl VelocityTestDb {
             CREATE INDEX IF NOT EXISTS idx_vtr_run_id ON velocity_test_results(run_id);
             CREATE INDEX IF NOT EXISTS idx_vtruns_started ON velocity_test_runs(started_at);
             CREATE INDEX IF NOT EXISTS idx_vtruns_status ON velocity_test_runs(status);
+
+            CREATE TABLE IF NOT EXISTS velocity_test_cases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                page_url TEXT NOT NULL,
+                key_element TEXT NOT NULL,
+                api_endpoint TEXT NOT NULL,
+                auth_json TEXT,
+                assertions_json TEXT NOT NULL DEFAULT '[]',
+                assertion_penalty REAL,
+                weights_json TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                budget_json TEXT,
+                retries INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS velocity_test_api_results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id TEXT NOT NULL REFERENCES velocity_test_runs(id),
+                path TEXT NOT NULL,
+                status_code INTEGER,
+                response_time_ms REAL,
+                tested_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_vtar_run_id ON velocity_test_api_results(run_id);
+
+            CREATE TABLE IF NOT EXISTS velocity_test_baseline (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                run_id TEXT NOT NULL,
+                pinned_at TEXT NOT NULL,
+                tolerance_pct REAL NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS velocity_improvement_loops (
+                id TEXT PRIMARY KEY,
+                started_at TEXT NOT NULL,
+                max_iterations INTEGER NOT NULL,
+                target_score REAL NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS velocity_improvement_iterations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                loop_id TEXT NOT NULL REFERENCES velocity_improvement_loops(id),
+                iteration INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                completed_at TEXT,
+                run_id TEXT,
+                overall_score REAL,
+                per_page_scores_json TEXT NOT NULL DEFAULT '[]',
+                fix_applied INTEGER NOT NULL DEFAULT 0,
+                fix_summary TEXT,
+                exit_reason TEXT,
+                reverted_to TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_vii_loop_id ON velocity_improvement_iterations(loop_id);
+
+            CREATE TABLE IF NOT EXISTS velocity_test_schedules (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                cron TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                concurrency INTEGER NOT NULL DEFAULT 0,
+                strict INTEGER NOT NULL DEFAULT 0,
+                last_fired_at TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
         ",
         )?;
+        if seed_cases {
+            for tc in default_test_cases() {
+                insert_test_case(&conn, &tc)?;
+            }
+        }
         // Run diagnostic columns migration
         self.migrate_diagnostics(&conn)?;
+        self.migrate_environment(&conn)?;
+        self.migrate_assertions(&conn)?;
+        self.migrate_budgets(&conn)?;
+        self.migrate_run_source(&conn)?;
+        self.migrate_screenshots(&conn)?;
+        self.migrate_cold_warm(&conn)?;
+        self.migrate_leak_check(&conn)?;
+        self.migrate_improvement_fix_mode(&conn)?;
+        self.migrate_improvement_ab(&conn)?;
+        self.migrate_improvement_diff(&conn)?;
+        self.migrate_improvement_resume(&conn)?;
+        Ok(())
+    }
+
+    /// Add the per-run environment snapshot column if it doesn't exist yet.
+    fn migrate_environment(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT environment_json FROM velocity_test_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE velocity_test_runs ADD COLUMN environment_json TEXT;")?;
+        }
+        Ok(())
+    }
+
+    /// Add the assertion outcome columns if they don't exist yet.
+    fn migrate_assertions(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT assertions_passed FROM velocity_test_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_results ADD COLUMN assertions_passed INTEGER;
+                 ALTER TABLE velocity_test_results ADD COLUMN assertion_failures TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the run source column if it doesn't exist yet; older runs were
+    /// all started by hand.
+    fn migrate_run_source(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT source FROM velocity_test_runs LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_runs ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the screenshot path column if it doesn't exist yet.
+    fn migrate_screenshots(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT screenshot_path FROM velocity_test_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_results ADD COLUMN screenshot_path TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the cold/warm column if it doesn't exist yet.
+    fn migrate_cold_warm(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT cold_warm_json FROM velocity_test_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_results ADD COLUMN cold_warm_json TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the leak check column if it doesn't exist yet.
+    fn migrate_leak_check(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT leak_check_json FROM velocity_test_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_results ADD COLUMN leak_check_json TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the improvement iteration fix mode column if it doesn't exist yet.
+    fn migrate_improvement_fix_mode(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT fix_mode FROM velocity_improvement_iterations LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_improvement_iterations ADD COLUMN fix_mode TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the improvement iteration A/B comparison column if it doesn't
+    /// exist yet.
+    fn migrate_improvement_ab(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT ab_comparison_json FROM velocity_improvement_iterations LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_improvement_iterations ADD COLUMN ab_comparison_json TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the improvement iteration diff column if it doesn't exist yet.
+    fn migrate_improvement_diff(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT diff FROM velocity_improvement_iterations LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_improvement_iterations ADD COLUMN diff TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the improvement loop resume columns if they don't exist yet.
+    fn migrate_improvement_resume(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT progress_json FROM velocity_improvement_loops LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_improvement_loops ADD COLUMN config_json TEXT;
+                 ALTER TABLE velocity_improvement_loops ADD COLUMN progress_json TEXT;
+                 ALTER TABLE velocity_improvement_loops ADD COLUMN finished_at TEXT;",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the budget and retry columns if they don't exist yet.
+    fn migrate_budgets(&self, conn: &Connection) -> anyhow::Result<()> {
+        if conn
+            .prepare("SELECT budget_json FROM velocity_test_cases LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch("ALTER TABLE velocity_test_cases ADD COLUMN budget_json TEXT;")?;
+        }
+        if conn
+            .prepare("SELECT retries FROM velocity_test_cases LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_cases ADD COLUMN retries INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+        if conn
+            .prepare("SELECT budget_passed FROM velocity_test_results LIMIT 0")
+            .is_err()
+        {
+            conn.execute_batch(
+                "ALTER TABLE velocity_test_results ADD COLUMN budget_passed INTEGER;
+                 ALTER TABLE velocity_test_results ADD COLUMN budget_violations TEXT;",
+            )?;
+        }
         Ok(())
     }
 
@@ -124,19 +385,46 @@ impl VelocityTestDb {
     pub fn insert_run(&self, run: &VelocityTestRun) -> anyhow::Result<()> {
         let conn = self.conn();
         conn.execute(
-            "INSERT INTO velocity_test_runs (id, started_at, status, tests_total, tests_completed)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO velocity_test_runs (id, started_at, status, tests_total, tests_completed, source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
                 run.id,
                 run.started_at,
                 run.status,
                 run.tests_total,
                 run.tests_completed,
+                run.source,
             ],
         )?;
         Ok(())
     }
 
+    pub fn set_run_environment(
+        &self,
+        run_id: &str,
+        snapshot: &EnvironmentSnapshot,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE velocity_test_runs SET environment_json=?2 WHERE id=?1",
+            params![run_id, serde_json::to_string(snapshot)?],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_run_environment(&self, run_id: &str) -> anyhow::Result<Option<EnvironmentSnapshot>> {
+        let conn = self.conn();
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT environment_json FROM velocity_test_runs WHERE id=?1",
+                params![run_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(json.and_then(|s| serde_json::from_str(&s).ok()))
+    }
+
     pub fn update_run_progress(&self, run_id: &str, tests_completed: i64) -> anyhow::Result<()> {
         let conn = self.conn();
         conn.execute(
@@ -163,7 +451,7 @@ impl VelocityTestDb {
     pub fn list_runs(&self) -> anyhow::Result<Vec<VelocityTestRun>> {
         let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, started_at, completed_at, overall_score, status, tests_total, tests_completed
+            "SELECT id, started_at, completed_at, overall_score, status, tests_total, tests_completed, source
              FROM velocity_test_runs ORDER BY started_at DESC LIMIT 50",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -175,6 +463,7 @@ impl VelocityTestDb {
                 status: row.get(4)?,
                 tests_total: row.get(5)?,
                 tests_completed: row.get(6)?,
+                source: row.get(7)?,
             })
         })?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
@@ -184,7 +473,7 @@ impl VelocityTestDb {
         let conn = self.conn();
         let result = conn
             .query_row(
-                "SELECT id, started_at, completed_at, overall_score, status, tests_total, tests_completed
+                "SELECT id, started_at, completed_at, overall_score, status, tests_total, tests_completed, source
                  FROM velocity_test_runs WHERE id=?1",
                 params![run_id],
                 |row| {
@@ -196,6 +485,7 @@ impl VelocityTestDb {
                         status: row.get(4)?,
                         tests_total: row.get(5)?,
                         tests_completed: row.get(6)?,
+                        source: row.get(7)?,
                     })
                 },
             )
@@ -214,8 +504,9 @@ impl VelocityTestDb {
                 run_id, test_name, page_url, load_time_ms, console_errors, element_found, score, error, tested_at,
                 api_response_time_ms, api_status_code, ttfb_ms, dom_interactive_ms, dom_complete_ms, fcp_ms,
                 long_task_count, long_task_total_ms, resource_count, total_transfer_size_bytes, slowest_resource_ms,
-                bottleneck, diagnostics_json
-             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+                bottleneck, diagnostics_json, assertions_passed, assertion_failures,
+                budget_passed, budget_violations, screenshot_path, cold_warm_json, leak_check_json
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)",
             params![
                 result.run_id,
                 result.test_name,
@@ -239,6 +530,29 @@ impl VelocityTestDb {
                 result.slowest_resource_ms,
                 result.bottleneck,
                 result.diagnostics_json,
+                result.assertions_passed.map(|p| p as i64),
+                result
+                    .assertion_failures
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                result.budget_passed.map(|p| p as i64),
+                result
+                    .budget_violations
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                result.screenshot_path,
+                result
+                    .cold_warm
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                result
+                    .leak_check
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
             ],
         )?;
         Ok(conn.last_insert_rowid())
@@ -250,7 +564,9 @@ impl VelocityTestDb {
             "SELECT id, run_id, test_name, page_url, load_time_ms, console_errors, element_found, score, error, tested_at,
                     api_response_time_ms, api_status_code, ttfb_ms, dom_interactive_ms, dom_complete_ms, fcp_ms,
                     long_task_count, long_task_total_ms, resource_count, total_transfer_size_bytes, slowest_resource_ms,
-                    bottleneck, diagnostics_json
+                    bottleneck, diagnostics_json, assertions_passed, assertion_failures,
+                    budget_passed, budget_violations, screenshot_path, cold_warm_json,
+                    leak_check_json
              FROM velocity_test_results WHERE run_id=?1 ORDER BY id",
         )?;
         let rows = stmt.query_map(params![run_id], |row| {
@@ -278,24 +594,435 @@ impl VelocityTestDb {
                 slowest_resource_ms: row.get::<_, Option<f64>>(20)?.unwrap_or(0.0),
                 bottleneck: row.get(21)?,
                 diagnostics_json: row.get(22)?,
+                assertions_passed: row.get::<_, Option<i64>>(23)?.map(|p| p != 0),
+                assertion_failures: row
+                    .get::<_, Option<String>>(24)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                budget_passed: row.get::<_, Option<i64>>(25)?.map(|p| p != 0),
+                budget_violations: row
+                    .get::<_, Option<String>>(26)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                screenshot_path: row.get(27)?,
+                cold_warm: row
+                    .get::<_, Option<String>>(28)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                leak_check: row
+                    .get::<_, Option<String>>(29)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    // ========================================================================
+    // Test cases
+    // ========================================================================
+
+    pub fn list_test_cases(&self) -> anyhow::Result<Vec<TestCase>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY id", SELECT_TEST_CASE))?;
+        let rows = stmt.query_map([], read_test_case)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn get_test_case(&self, id: i64) -> anyhow::Result<Option<TestCase>> {
+        let conn = self.conn();
+        let result = conn
+            .query_row(
+                &format!("{} WHERE id=?1", SELECT_TEST_CASE),
+                params![id],
+                read_test_case,
+            )
+            .optional()?;
+        Ok(result)
+    }
+
+    /// Insert `tc` (its `id` is ignored) and return the new id. Fails on a
+    /// duplicate name.
+    pub fn create_test_case(&self, tc: &TestCase) -> anyhow::Result<i64> {
+        let conn = self.conn();
+        insert_test_case(&conn, tc)
+    }
+
+    /// Replace test case `id` with `tc`. Returns `false` if there is no such
+    /// test case.
+    pub fn update_test_case(&self, id: i64, tc: &TestCase) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let changed = conn.execute(
+            "UPDATE velocity_test_cases SET
+                name=?2, page_url=?3, key_element=?4, api_endpoint=?5, auth_json=?6,
+                assertions_json=?7, assertion_penalty=?8, weights_json=?9, updated_at=?10,
+                budget_json=?11, retries=?12
+             WHERE id=?1",
+            params![
+                id,
+                tc.name,
+                tc.page_url,
+                tc.key_element,
+                tc.api_endpoint,
+                tc.auth.as_ref().map(serde_json::to_string).transpose()?,
+                serde_json::to_string(&tc.assertions)?,
+                tc.assertion_penalty,
+                serde_json::to_string(&tc.weights)?,
+                Utc::now().to_rfc3339(),
+                tc.budget.as_ref().map(serde_json::to_string).transpose()?,
+                tc.retries,
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    pub fn delete_test_case(&self, id: i64) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let changed = conn.execute("DELETE FROM velocity_test_cases WHERE id=?1", params![id])?;
+        Ok(changed > 0)
+    }
+
+    // ========================================================================
+    // API sweep
+    // ========================================================================
+
+    pub fn insert_api_result(&self, result: &ApiRouteResult) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO velocity_test_api_results (run_id, path, status_code, response_time_ms,
+                tested_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                result.run_id,
+                result.path,
+                result.status_code,
+                result.response_time_ms,
+                result.tested_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_api_results(&self, run_id: &str) -> anyhow::Result<Vec<ApiRouteResult>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT run_id, path, status_code, response_time_ms, tested_at
+             FROM velocity_test_api_results WHERE run_id=?1 ORDER BY path",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| {
+            Ok(ApiRouteResult {
+                run_id: row.get(0)?,
+                path: row.get(1)?,
+                status_code: row.get(2)?,
+                response_time_ms: row.get(3)?,
+                tested_at: row.get(4)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    // ========================================================================
+    // Baseline pin
+    // ========================================================================
+
+    pub fn get_baseline(&self) -> anyhow::Result<Option<BaselinePin>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT run_id, pinned_at, tolerance_pct FROM velocity_test_baseline WHERE id=1",
+            [],
+            |row| {
+                Ok(BaselinePin {
+                    run_id: row.get(0)?,
+                    pinned_at: row.get(1)?,
+                    tolerance_pct: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(Into::into)
+    }
+
+    pub fn set_baseline(&self, pin: &BaselinePin) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO velocity_test_baseline (id, run_id, pinned_at, tolerance_pct)
+             VALUES (1, ?1, ?2, ?3)",
+            params![pin.run_id, pin.pinned_at, pin.tolerance_pct],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear_baseline(&self) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let deleted = conn.execute("DELETE FROM velocity_test_baseline WHERE id=1", [])?;
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Schedules
+    // ========================================================================
+
+    pub fn list_schedules(&self) -> anyhow::Result<Vec<VelocityTestSchedule>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, cron, enabled, concurrency, strict, last_fired_at, created_at,
+                    updated_at
+             FROM velocity_test_schedules ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(VelocityTestSchedule {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                cron: row.get(2)?,
+                enabled: row.get::<_, i64>(3)? != 0,
+                concurrency: row.get::<_, i64>(4)? as usize,
+                strict: row.get::<_, i64>(5)? != 0,
+                last_fired_at: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
             })
         })?;
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    pub fn insert_schedule(&self, schedule: &VelocityTestSchedule) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO velocity_test_schedules (id, name, cron, enabled, concurrency, strict,
+                last_fired_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                schedule.id,
+                schedule.name,
+                schedule.cron,
+                schedule.enabled as i64,
+                schedule.concurrency as i64,
+                schedule.strict as i64,
+                schedule.last_fired_at,
+                schedule.created_at,
+                schedule.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_schedule_enabled(&self, id: &str, enabled: bool) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE velocity_test_schedules SET enabled=?2, updated_at=?3 WHERE id=?1",
+            params![id, enabled as i64, Utc::now().to_rfc3339()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn mark_schedule_fired(&self, id: &str, fired_at: &str) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE velocity_test_schedules SET last_fired_at=?2 WHERE id=?1",
+            params![id, fired_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_schedule(&self, id: &str) -> anyhow::Result<bool> {
+        let conn = self.conn();
+        let deleted = conn.execute(
+            "DELETE FROM velocity_test_schedules WHERE id=?1",
+            params![id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Improvement loop history
+    // ========================================================================
+
+    pub fn insert_improvement_loop(
+        &self,
+        id: &str,
+        started_at: &str,
+        max_iterations: u32,
+        target_score: f64,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO velocity_improvement_loops (id, started_at, max_iterations, target_score)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![id, started_at, max_iterations, target_score],
+        )?;
+        Ok(())
+    }
+
+    /// Record where a running loop would resume from, with its config.
+    pub fn save_improvement_progress(
+        &self,
+        id: &str,
+        config: &VelocityImprovementConfig,
+        progress: &LoopProgress,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE velocity_improvement_loops SET config_json=?2, progress_json=?3 WHERE id=?1",
+            params![
+                id,
+                serde_json::to_string(config)?,
+                serde_json::to_string(progress)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a loop as ended (target, limit, stop or error), so it isn't
+    /// offered for resuming.
+    pub fn finish_improvement_loop(&self, id: &str, finished_at: &str) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE velocity_improvement_loops SET finished_at=?2 WHERE id=?1",
+            params![id, finished_at],
+        )?;
+        Ok(())
+    }
+
+    /// The newest loop that was interrupted before it ended, if any.
+    pub fn get_resumable_loop(&self) -> anyhow::Result<Option<ResumableLoop>> {
+        let conn = self.conn();
+        let row = conn
+            .query_row(
+                "SELECT id, config_json, progress_json FROM velocity_improvement_loops
+                 WHERE finished_at IS NULL AND progress_json IS NOT NULL
+                 ORDER BY started_at DESC LIMIT 1",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((loop_id, config_json, progress_json)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(ResumableLoop {
+            iterations: improvement_iterations(&conn, &loop_id)?,
+            loop_id,
+            config: serde_json::from_str(&config_json)?,
+            progress: serde_json::from_str(&progress_json)?,
+        }))
+    }
+
+    pub fn insert_improvement_iteration(
+        &self,
+        loop_id: &str,
+        it: &VelocityImprovementIteration,
+    ) -> anyhow::Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO velocity_improvement_iterations (
+                loop_id, iteration, started_at, completed_at, run_id, overall_score,
+                per_page_scores_json, fix_applied, fix_summary, exit_reason, reverted_to,
+                fix_mode, ab_comparison_json, diff
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                loop_id,
+                it.iteration,
+                it.started_at,
+                it.completed_at,
+                it.run_id,
+                it.overall_score,
+                serde_json::to_string(&it.per_page_scores)?,
+                it.fix_applied as i64,
+                it.fix_summary,
+                it.exit_reason,
+                it.reverted_to,
+                it.fix_mode.map(FixMode::as_str),
+                it.ab_comparison
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                it.diff,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Patch the fix agent made in an iteration of a loop; `None` when the
+    /// iteration doesn't exist or recorded no diff.
+    pub fn get_improvement_diff(
+        &self,
+        loop_id: &str,
+        iteration: u32,
+    ) -> anyhow::Result<Option<String>> {
+        let conn = self.conn();
+        let diff = conn
+            .query_row(
+                "SELECT diff FROM velocity_improvement_iterations
+                 WHERE loop_id=?1 AND iteration=?2 ORDER BY id DESC LIMIT 1",
+                params![loop_id, iteration],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()?;
+        Ok(diff.flatten())
+    }
+
+    /// The last `limit` loops, newest first, each with its iterations in
+    /// order.
+    pub fn list_improvement_loops(
+        &self,
+        limit: i64,
+    ) -> anyhow::Result<Vec<VelocityImprovementLoop>> {
+        let conn = self.conn();
+        let mut loops_stmt = conn.prepare(
+            "SELECT id, started_at, max_iterations, target_score
+             FROM velocity_improvement_loops ORDER BY started_at DESC LIMIT ?1",
+        )?;
+        let loops = loops_stmt
+            .query_map(params![limit], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, f64>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = Vec::with_capacity(loops.len());
+        for (id, started_at, max_iterations, target_score) in loops {
+            let iterations = improvement_iterations(&conn, &id)?;
+            let last = iterations.last();
+            result.push(VelocityImprovementLoop {
+                id,
+                started_at,
+                max_iterations,
+                target_score,
+                completed_at: last.and_then(|it| it.completed_at.clone()),
+                exit_reason: last.and_then(|it| it.exit_reason.clone()),
+                first_score: iterations.first().and_then(|it| it.overall_score),
+                last_score: last.and_then(|it| it.overall_score),
+                iterations,
+            });
+        }
+        Ok(result)
+    }
+
     // ========================================================================
     // Trend
     // ========================================================================
 
-    pub fn get_trend(&self, limit: i64) -> anyhow::Result<Vec<VelocityTestTrendPoint>> {
+    /// Scores of the last `limit` completed runs, only those from `source`
+    /// when given.
+    pub fn get_trend(
+        &self,
+        limit: i64,
+        source: Option<&str>,
+    ) -> anyhow::Result<Vec<VelocityTestTrendPoint>> {
         let conn = self.conn();
         let mut stmt = conn.prepare(
             "SELECT id, started_at, overall_score
              FROM velocity_test_runs
              WHERE status = 'completed' AND overall_score IS NOT NULL
+               AND (?2 IS NULL OR source = ?2)
              ORDER BY started_at DESC LIMIT ?1",
         )?;
-        let rows = stmt.query_map(params![limit], |row| {
+        let rows = stmt.query_map(params![limit, source], |row| {
             Ok(VelocityTestTrendPoint {
                 run_id: row.get(0)?,
                 started_at: row.get(1)?,
@@ -307,4 +1034,313 @@ impl VelocityTestDb {
         points.reverse();
         Ok(points)
     }
+
+    /// `(page, metrics)` of every result in the last `limit` completed runs
+    /// (from `source` when given), oldest run first; only `page`'s results
+    /// when given.
+    pub fn get_page_history(
+        &self,
+        limit: i64,
+        page: Option<&str>,
+        source: Option<&str>,
+    ) -> anyhow::Result<Vec<(String, PageTrendPoint)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT r.test_name, r.run_id, runs.started_at, r.score, r.load_time_ms,
+                    r.api_response_time_ms, r.ttfb_ms, r.fcp_ms, r.console_errors,
+                    r.long_task_count, r.total_transfer_size_bytes
+             FROM velocity_test_results r
+             JOIN (SELECT id, started_at FROM velocity_test_runs
+                   WHERE status = 'completed' AND (?3 IS NULL OR source = ?3)
+                   ORDER BY started_at DESC LIMIT ?1) runs ON runs.id = r.run_id
+             WHERE ?2 IS NULL OR r.test_name = ?2
+             ORDER BY runs.started_at, r.id",
+        )?;
+        let rows = stmt.query_map(params![limit, page, source], |row| {
+            Ok((
+                row.get(0)?,
+                PageTrendPoint {
+                    run_id: row.get(1)?,
+                    started_at: row.get(2)?,
+                    score: row.get(3)?,
+                    load_time_ms: row.get(4)?,
+                    api_response_time_ms: row.get(5)?,
+                    ttfb_ms: row.get(6)?,
+                    fcp_ms: row.get(7)?,
+                    console_errors: row.get::<_, Option<i64>>(8)?.unwrap_or(0),
+                    long_task_count: row.get::<_, Option<i64>>(9)?.unwrap_or(0),
+                    total_transfer_size_bytes: row.get::<_, Option<i64>>(10)?.unwrap_or(0),
+                },
+            ))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
+
+const SELECT_TEST_CASE: &str = "SELECT id, name, page_url, key_element, api_endpoint, auth_json,
+        assertions_json, assertion_penalty, weights_json, budget_json, retries
+ FROM velocity_test_cases";
+
+fn insert_test_case(conn: &Connection, tc: &TestCase) -> anyhow::Result<i64> {
+    conn.execute(
+        "INSERT INTO velocity_test_cases (
+            name, page_url, key_element, api_endpoint, auth_json, assertions_json,
+            assertion_penalty, weights_json, updated_at, budget_json, retries
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            tc.name,
+            tc.page_url,
+            tc.key_element,
+            tc.api_endpoint,
+            tc.auth.as_ref().map(serde_json::to_string).transpose()?,
+            serde_json::to_string(&tc.assertions)?,
+            tc.assertion_penalty,
+            serde_json::to_string(&tc.weights)?,
+            Utc::now().to_rfc3339(),
+            tc.budget.as_ref().map(serde_json::to_string).transpose()?,
+            tc.retries,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn read_test_case(row: &rusqlite::Row<'_>) -> rusqlite::Result<TestCase> {
+    Ok(TestCase {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        page_url: row.get(2)?,
+        key_element: row.get(3)?,
+        api_endpoint: row.get(4)?,
+        auth: match row.get::<_, Option<String>>(5)? {
+            Some(_) => Some(json_column(row, 5)?),
+            None => None,
+        },
+        assertions: json_column(row, 6)?,
+        assertion_penalty: row.get(7)?,
+        weights: json_column(row, 8)?,
+        budget: match row.get::<_, Option<String>>(9)? {
+            Some(_) => Some(json_column(row, 9)?),
+            None => None,
+        },
+        retries: row.get(10)?,
+    })
+}
+
+/// A loop's iterations in order.
+fn improvement_iterations(
+    conn: &Connection,
+    loop_id: &str,
+) -> anyhow::Result<Vec<VelocityImprovementIteration>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT iteration, started_at, completed_at, run_id, overall_score,
+                per_page_scores_json, fix_applied, fix_summary, exit_reason, reverted_to,
+                fix_mode, ab_comparison_json
+         FROM velocity_improvement_iterations WHERE loop_id=?1 ORDER BY iteration, id",
+    )?;
+    let iterations = stmt
+        .query_map(params![loop_id], |row| {
+            Ok(VelocityImprovementIteration {
+                iteration: row.get(0)?,
+                started_at: row.get(1)?,
+                completed_at: row.get(2)?,
+                run_id: row.get(3)?,
+                overall_score: row.get(4)?,
+                per_page_scores: json_column(row, 5)?,
+                fix_applied: row.get::<_, i64>(6)? != 0,
+                fix_summary: row.get(7)?,
+                exit_reason: row.get(8)?,
+                reverted_to: row.get(9)?,
+                fix_mode: row
+                    .get::<_, Option<String>>(10)?
+                    .as_deref()
+                    .and_then(FixMode::parse),
+                ab_comparison: row
+                    .get::<_, Option<String>>(11)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                // Served on its own by `get_improvement_diff`
+                diff: None,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(iterations)
+}
+
+fn json_column<T: DeserializeOwned>(row: &rusqlite::Row<'_>, idx: usize) -> rusqlite::Result<T> {
+    let json: String = row.get(idx)?;
+    serde_json::from_str(&json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velocity_tests::budget::PageBudget;
+    use crate::velocity_tests::tests::TestAuth;
+
+    #[test]
+    fn new_database_is_seeded_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityTestDb::new(dir.path()).unwrap();
+        let cases = db.list_test_cases().unwrap();
+        assert_eq!(cases.len(), default_test_cases().len());
+        for tc in &cases {
+            assert!(db.delete_test_case(tc.id).unwrap());
+        }
+        drop(db);
+
+        let db = VelocityTestDb::new(dir.path()).unwrap();
+        assert!(db.list_test_cases().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_case_crud_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityTestDb::new(dir.path()).unwrap();
+        let mut tc = default_test_cases().remove(1);
+        tc.name = "Profile".to_string();
+        tc.weights.load_time = 60.0;
+        tc.budget = Some(PageBudget {
+            max_load_ms: Some(2000.0),
+            ..Default::default()
+        });
+        let id = db.create_test_case(&tc).unwrap();
+
+        let stored = db.get_test_case(id).unwrap().unwrap();
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.weights, tc.weights);
+        assert_eq!(stored.budget, tc.budget);
+        assert!(matches!(stored.auth, Some(TestAuth::ApiLogin { .. })));
+        // Names are unique
+        assert!(db.create_test_case(&tc).is_err());
+
+        tc.page_url = "/profile".to_string();
+        assert!(db.update_test_case(id, &tc).unwrap());
+        assert_eq!(db.get_test_case(id).unwrap().unwrap().page_url, "/profile");
+        assert!(!db.update_test_case(id + 100, &tc).unwrap());
+
+        assert!(db.delete_test_case(id).unwrap());
+        assert!(db.get_test_case(id).unwrap().is_none());
+    }
+    #[test]
+    fn trend_filters_by_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityTestDb::new(dir.path()).unwrap();
+        for (i, source) in ["scheduled", "manual", "scheduled"].iter().enumerate() {
+            let id = format!("run-{}", i);
+            db.insert_run(&VelocityTestRun {
+                id: id.clone(),
+                started_at: format!("2026-03-0{}T10:00:00+00:00", i + 1),
+                completed_at: None,
+                overall_score: None,
+                status: "completed".to_string(),
+                tests_total: 0,
+                tests_completed: 0,
+                source: source.to_string(),
+            })
+            .unwrap();
+            db.conn()
+                .execute(
+                    "UPDATE velocity_test_runs SET overall_score = 80 WHERE id = ?1",
+                    params![id],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(db.get_trend(10, None).unwrap().len(), 3);
+        let scheduled = db.get_trend(10, Some("scheduled")).unwrap();
+        let ids: Vec<_> = scheduled.iter().map(|p| p.run_id.as_str()).collect();
+        assert_eq!(ids, ["run-0", "run-2"]);
+        assert_eq!(db.list_runs().unwrap()[0].source, "scheduled");
+    }
+
+    #[test]
+    fn improvement_iterations_are_grouped_by_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityTestDb::new(dir.path()).unwrap();
+        let iteration = |n: u32, score: f64, exit: Option<&str>| VelocityImprovementIteration {
+            iteration: n,
+            started_at: "2026-03-01T10:00:00+00:00".to_string(),
+            completed_at: Some("2026-03-01T10:05:00+00:00".to_string()),
+            run_id: Some(format!("run-{}", n)),
+            overall_score: Some(score),
+            per_page_scores: vec![crate::velocity_improvement::PageScore {
+                name: "Dashboard".to_string(),
+                score,
+                bottleneck: "Slow Render".to_string(),
+            }],
+            fix_applied: exit.is_none(),
+            fix_mode: exit.is_none().then_some(FixMode::Backend),
+            fix_summary: exit.is_none().then(|| "Lazy-loaded the chart".to_string()),
+            exit_reason: exit.map(str::to_string),
+            ab_comparison: None,
+            reverted_to: None,
+            diff: exit
+                .is_none()
+                .then(|| "--- a/frontend/chart.tsx\n+++ b/frontend/chart.tsx\n".to_string()),
+        };
+        db.insert_improvement_loop("old", "2026-03-01T10:00:00+00:00", 5, 80.0)
+            .unwrap();
+        db.insert_improvement_iteration("old", &iteration(1, 60.0, None))
+            .unwrap();
+        db.insert_improvement_iteration("old", &iteration(2, 82.0, Some("Target score reached")))
+            .unwrap();
+        db.insert_improvement_loop("new", "2026-03-02T10:00:00+00:00", 3, 90.0)
+            .unwrap();
+
+        let loops = db.list_improvement_loops(10).unwrap();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].id, "new");
+        assert!(loops[0].iterations.is_empty());
+        let old = &loops[1];
+        assert_eq!(old.iterations.len(), 2);
+        assert_eq!(old.first_score, Some(60.0));
+        assert_eq!(old.last_score, Some(82.0));
+        assert_eq!(old.exit_reason.as_deref(), Some("Target score reached"));
+        assert_eq!(old.iterations[0].per_page_scores[0].name, "Dashboard");
+        assert!(old.iterations[0].fix_applied);
+        assert_eq!(old.iterations[0].fix_mode, Some(FixMode::Backend));
+        assert_eq!(old.iterations[1].fix_mode, None);
+        assert!(old.iterations[0].diff.is_none());
+        assert!(db
+            .get_improvement_diff("old", 1)
+            .unwrap()
+            .is_some_and(|d| d.contains("chart.tsx")));
+        assert_eq!(db.get_improvement_diff("old", 2).unwrap(), None);
+        assert_eq!(db.get_improvement_diff("new", 1).unwrap(), None);
+    }
+
+    #[test]
+    fn only_unfinished_loops_are_resumable() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = VelocityTestDb::new(dir.path()).unwrap();
+        let config: VelocityImprovementConfig =
+            serde_json::from_value(serde_json::json!({"max_iterations": 4})).unwrap();
+        assert!(db.get_resumable_loop().unwrap().is_none());
+
+        db.insert_improvement_loop("done", "2026-03-01T10:00:00+00:00", 4, 80.0)
+            .unwrap();
+        let progress = LoopProgress {
+            iteration: 3,
+            previous_score: Some(71.5),
+            no_improvement_streak: 1,
+        };
+        db.save_improvement_progress("done", &config, &progress)
+            .unwrap();
+        db.finish_improvement_loop("done", "2026-03-01T11:00:00+00:00")
+            .unwrap();
+        // Started but never reached a measurement
+        db.insert_improvement_loop("fresh", "2026-03-02T10:00:00+00:00", 4, 80.0)
+            .unwrap();
+        assert!(db.get_resumable_loop().unwrap().is_none());
+
+        db.save_improvement_progress("fresh", &config, &progress)
+            .unwrap();
+        let resumable = db.get_resumable_loop().unwrap().unwrap();
+        assert_eq!(resumable.loop_id, "fresh");
+        assert_eq!(resumable.progress.iteration, 3);
+        assert_eq!(resumable.progress.previous_score, Some(71.5));
+        assert_eq!(resumable.config.max_iterations, 4);
+        assert!(resumable.iterations.is_empty());
+    }
 }
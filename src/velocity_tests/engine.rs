@@ -1,28 +1,130 @@
 use chrono::Utc;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::watch;
 use tracing::{error, info, warn};
 
+use super::api_sweep::{self, ApiRouteResult};
+use super::assertions::{self, AssertionOutcome};
+use super::budget;
+use super::cold_warm::ColdWarm;
 use super::db::VelocityTestDb;
-use super::tests::TEST_CASES;
+use super::headless::{HeadlessBrowser, HeadlessPage};
+use super::leak::{self, LeakCheck};
+use super::retry;
+use super::schedule::RunSource;
+use super::screenshots;
+use super::tests::{ScoreWeights, TestAuth, TestCase};
 use super::{VelocityTestResult, VelocityTestRun};
 use crate::log_capture::{LogLevel, LogSource};
+use crate::process::env_forwarders::{resolve_test_auto_login_for_state, ResolvedTestAutoLogin};
 use crate::state::SharedState;
 
-const WEB_FRONTEND_BASE: &str = "http://localhost:3001";
+pub const WEB_FRONTEND_BASE: &str = "http://localhost:3001";
 const ELEMENT_POLL_INTERVAL_MS: u64 = 500;
 const ELEMENT_POLL_TIMEOUT_MS: u64 = 15_000;
 const BETWEEN_TESTS_DELAY_MS: u64 = 1_000;
-const BACKEND_API_BASE: &str = "http://localhost:8000";
+pub const BACKEND_API_BASE: &str = "http://localhost:8000";
+
+/// Upper bound on pages measured at once.
+pub const MAX_CONCURRENCY: usize = 4;
+
+/// Names the browser context a parallel worker drives. UI Bridge builds with
+/// context support give each value its own isolated page (navigation,
+/// console errors, performance entries); older builds ignore it and the
+/// workers share one page, so strict mode is the only accurate choice there.
+pub const UI_BRIDGE_CONTEXT_HEADER: &str = "x-ui-bridge-context";
+
+/// What drives the browser during a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VelocityDriver {
+    /// The dev frontend's UI Bridge.
+    #[default]
+    UiBridge,
+    /// A headless Chromium launched for the run ([`super::headless`]).
+    Headless,
+}
+
+/// How a run schedules its pages.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct VelocityRunOptions {
+    /// Pages measured at once, capped at [`MAX_CONCURRENCY`]; `0` and `1`
+    /// run serially.
+    #[serde(default)]
+    pub concurrency: usize,
+    /// Strict measurement: one page at a time with a pause between pages,
+    /// whatever `concurrency` says.
+    #[serde(default)]
+    pub strict: bool,
+    /// Measure each page cold (browser cache cleared) and then warm.
+    #[serde(default)]
+    pub cold_warm: bool,
+    #[serde(default)]
+    pub driver: VelocityDriver,
+    /// Extra loads of each page to sample the JS heap after; `0` skips the
+    /// leak check. Capped at [`leak::MAX_ITERATIONS`].
+    #[serde(default)]
+    pub leak_iterations: usize,
+    /// Heap growth that counts as a leak; [`leak::DEFAULT_THRESHOLD_PCT`]
+    /// when unset.
+    #[serde(default)]
+    pub leak_threshold_pct: Option<f64>,
+    /// After the pages, time every GET route of the backend
+    /// ([`api_sweep`]).
+    #[serde(default)]
+    pub api_sweep: bool,
+    /// Set by the scheduler; start requests are always manual.
+    #[serde(skip)]
+    pub source: RunSource,
+}
+
+impl VelocityRunOptions {
+    pub fn workers(&self) -> usize {
+        if self.strict {
+            1
+        } else {
+            self.concurrency.clamp(1, MAX_CONCURRENCY)
+        }
+    }
+}
+
+/// Shared by the workers of one run.
+struct RunContext<'a> {
+    db: &'a VelocityTestDb,
+    state: &'a SharedState,
+    stop_rx: &'a watch::Receiver<bool>,
+    run_id: &'a str,
+    test_cases: &'a [TestCase],
+    creds: Option<&'a ResolvedTestAutoLogin>,
+    screenshot_dir: std::path::PathBuf,
+    cold_warm: bool,
+    leak_iterations: usize,
+    leak_threshold_pct: f64,
+    next: AtomicUsize,
+    completed: AtomicUsize,
+}
 
-/// Run all velocity tests sequentially.
+/// Run all velocity tests, `options.workers()` pages at a time.
 pub async fn run_velocity_tests(
     db: Arc<VelocityTestDb>,
     state: SharedState,
     stop_rx: watch::Receiver<bool>,
+    options: VelocityRunOptions,
 ) {
     let run_id = uuid::Uuid::new_v4().to_string();
-    let total = TEST_CASES.len() as i64;
+    let test_cases = match db.list_test_cases() {
+        Ok(cases) => cases,
+        Err(e) => {
+            error!("Failed to load velocity test cases: {}", e);
+            let mut vt = state.velocity_tests.write().await;
+            vt.running = false;
+            vt.stop_tx = None;
+            return;
+        }
+    };
+    let total = test_cases.len() as i64;
 
     let run = VelocityTestRun {
         id: run_id.clone(),
@@ -32,6 +134,7 @@ pub async fn run_velocity_tests(
         status: "running".to_string(),
         tests_total: total,
         tests_completed: 0,
+        source: options.source.as_str().to_string(),
     };
 
     if let Err(e) = db.insert_run(&run) {
@@ -42,12 +145,17 @@ pub async fn run_velocity_tests(
         return;
     }
 
+    let environment = crate::run_environment::capture(&state).await;
+    if let Err(e) = db.set_run_environment(&run_id, &environment) {
+        warn!("Failed to record velocity run environment: {}", e);
+    }
+
     // Update in-memory state (running flag already set by start_handler)
     {
         let mut vt = state.velocity_tests.write().await;
         vt.current_run_id = Some(run_id.clone());
         vt.current_test_index = 0;
-        vt.total_tests = TEST_CASES.len();
+        vt.total_tests = test_cases.len();
     }
 
     state
@@ -58,40 +166,256 @@ pub async fn run_velocity_tests(
             format!(
                 "Velocity tests started: run_id={}, tests={}",
                 run_id,
-                TEST_CASES.len()
+                test_cases.len()
             ),
         )
         .await;
 
-    let http_client = state.http_client.clone();
+    let creds = resolve_test_auto_login_for_state(&state).await;
+    let ctx = RunContext {
+        db: &db,
+        state: &state,
+        stop_rx: &stop_rx,
+        run_id: &run_id,
+        test_cases: &test_cases,
+        creds: creds.as_ref(),
+        screenshot_dir: screenshots::run_dir(&state.config.dev_logs_dir, &run_id),
+        cold_warm: options.cold_warm,
+        leak_iterations: options.leak_iterations.min(leak::MAX_ITERATIONS),
+        leak_threshold_pct: options
+            .leak_threshold_pct
+            .unwrap_or(leak::DEFAULT_THRESHOLD_PCT),
+        next: AtomicUsize::new(0),
+        completed: AtomicUsize::new(0),
+    };
+    let workers = options.workers();
+    let outcome = match options.driver {
+        VelocityDriver::UiBridge => {
+            if workers == 1 {
+                run_worker(&ctx, state.http_client.clone(), None).await;
+            } else {
+                info!("Running velocity tests with {} parallel workers", workers);
+                let clients: Vec<reqwest::Client> = (0..workers)
+                    .map(context_client)
+                    .collect::<anyhow::Result<_>>()
+                    .unwrap_or_else(|e| {
+                        warn!("Falling back to a shared client for parallel tests: {}", e);
+                        vec![state.http_client.clone(); workers]
+                    });
+                futures::future::join_all(clients.into_iter().map(|c| run_worker(&ctx, c, None)))
+                    .await;
+            }
+            Ok(())
+        }
+        VelocityDriver::Headless => run_headless_workers(&ctx, workers).await,
+    };
+    if options.api_sweep && outcome.is_ok() && !*stop_rx.borrow() {
+        run_api_sweep(&ctx).await;
+    }
+
+    if let Err(e) = outcome {
+        error!("Velocity test run {} failed: {}", run_id, e);
+        let _ = db.complete_run(&run_id, "failed");
+        state
+            .logs
+            .emit(
+                LogSource::Supervisor,
+                LogLevel::Error,
+                format!("Velocity tests failed: {}", e),
+            )
+            .await;
+    } else if *stop_rx.borrow() {
+        info!(
+            "Velocity tests cancelled after {}/{} tests",
+            ctx.completed.load(Ordering::SeqCst),
+            test_cases.len()
+        );
+        let _ = db.complete_run(&run_id, "stopped");
+    } else {
+        let _ = db.complete_run(&run_id, "completed");
+        if let Ok(Some(run)) = db.get_run(&run_id) {
+            state.velocity_tests.write().await.last_completed_score = run.overall_score;
+        }
+    }
+
+    // Clear in-memory state
+    {
+        let mut vt = state.velocity_tests.write().await;
+        vt.running = false;
+        vt.current_run_id = None;
+        vt.current_test_index = 0;
+        vt.total_tests = 0;
+    }
+
+    state
+        .logs
+        .emit(
+            LogSource::Supervisor,
+            LogLevel::Info,
+            format!("Velocity tests completed: run_id={}", run_id),
+        )
+        .await;
+}
 
-    for (i, test_case) in TEST_CASES.iter().enumerate() {
-        // Check for cancellation
-        if *stop_rx.borrow() {
+/// Launch headless Chromium and run `workers` workers, each in its own tab.
+async fn run_headless_workers(ctx: &RunContext<'_>, workers: usize) -> anyhow::Result<()> {
+    let browser = HeadlessBrowser::launch()
+        .await
+        .map_err(|e| anyhow::anyhow!("could not launch headless Chromium: {}", e))?;
+    let pages = futures::future::join_all((0..workers).map(|_| browser.new_page()))
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>();
+    let outcome = match pages {
+        Ok(pages) => {
             info!(
-                "Velocity tests cancelled at test {}/{}",
-                i,
-                TEST_CASES.len()
+                "Running velocity tests in headless Chromium with {} tab(s)",
+                workers
             );
-            let _ = db.complete_run(&run_id, "stopped");
+            futures::future::join_all(
+                pages
+                    .into_iter()
+                    .map(|p| run_worker(ctx, ctx.state.http_client.clone(), Some(p))),
+            )
+            .await;
+            Ok(())
+        }
+        Err(e) => Err(anyhow::anyhow!("could not open a headless tab: {}", e)),
+    };
+    browser.close().await;
+    outcome
+}
+
+/// Call each backend GET route once and record its status and timing.
+async fn run_api_sweep(ctx: &RunContext<'_>) {
+    let http_client = &ctx.state.http_client;
+    let routes = match api_sweep::configured_routes() {
+        Some(routes) => routes,
+        None => match fetch_openapi_routes(http_client).await {
+            Ok(routes) => routes,
+            Err(e) => {
+                warn!("API sweep skipped, can't read the OpenAPI spec: {}", e);
+                return;
+            }
+        },
+    };
+    info!("API sweep: {} GET routes", routes.len());
+    let mut failed = 0;
+    for path in &routes {
+        if *ctx.stop_rx.borrow() {
             break;
         }
+        let (response_time_ms, status_code) = measure_api_response(http_client, path, None).await;
+        let result = ApiRouteResult {
+            run_id: ctx.run_id.to_string(),
+            path: path.clone(),
+            status_code,
+            response_time_ms,
+            tested_at: Utc::now().to_rfc3339(),
+        };
+        if !result.is_ok() {
+            failed += 1;
+        }
+        if let Err(e) = ctx.db.insert_api_result(&result) {
+            warn!("Failed to record API sweep result for {}: {}", path, e);
+        }
+    }
+    info!("API sweep done: {} routes, {} failed", routes.len(), failed);
+}
+
+async fn fetch_openapi_routes(http_client: &reqwest::Client) -> anyhow::Result<Vec<String>> {
+    let resp = http_client
+        .get(format!("{}{}", BACKEND_API_BASE, api_sweep::SPEC_PATH))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("{} returned {}", api_sweep::SPEC_PATH, resp.status());
+    }
+    let spec: serde_json::Value = resp.json().await?;
+    Ok(api_sweep::get_paths(&spec))
+}
+
+/// HTTP client whose UI Bridge calls target browser context `slot`.
+fn context_client(slot: usize) -> anyhow::Result<reqwest::Client> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        UI_BRIDGE_CONTEXT_HEADER,
+        reqwest::header::HeaderValue::from_str(&format!("velocity-{}", slot))?,
+    );
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?)
+}
+
+/// Take test cases off the run's queue until it is empty or the run is
+/// stopped, recording each result. `page` is the worker's tab with the
+/// headless driver; without it pages are driven through the UI Bridge.
+async fn run_worker(
+    ctx: &RunContext<'_>,
+    http_client: reqwest::Client,
+    page: Option<HeadlessPage>,
+) {
+    let total = ctx.test_cases.len();
+    loop {
+        if *ctx.stop_rx.borrow() {
+            break;
+        }
+        let i = ctx.next.fetch_add(1, Ordering::SeqCst);
+        let Some(test_case) = ctx.test_cases.get(i) else {
+            break;
+        };
 
         // Update progress
         {
-            let mut vt = state.velocity_tests.write().await;
+            let mut vt = ctx.state.velocity_tests.write().await;
             vt.current_test_index = i;
         }
 
         info!(
             "Testing page {}/{}: {} ({})",
             i + 1,
-            TEST_CASES.len(),
+            total,
             test_case.name,
             test_case.page_url
         );
 
-        let result = run_single_test(&http_client, &run_id, test_case).await;
+        let mut attempts = Vec::new();
+        loop {
+            let attempt = if ctx.cold_warm {
+                run_cold_warm_test(
+                    &http_client,
+                    page.as_ref(),
+                    ctx.run_id,
+                    test_case,
+                    ctx.creds,
+                )
+                .await
+            } else {
+                run_single_test(
+                    &http_client,
+                    page.as_ref(),
+                    ctx.run_id,
+                    test_case,
+                    ctx.creds,
+                )
+                .await
+            };
+            let again = retry::is_flaky(&attempt)
+                && attempts.len() < test_case.retries as usize
+                && !*ctx.stop_rx.borrow();
+            attempts.push(attempt);
+            if !again {
+                break;
+            }
+            info!(
+                "  {} — flaky attempt {}, retrying",
+                test_case.name,
+                attempts.len()
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(BETWEEN_TESTS_DELAY_MS)).await;
+        }
+        let result = retry::pick_best(attempts);
 
         match &result {
             Ok(r) => {
@@ -110,11 +434,11 @@ pub async fn run_velocity_tests(
         }
 
         // Build result (either from success or error)
-        let db_result = match result {
+        let mut db_result = match result {
             Ok(r) => r,
             Err(e) => VelocityTestResult {
                 id: 0,
-                run_id: run_id.clone(),
+                run_id: ctx.run_id.to_string(),
                 test_name: test_case.name.to_string(),
                 page_url: test_case.page_url.to_string(),
                 load_time_ms: None,
@@ -136,136 +460,239 @@ pub async fn run_velocity_tests(
                 slowest_resource_ms: 0.0,
                 bottleneck: None,
                 diagnostics_json: None,
+                assertions_passed: None,
+                assertion_failures: None,
+                budget_passed: None,
+                budget_violations: None,
+                screenshot_path: None,
+                cold_warm: None,
+                leak_check: None,
             },
         };
+        budget::apply(test_case.budget.as_ref(), &mut db_result);
+        // Taken after measuring so the capture can't skew the timings
+        db_result.screenshot_path = screenshots::capture(
+            &http_client,
+            page.as_ref(),
+            &ctx.screenshot_dir,
+            &screenshots::file_name(i, &test_case.name),
+        )
+        .await
+        .map(|p| p.to_string_lossy().into_owned());
+
+        if ctx.leak_iterations > 0 && !*ctx.stop_rx.borrow() {
+            db_result.leak_check = run_leak_check(
+                &http_client,
+                page.as_ref(),
+                test_case,
+                ctx.leak_iterations,
+                ctx.leak_threshold_pct,
+            )
+            .await;
+            if let Some(check) = db_result.leak_check.as_ref().filter(|c| c.leaking) {
+                warn!(
+                    "  {} — JS heap grew {:.1}% over {} loads; possible leak",
+                    test_case.name,
+                    check.growth_pct,
+                    check.heap_bytes.len()
+                );
+            }
+        }
 
-        let _ = db.insert_result(&db_result);
-        let _ = db.update_run_progress(&run_id, (i + 1) as i64);
+        let _ = ctx.db.insert_result(&db_result);
+        let completed = ctx.completed.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = ctx.db.update_run_progress(ctx.run_id, completed as i64);
 
         // Delay between tests
-        if i + 1 < TEST_CASES.len() {
+        if ctx.next.load(Ordering::SeqCst) < total {
             tokio::time::sleep(std::time::Duration::from_millis(BETWEEN_TESTS_DELAY_MS)).await;
         }
     }
-
-    // Complete the run (unless cancelled above)
-    if !*stop_rx.borrow() {
-        let _ = db.complete_run(&run_id, "completed");
-    }
-
-    // Clear in-memory state
-    {
-        let mut vt = state.velocity_tests.write().await;
-        vt.running = false;
-        vt.current_run_id = None;
-        vt.current_test_index = 0;
-        vt.total_tests = 0;
-    }
-
-    state
-        .logs
-        .emit(
-            LogSource::Supervisor,
-            LogLevel::Info,
-            format!("Velocity tests completed: run_id={}", run_id),
-        )
-        .await;
 }
 
-/// Run a single test case: navigate, poll for element, collect diagnostics, compute score.
-async fn run_single_test(
+/// Measure a page with a cleared browser cache and then again warm. The cold
+/// pass is the result; a failed warm pass just leaves `cold_warm` unset.
+async fn run_cold_warm_test(
     http_client: &reqwest::Client,
+    page: Option<&HeadlessPage>,
     run_id: &str,
-    test_case: &super::tests::TestCase,
+    test_case: &TestCase,
+    creds: Option<&ResolvedTestAutoLogin>,
 ) -> anyhow::Result<VelocityTestResult> {
-    let now = Utc::now().to_rfc3339();
+    let cache_cleared = match page {
+        Some(page) => match page.clear_cache().await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to clear the browser cache: {}", e);
+                false
+            }
+        },
+        None => clear_browser_cache(http_client).await,
+    };
+    let mut cold = run_single_test(http_client, page, run_id, test_case, creds).await?;
+    match run_single_test(http_client, page, run_id, test_case, creds).await {
+        Ok(warm) => {
+            let cw = ColdWarm::new(&cold, &warm, cache_cleared);
+            info!(
+                "  {} — cold: {:.0}ms, warm: {:.0}ms (delta {:.0}ms)",
+                test_case.name,
+                cold.load_time_ms.unwrap_or(0.0),
+                cw.warm_load_time_ms.unwrap_or(0.0),
+                cw.load_delta_ms.unwrap_or(0.0)
+            );
+            cold.cold_warm = Some(cw);
+        }
+        Err(e) => warn!("  {} — warm pass failed: {}", test_case.name, e),
+    }
+    Ok(cold)
+}
 
-    // 1. Clear console errors before navigating
-    let _ = http_client
-        .post(format!(
-            "{}/api/ui-bridge/control/console-errors/clear",
-            WEB_FRONTEND_BASE
-        ))
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await;
+/// Load the page `iterations` times, sampling the JS heap after each load.
+/// `None` when the heap can't be sampled.
+async fn run_leak_check(
+    http_client: &reqwest::Client,
+    page: Option<&HeadlessPage>,
+    test_case: &TestCase,
+    iterations: usize,
+    threshold_pct: f64,
+) -> Option<LeakCheck> {
+    let nav_url = page_nav_url(test_case);
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let sample = match page {
+            Some(page) => {
+                if let Err(e) = page.load(&nav_url, &test_case.key_element).await {
+                    warn!("  {} — leak check load failed: {}", test_case.name, e);
+                }
+                page.js_heap_used().await
+            }
+            None => {
+                load_via_bridge(http_client, &nav_url, &test_case.key_element).await;
+                get_js_heap_used(http_client).await
+            }
+        };
+        match sample {
+            Ok(bytes) => samples.push(bytes),
+            Err(e) => {
+                warn!("  {} — can't sample the JS heap: {}", test_case.name, e);
+                return None;
+            }
+        }
+    }
+    Some(leak::analyze(samples, threshold_pct))
+}
 
-    // 2. Clear performance entries
-    let _ = http_client
-        .post(format!(
-            "{}/api/ui-bridge/control/performance-entries/clear",
+/// Used JS heap in bytes, from the UI Bridge's memory endpoint.
+async fn get_js_heap_used(http_client: &reqwest::Client) -> anyhow::Result<f64> {
+    let resp = http_client
+        .get(format!(
+            "{}/api/ui-bridge/control/memory",
             WEB_FRONTEND_BASE
         ))
         .timeout(std::time::Duration::from_secs(5))
         .send()
-        .await;
-
-    // 3. Navigate to the page and start timer
-    let nav_start = std::time::Instant::now();
-
-    let nav_url = if test_case.page_url == "/" {
-        WEB_FRONTEND_BASE.to_string()
-    } else {
-        format!("{}{}", WEB_FRONTEND_BASE, test_case.page_url)
-    };
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("memory endpoint returned {}", resp.status());
+    }
+    let body: serde_json::Value = resp.json().await?;
+    // Response: { "success": true, "data": { "usedJSHeapSize": N, ... } }
+    body.get("data")
+        .and_then(|d| d.get("usedJSHeapSize"))
+        .and_then(|v| v.as_f64())
+        .ok_or_else(|| anyhow::anyhow!("memory response has no usedJSHeapSize"))
+}
 
-    let nav_resp = http_client
+/// Clear the browser's HTTP cache through UI Bridge; `false` when the bridge
+/// didn't confirm it.
+async fn clear_browser_cache(http_client: &reqwest::Client) -> bool {
+    let resp = http_client
         .post(format!(
-            "{}/api/ui-bridge/control/page/navigate",
+            "{}/api/ui-bridge/control/cache/clear",
             WEB_FRONTEND_BASE
         ))
-        .json(&serde_json::json!({ "url": nav_url }))
+        .json(&serde_json::json!({}))
         .timeout(std::time::Duration::from_secs(10))
         .send()
         .await;
-
-    if let Err(e) = &nav_resp {
-        let err_str = e.to_string();
-        if !err_str.contains("connection") && !err_str.contains("reset") && !err_str.contains("eof")
-        {
-            warn!("Navigate request error (may be expected): {}", e);
+    match resp {
+        Ok(r) if r.status().is_success() => true,
+        Ok(r) => {
+            warn!("Clearing the browser cache returned {}", r.status());
+            false
+        }
+        Err(e) => {
+            warn!("Failed to clear the browser cache: {}", e);
+            false
         }
     }
+}
 
-    // 4. Poll for key element to appear (this measures load time)
-    let mut element_found = false;
-    let deadline = nav_start + std::time::Duration::from_millis(ELEMENT_POLL_TIMEOUT_MS);
-
-    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+/// Run a single test case: navigate, poll for element, collect diagnostics, compute score.
+async fn run_single_test(
+    http_client: &reqwest::Client,
+    page: Option<&HeadlessPage>,
+    run_id: &str,
+    test_case: &TestCase,
+    creds: Option<&ResolvedTestAutoLogin>,
+) -> anyhow::Result<VelocityTestResult> {
+    let now = Utc::now().to_rfc3339();
 
-    loop {
-        if std::time::Instant::now() > deadline {
-            break;
+    // 0. Log in first for protected pages. Missing credentials fall back to an
+    // unauthenticated measurement; a failed login with credentials fails the test.
+    let bearer = match (&test_case.auth, creds) {
+        (Some(TestAuth::UiBridgeLogin { .. }), Some(_)) if page.is_some() => {
+            warn!(
+                "{} logs in through the UI Bridge, which the headless driver can't use; measuring unauthenticated",
+                test_case.name
+            );
+            None
         }
-
-        if let Ok(elements) = get_elements(http_client).await {
-            if has_key_element(&elements, test_case.key_element) {
-                element_found = true;
-                break;
-            }
+        (Some(auth), Some(creds)) => authenticate(http_client, auth, creds)
+            .await
+            .map_err(|e| anyhow::anyhow!("Login failed: {}", e))?,
+        (Some(_), None) => {
+            warn!(
+                "{} requires login but no test credentials are configured; measuring unauthenticated",
+                test_case.name
+            );
+            None
         }
+        (None, _) => None,
+    };
 
-        tokio::time::sleep(std::time::Duration::from_millis(ELEMENT_POLL_INTERVAL_MS)).await;
-    }
-
-    let load_time_ms = nav_start.elapsed().as_secs_f64() * 1000.0;
+    let nav_url = page_nav_url(test_case);
 
-    // 5. Let late resources finish loading
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    // 1-6. Navigate, wait for the key element and collect browser metrics
+    let load = match page {
+        Some(page) => page.load(&nav_url, &test_case.key_element).await?,
+        None => load_via_bridge(http_client, &nav_url, &test_case.key_element).await,
+    };
+    let PageLoad {
+        load_time_ms,
+        element_found,
+        console_errors,
+        perf_entries,
+        long_tasks,
+        loaf_events,
+    } = load;
 
-    // 6. Get console errors
-    let console_errors = get_console_error_count(http_client).await.unwrap_or(0);
+    // 7. Run functional assertions against a fresh snapshot (bridge only)
+    let assertion_outcome = if test_case.assertions.is_empty() || page.is_some() {
+        None
+    } else {
+        Some(match get_elements(http_client).await {
+            Ok(snapshot) => assertions::evaluate(&snapshot, &test_case.assertions),
+            Err(e) => AssertionOutcome {
+                passed: 0,
+                failures: vec![format!("snapshot unavailable: {}", e); test_case.assertions.len()],
+            },
+        })
+    };
 
-    // 7. Measure backend API response time
+    // 8. Measure backend API response time
     let (api_response_time_ms, api_status_code) =
-        measure_api_response(http_client, test_case.api_endpoint).await;
-
-    // 8. Get browser performance entries (navigation timing + resource waterfall)
-    let perf_entries = get_performance_entries(http_client).await;
-
-    // 9. Get long tasks and long animation frames from browser event capture
-    let long_tasks = get_long_tasks(http_client).await;
-    let loaf_events = get_loaf_events(http_client).await;
+        measure_api_response(http_client, &test_case.api_endpoint, bearer.as_deref()).await;
 
     // Extract metrics from performance data
     let (ttfb_ms, dom_interactive_ms, dom_complete_ms, fcp_ms) =
@@ -274,7 +701,7 @@ async fn run_single_test(
         extract_resource_stats(&perf_entries);
     let (long_task_count, long_task_total_ms) = extract_long_task_stats(&long_tasks);
 
-    // 10. Classify bottleneck
+    // 9. Classify bottleneck
     let bottleneck = classify_bottleneck(
         load_time_ms,
         api_response_time_ms,
@@ -291,8 +718,9 @@ async fn run_single_test(
     // Build diagnostics JSON blob (full resource list + long task list + script attribution)
     let diagnostics_json = build_diagnostics_json(&perf_entries, &long_tasks, &loaf_events);
 
-    // 11. Compute score with new weights
+    // 10. Compute score with the test case's weights
     let score = compute_score(
+        &test_case.weights,
         load_time_ms,
         api_response_time_ms,
         console_errors,
@@ -301,6 +729,16 @@ async fn run_single_test(
         resource_count,
         total_transfer_size_bytes,
     );
+    let score = match &assertion_outcome {
+        Some(outcome) => assertions::apply_penalty(
+            score,
+            outcome.failures.len(),
+            test_case
+                .assertion_penalty
+                .unwrap_or(assertions::ASSERTION_FAILURE_PENALTY),
+        ),
+        None => score,
+    };
 
     Ok(VelocityTestResult {
         id: 0,
@@ -326,9 +764,119 @@ async fn run_single_test(
         slowest_resource_ms,
         bottleneck: Some(bottleneck),
         diagnostics_json,
+        assertions_passed: assertion_outcome.as_ref().map(|o| o.failures.is_empty()),
+        assertion_failures: assertion_outcome.map(|o| o.failures),
+        budget_passed: None,
+        budget_violations: None,
+        screenshot_path: None,
+        cold_warm: None,
+        leak_check: None,
     })
 }
 
+fn page_nav_url(test_case: &TestCase) -> String {
+    if test_case.page_url == "/" {
+        WEB_FRONTEND_BASE.to_string()
+    } else {
+        format!("{}{}", WEB_FRONTEND_BASE, test_case.page_url)
+    }
+}
+
+/// What a driver measured while loading a page.
+pub struct PageLoad {
+    pub load_time_ms: f64,
+    pub element_found: bool,
+    pub console_errors: i64,
+    /// `{navigation, resources, paint}` as reported by the UI Bridge.
+    pub perf_entries: Option<serde_json::Value>,
+    /// `{events: [{durationMs, ..}]}`.
+    pub long_tasks: Option<serde_json::Value>,
+    pub loaf_events: Option<serde_json::Value>,
+}
+
+/// Load a page through the UI Bridge, polling its snapshot for the key
+/// element.
+async fn load_via_bridge(
+    http_client: &reqwest::Client,
+    nav_url: &str,
+    key_element: &str,
+) -> PageLoad {
+    // Clear console errors and performance entries before navigating
+    let _ = http_client
+        .post(format!(
+            "{}/api/ui-bridge/control/console-errors/clear",
+            WEB_FRONTEND_BASE
+        ))
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await;
+    let _ = http_client
+        .post(format!(
+            "{}/api/ui-bridge/control/performance-entries/clear",
+            WEB_FRONTEND_BASE
+        ))
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await;
+
+    // Navigate to the page and start timer
+    let nav_start = std::time::Instant::now();
+    let nav_resp = http_client
+        .post(format!(
+            "{}/api/ui-bridge/control/page/navigate",
+            WEB_FRONTEND_BASE
+        ))
+        .json(&serde_json::json!({ "url": nav_url }))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await;
+
+    if let Err(e) = &nav_resp {
+        let err_str = e.to_string();
+        if !err_str.contains("connection") && !err_str.contains("reset") && !err_str.contains("eof")
+        {
+            warn!("Navigate request error (may be expected): {}", e);
+        }
+    }
+
+    // Poll for key element to appear (this measures load time)
+    let mut element_found = false;
+    let deadline = nav_start + std::time::Duration::from_millis(ELEMENT_POLL_TIMEOUT_MS);
+
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    loop {
+        if std::time::Instant::now() > deadline {
+            break;
+        }
+
+        if let Ok(elements) = get_elements(http_client).await {
+            if has_key_element(&elements, key_element) {
+                element_found = true;
+                break;
+            }
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(ELEMENT_POLL_INTERVAL_MS)).await;
+    }
+
+    let load_time_ms = nav_start.elapsed().as_secs_f64() * 1000.0;
+
+    // Let late resources finish loading
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    PageLoad {
+        load_time_ms,
+        element_found,
+        console_errors: get_console_error_count(http_client).await.unwrap_or(0),
+        // Navigation timing + resource waterfall
+        perf_entries: get_performance_entries(http_client).await,
+        // Long tasks and long animation frames from browser event capture
+        long_tasks: get_long_tasks(http_client).await,
+        loaf_events: get_loaf_events(http_client).await,
+    }
+}
+
 /// Fetch elements from the UI Bridge via control snapshot (queries browser directly).
 async fn get_elements(http_client: &reqwest::Client) -> anyhow::Result<serde_json::Value> {
     let resp = http_client
@@ -350,48 +898,9 @@ async fn get_elements(http_client: &reqwest::Client) -> anyhow::Result<serde_jso
 
 /// Check if the key element is present in the snapshot response.
 fn has_key_element(snapshot: &serde_json::Value, key: &str) -> bool {
-    let key_lower = key.to_lowercase();
-
-    // Snapshot response: { "success": true, "data": { "elements": [...], ... } }
-    let elements = snapshot
-        .get("data")
-        .and_then(|d| d.get("elements"))
-        .and_then(|e| e.as_array());
-
-    let elements = match elements {
-        Some(e) => e,
-        None => return false,
-    };
-
-    for element in elements {
-        if let Some(id) = element.get("id").and_then(|v| v.as_str()) {
-            if id.to_lowercase().contains(&key_lower) {
-                return true;
-            }
-        }
-        if let Some(label) = element.get("label").and_then(|v| v.as_str()) {
-            if label.to_lowercase().contains(&key_lower) {
-                return true;
-            }
-        }
-        if let Some(etype) = element.get("type").and_then(|v| v.as_str()) {
-            if etype.to_lowercase().contains(&key_lower) {
-                return true;
-            }
-        }
-        // Also check textContent in state
-        if let Some(state) = element.get("state") {
-            for field in &["text_content", "textContent"] {
-                if let Some(tc) = state.get(*field).and_then(|v| v.as_str()) {
-                    if tc.to_lowercase().contains(&key_lower) {
-                        return true;
-                    }
-                }
-            }
-        }
-    }
-
-    false
+    assertions::matching_elements(snapshot, key)
+        .next()
+        .is_some()
 }
 
 /// Get the count of console errors since we cleared them.
@@ -423,6 +932,9 @@ async fn get_console_error_count(http_client: &reqwest::Client) -> anyhow::Resul
 
 /// Compute page score (0-100) from metrics with diagnostic weights.
 ///
+/// Each metric earns a fraction of its weight; the sum is scaled to 100 by
+/// the total weight. With [`ScoreWeights::default`]:
+///
 /// | Metric              | Weight | Scoring                                     |
 /// |---------------------|--------|---------------------------------------------|
 /// | Page load time      | 40%    | 40pts if <1s, linear decay to 0 at 10s      |
@@ -431,7 +943,9 @@ async fn get_console_error_count(http_client: &reqwest::Client) -> anyhow::Resul
 /// | Element presence    | 15%    | 15pts if found, 0 if missing                |
 /// | Long task penalty   | 10%    | 10pts if 0, -2pts per task (min 0)          |
 /// | Resource efficiency | 10%    | 10pts if <20 resources and <1MB             |
+#[allow(clippy::too_many_arguments)]
 fn compute_score(
+    weights: &ScoreWeights,
     load_time_ms: f64,
     api_response_time_ms: Option<f64>,
     console_errors: i64,
@@ -440,59 +954,60 @@ fn compute_score(
     resource_count: i64,
     total_transfer_size_bytes: i64,
 ) -> f64 {
-    // Load time score (0-40)
+    // Load time: full if <1s, linear decay to 0 at 10s
     let load_secs = load_time_ms / 1000.0;
     let load_score = if load_secs <= 1.0 {
-        40.0
+        1.0
     } else if load_secs >= 10.0 {
         0.0
     } else {
-        40.0 * (1.0 - (load_secs - 1.0) / 9.0)
+        1.0 - (load_secs - 1.0) / 9.0
     };
 
-    // API response time score (0-15)
+    // API response time: full if <200ms, linear decay to 0 at 3s
     let api_score = match api_response_time_ms {
         Some(ms) => {
             let secs = ms / 1000.0;
             if secs <= 0.2 {
-                15.0
+                1.0
             } else if secs >= 3.0 {
                 0.0
             } else {
-                15.0 * (1.0 - (secs - 0.2) / 2.8)
+                1.0 - (secs - 0.2) / 2.8
             }
         }
-        None => 7.5, // Neutral if we couldn't measure
+        None => 0.5, // Neutral if we couldn't measure
     };
 
-    // Console errors score (0-10)
-    let error_score = (10.0 - (console_errors as f64 * 3.0)).max(0.0);
+    // Console errors: -30% per error
+    let error_score = (1.0 - (console_errors as f64 * 0.3)).max(0.0);
 
-    // Element presence score (0-15)
-    let element_score = if element_found { 15.0 } else { 0.0 };
+    let element_score = if element_found { 1.0 } else { 0.0 };
 
-    // Long task penalty (0-10)
-    let long_task_score = (10.0 - (long_task_count as f64 * 2.0)).max(0.0);
+    // Long tasks: -20% per task
+    let long_task_score = (1.0 - (long_task_count as f64 * 0.2)).max(0.0);
 
-    // Resource efficiency (0-10)
+    // Resource efficiency: up to -50% each for count over 20 and size over 1MB
     let transfer_mb = total_transfer_size_bytes as f64 / (1024.0 * 1024.0);
-    let resource_score = if resource_count <= 20 && transfer_mb <= 1.0 {
-        10.0
+    let count_penalty = if resource_count > 20 {
+        ((resource_count - 20) as f64 * 0.02).min(0.5)
     } else {
-        let count_penalty = if resource_count > 20 {
-            ((resource_count - 20) as f64 * 0.2).min(5.0)
-        } else {
-            0.0
-        };
-        let size_penalty = if transfer_mb > 1.0 {
-            ((transfer_mb - 1.0) * 2.0).min(5.0)
-        } else {
-            0.0
-        };
-        (10.0 - count_penalty - size_penalty).max(0.0)
+        0.0
     };
-
-    load_score + api_score + error_score + element_score + long_task_score + resource_score
+    let size_penalty = if transfer_mb > 1.0 {
+        ((transfer_mb - 1.0) * 0.2).min(0.5)
+    } else {
+        0.0
+    };
+    let resource_score = (1.0 - count_penalty - size_penalty).max(0.0);
+
+    let total = weights.load_time * load_score
+        + weights.api_response * api_score
+        + weights.console_errors * error_score
+        + weights.element_presence * element_score
+        + weights.long_tasks * long_task_score
+        + weights.resources * resource_score;
+    total * 100.0 / weights.total()
 }
 
 // =============================================================================
@@ -503,16 +1018,18 @@ fn compute_score(
 async fn measure_api_response(
     http_client: &reqwest::Client,
     api_endpoint: &str,
+    bearer: Option<&str>,
 ) -> (Option<f64>, Option<i64>) {
     let url = format!("{}{}", BACKEND_API_BASE, api_endpoint);
+    let mut req = http_client
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(10));
+    if let Some(token) = bearer {
+        req = req.bearer_auth(token);
+    }
     let start = std::time::Instant::now();
 
-    match http_client
-        .get(&url)
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await
-    {
+    match req.send().await {
         Ok(resp) => {
             let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
             let status = resp.status().as_u16() as i64;
@@ -522,6 +1039,121 @@ async fn measure_api_response(
     }
 }
 
+/// Establish a session for a protected test case. Returns the bearer token
+/// for API-login auth, `None` for UI Bridge form login (the session lives in
+/// the browser).
+async fn authenticate(
+    http_client: &reqwest::Client,
+    auth: &TestAuth,
+    creds: &ResolvedTestAutoLogin,
+) -> anyhow::Result<Option<String>> {
+    match auth {
+        TestAuth::ApiLogin { login_endpoint } => {
+            let resp = http_client
+                .post(format!("{}{}", BACKEND_API_BASE, login_endpoint))
+                .json(&serde_json::json!({
+                    "email": creds.email,
+                    "password": creds.password,
+                }))
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await?;
+            if !resp.status().is_success() {
+                anyhow::bail!("{} returned {}", login_endpoint, resp.status());
+            }
+            let body: serde_json::Value = resp.json().await?;
+            match extract_access_token(&body) {
+                Some(token) => Ok(Some(token)),
+                None => anyhow::bail!("{} response had no access token", login_endpoint),
+            }
+        }
+        TestAuth::UiBridgeLogin {
+            login_url,
+            email_element,
+            password_element,
+            submit_element,
+        } => {
+            let _ = http_client
+                .post(format!(
+                    "{}/api/ui-bridge/control/page/navigate",
+                    WEB_FRONTEND_BASE
+                ))
+                .json(&serde_json::json!({ "url": format!("{}{}", WEB_FRONTEND_BASE, login_url) }))
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await;
+
+            // Wait for the login form to render before typing into it
+            let deadline = std::time::Instant::now()
+                + std::time::Duration::from_millis(ELEMENT_POLL_TIMEOUT_MS);
+            loop {
+                if let Ok(elements) = get_elements(http_client).await {
+                    if has_key_element(&elements, email_element) {
+                        break;
+                    }
+                }
+                if std::time::Instant::now() > deadline {
+                    anyhow::bail!("login form element '{}' never appeared", email_element);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(ELEMENT_POLL_INTERVAL_MS))
+                    .await;
+            }
+
+            element_action(
+                http_client,
+                email_element,
+                "type",
+                serde_json::json!({ "text": creds.email }),
+            )
+            .await?;
+            element_action(
+                http_client,
+                password_element,
+                "type",
+                serde_json::json!({ "text": creds.password }),
+            )
+            .await?;
+            element_action(http_client, submit_element, "click", serde_json::json!({})).await?;
+
+            // Give the app time to store the session before navigating away
+            tokio::time::sleep(std::time::Duration::from_millis(1_000)).await;
+            Ok(None)
+        }
+    }
+}
+
+/// Pull an access token out of a login response. Accepts a bare
+/// `{ "access_token": ... }` / `{ "token": ... }` body or one wrapped in `data`.
+fn extract_access_token(body: &serde_json::Value) -> Option<String> {
+    let inner = body.get("data").unwrap_or(body);
+    ["access_token", "token"]
+        .iter()
+        .find_map(|k| inner.get(*k).and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+/// Execute a UI Bridge element action (type, click, ...).
+async fn element_action(
+    http_client: &reqwest::Client,
+    element_id: &str,
+    action: &str,
+    params: serde_json::Value,
+) -> anyhow::Result<()> {
+    let resp = http_client
+        .post(format!(
+            "{}/api/ui-bridge/control/element/{}/action",
+            WEB_FRONTEND_BASE, element_id
+        ))
+        .json(&serde_json::json!({ "action": action, "params": params }))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("{} on '{}' returned {}", action, element_id, resp.status());
+    }
+    Ok(())
+}
+
 /// Fetch browser performance entries via UI Bridge.
 async fn get_performance_entries(http_client: &reqwest::Client) -> Option<serde_json::Value> {
     let resp = http_client
@@ -795,3 +1427,48 @@ fn build_diagnostics_json(
         Some(serde_json::Value::Object(diag).to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_mode_stays_serial() {
+        let options = |concurrency, strict| VelocityRunOptions {
+            concurrency,
+            strict,
+            ..Default::default()
+        };
+        assert_eq!(options(0, false).workers(), 1);
+        assert_eq!(options(3, false).workers(), 3);
+        assert_eq!(options(64, false).workers(), MAX_CONCURRENCY);
+        assert_eq!(options(3, true).workers(), 1);
+    }
+
+    #[test]
+    fn default_weights_keep_point_values() {
+        let weights = ScoreWeights::default();
+        assert_eq!(
+            compute_score(&weights, 500.0, Some(100.0), 0, true, 0, 10, 0),
+            100.0
+        );
+        // 5.5s load (20pts), no API probe (7.5), 1 error (7), no element,
+        // 2 long tasks (6), 30 resources (8).
+        let score = compute_score(&weights, 5500.0, None, 1, false, 2, 30, 0);
+        assert!((score - 48.5).abs() < 1e-9, "{}", score);
+    }
+
+    #[test]
+    fn weights_are_scaled_to_100() {
+        let weights = ScoreWeights {
+            load_time: 1.0,
+            api_response: 0.0,
+            console_errors: 0.0,
+            element_presence: 1.0,
+            long_tasks: 0.0,
+            resources: 0.0,
+        };
+        let score = compute_score(&weights, 500.0, None, 10, false, 10, 100, 0);
+        assert_eq!(score, 50.0);
+    }
+}
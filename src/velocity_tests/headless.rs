@@ -0,0 +1,258 @@
+//! Headless Chromium driver for velocity tests.
+//!
+//! The default driver goes through the UI Bridge, so a page on which the
+//! bridge itself fails to load just looks like a missing element. With
+//! `?driver=headless` the run launches its own Chromium over CDP instead:
+//! each worker gets a page of its own, navigation is a real page load,
+//! console errors and uncaught exceptions are counted from CDP runtime
+//! events, and navigation/resource/paint timing and long tasks are read from
+//! the page's Performance API in the same shape the bridge reports them, so
+//! scoring and bottleneck classification are unchanged.
+//!
+//! Chromium is found on the usual install paths, or set
+//! `QONTINUI_SUPERVISOR_VELOCITY_CHROME` to the executable.
+
+use chromiumoxide::browser::{Browser, BrowserConfig};
+use chromiumoxide::cdp::browser_protocol::network::ClearBrowserCacheParams;
+use chromiumoxide::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, CaptureScreenshotFormat,
+};
+use chromiumoxide::cdp::js_protocol::heap_profiler::CollectGarbageParams;
+use chromiumoxide::cdp::js_protocol::runtime::{
+    ConsoleApiCalledType, EventConsoleApiCalled, EventExceptionThrown, GetHeapUsageParams,
+};
+use chromiumoxide::page::{Page, ScreenshotParams};
+use futures::StreamExt;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use super::engine::PageLoad;
+
+pub const CHROME_ENV: &str = "QONTINUI_SUPERVISOR_VELOCITY_CHROME";
+
+const ELEMENT_POLL_INTERVAL_MS: u64 = 250;
+const ELEMENT_POLL_TIMEOUT_MS: u64 = 15_000;
+const NAVIGATION_TIMEOUT_SECS: u64 = 30;
+
+/// Collects long tasks from the first byte of every document.
+const LONG_TASK_OBSERVER: &str = r#"
+window.__velocityLongTasks = [];
+try {
+  new PerformanceObserver((list) => {
+    for (const e of list.getEntries()) {
+      window.__velocityLongTasks.push({ startTime: e.startTime, durationMs: e.duration });
+    }
+  }).observe({ type: "longtask", buffered: true });
+} catch (e) {}
+"#;
+
+/// Navigation timing, resources and paint entries, shaped like the UI
+/// Bridge's `performance-entries` data.
+const PERFORMANCE_ENTRIES: &str = r#"
+(() => {
+  const nav = performance.getEntriesByType("navigation")[0];
+  return {
+    navigation: nav ? {
+      ttfbMs: nav.responseStart - nav.startTime,
+      domInteractiveMs: nav.domInteractive - nav.startTime,
+      domCompleteMs: nav.domComplete - nav.startTime,
+    } : null,
+    resources: performance.getEntriesByType("resource").map((r) => ({
+      name: r.name,
+      initiatorType: r.initiatorType,
+      transferSize: Math.round(r.transferSize),
+      startTime: r.startTime,
+      duration: r.duration,
+    })),
+    paint: performance.getEntriesByType("paint").map((p) => ({
+      name: p.name,
+      startTime: p.startTime,
+    })),
+  };
+})()
+"#;
+
+pub struct HeadlessBrowser {
+    browser: Browser,
+    handler: JoinHandle<()>,
+}
+
+impl HeadlessBrowser {
+    pub async fn launch() -> anyhow::Result<Self> {
+        let mut config = BrowserConfig::builder().window_size(1280, 800);
+        if let Ok(path) = std::env::var(CHROME_ENV) {
+            config = config.chrome_executable(path);
+        }
+        let config = config.build().map_err(|e| anyhow::anyhow!(e))?;
+        let (browser, mut handler) = Browser::launch(config).await?;
+        let handler = tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                if event.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { browser, handler })
+    }
+
+    pub async fn new_page(&self) -> anyhow::Result<HeadlessPage> {
+        let page = self.browser.new_page("about:blank").await?;
+        page.execute(AddScriptToEvaluateOnNewDocumentParams::new(
+            LONG_TASK_OBSERVER,
+        ))
+        .await?;
+
+        let console_errors = Arc::new(AtomicI64::new(0));
+        let mut console = page.event_listener::<EventConsoleApiCalled>().await?;
+        let mut exceptions = page.event_listener::<EventExceptionThrown>().await?;
+        let listeners = vec![
+            tokio::spawn({
+                let errors = console_errors.clone();
+                async move {
+                    while let Some(event) = console.next().await {
+                        if event.r#type == ConsoleApiCalledType::Error {
+                            errors.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                }
+            }),
+            tokio::spawn({
+                let errors = console_errors.clone();
+                async move {
+                    while exceptions.next().await.is_some() {
+                        errors.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }),
+        ];
+        Ok(HeadlessPage {
+            page,
+            console_errors,
+            listeners,
+        })
+    }
+
+    pub async fn close(mut self) {
+        if let Err(e) = self.browser.close().await {
+            tracing::warn!("Failed to close headless browser: {}", e);
+        }
+        self.handler.abort();
+    }
+}
+
+/// One worker's browser tab.
+pub struct HeadlessPage {
+    page: Page,
+    console_errors: Arc<AtomicI64>,
+    listeners: Vec<JoinHandle<()>>,
+}
+
+impl HeadlessPage {
+    pub async fn clear_cache(&self) -> anyhow::Result<()> {
+        self.page
+            .execute(ClearBrowserCacheParams::default())
+            .await?;
+        Ok(())
+    }
+
+    /// Load `url` and wait for an element matching `key_element` (same
+    /// case-insensitive substring rule as the bridge, over `id`,
+    /// `data-ui-id`, `data-testid` and `aria-label`).
+    pub async fn load(&self, url: &str, key_element: &str) -> anyhow::Result<PageLoad> {
+        self.console_errors.store(0, Ordering::SeqCst);
+        let nav_start = Instant::now();
+        tokio::time::timeout(
+            Duration::from_secs(NAVIGATION_TIMEOUT_SECS),
+            self.page.goto(url),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("navigation to {} timed out", url))??;
+
+        let probe = element_probe(key_element);
+        let deadline = nav_start + Duration::from_millis(ELEMENT_POLL_TIMEOUT_MS);
+        let mut element_found = false;
+        while Instant::now() <= deadline {
+            if let Ok(found) = self.page.evaluate(probe.as_str()).await {
+                if found.into_value::<bool>().unwrap_or(false) {
+                    element_found = true;
+                    break;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(ELEMENT_POLL_INTERVAL_MS)).await;
+        }
+        let load_time_ms = nav_start.elapsed().as_secs_f64() * 1000.0;
+
+        // Let late resources finish loading
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let perf_entries = self.evaluate_json(PERFORMANCE_ENTRIES).await;
+        let long_tasks = self
+            .evaluate_json("({ events: window.__velocityLongTasks || [] })")
+            .await;
+        Ok(PageLoad {
+            load_time_ms,
+            element_found,
+            console_errors: self.console_errors.load(Ordering::SeqCst),
+            perf_entries,
+            long_tasks,
+            loaf_events: None,
+        })
+    }
+
+    /// Used JS heap in bytes, after a forced garbage collection.
+    pub async fn js_heap_used(&self) -> anyhow::Result<f64> {
+        self.page.execute(CollectGarbageParams::default()).await?;
+        let usage = self.page.execute(GetHeapUsageParams::default()).await?;
+        Ok(usage.result.used_size)
+    }
+
+    pub async fn screenshot(&self) -> anyhow::Result<Vec<u8>> {
+        let params = ScreenshotParams::builder()
+            .format(CaptureScreenshotFormat::Png)
+            .build();
+        Ok(self.page.screenshot(params).await?)
+    }
+
+    async fn evaluate_json(&self, expression: &str) -> Option<serde_json::Value> {
+        self.page
+            .evaluate(expression)
+            .await
+            .ok()?
+            .into_value::<serde_json::Value>()
+            .ok()
+    }
+}
+
+impl Drop for HeadlessPage {
+    fn drop(&mut self) {
+        for listener in &self.listeners {
+            listener.abort();
+        }
+    }
+}
+
+/// JS expression that is true once an element matches `key`.
+fn element_probe(key: &str) -> String {
+    let key = serde_json::to_string(&key.to_lowercase()).unwrap_or_else(|_| "\"\"".to_string());
+    format!(
+        r#"(() => {{
+  const key = {key};
+  const attrs = ["id", "data-ui-id", "data-testid", "aria-label"];
+  return Array.from(document.querySelectorAll("*")).some((el) =>
+    attrs.some((a) => (el.getAttribute(a) || "").toLowerCase().includes(key)));
+}})()"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_probe_escapes_the_key() {
+        let probe = element_probe(r#"Run "History""#);
+        assert!(probe.contains(r#"const key = "run \"history\"";"#));
+    }
+}
@@ -0,0 +1,83 @@
+//! Memory-leak detection across repeated navigations.
+//!
+//! With `?leak_iterations=N` each page is, after its measurement, loaded N
+//! more times with the JS heap sampled after every load (after a forced GC
+//! where the driver can trigger one). A page is flagged as leaking when the
+//! series only grows — no sample more than [`NOISE_PCT`] below the one
+//! before — and the last sample is more than the threshold above the first.
+
+use serde::{Deserialize, Serialize};
+
+/// Default growth from first to last sample that counts as a leak, in
+/// percent.
+pub const DEFAULT_THRESHOLD_PCT: f64 = 10.0;
+
+/// Upper bound on `leak_iterations`.
+pub const MAX_ITERATIONS: usize = 20;
+
+/// Drop between consecutive samples still treated as growth (GC noise).
+pub const NOISE_PCT: f64 = 1.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakCheck {
+    /// Used JS heap after each navigation, in bytes.
+    pub heap_bytes: Vec<f64>,
+    /// Last sample minus first.
+    pub growth_bytes: f64,
+    pub growth_pct: f64,
+    pub monotonic: bool,
+    pub threshold_pct: f64,
+    pub leaking: bool,
+}
+
+pub fn analyze(heap_bytes: Vec<f64>, threshold_pct: f64) -> LeakCheck {
+    let (first, last) = match (heap_bytes.first(), heap_bytes.last()) {
+        (Some(f), Some(l)) => (*f, *l),
+        _ => (0.0, 0.0),
+    };
+    let growth_bytes = last - first;
+    let growth_pct = if first > 0.0 {
+        growth_bytes / first * 100.0
+    } else {
+        0.0
+    };
+    let monotonic = heap_bytes
+        .windows(2)
+        .all(|w| w[1] >= w[0] * (1.0 - NOISE_PCT / 100.0));
+    LeakCheck {
+        leaking: heap_bytes.len() >= 2 && monotonic && growth_pct > threshold_pct,
+        heap_bytes,
+        growth_bytes,
+        growth_pct,
+        monotonic,
+        threshold_pct,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: f64 = 1024.0 * 1024.0;
+
+    #[test]
+    fn steady_growth_is_a_leak() {
+        let check = analyze(vec![20.0 * MB, 21.0 * MB, 22.5 * MB, 24.0 * MB], 10.0);
+        assert!(check.monotonic);
+        assert!((check.growth_pct - 20.0).abs() < 1e-9);
+        assert!(check.leaking);
+    }
+
+    #[test]
+    fn reclaimed_or_small_growth_is_not() {
+        // Falls back after a GC
+        let sawtooth = analyze(vec![20.0 * MB, 26.0 * MB, 21.0 * MB, 27.0 * MB], 10.0);
+        assert!(!sawtooth.monotonic);
+        assert!(!sawtooth.leaking);
+        // Within noise but under the threshold
+        let flat = analyze(vec![20.0 * MB, 19.9 * MB, 20.5 * MB], 10.0);
+        assert!(flat.monotonic);
+        assert!(!flat.leaking);
+        assert!(!analyze(vec![20.0 * MB], 10.0).leaking);
+    }
+}
@@ -1,6 +1,17 @@
+pub mod api_sweep;
+pub mod assertions;
+pub mod baseline;
+pub mod budget;
+pub mod cold_warm;
 pub mod db;
 pub mod engine;
+pub mod headless;
+pub mod leak;
+pub mod retry;
+pub mod schedule;
+pub mod screenshots;
 pub mod tests;
+pub mod trends;
 
 use serde::{Deserialize, Serialize};
 
@@ -17,6 +28,13 @@ pub struct VelocityTestRun {
     pub status: String, // running, completed, failed, stopped
     pub tests_total: i64,
     pub tests_completed: i64,
+    /// `manual` or `scheduled`.
+    #[serde(default = "default_source")]
+    pub source: String,
+}
+
+fn default_source() -> String {
+    schedule::RunSource::Manual.as_str().to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +63,27 @@ pub struct VelocityTestResult {
     pub slowest_resource_ms: f64,
     pub bottleneck: Option<String>,
     pub diagnostics_json: Option<String>,
+    /// `None` when the test case has no assertions.
+    #[serde(default)]
+    pub assertions_passed: Option<bool>,
+    #[serde(default)]
+    pub assertion_failures: Option<Vec<String>>,
+    /// `None` when the test case has no budget.
+    #[serde(default)]
+    pub budget_passed: Option<bool>,
+    #[serde(default)]
+    pub budget_violations: Option<Vec<String>>,
+    /// Saved screenshot of the page after the test; `None` when capture
+    /// failed.
+    #[serde(default)]
+    pub screenshot_path: Option<String>,
+    /// Warm pass and deltas of a cold/warm run; `None` otherwise.
+    #[serde(default)]
+    pub cold_warm: Option<cold_warm::ColdWarm>,
+    /// Heap series of a leak-check run; `None` otherwise or when the heap
+    /// couldn't be sampled.
+    #[serde(default)]
+    pub leak_check: Option<leak::LeakCheck>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +98,7 @@ pub struct VelocityTestStatus {
 pub struct VelocityTestRunWithResults {
     #[serde(flatten)]
     pub run: VelocityTestRun,
+    pub environment: Option<crate::run_environment::EnvironmentSnapshot>,
     pub results: Vec<VelocityTestResult>,
 }
 
@@ -0,0 +1,146 @@
+//! Retries of flaky page tests.
+//!
+//! A connection reset or an element poll that times out once produces a
+//! 0-score outlier that distorts trends. A test case with `retries` set is
+//! measured again while an attempt looks flaky (failed outright or never
+//! found its key element), up to `retries` extra times, and the best-scoring
+//! attempt is recorded. The attempt count and a summary of every attempt go
+//! into the result's `diagnostics_json` as `attempts` and `rawAttempts`.
+
+use serde_json::{json, Value};
+
+use super::VelocityTestResult;
+
+/// Upper bound on a test case's `retries`.
+pub const MAX_RETRIES: u32 = 5;
+
+/// Whether an attempt is worth repeating.
+pub fn is_flaky(attempt: &anyhow::Result<VelocityTestResult>) -> bool {
+    match attempt {
+        Ok(r) => !r.element_found,
+        Err(_) => true,
+    }
+}
+
+/// The best-scoring successful attempt, annotated with all of them; the
+/// last error when every attempt failed.
+pub fn pick_best(
+    attempts: Vec<anyhow::Result<VelocityTestResult>>,
+) -> anyhow::Result<VelocityTestResult> {
+    let count = attempts.len();
+    let raw: Vec<Value> = attempts
+        .iter()
+        .enumerate()
+        .map(|(i, a)| match a {
+            Ok(r) => json!({
+                "attempt": i + 1,
+                "score": r.score,
+                "loadTimeMs": r.load_time_ms,
+                "elementFound": r.element_found,
+                "consoleErrors": r.console_errors,
+                "bottleneck": r.bottleneck,
+            }),
+            Err(e) => json!({ "attempt": i + 1, "error": e.to_string() }),
+        })
+        .collect();
+
+    let mut last_err = None;
+    let mut best: Option<VelocityTestResult> = None;
+    for attempt in attempts {
+        match attempt {
+            Ok(r) => {
+                if best
+                    .as_ref()
+                    .is_none_or(|b| r.score.unwrap_or(0.0) > b.score.unwrap_or(0.0))
+                {
+                    best = Some(r);
+                }
+            }
+            Err(e) => last_err = Some(e),
+        }
+    }
+    let Some(mut best) = best else {
+        let e = last_err.unwrap_or_else(|| anyhow::anyhow!("no attempts"));
+        return Err(if count > 1 {
+            anyhow::anyhow!("all {} attempts failed; last: {}", count, e)
+        } else {
+            e
+        });
+    };
+    if count > 1 {
+        let mut diag = best
+            .diagnostics_json
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<serde_json::Map<String, Value>>(s).ok())
+            .unwrap_or_default();
+        diag.insert("attempts".to_string(), json!(count));
+        diag.insert("rawAttempts".to_string(), Value::Array(raw));
+        best.diagnostics_json = Some(Value::Object(diag).to_string());
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(score: f64, element_found: bool) -> VelocityTestResult {
+        serde_json::from_value(json!({
+            "id": 0,
+            "run_id": "run",
+            "test_name": "Dashboard",
+            "page_url": "/",
+            "load_time_ms": 1000.0,
+            "console_errors": 0,
+            "element_found": element_found,
+            "score": score,
+            "error": null,
+            "tested_at": "2026-03-01T10:00:00+00:00",
+            "api_response_time_ms": null,
+            "api_status_code": null,
+            "ttfb_ms": null,
+            "dom_interactive_ms": null,
+            "dom_complete_ms": null,
+            "fcp_ms": null,
+            "long_task_count": 0,
+            "long_task_total_ms": 0.0,
+            "resource_count": 0,
+            "total_transfer_size_bytes": 0,
+            "slowest_resource_ms": 0.0,
+            "bottleneck": null,
+            "diagnostics_json": "{\"paint\": []}",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn best_attempt_is_kept_with_all_attempts_recorded() {
+        let attempts = vec![
+            Err(anyhow::anyhow!("connection reset")),
+            Ok(result(20.0, false)),
+            Ok(result(85.0, true)),
+        ];
+        assert!(is_flaky(&attempts[0]) && is_flaky(&attempts[1]) && !is_flaky(&attempts[2]));
+
+        let best = pick_best(attempts).unwrap();
+        assert_eq!(best.score, Some(85.0));
+        let diag: Value = serde_json::from_str(best.diagnostics_json.as_deref().unwrap()).unwrap();
+        assert_eq!(diag["attempts"], 3);
+        assert_eq!(diag["rawAttempts"][0]["error"], "connection reset");
+        assert_eq!(diag["rawAttempts"][1]["score"], 20.0);
+        assert!(diag.get("paint").is_some());
+    }
+
+    #[test]
+    fn single_attempt_is_untouched_and_all_failures_error() {
+        let best = pick_best(vec![Ok(result(70.0, true))]).unwrap();
+        assert_eq!(best.diagnostics_json.as_deref(), Some("{\"paint\": []}"));
+
+        let err = pick_best(vec![
+            Err(anyhow::anyhow!("timeout")),
+            Err(anyhow::anyhow!("reset")),
+        ])
+        .unwrap_err();
+        assert_eq!(err.to_string(), "all 2 attempts failed; last: reset");
+    }
+}
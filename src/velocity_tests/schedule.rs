@@ -0,0 +1,65 @@
+//! Cron schedules for recurring velocity test runs.
+//!
+//! Expressions are the five-field local-time cron of
+//! [`crate::evaluation::schedule::CronExpr`], e.g. `0 9-18 * * 1-5` for
+//! hourly during working hours. Schedules are stored in
+//! `velocity_test_schedules` and fired by the loop in
+//! `routes::velocity_tests`, which enqueues a regular velocity test job
+//! tagged [`RunSource::Scheduled`] — so trend queries can leave out the
+//! manual runs started mid-refactor.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::evaluation::schedule;
+
+/// What started a velocity test run, stored as the run's `source`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunSource {
+    #[default]
+    Manual,
+    Scheduled,
+}
+
+impl RunSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunSource::Manual => "manual",
+            RunSource::Scheduled => "scheduled",
+        }
+    }
+}
+
+/// A persisted velocity test schedule. Run options mirror
+/// `POST /velocity-tests/start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VelocityTestSchedule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub cron: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub concurrency: usize,
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub last_fired_at: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl VelocityTestSchedule {
+    /// See [`crate::evaluation::schedule::EvalSchedule::next_fire`].
+    pub fn next_fire(&self) -> Option<NaiveDateTime> {
+        schedule::next_fire(&self.cron, self.last_fired_at.as_deref(), &self.updated_at)
+    }
+}
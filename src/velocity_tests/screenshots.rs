@@ -0,0 +1,153 @@
+//! Page screenshots attached to velocity test results.
+//!
+//! After each page is measured the worker takes a screenshot of its browser
+//! context (through the UI Bridge, or of its tab with the headless driver)
+//! and saves it as
+//! `{dev_logs}/velocity-screenshots/{run_id}/{index}-{page}.png`. The path is
+//! stored on the result, and `GET /velocity-tests/runs/{id}/screenshots/{file}`
+//! serves it, so a slow or broken page can be looked at after the run. A
+//! failed capture is logged and leaves the result without a screenshot.
+
+use base64::Engine;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use super::engine::WEB_FRONTEND_BASE;
+use super::headless::HeadlessPage;
+
+pub const SCREENSHOT_DIR: &str = "velocity-screenshots";
+
+const CAPTURE_TIMEOUT_SECS: u64 = 15;
+
+/// Directory holding the screenshots of run `run_id`.
+pub fn run_dir(dev_logs_dir: &Path, run_id: &str) -> PathBuf {
+    dev_logs_dir.join(SCREENSHOT_DIR).join(run_id)
+}
+
+/// File name for the `index`th page of a run, e.g. `02-runs-history.png`.
+pub fn file_name(index: usize, test_name: &str) -> String {
+    let slug: String = test_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("{:02}-{}.png", index, slug)
+}
+
+/// Whether `name` is a bare file name that can't escape the run directory.
+pub fn is_safe_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+/// Capture the page the worker is showing — the headless tab `page`, or
+/// the client's UI Bridge context — into `dir/file_name`, returning the
+/// saved path.
+pub async fn capture(
+    http_client: &reqwest::Client,
+    page: Option<&HeadlessPage>,
+    dir: &Path,
+    file_name: &str,
+) -> Option<PathBuf> {
+    let image = match page {
+        Some(page) => page.screenshot().await,
+        None => fetch_screenshot(http_client).await,
+    };
+    let image = match image {
+        Ok(image) => image,
+        Err(e) => {
+            warn!("Screenshot for {} failed: {}", file_name, e);
+            return None;
+        }
+    };
+    let path = dir.join(file_name);
+    let written = async {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&path, image).await
+    }
+    .await;
+    match written {
+        Ok(()) => Some(path),
+        Err(e) => {
+            warn!("Failed to save screenshot {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// PNG bytes of the current page. The bridge answers either with the image
+/// itself or with `{"data": {"image": <base64 or data URL>}}`.
+async fn fetch_screenshot(http_client: &reqwest::Client) -> anyhow::Result<Vec<u8>> {
+    let resp = http_client
+        .get(format!(
+            "{}/api/ui-bridge/control/screenshot",
+            WEB_FRONTEND_BASE
+        ))
+        .timeout(std::time::Duration::from_secs(CAPTURE_TIMEOUT_SECS))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("screenshot endpoint returned {}", resp.status());
+    }
+    let is_image = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("image/"));
+    if is_image {
+        return Ok(resp.bytes().await?.to_vec());
+    }
+    let body: serde_json::Value = resp.json().await?;
+    decode_image(&body).ok_or_else(|| anyhow::anyhow!("response has no image"))
+}
+
+fn decode_image(body: &serde_json::Value) -> Option<Vec<u8>> {
+    let data = body.get("data")?;
+    let encoded = data
+        .get("image")
+        .or_else(|| data.get("dataUrl"))
+        .and_then(|v| v.as_str())?;
+    // Strip a `data:image/png;base64,` prefix
+    let encoded = encoded
+        .split_once(";base64,")
+        .map_or(encoded, |(_, rest)| rest);
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_are_slugged_and_ordered() {
+        assert_eq!(file_name(2, "Runs History"), "02-runs-history.png");
+        assert_eq!(file_name(0, "Build / Tests"), "00-build-tests.png");
+        assert!(is_safe_file_name("02-runs-history.png"));
+        assert!(!is_safe_file_name("../velocity.db"));
+        assert!(!is_safe_file_name("run/02.png"));
+    }
+
+    #[test]
+    fn decodes_base64_and_data_urls() {
+        let plain = serde_json::json!({"success": true, "data": {"image": "iVBORw=="}});
+        assert_eq!(decode_image(&plain).unwrap(), b"\x89PNG");
+        let url = serde_json::json!({"data": {"dataUrl": "data:image/png;base64,iVBORw=="}});
+        assert_eq!(decode_image(&url).unwrap(), b"\x89PNG");
+        assert!(decode_image(&serde_json::json!({"data": {}})).is_none());
+    }
+}
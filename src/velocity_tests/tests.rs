@@ -1,44 +1,238 @@
+//! Velocity test case definitions.
+//!
+//! Test cases live in the `velocity_test_cases` table of `velocity.db` and are
+//! edited through `/velocity-tests/cases`. A new database is seeded with
+//! [`default_test_cases`]; after that the table is the only source, so
+//! deleting every case leaves nothing to run.
+
+use serde::{Deserialize, Serialize};
+
+use super::assertions::Assertion;
+use super::budget::PageBudget;
+use super::retry::MAX_RETRIES;
+
 /// Definition of a single velocity test case.
-#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestCase {
-    pub name: &'static str,
-    pub page_url: &'static str,
+    /// Database id; ignored on create and update.
+    #[serde(default)]
+    pub id: i64,
+    pub name: String,
+    pub page_url: String,
     /// CSS-style element identifier to look for in the UI Bridge elements list.
     /// We search for this substring in element labels/ids/types.
-    pub key_element: &'static str,
-    pub api_endpoint: &'static str,
+    pub key_element: String,
+    pub api_endpoint: String,
+    /// Login performed before measurement for pages behind auth. `None` for
+    /// public pages.
+    #[serde(default)]
+    pub auth: Option<TestAuth>,
+    /// Functional checks run against the UI Bridge snapshot after load.
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Score points deducted per failed assertion; `None` uses
+    /// [`super::assertions::ASSERTION_FAILURE_PENALTY`].
+    #[serde(default)]
+    pub assertion_penalty: Option<f64>,
+    #[serde(default)]
+    pub weights: ScoreWeights,
+    /// Hard limits the page is marked pass/fail against.
+    #[serde(default)]
+    pub budget: Option<PageBudget>,
+    /// Extra attempts when a measurement fails or misses its key element;
+    /// the best attempt is recorded. At most [`super::retry::MAX_RETRIES`].
+    #[serde(default)]
+    pub retries: u32,
 }
 
-/// The 5 test pages we measure.
-pub static TEST_CASES: &[TestCase] = &[
-    TestCase {
-        name: "Dashboard",
-        page_url: "/",
-        key_element: "project",
-        api_endpoint: "/api/v1/projects/",
-    },
-    TestCase {
-        name: "Settings",
-        page_url: "/settings",
-        key_element: "settings",
-        api_endpoint: "/api/v1/auth/users/me",
-    },
-    TestCase {
-        name: "Runs History",
-        page_url: "/runs",
-        key_element: "run",
-        api_endpoint: "/api/v1/task-runs/?limit=10",
-    },
-    TestCase {
-        name: "Runners",
-        page_url: "/runners",
-        key_element: "runner",
-        api_endpoint: "/api/v1/runners/",
+impl TestCase {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if !self.page_url.starts_with('/') {
+            return Err("page_url must start with /".to_string());
+        }
+        if self.key_element.trim().is_empty() {
+            return Err("key_element must not be empty".to_string());
+        }
+        if !self.api_endpoint.starts_with('/') {
+            return Err("api_endpoint must start with /".to_string());
+        }
+        if let Some(penalty) = self.assertion_penalty {
+            if !penalty.is_finite() || penalty < 0.0 {
+                return Err("assertion_penalty must not be negative".to_string());
+            }
+        }
+        if self.retries > MAX_RETRIES {
+            return Err(format!("retries must be at most {}", MAX_RETRIES));
+        }
+        if let Some(budget) = &self.budget {
+            budget.validate()?;
+        }
+        self.weights.validate()
+    }
+}
+
+/// How to establish a session before measuring a protected page.
+///
+/// Credentials are never stored here — they come from the same resolver as
+/// the runner's test auto-login (`POST /test-login`, runner `.env`, or
+/// `QONTINUI_TEST_LOGIN_EMAIL` / `_PASSWORD`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TestAuth {
+    /// POST `{email, password}` to a backend login endpoint and send the
+    /// returned access token as a bearer token on the API probe.
+    ApiLogin { login_endpoint: String },
+    /// Drive the frontend login form through UI Bridge before navigating to
+    /// the page under test. Element fields are UI Bridge element ids on
+    /// `login_url`.
+    UiBridgeLogin {
+        login_url: String,
+        email_element: String,
+        password_element: String,
+        submit_element: String,
     },
+}
+
+/// Relative weight of each score component. The score is scaled so the
+/// weights add up to 100; the defaults are the historical point values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    pub load_time: f64,
+    pub api_response: f64,
+    pub console_errors: f64,
+    pub element_presence: f64,
+    pub long_tasks: f64,
+    pub resources: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            load_time: 40.0,
+            api_response: 15.0,
+            console_errors: 10.0,
+            element_presence: 15.0,
+            long_tasks: 10.0,
+            resources: 10.0,
+        }
+    }
+}
+
+impl ScoreWeights {
+    pub fn total(&self) -> f64 {
+        self.load_time
+            + self.api_response
+            + self.console_errors
+            + self.element_presence
+            + self.long_tasks
+            + self.resources
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.load_time,
+            self.api_response,
+            self.console_errors,
+            self.element_presence,
+            self.long_tasks,
+            self.resources,
+        ];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("weights must not be negative".to_string());
+        }
+        if self.total() <= 0.0 {
+            return Err("at least one weight must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn case(name: &str, page_url: &str, key_element: &str, api_endpoint: &str) -> TestCase {
     TestCase {
-        name: "Build Tests",
-        page_url: "/build/tests",
-        key_element: "test",
-        api_endpoint: "/api/v1/test-suites/",
-    },
-];
+        id: 0,
+        name: name.to_string(),
+        page_url: page_url.to_string(),
+        key_element: key_element.to_string(),
+        api_endpoint: api_endpoint.to_string(),
+        auth: None,
+        assertions: Vec::new(),
+        assertion_penalty: None,
+        weights: ScoreWeights::default(),
+        budget: None,
+        retries: 0,
+    }
+}
+
+/// The 5 pages a new database starts with.
+pub fn default_test_cases() -> Vec<TestCase> {
+    vec![
+        case("Dashboard", "/", "project", "/api/v1/projects/"),
+        TestCase {
+            auth: Some(TestAuth::ApiLogin {
+                login_endpoint: "/api/v1/auth/login".to_string(),
+            }),
+            ..case("Settings", "/settings", "settings", "/api/v1/auth/users/me")
+        },
+        case(
+            "Runs History",
+            "/runs",
+            "run",
+            "/api/v1/task-runs/?limit=10",
+        ),
+        case("Runners", "/runners", "runner", "/api/v1/runners/"),
+        case(
+            "Build Tests",
+            "/build/tests",
+            "test",
+            "/api/v1/test-suites/",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        for tc in default_test_cases() {
+            assert_eq!(tc.validate(), Ok(()), "{}", tc.name);
+        }
+        assert_eq!(ScoreWeights::default().total(), 100.0);
+    }
+
+    #[test]
+    fn rejects_bad_definitions() {
+        let mut tc = case("Projects", "projects", "project", "/api/v1/projects/");
+        assert!(tc.validate().is_err());
+        tc.page_url = "/projects".to_string();
+        tc.weights.load_time = -1.0;
+        assert!(tc.validate().is_err());
+        tc.weights = ScoreWeights {
+            load_time: 0.0,
+            api_response: 0.0,
+            console_errors: 0.0,
+            element_presence: 0.0,
+            long_tasks: 0.0,
+            resources: 0.0,
+        };
+        assert!(tc.validate().is_err());
+    }
+
+    #[test]
+    fn partial_json_fills_defaults() {
+        let tc: TestCase = serde_json::from_str(
+            r#"{"name": "Projects", "page_url": "/projects", "key_element": "project",
+                "api_endpoint": "/api/v1/projects/", "weights": {"load_time": 60},
+                "assertions": [{"ElementPresent": "project-card"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(tc.weights.load_time, 60.0);
+        assert_eq!(tc.weights.api_response, 15.0);
+        assert_eq!(tc.assertions.len(), 1);
+        assert!(tc.auth.is_none());
+    }
+}
@@ -0,0 +1,193 @@
+//! Per-page trend analysis across velocity test runs.
+//!
+//! `GET /velocity-tests/trends` groups the results of the last N completed
+//! runs by page and, for each page's score series, fits a least-squares
+//! slope and looks for a single change point — the split into a before and
+//! after segment (each at least [`MIN_SEGMENT_RUNS`] long) with the lowest
+//! squared error around the segment means. A page is flagged as regressing
+//! when its slope is at most `-`[`REGRESSION_SLOPE`] points per run or its
+//! change point dropped the mean score by at least [`CHANGE_POINT_DROP`].
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Score points lost per run that count as a regressing trend.
+pub const REGRESSION_SLOPE: f64 = 0.5;
+
+/// Drop in mean score across a change point that counts as a regression.
+pub const CHANGE_POINT_DROP: f64 = 5.0;
+
+pub const MIN_SEGMENT_RUNS: usize = 3;
+
+/// One page's metrics in one run.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageTrendPoint {
+    pub run_id: String,
+    pub started_at: String,
+    pub score: Option<f64>,
+    pub load_time_ms: Option<f64>,
+    pub api_response_time_ms: Option<f64>,
+    pub ttfb_ms: Option<f64>,
+    pub fcp_ms: Option<f64>,
+    pub console_errors: i64,
+    pub long_task_count: i64,
+    pub total_transfer_size_bytes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangePoint {
+    /// First run of the after segment.
+    pub run_id: String,
+    pub started_at: String,
+    pub before_mean: f64,
+    pub after_mean: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageTrend {
+    pub page: String,
+    /// Oldest first.
+    pub points: Vec<PageTrendPoint>,
+    /// Score change per run; `None` with fewer than two scored runs.
+    pub score_slope: Option<f64>,
+    pub change_point: Option<ChangePoint>,
+    pub regressing: bool,
+}
+
+/// Trends of every page in `rows` (`(page, point)`, oldest first), by page
+/// name.
+pub fn analyze(rows: Vec<(String, PageTrendPoint)>) -> Vec<PageTrend> {
+    let mut pages: BTreeMap<String, Vec<PageTrendPoint>> = BTreeMap::new();
+    for (page, point) in rows {
+        pages.entry(page).or_default().push(point);
+    }
+    pages
+        .into_iter()
+        .map(|(page, points)| page_trend(page, points))
+        .collect()
+}
+
+fn page_trend(page: String, points: Vec<PageTrendPoint>) -> PageTrend {
+    let scored: Vec<(usize, f64)> = points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| p.score.map(|s| (i, s)))
+        .collect();
+    let score_slope = slope(&scored);
+    let change_point = change_point(&scored).map(|(split, before_mean, after_mean)| {
+        let first_after = &points[scored[split].0];
+        ChangePoint {
+            run_id: first_after.run_id.clone(),
+            started_at: first_after.started_at.clone(),
+            before_mean,
+            after_mean,
+        }
+    });
+    let regressing = score_slope.is_some_and(|s| s <= -REGRESSION_SLOPE)
+        || change_point
+            .as_ref()
+            .is_some_and(|c| c.before_mean - c.after_mean >= CHANGE_POINT_DROP);
+    PageTrend {
+        page,
+        points,
+        score_slope,
+        change_point,
+        regressing,
+    }
+}
+
+/// Least-squares slope of `(x, y)`.
+fn slope(points: &[(usize, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| *x as f64).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut num, mut den) = (0.0, 0.0);
+    for (x, y) in points {
+        let dx = *x as f64 - mean_x;
+        num += dx * (y - mean_y);
+        den += dx * dx;
+    }
+    (den > 0.0).then(|| num / den)
+}
+
+/// Best mean-shift split of the `y` values: `(index of the first after
+/// point, before mean, after mean)`.
+fn change_point(points: &[(usize, f64)]) -> Option<(usize, f64, f64)> {
+    if points.len() < 2 * MIN_SEGMENT_RUNS {
+        return None;
+    }
+    let ys: Vec<f64> = points.iter().map(|(_, y)| *y).collect();
+    let mean = |s: &[f64]| s.iter().sum::<f64>() / s.len() as f64;
+    let sse = |s: &[f64], m: f64| s.iter().map(|y| (y - m).powi(2)).sum::<f64>();
+    (MIN_SEGMENT_RUNS..=ys.len() - MIN_SEGMENT_RUNS)
+        .map(|split| {
+            let (before, after) = ys.split_at(split);
+            let (mb, ma) = (mean(before), mean(after));
+            (split, mb, ma, sse(before, mb) + sse(after, ma))
+        })
+        .min_by(|a, b| a.3.partial_cmp(&b.3).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(split, mb, ma, _)| (split, mb, ma))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(page: &str, scores: &[f64]) -> Vec<(String, PageTrendPoint)> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                (
+                    page.to_string(),
+                    PageTrendPoint {
+                        run_id: format!("run-{}", i),
+                        started_at: format!("2026-03-{:02}T10:00:00+00:00", i + 1),
+                        score: Some(*s),
+                        load_time_ms: None,
+                        api_response_time_ms: None,
+                        ttfb_ms: None,
+                        fcp_ms: None,
+                        console_errors: 0,
+                        long_task_count: 0,
+                        total_transfer_size_bytes: 0,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn step_drop_is_a_change_point() {
+        let trends = analyze(rows("Runs", &[90.0, 91.0, 89.0, 90.0, 78.0, 77.0, 79.0]));
+        let t = &trends[0];
+        let cp = t.change_point.as_ref().unwrap();
+        assert_eq!(cp.run_id, "run-4");
+        assert!((cp.before_mean - 90.0).abs() < 1e-9);
+        assert!((cp.after_mean - 78.0).abs() < 1e-9);
+        assert!(t.regressing);
+    }
+
+    #[test]
+    fn steady_pages_are_not_regressing() {
+        let mut all = rows("Dashboard", &[88.0, 90.0, 89.0, 91.0, 90.0, 89.0]);
+        all.extend(rows("Settings", &[60.0, 70.0]));
+        let trends = analyze(all);
+        assert_eq!(trends.len(), 2);
+        assert!(!trends[0].regressing);
+        // Too short for a change point, but the slope still counts
+        assert_eq!(trends[1].change_point, None);
+        assert_eq!(trends[1].score_slope, Some(10.0));
+        assert!(!trends[1].regressing);
+    }
+
+    #[test]
+    fn gradual_decline_is_regressing() {
+        let trends = analyze(rows("Runners", &[90.0, 89.0, 88.0, 87.0]));
+        assert_eq!(trends[0].score_slope, Some(-1.0));
+        assert!(trends[0].regressing);
+    }
+}
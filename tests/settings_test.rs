@@ -33,6 +33,9 @@ fn test_save_and_load_roundtrip() {
         ai_model: Some("opus".to_string()),
         auto_debug_enabled: Some(true),
         runners: vec![],
+        velocity_slos: vec![],
+        velocity_sampling: Default::default(),
+        restart_policy: Default::default(),
     };
 
     save_settings(&path, &settings);
@@ -290,6 +293,9 @@ fn test_migration_skipped_when_per_instance_file_exists() {
         ai_model: None,
         auto_debug_enabled: None,
         runners: vec![RunnerConfig::default_primary()],
+        velocity_slos: vec![],
+        velocity_sampling: Default::default(),
+        restart_policy: Default::default(),
     };
     save_settings(&path, &pre);
 